@@ -0,0 +1,37 @@
+//! Benchmarks of the detection hot paths.
+//!
+//! Runs on the image at `KOMARI_BENCH_FRAME` if set or a blank frame otherwise. The ONNX Runtime
+//! library is loaded from `ORT_DYLIB_PATH`.
+//!
+//! ```sh
+//! KOMARI_BENCH_FRAME=frame.png cargo bench -p backend --features bench
+//! ```
+
+use std::{env, hint::black_box};
+
+use backend::{
+    IntoEnumIterator,
+    bench::{BenchmarkFrame, BenchmarkStage},
+};
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+
+fn detection(c: &mut Criterion) {
+    let frame = match env::var("KOMARI_BENCH_FRAME") {
+        Ok(path) => BenchmarkFrame::from_file(&path).expect("failed to load benchmark frame"),
+        Err(_) => BenchmarkFrame::blank(1366, 768),
+    };
+    let mut group = c.benchmark_group("detection");
+    for stage in BenchmarkStage::iter() {
+        group.bench_function(stage.to_string(), |bencher| {
+            bencher.iter_batched(
+                || frame.detector(),
+                |detector| black_box(frame.run(&detector, stage)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, detection);
+criterion_main!(benches);
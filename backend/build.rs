@@ -50,6 +50,7 @@ fn main() {
     let hp_shield = dir.join("hp_shield_ideal_ratio.png");
     let hp_end = dir.join("hp_end_ideal_ratio.png");
     let spin_test = dir.join("spin_test_2");
+    let rune_sample = dir.join("spin_test_1/1746076027350.png");
     let familiar_button_save = dir.join("familiar_button_save_ideal_ratio.png");
     let familiar_button_setup = dir.join("familiar_button_setup_ideal_ratio.png");
     let familiar_button_level = dir.join("familiar_button_level_ideal_ratio.png");
@@ -66,6 +67,7 @@ fn main() {
     let familiar_menu = dir.join("familiar_menu_ideal_ratio.png");
     let familiar_essence_deplete = dir.join("familiar_essence_deplete_ideal_ratio.png");
     let change_channel_menu = dir.join("change_channel_menu_ideal_ratio.png");
+    let inventory_full = dir.join("inventory_full_ideal_ratio.png");
 
     let mob_model = dir.join("mob_nms.onnx");
     let rune_model = dir.join("rune_nms.onnx");
@@ -74,6 +76,8 @@ fn main() {
     let onnx_runtime = dir.join("onnxruntime/onnxruntime.dll");
     #[cfg(target_os = "macos")]
     let onnx_runtime = dir.join("onnxruntime-osx-arm64-1.16.3/lib/libonnxruntime.dylib");
+    #[cfg(target_os = "linux")]
+    let onnx_runtime = dir.join("onnxruntime-linux-x64-1.16.3/lib/libonnxruntime.so");
     #[cfg(feature = "gpu")]
     let onnx_runtime_cuda = dir.join("onnxruntime/onnxruntime_providers_cuda.dll");
     #[cfg(feature = "gpu")]
@@ -261,6 +265,10 @@ fn main() {
         "cargo:rustc-env=SPIN_TEST_DIR={}",
         spin_test.to_str().unwrap()
     );
+    println!(
+        "cargo:rustc-env=RUNE_SAMPLE={}",
+        rune_sample.to_str().unwrap()
+    );
     println!(
         "cargo:rustc-env=FAMILIAR_BUTTON_SAVE_TEMPLATE={}",
         familiar_button_save.to_str().unwrap()
@@ -325,6 +333,10 @@ fn main() {
         "cargo:rustc-env=CHANGE_CHANNEL_MENU_TEMPLATE={}",
         change_channel_menu.to_str().unwrap()
     );
+    println!(
+        "cargo:rustc-env=INVENTORY_FULL_TEMPLATE={}",
+        inventory_full.to_str().unwrap()
+    );
 
     // onnxruntime dependencies
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
@@ -0,0 +1,227 @@
+use std::time::{Duration, Instant};
+
+use crate::{database::AntiAfk, rng::Rng};
+
+/// Duration to hold down a direction key when walking.
+const WALK_DURATION: Duration = Duration::from_millis(300);
+
+/// Duration to keep the menu opened before closing it.
+const MENU_DURATION: Duration = Duration::from_millis(1500);
+
+/// Minimum duration between two actions regardless of the jitter.
+const MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A key used by the anti-afk actions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AntiAfkKey {
+    Left,
+    Right,
+    /// The [`AntiAfk::menu_key`].
+    Menu,
+}
+
+/// A key input to send for an anti-afk action.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AntiAfkInput {
+    Down(AntiAfkKey),
+    Up(AntiAfkKey),
+    Send(AntiAfkKey),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Action {
+    Walk,
+    FlipFacing,
+    Menu,
+}
+
+/// Performs a random [`AntiAfk`] action on a jittered interval while active.
+#[derive(Debug, Default)]
+pub struct AntiAfkRunner {
+    /// The player x when anti-afk became active that walking is bounded around.
+    anchor_x: Option<i32>,
+    /// The [`Instant`] to perform the next action at.
+    next_action: Option<Instant>,
+    /// The input to finish the current action with and the [`Instant`] to send it at.
+    finishing: Option<(AntiAfkInput, Instant)>,
+    /// Whether the last facing flip turned to the right.
+    facing_right: bool,
+}
+
+impl AntiAfkRunner {
+    /// Advances the anti-afk and returns the input to send if there is one.
+    ///
+    /// An action in progress is always finished first even when no longer `active` so that no
+    /// key is left held down or menu left opened.
+    pub fn update(
+        &mut self,
+        anti_afk: &AntiAfk,
+        active: bool,
+        player_x: Option<i32>,
+        rng: &Rng,
+        now: Instant,
+    ) -> Option<AntiAfkInput> {
+        if let Some((input, instant)) = self.finishing {
+            if active && now < instant {
+                return None;
+            }
+            self.finishing = None;
+            return Some(input);
+        }
+        if !anti_afk.enabled || !active {
+            self.anchor_x = None;
+            self.next_action = None;
+            return None;
+        }
+
+        if self.anchor_x.is_none() {
+            self.anchor_x = player_x;
+        }
+        let next_action = *self
+            .next_action
+            .get_or_insert_with(|| now + next_interval(anti_afk, rng));
+        if now < next_action {
+            return None;
+        }
+        self.next_action = Some(now + next_interval(anti_afk, rng));
+
+        let anchor_x_and_x = self.anchor_x.zip(player_x);
+        let mut actions = Vec::with_capacity(3);
+        if anti_afk.walk && anchor_x_and_x.is_some() {
+            actions.push(Action::Walk);
+        }
+        if anti_afk.flip_facing {
+            actions.push(Action::FlipFacing);
+        }
+        if anti_afk.menu_key.enabled {
+            actions.push(Action::Menu);
+        }
+
+        match rng.random_choose(actions.into_iter())? {
+            Action::Walk => {
+                let (anchor_x, x) = anchor_x_and_x.expect("has position");
+                let key = walk_key(anchor_x, x, anti_afk.walk_bound, rng);
+                self.finishing = Some((AntiAfkInput::Up(key), now + WALK_DURATION));
+                Some(AntiAfkInput::Down(key))
+            }
+            Action::FlipFacing => {
+                self.facing_right = !self.facing_right;
+                let key = if self.facing_right {
+                    AntiAfkKey::Right
+                } else {
+                    AntiAfkKey::Left
+                };
+                Some(AntiAfkInput::Send(key))
+            }
+            Action::Menu => {
+                let input = AntiAfkInput::Send(AntiAfkKey::Menu);
+                self.finishing = Some((input, now + MENU_DURATION));
+                Some(input)
+            }
+        }
+    }
+}
+
+fn next_interval(anti_afk: &AntiAfk, rng: &Rng) -> Duration {
+    let interval = anti_afk.interval_millis as i64;
+    let jitter = anti_afk
+        .interval_jitter_millis
+        .min(anti_afk.interval_millis) as i64;
+    let millis = interval + rng.random_range(-jitter..=jitter);
+    Duration::from_millis(millis as u64).max(MIN_INTERVAL)
+}
+
+/// Picks the direction to walk in from `x`.
+///
+/// Walks back toward `anchor_x` when the player is already `bound` or further away from it.
+fn walk_key(anchor_x: i32, x: i32, bound: u32, rng: &Rng) -> AntiAfkKey {
+    let offset = x - anchor_x;
+    let bound = bound as i32;
+    if offset >= bound {
+        AntiAfkKey::Left
+    } else if offset <= -bound {
+        AntiAfkKey::Right
+    } else if rng.random_bool(0.5) {
+        AntiAfkKey::Left
+    } else {
+        AntiAfkKey::Right
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::KeyBindingConfiguration;
+
+    #[test]
+    fn update_walks_back_within_bound() {
+        let rng = Rng::new([0; 32]);
+        let anti_afk = AntiAfk {
+            enabled: true,
+            interval_jitter_millis: 0,
+            walk: true,
+            walk_bound: 5,
+            flip_facing: false,
+            ..AntiAfk::default()
+        };
+        let mut runner = AntiAfkRunner::default();
+        let now = Instant::now();
+        let interval = Duration::from_millis(anti_afk.interval_millis);
+
+        assert!(
+            runner
+                .update(&anti_afk, true, Some(10), &rng, now)
+                .is_none()
+        );
+        assert_eq!(
+            runner.update(&anti_afk, true, Some(15), &rng, now + interval),
+            Some(AntiAfkInput::Down(AntiAfkKey::Left))
+        );
+        assert!(
+            runner
+                .update(&anti_afk, true, Some(14), &rng, now + interval)
+                .is_none()
+        );
+        assert_eq!(
+            runner.update(
+                &anti_afk,
+                true,
+                Some(14),
+                &rng,
+                now + interval + WALK_DURATION
+            ),
+            Some(AntiAfkInput::Up(AntiAfkKey::Left))
+        );
+    }
+
+    #[test]
+    fn update_finishes_action_when_inactive() {
+        let rng = Rng::new([0; 32]);
+        let default = AntiAfk::default();
+        let anti_afk = AntiAfk {
+            enabled: true,
+            interval_jitter_millis: 0,
+            walk: false,
+            flip_facing: false,
+            menu_key: KeyBindingConfiguration {
+                enabled: true,
+                ..default.menu_key
+            },
+            ..default
+        };
+        let mut runner = AntiAfkRunner::default();
+        let start = Instant::now();
+        let now = start + Duration::from_millis(anti_afk.interval_millis);
+
+        assert!(runner.update(&anti_afk, true, None, &rng, start).is_none());
+        assert_eq!(
+            runner.update(&anti_afk, true, None, &rng, now),
+            Some(AntiAfkInput::Send(AntiAfkKey::Menu))
+        );
+        assert_eq!(
+            runner.update(&anti_afk, false, None, &rng, now),
+            Some(AntiAfkInput::Send(AntiAfkKey::Menu))
+        );
+        assert!(runner.update(&anti_afk, false, None, &rng, now).is_none());
+    }
+}
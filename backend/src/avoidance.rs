@@ -0,0 +1,157 @@
+use anyhow::Result;
+use log::{error, info};
+use opencv::core::Point;
+
+use crate::{
+    database::{AvoidanceZone, insert_avoidance_zone, query_avoidance_zones},
+    pathing::AVOIDANCE_ZONE_RADIUS,
+    task::{Task, Update, update_task},
+};
+
+/// The number of times the player must get stuck near the same position before it is learned.
+const STUCK_COUNT_THRESHOLD: u32 = 3;
+
+/// Learns positions where the player repeatedly gets stuck as avoidance zones.
+#[derive(Debug, Default)]
+pub struct AvoidanceZones {
+    minimap_id: Option<i64>,
+    /// Learned zones of the current map.
+    zones: Vec<Point>,
+    /// Positions the player got stuck at that are not yet learned along with the number of
+    /// times the player got stuck nearby.
+    stuck: Vec<(Point, u32)>,
+    /// Whether the zones of the current map have been loaded from the database.
+    loaded: bool,
+    task: Option<Task<Result<Vec<AvoidanceZone>>>>,
+}
+
+impl AvoidanceZones {
+    /// Updates with the position `stuck_pos` the player just got stuck at on the map with
+    /// `minimap_id`.
+    ///
+    /// The zones are loaded from the database in the background after the map changes and no
+    /// zone is learned until they are loaded. A newly learned zone is persisted so that it is
+    /// avoided across sessions. Returns `true` if [`Self::zones`] has changed.
+    pub fn update(&mut self, minimap_id: Option<i64>, stuck_pos: Option<Point>) -> bool {
+        let mut changed = false;
+        if self.minimap_id != minimap_id {
+            self.reset(minimap_id);
+            changed = true;
+        }
+        if !self.loaded {
+            return self.update_load() || changed;
+        }
+
+        let (Some(id), Some(pos)) = (self.minimap_id, stuck_pos) else {
+            return changed;
+        };
+        if !self.learn(pos) {
+            return changed;
+        }
+
+        info!(target: "avoidance", "learned avoidance zone at {pos:?}");
+        insert_avoidance_zone(id, pos.x, pos.y);
+        true
+    }
+
+    /// Reloads the zones of the current map from the database on the next update.
+    pub fn reload(&mut self) {
+        self.reset(self.minimap_id);
+    }
+
+    #[inline]
+    pub fn zones(&self) -> &[Point] {
+        &self.zones
+    }
+
+    fn reset(&mut self, minimap_id: Option<i64>) {
+        *self = AvoidanceZones {
+            minimap_id,
+            loaded: minimap_id.is_none(),
+            ..AvoidanceZones::default()
+        };
+    }
+
+    /// Polls the loading of the current map zones and returns `true` if they are loaded.
+    fn update_load(&mut self) -> bool {
+        let Some(id) = self.minimap_id else {
+            return false;
+        };
+        let update = update_task(0, &mut self.task, || id, query_avoidance_zones);
+        let zones = match update {
+            Update::Ok(zones) => zones,
+            Update::Err(err) => {
+                error!(target: "avoidance", "failed to load avoidance zones {err}");
+                Vec::new()
+            }
+            Update::Pending => return false,
+        };
+
+        self.loaded = true;
+        self.task = None;
+        self.zones = zones
+            .into_iter()
+            .map(|zone| Point::new(zone.x, zone.y))
+            .collect();
+        true
+    }
+
+    /// Counts `pos` as stuck and returns `true` if it is learned as a new zone.
+    fn learn(&mut self, pos: Point) -> bool {
+        if self.zones.iter().any(|zone| is_near(*zone, pos)) {
+            return false;
+        }
+
+        let count = match self
+            .stuck
+            .iter_mut()
+            .find(|(point, _)| is_near(*point, pos))
+        {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                self.stuck.push((pos, 1));
+                1
+            }
+        };
+        if count < STUCK_COUNT_THRESHOLD {
+            return false;
+        }
+
+        self.stuck.retain(|(point, _)| !is_near(*point, pos));
+        self.zones.push(pos);
+        true
+    }
+}
+
+#[inline]
+fn is_near(first: Point, second: Point) -> bool {
+    (first.x - second.x).abs() <= AVOIDANCE_ZONE_RADIUS
+        && (first.y - second.y).abs() <= AVOIDANCE_ZONE_RADIUS
+}
+
+#[cfg(test)]
+mod tests {
+    use opencv::core::Point;
+
+    use super::{AvoidanceZones, STUCK_COUNT_THRESHOLD};
+
+    #[test]
+    fn learn_after_stuck_nearby_repeatedly() {
+        let mut zones = AvoidanceZones::default();
+
+        for i in 0..STUCK_COUNT_THRESHOLD - 1 {
+            assert!(!zones.learn(Point::new(50 + i as i32, 20)));
+            assert!(!zones.learn(Point::new(100, 80)));
+        }
+        assert!(zones.learn(Point::new(52, 21)));
+        assert_eq!(zones.zones(), &[Point::new(52, 21)]);
+
+        // Already learned
+        assert!(!zones.learn(Point::new(50, 20)));
+        assert!(zones.learn(Point::new(100, 80)));
+        assert_eq!(zones.zones().len(), 2);
+    }
+}
@@ -0,0 +1,139 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use opencv::{
+    core::{CV_8UC4, Mat, MatTraitConst, Rect, Scalar},
+    imgcodecs::{IMREAD_COLOR, imread},
+    imgproc::{COLOR_BGR2BGRA, cvt_color_def},
+};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::{
+    database::DetectionTuning,
+    detect::{CachedDetector, Detector, to_grayscale},
+    mat::OwnedMat,
+};
+
+/// A detection hot path to benchmark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumIter)]
+pub enum BenchmarkStage {
+    #[strum(to_string = "Color conversion")]
+    ColorConversion,
+    #[strum(to_string = "Minimap detection")]
+    MinimapDetection,
+    #[strum(to_string = "Player detection")]
+    PlayerDetection,
+}
+
+/// The latency of a [`BenchmarkStage`] over a number of iterations.
+#[derive(Clone, Debug)]
+pub struct BenchmarkLatency {
+    pub stage: BenchmarkStage,
+    pub iterations: u32,
+    pub mean: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    /// Whether the stage failed to detect in any iteration.
+    ///
+    /// A failing stage may return early and appear faster than it actually is.
+    pub failed: bool,
+}
+
+/// A detector of a [`BenchmarkFrame`] that does not share any cached detection.
+pub struct BenchmarkDetector(CachedDetector);
+
+/// A captured frame to run the benchmarks on.
+pub struct BenchmarkFrame {
+    mat: Mat,
+    /// The minimap detected once up front for the stages that depend on it.
+    minimap: Option<Rect>,
+}
+
+impl BenchmarkFrame {
+    /// Creates a frame from a BGRA `mat`.
+    pub fn new(mat: Mat) -> Self {
+        let minimap = CachedDetector::new(OwnedMat::from(mat.clone()))
+            .detect_minimap(None, DetectionTuning::default().minimap_border_threshold)
+            .ok();
+        Self { mat, minimap }
+    }
+
+    /// Creates a frame from the image at `path`.
+    pub fn from_file(path: &str) -> Result<Self> {
+        let mat = imread(path, IMREAD_COLOR)?;
+        if mat.empty() {
+            bail!("failed to read image {path}");
+        }
+        let mut bgra = Mat::default();
+        cvt_color_def(&mat, &mut bgra, COLOR_BGR2BGRA)?;
+        Ok(Self::new(bgra))
+    }
+
+    /// Creates a black frame of size `width` x `height`.
+    ///
+    /// Useful when no captured frame is available though most detections will fail.
+    pub fn blank(width: i32, height: i32) -> Self {
+        Self::new(
+            Mat::new_rows_cols_with_default(height, width, CV_8UC4, Scalar::all(0.0)).unwrap(),
+        )
+    }
+
+    /// Creates a fresh detector for a single [`Self::run`].
+    #[inline]
+    pub fn detector(&self) -> BenchmarkDetector {
+        BenchmarkDetector(CachedDetector::new(OwnedMat::from(self.mat.clone())))
+    }
+
+    /// Runs `stage` once using `detector` and returns whether it succeeded.
+    pub fn run(&self, detector: &BenchmarkDetector, stage: BenchmarkStage) -> bool {
+        match stage {
+            BenchmarkStage::ColorConversion => !to_grayscale(&self.mat, true).empty(),
+            BenchmarkStage::MinimapDetection => detector
+                .0
+                .detect_minimap(None, DetectionTuning::default().minimap_border_threshold)
+                .is_ok(),
+            BenchmarkStage::PlayerDetection => self.minimap.is_some_and(|minimap| {
+                detector
+                    .0
+                    .detect_player(minimap, DetectionTuning::default().player_score)
+                    .0
+                    .is_ok()
+            }),
+        }
+    }
+}
+
+/// Runs every [`BenchmarkStage`] on `frame` for `iterations` times and reports their latencies.
+///
+/// Creating the detector is not included in the latencies.
+pub fn run_benchmarks(frame: &BenchmarkFrame, iterations: u32) -> Vec<BenchmarkLatency> {
+    let iterations = iterations.max(1);
+
+    BenchmarkStage::iter()
+        .map(|stage| {
+            let mut total = Duration::ZERO;
+            let mut min = Duration::MAX;
+            let mut max = Duration::ZERO;
+            let mut failed = false;
+            for _ in 0..iterations {
+                let detector = frame.detector();
+                let instant = Instant::now();
+                failed |= !frame.run(&detector, stage);
+                let elapsed = instant.elapsed();
+
+                total += elapsed;
+                min = min.min(elapsed);
+                max = max.max(elapsed);
+            }
+
+            BenchmarkLatency {
+                stage,
+                iterations,
+                mean: total / iterations,
+                min,
+                max,
+                failed,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,266 @@
+use std::time::{Duration, Instant};
+
+use opencv::core::Point;
+
+use crate::{database::Breaks, rng::Rng};
+
+/// Maximum duration to walk to the break position before pausing anyway.
+const WALK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum x and y distance to the break position to be considered arrived.
+const ARRIVED_DISTANCE: i32 = 3;
+
+/// A change to apply to the bot when a [`Breaks`] break progresses.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BreakTransition {
+    /// Starts walking to the break position.
+    Walk,
+    /// Arrived at the break position or timed out and pauses rotating.
+    Pause,
+    /// The break has ended and rotating resumes.
+    Resume,
+}
+
+/// A reason rotating is held halted outside of the scheduled [`Breaks`] breaks.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BreakHold {
+    /// A [`crate::database::SafeMode`] mandatory break is ongoing.
+    SafeMode,
+    /// The game window lost focus while sending keys is paused.
+    FocusLost,
+}
+
+/// Schedules [`Breaks`] breaks while rotating.
+#[derive(Debug, Default)]
+pub struct BreakScheduler {
+    /// The [`Instant`] the next break starts at.
+    next_break: Option<Instant>,
+    /// The [`Instant`] walking to the break position started at.
+    walking_since: Option<Instant>,
+    /// The [`Instant`] the current break ends at.
+    break_end: Option<Instant>,
+    /// The active holds that halted rotating.
+    holds: Vec<BreakHold>,
+}
+
+impl BreakScheduler {
+    /// Whether the player is walking to the break position.
+    #[inline]
+    pub fn is_walking(&self) -> bool {
+        self.walking_since.is_some()
+    }
+
+    /// Whether a break is in progress.
+    #[inline]
+    pub fn is_breaking(&self) -> bool {
+        self.break_end.is_some()
+    }
+
+    /// Cancels the current break and holds if any and restarts the schedule.
+    pub fn reset(&mut self) {
+        self.reset_schedule();
+        self.holds.clear();
+    }
+
+    /// Updates whether `hold` is `active` and returns the transition to apply if there is one.
+    ///
+    /// Rotating is paused when a hold becomes active while not `halting` and resumed once every
+    /// hold that paused it has ended. A hold becoming active while already halting is ignored.
+    pub fn update_hold(
+        &mut self,
+        hold: BreakHold,
+        active: bool,
+        halting: bool,
+    ) -> Option<BreakTransition> {
+        let held = self.holds.contains(&hold);
+        if active && !held {
+            if self.holds.is_empty() && halting {
+                return None;
+            }
+            self.holds.push(hold);
+            return (self.holds.len() == 1).then_some(BreakTransition::Pause);
+        }
+        if !active && held {
+            self.holds.retain(|other| *other != hold);
+            return self.holds.is_empty().then_some(BreakTransition::Resume);
+        }
+        None
+    }
+
+    fn reset_schedule(&mut self) {
+        self.next_break = None;
+        self.walking_since = None;
+        self.break_end = None;
+    }
+
+    /// Advances the break schedule and returns the transition to apply if there is one.
+    ///
+    /// `halting` is whether the bot is halting and `arrived` is whether the player is at the break
+    /// position or there is no break position. Halting outside of a break restarts the schedule.
+    pub fn update(
+        &mut self,
+        breaks: &Breaks,
+        halting: bool,
+        arrived: bool,
+        rng: &Rng,
+        now: Instant,
+    ) -> Option<BreakTransition> {
+        if !breaks.enabled || breaks.interval_millis == 0 {
+            let was_breaking = self.is_breaking();
+            self.reset_schedule();
+            return (was_breaking && self.holds.is_empty()).then_some(BreakTransition::Resume);
+        }
+
+        if let Some(end) = self.break_end {
+            if now < end {
+                return None;
+            }
+            self.reset_schedule();
+            // Stays halted until the holds end
+            return self.holds.is_empty().then_some(BreakTransition::Resume);
+        }
+        if halting {
+            self.reset_schedule();
+            return None;
+        }
+        if let Some(since) = self.walking_since {
+            if !arrived && now.duration_since(since) < WALK_TIMEOUT {
+                return None;
+            }
+            self.walking_since = None;
+            self.break_end = Some(
+                now + rng.random_jittered_duration(
+                    breaks.duration_millis,
+                    breaks.duration_jitter_millis,
+                ),
+            );
+            return Some(BreakTransition::Pause);
+        }
+
+        let next_break = *self.next_break.get_or_insert_with(|| {
+            now + rng
+                .random_jittered_duration(breaks.interval_millis, breaks.interval_jitter_millis)
+        });
+        if now < next_break {
+            return None;
+        }
+        self.next_break = None;
+        self.walking_since = Some(now);
+        Some(BreakTransition::Walk)
+    }
+}
+
+/// Whether `position` is close enough to the break position `target`.
+#[inline]
+pub fn is_at_break_position(position: Point, target: Point) -> bool {
+    (position.x - target.x).abs() <= ARRIVED_DISTANCE
+        && (position.y - target.y).abs() <= ARRIVED_DISTANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaks() -> Breaks {
+        Breaks {
+            enabled: true,
+            interval_millis: 1000,
+            interval_jitter_millis: 0,
+            duration_millis: 500,
+            duration_jitter_millis: 0,
+        }
+    }
+
+    #[test]
+    fn update_walk_pause_then_resume() {
+        let mut scheduler = BreakScheduler::default();
+        let breaks = breaks();
+        let rng = Rng::new(rand::random());
+        let now = Instant::now();
+
+        assert_eq!(scheduler.update(&breaks, false, false, &rng, now), None);
+
+        let now = now + Duration::from_millis(1000);
+        assert_eq!(
+            scheduler.update(&breaks, false, false, &rng, now),
+            Some(BreakTransition::Walk)
+        );
+        assert!(scheduler.is_walking());
+        assert_eq!(scheduler.update(&breaks, false, false, &rng, now), None);
+        assert_eq!(
+            scheduler.update(&breaks, false, true, &rng, now),
+            Some(BreakTransition::Pause)
+        );
+        assert!(scheduler.is_breaking());
+
+        // Halting during a break is caused by the break itself
+        assert_eq!(scheduler.update(&breaks, true, true, &rng, now), None);
+        let now = now + Duration::from_millis(500);
+        assert_eq!(
+            scheduler.update(&breaks, true, true, &rng, now),
+            Some(BreakTransition::Resume)
+        );
+        assert!(!scheduler.is_breaking());
+    }
+
+    #[test]
+    fn update_walk_timeout_pauses() {
+        let mut scheduler = BreakScheduler::default();
+        let breaks = breaks();
+        let rng = Rng::new(rand::random());
+        let now = Instant::now() + Duration::from_millis(1000);
+        scheduler.walking_since = Some(now);
+
+        assert_eq!(
+            scheduler.update(&breaks, false, false, &rng, now + WALK_TIMEOUT),
+            Some(BreakTransition::Pause)
+        );
+    }
+
+    #[test]
+    fn update_halting_or_disabled() {
+        let mut scheduler = BreakScheduler::default();
+        let mut breaks = breaks();
+        let rng = Rng::new(rand::random());
+        let now = Instant::now();
+
+        scheduler.walking_since = Some(now);
+        assert_eq!(scheduler.update(&breaks, true, false, &rng, now), None);
+        assert!(!scheduler.is_walking());
+
+        scheduler.break_end = Some(now + Duration::from_secs(1));
+        breaks.enabled = false;
+        assert_eq!(
+            scheduler.update(&breaks, false, false, &rng, now),
+            Some(BreakTransition::Resume)
+        );
+        assert!(!scheduler.is_breaking());
+    }
+
+    #[test]
+    fn update_hold_pauses_and_resumes() {
+        let mut scheduler = BreakScheduler::default();
+
+        assert_eq!(
+            scheduler.update_hold(BreakHold::SafeMode, true, false),
+            Some(BreakTransition::Pause)
+        );
+        assert_eq!(
+            scheduler.update_hold(BreakHold::FocusLost, true, true),
+            None
+        );
+        assert_eq!(
+            scheduler.update_hold(BreakHold::SafeMode, false, true),
+            None
+        );
+        assert_eq!(
+            scheduler.update_hold(BreakHold::FocusLost, false, true),
+            Some(BreakTransition::Resume)
+        );
+        assert_eq!(scheduler.update_hold(BreakHold::SafeMode, true, true), None);
+        assert_eq!(
+            scheduler.update_hold(BreakHold::SafeMode, false, true),
+            None
+        );
+    }
+}
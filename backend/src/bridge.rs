@@ -1,25 +1,44 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
-use std::{any::Any, cell::RefCell};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    time::Instant,
+};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 #[cfg(test)]
 use mockall::automock;
 #[cfg(windows)]
 use platforms::windows::{
-    self, BitBltCapture, Frame, Handle, KeyInputKind, KeyKind, Keys, WgcCapture, WindowBoxCapture,
+    self, BitBltCapture, Frame, Handle, KeyInputKind, KeyKind, Keys, WgcCapture, WgcPixelFormat,
+    WindowBoxCapture,
 };
 
+#[cfg(target_os = "linux")]
+use platforms::linux::{self, BitBltCapture, Frame, Handle, KeyInputKind, KeyKind, Keys};
 #[cfg(target_os = "macos")]
 use platforms::macos::{
     self, BitBltCapture, Frame, Handle, KeyKind, KeyInputKind, Keys, screenshot::ScreenshotCapture,
 };
 
-use crate::context::MS_PER_TICK_F32;
-use crate::database::Seeds;
+use crate::DesktopPreview;
+use crate::context::ms_per_tick_f32;
+#[cfg(windows)]
+use crate::database::CaptureHdrMode;
+use crate::database::{HumanizationProfile, KeyOverride, KeyboardLayout, SafeMode, Seeds};
+use crate::frame_pool::FramePool;
+use crate::humanization::Humanization;
+use crate::key_mapping::KeyMapping;
+use crate::mat::OwnedMat;
+use crate::rate_limiter::{RateLimitBreak, RateLimiter};
 use crate::rng::Rng;
 use crate::rpc;
-use crate::{CaptureMode, context::MS_PER_TICK, rpc::KeysService, database::Settings};
+use crate::serial::{SerialMouseAction, SerialService};
+use crate::{CaptureMode, context::ms_per_tick, database::Settings, rpc::KeysService};
+
+/// The maximum width of a [`DesktopPreview`].
+const DESKTOP_PREVIEW_MAX_WIDTH: usize = 640;
 
 /// Base mean in milliseconds to generate a pair from.
 const BASE_MEAN_MS_DELAY: f32 = 100.0;
@@ -36,9 +55,10 @@ const MEAN_STD_VOLATILITY: f32 = 3.0;
 
 /// The input method to use for the key sender.
 ///
-/// This is a bridge enum between platform-specific and gRPC input options.
+/// This is a bridge enum between platform-specific, gRPC and serial input options.
 pub enum KeySenderMethod {
     Rpc(Handle, String),
+    Serial(Handle, String),
     Default(Handle, KeyInputKind),
 }
 
@@ -49,10 +69,20 @@ pub enum KeySenderMethod {
 #[derive(Debug)]
 enum KeySenderKind {
     Rpc(Handle, Option<RefCell<KeysService>>),
+    Serial(Handle, Option<RefCell<SerialService>>),
     Default(Keys),
 }
 
-#[derive(Debug)]
+/// The press duration and repeat interval of a key.
+///
+/// Keys without a [`KeyTiming`] use the random input delay as press duration.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct KeyTiming {
+    pub press_millis: Option<u64>,
+    pub repeat_millis: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug)]
 pub enum MouseAction {
     Move,
     Click,
@@ -64,6 +94,14 @@ pub enum MouseAction {
 pub trait KeySender: Debug {
     fn set_method(&mut self, method: KeySenderMethod);
 
+    /// Sets the [`KeyTiming`] of each key replacing the previous ones.
+    fn set_key_timings(&mut self, timings: HashMap<KeyKind, KeyTiming>);
+
+    /// Sets whether sending key presses and mouse inputs is paused.
+    ///
+    /// While paused, sending returns an error but keys can still be released.
+    fn set_paused(&mut self, paused: bool);
+
     fn send(&self, kind: KeyKind) -> Result<()>;
 
     /// Sends mouse to `(x, y)` relative to the client coordinate (e.g. capture area) and
@@ -80,15 +118,60 @@ pub trait KeySender: Debug {
 
     fn all_keys_cleared(&self) -> bool;
 
+    /// Releases all keys that are currently held down.
+    fn release_all(&self) -> Result<()>;
+
+    /// The [`SafeMode`] break state or [`None`] if there is no scheduled break.
+    fn rate_limit_break(&self) -> Option<RateLimitBreak>;
+
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
+/// A registry of every key currently held down by a [`DefaultKeySender`].
+///
+/// Keys are registered when sent down either explicitly or for an input delay and unregistered
+/// once actually sent up, so that all of them can be force-released.
+#[derive(Debug, Default)]
+struct HeldKeys(RefCell<HashSet<KeyKind>>);
+
+impl HeldKeys {
+    #[inline]
+    fn insert(&self, kind: KeyKind) {
+        self.0.borrow_mut().insert(kind);
+    }
+
+    #[inline]
+    fn remove(&self, kind: KeyKind) {
+        self.0.borrow_mut().remove(&kind);
+    }
+
+    #[inline]
+    fn contains(&self, kind: KeyKind) -> bool {
+        self.0.borrow().contains(&kind)
+    }
+
+    #[inline]
+    fn take(&self) -> HashSet<KeyKind> {
+        self.0.take()
+    }
+}
+
 #[derive(Debug)]
 pub struct DefaultKeySender {
     kind: KeySenderKind,
     delay_rng: Rng,
     delay_mean_std_pair: (f32, f32),
     delay_map: RefCell<HashMap<KeyKind, u32>>,
+    held_keys: HeldKeys,
+    humanization: Humanization,
+    /// Translates keys sent through the platform to the user keyboard layout.
+    key_mapping: KeyMapping,
+    key_timings: HashMap<KeyKind, KeyTiming>,
+    /// The last client coordinate the mouse was sent to.
+    mouse_position: Cell<Option<(i32, i32)>>,
+    /// Caps key presses and mouse clicks according to [`SafeMode`].
+    rate_limiter: RefCell<RateLimiter>,
+    paused: bool,
 }
 
 #[derive(Debug)]
@@ -105,6 +188,46 @@ impl DefaultKeySender {
             delay_rng: Rng::new(seeds.seed),
             delay_mean_std_pair: (BASE_MEAN_MS_DELAY, BASE_STD_MS_DELAY),
             delay_map: RefCell::new(HashMap::new()),
+            held_keys: HeldKeys::default(),
+            humanization: Humanization::default(),
+            key_mapping: KeyMapping::default(),
+            key_timings: HashMap::new(),
+            mouse_position: Cell::new(None),
+            rate_limiter: RefCell::new(RateLimiter::default()),
+            paused: false,
+        }
+    }
+
+    /// Sets the [`HumanizationProfile`] applied to subsequent key and mouse inputs.
+    #[inline]
+    pub fn set_humanization(&mut self, profile: HumanizationProfile) {
+        self.humanization.set_profile(profile);
+    }
+
+    /// Sets the [`KeyboardLayout`] and [`KeyOverride`]s applied to subsequent key inputs.
+    #[inline]
+    pub fn set_key_mapping(&mut self, layout: KeyboardLayout, overrides: &[KeyOverride]) {
+        self.key_mapping.set(layout, overrides);
+    }
+
+    /// Sets the [`SafeMode`] caps and starts or ends its mandatory breaks.
+    ///
+    /// All held keys are released when a break starts.
+    pub fn update_rate_limiter(&mut self, safe_mode: &SafeMode, now: Instant) {
+        let rate_limiter = self.rate_limiter.get_mut();
+        rate_limiter.set(safe_mode);
+        if rate_limiter.update(&self.delay_rng, now) {
+            log::info!(target: "bridge", "safe mode break started");
+            let _ = self.release_all();
+        }
+    }
+
+    /// Polls the background RPC heartbeat that resumes the session if the connection dropped.
+    pub fn update_rpc_heartbeat(&mut self) {
+        if let KeySenderKind::Rpc(_, Some(service)) = &self.kind
+            && let Err(err) = service.borrow_mut().heartbeat()
+        {
+            log::warn!(target: "bridge", "failed to resume RPC session {err}");
         }
     }
 
@@ -112,16 +235,28 @@ impl DefaultKeySender {
     fn send_inner(&self, kind: KeyKind) -> Result<()> {
         match &self.kind {
             KeySenderKind::Rpc(_, service) => {
+                if let Some(cell) = service {
+                    cell.borrow_mut().send(
+                        self.key_mapping.map(kind),
+                        self.input_delay_tick_count(kind).0,
+                    )?;
+                }
+                Ok(())
+            }
+            KeySenderKind::Serial(_, service) => {
                 if let Some(cell) = service {
                     cell.borrow_mut()
-                        .send(kind, self.random_input_delay_tick_count().0)?;
+                        .send(kind, self.input_delay_tick_count(kind).0)?;
                 }
                 Ok(())
             }
             KeySenderKind::Default(keys) => {
                 match self.track_input_delay(kind) {
-                    InputDelay::Untracked => keys.send(kind)?,
-                    InputDelay::Tracked => keys.send_down(kind)?,
+                    InputDelay::Untracked => keys.send(self.key_mapping.map(kind))?,
+                    InputDelay::Tracked => {
+                        self.held_keys.insert(kind);
+                        keys.send_down(self.key_mapping.map(kind))?;
+                    }
                     InputDelay::AlreadyTracked => (),
                 }
                 Ok(())
@@ -133,6 +268,12 @@ impl DefaultKeySender {
     fn send_up_inner(&self, kind: KeyKind, forced: bool) -> Result<()> {
         match &self.kind {
             KeySenderKind::Rpc(_, service) => {
+                if let Some(cell) = service {
+                    cell.borrow_mut().send_up(self.key_mapping.map(kind))?;
+                }
+                Ok(())
+            }
+            KeySenderKind::Serial(_, service) => {
                 if let Some(cell) = service {
                     cell.borrow_mut().send_up(kind)?;
                 }
@@ -140,7 +281,7 @@ impl DefaultKeySender {
             }
             KeySenderKind::Default(keys) => {
                 if forced || !self.has_input_delay(kind) {
-                    keys.send_up(kind)?;
+                    keys.send_up(self.key_mapping.map(kind))?;
                 }
                 Ok(())
             }
@@ -151,6 +292,12 @@ impl DefaultKeySender {
     fn send_down_inner(&self, kind: KeyKind) -> Result<()> {
         match &self.kind {
             KeySenderKind::Rpc(_, service) => {
+                if let Some(cell) = service {
+                    cell.borrow_mut().send_down(self.key_mapping.map(kind))?;
+                }
+                Ok(())
+            }
+            KeySenderKind::Serial(_, service) => {
                 if let Some(cell) = service {
                     cell.borrow_mut().send_down(kind)?;
                 }
@@ -158,7 +305,7 @@ impl DefaultKeySender {
             }
             KeySenderKind::Default(keys) => {
                 if !self.has_input_delay(kind) {
-                    keys.send_down(kind)?;
+                    keys.send_down(self.key_mapping.map(kind))?;
                 }
                 Ok(())
             }
@@ -185,7 +332,7 @@ impl DefaultKeySender {
             return InputDelay::AlreadyTracked;
         }
 
-        let (_, delay_tick_count) = self.random_input_delay_tick_count();
+        let (_, delay_tick_count) = self.input_delay_tick_count(kind);
         if delay_tick_count > 0 {
             let _ = map.insert(kind, delay_tick_count);
             InputDelay::Tracked
@@ -218,46 +365,49 @@ impl DefaultKeySender {
         map.retain(|kind, delay| {
             *delay = delay.saturating_sub(1);
             if *delay == 0 {
+                self.held_keys.remove(*kind);
                 let _ = self.send_up_inner(*kind, true);
             }
             *delay != 0
         });
     }
 
-    fn random_input_delay_tick_count(&self) -> (f32, u32) {
-        let (mean, std) = self.delay_mean_std_pair;
-        self.delay_rng
-            .random_delay_tick_count(mean, std, MS_PER_TICK_F32, 80.0, 120.0)
-    }
-}
+    /// Retrieves the `(delay, tick count)` pair of how long `kind` should be held down.
+    ///
+    /// The [`KeyTiming`] of `kind` takes precedence over the random input delay.
+    fn input_delay_tick_count(&self, kind: KeyKind) -> (f32, u32) {
+        let Some(timing) = self.key_timings.get(&kind) else {
+            return self.random_input_delay_tick_count();
+        };
 
-impl KeySender for DefaultKeySender {
-    fn set_method(&mut self, method: KeySenderMethod) {
-        match &method {
-            KeySenderMethod::Rpc(handle, url) => {
-                if let KeySenderKind::Rpc(ref cur_handle, ref option) = self.kind {
-                    let service = option.as_ref();
-                    let service_borrow = service.map(|service| service.borrow_mut());
-                    if let Some(mut borrow) = service_borrow
-                        && borrow.url() == url
-                        && handle == cur_handle
-                    {
-                        let _ = borrow.init(self.delay_rng.seed());
-                        borrow.reset();
-                        return;
-                    }
-                }
-            }
-            KeySenderMethod::Default(_, _) => (),
+        let mut ms = match timing.press_millis {
+            Some(millis) => millis as f32,
+            None => self.random_input_delay_tick_count().0,
+        };
+        if let Some(millis) = timing.repeat_millis {
+            ms = ms.min(millis as f32);
         }
-        self.kind = to_key_sender_kind_from(method, self.delay_rng.seed());
+        (ms, (ms / ms_per_tick_f32()).round() as u32)
     }
 
-    fn send(&self, kind: KeyKind) -> Result<()> {
-        self.send_inner(kind)
+    fn random_input_delay_tick_count(&self) -> (f32, u32) {
+        const MIN_MS_DELAY: f32 = 80.0;
+
+        let (mean, std) = self.delay_mean_std_pair;
+        let (ms, _) = self.delay_rng.random_delay_tick_count(
+            mean,
+            std,
+            ms_per_tick_f32(),
+            MIN_MS_DELAY,
+            120.0,
+        );
+        let ms = self
+            .humanization
+            .key_press_ms(&self.delay_rng, ms, MIN_MS_DELAY);
+        (ms, (ms / ms_per_tick_f32()).round() as u32)
     }
 
-    fn send_mouse(&self, x: i32, y: i32, action: MouseAction) -> Result<()> {
+    fn send_mouse_inner(&self, x: i32, y: i32, action: MouseAction) -> Result<()> {
         match &self.kind {
             KeySenderKind::Rpc(handle, service) => {
                 if let Some(cell) = service {
@@ -267,6 +417,8 @@ impl KeySender for DefaultKeySender {
                         { windows::client_to_monitor_or_frame(*handle, x, y, matches!(borrow.mouse_coordinate(), rpc::Coordinate::Screen))? }
                         #[cfg(target_os = "macos")]
                         { macos::client_to_monitor_or_frame(*handle, x, y, matches!(borrow.mouse_coordinate(), rpc::Coordinate::Screen))? }
+                        #[cfg(target_os = "linux")]
+                        { linux::client_to_monitor_or_frame(*handle, x, y, matches!(borrow.mouse_coordinate(), rpc::Coordinate::Screen))? }
                     };
                     let action = match action {
                         MouseAction::Move => rpc::MouseAction::Move,
@@ -284,6 +436,38 @@ impl KeySender for DefaultKeySender {
                 }
                 Ok(())
             }
+            KeySenderKind::Serial(handle, service) => {
+                if let Some(cell) = service {
+                    let coordinates = {
+                        #[cfg(windows)]
+                        {
+                            windows::client_to_monitor_or_frame(*handle, x, y, true)?
+                        }
+                        #[cfg(target_os = "macos")]
+                        {
+                            macos::client_to_monitor_or_frame(*handle, x, y, true)?
+                        }
+                        #[cfg(target_os = "linux")]
+                        {
+                            linux::client_to_monitor_or_frame(*handle, x, y, true)?
+                        }
+                    };
+                    let action = match action {
+                        MouseAction::Move => SerialMouseAction::Move,
+                        MouseAction::Click => SerialMouseAction::Click,
+                        MouseAction::Scroll => SerialMouseAction::ScrollDown,
+                    };
+
+                    cell.borrow_mut().send_mouse(
+                        coordinates.width,
+                        coordinates.height,
+                        coordinates.x,
+                        coordinates.y,
+                        action,
+                    )?;
+                }
+                Ok(())
+            }
             KeySenderKind::Default(keys) => {
                 let action = {
                     #[cfg(windows)]
@@ -302,18 +486,111 @@ impl KeySender for DefaultKeySender {
                             MouseAction::Scroll => macos::MouseAction::Scroll,
                         }
                     }
+                    #[cfg(target_os = "linux")]
+                    {
+                        match action {
+                            MouseAction::Move => linux::MouseAction::Move,
+                            MouseAction::Click => linux::MouseAction::Click,
+                            MouseAction::Scroll => linux::MouseAction::Scroll,
+                        }
+                    }
                 };
                 keys.send_mouse(x, y, action)?;
                 Ok(())
             }
         }
     }
+}
+
+impl KeySender for DefaultKeySender {
+    fn set_method(&mut self, method: KeySenderMethod) {
+        match &method {
+            KeySenderMethod::Rpc(handle, url) => {
+                if let KeySenderKind::Rpc(ref cur_handle, ref option) = self.kind {
+                    let service = option.as_ref();
+                    let service_borrow = service.map(|service| service.borrow_mut());
+                    if let Some(mut borrow) = service_borrow
+                        && borrow.url() == url
+                        && handle == cur_handle
+                    {
+                        let _ = borrow.init(self.delay_rng.seed());
+                        borrow.reset();
+                        return;
+                    }
+                }
+            }
+            KeySenderMethod::Serial(handle, path) => {
+                if let KeySenderKind::Serial(ref cur_handle, Some(ref cell)) = self.kind {
+                    let mut borrow = cell.borrow_mut();
+                    if borrow.path().as_str() == path.trim() && handle == cur_handle {
+                        borrow.reset();
+                        return;
+                    }
+                }
+            }
+            KeySenderMethod::Default(_, _) => (),
+        }
+        // Keys held by the previous method can no longer be released after replacing it
+        let _ = self.release_all();
+        self.kind = to_key_sender_kind_from(method, self.delay_rng.seed());
+    }
+
+    fn set_key_timings(&mut self, timings: HashMap<KeyKind, KeyTiming>) {
+        self.key_timings = timings;
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    fn send(&self, kind: KeyKind) -> Result<()> {
+        if self.paused {
+            bail!("sending keys is paused");
+        }
+        if !self.rate_limiter.borrow_mut().try_key(Instant::now()) {
+            bail!("sending keys is rate limited");
+        }
+        self.send_inner(kind)
+    }
+
+    fn send_mouse(&self, x: i32, y: i32, action: MouseAction) -> Result<()> {
+        if self.paused {
+            bail!("sending mouse is paused");
+        }
+        if !matches!(action, MouseAction::Move)
+            && !self.rate_limiter.borrow_mut().try_mouse(Instant::now())
+        {
+            bail!("sending mouse is rate limited");
+        }
+        if let Some(from) = self.mouse_position.replace(Some((x, y))) {
+            let mut path = self.humanization.mouse_path(&self.delay_rng, from, (x, y));
+            // The destination is sent last with the actual action
+            path.pop();
+            for (x, y) in path {
+                self.send_mouse_inner(x, y, MouseAction::Move)?;
+            }
+        }
+        self.send_mouse_inner(x, y, action)
+    }
 
     fn send_up(&self, kind: KeyKind) -> Result<()> {
+        // Still held until the input delay times out
+        if !self.has_input_delay(kind) {
+            self.held_keys.remove(kind);
+        }
         self.send_up_inner(kind, false)
     }
 
     fn send_down(&self, kind: KeyKind) -> Result<()> {
+        if self.paused {
+            bail!("sending keys is paused");
+        }
+        // Re-sending an already held key (e.g. while moving) is not a new key press
+        if !self.held_keys.contains(kind) && !self.rate_limiter.borrow_mut().try_key(Instant::now())
+        {
+            bail!("sending keys is rate limited");
+        }
+        self.held_keys.insert(kind);
         self.send_down_inner(kind)
     }
 
@@ -322,12 +599,37 @@ impl KeySender for DefaultKeySender {
         self.delay_map.borrow().is_empty()
     }
 
+    fn release_all(&self) -> Result<()> {
+        self.delay_map.take();
+
+        let mut result = Ok(());
+        for kind in self.held_keys.take() {
+            // Continues releasing the remaining keys so a single failure does not leave them stuck
+            if let Err(err) = self.send_up_inner(kind, true)
+                && result.is_ok()
+            {
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    fn rate_limit_break(&self) -> Option<RateLimitBreak> {
+        self.rate_limiter.borrow().break_state(Instant::now())
+    }
+
     #[inline]
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
 }
 
+impl Drop for DefaultKeySender {
+    fn drop(&mut self) {
+        let _ = self.release_all();
+    }
+}
+
 /// A bridge enum between platform-specific and database capture options.
 #[derive(Debug)]
 pub enum ImageCaptureKind {
@@ -340,6 +642,8 @@ pub enum ImageCaptureKind {
     BitBltArea(ScreenshotCapture),
     #[cfg(target_os = "macos")]
     Screenshot(ScreenshotCapture),
+    #[cfg(target_os = "linux")]
+    BitBltArea(BitBltCapture),
 }
 
 /// A struct for managing different capture modes.
@@ -359,25 +663,65 @@ impl ImageCapture {
         &self.kind
     }
 
+    #[inline]
     pub fn grab(&mut self) -> Option<Frame> {
+        self.grab_into(&mut Vec::new())
+    }
+
+    /// Grabs a frame into a buffer from `pool` and wraps it as [`OwnedMat`].
+    ///
+    /// The buffer is returned to `pool` once the [`OwnedMat`] and all of its clones are dropped
+    /// or right away if no frame is grabbed.
+    pub fn grab_pooled(&mut self, pool: &FramePool) -> Option<OwnedMat> {
+        let mut buffer = pool.acquire();
+        let frame = self.grab_into(buffer.data_mut())?;
+        *buffer.data_mut() = frame.data;
+        Some(OwnedMat::new_pooled(frame.width, frame.height, buffer))
+    }
+
+    fn grab_into(&mut self, data: &mut Vec<u8>) -> Option<Frame> {
         match &mut self.kind {
-            ImageCaptureKind::BitBlt(capture) => capture.grab().ok(),
+            ImageCaptureKind::BitBlt(capture) => capture.grab_into(data).ok(),
             #[cfg(windows)]
-            ImageCaptureKind::Wgc(capture) => {
-                capture.as_mut().and_then(|capture| capture.grab().ok())
-            }
+            ImageCaptureKind::Wgc(capture) => capture
+                .as_mut()
+                .and_then(|capture| capture.grab_into(data).ok()),
             #[cfg(windows)]
-            ImageCaptureKind::BitBltArea(capture) => capture.grab().ok(),
+            ImageCaptureKind::BitBltArea(capture) => capture.grab_into(data).ok(),
             #[cfg(target_os = "macos")]
-            ImageCaptureKind::BitBltArea(capture) => capture.grab().ok(),
+            ImageCaptureKind::BitBltArea(capture) => capture.grab_into(data).ok(),
             #[cfg(target_os = "macos")]
-            ImageCaptureKind::Screenshot(capture) => capture.grab().ok(),
+            ImageCaptureKind::Screenshot(capture) => capture.grab_into(data).ok(),
+            #[cfg(target_os = "linux")]
+            ImageCaptureKind::BitBltArea(capture) => capture.grab_into(data).ok(),
         }
     }
 
     pub fn set_mode(&mut self, handle: Handle, mode: CaptureMode, settings: &Settings) {
         self.kind = to_image_capture_kind_from(handle, mode, settings);
     }
+
+    /// The offset to add to a frame coordinate for the client coordinate of the foreground window.
+    ///
+    /// Only [`ImageCaptureKind::BitBltArea`] captures an area of the screen that can differ from
+    /// the foreground window client area. On macOS, the area is already the client area of the key
+    /// sender handle.
+    pub fn foreground_client_offset(&self) -> (i32, i32) {
+        let origin = match &self.kind {
+            #[cfg(windows)]
+            ImageCaptureKind::BitBltArea(capture) => capture
+                .position()
+                .zip(windows::foreground_client_origin().ok()),
+            #[cfg(target_os = "linux")]
+            ImageCaptureKind::BitBltArea(capture) => capture
+                .area_origin()
+                .zip(linux::foreground_client_origin().ok()),
+            _ => None,
+        };
+        origin
+            .map(|((x, y), (client_x, client_y))| (x - client_x, y - client_y))
+            .unwrap_or_default()
+    }
 }
 
 #[inline]
@@ -390,6 +734,13 @@ fn to_key_sender_kind_from(method: KeySenderMethod, seed: &[u8]) -> KeySenderKin
             }
             KeySenderKind::Rpc(handle, service.ok().map(RefCell::new))
         }
+        KeySenderMethod::Serial(handle, path) => {
+            let service = SerialService::connect(path);
+            if let Err(ref err) = service {
+                log::error!("Failed to open serial port: {err}");
+            }
+            KeySenderKind::Serial(handle, service.ok().map(RefCell::new))
+        }
         KeySenderMethod::Default(handle, kind) => KeySenderKind::Default(Keys::new(handle, kind)),
     }
 }
@@ -424,9 +775,16 @@ fn to_image_capture_kind_from(handle: Handle, mode: CaptureMode, settings: &Sett
                 }
             }
         }
+        #[cfg(target_os = "linux")]
+        CaptureMode::BitBlt => ImageCaptureKind::BitBlt(BitBltCapture::new(handle)),
         #[cfg(windows)]
         CaptureMode::WindowsGraphicsCapture => {
-            ImageCaptureKind::Wgc(WgcCapture::new(handle, MS_PER_TICK).ok())
+            let pixel_format = match settings.capture_hdr_mode {
+                CaptureHdrMode::Auto => WgcPixelFormat::Auto,
+                CaptureHdrMode::Off => WgcPixelFormat::Bgra8,
+                CaptureHdrMode::On => WgcPixelFormat::Rgba16Float,
+            };
+            ImageCaptureKind::Wgc(WgcCapture::new(handle, ms_per_tick(), pixel_format).ok())
         }
         #[cfg(target_os = "macos")]
         CaptureMode::WindowsGraphicsCapture => {
@@ -447,8 +805,18 @@ fn to_image_capture_kind_from(handle: Handle, mode: CaptureMode, settings: &Sett
                 }
             }
         }
+        // There is no Windows Graphics Capture equivalent so the window capture is used instead
+        #[cfg(target_os = "linux")]
+        CaptureMode::WindowsGraphicsCapture => ImageCaptureKind::BitBlt(BitBltCapture::new(handle)),
         #[cfg(windows)]
         CaptureMode::BitBltArea => ImageCaptureKind::BitBltArea(WindowBoxCapture::default()),
+        #[cfg(target_os = "linux")]
+        CaptureMode::BitBltArea => ImageCaptureKind::BitBltArea(BitBltCapture::new_area(
+            settings.capture_x,
+            settings.capture_y,
+            settings.capture_width,
+            settings.capture_height,
+        )),
         #[cfg(target_os = "macos")]
         CaptureMode::BitBltArea => {
             // Use coordinates from settings for BitBltArea mode
@@ -456,8 +824,8 @@ fn to_image_capture_kind_from(handle: Handle, mode: CaptureMode, settings: &Sett
             let display_index = platforms::macos::find_display_for_coordinates(
                 settings.capture_x,
                 settings.capture_y,
-                1366, // Fixed MapleStory window width
-                768   // Fixed MapleStory window height
+                settings.capture_width,
+                settings.capture_height,
             ).unwrap_or(0); // Fallback to primary display if detection fails
             
             log::info!("BitBltArea mode: Using coordinates ({}, {}) on display index {}", 
@@ -467,8 +835,8 @@ fn to_image_capture_kind_from(handle: Handle, mode: CaptureMode, settings: &Sett
                 display_index,
                 settings.capture_x,
                 settings.capture_y, 
-                1366, // Fixed MapleStory window width
-                768   // Fixed MapleStory window height
+                settings.capture_width,
+                settings.capture_height,
             );
             
             match ScreenshotCapture::new(configured_handle) {
@@ -508,11 +876,55 @@ fn to_image_capture_kind_from(handle: Handle, mode: CaptureMode, settings: &Sett
     }
 }
 
+/// Captures the desktop and downscales it into a [`DesktopPreview`].
+pub fn capture_desktop_preview() -> Option<DesktopPreview> {
+    let frame = {
+        #[cfg(windows)]
+        {
+            windows::capture_desktop()
+        }
+        #[cfg(target_os = "macos")]
+        {
+            macos::capture_desktop()
+        }
+        #[cfg(target_os = "linux")]
+        {
+            linux::capture_desktop()
+        }
+    };
+    frame.ok().map(to_desktop_preview)
+}
+
+/// Downscales BGRA `frame` by sampling every `scale` pixels into a RGBA [`DesktopPreview`].
+fn to_desktop_preview(frame: Frame) -> DesktopPreview {
+    let frame_width = frame.width as usize;
+    let frame_height = frame.height as usize;
+    let scale = frame_width.div_ceil(DESKTOP_PREVIEW_MAX_WIDTH).max(1);
+    let width = frame_width / scale;
+    let height = frame_height / scale;
+    let mut data = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let index = (y * scale * frame_width + x * scale) * 4;
+            let bgra = &frame.data[index..index + 4];
+            data.extend_from_slice(&[bgra[2], bgra[1], bgra[0], 255]);
+        }
+    }
+
+    DesktopPreview {
+        frame: data,
+        width,
+        height,
+        scale,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
 
     use super::*;
+    use crate::database::Bound;
 
     const SEED: [u8; 32] = [
         64, 241, 206, 219, 49, 21, 218, 145, 254, 152, 68, 176, 242, 238, 152, 14, 176, 241, 153,
@@ -530,6 +942,65 @@ mod tests {
         )
     }
 
+    #[test]
+    fn send_down_rejected_if_paused() {
+        let mut sender = test_key_sender();
+        sender.set_paused(true);
+
+        assert!(sender.send_down(KeyKind::Ctrl).is_err());
+        assert!(!sender.held_keys.contains(KeyKind::Ctrl));
+
+        sender.set_paused(false);
+        let _ = sender.send_down(KeyKind::Ctrl);
+        assert!(sender.held_keys.contains(KeyKind::Ctrl));
+    }
+
+    #[test]
+    fn send_down_rate_limited_only_if_not_held() {
+        let mut sender = test_key_sender();
+        sender.update_rate_limiter(
+            &SafeMode {
+                enabled: true,
+                max_keys_per_second: 2,
+                max_inputs_per_minute: 0,
+                break_interval_millis: 0,
+                ..SafeMode::default()
+            },
+            Instant::now(),
+        );
+
+        let _ = sender.send_down(KeyKind::Ctrl);
+        let _ = sender.send_down(KeyKind::Ctrl);
+        let _ = sender.send_down(KeyKind::Alt);
+        assert!(sender.held_keys.contains(KeyKind::Alt));
+
+        let _ = sender.send_down(KeyKind::Shift);
+        assert!(!sender.held_keys.contains(KeyKind::Shift));
+    }
+
+    #[test]
+    fn send_up_keeps_delayed_key_held() {
+        let sender = test_key_sender();
+        sender.held_keys.insert(KeyKind::Ctrl);
+        sender.delay_map.borrow_mut().insert(KeyKind::Ctrl, 3);
+
+        let _ = sender.send_up(KeyKind::Ctrl);
+        assert!(sender.held_keys.contains(KeyKind::Ctrl));
+    }
+
+    #[test]
+    fn release_all_clears_held_and_delayed_keys() {
+        let sender = test_key_sender();
+        sender.held_keys.insert(KeyKind::Ctrl);
+        sender.held_keys.insert(KeyKind::Left);
+        sender.delay_map.borrow_mut().insert(KeyKind::Left, 3);
+
+        let _ = sender.release_all();
+        assert!(!sender.held_keys.contains(KeyKind::Ctrl));
+        assert!(!sender.held_keys.contains(KeyKind::Left));
+        assert!(sender.all_keys_cleared());
+    }
+
     #[test]
     fn track_input_delay_tracked() {
         let sender = test_key_sender();
@@ -540,6 +1011,37 @@ mod tests {
         assert!(sender.has_input_delay(KeyKind::Ctrl));
     }
 
+    #[test]
+    fn track_input_delay_key_timing() {
+        let mut sender = test_key_sender();
+        sender.set_key_timings(HashMap::from([
+            (
+                KeyKind::Ctrl,
+                KeyTiming {
+                    press_millis: Some(1000),
+                    repeat_millis: None,
+                },
+            ),
+            (
+                KeyKind::Alt,
+                KeyTiming {
+                    press_millis: Some(1000),
+                    repeat_millis: Some(0),
+                },
+            ),
+        ]));
+
+        assert_matches!(sender.track_input_delay(KeyKind::Ctrl), InputDelay::Tracked);
+        assert_eq!(
+            sender.delay_map.borrow().get(&KeyKind::Ctrl).copied(),
+            Some((1000.0 / ms_per_tick_f32()).round() as u32)
+        );
+        assert_matches!(
+            sender.track_input_delay(KeyKind::Alt),
+            InputDelay::Untracked
+        );
+    }
+
     #[test]
     fn track_input_delay_already_tracked() {
         let sender = test_key_sender();
@@ -555,11 +1057,14 @@ mod tests {
         let count = 50;
         sender.delay_map.borrow_mut().insert(KeyKind::Ctrl, count);
 
+        sender.held_keys.insert(KeyKind::Ctrl);
+
         for _ in 0..count {
             sender.update_input_delay(0);
         }
         // After `count` updates, key should be released and removed
         assert!(!sender.has_input_delay(KeyKind::Ctrl));
+        assert!(!sender.held_keys.contains(KeyKind::Ctrl));
     }
 
     #[test]
@@ -575,4 +1080,34 @@ mod tests {
         sender.update_input_delay(200);
         assert_ne!(sender.delay_mean_std_pair, original_pair);
     }
+
+    #[test]
+    fn to_desktop_preview_downscale_and_capture_area() {
+        let frame = Frame {
+            width: 1920,
+            height: 1080,
+            data: [10, 20, 30, 255].repeat(1920 * 1080),
+        };
+
+        let preview = to_desktop_preview(frame);
+        assert_eq!(
+            (preview.width, preview.height, preview.scale),
+            (640, 360, 3)
+        );
+        assert_eq!(&preview.frame[..4], &[30, 20, 10, 255]);
+        assert_eq!(
+            preview.to_capture_area(Bound {
+                x: 100,
+                y: 50,
+                width: 600,
+                height: 100,
+            }),
+            Bound {
+                x: 300,
+                y: 150,
+                width: 1620,
+                height: 300,
+            }
+        );
+    }
 }
@@ -4,7 +4,8 @@ use std::{
 };
 
 use anyhow::Result;
-use strum::EnumIter;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString};
 
 use crate::{
     Character, Settings,
@@ -74,7 +75,7 @@ impl BuffState {
             BuffKind::ExtremeBluePotion => character.extreme_blue_potion_key.enabled,
             BuffKind::ExtremeGreenPotion => character.extreme_green_potion_key.enabled,
             BuffKind::ExtremeGoldPotion => character.extreme_gold_potion_key.enabled,
-        };
+        } && !character.is_loadout_buff_disabled(self.kind);
         if !self.enabled {
             self.fail_count = 0;
             self.task = None;
@@ -93,12 +94,15 @@ pub enum Buff {
     Volatile,
 }
 
-#[derive(Clone, Copy, Debug, EnumIter)]
-#[cfg_attr(test, derive(PartialEq))]
+/// The kinds of buff detected and kept up by the bot.
+#[derive(
+    Clone, Copy, Default, PartialEq, Debug, EnumIter, Display, EnumString, Serialize, Deserialize,
+)]
 #[repr(usize)]
 pub enum BuffKind {
     // NOTE: Upon failing to solving rune, there is a cooldown
     // that looks exactly like the normal rune buff.
+    #[default]
     Rune,
     Familiar,
     SayramElixir,
@@ -0,0 +1,67 @@
+#[cfg(target_os = "linux")]
+use platforms::linux::{Handle, query_capture_handles, query_process_name};
+#[cfg(target_os = "macos")]
+use platforms::macos::{Handle, query_capture_handles, query_process_name};
+#[cfg(windows)]
+use platforms::windows::{Handle, query_capture_handles, query_process_name};
+
+use crate::database::CaptureWindow;
+
+/// Creates the [`CaptureWindow`] persisted when the capture handle `title` and `handle` is
+/// selected.
+pub fn capture_window_of(title: &str, handle: Handle) -> CaptureWindow {
+    CaptureWindow {
+        title: title.to_string(),
+        process_name: query_process_name(handle).unwrap_or_default(),
+    }
+}
+
+/// Finds the handle of the currently opened window matching `window`.
+pub fn find_capture_window(window: &CaptureWindow) -> Option<Handle> {
+    query_capture_handles()
+        .into_iter()
+        .find(|(title, handle)| is_window_matched(window, title, || query_process_name(*handle)))
+        .map(|(_, handle)| handle)
+}
+
+/// Whether the window with `title` and process name from `process_name` matches `window`.
+///
+/// The process name is only queried when both `window` has one and the title matches.
+fn is_window_matched(
+    window: &CaptureWindow,
+    title: &str,
+    process_name: impl FnOnce() -> Option<String>,
+) -> bool {
+    if !title.to_lowercase().contains(&window.title.to_lowercase()) {
+        return false;
+    }
+    window.process_name.is_empty()
+        || process_name().is_some_and(|name| name.eq_ignore_ascii_case(&window.process_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_window_matched_title_and_process_name() {
+        let window = CaptureWindow {
+            title: "MapleStory".to_string(),
+            process_name: "maplestory.exe".to_string(),
+        };
+
+        let maplestory = || Some("MapleStory.exe".to_string());
+        let notepad = || Some("notepad.exe".to_string());
+
+        assert!(is_window_matched(&window, "MAPLESTORY", maplestory));
+        assert!(!is_window_matched(&window, "MapleStory", notepad));
+        assert!(!is_window_matched(&window, "MapleStory", || None));
+        assert!(!is_window_matched(&window, "Notepad", maplestory));
+
+        let window = CaptureWindow {
+            process_name: String::new(),
+            ..window
+        };
+        assert!(is_window_matched(&window, "MapleStory - Ch. 1", notepad));
+    }
+}
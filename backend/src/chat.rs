@@ -0,0 +1,87 @@
+use anyhow::Result;
+use log::debug;
+use opencv::core::Rect;
+
+use crate::{
+    context::Context,
+    database::ChatDetection,
+    task::{Task, Update, update_detection_task},
+};
+
+/// Milliseconds interval between each chat box detection.
+const CHAT_UPDATE_MILLIS: u64 = 2000;
+
+/// Watches the chat box for new lines containing any of the configured keywords.
+#[derive(Debug, Default)]
+pub struct ChatWatcher {
+    task: Option<Task<Result<Vec<String>>>>,
+    /// The lines from the last detection used to skip lines that were already checked.
+    lines: Vec<String>,
+}
+
+impl ChatWatcher {
+    /// Detects the chat box lines and returns `true` if a new line contains a keyword.
+    pub fn update(&mut self, context: &Context, chat: &ChatDetection) -> bool {
+        if !chat.enabled || chat.keywords.is_empty() {
+            self.lines.clear();
+            return false;
+        }
+
+        let region = Rect::new(chat.x, chat.y, chat.width, chat.height);
+        let Update::Ok(lines) = update_detection_task(
+            context,
+            CHAT_UPDATE_MILLIS,
+            &mut self.task,
+            move |detector| Ok(detector.detect_chat_lines(region)),
+        ) else {
+            return false;
+        };
+        let matched = find_new_keyword_line(&self.lines, &lines, &chat.keywords);
+        if let Some(line) = matched {
+            debug!(target: "chat", "keyword detected in {line}");
+        }
+        let has_keyword = matched.is_some();
+        self.lines = lines;
+        has_keyword
+    }
+}
+
+/// Finds the first line in `lines` not in `previous` that contains any of the `keywords`.
+fn find_new_keyword_line<'a>(
+    previous: &[String],
+    lines: &'a [String],
+    keywords: &[String],
+) -> Option<&'a String> {
+    lines
+        .iter()
+        .filter(|line| !previous.contains(line))
+        .find(|line| {
+            let line = line.to_lowercase();
+            keywords
+                .iter()
+                .filter(|keyword| !keyword.is_empty())
+                .any(|keyword| line.contains(&keyword.to_lowercase()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_new_keyword_line;
+
+    #[test]
+    fn find_new_keyword_line_skips_previous_lines() {
+        let keywords = vec!["GM".to_string(), String::new()];
+        let previous = vec!["[GM] hello".to_string()];
+        let lines = vec![
+            "[GM] hello".to_string(),
+            "someone: hi".to_string(),
+            "gmBob >> are you there?".to_string(),
+        ];
+
+        assert_eq!(
+            find_new_keyword_line(&previous, &lines, &keywords),
+            Some(&lines[2])
+        );
+        assert_eq!(find_new_keyword_line(&lines, &lines, &keywords), None);
+    }
+}
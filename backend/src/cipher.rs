@@ -0,0 +1,83 @@
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce, aead::Aead};
+use anyhow::{Result, anyhow};
+use argon2::Argon2;
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+/// The prefix marking an encrypted value.
+///
+/// Values without it are plain text written before the database was encrypted.
+const ENCRYPTED_PREFIX: &str = "enc:";
+
+/// The length in bytes of the random salt the key is derived with.
+pub const SALT_LEN: usize = 16;
+
+const NONCE_LEN: usize = 12;
+
+/// Encrypts the JSON `data` columns with a key derived from the database passphrase.
+pub struct DataCipher {
+    cipher: Aes256Gcm,
+}
+
+impl DataCipher {
+    /// Derives the key from `passphrase` and `salt`.
+    pub fn new(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        let mut key = [0; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|err| anyhow!("failed to derive database key {err}"))?;
+        Ok(Self {
+            cipher: Aes256Gcm::new(&key.into()),
+        })
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let nonce = rand::random::<[u8; NONCE_LEN]>();
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .map_err(|err| anyhow!("failed to encrypt {err}"))?;
+        let bytes = nonce.into_iter().chain(ciphertext).collect::<Vec<_>>();
+        Ok(format!("{ENCRYPTED_PREFIX}{}", STANDARD.encode(bytes)))
+    }
+
+    /// Decrypts `data` or returns it as is if it is not encrypted.
+    pub fn decrypt(&self, data: &str) -> Result<String> {
+        let Some(encoded) = data.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(data.to_string());
+        };
+        let bytes = STANDARD.decode(encoded)?;
+        if bytes.len() < NONCE_LEN {
+            return Err(anyhow!("encrypted data is too short"));
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("incorrect passphrase or corrupted data"))?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_roundtrip() {
+        let salt = [1; SALT_LEN];
+        let cipher = DataCipher::new("passphrase", &salt).unwrap();
+        let encrypted = cipher.encrypt(r#"{"webhook":"url"}"#).unwrap();
+        assert!(encrypted.starts_with(ENCRYPTED_PREFIX));
+        assert!(!encrypted.contains("webhook"));
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), r#"{"webhook":"url"}"#);
+
+        let wrong = DataCipher::new("wrong", &salt).unwrap();
+        assert!(wrong.decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn decrypt_plain_text_as_is() {
+        let cipher = DataCipher::new("passphrase", &[1; SALT_LEN]).unwrap();
+        assert_eq!(cipher.decrypt("{}").unwrap(), "{}");
+    }
+}
@@ -0,0 +1,241 @@
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "linux")]
+use platforms::linux::ProcessChange;
+#[cfg(target_os = "macos")]
+use platforms::macos::ProcessChange;
+#[cfg(windows)]
+use platforms::windows::ProcessChange;
+
+use crate::database::ClientRestart;
+
+/// The interval between each poll of the game process.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The delay after the game process exited before relaunching it.
+///
+/// This gives time for crash reporters and the previous window to close.
+const LAUNCH_DELAY: Duration = Duration::from_secs(5);
+
+/// An action returned from [`ClientRestarter::update`] for the caller to perform.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClientRestartAction {
+    /// The game process has exited and the bot should halt.
+    Halt,
+    /// The game client should be relaunched.
+    Launch,
+    /// The game window is found and the bot should log in and resume.
+    Resume,
+    /// The game window is not found in time and the bot should stay halted.
+    GiveUp,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum State {
+    Watching,
+    /// Waiting until the [`Instant`] to relaunch.
+    Launching(Instant),
+    /// Waiting for the game window until the [`Instant`] deadline.
+    WaitingWindow(Instant),
+    /// Waiting until the [`Instant`] to log in after the game window is found.
+    LoggingIn(Instant),
+}
+
+/// Tracks the game client restart after its process exited.
+#[derive(Debug)]
+pub struct ClientRestarter {
+    state: State,
+    next_poll_instant: Option<Instant>,
+}
+
+impl Default for ClientRestarter {
+    fn default() -> Self {
+        Self {
+            state: State::Watching,
+            next_poll_instant: None,
+        }
+    }
+}
+
+impl ClientRestarter {
+    /// Whether the game process should be polled at `now`.
+    pub fn should_poll(&mut self, now: Instant) -> bool {
+        if self.next_poll_instant.is_some_and(|instant| now < instant) {
+            return false;
+        }
+        self.next_poll_instant = Some(now + POLL_INTERVAL);
+        true
+    }
+
+    /// Whether the game window should be looked up because the client is restarting.
+    #[inline]
+    pub fn is_waiting_window(&self) -> bool {
+        matches!(self.state, State::WaitingWindow(_))
+    }
+
+    /// Updates the restart state with the polled `change` of the game process.
+    ///
+    /// The game process exiting is ignored unless `session_active` or the client is already
+    /// restarting.
+    pub fn update(
+        &mut self,
+        restart: &ClientRestart,
+        change: Option<ProcessChange>,
+        session_active: bool,
+        now: Instant,
+    ) -> Option<ClientRestartAction> {
+        let restarting = !matches!(self.state, State::Watching);
+        if change == Some(ProcessChange::Exited)
+            && (session_active || restarting)
+            && !matches!(self.state, State::Launching(_))
+        {
+            self.state = if restart.path.is_empty() {
+                State::WaitingWindow(now + Duration::from_millis(restart.window_timeout_millis))
+            } else {
+                State::Launching(now + LAUNCH_DELAY)
+            };
+            return Some(ClientRestartAction::Halt);
+        }
+
+        match self.state {
+            State::Watching => None,
+            State::Launching(instant) => {
+                if now < instant {
+                    return None;
+                }
+                self.state = State::WaitingWindow(
+                    now + Duration::from_millis(restart.window_timeout_millis),
+                );
+                Some(ClientRestartAction::Launch)
+            }
+            State::WaitingWindow(deadline) => {
+                if change == Some(ProcessChange::Started) {
+                    self.state =
+                        State::LoggingIn(now + Duration::from_millis(restart.login_delay_millis));
+                    return None;
+                }
+                if now < deadline {
+                    return None;
+                }
+                self.cancel();
+                Some(ClientRestartAction::GiveUp)
+            }
+            State::LoggingIn(instant) => {
+                if now < instant {
+                    return None;
+                }
+                self.cancel();
+                Some(ClientRestartAction::Resume)
+            }
+        }
+    }
+
+    /// Cancels the ongoing restart if any.
+    #[inline]
+    pub fn cancel(&mut self) {
+        self.state = State::Watching;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{ClientRestartAction, ClientRestarter, LAUNCH_DELAY, POLL_INTERVAL, ProcessChange};
+    use crate::database::ClientRestart;
+
+    fn restart() -> ClientRestart {
+        ClientRestart {
+            enabled: true,
+            path: "MapleStory.exe".to_string(),
+            window_timeout_millis: 10000,
+            login_delay_millis: 1000,
+        }
+    }
+
+    #[test]
+    fn should_poll_every_interval() {
+        let mut restarter = ClientRestarter::default();
+        let now = Instant::now();
+
+        assert!(restarter.should_poll(now));
+        assert!(!restarter.should_poll(now + POLL_INTERVAL / 2));
+        assert!(restarter.should_poll(now + POLL_INTERVAL));
+    }
+
+    #[test]
+    fn update_relaunches_and_resumes() {
+        let mut restarter = ClientRestarter::default();
+        let restart = restart();
+        let now = Instant::now();
+
+        assert_eq!(
+            restarter.update(&restart, Some(ProcessChange::Started), true, now),
+            None
+        );
+        assert_eq!(
+            restarter.update(&restart, Some(ProcessChange::Exited), true, now),
+            Some(ClientRestartAction::Halt)
+        );
+        assert_eq!(restarter.update(&restart, None, true, now), None);
+        let launched = now + LAUNCH_DELAY;
+        assert_eq!(
+            restarter.update(&restart, None, true, launched),
+            Some(ClientRestartAction::Launch)
+        );
+        assert!(restarter.is_waiting_window());
+        assert_eq!(
+            restarter.update(&restart, Some(ProcessChange::Started), true, launched),
+            None
+        );
+        assert!(!restarter.is_waiting_window());
+        assert_eq!(
+            restarter.update(&restart, None, true, launched + Duration::from_millis(999)),
+            None
+        );
+        assert_eq!(
+            restarter.update(&restart, None, true, launched + Duration::from_millis(1000)),
+            Some(ClientRestartAction::Resume)
+        );
+    }
+
+    #[test]
+    fn update_ignores_exit_without_active_session() {
+        let mut restarter = ClientRestarter::default();
+        let now = Instant::now();
+
+        assert_eq!(
+            restarter.update(&restart(), Some(ProcessChange::Exited), false, now),
+            None
+        );
+        assert_eq!(
+            restarter.update(&restart(), None, false, now + LAUNCH_DELAY),
+            None
+        );
+    }
+
+    #[test]
+    fn update_gives_up_waiting_window() {
+        let mut restarter = ClientRestarter::default();
+        let restart = ClientRestart {
+            path: String::new(),
+            ..restart()
+        };
+        let now = Instant::now();
+
+        assert_eq!(
+            restarter.update(&restart, Some(ProcessChange::Exited), true, now),
+            Some(ClientRestartAction::Halt)
+        );
+        assert!(restarter.is_waiting_window());
+        assert_eq!(
+            restarter.update(&restart, None, true, now + Duration::from_millis(9999)),
+            None
+        );
+        assert_eq!(
+            restarter.update(&restart, None, true, now + Duration::from_millis(10000)),
+            Some(ClientRestartAction::GiveUp)
+        );
+        assert!(!restarter.is_waiting_window());
+    }
+}
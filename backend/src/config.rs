@@ -0,0 +1,184 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+};
+
+use anyhow::Result;
+use log::{LevelFilter, error, info};
+use serde::Deserialize;
+
+use crate::database::CaptureMode;
+
+/// The file name of the configuration next to the executable.
+const CONFIG_FILE_NAME: &str = "komari.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Startup overrides read from `komari.toml` next to the executable.
+///
+/// Every field is optional and left unset fields use the built-in defaults.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The path to the SQLite database file.
+    pub database_path: Option<PathBuf>,
+    /// The maximum log level such as `info` or `debug`.
+    pub log_level: Option<String>,
+    /// The number of update loop ticks per second.
+    ///
+    /// Timeouts counted in ticks scale with this value.
+    pub tick_rate: Option<u32>,
+    /// The capture mode used when settings are first created.
+    pub capture_mode: Option<CaptureMode>,
+    /// The address (e.g. `127.0.0.1:9184`) to serve Prometheus metrics on.
+    pub metrics_address: Option<String>,
+    /// The address (e.g. `127.0.0.1:9186`) to serve the bot status for streaming overlays on.
+    pub overlay_address: Option<String>,
+    /// Whether to encrypt the database with a passphrase asked at startup.
+    ///
+    /// Once encrypted, the passphrase is always asked even if this is unset.
+    pub encrypt_database: bool,
+    /// The proxy to route webhook and RPC traffic through.
+    pub proxy: Option<ProxyConfig>,
+    pub models: ModelPaths,
+}
+
+/// A HTTP or SOCKS5 proxy.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyConfig {
+    /// The proxy URL such as `http://127.0.0.1:8080` or `socks5://127.0.0.1:1080`.
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl ProxyConfig {
+    /// Returns the username and password if the proxy requires authentication.
+    pub fn credentials(&self) -> Option<(&str, &str)> {
+        self.username
+            .as_deref()
+            .map(|username| (username, self.password.as_deref().unwrap_or_default()))
+    }
+}
+
+/// Paths to ONNX models used instead of the embedded ones.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelPaths {
+    pub minimap: Option<PathBuf>,
+    pub mob: Option<PathBuf>,
+    pub rune: Option<PathBuf>,
+    pub text_detection: Option<PathBuf>,
+    pub text_recognition: Option<PathBuf>,
+}
+
+impl Config {
+    /// Parses the log level or [`None`] if unset or invalid.
+    pub fn log_level(&self) -> Option<LevelFilter> {
+        self.log_level
+            .as_deref()
+            .and_then(|level| LevelFilter::from_str(level).ok())
+    }
+
+    fn from_file(path: &Path) -> Result<Option<Config>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(toml::from_str(&fs::read_to_string(path)?)?))
+    }
+}
+
+/// Returns the startup configuration, loading it on first call.
+///
+/// Falls back to the default configuration if the file is missing or invalid.
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        let path = env::current_exe()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join(CONFIG_FILE_NAME);
+        match Config::from_file(&path) {
+            Ok(Some(config)) => {
+                info!(target: "config", "loaded configuration from {}", path.display());
+                config
+            }
+            Ok(None) => Config::default(),
+            Err(err) => {
+                error!(target: "config", "failed to load {}: {err}", path.display());
+                Config::default()
+            }
+        }
+    })
+}
+
+/// Reads the model at `path` if set or returns the `embedded` model otherwise.
+///
+/// Falls back to `embedded` if the file cannot be read.
+pub fn model_bytes(path: Option<&PathBuf>, embedded: &'static [u8]) -> Vec<u8> {
+    path.and_then(|path| {
+        fs::read(path)
+            .inspect_err(
+                |err| error!(target: "config", "failed to read model {}: {err}", path.display()),
+            )
+            .ok()
+    })
+    .unwrap_or_else(|| embedded.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use log::LevelFilter;
+
+    use super::Config;
+    use crate::database::CaptureMode;
+
+    #[test]
+    fn config_parse_partial() {
+        let config = toml::from_str::<Config>(
+            r#"
+            log_level = "debug"
+            tick_rate = 20
+            capture_mode = "BitBltArea"
+            metrics_address = "127.0.0.1:9184"
+            overlay_address = "127.0.0.1:9186"
+
+            [proxy]
+            url = "socks5://127.0.0.1:1080"
+            username = "user"
+
+            [models]
+            minimap = "models/minimap.onnx"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.log_level(), Some(LevelFilter::Debug));
+        assert_eq!(config.tick_rate, Some(20));
+        assert_eq!(config.capture_mode, Some(CaptureMode::BitBltArea));
+        assert_eq!(config.metrics_address.as_deref(), Some("127.0.0.1:9184"));
+        assert_eq!(config.overlay_address.as_deref(), Some("127.0.0.1:9186"));
+        assert_eq!(
+            config.proxy.as_ref().unwrap().url,
+            "socks5://127.0.0.1:1080"
+        );
+        assert_eq!(
+            config.proxy.as_ref().unwrap().credentials(),
+            Some(("user", ""))
+        );
+        assert_eq!(
+            config.models.minimap.as_deref(),
+            Some("models/minimap.onnx".as_ref())
+        );
+        assert!(config.database_path.is_none());
+        assert!(config.models.mob.is_none());
+    }
+
+    #[test]
+    fn config_parse_empty() {
+        assert_eq!(toml::from_str::<Config>("").unwrap(), Config::default());
+    }
+}
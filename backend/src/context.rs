@@ -1,5 +1,5 @@
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     env,
     rc::Rc,
     sync::atomic::{AtomicBool, Ordering},
@@ -10,47 +10,118 @@ use std::{
 use dyn_clone::clone_box;
 #[cfg(debug_assertions)]
 use log::debug;
+use log::error;
 use opencv::{
     core::{Vector, VectorToVec},
-    imgcodecs::imencode_def,
+    imgcodecs::{IMWRITE_JPEG_QUALITY, imencode},
 };
 #[cfg(windows)]
-use platforms::windows::{self, Handle, KeyInputKind, KeyReceiver};
+use platforms::windows::{
+    self, FocusMonitor, Handle, KeyInputKind, KeyReceiver, ProcessSupervisor,
+};
+#[cfg(target_os = "linux")]
+use platforms::linux::{self, FocusMonitor, Handle, KeyInputKind, KeyReceiver, ProcessSupervisor};
 #[cfg(target_os = "macos")]
-use platforms::macos::{self, Handle, KeyInputKind, KeyReceiver};
+use platforms::macos::{self, FocusMonitor, Handle, KeyInputKind, KeyReceiver, ProcessSupervisor};
 use strum::IntoEnumIterator;
 use tokio::sync::broadcast;
 
 use crate::{
     Action,
+    anti_afk::AntiAfkRunner,
+    avoidance::AvoidanceZones,
+    breaks::{BreakScheduler, BreakTransition},
     bridge::{DefaultKeySender, ImageCapture, ImageCaptureKind, KeySender, KeySenderMethod},
     buff::{Buff, BuffKind, BuffState},
-    database::{CaptureMode, InputMethod, KeyBinding, query_seeds, query_settings},
-    detect::{CachedDetector, Detector},
+    capture_window::find_capture_window,
+    chat::ChatWatcher,
+    client_restart::ClientRestarter,
+    config::config,
+    database::{
+        CaptureMode, DetectionTuning, InputMethod, KeyBinding, query_seeds, query_settings,
+    },
+    detect::{CachedDetector, DetectionScores, Detector},
+    diagnostics,
+    events::{Event, EventBus},
+    following::FollowingLink,
+    frame_diff::FrameDiff,
+    frame_pool::FramePool,
+    itinerary::ItineraryRunner,
+    latency::LatencyTester,
+    login::LoginRunner,
     mat::OwnedMat,
+    metrics,
     minimap::{Minimap, MinimapState},
-    network::{DiscordNotification, NotificationKind},
+    network::{DiscordNotification, NotificationKind, http_client},
+    overlay,
+    panic_playbook::{PanicPlaybookRunner, PanicTrigger},
     player::{PanicTo, Panicking, Player, PlayerState},
+    plugin::PluginHost,
+    recording::VideoRecorder,
     request_handler::DefaultRequestHandler,
     rng::Rng,
     rotator::Rotator,
+    routine::RoutineRunner,
+    schedule::PresetScheduler,
+    self_test,
+    session::SessionTracker,
+    shutdown::ShutdownRunner,
     skill::{Skill, SkillKind, SkillState},
+    stats::{ActionStatsTracker, ExpStats, MesoStats, RuneStats},
+    suspension::CaptureSuspension,
+    update::{self, UpdateChecker},
+    watchdog::WatchdogState,
+    wizard::CaptureTester,
 };
 #[cfg(test)]
 use crate::{Settings, bridge::MockKeySender, detect::MockDetector};
 
 const FPS: u32 = 30;
-pub const MS_PER_TICK: u64 = MS_PER_TICK_F32 as u64;
-pub const MS_PER_TICK_F32: f32 = 1000.0 / FPS as f32;
+/// The FPS to tick at while [`Context::low_power`].
+const LOW_POWER_FPS: u32 = 2;
+
+/// The number of update loop ticks per second, overridable by
+/// [`Config::tick_rate`](crate::config::Config::tick_rate).
+#[inline]
+pub fn tick_rate() -> u32 {
+    config().tick_rate.filter(|rate| *rate > 0).unwrap_or(FPS)
+}
+
+/// The milliseconds between two ticks at [`tick_rate`].
+#[inline]
+pub fn ms_per_tick() -> u64 {
+    ms_per_tick_f32() as u64
+}
+
+/// The fractional milliseconds between two ticks at [`tick_rate`].
+#[inline]
+pub fn ms_per_tick_f32() -> f32 {
+    1000.0 / tick_rate() as f32
+}
 
 // Simple shutdown flag for update loop - using AtomicBool instead of LazyLock to avoid race conditions
 static UPDATE_LOOP_SHUTDOWN: AtomicBool = AtomicBool::new(false);
+static UPDATE_LOOP_EXITED: AtomicBool = AtomicBool::new(false);
 
 /// Signal the update loop to shutdown gracefully
 pub fn signal_update_loop_shutdown() {
     UPDATE_LOOP_SHUTDOWN.store(true, Ordering::Relaxed);
 }
 
+/// Waits up to `timeout` for the update loop to exit after [`signal_update_loop_shutdown`].
+///
+/// Returns `true` if the update loop exited and released all held keys.
+pub fn wait_update_loop_shutdown(timeout: Duration) -> bool {
+    let instant = Instant::now();
+    while !UPDATE_LOOP_EXITED.load(Ordering::Acquire) {
+        if instant.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    true
+}
+
 /// Check if the update loop should shutdown
 fn should_update_loop_shutdown() -> bool {
     UPDATE_LOOP_SHUTDOWN.load(Ordering::Relaxed)
@@ -108,6 +179,30 @@ pub struct Context {
     pub buffs: [Buff; BuffKind::COUNT],
     /// Whether the bot is halting.
     pub halting: bool,
+    /// Whether the minimap region of the current frame has not changed since the last changed
+    /// frame.
+    ///
+    /// Detections inside the minimap can be skipped when this is true.
+    pub minimap_unchanged: bool,
+    /// The detection thresholds tuned through [`crate::Settings::detection_tuning`].
+    pub detection_tuning: DetectionTuning,
+    /// The latest template match scores of the detections tuned by [`Self::detection_tuning`].
+    pub detection_scores: Cell<DetectionScores>,
+    /// Whether the capture is suspended because the game window can no longer be captured.
+    ///
+    /// Keys are released when suspended and the capture handle is periodically re-acquired
+    /// until a frame is captured again.
+    pub suspended: bool,
+    /// Whether the update loop is ticking at [`LOW_POWER_FPS`] because the bot is halting with
+    /// nothing else running.
+    ///
+    /// Player and rune detections are skipped when this is true.
+    pub low_power: bool,
+    /// The offset to add to a frame coordinate for the client coordinate of mouse inputs.
+    ///
+    /// This is non-zero only when the frame is captured from an area of the screen instead of
+    /// the game window.
+    pub frame_to_client_offset: (i32, i32),
     /// The game current tick.
     ///
     /// This is increased on each update tick.
@@ -128,6 +223,12 @@ impl Context {
             skills: [Skill::Detecting; SkillKind::COUNT],
             buffs: [Buff::No; BuffKind::COUNT],
             halting: false,
+            minimap_unchanged: false,
+            detection_tuning: DetectionTuning::default(),
+            detection_scores: Cell::new(DetectionScores::default()),
+            suspended: false,
+            low_power: false,
+            frame_to_client_offset: (0, 0),
             tick: 0,
         }
     }
@@ -153,6 +254,12 @@ pub fn init() {
         .compare_exchange(false, true, Ordering::SeqCst, Ordering::Acquire)
         .is_ok()
     {
+        if let Some(level) = config().log_level() {
+            log::set_max_level(level);
+        }
+        // The replaced process must release the database and ports before this one starts
+        update::wait_for_previous_process();
+        update::remove_previous_binary();
         let dll = env::current_exe()
             .unwrap()
             .parent()
@@ -162,13 +269,29 @@ pub fn init() {
                 { "onnxruntime.dll" }
                 #[cfg(target_os = "macos")]
                 { "libonnxruntime.dylib" }
+                #[cfg(target_os = "linux")]
+                { "libonnxruntime.so" }
             });
 
         ort::init_from(dll.to_str().unwrap()).commit().unwrap();
+        self_test::spawn();
+        diagnostics::install_panic_hook();
+        if let Some(address) = config().metrics_address.as_deref()
+            && let Err(err) = metrics::serve(address)
+        {
+            error!(target: "context", "failed to serve metrics on {address} {err}");
+        }
+        if let Some(address) = config().overlay_address.as_deref()
+            && let Err(err) = overlay::serve(address)
+        {
+            error!(target: "context", "failed to serve overlay on {address} {err}");
+        }
         #[cfg(windows)]
         windows::init();
         #[cfg(target_os = "macos")]
         macos::init();
+        #[cfg(target_os = "linux")]
+        linux::init();
         thread::spawn(|| {
             let tokio_rt = tokio::runtime::Builder::new_multi_thread()
                 .enable_all()
@@ -190,44 +313,63 @@ fn update_loop() {
     let handle = Handle::new("MapleStoryClass");
     let mut rotator = Rotator::default();
     let mut actions = Vec::<Action>::new();
+    let mut pending_actions = None;
     let mut character = None; // Override by UI
     let mut buffs = vec![];
-    let settings = query_settings(); // Override by UI
+    let settings = query_settings().expect("failed to query settings"); // Override by UI
     let seeds = query_seeds(); // Fixed, unchanged
     let rng = Rng::new(seeds.seed); // Create one for Context
-
-    let key_sender_method = if let InputMethod::Rpc = settings.input_method {
-        KeySenderMethod::Rpc(handle, settings.input_method_rpc_server_url.clone())
-    } else {
-        match settings.capture_mode {
+    // Re-selects the persisted capture window if it is already opened
+    let mut selected_capture_handle = settings
+        .capture_window
+        .as_ref()
+        .and_then(find_capture_window);
+    let capture_handle = selected_capture_handle.unwrap_or(handle);
+
+    let key_sender_method = match settings.input_method {
+        InputMethod::Rpc => {
+            KeySenderMethod::Rpc(capture_handle, settings.input_method_rpc_server_url.clone())
+        }
+        InputMethod::Serial => {
+            KeySenderMethod::Serial(capture_handle, settings.input_method_serial_port.clone())
+        }
+        InputMethod::Default => match settings.capture_mode {
             CaptureMode::BitBlt | CaptureMode::WindowsGraphicsCapture => {
-                KeySenderMethod::Default(handle, KeyInputKind::Fixed)
+                KeySenderMethod::Default(capture_handle, KeyInputKind::Fixed)
             }
             // This shouldn't matter because we have to get the Handle from the box capture anyway
             CaptureMode::BitBltArea => KeySenderMethod::Default(handle, KeyInputKind::Foreground),
-        }
+        },
     };
     let mut keys = DefaultKeySender::new(key_sender_method, seeds);
     let key_sender = broadcast::channel::<KeyBinding>(1).0; // Callback to UI
-    let mut key_receiver = KeyReceiver::new(handle, KeyInputKind::Fixed);
+    let mut key_receiver = KeyReceiver::new(capture_handle, KeyInputKind::Fixed);
 
     let mut capture_handles = Vec::<(String, Handle)>::new();
-    let mut selected_capture_handle = None;
-    let mut image_capture = ImageCapture::new(handle, settings.capture_mode, &settings);
+    let mut image_capture = ImageCapture::new(capture_handle, settings.capture_mode, &settings);
     if let ImageCaptureKind::BitBltArea(capture) = image_capture.kind() {
         key_receiver = KeyReceiver::new(capture.handle(), KeyInputKind::Foreground);
-        // Only override to Default if user chose Default input method, preserve RPC choice
-        if let InputMethod::Default = settings.input_method {
-            keys.set_method(KeySenderMethod::Default(
-                capture.handle(),
-                KeyInputKind::Foreground,
-            ));
-        } else {
-            // For RPC mode, update the handle but preserve RPC method
-            keys.set_method(KeySenderMethod::Rpc(
-                capture.handle(),
-                settings.input_method_rpc_server_url.clone(),
-            ));
+        // Only override to Default if user chose Default input method, preserve RPC or serial choice
+        match settings.input_method {
+            InputMethod::Default => {
+                keys.set_method(KeySenderMethod::Default(
+                    capture.handle(),
+                    KeyInputKind::Foreground,
+                ));
+            }
+            // For RPC and serial modes, update the handle but preserve the method
+            InputMethod::Rpc => {
+                keys.set_method(KeySenderMethod::Rpc(
+                    capture.handle(),
+                    settings.input_method_rpc_server_url.clone(),
+                ));
+            }
+            InputMethod::Serial => {
+                keys.set_method(KeySenderMethod::Serial(
+                    capture.handle(),
+                    settings.input_method_serial_port.clone(),
+                ));
+            }
         }
     }
 
@@ -243,6 +385,12 @@ fn update_loop() {
         skills: [Skill::Detecting],
         buffs: [Buff::No; BuffKind::COUNT],
         halting: true,
+        minimap_unchanged: false,
+        detection_tuning: settings.borrow().detection_tuning,
+        detection_scores: Cell::new(DetectionScores::default()),
+        suspended: false,
+        low_power: false,
+        frame_to_client_offset: (0, 0),
         tick: 0,
     };
     let mut player_state = PlayerState::default();
@@ -253,23 +401,71 @@ fn update_loop() {
     let mut buff_states = BuffKind::iter()
         .map(BuffState::new)
         .collect::<Vec<BuffState>>();
+    let mut frame_diff = FrameDiff::default();
+    let frame_pool = FramePool::default();
+    let mut exp_stats = ExpStats::default();
+    let mut meso_stats = MesoStats::default();
+    let mut rune_stats = RuneStats::default();
+    let mut action_stats = ActionStatsTracker::default();
+    let mut avoidance_zones = AvoidanceZones::default();
+    let mut panic_playbook = PanicPlaybookRunner::default();
+    let mut routines = RoutineRunner::default();
+    routines.load();
+    let mut itinerary = ItineraryRunner::default();
+    let mut login = LoginRunner::default();
+    let mut shutdown = ShutdownRunner::default();
+    let mut chat_watcher = ChatWatcher::default();
+    let mut watchdog = WatchdogState::new(http_client());
+    let mut suspension = CaptureSuspension::default();
+    let mut focus_monitor = FocusMonitor::new(capture_handle);
+    let mut process_supervisor = ProcessSupervisor::new(capture_handle);
+    let mut client_restarter = ClientRestarter::default();
+    let mut events = EventBus::default();
+    let mut preset_scheduler = PresetScheduler::default();
+    let mut video_recorder = VideoRecorder::default();
+    let mut session = SessionTracker::load();
+    let mut update_checker = UpdateChecker::new(http_client());
+    let mut latency_tester = LatencyTester::default();
+    let mut capture_tester = CaptureTester::default();
+    let mut anti_afk = AntiAfkRunner::default();
+    let mut breaks = BreakScheduler::default();
+    let mut following = FollowingLink::default();
+    let mut plugins = PluginHost::default();
+    plugins.load();
 
     #[cfg(debug_assertions)]
     let mut recording_images_id = None;
     #[cfg(debug_assertions)]
     let mut infering_rune = None;
 
-    loop_with_fps(FPS, || {
-        let mat = image_capture.grab().map(OwnedMat::new);
+    loop_with_fps(tick_rate(), || {
+        let tick_start = Instant::now();
+        let mat = image_capture.grab_pooled(&frame_pool);
+        let has_frame = mat.is_some();
         let was_minimap_idle = matches!(context.minimap, Minimap::Idle(_));
         let was_player_alive = !player_state.is_dead;
+        let was_player_unstucking = matches!(context.player, Player::Unstucking(_, _, _));
+        let was_inventory_full =
+            matches!(context.minimap, Minimap::Idle(idle) if idle.has_inventory_full());
         let detector = mat.map(CachedDetector::new);
+        let mut has_chat_keyword = false;
+        let mut detection_duration = None;
 
         context.tick += 1;
         if let Some(detector) = detector {
+            let detection_start = Instant::now();
             context.detector = Some(Box::new(detector));
+            context.minimap_unchanged = frame_diff.update(
+                context.detector_unwrap().mat(),
+                match context.minimap {
+                    Minimap::Idle(idle) => Some(idle.bbox),
+                    Minimap::Detecting => None,
+                },
+            );
             context.minimap = fold_context(&context, context.minimap, &mut minimap_state);
-            context.player = fold_context(&context, context.player, &mut player_state);
+            if !context.low_power {
+                context.player = fold_context(&context, context.player, &mut player_state);
+            }
             for (i, state) in skill_states
                 .iter_mut()
                 .enumerate()
@@ -280,17 +476,31 @@ fn update_loop() {
             for (i, state) in buff_states.iter_mut().enumerate().take(context.buffs.len()) {
                 context.buffs[i] = fold_context(&context, context.buffs[i], state);
             }
+            if !context.halting {
+                exp_stats.update(&context);
+                meso_stats.update(&context, &settings.borrow().mesos);
+            }
+            has_chat_keyword = chat_watcher.update(&context, &settings.borrow().chat);
+            detection_duration = Some(detection_start.elapsed());
             // Rotating action must always be done last
             rotator.rotate_action(&context, &mut player_state);
         }
+        update_checker.update(settings.borrow().check_updates);
         // TODO: Maybe should not downcast but really don't want to public update_input_delay
         // method
-        context
+        let keys = context
             .keys
             .as_any_mut()
             .downcast_mut::<DefaultKeySender>()
-            .unwrap()
-            .update_input_delay(context.tick);
+            .unwrap();
+        keys.set_humanization(settings.borrow().humanization);
+        keys.set_key_mapping(
+            settings.borrow().keyboard_layout,
+            &settings.borrow().key_overrides,
+        );
+        keys.update_input_delay(context.tick);
+        keys.update_rate_limiter(&settings.borrow().safe_mode, Instant::now());
+        keys.update_rpc_heartbeat();
 
         // Poll requests, keys and update scheduled notifications frames
         let mut settings_borrow_mut = settings.borrow_mut();
@@ -302,9 +512,36 @@ fn update_loop() {
             buffs: &mut buffs,
             buff_states: &mut buff_states,
             actions: &mut actions,
+            pending_actions: &mut pending_actions,
             rotator: &mut rotator,
             player: &mut player_state,
             minimap: &mut minimap_state,
+            exp_stats: &mut exp_stats,
+            meso_stats: &mut meso_stats,
+            rune_stats: &mut rune_stats,
+            action_stats: &mut action_stats,
+            avoidance_zones: &mut avoidance_zones,
+            panic_playbook: &mut panic_playbook,
+            routines: &mut routines,
+            itinerary: &mut itinerary,
+            login: &mut login,
+            shutdown: &mut shutdown,
+            watchdog: &mut watchdog,
+            suspension: &mut suspension,
+            focus_monitor: &mut focus_monitor,
+            process_supervisor: &mut process_supervisor,
+            client_restarter: &mut client_restarter,
+            events: &mut events,
+            preset_scheduler: &mut preset_scheduler,
+            video_recorder: &mut video_recorder,
+            session: &mut session,
+            update_checker: &mut update_checker,
+            latency_tester: &mut latency_tester,
+            capture_tester: &mut capture_tester,
+            anti_afk: &mut anti_afk,
+            breaks: &mut breaks,
+            following: &mut following,
+            plugins: &mut plugins,
             key_sender: &key_sender,
             key_receiver: &mut key_receiver,
             image_capture: &mut image_capture,
@@ -319,30 +556,93 @@ fn update_loop() {
         handler.poll_key();
         #[cfg(debug_assertions)]
         handler.poll_debug();
+        handler.update_capture_suspension(has_frame);
+        handler.update_frame_to_client_offset();
+        let jpeg_quality = handler.settings.notifications.screenshot_jpeg_quality;
         handler.context.notification.update_scheduled_frames(|| {
-            to_png(
+            to_jpeg(
                 handler
                     .context
                     .detector
                     .as_ref()
                     .map(|detector| detector.mat()),
+                jpeg_quality,
             )
         });
 
+        if has_chat_keyword && handler.settings.chat.trigger_panic_playbook {
+            handler.start_panic_playbook(PanicTrigger::GmWhisper);
+        }
+        // Avoids running anything that sends keys while the game window is gone
+        let suspended = handler.context.suspended;
+        let notify_panic = !suspended && handler.update_panic_playbook();
+        let break_transition = if suspended {
+            None
+        } else {
+            handler.update_safe_mode_break();
+            handler.update_breaks()
+        };
+        let shutdown_summary = if suspended {
+            None
+        } else {
+            handler.update_shutdown()
+        };
+        if !suspended {
+            handler.update_routines();
+            handler.update_latency_test();
+            handler.update_anti_afk();
+        }
+        handler.update_capture_test();
+        let notify_watchdog = handler.update_watchdog(has_frame);
+        let notify_focus_lost = handler.update_focus();
+        handler.update_client_restart();
+        if !suspended {
+            handler.update_login();
+        }
+        handler.update_preset_schedule();
+        handler.update_preset_hot_swap();
+        let emitted_events = handler.update_events();
+        metrics::record_actions_completed(
+            Instant::now(),
+            emitted_events
+                .iter()
+                .filter(|event| matches!(event, Event::ActionCompleted { .. }))
+                .count(),
+        );
+        handler.update_rune_stats(&emitted_events);
+        handler.update_action_stats(&emitted_events);
+        handler.update_following();
+        handler.update_avoidance_zones(was_player_unstucking);
+        handler.update_diagnostics();
+        handler.update_overlay();
+        handler.update_video_recording();
+        handler.update_session();
+        if !suspended {
+            handler.update_itinerary(was_minimap_idle);
+            handler.update_plugins(&emitted_events);
+        }
+        handler.context.low_power = handler.can_low_power();
+
         // Upon accidental or white roomed causing map to change,
         // abort actions and send notification
-        if handler.minimap.data().is_some() && !handler.context.halting {
+        let mut notify_fail_or_map_change = false;
+        if handler.minimap.data().is_some() && !handler.context.halting && !suspended {
             let minimap_changed =
                 was_minimap_idle && matches!(handler.context.minimap, Minimap::Detecting);
+            let minimap_unmatched = handler.update_minimap_switch(was_minimap_idle);
             let player_died = was_player_alive && handler.player.is_dead;
-            let can_halt_or_notify = minimap_changed
+            // Waits for the minimap to be detected again before halting when auto switching
+            let can_halt_or_notify = (minimap_changed
+                && !handler.settings.auto_switch_minimap
+                && handler.itinerary.traveling().is_none()
                 && !matches!(
                     handler.context.player,
                     Player::Panicking(Panicking {
                         to: PanicTo::Channel,
                         ..
                     })
-                );
+                ))
+                || minimap_unmatched;
             match (
                 player_died,
                 can_halt_or_notify,
@@ -357,18 +657,79 @@ fn update_loop() {
                 }
                 _ => (),
             }
-            if can_halt_or_notify {
-                drop(settings_borrow_mut); // For notification to borrow immutably
+            let inventory_full = !was_inventory_full
+                && matches!(
+                    handler.context.minimap,
+                    Minimap::Idle(idle) if idle.has_inventory_full()
+                );
+            if inventory_full && !handler.context.halting {
+                handler.update_inventory_full();
+            }
+            notify_fail_or_map_change = can_halt_or_notify;
+        }
+
+        drop(settings_borrow_mut); // For notification to borrow immutably
+        if notify_fail_or_map_change {
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::FailOrMapChange);
+        }
+        if notify_panic {
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::PanicTriggered);
+        }
+        if has_chat_keyword {
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::ChatKeyword);
+        }
+        if notify_watchdog {
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::WatchdogTripped);
+        }
+        if notify_focus_lost {
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::FocusLost);
+        }
+        match break_transition {
+            Some(BreakTransition::Pause) => {
+                let _ = context
+                    .notification
+                    .schedule_notification(NotificationKind::BreakStarted);
+            }
+            Some(BreakTransition::Resume) => {
                 let _ = context
                     .notification
-                    .schedule_notification(NotificationKind::FailOrMapChange);
+                    .schedule_notification(NotificationKind::BreakEnded);
             }
+            Some(BreakTransition::Walk) | None => (),
         }
+        if let Some(summary) = shutdown_summary {
+            let _ = context
+                .notification
+                .schedule_notification_with_details(NotificationKind::SessionEnded, summary);
+        }
+        if emitted_events.contains(&Event::Death) {
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::PlayerIsDead);
+        }
+
+        let now = Instant::now();
+        metrics::record_tick(now, now.duration_since(tick_start), detection_duration);
+        context.low_power
     });
+
+    // Keys held down must not outlive the update loop
+    let _ = context.keys.release_all();
+    UPDATE_LOOP_EXITED.store(true, Ordering::Release);
 }
 
 #[inline]
-fn fold_context<C>(
+pub fn fold_context<C>(
     context: &Context,
     contextual: C,
     persistent: &mut <C as Contextual>::Persistent,
@@ -387,12 +748,14 @@ where
     }
 }
 
+/// Calls `on_tick` at `fps` or at [`LOW_POWER_FPS`] while `on_tick` returns `true`.
 #[inline]
-fn loop_with_fps(fps: u32, mut on_tick: impl FnMut()) {
+fn loop_with_fps(fps: u32, mut on_tick: impl FnMut() -> bool) {
     #[cfg(debug_assertions)]
     const LOG_INTERVAL_SECS: u64 = 5;
 
-    let nanos_per_frame = (1_000_000_000 / fps) as u128;
+    let nanos_per_normal_frame = (1_000_000_000 / fps) as u128;
+    let nanos_per_low_power_frame = (1_000_000_000 / LOW_POWER_FPS) as u128;
     #[cfg(debug_assertions)]
     let mut last_logged_instant = Instant::now();
 
@@ -405,7 +768,11 @@ fn loop_with_fps(fps: u32, mut on_tick: impl FnMut()) {
         
         let start = Instant::now();
 
-        on_tick();
+        let nanos_per_frame = if on_tick() {
+            nanos_per_low_power_frame
+        } else {
+            nanos_per_normal_frame
+        };
 
         let now = Instant::now();
         let elapsed_duration = now.duration_since(start);
@@ -423,10 +790,11 @@ fn loop_with_fps(fps: u32, mut on_tick: impl FnMut()) {
 }
 
 #[inline]
-fn to_png(frame: Option<&OwnedMat>) -> Option<Vec<u8>> {
+fn to_jpeg(frame: Option<&OwnedMat>, quality: u32) -> Option<Vec<u8>> {
     frame.and_then(|image| {
         let mut bytes = Vector::new();
-        imencode_def(".png", image, &mut bytes).ok()?;
+        let params = Vector::from_slice(&[IMWRITE_JPEG_QUALITY, quality.clamp(1, 100) as i32]);
+        imencode(".jpg", image, &mut bytes, &params).ok()?;
         Some(bytes.to_vec())
     })
 }
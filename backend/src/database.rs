@@ -1,23 +1,225 @@
 use std::{
+    cell::OnceCell,
     collections::{HashMap, HashSet},
-    env,
-    sync::{LazyLock, Mutex},
+    env, iter,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    sync::{
+        Condvar, LazyLock, Mutex, OnceLock,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
 };
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use base64::{Engine, engine::general_purpose::STANDARD};
 use opencv::core::Rect;
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
-use rusqlite::{Connection, Params, Statement, types::Null};
+use rusqlite::{Connection, OptionalExtension, Params, Statement, types::Null};
 use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
 use serde_json::Value;
 use strum::{Display, EnumIter, EnumString};
+use tokio::sync::oneshot;
+
+use crate::{
+    buff::BuffKind,
+    cipher::{DataCipher, SALT_LEN},
+    config::config,
+    context::ms_per_tick,
+    events::{Event, EventRecord},
+    pathing,
+};
+
+/// A job to run on the database worker thread.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A passphrase to unlock the database with and the sender of the unlock result.
+type Unlock = (String, mpsc::SyncSender<Result<()>>);
+
+/// The tables with an encrypted JSON `data` column.
+const DATA_TABLES: [&str; 7] = [
+    "maps",
+    "characters",
+    "settings",
+    "seeds",
+    "events",
+    "routines",
+    "sessions",
+];
+
+/// The number of latest rows kept in the `events` table.
+const EVENTS_MAX_ROWS: i64 = 10000;
+
+/// The number of latest rows kept per map in the `rune_spawns` table.
+const RUNE_SPAWNS_MAX_ROWS: i64 = 500;
+
+/// The known plain text encrypted in the `meta` table to verify the passphrase.
+const CIPHER_CHECK: &str = "komari";
+
+/// The lock state of the database while the worker thread opens it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum LockState {
+    Opening,
+    Locked,
+    Unlocked,
+}
+
+static LOCK_STATE: (Mutex<LockState>, Condvar) = (Mutex::new(LockState::Opening), Condvar::new());
+
+/// The sender of passphrases to the database worker thread while it is locked.
+static UNLOCKS: LazyLock<(Sender<Unlock>, Mutex<Receiver<Unlock>>)> = LazyLock::new(|| {
+    let (tx, rx) = mpsc::channel();
+    (tx, Mutex::new(rx))
+});
+
+/// The cipher of the `data` columns or [`None`] if the database is not encrypted.
+///
+/// This is set once by the worker thread before it runs any job.
+static CIPHER: OnceLock<Option<DataCipher>> = OnceLock::new();
+
+/// The sender of jobs to the database worker thread.
+///
+/// The worker thread is the only thread owning the [`Connection`].
+static WORKER: LazyLock<mpsc::Sender<Job>> = LazyLock::new(|| {
+    let (tx, rx) = mpsc::channel::<Job>();
+    thread::Builder::new()
+        .name("database".to_string())
+        .spawn(move || run_worker(rx))
+        .expect("failed to spawn database worker");
+    tx
+});
+
+thread_local! {
+    /// The connection of the database worker thread, unset on any other thread.
+    static CONNECTION: OnceCell<Connection> = const { OnceCell::new() };
+}
+
+/// Runs `f` on the database worker thread.
+///
+/// Database functions called inside `f` run directly on the worker thread. Jobs queued while
+/// another is running are batched into a single transaction.
+///
+/// Returns an error if the worker thread stopped or `f` panicked.
+pub async fn run<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    WORKER
+        .send(Box::new(move || {
+            let _ = tx.send(f());
+        }))
+        .map_err(|_| anyhow!("database worker stopped"))?;
+    rx.await.map_err(|_| anyhow!("database job panicked"))
+}
+
+/// Runs `f` with the worker thread [`Connection`] and blocks until it completes.
+///
+/// If the current thread is already the worker thread, `f` is run directly.
+fn with_connection<T, F>(f: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection) -> T + Send + 'static,
+{
+    if CONNECTION.with(|conn| conn.get().is_some()) {
+        return CONNECTION.with(|conn| f(conn.get().unwrap()));
+    }
+
+    let (tx, rx) = mpsc::sync_channel(1);
+    WORKER
+        .send(Box::new(move || {
+            let _ = tx.send(CONNECTION.with(|conn| f(conn.get().unwrap())));
+        }))
+        .expect("database worker stopped");
+    rx.recv().expect("database job panicked")
+}
+
+/// Runs `f` with the worker thread [`Connection`] without waiting for it to complete.
+///
+/// This is used for writes from the update loop thread that do not need a result.
+fn spawn_with_connection<F>(f: F)
+where
+    F: FnOnce(&Connection) + Send + 'static,
+{
+    if CONNECTION.with(|conn| conn.get().is_some()) {
+        return CONNECTION.with(|conn| f(conn.get().unwrap()));
+    }
 
-use crate::pathing;
+    if WORKER
+        .send(Box::new(move || {
+            CONNECTION.with(|conn| f(conn.get().unwrap()))
+        }))
+        .is_err()
+    {
+        log::error!("database worker stopped");
+    }
+}
+
+/// Whether the database is encrypted and waiting for [`unlock`].
+///
+/// This blocks until the database is opened.
+pub fn is_locked() -> bool {
+    LazyLock::force(&WORKER);
+    let (state, condvar) = &LOCK_STATE;
+    let state = condvar
+        .wait_while(state.lock().unwrap(), |state| *state == LockState::Opening)
+        .unwrap();
+    *state == LockState::Locked
+}
+
+/// Unlocks the encrypted database with `passphrase`.
+///
+/// Returns an error if `passphrase` is incorrect or the database is not locked.
+pub fn unlock(passphrase: String) -> Result<()> {
+    if !is_locked() {
+        return Err(anyhow!("database is not locked"));
+    }
+    let (tx, rx) = mpsc::sync_channel(1);
+    UNLOCKS
+        .0
+        .send((passphrase, tx))
+        .map_err(|_| anyhow!("database worker stopped"))?;
+    rx.recv().map_err(|_| anyhow!("database worker stopped"))?
+}
+
+fn set_lock_state(lock_state: LockState) {
+    let (state, condvar) = &LOCK_STATE;
+    *state.lock().unwrap() = lock_state;
+    condvar.notify_all();
+}
+
+fn run_worker(rx: Receiver<Job>) {
+    CONNECTION.with(|conn| {
+        let conn = conn.get_or_init(open_connection);
+        let _ = CIPHER.set(open_cipher(conn));
+        while let Ok(job) = rx.recv() {
+            // Batches jobs queued up such as when the UI saves rapidly
+            let jobs = iter::once(job).chain(rx.try_iter()).collect::<Vec<_>>();
+            let transaction = jobs.len() > 1 && conn.execute_batch("BEGIN;").is_ok();
+            for job in jobs {
+                // The sender of a panicked job is dropped and so the caller will panic instead
+                let _ = panic::catch_unwind(AssertUnwindSafe(job));
+            }
+            if transaction && let Err(err) = conn.execute_batch("COMMIT;") {
+                log::error!("failed to commit database transaction {err}");
+                let _ = conn.execute_batch("ROLLBACK;");
+            }
+        }
+    });
+}
+
+fn open_connection() -> Connection {
+    if let Some(path) = config().database_path.as_ref() {
+        log::info!("Using configured database location: {}", path.display());
+        return open_connection_at(path);
+    }
 
-static CONNECTION: LazyLock<Mutex<Connection>> = LazyLock::new(|| {
     // Use a consistent database location regardless of build mode (debug/release)
     // Look for Cargo.toml to find project root, fallback to current directory
     let mut current_dir = env::current_dir().expect("Failed to get current directory");
@@ -51,7 +253,124 @@ static CONNECTION: LazyLock<Mutex<Connection>> = LazyLock::new(|| {
     let path = db_dir.join("local.db");
     log::info!("Using database location: {}", path.display());
     
-    let conn = Connection::open(path.to_str().unwrap()).expect("failed to open local.db");
+    open_connection_at(&path)
+}
+
+/// Waits for the passphrase if the database is encrypted or [`Config::encrypt_database`] is set.
+///
+/// [`Config::encrypt_database`]: crate::config::Config::encrypt_database
+fn open_cipher(conn: &Connection) -> Option<DataCipher> {
+    let check = query_meta(conn, "cipher_check");
+    if check.is_none() && !config().encrypt_database {
+        set_lock_state(LockState::Unlocked);
+        return None;
+    }
+
+    set_lock_state(LockState::Locked);
+    let unlocks = UNLOCKS.1.lock().unwrap();
+    while let Ok((passphrase, result)) = unlocks.recv() {
+        match unlock_cipher(conn, &passphrase, check.as_deref()) {
+            Ok(cipher) => {
+                let _ = result.send(Ok(()));
+                set_lock_state(LockState::Unlocked);
+                return Some(cipher);
+            }
+            Err(err) => {
+                let _ = result.send(Err(err));
+            }
+        }
+    }
+    None
+}
+
+/// Creates the cipher from `passphrase` and verifies it against `check`.
+///
+/// If there is no `check`, the database is encrypted for the first time by encrypting all the
+/// existing plain text rows.
+fn unlock_cipher(conn: &Connection, passphrase: &str, check: Option<&str>) -> Result<DataCipher> {
+    if let Some(check) = check {
+        let salt = STANDARD.decode(
+            query_meta(conn, "cipher_salt").ok_or_else(|| anyhow!("missing database salt"))?,
+        )?;
+        let cipher = DataCipher::new(passphrase, &salt)?;
+        if cipher.decrypt(check)? != CIPHER_CHECK {
+            return Err(anyhow!("incorrect passphrase"));
+        }
+        return Ok(cipher);
+    }
+
+    let salt = rand::random::<[u8; SALT_LEN]>();
+    let cipher = DataCipher::new(passphrase, &salt)?;
+    conn.execute_batch("BEGIN;")?;
+    let encrypted = (|| -> Result<()> {
+        for table in DATA_TABLES {
+            let rows = conn
+                .prepare(&format!("SELECT id, data FROM {table}"))?
+                .query_map([], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (id, data) in rows {
+                conn.execute(
+                    &format!("UPDATE {table} SET data = ?1 WHERE id = ?2;"),
+                    (cipher.encrypt(&data)?, id),
+                )?;
+            }
+        }
+        upsert_meta(conn, "cipher_salt", &STANDARD.encode(salt))?;
+        upsert_meta(conn, "cipher_check", &cipher.encrypt(CIPHER_CHECK)?)?;
+        Ok(())
+    })();
+    match encrypted {
+        Ok(()) => conn.execute_batch("COMMIT;")?,
+        Err(err) => {
+            let _ = conn.execute_batch("ROLLBACK;");
+            return Err(err);
+        }
+    }
+    // The overwritten plain text rows otherwise stay in the free pages of the database file
+    if let Err(err) = conn.execute_batch("VACUUM;") {
+        log::error!("failed to vacuum encrypted database {err}");
+    }
+    log::info!("Encrypted database");
+    Ok(cipher)
+}
+
+fn query_meta(conn: &Connection, key: &str) -> Option<String> {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1;", [key], |row| {
+        row.get::<_, String>(0)
+    })
+    .optional()
+    .ok()
+    .flatten()
+}
+
+fn upsert_meta(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2) ON CONFLICT (key) DO UPDATE SET value = ?2;",
+        (key, value),
+    )?;
+    Ok(())
+}
+
+/// Encrypts `json` if the database is encrypted.
+fn encrypt_data(json: String) -> Result<String> {
+    match CIPHER.get() {
+        Some(Some(cipher)) => cipher.encrypt(&json),
+        _ => Ok(json),
+    }
+}
+
+/// Decrypts `data` if the database is encrypted.
+fn decrypt_data(data: String) -> Result<String> {
+    match CIPHER.get() {
+        Some(Some(cipher)) => cipher.decrypt(&data),
+        _ => Ok(data),
+    }
+}
+
+fn open_connection_at(path: &Path) -> Connection {
+    let conn = Connection::open(path).expect("failed to open database");
     conn.execute_batch(
         r#"
         CREATE TABLE IF NOT EXISTS maps (
@@ -70,11 +389,44 @@ static CONNECTION: LazyLock<Mutex<Connection>> = LazyLock::new(|| {
             id INTEGER PRIMARY KEY,
             data TEXT NOT NULL
         );
+        CREATE TABLE IF NOT EXISTS events (
+            id INTEGER PRIMARY KEY,
+            timestamp INTEGER NOT NULL,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS routines (
+            id INTEGER PRIMARY KEY,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS routine_runs (
+            routine_id INTEGER PRIMARY KEY,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS rune_spawns (
+            id INTEGER PRIMARY KEY,
+            minimap_id INTEGER NOT NULL,
+            x INTEGER NOT NULL,
+            y INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS avoidance_zones (
+            id INTEGER PRIMARY KEY,
+            minimap_id INTEGER NOT NULL,
+            x INTEGER NOT NULL,
+            y INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS sessions (
+            id INTEGER PRIMARY KEY,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
         "#,
     )
     .unwrap();
-    Mutex::new(conn)
-});
+    conn
+}
 
 trait Identifiable {
     fn id(&self) -> Option<i64>;
@@ -96,6 +448,13 @@ macro_rules! impl_identifiable {
     };
 }
 
+impl_identifiable!(Seeds);
+impl_identifiable!(Session);
+impl_identifiable!(Routine);
+impl_identifiable!(Settings);
+impl_identifiable!(Character);
+impl_identifiable!(Minimap);
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Seeds {
     pub id: Option<i64>,
@@ -111,7 +470,28 @@ impl Default for Seeds {
     }
 }
 
-impl_identifiable!(Seeds);
+/// The states of the running session persisted for resuming after a crash or restart.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(skip_serializing, default)]
+    pub id: Option<i64>,
+    pub minimap_id: Option<i64>,
+    pub preset: Option<String>,
+    pub character_id: Option<i64>,
+    pub halting: bool,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            id: None,
+            minimap_id: None,
+            preset: None,
+            character_id: None,
+            halting: true,
+        }
+    }
+}
 
 #[derive(
     Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
@@ -120,6 +500,36 @@ pub enum InputMethod {
     #[default]
     Default,
     Rpc,
+    Serial,
+}
+
+/// How much human-like randomness is applied to key and mouse inputs.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum HumanizationProfile {
+    Off,
+    #[default]
+    Light,
+    Heavy,
+}
+
+/// The physical keyboard layout keys are sent through.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Azerty,
+    Qwertz,
+}
+
+/// Sends [`Self::to`] in place of [`Self::from`] regardless of the [`KeyboardLayout`].
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct KeyOverride {
+    pub from: KeyBinding,
+    pub to: KeyBinding,
 }
 
 #[derive(
@@ -139,6 +549,27 @@ pub enum FamiliarRarity {
     Epic,
 }
 
+/// The rarity of replacement familiar cards selected first when swapping.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum FamiliarRarityPriority {
+    #[default]
+    Any,
+    Rare,
+    Epic,
+}
+
+/// The level of replacement familiar cards selected first when swapping.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum FamiliarLevelPriority {
+    #[default]
+    Lowest,
+    Highest,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Familiars {
     pub enable_familiars_swapping: bool,
@@ -146,6 +577,13 @@ pub struct Familiars {
     pub swap_check_millis: u64,
     pub swappable_familiars: SwappableFamiliars,
     pub swappable_rarities: HashSet<FamiliarRarity>,
+    /// Whether to also swap out familiars whose points are depleted in addition to level 5 ones.
+    #[serde(default)]
+    pub swap_when_points_depleted: bool,
+    #[serde(default)]
+    pub rarity_priority: FamiliarRarityPriority,
+    #[serde(default)]
+    pub level_priority: FamiliarLevelPriority,
 }
 
 impl Default for Familiars {
@@ -155,6 +593,9 @@ impl Default for Familiars {
             swap_check_millis: familiars_swap_check_millis(),
             swappable_familiars: SwappableFamiliars::default(),
             swappable_rarities: HashSet::default(),
+            swap_when_points_depleted: false,
+            rarity_priority: FamiliarRarityPriority::default(),
+            level_priority: FamiliarLevelPriority::default(),
         }
     }
 }
@@ -170,9 +611,571 @@ pub enum EliteBossBehavior {
     #[default]
     CycleChannel,
     UseKey,
+    /// Moves to the elite boss and uses [`Minimap::elite_boss_fight_preset`] until it is gone.
+    Fight,
+}
+
+/// A point relative to the client coordinate (e.g. capture area).
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ClientPoint {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SellItems {
+    pub enabled: bool,
+    /// The shop NPC position on the minimap.
+    pub npc_position: Position,
+    /// Sells every milliseconds with `0` indicating disabled.
+    #[serde(default = "sell_items_every_millis_default")]
+    pub every_millis: u64,
+    /// Also sells when the inventory is detected as full.
+    pub on_inventory_full: bool,
+    /// The inventory tabs to click in the shop.
+    pub tabs: Vec<ClientPoint>,
+    /// The slots to double click to sell in each of the tabs.
+    pub slots: Vec<ClientPoint>,
+}
+
+impl Default for SellItems {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            npc_position: Position::default(),
+            every_millis: sell_items_every_millis_default(),
+            on_inventory_full: false,
+            tabs: vec![],
+            slots: vec![],
+        }
+    }
+}
+
+fn sell_items_every_millis_default() -> u64 {
+    1800000
+}
+
+/// Fires the [`ActionConfiguration`]s marked as burst together on a shared interval.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BurstWindow {
+    pub enabled: bool,
+    /// The interval between bursts, usually the longest cooldown among the burst actions.
+    pub interval_millis: u64,
 }
 
+impl Default for BurstWindow {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_millis: 120000,
+        }
+    }
+}
+
+/// The steps run to wind down a session when rotating actions is stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ShutdownRoutine {
+    pub enabled: bool,
+    /// Whether to return to town before halting.
+    pub go_to_town: bool,
+    /// Whether to send a notification summarizing the session.
+    pub notify: bool,
+    /// Whether to exit to the lobby through the game menu and close the game client after
+    /// returning to town.
+    pub exit_game: bool,
+    /// Stops rotating actions after running for milliseconds with `0` indicating disabled.
+    pub stop_after_millis: u64,
+}
+
+impl Default for ShutdownRoutine {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            go_to_town: true,
+            notify: true,
+            exit_game: false,
+            stop_after_millis: 0,
+        }
+    }
+}
+
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum InventoryFullBehavior {
+    #[default]
+    Stop,
+    GoToTown,
+    RunScript,
+}
+
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum PanicStepKind {
+    #[default]
+    StopInput,
+    GoToTown,
+    ChangeChannel,
+    ExitToLobby,
+    RunScript,
+    Notify,
+}
+
+/// A single step of a [`PanicPlaybook`].
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct PanicStep {
+    pub kind: PanicStepKind,
+    /// Milliseconds to wait before running this step.
+    pub delay_millis: u64,
+    /// The script path to run when [`PanicStepKind::RunScript`] is used.
+    #[serde(default)]
+    pub script: String,
+}
+
+/// An ordered list of steps to run when a panic trigger happens.
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct PanicPlaybook {
+    pub enabled: bool,
+    pub steps: Vec<PanicStep>,
+}
+
+/// The panic playbooks for each of the triggers.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PanicPlaybooks {
+    /// Runs when a stranger player is detected on the minimap.
+    pub stranger_detected: PanicPlaybook,
+    /// Runs when a whisper from a GM-like player is detected in the chat.
+    pub gm_whisper: PanicPlaybook,
+    /// Runs when the player cannot be detected for
+    /// [`Self::detection_failure_streak_millis`].
+    pub detection_failure_streak: PanicPlaybook,
+    #[serde(default = "detection_failure_streak_millis_default")]
+    pub detection_failure_streak_millis: u64,
+}
+
+impl Default for PanicPlaybooks {
+    fn default() -> Self {
+        Self {
+            stranger_detected: PanicPlaybook {
+                enabled: false,
+                steps: vec![PanicStep {
+                    kind: PanicStepKind::ChangeChannel,
+                    ..PanicStep::default()
+                }],
+            },
+            gm_whisper: PanicPlaybook::default(),
+            detection_failure_streak: PanicPlaybook::default(),
+            detection_failure_streak_millis: detection_failure_streak_millis_default(),
+        }
+    }
+}
+
+fn detection_failure_streak_millis_default() -> u64 {
+    10000
+}
+
+/// When a [`Routine`] runs.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum RoutineTrigger {
+    /// Runs once each time actions start rotating such as after logging in.
+    #[default]
+    Login,
+    /// Runs every [`Routine::interval_millis`].
+    Schedule,
+}
+
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum RoutineStepKind {
+    #[default]
+    Key,
+    Click,
+}
+
+/// A single step of a [`Routine`].
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct RoutineStep {
+    pub kind: RoutineStepKind,
+    /// Milliseconds to wait before running this step.
+    pub delay_millis: u64,
+    /// The key to press when [`RoutineStepKind::Key`] is used.
+    pub key: KeyBinding,
+    /// The point to click when [`RoutineStepKind::Click`] is used.
+    pub point: ClientPoint,
+}
+
+/// A reusable ordered list of steps independent from minimaps (e.g. guild check-in).
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct Routine {
+    #[serde(skip_serializing, default)]
+    pub id: Option<i64>,
+    pub name: String,
+    pub enabled: bool,
+    pub trigger: RoutineTrigger,
+    /// Milliseconds between runs when [`RoutineTrigger::Schedule`] is used.
+    pub interval_millis: u64,
+    pub steps: Vec<RoutineStep>,
+}
+
+/// Dead-man switch thresholds for halting the bot when it can no longer run safely.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Watchdog {
+    pub enabled: bool,
+    /// Milliseconds without any captured frame before tripping with `0` indicating disabled.
+    pub capture_lost_millis: u64,
+    /// Milliseconds of consecutive detection failures before tripping with `0` indicating
+    /// disabled.
+    pub detection_failure_millis: u64,
+    /// The URL to periodically request with empty indicating disabled.
+    pub heartbeat_url: String,
+    pub heartbeat_interval_millis: u64,
+    /// Number of consecutive failed heartbeat requests before tripping.
+    pub heartbeat_max_failures: u32,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capture_lost_millis: 10000,
+            detection_failure_millis: 30000,
+            heartbeat_url: String::default(),
+            heartbeat_interval_millis: 30000,
+            heartbeat_max_failures: 3,
+        }
+    }
+}
+
+/// Restarts the session when the game client process exits (e.g. crashes).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClientRestart {
+    pub enabled: bool,
+    /// The game client or launcher executable to relaunch with empty indicating to wait for the
+    /// client to be relaunched manually.
+    pub path: String,
+    /// Milliseconds to wait for the game window after the client exited before giving up.
+    pub window_timeout_millis: u64,
+    /// Milliseconds to wait after the game window is found before logging in and resuming.
+    pub login_delay_millis: u64,
+}
+
+impl Default for ClientRestart {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: String::new(),
+            window_timeout_millis: 120000,
+            login_delay_millis: 20000,
+        }
+    }
+}
+
+/// Logs in from the login screen through scripted steps and the on-screen PIC keypad.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoginSequence {
+    pub enabled: bool,
+    /// The steps run before entering the PIC (e.g. clicking the world, channel and character).
+    pub steps: Vec<RoutineStep>,
+    /// The PIC digits entered through the on-screen keypad with empty indicating none.
+    pub pic: String,
+    /// The on-screen keypad region relative to the client.
+    pub pic_keypad: Bound,
+    /// Milliseconds without detecting the minimap while changing channel before assuming the
+    /// login screen is shown.
+    pub login_screen_after_millis: u64,
+}
+
+impl Default for LoginSequence {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            steps: vec![],
+            pic: String::new(),
+            pic_keypad: Bound::default(),
+            login_screen_after_millis: 30000,
+        }
+    }
+}
+
+/// Small randomized actions performed while halting or without any pending action to avoid
+/// appearing away from keyboard.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AntiAfk {
+    pub enabled: bool,
+    pub interval_millis: u64,
+    /// Random milliseconds in `[-jitter, jitter]` added to each interval.
+    pub interval_jitter_millis: u64,
+    /// Walks a few steps left or right.
+    pub walk: bool,
+    /// Maximum x distance from where anti-afk started that walking can move the player to.
+    pub walk_bound: u32,
+    /// Turns to face the other direction.
+    pub flip_facing: bool,
+    /// Opens and then closes a harmless menu with this key.
+    pub menu_key: KeyBindingConfiguration,
+}
+
+impl Default for AntiAfk {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_millis: 60000,
+            interval_jitter_millis: 20000,
+            walk: true,
+            walk_bound: 5,
+            flip_facing: true,
+            menu_key: KeyBindingConfiguration {
+                key: KeyBinding::I,
+                enabled: false,
+                ..KeyBindingConfiguration::default()
+            },
+        }
+    }
+}
+
+/// Pauses rotating for a random duration at random intervals.
+///
+/// Before pausing, the player walks to [`Minimap::break_position`] if there is one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Breaks {
+    pub enabled: bool,
+    pub interval_millis: u64,
+    /// Random milliseconds in `[-jitter, jitter]` added to each interval.
+    pub interval_jitter_millis: u64,
+    pub duration_millis: u64,
+    /// Random milliseconds in `[-jitter, jitter]` added to each duration.
+    pub duration_jitter_millis: u64,
+}
+
+impl Default for Breaks {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_millis: 5400000,
+            interval_jitter_millis: 1800000,
+            duration_millis: 600000,
+            duration_jitter_millis: 300000,
+        }
+    }
+}
+
+/// Hard caps on the inputs sent regardless of the rotation configuration.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SafeMode {
+    pub enabled: bool,
+    /// Maximum key presses per second or `0` for no cap.
+    pub max_keys_per_second: u32,
+    /// Maximum key presses and mouse clicks per minute or `0` for no cap.
+    pub max_inputs_per_minute: u32,
+    /// Milliseconds between two mandatory breaks or `0` for no breaks.
+    pub break_interval_millis: u64,
+    /// Random milliseconds in `[-jitter, jitter]` added to each break interval.
+    pub break_interval_jitter_millis: u64,
+    /// Milliseconds of a mandatory break during which no input is sent.
+    pub break_duration_millis: u64,
+    /// Random milliseconds in `[-jitter, jitter]` added to each break duration.
+    pub break_duration_jitter_millis: u64,
+}
+
+impl Default for SafeMode {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_keys_per_second: 10,
+            max_inputs_per_minute: 300,
+            break_interval_millis: 3600000,
+            break_interval_jitter_millis: 600000,
+            break_duration_millis: 300000,
+            break_duration_jitter_millis: 120000,
+        }
+    }
+}
+
+/// The role of this instance when following another character on the same map.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum FollowingRole {
+    #[default]
+    Off,
+    /// Shares the player minimap position with the connected followers.
+    Leader,
+    /// Moves toward the minimap position shared by the leader.
+    Follower,
+}
+
+/// Co-op mode where a follower instance paths toward the minimap position of a leader instance
+/// on the same map.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Following {
+    pub role: FollowingRole,
+    /// The address (e.g. `127.0.0.1:9185`) the leader listens on and the follower connects to.
+    pub address: String,
+    /// Maximum x or y distance from the leader before the follower moves toward it.
+    pub distance: u32,
+}
+
+impl Default for Following {
+    fn default() -> Self {
+        Self {
+            role: FollowingRole::default(),
+            address: "127.0.0.1:9185".to_string(),
+            distance: 10,
+        }
+    }
+}
+
+/// Recording of the capture stream to a video file for reviewing the bot behavior afterward.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VideoRecording {
+    pub format: VideoFormat,
+    /// Maximum number of frames written per second.
+    pub fps: u32,
+    /// Size in megabytes at which the recording stops with `0` indicating unlimited.
+    pub max_size_mb: u32,
+    /// Draws a box around the detected player on the minimap.
+    pub overlay_player: bool,
+    /// Draws the detected minimap bounding box.
+    pub overlay_minimap: bool,
+    /// Draws the current player state.
+    pub overlay_state: bool,
+}
+
+impl Default for VideoRecording {
+    fn default() -> Self {
+        Self {
+            format: VideoFormat::default(),
+            fps: 10,
+            max_size_mb: 500,
+            overlay_player: true,
+            overlay_minimap: true,
+            overlay_state: true,
+        }
+    }
+}
+
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum VideoFormat {
+    #[default]
+    Mp4,
+    Avi,
+}
+
+/// The behaviors when the game window loses focus.
+///
+/// Only applies to capture modes that capture the game window directly.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FocusTracking {
+    pub enabled: bool,
+    /// Halts rotating and releases held keys until the game window is focused again.
+    pub pause_keys: bool,
+    /// Attempts to bring the game window back to the foreground.
+    pub refocus: bool,
+}
+
+impl Default for FocusTracking {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pause_keys: true,
+            refocus: false,
+        }
+    }
+}
+
+/// The position and client size of the game window to arrange to when rotation starts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowArrangement {
+    pub enabled: bool,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Default for WindowArrangement {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            x: 0,
+            y: 0,
+            width: 1366,
+            height: 768,
+        }
+    }
+}
+
+/// Detection thresholds that can be tuned for unusual resolutions or game settings.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionTuning {
+    /// The grayscale value from 0 to 255 a minimap border pixel must be at least.
+    pub minimap_border_threshold: u8,
+    /// The minimum fingerprint similarity from 0 to 1 for a stored minimap to match the
+    /// detected one.
+    pub minimap_similarity: f32,
+    /// The minimum template match score from 0 to 1 of the player in the minimap.
+    pub player_score: f32,
+    /// The minimum template match score from 0 to 1 of both ends of the health bar.
+    pub health_bar_score: f32,
+    /// The lower HSV bound from 0 to 255 of a filled health bar gauge pixel.
+    ///
+    /// The hue range wraps around when the lower hue is greater than the upper hue. The gauge
+    /// is only used when the health values cannot be read.
+    pub health_bar_color_lower: [u8; 3],
+    /// The upper HSV bound from 0 to 255 of a filled health bar gauge pixel.
+    pub health_bar_color_upper: [u8; 3],
+    /// Whether to show the live match scores over the minimap.
+    pub show_scores: bool,
+}
+
+impl Default for DetectionTuning {
+    fn default() -> Self {
+        Self {
+            minimap_border_threshold: 160,
+            minimap_similarity: 0.9,
+            player_score: 0.75,
+            health_bar_score: 0.8,
+            health_bar_color_lower: [245, 150, 150],
+            health_bar_color_upper: [10, 255, 255],
+            show_scores: false,
+        }
+    }
+}
+
+/// Meso count detection for estimating mesos per hour.
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct MesoDetection {
+    pub enabled: bool,
+    /// The meso count region relative to the client (e.g. the inventory window kept open).
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Chat box keyword detection.
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct ChatDetection {
+    pub enabled: bool,
+    /// The chat box region relative to the client.
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// Case-insensitive keywords to look for in new chat lines (e.g. whispers, GM names).
+    pub keywords: Vec<String>,
+    /// Runs [`PanicPlaybooks::gm_whisper`] when a keyword is detected.
+    pub trigger_panic_playbook: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Notifications {
     pub discord_webhook_url: String,
     pub discord_user_id: String,
@@ -183,6 +1186,49 @@ pub struct Notifications {
     pub notify_on_player_guildie_appear: bool,
     pub notify_on_player_stranger_appear: bool,
     pub notify_on_player_friend_appear: bool,
+    #[serde(default)]
+    pub notify_on_inventory_full: bool,
+    #[serde(default)]
+    pub notify_on_chat_keyword: bool,
+    #[serde(default)]
+    pub notify_on_watchdog_trip: bool,
+    #[serde(default)]
+    pub notify_on_focus_lost: bool,
+    #[serde(default)]
+    pub notify_on_break: bool,
+    /// Whether to leave game screenshots out of notifications for privacy.
+    #[serde(default)]
+    pub hide_screenshots: bool,
+    /// The JPEG quality from 1 to 100 of the attached game screenshots.
+    #[serde(default = "screenshot_jpeg_quality_default")]
+    pub screenshot_jpeg_quality: u32,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            discord_webhook_url: String::new(),
+            discord_user_id: String::new(),
+            notify_on_fail_or_change_map: false,
+            notify_on_rune_appear: false,
+            notify_on_elite_boss_appear: false,
+            notify_on_player_die: false,
+            notify_on_player_guildie_appear: false,
+            notify_on_player_stranger_appear: false,
+            notify_on_player_friend_appear: false,
+            notify_on_inventory_full: false,
+            notify_on_chat_keyword: false,
+            notify_on_watchdog_trip: false,
+            notify_on_focus_lost: false,
+            notify_on_break: false,
+            hide_screenshots: false,
+            screenshot_jpeg_quality: screenshot_jpeg_quality_default(),
+        }
+    }
+}
+
+fn screenshot_jpeg_quality_default() -> u32 {
+    80
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -190,16 +1236,93 @@ pub struct Settings {
     #[serde(skip_serializing, default)]
     pub id: Option<i64>,
     pub capture_mode: CaptureMode,
+    /// The HDR handling of [`CaptureMode::WindowsGraphicsCapture`].
+    #[serde(default)]
+    pub capture_hdr_mode: CaptureHdrMode,
+    /// The selected capture window re-selected on startup and after the game restarts.
+    #[serde(default)]
+    pub capture_window: Option<CaptureWindow>,
     #[serde(default = "capture_x_default")]
     pub capture_x: i32,
     #[serde(default = "capture_y_default")]
     pub capture_y: i32,
+    #[serde(default = "capture_width_default")]
+    pub capture_width: i32,
+    #[serde(default = "capture_height_default")]
+    pub capture_height: i32,
     #[serde(default = "enable_rune_solving_default")]
     pub enable_rune_solving: bool,
+    /// Whether to keep the player near frequent rune spawn positions when a rune is expected.
+    #[serde(default)]
+    pub enable_rune_spawn_pathing: bool,
+    #[serde(default)]
+    pub panic_playbooks: PanicPlaybooks,
+    /// The removed panic mode toggle only read for migrating to [`Self::panic_playbooks`].
+    #[serde(default, skip_serializing)]
     pub enable_panic_mode: bool,
+    #[serde(default)]
+    pub chat: ChatDetection,
+    #[serde(default)]
+    pub mesos: MesoDetection,
+    #[serde(default)]
+    pub watchdog: Watchdog,
+    #[serde(default)]
+    pub client_restart: ClientRestart,
+    #[serde(default)]
+    pub login_sequence: LoginSequence,
+    #[serde(default)]
+    pub focus_tracking: FocusTracking,
+    #[serde(default)]
+    pub window_arrangement: WindowArrangement,
+    #[serde(default)]
+    pub detection_tuning: DetectionTuning,
+    #[serde(default)]
+    pub anti_afk: AntiAfk,
+    #[serde(default)]
+    pub breaks: Breaks,
+    #[serde(default)]
+    pub following: Following,
     pub stop_on_fail_or_change_map: bool,
+    /// Whether to switch to the stored minimap matching the detected minimap when the map
+    /// changes.
+    #[serde(default)]
+    pub auto_switch_minimap: bool,
+    #[serde(default)]
+    pub itinerary: Itinerary,
     pub input_method: InputMethod,
     pub input_method_rpc_server_url: String,
+    /// The serial port (e.g. `COM3`) of the HID device used by [`InputMethod::Serial`].
+    #[serde(default)]
+    pub input_method_serial_port: String,
+    #[serde(default)]
+    pub humanization: HumanizationProfile,
+    /// The keyboard layout used for translating keys to the same physical positions as QWERTY.
+    ///
+    /// Serial input is not translated because its keys are already sent by position.
+    #[serde(default)]
+    pub keyboard_layout: KeyboardLayout,
+    #[serde(default)]
+    pub key_overrides: Vec<KeyOverride>,
+    #[serde(default)]
+    pub safe_mode: SafeMode,
+    /// Whether downscaled frames are also recorded in the diagnostics history.
+    #[serde(default)]
+    pub record_diagnostics_frames: bool,
+    #[serde(default)]
+    pub video_recording: VideoRecording,
+    /// Whether to check for and download a newer release on startup.
+    ///
+    /// The downloaded binary must match the `.sha256` checksum published with the release.
+    #[serde(default)]
+    pub check_updates: bool,
+    /// Whether closing the main window hides it to the system tray instead of exiting.
+    #[serde(default)]
+    pub close_to_tray: bool,
+    /// Whether the first-run setup has been completed.
+    ///
+    /// Settings created before the first-run setup existed are treated as completed.
+    #[serde(default = "setup_completed_default")]
+    pub setup_completed: bool,
     pub notifications: Notifications,
     pub familiars: Familiars,
     #[serde(default = "toggle_actions_key_default")]
@@ -217,13 +1340,42 @@ impl Default for Settings {
         Self {
             id: None,
             capture_mode: CaptureMode::default(),
+            capture_hdr_mode: CaptureHdrMode::default(),
+            capture_window: None,
             capture_x: capture_x_default(),
             capture_y: capture_y_default(),
+            capture_width: capture_width_default(),
+            capture_height: capture_height_default(),
             enable_rune_solving: enable_rune_solving_default(),
+            enable_rune_spawn_pathing: false,
+            panic_playbooks: PanicPlaybooks::default(),
             enable_panic_mode: false,
+            chat: ChatDetection::default(),
+            mesos: MesoDetection::default(),
+            watchdog: Watchdog::default(),
+            client_restart: ClientRestart::default(),
+            login_sequence: LoginSequence::default(),
+            focus_tracking: FocusTracking::default(),
+            window_arrangement: WindowArrangement::default(),
+            detection_tuning: DetectionTuning::default(),
+            anti_afk: AntiAfk::default(),
+            breaks: Breaks::default(),
+            following: Following::default(),
             input_method: InputMethod::default(),
             input_method_rpc_server_url: String::default(),
+            input_method_serial_port: String::default(),
+            humanization: HumanizationProfile::default(),
+            keyboard_layout: KeyboardLayout::default(),
+            key_overrides: vec![],
+            safe_mode: SafeMode::default(),
+            record_diagnostics_frames: false,
+            video_recording: VideoRecording::default(),
+            check_updates: false,
+            close_to_tray: false,
+            setup_completed: false,
             stop_on_fail_or_change_map: false,
+            auto_switch_minimap: false,
+            itinerary: Itinerary::default(),
             notifications: Notifications::default(),
             familiars: Familiars::default(),
             toggle_actions_key: toggle_actions_key_default(),
@@ -234,7 +1386,9 @@ impl Default for Settings {
     }
 }
 
-impl_identifiable!(Settings);
+fn setup_completed_default() -> bool {
+    true
+}
 
 fn capture_x_default() -> i32 {
     0
@@ -244,6 +1398,14 @@ fn capture_y_default() -> i32 {
     0
 }
 
+fn capture_width_default() -> i32 {
+    1366
+}
+
+fn capture_height_default() -> i32 {
+    768
+}
+
 fn enable_rune_solving_default() -> bool {
     true
 }
@@ -252,6 +1414,7 @@ fn toggle_actions_key_default() -> KeyBindingConfiguration {
     KeyBindingConfiguration {
         key: KeyBinding::Comma,
         enabled: false,
+        ..KeyBindingConfiguration::default()
     }
 }
 
@@ -259,6 +1422,7 @@ fn platform_start_key_default() -> KeyBindingConfiguration {
     KeyBindingConfiguration {
         key: KeyBinding::J,
         enabled: true,
+        ..KeyBindingConfiguration::default()
     }
 }
 
@@ -266,6 +1430,7 @@ fn platform_end_key_default() -> KeyBindingConfiguration {
     KeyBindingConfiguration {
         key: KeyBinding::K,
         enabled: true,
+        ..KeyBindingConfiguration::default()
     }
 }
 
@@ -273,6 +1438,7 @@ fn platform_add_key_default() -> KeyBindingConfiguration {
     KeyBindingConfiguration {
         key: KeyBinding::L,
         enabled: true,
+        ..KeyBindingConfiguration::default()
     }
 }
 
@@ -287,6 +1453,30 @@ pub enum CaptureMode {
     BitBltArea,
 }
 
+/// Whether HDR frames are requested and tone-mapped to 8-bit before detection.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum CaptureHdrMode {
+    /// Tone-maps only when the game window is on an HDR monitor.
+    #[default]
+    Auto,
+    /// Lets Windows convert HDR frames to 8-bit.
+    Off,
+    /// Always requests HDR frames and tone-maps them.
+    On,
+}
+
+/// Identifies a capture window across game restarts in which its handle changes.
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct CaptureWindow {
+    /// Case-insensitive text contained in the window title.
+    pub title: String,
+    /// Case-insensitive executable name of the process owning the window or empty to match any.
+    #[serde(default)]
+    pub process_name: String,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Character {
     #[serde(skip_serializing, default)]
@@ -297,6 +1487,12 @@ pub struct Character {
     #[serde(default = "jump_key_default")]
     pub jump_key: KeyBindingConfiguration,
     pub up_jump_key: Option<KeyBindingConfiguration>,
+    /// The key to start flying or gliding for moving upward.
+    #[serde(default)]
+    pub flight_key: Option<KeyBindingConfiguration>,
+    /// Whether [`Self::flight_key`] is held down while flying instead of pressed once.
+    #[serde(default)]
+    pub hold_flight_key: bool,
     #[serde(default = "key_default")]
     pub interact_key: KeyBindingConfiguration,
     #[serde(default = "key_default")]
@@ -307,12 +1503,27 @@ pub struct Character {
     pub to_town_key: KeyBindingConfiguration,
     #[serde(default = "key_default")]
     pub change_channel_key: KeyBindingConfiguration,
+    #[serde(default = "key_default")]
+    pub pickup_key: KeyBindingConfiguration,
+    #[serde(default = "pickup_ignore_millis_default")]
+    pub pickup_ignore_millis: u64,
     pub feed_pet_key: KeyBindingConfiguration,
     pub feed_pet_millis: u64,
     #[serde(default = "num_pets_default")]
     pub num_pets: u32,
     pub potion_key: KeyBindingConfiguration,
     pub potion_mode: PotionMode,
+    /// Additional health-based potions in order of priority.
+    ///
+    /// These are always checked regardless of [`Self::potion_mode`] and take priority over the potion
+    /// keys that use [`PotionMode::Percentage`].
+    #[serde(default)]
+    pub potions: Vec<Potion>,
+    /// A separate potion key for restoring MP.
+    #[serde(default)]
+    pub mp_potion_key: KeyBindingConfiguration,
+    #[serde(default = "mp_potion_mode_default")]
+    pub mp_potion_mode: PotionMode,
     pub health_update_millis: u64,
     pub familiar_buff_key: KeyBindingConfiguration,
     #[serde(default = "key_default")]
@@ -329,8 +1540,17 @@ pub struct Character {
     pub extreme_blue_potion_key: KeyBindingConfiguration,
     pub extreme_green_potion_key: KeyBindingConfiguration,
     pub extreme_gold_potion_key: KeyBindingConfiguration,
+    /// Per-key press duration and repeat interval overrides.
+    ///
+    /// These apply to every key press of the same key including skills in actions.
+    #[serde(default)]
+    pub key_timings: Vec<KeyBindingConfiguration>,
     pub class: Class,
     pub disable_adjusting: bool,
+    /// Whether to route to the platform edge instead of jumping down through a platform in
+    /// platforms pathing.
+    #[serde(default)]
+    pub disable_jump_down: bool,
     pub actions: Vec<ActionConfiguration>,
     #[serde(default)]
     pub elite_boss_behavior_enabled: bool,
@@ -338,17 +1558,71 @@ pub struct Character {
     pub elite_boss_behavior: EliteBossBehavior,
     #[serde(default)]
     pub elite_boss_behavior_key: KeyBinding,
+    #[serde(default)]
+    pub inventory_full_behavior_enabled: bool,
+    #[serde(default)]
+    pub inventory_full_behavior: InventoryFullBehavior,
+    /// The script path to run when [`InventoryFullBehavior::RunScript`] is used.
+    #[serde(default)]
+    pub inventory_full_script: String,
+    #[serde(default)]
+    pub sell_items: SellItems,
+    #[serde(default)]
+    pub burst_window: BurstWindow,
+    #[serde(default)]
+    pub shutdown_routine: ShutdownRoutine,
+    #[serde(default)]
+    pub combos: Vec<ComboAction>,
+    /// The id of the [`Minimap`] to load when this character is selected.
+    #[serde(default)]
+    pub minimap_id: Option<i64>,
+    /// The preset of [`Self::minimap_id`] to load when this character is selected.
+    #[serde(default)]
+    pub minimap_preset: Option<String>,
+    #[serde(default)]
+    pub loadouts: Vec<Loadout>,
+    /// The name of the active [`Loadout`] or [`None`] to use the base configuration.
+    #[serde(default)]
+    pub loadout: Option<String>,
+}
+
+impl Character {
+    pub fn active_loadout(&self) -> Option<&Loadout> {
+        let name = self.loadout.as_ref()?;
+        self.loadouts.iter().find(|loadout| &loadout.name == name)
+    }
+
+    /// The actions of the active [`Loadout`] or [`Self::actions`] if there is none.
+    pub fn active_actions(&self) -> &[ActionConfiguration] {
+        self.active_loadout()
+            .map_or(&self.actions, |loadout| &loadout.actions)
+    }
+
+    /// Whether `kind` is disabled by the active [`Loadout`].
+    pub fn is_loadout_buff_disabled(&self, kind: BuffKind) -> bool {
+        self.active_loadout()
+            .is_some_and(|loadout| loadout.disabled_buffs.contains(&kind))
+    }
 }
 
 fn num_pets_default() -> u32 {
     3
 }
 
+fn pickup_ignore_millis_default() -> u64 {
+    10000
+}
+
+fn mp_potion_mode_default() -> PotionMode {
+    PotionMode::MpPercentage(30.0)
+}
+
 fn jump_key_default() -> KeyBindingConfiguration {
     // Enabled is not neccessary but for semantic purpose
     KeyBindingConfiguration {
         key: KeyBinding::Space,
         enabled: true,
+        ..KeyBindingConfiguration::default()
     }
 }
 
@@ -357,6 +1631,7 @@ fn key_default() -> KeyBindingConfiguration {
     KeyBindingConfiguration {
         key: KeyBinding::default(),
         enabled: true,
+        ..KeyBindingConfiguration::default()
     }
 }
 
@@ -369,16 +1644,23 @@ impl Default for Character {
             teleport_key: None,
             jump_key: jump_key_default(),
             up_jump_key: None,
+            flight_key: None,
+            hold_flight_key: false,
             interact_key: key_default(),
             cash_shop_key: key_default(),
             familiar_menu_key: key_default(),
             to_town_key: key_default(),
             change_channel_key: key_default(),
+            pickup_key: key_default(),
+            pickup_ignore_millis: pickup_ignore_millis_default(),
             feed_pet_key: KeyBindingConfiguration::default(),
             feed_pet_millis: 320000,
             num_pets: num_pets_default(),
             potion_key: KeyBindingConfiguration::default(),
             potion_mode: PotionMode::EveryMillis(180000),
+            potions: vec![],
+            mp_potion_key: KeyBindingConfiguration::default(),
+            mp_potion_mode: mp_potion_mode_default(),
             health_update_millis: 1000,
             familiar_buff_key: KeyBindingConfiguration::default(),
             familiar_essence_key: key_default(),
@@ -394,20 +1676,62 @@ impl Default for Character {
             extreme_blue_potion_key: KeyBindingConfiguration::default(),
             extreme_green_potion_key: KeyBindingConfiguration::default(),
             extreme_gold_potion_key: KeyBindingConfiguration::default(),
+            key_timings: vec![],
             class: Class::default(),
             disable_adjusting: false,
+            disable_jump_down: false,
             actions: vec![],
             elite_boss_behavior_enabled: false,
             elite_boss_behavior_key: KeyBinding::default(),
             elite_boss_behavior: EliteBossBehavior::default(),
+            inventory_full_behavior_enabled: false,
+            inventory_full_behavior: InventoryFullBehavior::default(),
+            inventory_full_script: String::new(),
+            sell_items: SellItems::default(),
+            burst_window: BurstWindow::default(),
+            shutdown_routine: ShutdownRoutine::default(),
+            combos: vec![],
+            minimap_id: None,
+            minimap_preset: None,
+            loadouts: vec![],
+            loadout: None,
         }
     }
 }
 
+/// A named set of actions and buffs switchable within the same [`Character`].
+///
+/// This allows, for example, bossing and farming skill rotations without duplicating the whole
+/// character.
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct Loadout {
+    /// The identifier referenced by [`ActionCondition::LoadoutActive`].
+    pub id: u32,
+    pub name: String,
+    /// The actions used in place of [`Character::actions`] while this loadout is active.
+    #[serde(default)]
+    pub actions: Vec<ActionConfiguration>,
+    /// The buffs not used while this loadout is active.
+    #[serde(default)]
+    pub disabled_buffs: Vec<BuffKind>,
+}
+
+/// A health-based potion with its own threshold and cooldown.
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct Potion {
+    pub key: KeyBinding,
+    pub enabled: bool,
+    /// Uses the potion when health is at or below this percentage.
+    pub below_percent: f32,
+    /// Milliseconds before the potion can be used again.
+    pub cooldown_millis: u64,
+}
+
 #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString)]
 pub enum PotionMode {
     EveryMillis(u64),
     Percentage(f32),
+    MpPercentage(f32),
 }
 
 impl Default for PotionMode {
@@ -428,7 +1752,7 @@ impl Default for ActionConfigurationCondition {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct ActionConfiguration {
     pub key: KeyBinding,
     pub link_key: Option<LinkKeyBinding>,
@@ -440,6 +1764,14 @@ pub struct ActionConfiguration {
     pub wait_after_millis: u64,
     pub wait_after_millis_random_range: u64,
     pub enabled: bool,
+    /// Whether this action and its linked actions are used in the character's [`BurstWindow`].
+    #[serde(default)]
+    pub burst: bool,
+    /// The ids of the only [`Minimap`]s this action and its linked actions are used on.
+    ///
+    /// The action is used on every minimap if empty.
+    #[serde(default)]
+    pub minimap_ids: Vec<i64>,
 }
 
 impl Default for ActionConfiguration {
@@ -456,6 +1788,8 @@ impl Default for ActionConfiguration {
             wait_after_millis: 500,
             wait_after_millis_random_range: 0,
             enabled: false,
+            burst: false,
+            minimap_ids: Vec::new(),
         }
     }
 }
@@ -480,14 +1814,58 @@ impl From<ActionConfiguration> for Action {
             wait_before_use_millis_random_range: value.wait_before_millis_random_range,
             wait_after_use_millis: value.wait_after_millis,
             wait_after_use_millis_random_range: value.wait_after_millis_random_range,
+            verify: None,
+            ..ActionKey::default()
         })
     }
 }
 
+/// An ordered sequence of keys used together without being interrupted by other actions.
+///
+/// Unlike [`LinkKeyBinding`], each step can have its own delay and facing direction.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ComboAction {
+    pub name: String,
+    pub enabled: bool,
+    pub every_millis: u64,
+    pub steps: Vec<ComboStep>,
+}
+
+impl Default for ComboAction {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            enabled: false,
+            every_millis: 120000,
+            steps: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct ComboStep {
+    pub key: KeyBinding,
+    /// The direction to face before sending [`Self::key`].
+    pub direction: ActionKeyDirection,
+    /// The number of ticks to wait after the previous step or after the combo started.
+    pub delay_ticks: u32,
+}
+
 #[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
 pub struct KeyBindingConfiguration {
     pub key: KeyBinding,
     pub enabled: bool,
+    /// How long the key is held down for each press instead of the random input delay.
+    ///
+    /// Used for skills that need to be charged.
+    #[serde(default)]
+    pub press_millis: Option<u64>,
+    /// The maximum milliseconds between two presses when the key is sent repeatedly.
+    ///
+    /// The key press duration is shortened to fit this interval so that the key can be
+    /// pressed again sooner.
+    #[serde(default)]
+    pub repeat_millis: Option<u64>,
 }
 
 #[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
@@ -555,11 +1933,60 @@ pub enum RotationMode {
     StartToEnd,
     #[default]
     StartToEndThenReverse,
+    /// Rotates normal actions in a random order that is reshuffled each cycle.
+    Shuffled,
     AutoMobbing,
     PingPong,
 }
 
-impl_identifiable!(Character);
+/// A named bound of [`RotationMode::AutoMobbing`].
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct AutoMobRegion {
+    pub name: String,
+    pub bound: Bound,
+    /// The relative chance of picking this region with [`AutoMobRegionOrder::Weighted`].
+    pub weight: u32,
+}
+
+/// How [`RotationMode::AutoMobbing`] moves between [`Minimap::rotation_auto_mob_regions`].
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum AutoMobRegionOrder {
+    /// Visits the regions in order.
+    #[default]
+    Cycle,
+    /// Picks the next region randomly by [`AutoMobRegion::weight`].
+    Weighted,
+}
+
+/// A vertical band inside the [`RotationMode::PingPong`] bound.
+///
+/// The y values are in player coordinate similar to [`Platform::y`].
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct PingPongBand {
+    pub y_start: i32,
+    /// The exclusive top y of the band.
+    pub y_end: i32,
+    /// The percentage chance of staying inside this band on a pass.
+    pub probability: u32,
+}
+
+/// How [`RotationMode::AutoMobbing`] picks the next position to mob.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum AutoMobStrategy {
+    /// Mobs inside one quadrant of the bound at a time and advances clockwise.
+    #[default]
+    RotateQuadrants,
+    /// Mobs the cluster with the most mobs anywhere inside the bound.
+    Densest,
+    /// Mobs the nearest mob anywhere inside the bound.
+    Nearest,
+    /// Mobs inside each of [`Minimap::rotation_auto_mob_sequence`] in order.
+    Sequence,
+}
 
 #[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Minimap {
@@ -572,21 +1999,169 @@ pub struct Minimap {
     pub rotation_mode: RotationMode,
     #[serde(default)]
     pub rotation_ping_pong_bound: Bound,
+    /// The vertical bands [`RotationMode::PingPong`] may stay inside on each pass.
+    #[serde(default)]
+    pub rotation_ping_pong_bands: Vec<PingPongBand>,
     #[serde(default)]
     pub rotation_auto_mob_bound: Bound,
+    #[serde(default, deserialize_with = "deserialize_with_ok_or_default")]
+    pub rotation_auto_mob_strategy: AutoMobStrategy,
+    /// The sub-bounds visited in order by [`AutoMobStrategy::Sequence`].
+    #[serde(default)]
+    pub rotation_auto_mob_sequence: Vec<Bound>,
+    /// The regions used in place of [`Self::rotation_auto_mob_bound`] when not empty.
+    #[serde(default)]
+    pub rotation_auto_mob_regions: Vec<AutoMobRegion>,
+    #[serde(default, deserialize_with = "deserialize_with_ok_or_default")]
+    pub rotation_auto_mob_region_order: AutoMobRegionOrder,
     #[serde(default)]
     pub rotation_mobbing_key: MobbingKey,
     pub platforms: Vec<Platform>,
+    /// The ropes and ladders used by platforms pathing.
+    #[serde(default)]
+    pub ropes: Vec<Rope>,
     pub rune_platforms_pathing: bool,
     pub rune_platforms_pathing_up_jump_only: bool,
     pub auto_mob_platforms_pathing: bool,
     pub auto_mob_platforms_pathing_up_jump_only: bool,
     pub auto_mob_platforms_bound: bool,
+    #[serde(default)]
+    pub auto_mob_pickup_drops: bool,
     pub actions_any_reset_on_erda_condition: bool,
     pub actions: HashMap<String, Vec<Action>>,
+    #[serde(default)]
+    pub preset_schedule: PresetSchedule,
+    /// The preset used while fighting an elite boss with [`EliteBossBehavior::Fight`].
+    #[serde(default)]
+    pub elite_boss_fight_preset: Option<String>,
+    /// A downscaled grayscale image of the minimap taken when it was created.
+    ///
+    /// This is used to match the detected minimap against stored minimaps.
+    #[serde(default)]
+    pub fingerprint: Vec<u8>,
+    /// The id of the [`Character`] to load when this minimap is selected.
+    #[serde(default)]
+    pub character_id: Option<i64>,
+    #[serde(default)]
+    pub movement_overrides: MovementOverrides,
+    /// The position to walk to before taking a [`Breaks`] break.
+    #[serde(default)]
+    pub break_position: Option<Position>,
+    /// The areas platforms pathing and auto-mobbing never move the player through or into.
+    #[serde(default)]
+    pub no_go_zones: Vec<Bound>,
+    /// The capture settings used in place of the [`Settings`] ones while this minimap is selected.
+    #[serde(default)]
+    pub capture_override: Option<CaptureOverride>,
 }
 
-impl_identifiable!(Minimap);
+/// The capture mode and area of a [`Minimap`] played in a different window size or monitor.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct CaptureOverride {
+    pub mode: CaptureMode,
+    /// The capture area of [`CaptureMode::BitBltArea`].
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl CaptureOverride {
+    /// Returns a copy of `settings` with the capture mode and area overridden.
+    pub fn apply(&self, settings: &Settings) -> Settings {
+        Settings {
+            capture_mode: self.mode,
+            capture_x: self.x,
+            capture_y: self.y,
+            capture_width: self.width,
+            capture_height: self.height,
+            ..settings.clone()
+        }
+    }
+}
+
+/// Overrides of the player movement thresholds for maps with unusual platform spacing.
+///
+/// A [`None`] field uses the built-in value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct MovementOverrides {
+    /// The minimum x distance to double jump.
+    pub double_jump_threshold: Option<i32>,
+    /// The minimum y distance to jump.
+    pub jump_threshold: Option<i32>,
+    /// The minimum y distance to grapple.
+    pub grappling_threshold: Option<i32>,
+    /// The maximum y distance that can be grappled.
+    pub grappling_max_threshold: Option<i32>,
+    /// The number of ticks without position change before a movement times out.
+    pub move_timeout: Option<u32>,
+}
+
+/// A position on a minimap learned from the player repeatedly getting stuck nearby.
+///
+/// Platforms near an avoidance zone are routed around by platforms pathing.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct AvoidanceZone {
+    pub id: i64,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Automatically switches between [`Minimap::actions`] presets while rotating.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct PresetSchedule {
+    pub enabled: bool,
+    /// Whether the next entry is chosen randomly by [`PresetScheduleEntry::weight`] instead of
+    /// in order.
+    pub random: bool,
+    pub entries: Vec<PresetScheduleEntry>,
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct PresetScheduleEntry {
+    pub preset: String,
+    /// How long the preset is used while rotating before switching to the next entry.
+    ///
+    /// A duration shorter than a minute is treated as a minute.
+    pub duration_millis: u64,
+    pub weight: u32,
+}
+
+/// Farms multiple minimaps in order, changing maps after each step's duration.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Itinerary {
+    pub enabled: bool,
+    pub steps: Vec<ItineraryStep>,
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct ItineraryStep {
+    /// The id of the [`Minimap`] to farm.
+    pub minimap_id: Option<i64>,
+    /// The preset of the minimap to farm with.
+    pub preset: Option<String>,
+    /// How long the minimap is farmed before leaving for the next step.
+    pub duration_millis: u64,
+    pub exit: ItineraryExit,
+    /// The portal position on the minimap when [`ItineraryExit::Portal`] is used.
+    pub portal: Position,
+    /// The key to press when [`ItineraryExit::Key`] is used.
+    pub key: KeyBinding,
+}
+
+/// How an [`ItineraryStep`] leaves its minimap for the next step.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum ItineraryExit {
+    /// Walks to [`ItineraryStep::portal`] and enters the portal.
+    #[default]
+    Portal,
+    /// Goes to town using the character's to town key.
+    ToTown,
+    /// Presses [`ItineraryStep::key`] such as a teleport item.
+    Key,
+}
 
 fn deserialize_with_ok_or_default<'a, T, D>(deserializer: D) -> Result<T, D::Error>
 where
@@ -597,18 +2172,44 @@ where
     Ok(T::deserialize(value).unwrap_or_default())
 }
 
+/// How a [`Platform`] moves x-wise.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum PlatformMotion {
+    #[default]
+    None,
+    /// The platform moves back and forth between its position and [`Platform::motion_range`]
+    /// away from it.
+    Moving,
+    /// The platform carries the player standing on it by [`Platform::motion_range`] every
+    /// period.
+    Conveyor,
+}
+
 #[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub struct Platform {
     pub x_start: i32,
     pub x_end: i32,
     pub y: i32,
+    #[serde(default)]
+    pub motion: PlatformMotion,
+    /// The period of [`Platform::motion`] in milliseconds.
+    ///
+    /// For [`PlatformMotion::Moving`], this is the duration to move to the other end and back.
+    #[serde(default)]
+    pub motion_period_millis: u64,
+    /// The x range of [`Platform::motion`], negative for moving to the left.
+    #[serde(default)]
+    pub motion_range: i32,
 }
 
-// TODO: Should be part of pathing logics, not here
-impl From<Platform> for pathing::Platform {
-    fn from(value: Platform) -> Self {
-        Self::new(value.x_start..value.x_end, value.y)
-    }
+/// A rope or ladder that can be climbed from `y_start` to `y_end`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct Rope {
+    pub x: i32,
+    pub y_start: i32,
+    pub y_end: i32,
 }
 
 #[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
@@ -624,6 +2225,15 @@ pub struct ActionMove {
     pub position: Position,
     pub condition: ActionCondition,
     pub wait_after_move_millis: u64,
+    /// The number of times this action is retried after failing to reach its position.
+    #[serde(default)]
+    pub max_retries: u32,
+    /// The milliseconds to wait before each retry.
+    #[serde(default)]
+    pub retry_delay_millis: u64,
+    /// What to do after all retries have failed.
+    #[serde(default)]
+    pub on_failure: ActionFailure,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
@@ -641,6 +2251,16 @@ pub struct ActionKey {
     pub wait_after_use_millis: u64,
     pub wait_after_use_millis_random_range: u64,
     pub queue_to_front: Option<bool>,
+    pub verify: Option<ActionKeyVerify>,
+    /// The number of times this action is retried after failing to reach its position.
+    #[serde(default)]
+    pub max_retries: u32,
+    /// The milliseconds to wait before each retry.
+    #[serde(default)]
+    pub retry_delay_millis: u64,
+    /// What to do after all retries have failed.
+    #[serde(default)]
+    pub on_failure: ActionFailure,
 }
 
 impl Default for ActionKey {
@@ -658,10 +2278,71 @@ impl Default for ActionKey {
             wait_after_use_millis: 0,
             wait_after_use_millis_random_range: 0,
             queue_to_front: None,
+            verify: None,
+            max_retries: 0,
+            retry_delay_millis: 0,
+            on_failure: ActionFailure::default(),
         }
     }
 }
 
+/// A mouse input at a fixed point for interacting with the game UI such as NPC dialogs.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ActionMouse {
+    pub x: i32,
+    pub y: i32,
+    pub action: ActionMouseKind,
+    pub relative_to: ActionMouseRelativeTo,
+    pub condition: ActionCondition,
+    pub wait_after_use_millis: u64,
+}
+
+#[derive(
+    Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum ActionMouseKind {
+    #[default]
+    Click,
+    DoubleClick,
+    /// Scrolls down once.
+    Scroll,
+}
+
+/// The origin of [`ActionMouse::x`] and [`ActionMouse::y`].
+#[derive(
+    Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum ActionMouseRelativeTo {
+    /// The top-left of the game window or capture area.
+    #[default]
+    Window,
+    /// The top-left of the detected minimap.
+    Minimap,
+}
+
+/// What to do after an action has failed more than its `max_retries`.
+#[derive(
+    Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum ActionFailure {
+    /// Skips to the next action.
+    #[default]
+    Skip,
+    /// Restarts the normal actions rotation from the start.
+    AbortCycle,
+    /// Skips to the next action and sends a notification.
+    Notify,
+}
+
+/// Verifies that using an [`ActionKey`] succeeded by checking that a screen region changed.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ActionKeyVerify {
+    /// The screen region compared before and after using the key.
+    pub region: Bound,
+    /// The number of times the key is re-used if the region did not change.
+    pub retry_count: u32,
+}
+
 #[derive(Clone, Copy, Display, EnumString, EnumIter, PartialEq, Debug, Serialize, Deserialize)]
 pub enum LinkKeyBinding {
     Before(KeyBinding),
@@ -715,6 +2396,8 @@ pub enum Class {
 pub enum Action {
     Move(ActionMove),
     Key(ActionKey),
+    Group(ActionGroup),
+    Mouse(ActionMouse),
 }
 
 impl Action {
@@ -722,6 +2405,8 @@ impl Action {
         match self {
             Action::Move(action) => action.condition,
             Action::Key(action) => action.condition,
+            Action::Group(action) => action.condition,
+            Action::Mouse(action) => action.condition,
         }
     }
 
@@ -735,10 +2420,43 @@ impl Action {
                 condition,
                 ..*action
             }),
+            Action::Group(action) => Action::Group(ActionGroup {
+                condition,
+                ..*action
+            }),
+            Action::Mouse(action) => Action::Mouse(ActionMouse {
+                condition,
+                ..*action
+            }),
         }
     }
 }
 
+/// An action that branches into the [`ActionCondition::Linked`] actions following it.
+///
+/// The first [`Self::then_count`] linked actions are executed if [`Self::predicate`] holds at the
+/// time this action is rotated. Otherwise, the remaining linked actions are executed.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ActionGroup {
+    pub predicate: ActionGroupPredicate,
+    pub condition: ActionCondition,
+    pub then_count: u32,
+}
+
+#[derive(
+    Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum ActionGroupPredicate {
+    /// Current health is at or below the percentage of max health.
+    HealthBelowPercent(u32),
+    BuffPresent(BuffKind),
+    /// A rune is present on the minimap.
+    #[default]
+    RuneActive,
+    /// The player is inside the bound relative to the minimap top-left.
+    PlayerInRegion(Bound),
+}
+
 #[derive(
     Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
 )]
@@ -748,6 +2466,8 @@ pub enum ActionCondition {
     EveryMillis(u64),
     ErdaShowerOffCooldown,
     Linked,
+    /// A normal action only used while the character [`Loadout`] with the id is active.
+    LoadoutActive(u32),
 }
 
 #[derive(
@@ -1001,6 +2721,36 @@ impl From<KeyKind> for KeyBinding {
     }
 }
 
+// TODO: Should be part of pathing logics, not here
+impl From<Platform> for pathing::Platform {
+    fn from(value: Platform) -> Self {
+        let period = (value.motion_period_millis / ms_per_tick()).max(1) as u32;
+        let range = value.motion_range;
+        match value.motion {
+            PlatformMotion::None => Self::new(value.x_start..value.x_end, value.y),
+            // Only the x range covered at every phase can be landed on safely
+            PlatformMotion::Moving => Self::new(
+                value.x_start + range.max(0)..value.x_end + range.min(0),
+                value.y,
+            )
+            .with_motion(pathing::PlatformMotion::Moving { period, range }),
+            PlatformMotion::Conveyor => Self::new(value.x_start..value.x_end, value.y)
+                .with_motion(pathing::PlatformMotion::Conveyor { period, range }),
+        }
+    }
+}
+
+impl From<Rope> for pathing::Rope {
+    fn from(value: Rope) -> Self {
+        let (y_min, y_max) = if value.y_start <= value.y_end {
+            (value.y_start, value.y_end)
+        } else {
+            (value.y_end, value.y_start)
+        };
+        Self::new(value.x, y_min..y_max + 1)
+    }
+}
+
 pub fn query_seeds() -> Seeds {
     let mut seeds = query_from_table::<Seeds>("seeds")
         .unwrap()
@@ -1013,22 +2763,46 @@ pub fn query_seeds() -> Seeds {
     seeds
 }
 
-pub fn query_settings() -> Settings {
-    let mut settings = query_from_table::<Settings>("settings")
-        .unwrap()
+pub fn query_settings() -> Result<Settings> {
+    let mut settings = query_from_table::<Settings>("settings")?
         .into_iter()
         .next()
         .unwrap_or_default();
     if settings.id.is_none() {
-        upsert_settings(&mut settings).unwrap();
+        if let Some(capture_mode) = config().capture_mode {
+            settings.capture_mode = capture_mode;
+        }
+        upsert_settings(&mut settings)?;
     }
-    settings
+    if settings.enable_panic_mode {
+        // Panic mode changed channel when a stranger is detected like the default playbook
+        settings.enable_panic_mode = false;
+        settings.panic_playbooks.stranger_detected.enabled = true;
+        upsert_settings(&mut settings)?;
+    }
+    Ok(settings)
 }
 
 pub fn upsert_settings(settings: &mut Settings) -> Result<()> {
     upsert_to_table("settings", settings)
 }
 
+pub fn query_session() -> Result<Session> {
+    Ok(query_from_table::<Session>("sessions")?
+        .into_iter()
+        .next()
+        .unwrap_or_default())
+}
+
+pub fn upsert_session(session: &mut Session) -> Result<()> {
+    upsert_to_table("sessions", session)
+}
+
+/// Updates the already inserted `session` without waiting.
+pub fn spawn_update_session(session: &Session) {
+    spawn_update_to_table("sessions", session);
+}
+
 pub fn query_characters() -> Result<Vec<Character>> {
     query_from_table("characters")
 }
@@ -1055,60 +2829,277 @@ pub fn delete_minimap(map: &Minimap) -> Result<()> {
     delete_from_table("maps", map)
 }
 
+/// Inserts `event` emitted at `timestamp_millis` without waiting and passes its id to `on_insert`.
+///
+/// Only the latest [`EVENTS_MAX_ROWS`] events are kept.
+pub fn insert_event(
+    timestamp_millis: u64,
+    event: &Event,
+    on_insert: impl FnOnce(Result<i64>) + Send + 'static,
+) {
+    let json = serde_json::to_string(event);
+    spawn_with_connection(move |conn| {
+        let result = json.map_err(anyhow::Error::from).and_then(|json| {
+            conn.execute(
+                "INSERT INTO events (timestamp, data) VALUES (?1, ?2);",
+                (timestamp_millis as i64, encrypt_data(json)?),
+            )?;
+            let id = conn.last_insert_rowid();
+            conn.execute("DELETE FROM events WHERE id <= ?1;", [id - EVENTS_MAX_ROWS])?;
+            Ok(id)
+        });
+        on_insert(result);
+    });
+}
+
+/// Queries the latest `limit` events in chronological order.
+pub fn query_events(limit: u32) -> Result<Vec<EventRecord>> {
+    with_connection(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, data FROM \
+            (SELECT * FROM events ORDER BY id DESC LIMIT ?1) ORDER BY id;",
+        )?;
+        let rows = stmt
+            .query_map([limit], |row| {
+                let id = row.get::<_, i64>(0)?;
+                let timestamp = row.get::<_, i64>(1)?;
+                let data = row.get::<_, String>(2)?;
+                Ok((id, timestamp, data))
+            })?
+            .filter_map(|row| row.ok())
+            .collect::<Vec<_>>();
+        let mut events = Vec::with_capacity(rows.len());
+        for (id, timestamp, data) in rows {
+            let Ok(event) = serde_json::from_str(&decrypt_data(data)?) else {
+                continue;
+            };
+            events.push(EventRecord {
+                id: Some(id),
+                timestamp_millis: timestamp as u64,
+                event,
+            });
+        }
+        Ok(events)
+    })
+}
+
+pub fn query_routines() -> Result<Vec<Routine>> {
+    query_from_table("routines")
+}
+
+pub fn upsert_routine(routine: &mut Routine) -> Result<()> {
+    upsert_to_table("routines", routine)
+}
+
+pub fn delete_routine(routine: &Routine) -> Result<()> {
+    delete_from_table("routines", routine)
+}
+
+/// Queries the Unix timestamp in milliseconds each routine id last ran.
+pub fn query_routine_runs() -> Result<HashMap<i64, u64>> {
+    with_connection(|conn| {
+        let mut stmt = conn.prepare("SELECT routine_id, timestamp FROM routine_runs;")?;
+        Ok(stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .filter_map(|row| row.ok())
+            .collect())
+    })
+}
+
+/// Records the routine with `routine_id` as last ran at `timestamp_millis` without waiting.
+pub fn upsert_routine_run(routine_id: i64, timestamp_millis: u64) {
+    spawn_with_connection(move |conn| {
+        if let Err(err) = conn.execute(
+            "INSERT INTO routine_runs (routine_id, timestamp) VALUES (?1, ?2) \
+            ON CONFLICT (routine_id) DO UPDATE SET timestamp = excluded.timestamp;",
+            (routine_id, timestamp_millis as i64),
+        ) {
+            log::error!("failed to persist routine {routine_id} run {err}");
+        }
+    });
+}
+
+/// Inserts a rune spawn position `(x, y)` observed on the map with `minimap_id` without waiting.
+///
+/// Only the latest [`RUNE_SPAWNS_MAX_ROWS`] spawn positions of each map are kept.
+pub fn insert_rune_spawn(minimap_id: i64, x: i32, y: i32) {
+    spawn_with_connection(move |conn| {
+        let result = conn
+            .execute(
+                "INSERT INTO rune_spawns (minimap_id, x, y) VALUES (?1, ?2, ?3);",
+                (minimap_id, x, y),
+            )
+            .and_then(|_| {
+                conn.execute(
+                    "DELETE FROM rune_spawns WHERE minimap_id = ?1 AND id NOT IN (
+                        SELECT id FROM rune_spawns WHERE minimap_id = ?1 ORDER BY id DESC LIMIT ?2
+                    );",
+                    (minimap_id, RUNE_SPAWNS_MAX_ROWS),
+                )
+            });
+        if let Err(err) = result {
+            log::error!("failed to persist rune spawn {err}");
+        }
+    });
+}
+
+/// Queries the latest `limit` rune spawn positions observed on the map with `minimap_id`.
+pub fn query_rune_spawns(minimap_id: i64, limit: u32) -> Result<Vec<(i32, i32)>> {
+    with_connection(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT x, y FROM rune_spawns WHERE minimap_id = ?1 ORDER BY id DESC LIMIT ?2;",
+        )?;
+        Ok(stmt
+            .query_map((minimap_id, limit), |row| {
+                Ok((row.get::<_, i32>(0)?, row.get::<_, i32>(1)?))
+            })?
+            .filter_map(|row| row.ok())
+            .collect())
+    })
+}
+
+/// Inserts an avoidance zone at `(x, y)` learned on the map with `minimap_id` without waiting.
+pub fn insert_avoidance_zone(minimap_id: i64, x: i32, y: i32) {
+    spawn_with_connection(move |conn| {
+        if let Err(err) = conn.execute(
+            "INSERT INTO avoidance_zones (minimap_id, x, y) VALUES (?1, ?2, ?3);",
+            (minimap_id, x, y),
+        ) {
+            log::error!("failed to persist avoidance zone {err}");
+        }
+    });
+}
+
+/// Queries the avoidance zones learned on the map with `minimap_id`.
+pub fn query_avoidance_zones(minimap_id: i64) -> Result<Vec<AvoidanceZone>> {
+    with_connection(move |conn| {
+        let mut stmt = conn
+            .prepare("SELECT id, x, y FROM avoidance_zones WHERE minimap_id = ?1 ORDER BY id;")?;
+        Ok(stmt
+            .query_map([minimap_id], |row| {
+                Ok(AvoidanceZone {
+                    id: row.get::<_, i64>(0)?,
+                    x: row.get::<_, i32>(1)?,
+                    y: row.get::<_, i32>(2)?,
+                })
+            })?
+            .filter_map(|row| row.ok())
+            .collect())
+    })
+}
+
+/// Deletes the avoidance zone with `id`.
+pub fn delete_avoidance_zone(id: i64) -> Result<()> {
+    with_connection(move |conn| {
+        conn.execute("DELETE FROM avoidance_zones WHERE id = ?1;", [id])?;
+        Ok(())
+    })
+}
+
+/// Deletes all avoidance zones learned on the map with `minimap_id`.
+pub fn delete_avoidance_zones(minimap_id: i64) -> Result<()> {
+    with_connection(move |conn| {
+        conn.execute(
+            "DELETE FROM avoidance_zones WHERE minimap_id = ?1;",
+            [minimap_id],
+        )?;
+        Ok(())
+    })
+}
+
 fn map_data<T>(mut stmt: Statement<'_>, params: impl Params) -> Result<Vec<T>>
 where
     T: DeserializeOwned + Identifiable + Default,
 {
-    Ok(stmt
-        .query_map::<T, _, _>(params, |row| {
-            let id = row.get::<_, i64>(0).unwrap();
-            let data = row.get::<_, String>(1).unwrap();
+    let rows = stmt
+        .query_map(params, |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .filter_map(|c| c.ok())
+        .collect::<Vec<_>>();
+    rows.into_iter()
+        .map(|(id, data)| {
+            // Fails instead of defaulting so that the row is not overwritten when saved again
+            let data = decrypt_data(data)?;
             let mut value = serde_json::from_str::<'_, T>(data.as_str()).unwrap_or_default();
             value.set_id(id);
             Ok(value)
-        })?
-        .filter_map(|c| c.ok())
-        .collect::<Vec<_>>())
+        })
+        .collect()
 }
 
-fn query_from_table<T>(table: &str) -> Result<Vec<T>>
+fn query_from_table<T>(table: &'static str) -> Result<Vec<T>>
 where
-    T: DeserializeOwned + Identifiable + Default,
+    T: DeserializeOwned + Identifiable + Default + Send + 'static,
 {
-    let conn = CONNECTION.lock().unwrap();
-    let stmt = format!("SELECT id, data FROM {table}");
-    let stmt = conn.prepare(&stmt).unwrap();
-    map_data(stmt, [])
+    with_connection(move |conn| {
+        let stmt = format!("SELECT id, data FROM {table}");
+        let stmt = conn.prepare(&stmt).unwrap();
+        map_data(stmt, [])
+    })
 }
 
-fn upsert_to_table<T>(table: &str, data: &mut T) -> Result<()>
+fn upsert_to_table<T>(table: &'static str, data: &mut T) -> Result<()>
 where
     T: Serialize + Identifiable,
 {
     let json = serde_json::to_string(&data).unwrap();
-    let conn = CONNECTION.lock().unwrap();
+    let id = data.id();
     let stmt = format!(
         "INSERT INTO {table} (id, data) VALUES (?1, ?2) ON CONFLICT (id) DO UPDATE SET data = ?2;",
     );
-    match data.id() {
-        Some(id) => {
-            conn.execute(&stmt, (id, &json))?;
-            Ok(())
+    let inserted_id = with_connection(move |conn| -> Result<Option<i64>> {
+        let json = encrypt_data(json)?;
+        match id {
+            Some(id) => {
+                conn.execute(&stmt, (id, &json))?;
+                Ok(None)
+            }
+            None => {
+                conn.execute(&stmt, (Null, &json))?;
+                Ok(Some(conn.last_insert_rowid()))
+            }
         }
-        None => {
-            conn.execute(&stmt, (Null, &json))?;
-            data.set_id(conn.last_insert_rowid());
+    })?;
+    if let Some(id) = inserted_id {
+        data.set_id(id);
+    }
+    Ok(())
+}
+
+/// Updates the already inserted `data` in `table` without waiting.
+fn spawn_update_to_table<T>(table: &'static str, data: &T)
+where
+    T: Serialize + Identifiable,
+{
+    let Some(id) = data.id() else {
+        log::error!("cannot update {table} data without an id");
+        return;
+    };
+    let json = serde_json::to_string(data).unwrap();
+    spawn_with_connection(move |conn| {
+        let result = encrypt_data(json).and_then(|json| {
+            conn.execute(
+                &format!("UPDATE {table} SET data = ?2 WHERE id = ?1;"),
+                (id, json),
+            )?;
             Ok(())
+        });
+        if let Err(err) = result {
+            log::error!("failed to update {table} data {err}");
         }
-    }
+    });
 }
 
-fn delete_from_table<T: Identifiable>(table: &str, data: &T) -> Result<()> {
-    fn inner(table: &str, id: Option<i64>) -> Result<()> {
-        if id.is_some() {
-            let conn = CONNECTION.lock().unwrap();
-            let stmt = format!("DELETE FROM {table} WHERE id = ?1;");
-            conn.execute(&stmt, [id.unwrap()])?;
+fn delete_from_table<T: Identifiable>(table: &'static str, data: &T) -> Result<()> {
+    fn inner(table: &'static str, id: Option<i64>) -> Result<()> {
+        if let Some(id) = id {
+            with_connection(move |conn| {
+                let stmt = format!("DELETE FROM {table} WHERE id = ?1;");
+                conn.execute(&stmt, [id])
+            })?;
         }
         Ok(())
     }
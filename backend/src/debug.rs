@@ -24,6 +24,8 @@ use opencv::{
 };
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 use rand::distr::{Alphanumeric, SampleString};
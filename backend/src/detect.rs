@@ -7,6 +7,7 @@ use std::{
         Arc, LazyLock, Mutex,
         atomic::{AtomicBool, Ordering},
     },
+    time::{Duration, Instant},
 };
 
 use anyhow::{Result, anyhow, bail};
@@ -19,9 +20,9 @@ use opencv::{
     core::{
         BORDER_CONSTANT, CMP_EQ, CMP_GT, CV_8U, CV_32FC3, CV_32S, Mat, MatExprTraitConst, MatTrait,
         MatTraitConst, MatTraitConstManual, ModifyInplace, Point, Point2f, Range, Rect, Scalar,
-        Size, ToInputArray, Vec3b, Vec4b, Vector, add, add_weighted_def, bitwise_and_def, compare,
-        copy_make_border, divide2_def, extract_channel, find_non_zero, min_max_loc, no_array,
-        subtract_def, transpose_nd,
+        Size, ToInputArray, Vec3b, Vec4b, Vector, add, add_weighted_def, bitwise_and_def,
+        bitwise_or_def, compare, copy_make_border, count_non_zero, divide2_def, extract_channel,
+        find_non_zero, in_range, mean_def, min_max_loc, no_array, subtract_def, transpose_nd,
     },
     dnn::{
         ModelTrait, TextRecognitionModel, TextRecognitionModelTrait,
@@ -44,12 +45,22 @@ use ort::{
 };
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
 
 #[cfg(debug_assertions)]
 use crate::debug::{debug_mat, debug_spinning_arrows};
-use crate::{array::Array, buff::BuffKind, mat::OwnedMat};
+use crate::{
+    array::Array,
+    buff::BuffKind,
+    config::{config, model_bytes},
+    mat::OwnedMat,
+    self_test::DetectionModel,
+};
 
 const MAX_ARROWS: usize = 4;
 const MAX_SPIN_ARROWS: usize = 2; // PRAY
@@ -63,6 +74,8 @@ struct SpinArrow {
     region: Rect,
     /// The last arrow head relative to the centroid
     last_arrow_head: Option<Point>,
+    /// The number of frames the spinning arrow has been tracked for
+    tracked_frames: u32,
     final_arrow: Option<KeyKind>,
     #[cfg(debug_assertions)]
     is_spin_testing: bool,
@@ -87,12 +100,55 @@ pub struct ArrowsCalibrating {
 }
 
 impl ArrowsCalibrating {
+    /// Whether the remaining arrows can be re-detected by
+    /// [`Detector::detect_rune_arrows_remaining`] after pressing each key.
+    ///
+    /// Spinning arrows are not detected as arrows so they cannot be told apart from solved ones.
+    #[inline]
+    pub fn is_verifiable(&self) -> bool {
+        self.rune_region.is_some() && self.spin_arrows.is_none()
+    }
+
+    /// Whether the rune is the spinning arrows variant.
+    #[inline]
+    pub fn has_spin_arrows(&self) -> bool {
+        self.spin_arrows.is_some()
+    }
+
+    #[cfg(test)]
+    pub fn set_rune_region(&mut self, region: Rect) {
+        self.rune_region = Some(region);
+    }
+
+    #[cfg(test)]
+    pub fn set_spin_arrows(&mut self) {
+        let arrow = SpinArrow {
+            centroid: Point::default(),
+            region: Rect::default(),
+            last_arrow_head: None,
+            tracked_frames: 0,
+            final_arrow: None,
+            #[cfg(debug_assertions)]
+            is_spin_testing: false,
+        };
+        self.spin_arrows = Some(Array::from_iter([arrow; MAX_SPIN_ARROWS]));
+    }
+
     #[cfg(debug_assertions)]
     pub fn enable_spin_test(&mut self) {
         self.is_spin_testing = true;
     }
 }
 
+/// The result of running a model once through [`check_model`].
+#[derive(Debug, Clone, Copy)]
+pub struct ModelCheck {
+    pub load_time: Duration,
+    pub inference_time: Duration,
+    /// The number of predictions with score above the detection threshold.
+    pub predictions: usize,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum OtherPlayerKind {
     Guildie,
@@ -106,12 +162,87 @@ pub enum FamiliarLevel {
     LevelOther,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum FamiliarRank {
     Rare,
     Epic,
 }
 
+/// A familiar slot read from the familiar setup window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FamiliarSlot {
+    Free,
+    /// The slot is occupied by a familiar with the rank read from its badge color.
+    ///
+    /// The rank is [`None`] if the badge color cannot be recognized.
+    Occupied(Option<FamiliarRank>),
+}
+
+/// Padding around the last known region of [`SearchRegion`] to search within.
+const SEARCH_REGION_PADDING: i32 = 50;
+
+/// Number of consecutive misses inside the padded region before searching the whole frame.
+const SEARCH_REGION_MAX_MISSES: u32 = 2;
+
+/// Caches the last known screen region of a detection so that only a padded region around it
+/// is searched.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchRegion {
+    last: Option<Rect>,
+    misses: u32,
+}
+
+impl SearchRegion {
+    /// The padded region to search within or [`None`] to search the whole frame.
+    pub fn get(&self) -> Option<Rect> {
+        self.last
+            .filter(|_| self.misses < SEARCH_REGION_MAX_MISSES)
+            .map(|region| {
+                Rect::new(
+                    region.x - SEARCH_REGION_PADDING,
+                    region.y - SEARCH_REGION_PADDING,
+                    region.width + SEARCH_REGION_PADDING * 2,
+                    region.height + SEARCH_REGION_PADDING * 2,
+                )
+            })
+    }
+
+    /// The last detected region regardless of the misses.
+    #[inline]
+    pub fn last(&self) -> Option<Rect> {
+        self.last
+    }
+
+    /// Updates with the detected region of a search within [`Self::get`].
+    pub fn update(&mut self, detected: Option<Rect>) {
+        match detected {
+            Some(region) => {
+                self.last = Some(region);
+                self.misses = 0;
+            }
+            None => self.misses = self.misses.saturating_add(1),
+        }
+    }
+}
+
+/// The level and points of a familiar read from its hovering tooltip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FamiliarStats {
+    pub level: u32,
+    pub points: u32,
+}
+
+/// The latest template match scores of the tunable detections.
+///
+/// A score is recorded whether the match passes its threshold or not and is [`None`] if never
+/// detected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct DetectionScores {
+    pub player: Option<f32>,
+    /// The lower score of both ends of the health bar.
+    pub health_bar: Option<f32>,
+}
+
 pub trait Detector: 'static + Send + DynClone + Debug {
     fn mat(&self) -> &OwnedMat;
 
@@ -120,6 +251,11 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Returns a list of mobs coordinate relative to minimap coordinate.
     fn detect_mobs(&self, minimap: Rect, bound: Rect, player: Point) -> Result<Vec<Point>>;
 
+    /// Detects a list of item drops near the player.
+    ///
+    /// Returns a list of drops coordinate relative to minimap coordinate.
+    fn detect_drops(&self, minimap: Rect, bound: Rect, player: Point) -> Result<Vec<Point>>;
+
     /// Detects whether to press ESC for unstucking.
     fn detect_esc_settings(&self) -> bool;
 
@@ -132,11 +268,14 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Detects whether there is an elite boss bar.
     fn detect_elite_boss_bar(&self) -> bool;
 
-    /// Detects the minimap.
+    /// Detects the minimap inside `region` or the whole frame if [`None`].
+    ///
+    /// The `region` is a [`SearchRegion::get`] around the last detected minimap. The whole frame
+    /// is searched if the minimap is no longer there.
     ///
     /// The `border_threshold` determines the "whiteness" (grayscale value from 0..255) of
     /// the minimap's white border.
-    fn detect_minimap(&self, border_threshold: u8) -> Result<Rect>;
+    fn detect_minimap(&self, region: Option<Rect>, border_threshold: u8) -> Result<Rect>;
 
     /// Detects the portals from the given `minimap` rectangle.
     ///
@@ -148,13 +287,23 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Returns `Rect` relative to `minimap` coordinate.
     fn detect_minimap_rune(&self, minimap: Rect) -> Result<Rect>;
 
-    /// Detects the player in the provided `minimap` rectangle.
+    /// Detects the elite boss marker in the provided `minimap` rectangle.
     ///
     /// Returns `Rect` relative to `minimap` coordinate.
-    fn detect_player(&self, minimap: Rect) -> Result<Rect>;
+    fn detect_minimap_elite_boss(&self, minimap: Rect) -> Result<Rect>;
+
+    /// Detects the player in the provided `minimap` rectangle.
+    ///
+    /// The `score_threshold` is the minimum template match score from 0 to 1.
+    ///
+    /// Returns `Rect` relative to `minimap` coordinate along with the match score even if below
+    /// `score_threshold`.
+    fn detect_player(&self, minimap: Rect, score_threshold: f32) -> (Result<Rect>, Option<f32>);
 
-    /// Detects whether a player of `kind` is in the minimap.
-    fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> bool;
+    /// Detects players of `kind` in the provided `minimap` rectangle.
+    ///
+    /// Returns `Rect`s relative to `minimap` coordinate.
+    fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> Vec<Rect>;
 
     /// Detects whether the player is dead.
     fn detect_player_is_dead(&self) -> bool;
@@ -162,8 +311,15 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Detects whether the player is in cash shop.
     fn detect_player_in_cash_shop(&self) -> bool;
 
-    /// Detects the player health bar.
-    fn detect_player_health_bar(&self) -> Result<Rect>;
+    /// Detects the player health bar inside `region` or the whole frame if [`None`].
+    ///
+    /// The `score_threshold` is the minimum template match score from 0 to 1. The lower match
+    /// score of both ends is also returned even if below `score_threshold`.
+    fn detect_player_health_bar(
+        &self,
+        region: Option<Rect>,
+        score_threshold: f32,
+    ) -> (Result<Rect>, Option<f32>);
 
     /// Detects the player current and max health bars.
     fn detect_player_current_max_health_bars(&self, health_bar: Rect) -> Result<(Rect, Rect)>;
@@ -171,15 +327,44 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Detects the player current health and max health.
     fn detect_player_health(&self, current_bar: Rect, max_bar: Rect) -> Result<(u32, u32)>;
 
+    /// Detects the player health ratio from 0 to 1 by the filled width of the `health_bar` gauge.
+    ///
+    /// A gauge pixel has its HSV within `color_lower` and `color_upper`.
+    fn detect_player_health_ratio(
+        &self,
+        health_bar: Rect,
+        color_lower: [u8; 3],
+        color_upper: [u8; 3],
+    ) -> Result<f32>;
+
+    /// Detects the player current and max mana bars.
+    ///
+    /// The mana bar is located using the previously detected `health_bar`.
+    fn detect_player_current_max_mana_bars(&self, health_bar: Rect) -> Result<(Rect, Rect)>;
+
+    /// Detects the player current mana and max mana.
+    fn detect_player_mana(&self, current_bar: Rect, max_bar: Rect) -> Result<(u32, u32)>;
+
     /// Detects whether the player has a buff specified by `kind`.
     fn detect_player_buff(&self, kind: BuffKind) -> bool;
 
+    /// Detects the player current EXP points and percentage from the EXP bar.
+    fn detect_player_exp(&self) -> Result<(u64, f32)>;
+
+    /// Detects the meso count shown inside the screen `region`.
+    fn detect_player_mesos(&self, region: Rect) -> Result<u64>;
+
     /// Detects arrows from the given RGBA `Mat` image.
     ///
     /// `calibrating` represents the previous calibrating state returned by
     /// [`ArrowsState::Calibrating`]
     fn detect_rune_arrows(&self, calibrating: ArrowsCalibrating) -> Result<ArrowsState>;
 
+    /// Detects the arrows that are still highlighted inside the rune region of `calibrating`.
+    ///
+    /// The arrows are ordered from left to right and solved arrows are not included.
+    fn detect_rune_arrows_remaining(&self, calibrating: ArrowsCalibrating) -> Result<Vec<KeyKind>>;
+
     /// Detects the Erda Shower skill from the given BGRA `Mat` image.
     fn detect_erda_shower(&self) -> Result<Rect>;
 
@@ -200,9 +385,16 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Detects whether the familiar slot is free.
     fn detect_familiar_slot_is_free(&self, slot: Rect) -> bool;
 
+    /// Detects the familiar slots with the rank of each slotted familiar assuming the familiar
+    /// menu setup opened.
+    fn detect_familiar_setup(&self) -> Vec<FamiliarSlot>;
+
     /// Detects the currently mouse hovering familiar level.
     fn detect_familiar_hover_level(&self) -> Result<FamiliarLevel>;
 
+    /// Detects the currently mouse hovering familiar level and points through OCR.
+    fn detect_familiar_hover_stats(&self) -> Result<FamiliarStats>;
+
     /// Detects all the familiar cards assuming the familiar menu opened.
     fn detect_familiar_cards(&self) -> Vec<(Rect, FamiliarRank)>;
 
@@ -217,6 +409,20 @@ pub trait Detector: 'static + Send + DynClone + Debug {
 
     /// Detects whether the change channel menu is opened.
     fn detect_change_channel_menu_opened(&self) -> bool;
+
+    /// Detects whether the inventory full notice is shown.
+    fn detect_inventory_full(&self) -> bool;
+
+    /// Detects the text lines inside the chat box `region`.
+    ///
+    /// Lines are ordered from top to bottom.
+    fn detect_chat_lines(&self, region: Rect) -> Vec<String>;
+
+    /// Detects the mean BGR color of the screen `region`.
+    fn detect_region_mean_color(&self, region: Rect) -> Result<[f64; 3]>;
+
+    /// Detects the digit keys of the on-screen PIC keypad inside `region`.
+    fn detect_pic_keypad(&self, region: Rect) -> Vec<(Rect, u8)>;
 }
 
 #[cfg(test)]
@@ -226,37 +432,63 @@ mock! {
     impl Detector for Detector {
         fn mat(&self) -> &OwnedMat;
         fn detect_mobs(&self, minimap: Rect, bound: Rect, player: Point) -> Result<Vec<Point>>;
+        fn detect_drops(&self, minimap: Rect, bound: Rect, player: Point) -> Result<Vec<Point>>;
         fn detect_esc_settings(&self) -> bool;
         fn detect_esc_confirm_button(&self) -> Result<Rect>;
         fn detect_tomb_ok_button(&self) -> Result<Rect>;
         fn detect_elite_boss_bar(&self) -> bool;
-        fn detect_minimap(&self, border_threshold: u8) -> Result<Rect>;
+        fn detect_minimap(&self, region: Option<Rect>, border_threshold: u8) -> Result<Rect>;
         fn detect_minimap_portals(&self, minimap: Rect) -> Vec<Rect>;
         fn detect_minimap_rune(&self, minimap: Rect) -> Result<Rect>;
-        fn detect_player(&self, minimap: Rect) -> Result<Rect>;
-        fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> bool;
+        fn detect_minimap_elite_boss(&self, minimap: Rect) -> Result<Rect>;
+        fn detect_player(&self, minimap: Rect, score_threshold: f32) -> (Result<Rect>, Option<f32>);
+        fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> Vec<Rect>;
         fn detect_player_is_dead(&self) -> bool;
         fn detect_player_in_cash_shop(&self) -> bool;
-        fn detect_player_health_bar(&self) -> Result<Rect>;
+        fn detect_player_health_bar(
+            &self,
+            region: Option<Rect>,
+            score_threshold: f32,
+        ) -> (Result<Rect>, Option<f32>);
         fn detect_player_current_max_health_bars(&self, health_bar: Rect) -> Result<(Rect, Rect)>;
         fn detect_player_health(&self, current_bar: Rect, max_bar: Rect) -> Result<(u32, u32)>;
+        fn detect_player_health_ratio(
+            &self,
+            health_bar: Rect,
+            color_lower: [u8; 3],
+            color_upper: [u8; 3],
+        ) -> Result<f32>;
+        fn detect_player_current_max_mana_bars(&self, health_bar: Rect) -> Result<(Rect, Rect)>;
+        fn detect_player_mana(&self, current_bar: Rect, max_bar: Rect) -> Result<(u32, u32)>;
         fn detect_player_buff(&self, kind: BuffKind) -> bool;
+        fn detect_player_exp(&self) -> Result<(u64, f32)>;
+        fn detect_player_mesos(&self, region: Rect) -> Result<u64>;
         fn detect_rune_arrows<'a>(
             &self,
             calibrating: ArrowsCalibrating,
         ) -> Result<ArrowsState>;
+        fn detect_rune_arrows_remaining(
+            &self,
+            calibrating: ArrowsCalibrating,
+        ) -> Result<Vec<KeyKind>>;
         fn detect_erda_shower(&self) -> Result<Rect>;
         fn detect_familiar_save_button(&self) -> Result<Rect>;
         fn detect_familiar_setup_button(&self) -> Result<Rect>;
         fn detect_familiar_level_button(&self) -> Result<Rect>;
         fn detect_familiar_slots(&self) -> Vec<(Rect, bool)>;
         fn detect_familiar_slot_is_free(&self, slot: Rect) -> bool;
+        fn detect_familiar_setup(&self) -> Vec<FamiliarSlot>;
         fn detect_familiar_hover_level(&self) -> Result<FamiliarLevel>;
+        fn detect_familiar_hover_stats(&self) -> Result<FamiliarStats>;
         fn detect_familiar_cards(&self) -> Vec<(Rect, FamiliarRank)>;
         fn detect_familiar_scrollbar(&self) -> Result<Rect>;
         fn detect_familiar_menu_opened(&self) -> bool;
         fn detect_familiar_essence_depleted(&self) -> bool;
         fn detect_change_channel_menu_opened(&self) -> bool;
+        fn detect_inventory_full(&self) -> bool;
+        fn detect_chat_lines(&self, region: Rect) -> Vec<String>;
+        fn detect_region_mean_color(&self, region: Rect) -> Result<[f64; 3]>;
+        fn detect_pic_keypad(&self, region: Rect) -> Vec<(Rect, u8)>;
     }
 
     impl Debug for Detector {
@@ -311,6 +543,10 @@ impl Detector for CachedDetector {
         detect_mobs(&*self.mat, minimap, bound, player)
     }
 
+    fn detect_drops(&self, minimap: Rect, bound: Rect, player: Point) -> Result<Vec<Point>> {
+        detect_drops(&*self.mat, minimap, bound, player)
+    }
+
     fn detect_esc_settings(&self) -> bool {
         detect_esc_settings(&**self.grayscale)
     }
@@ -327,7 +563,23 @@ impl Detector for CachedDetector {
         detect_elite_boss_bar(&**self.grayscale)
     }
 
-    fn detect_minimap(&self, border_threshold: u8) -> Result<Rect> {
+    fn detect_minimap(&self, region: Option<Rect>, border_threshold: u8) -> Result<Rect> {
+        // The minimap rarely moves so the last detected minimap inside the search region is
+        // verified first without running the model
+        if let Some(region) = region {
+            let last = Rect::new(
+                region.x + SEARCH_REGION_PADDING,
+                region.y + SEARCH_REGION_PADDING,
+                region.width - SEARCH_REGION_PADDING * 2,
+                region.height - SEARCH_REGION_PADDING * 2,
+            );
+            if let Ok(minimap) = verify_minimap_candidates(&*self.mat, &[last], border_threshold) {
+                return Ok(minimap);
+            }
+        }
+
+        // The minimap may have moved or resized after changing map so search the whole frame
+        // right away instead of waiting for the next detection
         detect_minimap(&*self.mat, border_threshold)
     }
 
@@ -341,12 +593,19 @@ impl Detector for CachedDetector {
         detect_minimap_rune(&minimap_color)
     }
 
-    fn detect_player(&self, minimap: Rect) -> Result<Rect> {
-        let minimap_color = to_bgr(&self.mat.roi(minimap)?);
-        detect_player(&minimap_color)
+    fn detect_minimap_elite_boss(&self, minimap: Rect) -> Result<Rect> {
+        detect_minimap_elite_boss(&self.mat.roi(minimap)?)
     }
 
-    fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> bool {
+    fn detect_player(&self, minimap: Rect, score_threshold: f32) -> (Result<Rect>, Option<f32>) {
+        let minimap_color = match self.mat.roi(minimap) {
+            Ok(minimap) => to_bgr(&minimap),
+            Err(err) => return (Err(err.into()), None),
+        };
+        detect_player(&minimap_color, score_threshold as f64)
+    }
+
+    fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> Vec<Rect> {
         let minimap_color = to_bgr(&self.mat.roi(minimap).unwrap());
         detect_player_kind(&minimap_color, kind)
     }
@@ -359,16 +618,48 @@ impl Detector for CachedDetector {
         detect_player_in_cash_shop(&**self.grayscale)
     }
 
-    fn detect_player_health_bar(&self) -> Result<Rect> {
-        detect_player_health_bar(&**self.grayscale)
+    fn detect_player_health_bar(
+        &self,
+        region: Option<Rect>,
+        score_threshold: f32,
+    ) -> (Result<Rect>, Option<f32>) {
+        let score_threshold = score_threshold as f64;
+        let Some(region) = region else {
+            return detect_player_health_bar(&**self.grayscale, score_threshold);
+        };
+        let roi = search_region_in(&**self.grayscale, region)
+            .and_then(|region| Ok((region, self.grayscale.roi(region)?)));
+        let (region, roi) = match roi {
+            Ok(roi) => roi,
+            Err(err) => return (Err(err), None),
+        };
+        let (health_bar, score) = detect_player_health_bar(&roi, score_threshold);
+        (health_bar.map(|health_bar| health_bar + region.tl()), score)
     }
 
     fn detect_player_current_max_health_bars(&self, health_bar: Rect) -> Result<(Rect, Rect)> {
         detect_player_current_max_health_bars(&*self.mat, &**self.grayscale, health_bar)
     }
 
+    fn detect_player_health_ratio(
+        &self,
+        health_bar: Rect,
+        color_lower: [u8; 3],
+        color_upper: [u8; 3],
+    ) -> Result<f32> {
+        detect_player_health_ratio(&*self.mat, health_bar, color_lower, color_upper)
+    }
+
     fn detect_player_health(&self, current_bar: Rect, max_bar: Rect) -> Result<(u32, u32)> {
-        detect_player_health(&*self.mat, current_bar, max_bar)
+        detect_player_current_max_values(&*self.mat, current_bar, max_bar)
+    }
+
+    fn detect_player_current_max_mana_bars(&self, health_bar: Rect) -> Result<(Rect, Rect)> {
+        detect_player_current_max_mana_bars(&*self.mat, &**self.grayscale, health_bar)
+    }
+
+    fn detect_player_mana(&self, current_bar: Rect, max_bar: Rect) -> Result<(u32, u32)> {
+        detect_player_current_max_values(&*self.mat, current_bar, max_bar)
     }
 
     fn detect_player_buff(&self, kind: BuffKind) -> bool {
@@ -391,10 +682,22 @@ impl Detector for CachedDetector {
         detect_player_buff(mat, kind)
     }
 
+    fn detect_player_exp(&self) -> Result<(u64, f32)> {
+        detect_player_exp(&*self.mat)
+    }
+
+    fn detect_player_mesos(&self, region: Rect) -> Result<u64> {
+        detect_player_mesos(&*self.mat, region)
+    }
+
     fn detect_rune_arrows(&self, calibrating: ArrowsCalibrating) -> Result<ArrowsState> {
         detect_rune_arrows(&*self.mat, calibrating)
     }
 
+    fn detect_rune_arrows_remaining(&self, calibrating: ArrowsCalibrating) -> Result<Vec<KeyKind>> {
+        detect_rune_arrows_remaining(&*self.mat, calibrating)
+    }
+
     fn detect_erda_shower(&self) -> Result<Rect> {
         detect_erda_shower(&**self.grayscale)
     }
@@ -419,10 +722,27 @@ impl Detector for CachedDetector {
         detect_familiar_slot_is_free(&to_bgr(&self.mat.roi(slot).unwrap()))
     }
 
+    fn detect_familiar_setup(&self) -> Vec<FamiliarSlot> {
+        detect_familiar_slots(&to_bgr(&*self.mat))
+            .into_iter()
+            .map(|(slot, is_free)| {
+                if is_free {
+                    FamiliarSlot::Free
+                } else {
+                    FamiliarSlot::Occupied(detect_familiar_slot_rank(&self.mat.roi(slot).unwrap()))
+                }
+            })
+            .collect()
+    }
+
     fn detect_familiar_hover_level(&self) -> Result<FamiliarLevel> {
         detect_familiar_hover_level(&to_bgr(&*self.mat))
     }
 
+    fn detect_familiar_hover_stats(&self) -> Result<FamiliarStats> {
+        detect_familiar_hover_stats(&to_bgr(&*self.mat))
+    }
+
     fn detect_familiar_cards(&self) -> Vec<(Rect, FamiliarRank)> {
         detect_familiar_cards(&to_bgr(&*self.mat))
     }
@@ -442,6 +762,22 @@ impl Detector for CachedDetector {
     fn detect_change_channel_menu_opened(&self) -> bool {
         detect_change_channel_menu_opened(&**self.grayscale)
     }
+
+    fn detect_inventory_full(&self) -> bool {
+        detect_inventory_full(&**self.grayscale)
+    }
+
+    fn detect_chat_lines(&self, region: Rect) -> Vec<String> {
+        detect_chat_lines(&*self.mat, region)
+    }
+
+    fn detect_region_mean_color(&self, region: Rect) -> Result<[f64; 3]> {
+        detect_region_mean_color(&*self.mat, region)
+    }
+
+    fn detect_pic_keypad(&self, region: Rect) -> Vec<(Rect, u8)> {
+        detect_pic_keypad(&*self.mat, region)
+    }
 }
 
 fn crop_to_buffs_region(mat: &impl MatTraitConst) -> BoxedRef<'_, Mat> {
@@ -461,81 +797,10 @@ fn detect_mobs(
 ) -> Result<Vec<Point>> {
     static MOB_MODEL: LazyLock<Mutex<Session>> = LazyLock::new(|| {
         Mutex::new(
-            build_session(include_bytes!(env!("MOB_MODEL")))
-                .expect("build mob detection session successfully"),
+            build_session(&mob_model_bytes()).expect("build mob detection session successfully"),
         )
     });
 
-    /// Approximates the mob coordinate on screen to mob coordinate on minimap.
-    ///
-    /// This function tries to approximate the delta (dx, dy) that the player needs to move
-    /// in relative to the minimap coordinate in order to reach the mob. Returns the mob
-    /// coordinate on the minimap by adding the delta to the player position.
-    ///
-    /// Note: It is not that accurate but that is that and this is this. Hey it seems better than
-    /// the previous alchemy.
-    #[inline]
-    fn to_minimap_coordinate(
-        mob_bbox: Rect,
-        minimap_bbox: Rect,
-        mobbing_bound: Rect,
-        player: Point,
-        mat_size: Size,
-    ) -> Option<Point> {
-        // These numbers are for scaling dx/dy on the screen to dx/dy on the minimap.
-        // They are approximated in 1280x720 resolution by going from one point to another point
-        // from the middle of the screen with both points visible on screen before traveling. Take
-        // the distance traveled on the minimap and divide it by half of the resolution
-        // (e.g. tralveled minimap x / 640). Whether it is correct or not, time will tell.
-        const X_SCALE: f32 = 0.059_375;
-        const Y_SCALE: f32 = 0.036_111;
-
-        // The main idea is to calculate the offset of the detected mob from the middle of screen
-        // and use that distance as dx/dy to move the player. This assumes the player will
-        // most of the time be near or very close to the middle of the screen. This is already
-        // not accurate in the sense that the camera will have a bit of lag before
-        // it is centered again on the player. And when the player is near edges of the map,
-        // this function is just plain wrong. For better accuracy, detecting where the player is
-        // on the screen and use that as the basis is required.
-        let x_screen_mid = mat_size.width / 2;
-        let x_mob_mid = mob_bbox.x + mob_bbox.width / 2;
-        let x_screen_delta = x_screen_mid - x_mob_mid;
-        let x_minimap_delta = (x_screen_delta as f32 * X_SCALE) as i32;
-
-        // For dy, if the whole mob bounding box is above the screen mid point, then the
-        // box top edge is used to increase the dy distance as to help the player move up. The same
-        // goes for moving down. If the bounding box overlaps with the screen mid point, the box
-        // mid point is used as to to help the player stay in place.
-        let y_screen_mid = mat_size.height / 2;
-        let y_mob = if mob_bbox.y + mob_bbox.height < y_screen_mid {
-            mob_bbox.y
-        } else if mob_bbox.y > y_screen_mid {
-            mob_bbox.y + mob_bbox.height
-        } else {
-            mob_bbox.y + mob_bbox.height / 2
-        };
-        let y_screen_delta = y_screen_mid - y_mob;
-        let y_minimap_delta = (y_screen_delta as f32 * Y_SCALE) as i32;
-
-        let point_x = if x_minimap_delta > 0 {
-            (player.x - x_minimap_delta).max(0)
-        } else {
-            (player.x - x_minimap_delta).min(minimap_bbox.width)
-        };
-        let point_y = (player.y + y_minimap_delta).max(0).min(minimap_bbox.height);
-        // Minus the y by minimap height to make it relative to the minimap top edge
-        let point = Point::new(point_x, minimap_bbox.height - point_y);
-        if point.x < mobbing_bound.x
-            || point.x > mobbing_bound.x + mobbing_bound.width
-            || point.y < mobbing_bound.y
-            || point.y > mobbing_bound.y + mobbing_bound.height
-        {
-            None
-        } else {
-            Some(point)
-        }
-    }
-
     let size = mat.size().unwrap();
     let (mat_in, w_ratio, h_ratio, left, top) = preprocess_for_yolo(mat);
     let mut model = MOB_MODEL.lock().unwrap();
@@ -551,6 +816,136 @@ fn detect_mobs(
     Ok(points)
 }
 
+/// Approximates the `bbox` coordinate on screen to coordinate on minimap.
+///
+/// This function tries to approximate the delta (dx, dy) that the player needs to move
+/// in relative to the minimap coordinate in order to reach the `bbox`. Returns the `bbox`
+/// coordinate on the minimap by adding the delta to the player position or [`None`] if it is
+/// outside of `bound`.
+///
+/// Note: It is not that accurate but that is that and this is this. Hey it seems better than
+/// the previous alchemy.
+#[inline]
+fn to_minimap_coordinate(
+    bbox: Rect,
+    minimap_bbox: Rect,
+    bound: Rect,
+    player: Point,
+    mat_size: Size,
+) -> Option<Point> {
+    // These numbers are for scaling dx/dy on the screen to dx/dy on the minimap.
+    // They are approximated in 1280x720 resolution by going from one point to another point
+    // from the middle of the screen with both points visible on screen before traveling. Take
+    // the distance traveled on the minimap and divide it by half of the resolution
+    // (e.g. tralveled minimap x / 640). Whether it is correct or not, time will tell.
+    const X_SCALE: f32 = 0.059_375;
+    const Y_SCALE: f32 = 0.036_111;
+
+    // The main idea is to calculate the offset of the detected bbox from the middle of screen
+    // and use that distance as dx/dy to move the player. This assumes the player will
+    // most of the time be near or very close to the middle of the screen. This is already
+    // not accurate in the sense that the camera will have a bit of lag before
+    // it is centered again on the player. And when the player is near edges of the map,
+    // this function is just plain wrong. For better accuracy, detecting where the player is
+    // on the screen and use that as the basis is required.
+    let x_screen_mid = mat_size.width / 2;
+    let x_bbox_mid = bbox.x + bbox.width / 2;
+    let x_screen_delta = x_screen_mid - x_bbox_mid;
+    let x_minimap_delta = (x_screen_delta as f32 * X_SCALE) as i32;
+
+    // For dy, if the whole bounding box is above the screen mid point, then the
+    // box top edge is used to increase the dy distance as to help the player move up. The same
+    // goes for moving down. If the bounding box overlaps with the screen mid point, the box
+    // mid point is used as to to help the player stay in place.
+    let y_screen_mid = mat_size.height / 2;
+    let y_bbox = if bbox.y + bbox.height < y_screen_mid {
+        bbox.y
+    } else if bbox.y > y_screen_mid {
+        bbox.y + bbox.height
+    } else {
+        bbox.y + bbox.height / 2
+    };
+    let y_screen_delta = y_screen_mid - y_bbox;
+    let y_minimap_delta = (y_screen_delta as f32 * Y_SCALE) as i32;
+
+    let point_x = if x_minimap_delta > 0 {
+        (player.x - x_minimap_delta).max(0)
+    } else {
+        (player.x - x_minimap_delta).min(minimap_bbox.width)
+    };
+    let point_y = (player.y + y_minimap_delta).max(0).min(minimap_bbox.height);
+    // Minus the y by minimap height to make it relative to the minimap top edge
+    let point = Point::new(point_x, minimap_bbox.height - point_y);
+    if point.x < bound.x
+        || point.x > bound.x + bound.width
+        || point.y < bound.y
+        || point.y > bound.y + bound.height
+    {
+        None
+    } else {
+        Some(point)
+    }
+}
+
+fn detect_drops(
+    mat: &impl MatTraitConst,
+    minimap: Rect,
+    bound: Rect,
+    player: Point,
+) -> Result<Vec<Point>> {
+    /// The minimum pixel area of a drop blob.
+    const DROP_MIN_AREA: i32 = 30;
+    /// The maximum pixel area of a drop blob, larger blobs are most likely skill effects.
+    const DROP_MAX_AREA: i32 = 900;
+
+    // Drops are searched around the player which is assumed to be near the middle of the screen.
+    // The same assumption is used when approximating the minimap coordinate.
+    let size = mat.size().unwrap();
+    let crop_bbox = Rect::new(
+        size.width / 4,
+        size.height / 3,
+        size.width / 2,
+        size.height / 2,
+    );
+    let drops = to_hsv(&mat.roi(crop_bbox)?);
+    let mut drops_thresh = Mat::default();
+    // Mesos and the glow of item drops are saturated yellow-ish. Hue is in the 0..255 range.
+    in_range(
+        &drops,
+        &Scalar::new(20.0, 150.0, 180.0, 0.0),
+        &Scalar::new(45.0, 255.0, 255.0, 0.0),
+        &mut drops_thresh,
+    )
+    .unwrap();
+
+    let mut stats = Mat::default();
+    let labels_count = connected_components_with_stats(
+        &drops_thresh,
+        &mut Mat::default(),
+        &mut stats,
+        &mut Mat::default(),
+        8,
+        CV_32S,
+    )
+    .unwrap();
+    let points = (1..labels_count)
+        .filter_map(|i| {
+            let area = *stats.at_2d::<i32>(i, CC_STAT_AREA).unwrap();
+            if !(DROP_MIN_AREA..=DROP_MAX_AREA).contains(&area) {
+                return None;
+            }
+            let bbox = Rect::new(
+                *stats.at_2d::<i32>(i, CC_STAT_LEFT).unwrap(),
+                *stats.at_2d::<i32>(i, CC_STAT_TOP).unwrap(),
+                *stats.at_2d::<i32>(i, CC_STAT_WIDTH).unwrap(),
+                *stats.at_2d::<i32>(i, CC_STAT_HEIGHT).unwrap(),
+            ) + crop_bbox.tl();
+            to_minimap_coordinate(bbox, minimap, bound, player, size)
+        })
+        .collect::<Vec<_>>();
+    Ok(points)
+}
+
 /// TODO: Support default ratio
 static ESC_SETTINGS: LazyLock<[Mat; 11]> = LazyLock::new(|| {
     [
@@ -649,14 +1044,101 @@ fn detect_elite_boss_bar(mat: &impl MatTraitConst) -> bool {
         || detect_template(&boss_bar, template_2, Point::default(), 0.9).is_ok()
 }
 
-fn detect_minimap(mat: &impl MatTraitConst, border_threshold: u8) -> Result<Rect> {
+fn detect_minimap(mat: &(impl MatTraitConst + Sync), border_threshold: u8) -> Result<Rect> {
     static MINIMAP_MODEL: LazyLock<Mutex<Session>> = LazyLock::new(|| {
         Mutex::new(
-            build_session(include_bytes!(env!("MINIMAP_MODEL")))
+            build_session(&minimap_model_bytes())
                 .expect("build minimap detection session successfully"),
         )
     });
 
+    /// A prediction at or above this score is verified alone before the other candidates.
+    const HIGH_SCORE: f32 = 0.9;
+    const MAX_CANDIDATES: usize = 4;
+
+    let size = mat.size().unwrap();
+    let (mat_in, w_ratio, h_ratio, left, top) = preprocess_for_yolo(mat);
+    let mut candidates = {
+        let mut model = MINIMAP_MODEL.lock().unwrap();
+        let result = model.run([norm_rgb_to_input_value(&mat_in)]).unwrap();
+        let mat_out = from_output_value(&result);
+        (0..mat_out.rows())
+            // SAFETY: 0..result.rows() is within Mat bounds
+            .map(|i| unsafe { mat_out.at_row_unchecked::<f32>(i).unwrap() })
+            // pred has shape [bbox(4) + class(1)]
+            .filter(|pred| pred[4] >= 0.7)
+            .map(|pred| {
+                (
+                    remap_from_yolo(pred, size, w_ratio, h_ratio, left, top),
+                    pred[4],
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    if candidates.is_empty() {
+        bail!("minimap detection failed");
+    }
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    candidates.truncate(MAX_CANDIDATES);
+
+    debug!(target: "minimap", "yolo candidates: {candidates:?}");
+
+    // Early exit on a confident prediction which is the common case outside of map switching
+    let (first_bbox, first_score) = candidates[0];
+    if first_score >= HIGH_SCORE
+        && let Ok(bbox) = crop_minimap_borders(mat, first_bbox, border_threshold)
+    {
+        return Ok(bbox);
+    }
+
+    // During map switching, the highest scored prediction may be the fading out minimap so
+    // verify all candidates in parallel and take the highest scored one that passes
+    let candidates = candidates
+        .into_iter()
+        .map(|(bbox, _)| bbox)
+        .collect::<Vec<_>>();
+    verify_minimap_candidates(mat, &candidates, border_threshold)
+}
+
+/// Verifies the minimap `candidates` ordered by priority, each at multiple scales in parallel.
+///
+/// A candidate bounding box is scaled up around its center because the prediction or the last
+/// detected minimap may not include the whole white border. Returns the cropped minimap of the
+/// first passing candidate at its smallest passing scale.
+fn verify_minimap_candidates(
+    mat: &(impl MatTraitConst + Sync),
+    candidates: &[Rect],
+    border_threshold: u8,
+) -> Result<Rect> {
+    const SCALES: [f32; 4] = [1.0, 1.05, 1.1, 1.2];
+
+    let size = mat.size().unwrap();
+    let frame = Rect::new(0, 0, size.width, size.height);
+    candidates
+        .par_iter()
+        .flat_map_iter(|bbox| {
+            SCALES.into_iter().map(move |scale| {
+                let width = (bbox.width as f32 * scale).round() as i32;
+                let height = (bbox.height as f32 * scale).round() as i32;
+                let x = bbox.x - (width - bbox.width) / 2;
+                let y = bbox.y - (height - bbox.height) / 2;
+                Rect::new(x, y, width, height) & frame
+            })
+        })
+        .filter(|bbox| !bbox.empty())
+        .map(|bbox| crop_minimap_borders(mat, bbox, border_threshold))
+        .find_first(Result::is_ok)
+        .unwrap_or_else(|| Err(anyhow!("minimap candidates failed verification")))
+}
+
+/// Crops the white borders of the minimap predicted at `minimap_bbox`.
+///
+/// Fails if `minimap_bbox` does not tightly contain the minimap contour.
+fn crop_minimap_borders(
+    mat: &impl MatTraitConst,
+    minimap_bbox: Rect,
+    border_threshold: u8,
+) -> Result<Rect> {
     enum Border {
         Top,
         Bottom,
@@ -717,25 +1199,7 @@ fn detect_minimap(mat: &impl MatTraitConst, border_threshold: u8) -> Result<Rect
             .unwrap_or_default() as i32
     }
 
-    let size = mat.size().unwrap();
-    let (mat_in, w_ratio, h_ratio, left, top) = preprocess_for_yolo(mat);
-    let mut model = MINIMAP_MODEL.lock().unwrap();
-    let result = model.run([norm_rgb_to_input_value(&mat_in)]).unwrap();
-    let mat_out = from_output_value(&result);
-    let pred = (0..mat_out.rows())
-        // SAFETY: 0..result.rows() is within Mat bounds
-        .map(|i| unsafe { mat_out.at_row_unchecked::<f32>(i).unwrap() })
-        .max_by(|&a, &b| {
-            // a and b have shapes [bbox(4) + class(1)]
-            a[4].total_cmp(&b[4])
-        })
-        .filter(|pred| pred[4] >= 0.7)
-        .ok_or(anyhow!("minimap detection failed"))?;
-
-    debug!(target: "minimap", "yolo detection: {pred:?}");
-
     // Extract the thresholded minimap
-    let minimap_bbox = remap_from_yolo(pred, size, w_ratio, h_ratio, left, top);
     let mut minimap_thresh = to_grayscale(&mat.roi(minimap_bbox).unwrap(), true);
     unsafe {
         // SAFETY: threshold can be called in place.
@@ -824,19 +1288,64 @@ fn detect_minimap_rune(minimap: &impl ToInputArray) -> Result<Rect> {
         .map(|(rect, _)| Rect::new(rect.x - 1, rect.y - 1, rect.width + 2, rect.height + 2))
 }
 
-fn detect_player(mat: &impl ToInputArray) -> Result<Rect> {
+fn detect_minimap_elite_boss(minimap: &impl MatTraitConst) -> Result<Rect> {
+    const MARKER_MIN_AREA: i32 = 6;
+
+    let minimap = to_hsv(minimap);
+    let mut marker_thresh = Mat::default();
+    // The elite boss marker is a saturated red dot. Hue is in the 0..255 range.
+    in_range(
+        &minimap,
+        &Scalar::new(0.0, 180.0, 180.0, 0.0),
+        &Scalar::new(8.0, 255.0, 255.0, 0.0),
+        &mut marker_thresh,
+    )
+    .unwrap();
+
+    let mut stats = Mat::default();
+    let labels_count = connected_components_with_stats(
+        &marker_thresh,
+        &mut Mat::default(),
+        &mut stats,
+        &mut Mat::default(),
+        8,
+        CV_32S,
+    )
+    .unwrap();
+    (1..labels_count)
+        .map(|i| {
+            let area = *stats.at_2d::<i32>(i, CC_STAT_AREA).unwrap();
+            let bbox = Rect::new(
+                *stats.at_2d::<i32>(i, CC_STAT_LEFT).unwrap(),
+                *stats.at_2d::<i32>(i, CC_STAT_TOP).unwrap(),
+                *stats.at_2d::<i32>(i, CC_STAT_WIDTH).unwrap(),
+                *stats.at_2d::<i32>(i, CC_STAT_HEIGHT).unwrap(),
+            );
+            (area, bbox)
+        })
+        .filter(|(area, _)| *area >= MARKER_MIN_AREA)
+        .max_by_key(|(area, _)| *area)
+        .map(|(_, bbox)| bbox)
+        .ok_or(anyhow!("elite boss marker not found"))
+}
+
+fn detect_player(mat: &impl ToInputArray, threshold: f64) -> (Result<Rect>, Option<f32>) {
     /// TODO: Support default ratio
     static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
         imgcodecs::imdecode(include_bytes!(env!("PLAYER_TEMPLATE")), IMREAD_COLOR).unwrap()
     });
 
+    let result = detect_template_single(mat, &*TEMPLATE, no_array(), Point::default(), threshold);
+    let score = template_score(&result).map(|score| score as f32);
+
     // Expands by 2 pixels to preserve previous position calculation. Previous template is 10x10
     // while the current template is 8x8.
-    detect_template_single(mat, &*TEMPLATE, no_array(), Point::default(), 0.75)
-        .map(|(rect, _)| Rect::new(rect.x - 1, rect.y - 1, rect.width + 2, rect.height + 2))
+    let result =
+        result.map(|(rect, _)| Rect::new(rect.x - 1, rect.y - 1, rect.width + 2, rect.height + 2));
+    (result, score)
 }
 
-fn detect_player_kind(mat: &impl ToInputArray, kind: OtherPlayerKind) -> bool {
+fn detect_player_kind(mat: &impl ToInputArray, kind: OtherPlayerKind) -> Vec<Rect> {
     /// TODO: Support default ratio
     static STRANGER_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
         imgcodecs::imdecode(
@@ -856,17 +1365,16 @@ fn detect_player_kind(mat: &impl ToInputArray, kind: OtherPlayerKind) -> bool {
         imgcodecs::imdecode(include_bytes!(env!("PLAYER_FRIEND_TEMPLATE")), IMREAD_COLOR).unwrap()
     });
 
-    match kind {
-        OtherPlayerKind::Stranger => {
-            detect_template(mat, &*STRANGER_TEMPLATE, Point::default(), 0.85).is_ok()
-        }
-        OtherPlayerKind::Guildie => {
-            detect_template(mat, &*GUILDIE_TEMPLATE, Point::default(), 0.85).is_ok()
-        }
-        OtherPlayerKind::Friend => {
-            detect_template(mat, &*FRIEND_TEMPLATE, Point::default(), 0.85).is_ok()
-        }
-    }
+    let template = match kind {
+        OtherPlayerKind::Stranger => &*STRANGER_TEMPLATE,
+        OtherPlayerKind::Guildie => &*GUILDIE_TEMPLATE,
+        OtherPlayerKind::Friend => &*FRIEND_TEMPLATE,
+    };
+    detect_template_multiple(mat, template, no_array(), Point::default(), 16, 0.85)
+        .into_iter()
+        .filter_map(|result| result.ok())
+        .map(|(bbox, _)| bbox)
+        .collect()
 }
 
 fn detect_player_is_dead(mat: &impl ToInputArray) -> bool {
@@ -887,7 +1395,10 @@ fn detect_player_in_cash_shop(mat: &impl ToInputArray) -> bool {
     detect_template(mat, &*CASH_SHOP, Point::default(), 0.7).is_ok()
 }
 
-fn detect_player_health_bar(mat: &impl ToInputArray) -> Result<Rect> {
+fn detect_player_health_bar(
+    mat: &impl ToInputArray,
+    threshold: f64,
+) -> (Result<Rect>, Option<f32>) {
     /// TODO: Support default ratio
     static HP_START: LazyLock<Mat> = LazyLock::new(|| {
         imgcodecs::imdecode(include_bytes!(env!("HP_START_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
@@ -896,21 +1407,117 @@ fn detect_player_health_bar(mat: &impl ToInputArray) -> Result<Rect> {
         imgcodecs::imdecode(include_bytes!(env!("HP_END_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
     });
 
-    let hp_start = detect_template(mat, &*HP_START, Point::default(), 0.8)?;
-    let hp_start_to_edge_x = hp_start.x + hp_start.width;
-    let hp_end = detect_template(mat, &*HP_END, Point::default(), 0.8)?;
-    Ok(Rect::new(
-        hp_start_to_edge_x,
-        hp_start.y,
-        hp_end.x - hp_start_to_edge_x,
-        hp_start.height,
-    ))
+    let hp_start = detect_template_single(mat, &*HP_START, no_array(), Point::default(), threshold);
+    let hp_end = detect_template_single(mat, &*HP_END, no_array(), Point::default(), threshold);
+    let score = template_score(&hp_start)
+        .zip(template_score(&hp_end))
+        .map(|(start, end)| start.min(end) as f32);
+
+    let result = hp_start.and_then(|(hp_start, _)| {
+        let (hp_end, _) = hp_end?;
+        let hp_start_to_edge_x = hp_start.x + hp_start.width;
+        Ok(Rect::new(
+            hp_start_to_edge_x,
+            hp_start.y,
+            hp_end.x - hp_start_to_edge_x,
+            hp_start.height,
+        ))
+    });
+    (result, score)
+}
+
+fn detect_player_health_ratio(
+    mat: &impl MatTraitConst,
+    health_bar: Rect,
+    lower: [u8; 3],
+    upper: [u8; 3],
+) -> Result<f32> {
+    #[inline]
+    fn hsv_scalar([h, s, v]: [u8; 3]) -> Scalar {
+        Scalar::new(h as f64, s as f64, v as f64, 0.0)
+    }
+
+    let bar = to_hsv(&mat.roi(health_bar)?);
+    let mut gauge = Mat::default();
+    if lower[0] <= upper[0] {
+        in_range(&bar, &hsv_scalar(lower), &hsv_scalar(upper), &mut gauge)?;
+    } else {
+        // The hue range wraps around (e.g. red) when the lower hue is greater than the upper hue
+        let mut gauge_low = Mat::default();
+        let mut gauge_high = Mat::default();
+        let lower_low = [0, lower[1], lower[2]];
+        let upper_high = [u8::MAX, upper[1], upper[2]];
+        in_range(
+            &bar,
+            &hsv_scalar(lower_low),
+            &hsv_scalar(upper),
+            &mut gauge_low,
+        )?;
+        in_range(
+            &bar,
+            &hsv_scalar(lower),
+            &hsv_scalar(upper_high),
+            &mut gauge_high,
+        )?;
+        bitwise_or_def(&gauge_low, &gauge_high, &mut gauge)?;
+    }
+
+    // The gauge fills from the left with the value text drawn over it so the right-most gauge
+    // pixel is used
+    let mut points = Vector::<Point>::new();
+    find_non_zero(&gauge, &mut points)?;
+    let filled = points
+        .iter()
+        .map(|point| point.x + 1)
+        .max()
+        .ok_or(anyhow!("health bar gauge not found"))?;
+    Ok(filled as f32 / health_bar.width as f32)
 }
 
 fn detect_player_current_max_health_bars(
     mat: &impl MatTraitConst,
     grayscale: &impl MatTraitConst,
     hp_bar: Rect,
+) -> Result<(Rect, Rect)> {
+    static HP_SHIELD: LazyLock<Mat> = LazyLock::new(|| {
+        imgcodecs::imdecode(include_bytes!(env!("HP_SHIELD_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
+    });
+
+    let hp_shield = detect_template(
+        &grayscale.roi(hp_bar).unwrap(),
+        &*HP_SHIELD,
+        hp_bar.tl(),
+        0.8,
+    )
+    .ok();
+    detect_player_current_max_bars(mat, grayscale, hp_bar, hp_shield)
+}
+
+fn detect_player_current_max_mana_bars(
+    mat: &impl MatTraitConst,
+    grayscale: &impl MatTraitConst,
+    hp_bar: Rect,
+) -> Result<(Rect, Rect)> {
+    let size = mat.size().unwrap();
+    // The MP bar is right below the HP bar with the same width, search a bit taller region to
+    // account for the gap in between
+    let mp_bar_y = hp_bar.y + hp_bar.height;
+    let mp_bar_height = (hp_bar.height * 2).min(size.height - mp_bar_y);
+    if mp_bar_height <= 0 {
+        bail!("mana bar is outside of the image");
+    }
+    let mp_bar = Rect::new(hp_bar.x, mp_bar_y, hp_bar.width, mp_bar_height);
+    detect_player_current_max_bars(mat, grayscale, mp_bar, None)
+}
+
+/// Detects the current and max value bars separated by a `/` inside `hp_bar`.
+///
+/// When `hp_shield` is provided, the current value bar starts after it.
+fn detect_player_current_max_bars(
+    mat: &impl MatTraitConst,
+    grayscale: &impl MatTraitConst,
+    hp_bar: Rect,
+    hp_shield: Option<Rect>,
 ) -> Result<(Rect, Rect)> {
     /// TODO: Support default ratio
     static HP_SEPARATOR_1: LazyLock<Mat> = LazyLock::new(|| {
@@ -927,9 +1534,6 @@ fn detect_player_current_max_health_bars(
         )
         .unwrap()
     });
-    static HP_SHIELD: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("HP_SHIELD_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
-    });
     static HP_SEPARATOR_TYPE_1: AtomicBool = AtomicBool::new(true);
 
     let hp_separator_type_1 = HP_SEPARATOR_TYPE_1.load(Ordering::Relaxed);
@@ -947,13 +1551,6 @@ fn detect_player_current_max_health_bars(
     .inspect_err(|_| {
         HP_SEPARATOR_TYPE_1.store(!hp_separator_type_1, Ordering::Release);
     })?;
-    let hp_shield = detect_template(
-        &grayscale.roi(hp_bar).unwrap(),
-        &*HP_SHIELD,
-        hp_bar.tl(),
-        0.8,
-    )
-    .ok();
     let left = mat
         .roi(Rect::new(
             hp_bar.x,
@@ -966,7 +1563,7 @@ fn detect_player_current_max_health_bars(
     let left_bbox = extract_text_bboxes(&left_in, left_w_ratio, left_h_ratio, hp_bar.x, hp_bar.y)
         .into_iter()
         .min_by_key(|bbox| ((bbox.x + bbox.width) - hp_separator.x).abs())
-        .ok_or(anyhow!("failed to detect current health bar"))?;
+        .ok_or(anyhow!("failed to detect current value bar"))?;
     let left_bbox_x = hp_shield
         .map(|bbox| bbox.x + bbox.width)
         .unwrap_or(left_bbox.x); // When there is shield, skips past it
@@ -994,26 +1591,66 @@ fn detect_player_current_max_health_bars(
     )
     .into_iter()
     .reduce(|acc, cur| acc | cur)
-    .ok_or(anyhow!("failed to detect max health bar"))?;
+    .ok_or(anyhow!("failed to detect max value bar"))?;
     Ok((left_bbox, right_bbox))
 }
 
-fn detect_player_health(
+fn detect_player_current_max_values(
     mat: &impl MatTraitConst,
     current_bar: Rect,
     max_bar: Rect,
 ) -> Result<(u32, u32)> {
-    let current_health = extract_texts(mat, &[current_bar]);
-    let current_health = current_health
+    let current_value = extract_texts(mat, &[current_bar]);
+    let current_value = current_value
         .first()
         .and_then(|value| value.parse::<u32>().ok())
-        .ok_or(anyhow!("cannot detect current health"))?;
-    let max_health = extract_texts(mat, &[max_bar]);
-    let max_health = max_health
+        .ok_or(anyhow!("cannot detect current value"))?;
+    let max_value = extract_texts(mat, &[max_bar]);
+    let max_value = max_value
         .first()
         .and_then(|value| value.parse::<u32>().ok())
-        .ok_or(anyhow!("cannot detect max health"))?;
-    Ok((current_health.min(max_health), max_health))
+        .ok_or(anyhow!("cannot detect max value"))?;
+    Ok((current_value.min(max_value), max_value))
+}
+
+fn detect_player_exp(mat: &impl MatTraitConst) -> Result<(u64, f32)> {
+    let size = mat.size().unwrap();
+    // The EXP text (e.g. "1234567 [12.345%]") is inside the bar at the bottom middle of the screen
+    let crop_y = size.height / 36;
+    let crop_bbox = Rect::new(size.width / 4, size.height - crop_y, size.width / 2, crop_y);
+    let exp_bar = mat.roi(crop_bbox).unwrap();
+    let (exp_bar_in, w_ratio, h_ratio) = preprocess_for_text_bboxes(&exp_bar);
+    let mut bboxes = extract_text_bboxes(&exp_bar_in, w_ratio, h_ratio, crop_bbox.x, crop_bbox.y);
+    bboxes.sort_by_key(|bbox| bbox.x);
+    let text = extract_texts(mat, &bboxes).join(" ");
+    let (exp, percent) = text
+        .split_once('[')
+        .ok_or(anyhow!("cannot detect exp percentage"))?;
+    let exp = exp
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>()
+        .parse::<u64>()?;
+    let percent = percent
+        .split('%')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .parse::<f32>()?;
+    Ok((exp, percent))
+}
+
+fn detect_player_mesos(mat: &impl MatTraitConst, region: Rect) -> Result<u64> {
+    // The meso count (e.g. "1,234,567") is the only text inside the region
+    let mesos = extract_text_lines(mat, region)
+        .join("")
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>();
+    if mesos.is_empty() {
+        bail!("cannot detect mesos");
+    }
+    Ok(mesos.parse::<u64>()?)
 }
 
 fn detect_player_buff<T: MatTraitConst + ToInputArray>(mat: &T, kind: BuffKind) -> bool {
@@ -1242,8 +1879,7 @@ fn detect_player_buff<T: MatTraitConst + ToInputArray>(mat: &T, kind: BuffKind)
 fn detect_rune_arrows_with_scores_regions(mat: &impl MatTraitConst) -> Vec<(Rect, KeyKind, f32)> {
     static RUNE_MODEL: LazyLock<Mutex<Session>> = LazyLock::new(|| {
         Mutex::new(
-            build_session(include_bytes!(env!("RUNE_MODEL")))
-                .expect("build rune detection session successfully"),
+            build_session(&rune_model_bytes()).expect("build rune detection session successfully"),
         )
     });
 
@@ -1405,9 +2041,8 @@ fn detect_rune_arrows(
         .into_iter()
         .filter_map(|(rect, arrow, score)| (score >= SCORE_THRESHOLD).then_some((rect, arrow)))
         .collect::<Vec<_>>();
-    // TODO: If there are spinning arrows, either set the limit internally
-    // or ensure caller only try to solve rune for a fixed time frame. Otherwise, it may
-    // return `[ArrowsState::Calibrating]` forever.
+    // Caller only solves rune for a fixed time frame so this does not return
+    // `[ArrowsState::Calibrating]` forever
     if calibrating.spin_arrows.is_some() {
         if result.len() != MAX_ARROWS / 2 {
             return Ok(ArrowsState::Calibrating(calibrating));
@@ -1431,6 +2066,25 @@ fn detect_rune_arrows(
     }
 }
 
+fn detect_rune_arrows_remaining(
+    mat: &impl MatTraitConst,
+    calibrating: ArrowsCalibrating,
+) -> Result<Vec<KeyKind>> {
+    const SCORE_THRESHOLD: f32 = 0.8;
+
+    let rune_region = calibrating
+        .rune_region
+        .ok_or(anyhow!("rune region not found"))?;
+    let arrows = detect_rune_arrows_with_scores_regions(&mat.roi(rune_region)?)
+        .into_iter()
+        .filter_map(|(_, arrow, score)| (score >= SCORE_THRESHOLD).then_some(arrow))
+        .collect::<Vec<_>>();
+    if arrows.len() > MAX_ARROWS {
+        bail!("more than {MAX_ARROWS} rune arrows detected");
+    }
+    Ok(arrows)
+}
+
 fn calibrate_for_spin_arrows(
     mat: &impl MatTraitConst,
     rune_region: Rect,
@@ -1523,6 +2177,7 @@ fn calibrate_for_spin_arrows(
             centroid,
             region: rect,
             last_arrow_head: None,
+            tracked_frames: 0,
             final_arrow: None,
             #[cfg(debug_assertions)]
             is_spin_testing: calibrating.is_spin_testing,
@@ -1540,6 +2195,13 @@ fn calibrate_for_spin_arrows(
 fn detect_spin_arrow(mat: &impl MatTraitConst, spin_arrow: &mut SpinArrow) -> Result<()> {
     const INTERPOLATE_FROM_CENTROID: f32 = 0.785;
     const SPIN_LAG_THRESHOLD: i32 = 25;
+    /// The minimum number of frames the arrow must be seen spinning before a lag is trusted
+    ///
+    /// The first few frames right after calibration can be noisy while the arrow is being
+    /// rendered and may look like a lag.
+    const SPIN_MIN_TRACKED_FRAMES: u32 = 3;
+    /// The maximum number of frames to track before giving up on the spinning arrow
+    const SPIN_MAX_TRACKED_FRAMES: u32 = 180;
     const SPIN_ARROW_HUE_THRESHOLD: u8 = 30;
 
     // Extract spin arrow region
@@ -1633,11 +2295,16 @@ fn detect_spin_arrow(mat: &impl MatTraitConst, spin_arrow: &mut SpinArrow) -> Re
         return Ok(());
     }
 
+    spin_arrow.tracked_frames += 1;
+    if spin_arrow.tracked_frames > SPIN_MAX_TRACKED_FRAMES {
+        bail!("spinning arrow did not lag after {SPIN_MAX_TRACKED_FRAMES} frames");
+    }
+
     let prev_arrow_head = spin_arrow.last_arrow_head.unwrap() - centroid;
     let cur_arrow_head = collinear - centroid;
     // https://stackoverflow.com/a/13221874
     let dot = prev_arrow_head.x * -cur_arrow_head.y + prev_arrow_head.y * cur_arrow_head.x;
-    if dot >= SPIN_LAG_THRESHOLD {
+    if dot >= SPIN_LAG_THRESHOLD && spin_arrow.tracked_frames >= SPIN_MIN_TRACKED_FRAMES {
         debug!(target: "rune", "spinning arrow lag detected");
         let up = prev_arrow_head.dot(Point::new(0, -1));
         let down = prev_arrow_head.dot(Point::new(0, 1));
@@ -1798,29 +2465,96 @@ fn detect_familiar_slot_is_free(mat: &impl ToInputArray) -> bool {
     detect_template(mat, &*FAMILIAR_SLOT_FREE, Point::default(), 0.75).is_ok()
 }
 
-fn detect_familiar_hover_level<T: ToInputArray + MatTraitConst>(mat: &T) -> Result<FamiliarLevel> {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_LEVEL_5_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static TEMPLATE_MASK: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_LEVEL_5_MASK_TEMPLATE")),
-            IMREAD_GRAYSCALE,
+/// Detects the rank of the familiar inside an occupied slot from its badge color.
+fn detect_familiar_slot_rank(mat: &impl MatTraitConst) -> Option<FamiliarRank> {
+    /// The minimum ratio of the slot pixels in the badge color to recognize a rank.
+    const BADGE_MIN_RATIO: f32 = 0.02;
+
+    let hsv = to_hsv(mat);
+    let count = |hue_start: f64, hue_end: f64| {
+        let mut thresh = Mat::default();
+        in_range(
+            &hsv,
+            &Scalar::new(hue_start, 120.0, 120.0, 0.0),
+            &Scalar::new(hue_end, 255.0, 255.0, 0.0),
+            &mut thresh,
         )
-        .unwrap()
-    });
+        .unwrap();
+        count_non_zero(&thresh).unwrap()
+    };
+    // Hue is in the 0..255 range, rare badge is blue and epic badge is purple
+    let rare = count(140.0, 180.0);
+    let epic = count(181.0, 215.0);
+    let min_count = (hsv.total() as f32 * BADGE_MIN_RATIO) as i32;
+    if rare.max(epic) < min_count {
+        None
+    } else if rare >= epic {
+        Some(FamiliarRank::Rare)
+    } else {
+        Some(FamiliarRank::Epic)
+    }
+}
 
-    let level_bbox = detect_template(mat, &*TEMPLATE, Point::default(), 0.75)?;
+static FAMILIAR_LEVEL_5: LazyLock<Mat> = LazyLock::new(|| {
+    imgcodecs::imdecode(
+        include_bytes!(env!("FAMILIAR_LEVEL_5_TEMPLATE")),
+        IMREAD_COLOR,
+    )
+    .unwrap()
+});
+static FAMILIAR_LEVEL_5_MASK: LazyLock<Mat> = LazyLock::new(|| {
+    imgcodecs::imdecode(
+        include_bytes!(env!("FAMILIAR_LEVEL_5_MASK_TEMPLATE")),
+        IMREAD_GRAYSCALE,
+    )
+    .unwrap()
+});
+
+fn detect_familiar_hover_level<T: ToInputArray + MatTraitConst>(mat: &T) -> Result<FamiliarLevel> {
+    let level_bbox = detect_template(mat, &*FAMILIAR_LEVEL_5, Point::default(), 0.75)?;
     let level = mat.roi(level_bbox)?;
-    Ok(
-        detect_template_single(&level, &*TEMPLATE, &*TEMPLATE_MASK, Point::default(), 0.70)
-            .map(|_| FamiliarLevel::Level5)
-            .unwrap_or(FamiliarLevel::LevelOther),
+    Ok(detect_template_single(
+        &level,
+        &*FAMILIAR_LEVEL_5,
+        &*FAMILIAR_LEVEL_5_MASK,
+        Point::default(),
+        0.70,
     )
+    .map(|_| FamiliarLevel::Level5)
+    .unwrap_or(FamiliarLevel::LevelOther))
+}
+
+fn detect_familiar_hover_stats<T: ToInputArray + MatTraitConst>(mat: &T) -> Result<FamiliarStats> {
+    let level_bbox = detect_template(mat, &*FAMILIAR_LEVEL_5, Point::default(), 0.75)?;
+    // The level and points are listed from the level text downward inside the tooltip
+    let region = Rect::new(
+        level_bbox.x - level_bbox.width,
+        level_bbox.y,
+        level_bbox.width * 6,
+        level_bbox.height * 8,
+    );
+    let lines = extract_text_lines(mat, region);
+    let mut level = None;
+    let mut points = None;
+    for line in lines.iter().map(|line| line.to_lowercase()) {
+        let value = line
+            .chars()
+            .skip_while(|char| !char.is_ascii_digit())
+            .take_while(char::is_ascii_digit)
+            .collect::<String>()
+            .parse::<u32>()
+            .ok();
+        if level.is_none() && line.contains("lv") {
+            level = value;
+        } else if points.is_none() && line.contains("point") {
+            points = value;
+        }
+    }
+
+    Ok(FamiliarStats {
+        level: level.ok_or(anyhow!("cannot detect familiar level"))?,
+        points: points.ok_or(anyhow!("cannot detect familiar points"))?,
+    })
 }
 
 fn detect_familiar_cards<T: MatTraitConst + ToInputArray>(mat: &T) -> Vec<(Rect, FamiliarRank)> {
@@ -1945,6 +2679,115 @@ fn detect_change_channel_menu_opened(mat: &impl ToInputArray) -> bool {
     detect_template(mat, &*TEMPLATE, Point::default(), 0.75).is_ok()
 }
 
+/// Clips `region` to inside `mat`.
+#[inline]
+fn search_region_in(mat: &impl MatTraitConst, region: Rect) -> Result<Rect> {
+    let size = mat.size().unwrap();
+    let region = region & Rect::new(0, 0, size.width, size.height);
+    if region.empty() {
+        bail!("search region is outside of the screen");
+    }
+    Ok(region)
+}
+
+fn detect_region_mean_color(mat: &impl MatTraitConst, region: Rect) -> Result<[f64; 3]> {
+    let size = mat.size().unwrap();
+    let region = region & Rect::new(0, 0, size.width, size.height);
+    if region.empty() {
+        bail!("region is outside of the screen");
+    }
+
+    let color = mean_def(&mat.roi(region).unwrap())?;
+    Ok([color[0], color[1], color[2]])
+}
+
+fn detect_chat_lines(mat: &impl MatTraitConst, region: Rect) -> Vec<String> {
+    extract_text_lines(mat, region)
+}
+
+/// Extracts the text lines inside `region` ordered from top to bottom.
+fn extract_text_lines(mat: &impl MatTraitConst, region: Rect) -> Vec<String> {
+    let size = mat.size().unwrap();
+    let region = region & Rect::new(0, 0, size.width, size.height);
+    if region.empty() {
+        return vec![];
+    }
+
+    let roi = mat.roi(region).unwrap();
+    let (roi_in, w_ratio, h_ratio) = preprocess_for_text_bboxes(&roi);
+    let mut bboxes = extract_text_bboxes(&roi_in, w_ratio, h_ratio, region.x, region.y);
+    bboxes.sort_by_key(|bbox| (bbox.y, bbox.x));
+
+    // Groups the bounding boxes into lines by their vertical centers
+    let mut lines = Vec::<Vec<Rect>>::new();
+    for bbox in bboxes {
+        let center_y = bbox.y + bbox.height / 2;
+        match lines.last_mut() {
+            Some(line)
+                if line
+                    .first()
+                    .is_some_and(|first| (first.y..first.y + first.height).contains(&center_y)) =>
+            {
+                line.push(bbox);
+            }
+            _ => lines.push(vec![bbox]),
+        }
+    }
+    lines
+        .into_iter()
+        .map(|mut line| {
+            line.sort_by_key(|bbox| bbox.x);
+            extract_texts(mat, &line).join(" ")
+        })
+        .collect()
+}
+
+fn detect_pic_keypad(mat: &impl MatTraitConst, region: Rect) -> Vec<(Rect, u8)> {
+    let size = mat.size().unwrap();
+    let region = region & Rect::new(0, 0, size.width, size.height);
+    if region.empty() {
+        return vec![];
+    }
+
+    let roi = mat.roi(region).unwrap();
+    let (roi_in, w_ratio, h_ratio) = preprocess_for_text_bboxes(&roi);
+    extract_text_bboxes(&roi_in, w_ratio, h_ratio, region.x, region.y)
+        .into_iter()
+        .filter_map(|bbox| {
+            let text = extract_texts(mat, &[bbox]).pop()?;
+            Some((bbox, keypad_digit(&text)?))
+        })
+        .collect()
+}
+
+/// Parses the recognized `text` of a keypad key as a single digit.
+///
+/// Letters commonly recognized in place of digits are also accepted.
+fn keypad_digit(text: &str) -> Option<u8> {
+    let mut chars = text.trim().chars();
+    let char = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    match char {
+        'O' | 'o' => Some(0),
+        'I' | 'l' | '|' => Some(1),
+        _ => char.to_digit(10).map(|digit| digit as u8),
+    }
+}
+
+fn detect_inventory_full(mat: &impl ToInputArray) -> bool {
+    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
+        imgcodecs::imdecode(
+            include_bytes!(env!("INVENTORY_FULL_TEMPLATE")),
+            IMREAD_GRAYSCALE,
+        )
+        .unwrap()
+    });
+
+    detect_template(mat, &*TEMPLATE, Point::default(), 0.75).is_ok()
+}
+
 /// Detects a single match from `template` with the given BGR image `Mat`.
 #[inline]
 fn detect_template<T: ToInputArray + MatTraitConst>(
@@ -1972,6 +2815,15 @@ fn detect_template_single<T: ToInputArray + MatTraitConst>(
         .and_then(|x| x)
 }
 
+/// Retrieves the match score of a [`detect_template_single`] `result` even if below threshold.
+#[inline]
+fn template_score(result: &Result<(Rect, f64)>) -> Option<f64> {
+    match result {
+        Ok((_, score)) => Some(*score),
+        Err(err) => err.downcast_ref::<f64>().copied(),
+    }
+}
+
 /// Detects multiple matches from `template` with the given BGR image `Mat`.
 #[inline]
 fn detect_template_multiple<T: ToInputArray + MatTraitConst>(
@@ -2075,9 +2927,10 @@ fn detect_template_multiple<T: ToInputArray + MatTraitConst>(
 /// Extracts texts from the non-preprocessed `Mat` and detected text bounding boxes.
 fn extract_texts(mat: &impl MatTraitConst, bboxes: &[Rect]) -> Vec<String> {
     static TEXT_RECOGNITION_MODEL: LazyLock<Mutex<TextRecognitionModel>> = LazyLock::new(|| {
-        let model = read_net_from_onnx_buffer(&Vector::from_slice(include_bytes!(env!(
-            "TEXT_RECOGNITION_MODEL"
-        ))))
+        let model = read_net_from_onnx_buffer(&Vector::from_slice(&model_bytes(
+            config().models.text_recognition.as_ref(),
+            include_bytes!(env!("TEXT_RECOGNITION_MODEL")),
+        )))
         .unwrap();
         Mutex::new(
             TextRecognitionModel::new(&model)
@@ -2130,8 +2983,11 @@ fn extract_text_bboxes(
     const LINK_SCORE_THRESHOLD: f64 = 0.4;
     static TEXT_DETECTION_MODEL: LazyLock<Mutex<Session>> = LazyLock::new(|| {
         Mutex::new(
-            build_session(include_bytes!(env!("TEXT_DETECTION_MODEL")))
-                .expect("build text detection session normally"),
+            build_session(&model_bytes(
+                config().models.text_detection.as_ref(),
+                include_bytes!(env!("TEXT_DETECTION_MODEL")),
+            ))
+            .expect("build text detection session normally"),
         )
     });
 
@@ -2438,7 +3294,7 @@ fn to_bgr(mat: &impl MatTraitConst) -> Mat {
 /// `add_contrast` can be set to `true` in order to increase contrast by a fixed amount
 /// used for template matching.
 #[inline]
-fn to_grayscale(mat: &impl MatTraitConst, add_contrast: bool) -> Mat {
+pub fn to_grayscale(mat: &impl MatTraitConst, add_contrast: bool) -> Mat {
     let mut mat = mat.try_clone().unwrap();
     unsafe {
         // SAFETY: all of the functions below can be called in place.
@@ -2481,6 +3337,64 @@ fn norm_rgb_to_input_value(mat: &impl MatTraitConst) -> SessionInputValue<'_> {
     SessionInputValue::Owned(tensor.clone().into_dyn())
 }
 
+#[inline]
+fn mob_model_bytes() -> Vec<u8> {
+    model_bytes(
+        config().models.mob.as_ref(),
+        include_bytes!(env!("MOB_MODEL")),
+    )
+}
+
+#[inline]
+fn minimap_model_bytes() -> Vec<u8> {
+    model_bytes(
+        config().models.minimap.as_ref(),
+        include_bytes!(env!("MINIMAP_MODEL")),
+    )
+}
+
+#[inline]
+fn rune_model_bytes() -> Vec<u8> {
+    model_bytes(
+        config().models.rune.as_ref(),
+        include_bytes!(env!("RUNE_MODEL")),
+    )
+}
+
+/// Builds a new session of `model` separate from the one used for detection and runs it once
+/// on `mat`.
+///
+/// The number of predictions uses the same score threshold as the detection of `model`.
+pub fn check_model(model: DetectionModel, mat: &impl MatTraitConst) -> Result<ModelCheck> {
+    let (bytes, score) = match model {
+        DetectionModel::Minimap => (minimap_model_bytes(), 0.7),
+        DetectionModel::Mob => (mob_model_bytes(), 0.5),
+        DetectionModel::Rune => (rune_model_bytes(), 0.8),
+    };
+
+    let instant = Instant::now();
+    let mut session = build_session(&bytes)?;
+    let load_time = instant.elapsed();
+
+    let (mat_in, _, _, _, _) = preprocess_for_yolo(mat);
+    let instant = Instant::now();
+    let result = session.run([norm_rgb_to_input_value(&mat_in)])?;
+    let inference_time = instant.elapsed();
+
+    let mat_out = from_output_value(&result);
+    let predictions = (0..mat_out.rows())
+        // SAFETY: 0..mat_out.rows() is within Mat bounds
+        .map(|i| unsafe { mat_out.at_row_unchecked::<f32>(i).unwrap() })
+        .filter(|pred| pred[4] >= score)
+        .count();
+
+    Ok(ModelCheck {
+        load_time,
+        inference_time,
+        predictions,
+    })
+}
+
 #[inline]
 fn build_session(model: &[u8]) -> Result<Session> {
     // TODO: ort supports fallback to CPU if GPU is not found. Check if missing GPU-related
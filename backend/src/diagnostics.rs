@@ -0,0 +1,222 @@
+use std::{
+    collections::VecDeque,
+    env, fs, panic,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex, TryLockError},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use log::{error, info};
+use opencv::{
+    core::{Mat, MatTraitConst, Size, Vector},
+    imgcodecs::imencode_def,
+    imgproc::{INTER_AREA, resize},
+};
+use serde::Serialize;
+
+use crate::GameState;
+
+/// Snapshots older than this are dropped from the history.
+const HISTORY_WINDOW: Duration = Duration::from_secs(30);
+
+/// Minimum duration between each snapshot.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Minimum duration between each snapshot that also records a frame.
+const FRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The scale of a recorded frame relative to the captured frame.
+const FRAME_SCALE: f64 = 0.25;
+
+static DIAGNOSTICS_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+    env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("diagnostics")
+});
+
+static HISTORY: LazyLock<Mutex<GameStateHistory>> =
+    LazyLock::new(|| Mutex::new(GameStateHistory::default()));
+
+#[derive(Clone, Debug, Serialize)]
+struct Snapshot {
+    /// The Unix timestamp in milliseconds of this snapshot.
+    timestamp_millis: u64,
+    #[serde(skip)]
+    instant: Instant,
+    #[serde(flatten)]
+    state: GameState,
+    /// The downscaled frame encoded as PNG only when dumped.
+    #[serde(skip)]
+    frame: Option<Mat>,
+}
+
+/// A ring buffer of the recent [`GameState`] snapshots.
+#[derive(Debug, Default)]
+struct GameStateHistory {
+    snapshots: VecDeque<Snapshot>,
+    last_frame: Option<Instant>,
+}
+
+impl GameStateHistory {
+    fn should_record(&self, now: Instant) -> bool {
+        self.snapshots
+            .back()
+            .is_none_or(|snapshot| now.duration_since(snapshot.instant) >= SNAPSHOT_INTERVAL)
+    }
+
+    fn should_record_frame(&self, now: Instant) -> bool {
+        self.last_frame
+            .is_none_or(|instant| now.duration_since(instant) >= FRAME_INTERVAL)
+    }
+
+    fn push(&mut self, now: Instant, state: GameState, frame: Option<Mat>) {
+        while self
+            .snapshots
+            .front()
+            .is_some_and(|snapshot| now.duration_since(snapshot.instant) > HISTORY_WINDOW)
+        {
+            self.snapshots.pop_front();
+        }
+        if frame.is_some() {
+            self.last_frame = Some(now);
+        }
+        self.snapshots.push_back(Snapshot {
+            timestamp_millis: unix_millis(),
+            instant: now,
+            state,
+            frame,
+        });
+    }
+}
+
+/// Writes `snapshots` and their frames to the new directory `dir`.
+fn write_snapshots(dir: &Path, snapshots: &VecDeque<Snapshot>) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(
+        dir.join("states.json"),
+        serde_json::to_string_pretty(snapshots)?,
+    )?;
+    for snapshot in snapshots {
+        if let Some(frame) = &snapshot.frame {
+            let mut bytes = Vector::new();
+            imencode_def(".png", frame, &mut bytes)?;
+            fs::write(
+                dir.join(format!("{}.png", snapshot.timestamp_millis)),
+                bytes.as_slice(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Records a [`GameState`] snapshot if enough time has passed since the last one.
+///
+/// `snapshot` is called with whether a frame should also be recorded.
+pub fn record(now: Instant, snapshot: impl FnOnce(bool) -> (GameState, Option<Mat>)) {
+    let mut history = HISTORY.lock().unwrap();
+    if !history.should_record(now) {
+        return;
+    }
+    let (state, frame) = snapshot(history.should_record_frame(now));
+    history.push(now, state, frame);
+}
+
+/// Dumps the recorded snapshots to disk in the background and returns the dumped directory.
+pub fn dump(reason: &str) -> PathBuf {
+    let dir = dump_dir(reason);
+    let snapshots = HISTORY.lock().unwrap().snapshots.clone();
+    let thread_dir = dir.clone();
+    thread::spawn(move || match write_snapshots(&thread_dir, &snapshots) {
+        Ok(()) => {
+            info!(target: "diagnostics", "dumped diagnostics to {}", thread_dir.display())
+        }
+        Err(err) => error!(target: "diagnostics", "failed to dump diagnostics {err}"),
+    });
+    dir
+}
+
+/// Dumps the recorded snapshots on panic before running the previous panic hook.
+pub fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // The panicking thread may be holding the lock
+        let history = match HISTORY.try_lock() {
+            Ok(history) => Some(history),
+            Err(TryLockError::Poisoned(err)) => Some(err.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        };
+        if let Some(history) = history {
+            let _ = write_snapshots(&dump_dir("panic"), &history.snapshots).inspect_err(
+                |err| error!(target: "diagnostics", "failed to dump diagnostics {err}"),
+            );
+        }
+        hook(info);
+    }));
+}
+
+/// Downscales `mat` for recording.
+pub fn downscale_frame(mat: &impl MatTraitConst) -> Option<Mat> {
+    let mut downscaled = Mat::default();
+    resize(
+        mat,
+        &mut downscaled,
+        Size::default(),
+        FRAME_SCALE,
+        FRAME_SCALE,
+        INTER_AREA,
+    )
+    .ok()?;
+    Some(downscaled)
+}
+
+#[inline]
+fn dump_dir(reason: &str) -> PathBuf {
+    DIAGNOSTICS_DIR.join(format!("{}-{reason}", unix_millis()))
+}
+
+#[inline]
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use opencv::core::Mat;
+
+    use super::{GameStateHistory, HISTORY_WINDOW, SNAPSHOT_INTERVAL};
+    use crate::GameState;
+
+    #[test]
+    fn push_drops_snapshots_outside_window() {
+        let now = Instant::now();
+        let mut history = GameStateHistory::default();
+        history.push(now, GameState::default(), Some(Mat::default()));
+        assert!(!history.should_record(now));
+        assert!(!history.should_record_frame(now));
+        assert!(history.should_record(now + SNAPSHOT_INTERVAL));
+
+        history.push(now + Duration::from_secs(1), GameState::default(), None);
+        history.push(
+            now + HISTORY_WINDOW + Duration::from_millis(1),
+            GameState::default(),
+            None,
+        );
+
+        assert_eq!(history.snapshots.len(), 2);
+        assert!(
+            history
+                .snapshots
+                .iter()
+                .all(|snapshot| snapshot.frame.is_none())
+        );
+    }
+}
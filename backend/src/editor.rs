@@ -0,0 +1,482 @@
+use anyhow::{Result, anyhow, bail};
+use opencv::core::Point;
+
+use crate::{
+    array::Array,
+    database::{
+        Action, ActionCondition, ActionGroupPredicate, ActionMouseRelativeTo, Bound, Minimap,
+        Position,
+    },
+    pathing::{
+        self, FindPointsArgs, MAX_PLATFORMS_COUNT, connect_ropes, find_neighbors, find_points_with,
+    },
+    player::{FLYING_MAX_THRESHOLD, MovementThresholds},
+};
+
+/// A problem with a preset found by [`validate_preset`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PresetIssue {
+    /// The minimap has no platforms to path find to positioned actions.
+    NoPlatforms,
+    /// The action at `index` has a `position` that is not on or near any platform.
+    MissingPlatform { index: usize, position: Position },
+    /// The action at `index` has a `position` that cannot be reached from `unreachable_count`
+    /// platforms.
+    Unreachable {
+        index: usize,
+        position: Position,
+        unreachable_count: usize,
+    },
+}
+
+/// Converts `point` on a canvas of `canvas_size` displaying a minimap of `minimap_size` to the
+/// minimap coordinate.
+///
+/// The canvas coordinate is top-left while the minimap coordinate is bottom-left. The returned
+/// point is clamped inside the minimap.
+pub fn canvas_to_minimap_point(
+    point: (f32, f32),
+    canvas_size: (f32, f32),
+    minimap_size: (i32, i32),
+) -> (i32, i32) {
+    let (canvas_width, canvas_height) = canvas_size;
+    let (width, height) = minimap_size;
+    if canvas_width <= 0.0 || canvas_height <= 0.0 || width <= 0 || height <= 0 {
+        return (0, 0);
+    }
+
+    let x = (point.0 / canvas_width * width as f32).round() as i32;
+    let y = height - (point.1 / canvas_height * height as f32).round() as i32;
+    (x.clamp(0, width), y.clamp(0, height))
+}
+
+/// Converts `point` in the minimap coordinate of `minimap_size` to a canvas of `canvas_size`.
+///
+/// This is the inverse of [`canvas_to_minimap_point`].
+pub fn minimap_to_canvas_point(
+    point: (i32, i32),
+    canvas_size: (f32, f32),
+    minimap_size: (i32, i32),
+) -> (f32, f32) {
+    let (canvas_width, canvas_height) = canvas_size;
+    let (width, height) = minimap_size;
+    if width <= 0 || height <= 0 {
+        return (0.0, 0.0);
+    }
+
+    let x = point.0 as f32 / width as f32 * canvas_width;
+    let y = (height - point.1) as f32 / height as f32 * canvas_height;
+    (x, y)
+}
+
+/// Reorders `actions` so that the action at `order[i]` becomes the `i`-th action.
+///
+/// Returns [`None`] if `order` is not a permutation of `actions` indices or if any
+/// [`ActionCondition::Linked`] action would no longer follow the action it is linked to.
+pub fn reorder_actions(actions: &[Action], order: &[usize]) -> Option<Vec<Action>> {
+    if order.len() != actions.len() {
+        return None;
+    }
+    let mut seen = vec![false; actions.len()];
+    for &index in order {
+        if *seen.get(index)? {
+            return None;
+        }
+        seen[index] = true;
+    }
+
+    // The index of the non-linked action each action is linked to
+    let mut heads = Vec::with_capacity(actions.len());
+    for (i, action) in actions.iter().enumerate() {
+        let head = if matches!(action.condition(), ActionCondition::Linked) {
+            *heads.last()?
+        } else {
+            i
+        };
+        heads.push(head);
+    }
+    for (i, &index) in order.iter().enumerate() {
+        if !matches!(actions[index].condition(), ActionCondition::Linked) {
+            continue;
+        }
+        let prev = order.get(i.checked_sub(1)?)?;
+        if heads[*prev] != heads[index] {
+            return None;
+        }
+    }
+
+    Some(order.iter().map(|&index| actions[index]).collect())
+}
+
+/// Duplicates `preset` of `minimap` as a new preset named `new_name`.
+pub fn duplicate_preset(minimap: &mut Minimap, preset: &str, new_name: String) -> Result<()> {
+    let actions = minimap
+        .actions
+        .get(preset)
+        .cloned()
+        .ok_or(anyhow!("preset {preset} does not exist"))?;
+    if minimap.actions.contains_key(&new_name) {
+        bail!("preset {new_name} already exists");
+    }
+
+    minimap.actions.insert(new_name, actions);
+    Ok(())
+}
+
+/// Copies `preset` of `from` to `to` under the same name.
+///
+/// Action positions are scaled from `from` dimensions to `to` dimensions.
+pub fn copy_preset(from: &Minimap, preset: &str, to: &mut Minimap) -> Result<()> {
+    let actions = from
+        .actions
+        .get(preset)
+        .ok_or(anyhow!("preset {preset} does not exist"))?;
+    if to.actions.contains_key(preset) {
+        bail!("preset {preset} already exists in {}", to.name);
+    }
+    if from.width <= 0 || from.height <= 0 {
+        bail!("map {} has invalid dimensions", from.name);
+    }
+
+    let from_size = (from.width, from.height);
+    let to_size = (to.width, to.height);
+    let actions = actions
+        .iter()
+        .map(|action| match *action {
+            Action::Move(mut action) => {
+                action.position = scale_position(action.position, from_size, to_size);
+                Action::Move(action)
+            }
+            Action::Key(mut action) => {
+                action.position = action
+                    .position
+                    .map(|position| scale_position(position, from_size, to_size));
+                Action::Key(action)
+            }
+            Action::Group(mut action) => {
+                if let ActionGroupPredicate::PlayerInRegion(bound) = action.predicate {
+                    action.predicate = ActionGroupPredicate::PlayerInRegion(scale_bound(
+                        bound, from_size, to_size,
+                    ));
+                }
+                Action::Group(action)
+            }
+            Action::Mouse(mut action) => {
+                if matches!(action.relative_to, ActionMouseRelativeTo::Minimap) {
+                    action.x = scale(action.x, from_size.0, to_size.0);
+                    action.y = scale(action.y, from_size.1, to_size.1);
+                }
+                Action::Mouse(action)
+            }
+        })
+        .collect();
+    to.actions.insert(preset.to_string(), actions);
+    Ok(())
+}
+
+/// Validates that every positioned action in `preset` of `minimap` can be reached by path finding
+/// from each of the minimap platforms.
+///
+/// Path finding assumes the player cannot fly. Returns an empty list if `preset` does not exist or
+/// has no issue.
+pub fn validate_preset(minimap: &Minimap, preset: &str) -> Vec<PresetIssue> {
+    let Some(actions) = minimap.actions.get(preset) else {
+        return vec![];
+    };
+    let positions = actions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, action)| match action {
+            Action::Move(action) => Some((index, action.position)),
+            Action::Key(action) => action.position.map(|position| (index, position)),
+            Action::Group(_) | Action::Mouse(_) => None,
+        })
+        .collect::<Vec<_>>();
+    if positions.is_empty() {
+        return vec![];
+    }
+    if minimap.platforms.is_empty() {
+        return vec![PresetIssue::NoPlatforms];
+    }
+
+    let thresholds = MovementThresholds::from(minimap.movement_overrides);
+    let mut platforms = find_neighbors(
+        &minimap
+            .platforms
+            .iter()
+            .copied()
+            .map(pathing::Platform::from)
+            .collect::<Vec<_>>(),
+        thresholds.double_jump,
+        thresholds.jump,
+        FLYING_MAX_THRESHOLD,
+    );
+    connect_ropes(
+        &mut platforms,
+        &minimap
+            .ropes
+            .iter()
+            .copied()
+            .map(pathing::Rope::from)
+            .collect::<Vec<_>>(),
+    );
+    let platforms = platforms
+        .into_iter()
+        .take(MAX_PLATFORMS_COUNT)
+        .collect::<Array<_, MAX_PLATFORMS_COUNT>>();
+
+    positions
+        .into_iter()
+        .filter_map(|(index, position)| {
+            let has_platform = minimap.platforms.iter().any(|platform| {
+                (platform.x_start..platform.x_end).contains(&position.x)
+                    && (platform.y - position.y).abs() < thresholds.jump
+            });
+            if !has_platform {
+                return Some(PresetIssue::MissingPlatform { index, position });
+            }
+
+            let to = Point::new(position.x, position.y);
+            let unreachable_count = platforms
+                .iter()
+                .filter(|platform| {
+                    let xs = platform.xs();
+                    let from = Point::new(xs.start + (xs.end - xs.start) / 2, platform.y());
+                    let args = FindPointsArgs {
+                        enable_hint: false,
+                        double_jump_threshold: thresholds.double_jump,
+                        jump_threshold: thresholds.jump,
+                        vertical_threshold: thresholds.grappling_max,
+                        flying: false,
+                        jump_down: true,
+                    };
+                    find_points_with(&platforms, from, to, args).is_none()
+                })
+                .count();
+            (unreachable_count > 0).then_some(PresetIssue::Unreachable {
+                index,
+                position,
+                unreachable_count,
+            })
+        })
+        .collect()
+}
+
+fn scale_position(position: Position, from_size: (i32, i32), to_size: (i32, i32)) -> Position {
+    Position {
+        x: scale(position.x, from_size.0, to_size.0).clamp(0, to_size.0),
+        x_random_range: scale(position.x_random_range, from_size.0, to_size.0),
+        y: scale(position.y, from_size.1, to_size.1).clamp(0, to_size.1),
+        ..position
+    }
+}
+
+fn scale_bound(bound: Bound, from_size: (i32, i32), to_size: (i32, i32)) -> Bound {
+    Bound {
+        x: scale(bound.x, from_size.0, to_size.0),
+        y: scale(bound.y, from_size.1, to_size.1),
+        width: scale(bound.width, from_size.0, to_size.0),
+        height: scale(bound.height, from_size.1, to_size.1),
+    }
+}
+
+#[inline]
+fn scale(value: i32, from: i32, to: i32) -> i32 {
+    (value as f32 * to as f32 / from as f32).round() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{
+        PresetIssue, canvas_to_minimap_point, copy_preset, duplicate_preset,
+        minimap_to_canvas_point, reorder_actions, validate_preset,
+    };
+    use crate::database::{
+        Action, ActionCondition, ActionGroup, ActionKey, ActionMove, Minimap, Platform, Position,
+    };
+
+    fn key(condition: ActionCondition) -> Action {
+        Action::Key(ActionKey {
+            condition,
+            ..ActionKey::default()
+        })
+    }
+
+    #[test]
+    fn canvas_to_minimap_point_round_trip_and_clamp() {
+        let canvas = (400.0, 100.0);
+        let minimap = (200, 50);
+
+        assert_eq!(
+            canvas_to_minimap_point((100.0, 20.0), canvas, minimap),
+            (50, 40)
+        );
+        assert_eq!(
+            minimap_to_canvas_point((50, 40), canvas, minimap),
+            (100.0, 20.0)
+        );
+        assert_eq!(
+            canvas_to_minimap_point((-5.0, 200.0), canvas, minimap),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn reorder_actions_moves_linked_actions_with_head() {
+        let actions = vec![
+            Action::Move(ActionMove::default()),
+            key(ActionCondition::Any),
+            key(ActionCondition::Linked),
+        ];
+
+        assert_eq!(
+            reorder_actions(&actions, &[1, 2, 0]),
+            Some(vec![actions[1], actions[2], actions[0]])
+        );
+        assert_eq!(reorder_actions(&actions, &[2, 1, 0]), None);
+        assert_eq!(reorder_actions(&actions, &[1, 0, 2]), None);
+        assert_eq!(reorder_actions(&actions, &[0, 0, 1]), None);
+        assert_eq!(reorder_actions(&actions, &[0, 1]), None);
+    }
+
+    #[test]
+    fn duplicate_preset_rejects_missing_or_existing() {
+        let mut minimap = Minimap {
+            actions: HashMap::from([("a".to_string(), vec![key(ActionCondition::Any)])]),
+            ..Minimap::default()
+        };
+
+        assert!(duplicate_preset(&mut minimap, "a", "b".to_string()).is_ok());
+        assert_eq!(minimap.actions["a"], minimap.actions["b"]);
+        assert!(duplicate_preset(&mut minimap, "a", "b".to_string()).is_err());
+        assert!(duplicate_preset(&mut minimap, "c", "d".to_string()).is_err());
+    }
+
+    #[test]
+    fn copy_preset_scales_positions() {
+        let position = Position {
+            x: 50,
+            x_random_range: 10,
+            y: 20,
+            allow_adjusting: true,
+        };
+        let from = Minimap {
+            width: 100,
+            height: 40,
+            actions: HashMap::from([(
+                "a".to_string(),
+                vec![
+                    Action::Move(ActionMove {
+                        position,
+                        ..ActionMove::default()
+                    }),
+                    key(ActionCondition::Any),
+                ],
+            )]),
+            ..Minimap::default()
+        };
+        let mut to = Minimap {
+            width: 200,
+            height: 20,
+            ..Minimap::default()
+        };
+
+        assert!(copy_preset(&from, "a", &mut to).is_ok());
+        assert_eq!(
+            to.actions["a"],
+            vec![
+                Action::Move(ActionMove {
+                    position: Position {
+                        x: 100,
+                        x_random_range: 20,
+                        y: 10,
+                        allow_adjusting: true,
+                    },
+                    ..ActionMove::default()
+                }),
+                key(ActionCondition::Any),
+            ]
+        );
+        assert!(copy_preset(&from, "a", &mut to).is_err());
+    }
+
+    #[test]
+    fn validate_preset_reports_missing_platforms_and_unreachable_positions() {
+        let position = |x, y| Position {
+            x,
+            y,
+            ..Position::default()
+        };
+        let move_to = |x, y| {
+            Action::Move(ActionMove {
+                position: position(x, y),
+                ..ActionMove::default()
+            })
+        };
+        let mut minimap = Minimap {
+            actions: HashMap::from([(
+                "a".to_string(),
+                vec![
+                    move_to(10, 40),
+                    key(ActionCondition::Any),
+                    move_to(210, 5),
+                    Action::Key(ActionKey {
+                        position: Some(position(50, 30)),
+                        ..ActionKey::default()
+                    }),
+                    Action::Group(ActionGroup::default()),
+                ],
+            )]),
+            ..Minimap::default()
+        };
+
+        assert_eq!(
+            validate_preset(&minimap, "a"),
+            vec![PresetIssue::NoPlatforms]
+        );
+        assert!(validate_preset(&minimap, "b").is_empty());
+
+        // The platform at x 200 is too far to double jump to or from
+        minimap.platforms = vec![
+            Platform {
+                x_start: 0,
+                x_end: 100,
+                y: 5,
+                ..Platform::default()
+            },
+            Platform {
+                x_start: 0,
+                x_end: 20,
+                y: 40,
+                ..Platform::default()
+            },
+            Platform {
+                x_start: 200,
+                x_end: 220,
+                y: 5,
+                ..Platform::default()
+            },
+        ];
+        assert_eq!(
+            validate_preset(&minimap, "a"),
+            vec![
+                PresetIssue::Unreachable {
+                    index: 0,
+                    position: position(10, 40),
+                    unreachable_count: 1,
+                },
+                PresetIssue::Unreachable {
+                    index: 2,
+                    position: position(210, 5),
+                    unreachable_count: 2,
+                },
+                PresetIssue::MissingPlatform {
+                    index: 3,
+                    position: position(50, 30),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use std::{error::Error, fmt};
+
+/// An error returned from the public backend API.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BackendError {
+    /// Reading from or writing to the database failed.
+    Database(String),
+    /// The main game loop or the database worker is no longer running.
+    ChannelClosed,
+    /// The desktop or the game window cannot be captured.
+    CaptureUnavailable,
+}
+
+impl BackendError {
+    #[inline]
+    pub(crate) fn database(error: anyhow::Error) -> Self {
+        BackendError::Database(error.to_string())
+    }
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::Database(error) => write!(f, "database error: {error}"),
+            BackendError::ChannelClosed => write!(f, "the bot is no longer running"),
+            BackendError::CaptureUnavailable => write!(f, "capture is unavailable"),
+        }
+    }
+}
+
+impl Error for BackendError {}
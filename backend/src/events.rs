@@ -0,0 +1,216 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+use crate::database::insert_event;
+
+/// A notable change in the bot or game state.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Event {
+    /// A normal or priority action provided by the rotator started.
+    ActionStarted {
+        id: u32,
+        name: String,
+    },
+    /// A normal or priority action is no longer being executed.
+    ActionCompleted {
+        id: u32,
+        name: String,
+    },
+    RuneSolved,
+    Death,
+    ChannelChanged,
+    /// The minimap was detected but can no longer be detected.
+    DetectionLost,
+    /// The active minimap was automatically switched to a stored minimap matching the detected
+    /// one or linked with the selected character.
+    MinimapSwitched {
+        id: i64,
+        name: String,
+        preset: Option<String>,
+    },
+    /// The actions of the current preset were edited while rotating and applied at the start of
+    /// the next rotation cycle.
+    PresetHotSwapped {
+        preset: String,
+    },
+    /// The current preset was switched to the next entry of the minimap preset schedule.
+    PresetScheduled {
+        preset: String,
+    },
+    /// The active character was switched to the one linked with the selected minimap.
+    CharacterSwitched {
+        id: i64,
+        name: String,
+    },
+    /// The game window can no longer be captured and the bot is suspended.
+    CaptureSuspended,
+    /// The game window is captured again after being suspended.
+    CaptureResumed,
+}
+
+/// An [`Event`] with the time it was emitted.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct EventRecord {
+    /// The id of the persisted record.
+    ///
+    /// This is [`None`] if the record failed to persist.
+    pub id: Option<i64>,
+    /// Unix timestamp in milliseconds.
+    pub timestamp_millis: u64,
+    pub event: Event,
+}
+
+/// The states [`EventBus`] derives [`Event`]s from by comparing between ticks.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct EventSnapshot {
+    /// The current normal action id and name.
+    pub normal_action: Option<(u32, String)>,
+    /// The current priority action id and name.
+    pub priority_action: Option<(u32, String)>,
+    pub has_rune_buff: bool,
+    pub is_dead: bool,
+    /// Whether the player is panicking to change channel.
+    pub is_changing_channel: bool,
+    pub is_minimap_idle: bool,
+}
+
+/// Emits typed [`Event`]s to subscribers and persists them to the database.
+#[derive(Debug)]
+pub struct EventBus {
+    last_snapshot: EventSnapshot,
+    sender: Sender<EventRecord>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            last_snapshot: EventSnapshot::default(),
+            sender: broadcast::channel(100).0,
+        }
+    }
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> Receiver<EventRecord> {
+        self.sender.subscribe()
+    }
+
+    /// Compares `snapshot` with the previous one and emits the resulting events.
+    ///
+    /// Returns the emitted events in order for the caller to react on.
+    pub fn update(&mut self, snapshot: EventSnapshot) -> Vec<Event> {
+        let events = diff_snapshots(&self.last_snapshot, &snapshot);
+        self.last_snapshot = snapshot;
+        for event in events.iter().cloned() {
+            self.emit(event);
+        }
+        events
+    }
+
+    /// Emits `event` directly without comparing snapshots.
+    pub fn emit(&self, event: Event) {
+        info!(target: "events", "{event:?}");
+        let timestamp_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let sender = self.sender.clone();
+        let persisted_event = event.clone();
+        // Sent once persisted so that the record has its id
+        insert_event(timestamp_millis, &event, move |id| {
+            let id = id
+                .inspect_err(|err| error!(target: "events", "failed to persist event {err}"))
+                .ok();
+            let _ = sender.send(EventRecord {
+                id,
+                timestamp_millis,
+                event: persisted_event,
+            });
+        });
+    }
+}
+
+fn diff_snapshots(prev: &EventSnapshot, next: &EventSnapshot) -> Vec<Event> {
+    let mut events = vec![];
+    for (prev_action, next_action) in [
+        (&prev.priority_action, &next.priority_action),
+        (&prev.normal_action, &next.normal_action),
+    ] {
+        if prev_action == next_action {
+            continue;
+        }
+        if let Some((id, name)) = prev_action.clone() {
+            events.push(Event::ActionCompleted { id, name });
+        }
+        if let Some((id, name)) = next_action.clone() {
+            events.push(Event::ActionStarted { id, name });
+        }
+    }
+    if !prev.has_rune_buff && next.has_rune_buff {
+        events.push(Event::RuneSolved);
+    }
+    if !prev.is_dead && next.is_dead {
+        events.push(Event::Death);
+    }
+    if prev.is_changing_channel && !next.is_changing_channel {
+        events.push(Event::ChannelChanged);
+    }
+    if prev.is_minimap_idle && !next.is_minimap_idle {
+        events.push(Event::DetectionLost);
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, EventSnapshot, diff_snapshots};
+
+    #[test]
+    fn diff_snapshots_action_replaced_and_state_transitions() {
+        let prev = EventSnapshot {
+            normal_action: Some((1, "Move".to_string())),
+            is_changing_channel: true,
+            is_minimap_idle: true,
+            ..EventSnapshot::default()
+        };
+        let next = EventSnapshot {
+            normal_action: Some((2, "Key".to_string())),
+            has_rune_buff: true,
+            is_dead: true,
+            ..EventSnapshot::default()
+        };
+
+        assert_eq!(
+            diff_snapshots(&prev, &next),
+            vec![
+                Event::ActionCompleted {
+                    id: 1,
+                    name: "Move".to_string()
+                },
+                Event::ActionStarted {
+                    id: 2,
+                    name: "Key".to_string()
+                },
+                Event::RuneSolved,
+                Event::Death,
+                Event::ChannelChanged,
+                Event::DetectionLost,
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_snapshots_empty_if_unchanged() {
+        let snapshot = EventSnapshot {
+            priority_action: Some((1, "SolveRune".to_string())),
+            is_minimap_idle: true,
+            ..EventSnapshot::default()
+        };
+
+        assert!(diff_snapshots(&snapshot, &snapshot.clone()).is_empty());
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow, bail};
+use log::{debug, error, info};
+use opencv::core::Point;
+
+use crate::database::{Following, FollowingRole};
+
+/// Interval to re-send an unchanged leader position so that followers can detect a stale leader.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Duration without receiving any position after which the leader is considered gone.
+const STALE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Interval between two connection attempts to the leader.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Maximum duration to block writing to the other followers on a slow follower.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(50);
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The last leader position received and the [`Instant`] it was received at.
+type LeaderPosition = Arc<Mutex<Option<(Point, Instant)>>>;
+
+#[derive(Debug)]
+enum Link {
+    Leader {
+        /// Sends positions to the background thread writing to the followers.
+        positions: Sender<Point>,
+        /// The last position sent and the [`Instant`] it was sent at.
+        last_sent: Option<(Point, Instant)>,
+    },
+    Follower {
+        leader: LeaderPosition,
+    },
+}
+
+/// Shares the player minimap position of a leader instance with follower instances over a
+/// line-based TCP channel.
+///
+/// Each line is the `x y` minimap position of the leader player.
+#[derive(Debug, Default)]
+pub struct FollowingLink {
+    /// The role and address the current link is started with.
+    started: Option<(FollowingRole, String)>,
+    link: Option<Link>,
+    /// Signals the background thread of the current link to stop.
+    stop: Arc<AtomicBool>,
+}
+
+impl FollowingLink {
+    /// Sends `position` to the followers as the leader or returns the latest leader position
+    /// as the follower.
+    ///
+    /// The link is restarted whenever the role or address of `following` changes.
+    pub fn update(
+        &mut self,
+        following: &Following,
+        position: Option<Point>,
+        now: Instant,
+    ) -> Option<Point> {
+        if self
+            .started
+            .as_ref()
+            .is_none_or(|(role, address)| *role != following.role || *address != following.address)
+        {
+            self.restart(following.role, following.address.clone());
+        }
+
+        match self.link.as_mut()? {
+            Link::Leader {
+                positions,
+                last_sent,
+            } => {
+                let position = position?;
+                if last_sent.is_some_and(|(last, instant)| {
+                    last == position && now.duration_since(instant) < HEARTBEAT_INTERVAL
+                }) {
+                    return None;
+                }
+                *last_sent = Some((position, now));
+                let _ = positions.send(position);
+                None
+            }
+            Link::Follower { leader } => leader
+                .lock()
+                .unwrap()
+                .filter(|(_, instant)| now.saturating_duration_since(*instant) < STALE_TIMEOUT)
+                .map(|(position, _)| position),
+        }
+    }
+
+    fn restart(&mut self, role: FollowingRole, address: String) {
+        self.stop.store(true, Ordering::Release);
+        self.stop = Arc::new(AtomicBool::new(false));
+        self.link = match role {
+            FollowingRole::Off => None,
+            FollowingRole::Leader => match listen(&address, self.stop.clone()) {
+                Ok(positions) => Some(Link::Leader {
+                    positions,
+                    last_sent: None,
+                }),
+                Err(err) => {
+                    error!(target: "following", "failed to listen on {address} {err}");
+                    None
+                }
+            },
+            FollowingRole::Follower => Some(Link::Follower {
+                leader: connect(address.clone(), self.stop.clone()),
+            }),
+        };
+        self.started = Some((role, address));
+    }
+}
+
+impl Drop for FollowingLink {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+    }
+}
+
+/// Whether the player at `position` is far enough from `leader` to move toward it.
+pub fn should_follow(position: Point, leader: Point, distance: u32) -> bool {
+    let distance = distance as i32;
+    (position.x - leader.x).abs() > distance || (position.y - leader.y).abs() > distance
+}
+
+/// Accepts followers on `address` in a background thread until `stop` is set.
+///
+/// Returns the [`Sender`] for positions that are written to the followers in another background
+/// thread so that a slow follower does not block the update loop.
+fn listen(address: &str, stop: Arc<AtomicBool>) -> Result<Sender<Point>> {
+    let listener = TcpListener::bind(address)?;
+    listener.set_nonblocking(true)?;
+    info!(target: "following", "leading followers on {address}");
+
+    let followers = Arc::new(Mutex::new(Vec::<TcpStream>::new()));
+    let accepted = followers.clone();
+    let (tx, rx) = mpsc::channel::<Point>();
+    thread::spawn(move || {
+        // Stops when the sender is dropped on restart
+        for position in rx {
+            let line = format_position(position);
+            followers
+                .lock()
+                .unwrap()
+                .retain_mut(|stream| stream.write_all(line.as_bytes()).is_ok());
+        }
+    });
+    thread::spawn(move || {
+        while !stop.load(Ordering::Acquire) {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    if let Err(err) = stream
+                        .set_nonblocking(false)
+                        .and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+                    {
+                        debug!(target: "following", "failed to set up follower {peer} {err}");
+                        continue;
+                    }
+                    info!(target: "following", "follower {peer} connected");
+                    accepted.lock().unwrap().push(stream);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(ACCEPT_POLL_INTERVAL)
+                }
+                Err(err) => {
+                    error!(target: "following", "failed to accept follower {err}");
+                    thread::sleep(ACCEPT_POLL_INTERVAL);
+                }
+            }
+        }
+    });
+    Ok(tx)
+}
+
+/// Connects and reconnects to the leader on `address` in a background thread until `stop` is
+/// set.
+fn connect(address: String, stop: Arc<AtomicBool>) -> LeaderPosition {
+    let leader = LeaderPosition::default();
+    let received = leader.clone();
+    thread::spawn(move || {
+        while !stop.load(Ordering::Acquire) {
+            if let Err(err) = follow(&address, &stop, &received) {
+                debug!(target: "following", "leader connection on {address} failed {err}");
+            }
+            thread::sleep(RECONNECT_INTERVAL);
+        }
+    });
+    leader
+}
+
+fn follow(address: &str, stop: &AtomicBool, leader: &LeaderPosition) -> Result<()> {
+    let socket = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or(anyhow!("no socket address"))?;
+    let stream = TcpStream::connect_timeout(&socket, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(STALE_TIMEOUT))?;
+    info!(target: "following", "following leader on {address}");
+
+    let mut lines = BufReader::new(stream).lines();
+    while !stop.load(Ordering::Acquire) {
+        let Some(line) = lines.next() else {
+            bail!("leader disconnected");
+        };
+        if let Some(position) = parse_position(&line?) {
+            *leader.lock().unwrap() = Some((position, Instant::now()));
+        }
+    }
+    Ok(())
+}
+
+#[inline]
+fn format_position(position: Point) -> String {
+    format!("{} {}\n", position.x, position.y)
+}
+
+fn parse_position(line: &str) -> Option<Point> {
+    let (x, y) = line.trim().split_once(' ')?;
+    Some(Point::new(x.parse().ok()?, y.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_position_round_trip() {
+        let position = Point::new(120, -5);
+
+        assert_eq!(parse_position(&format_position(position)), Some(position));
+        assert_eq!(parse_position("12"), None);
+        assert_eq!(parse_position("a 1"), None);
+    }
+
+    #[test]
+    fn should_follow_outside_distance() {
+        let leader = Point::new(100, 20);
+
+        assert!(!should_follow(Point::new(95, 20), leader, 5));
+        assert!(should_follow(Point::new(94, 20), leader, 5));
+        assert!(should_follow(Point::new(100, 30), leader, 5));
+    }
+}
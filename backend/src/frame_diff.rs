@@ -0,0 +1,107 @@
+use opencv::{
+    core::{Mat, MatTraitConst, Rect, absdiff, count_non_zero},
+    imgproc::{COLOR_BGRA2GRAY, THRESH_BINARY, cvt_color_def, threshold},
+};
+
+/// Minimum grayscale difference for a pixel to be considered changed.
+const PIXEL_DIFF_THRESHOLD: f64 = 20.0;
+
+/// Maximum number of consecutive frames considered unchanged.
+///
+/// Forces a re-detection every so often in case small changes accumulate.
+const MAX_UNCHANGED_COUNT: u32 = 30;
+
+/// Tracks the minimap region across frames to skip re-detection when it has not changed.
+#[derive(Debug, Default)]
+pub struct FrameDiff {
+    /// The minimap bounding box and its region of the last changed frame.
+    reference: Option<(Rect, Mat)>,
+    /// The number of consecutive frames considered unchanged.
+    unchanged_count: u32,
+}
+
+impl FrameDiff {
+    /// Updates with the region `minimap` of the current frame `mat`.
+    ///
+    /// Returns `true` if the region has not changed since the last changed frame.
+    pub fn update(&mut self, mat: &impl MatTraitConst, minimap: Option<Rect>) -> bool {
+        let Some((bbox, region)) = minimap.and_then(|bbox| {
+            mat.roi(bbox)
+                .ok()
+                .map(|region| (bbox, region.clone_pointee()))
+        }) else {
+            *self = FrameDiff::default();
+            return false;
+        };
+
+        let unchanged = self.unchanged_count < MAX_UNCHANGED_COUNT
+            && self
+                .reference
+                .as_ref()
+                .is_some_and(|(reference_bbox, reference)| {
+                    *reference_bbox == bbox && is_unchanged(reference, &region)
+                });
+        if unchanged {
+            self.unchanged_count += 1;
+        } else {
+            self.reference = Some((bbox, region));
+            self.unchanged_count = 0;
+        }
+        unchanged
+    }
+}
+
+#[inline]
+fn is_unchanged(reference: &Mat, current: &Mat) -> bool {
+    let mut diff = Mat::default();
+    let mut gray = Mat::default();
+    let mut mask = Mat::default();
+    absdiff(reference, current, &mut diff)
+        .and_then(|_| cvt_color_def(&diff, &mut gray, COLOR_BGRA2GRAY))
+        .and_then(|_| threshold(&gray, &mut mask, PIXEL_DIFF_THRESHOLD, 255.0, THRESH_BINARY))
+        .and_then(|_| count_non_zero(&mask))
+        .is_ok_and(|count| count == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use opencv::core::{CV_8UC4, Mat, MatTrait, Rect, Scalar, Vec4b};
+
+    use super::{FrameDiff, MAX_UNCHANGED_COUNT};
+
+    fn mat() -> Mat {
+        Mat::new_rows_cols_with_default(50, 50, CV_8UC4, Scalar::all(0.0)).unwrap()
+    }
+
+    #[test]
+    fn update_unchanged_until_region_changes() {
+        let minimap = Some(Rect::new(0, 0, 20, 20));
+        let mut diff = FrameDiff::default();
+        let mut frame = mat();
+
+        assert!(!diff.update(&frame, minimap));
+        assert!(diff.update(&frame, minimap));
+
+        // Outside of minimap
+        *frame.at_2d_mut::<Vec4b>(30, 30).unwrap() = Vec4b::all(255);
+        assert!(diff.update(&frame, minimap));
+
+        *frame.at_2d_mut::<Vec4b>(10, 10).unwrap() = Vec4b::all(255);
+        assert!(!diff.update(&frame, minimap));
+        assert!(diff.update(&frame, minimap));
+        assert!(!diff.update(&frame, None));
+    }
+
+    #[test]
+    fn update_forces_changed_after_max_unchanged_count() {
+        let minimap = Some(Rect::new(0, 0, 20, 20));
+        let mut diff = FrameDiff::default();
+        let frame = mat();
+
+        assert!(!diff.update(&frame, minimap));
+        for _ in 0..MAX_UNCHANGED_COUNT {
+            assert!(diff.update(&frame, minimap));
+        }
+        assert!(!diff.update(&frame, minimap));
+    }
+}
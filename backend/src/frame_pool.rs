@@ -0,0 +1,141 @@
+use std::{
+    collections::VecDeque,
+    mem,
+    sync::{Arc, Mutex},
+};
+
+/// The number of frame buffers owned by a [`FramePool`].
+///
+/// A frame is held by the update loop for the current tick and by the detection tasks still
+/// running on it. When more frames are held, the buffer of the oldest one is dropped instead of
+/// returned once its consumers are done with it.
+pub const FRAME_POOL_CAPACITY: usize = 4;
+
+#[derive(Debug, Default)]
+struct FramePoolInner {
+    /// The buffers available for capturing the next frame.
+    free: Vec<Vec<u8>>,
+    /// The ids of the pooled buffers currently held by frames from oldest to newest.
+    in_use: VecDeque<u64>,
+    next_id: u64,
+}
+
+/// A fixed pool of reusable frame buffers handed off from capture to detection.
+///
+/// Capturing at high FPS would otherwise allocate a fresh buffer per frame. A buffer is returned to
+/// the pool when the [`PooledBuffer`] holding it is dropped.
+///
+/// When the consumers lag and all buffers are in use, the oldest buffer is detached from the pool
+/// so that it is dropped instead of returned and the pool never grows beyond
+/// [`FRAME_POOL_CAPACITY`].
+#[derive(Clone, Debug, Default)]
+pub struct FramePool {
+    inner: Arc<Mutex<FramePoolInner>>,
+}
+
+impl FramePool {
+    /// Takes a buffer for capturing the next frame.
+    pub fn acquire(&self) -> PooledBuffer {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.in_use.len() >= FRAME_POOL_CAPACITY {
+            inner.in_use.pop_front();
+        }
+
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.in_use.push_back(id);
+        PooledBuffer {
+            data: inner.free.pop().unwrap_or_default(),
+            pool: Some((self.clone(), id)),
+        }
+    }
+
+    fn release(&self, id: u64, data: Vec<u8>) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(index) = inner.in_use.iter().position(|in_use| *in_use == id) else {
+            return;
+        };
+        inner.in_use.remove(index);
+        inner.free.push(data);
+    }
+}
+
+/// A frame buffer taken from a [`FramePool`].
+#[derive(Debug, Default)]
+pub struct PooledBuffer {
+    data: Vec<u8>,
+    /// The pool to return the buffer to and the buffer id or [`None`] if it is not pooled.
+    pool: Option<(FramePool, u64)>,
+}
+
+impl PooledBuffer {
+    /// The underlying buffer to capture into.
+    #[inline]
+    pub fn data_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.data
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl From<Vec<u8>> for PooledBuffer {
+    fn from(data: Vec<u8>) -> Self {
+        Self { data, pool: None }
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if let Some((pool, id)) = self.pool.take() {
+            pool.release(id, mem::take(&mut self.data));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_use(pool: &FramePool) -> usize {
+        pool.inner.lock().unwrap().in_use.len()
+    }
+
+    #[test]
+    fn acquire_reuses_released_buffer() {
+        let pool = FramePool::default();
+        let mut buffer = pool.acquire();
+        *buffer.data_mut() = Vec::with_capacity(64);
+        assert_eq!(in_use(&pool), 1);
+
+        drop(buffer);
+        assert_eq!(in_use(&pool), 0);
+
+        let mut buffer = pool.acquire();
+        assert!(buffer.data_mut().capacity() >= 64);
+    }
+
+    #[test]
+    fn acquire_detaches_oldest_buffer_when_exhausted() {
+        let pool = FramePool::default();
+        let mut buffers = (0..FRAME_POOL_CAPACITY)
+            .map(|_| pool.acquire())
+            .collect::<Vec<_>>();
+        assert_eq!(in_use(&pool), FRAME_POOL_CAPACITY);
+
+        let newest = pool.acquire();
+        assert_eq!(in_use(&pool), FRAME_POOL_CAPACITY);
+
+        // The oldest buffer is dropped instead of returned
+        drop(buffers.remove(0));
+        assert_eq!(in_use(&pool), FRAME_POOL_CAPACITY);
+        assert!(pool.inner.lock().unwrap().free.is_empty());
+
+        drop(buffers);
+        drop(newest);
+        assert_eq!(in_use(&pool), 0);
+        assert_eq!(pool.inner.lock().unwrap().free.len(), FRAME_POOL_CAPACITY);
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    collections::VecDeque,
+    sync::{LazyLock, Mutex},
+};
+
+use anyhow::Result;
+
+use crate::database::{self, Minimap};
+
+/// Maximum number of edits that can be undone.
+const MAX_HISTORY_COUNT: usize = 100;
+
+static HISTORY: LazyLock<Mutex<EditHistory>> =
+    LazyLock::new(|| Mutex::new(EditHistory::new(MAX_HISTORY_COUNT)));
+
+/// A persisted [`Minimap`] edit that can be inverted.
+#[derive(Clone, PartialEq, Debug)]
+enum Edit {
+    /// `after` is upserted over `before`, which is [`None`] if `after` is newly created.
+    Upsert {
+        before: Option<Minimap>,
+        after: Minimap,
+    },
+    Delete(Minimap),
+}
+
+impl Edit {
+    fn inverse(&self) -> Edit {
+        match self {
+            Edit::Upsert {
+                before: Some(before),
+                after,
+            } => Edit::Upsert {
+                before: Some(after.clone()),
+                after: before.clone(),
+            },
+            Edit::Upsert {
+                before: None,
+                after,
+            } => Edit::Delete(after.clone()),
+            Edit::Delete(minimap) => Edit::Upsert {
+                before: None,
+                after: minimap.clone(),
+            },
+        }
+    }
+
+    fn minimap_id(&self) -> Option<i64> {
+        match self {
+            Edit::Upsert { after, .. } => after.id,
+            Edit::Delete(minimap) => minimap.id,
+        }
+    }
+
+    fn apply(&self) -> Result<()> {
+        match self {
+            Edit::Upsert { after, .. } => database::upsert_minimap(&mut after.clone()),
+            Edit::Delete(minimap) => database::delete_minimap(minimap),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EditHistory {
+    undos: VecDeque<Edit>,
+    redos: Vec<Edit>,
+    capacity: usize,
+}
+
+impl EditHistory {
+    fn new(capacity: usize) -> Self {
+        Self {
+            undos: VecDeque::with_capacity(capacity),
+            redos: vec![],
+            capacity,
+        }
+    }
+
+    fn record(&mut self, edit: Edit) {
+        if self.undos.len() >= self.capacity {
+            self.undos.pop_front();
+        }
+        self.undos.push_back(edit);
+        self.redos.clear();
+    }
+
+    /// Applies the inverse of the last recorded edit with `apply`.
+    ///
+    /// The edit is kept for undoing if `apply` fails.
+    fn undo(&mut self, apply: impl FnOnce(&Edit) -> Result<()>) -> Result<Option<Edit>> {
+        let Some(edit) = self.undos.pop_back() else {
+            return Ok(None);
+        };
+        let inverse = edit.inverse();
+        if let Err(err) = apply(&inverse) {
+            self.undos.push_back(edit);
+            return Err(err);
+        }
+        self.redos.push(edit);
+        Ok(Some(inverse))
+    }
+
+    /// Re-applies the last undone edit with `apply`.
+    fn redo(&mut self, apply: impl FnOnce(&Edit) -> Result<()>) -> Result<Option<Edit>> {
+        let Some(edit) = self.redos.pop() else {
+            return Ok(None);
+        };
+        if let Err(err) = apply(&edit) {
+            self.redos.push(edit);
+            return Err(err);
+        }
+        self.undos.push_back(edit.clone());
+        Ok(Some(edit))
+    }
+}
+
+/// Upserts `minimap` to the database and records the edit for undoing.
+pub fn upsert_minimap(minimap: &mut Minimap) -> Result<()> {
+    let before = match minimap.id {
+        Some(id) => database::query_minimaps()?
+            .into_iter()
+            .find(|minimap| minimap.id == Some(id)),
+        None => None,
+    };
+    database::upsert_minimap(minimap)?;
+    if before.as_ref() != Some(minimap) {
+        HISTORY.lock().unwrap().record(Edit::Upsert {
+            before,
+            after: minimap.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Deletes `minimap` from the database and records the edit for undoing.
+pub fn delete_minimap(minimap: &Minimap) -> Result<()> {
+    database::delete_minimap(minimap)?;
+    HISTORY
+        .lock()
+        .unwrap()
+        .record(Edit::Delete(minimap.clone()));
+    Ok(())
+}
+
+/// Undoes the last minimap edit.
+///
+/// Returns the id of the affected minimap or [`None`] if there is nothing to undo.
+pub fn undo() -> Result<Option<i64>> {
+    Ok(HISTORY
+        .lock()
+        .unwrap()
+        .undo(Edit::apply)?
+        .and_then(|edit| edit.minimap_id()))
+}
+
+/// Redoes the last undone minimap edit.
+///
+/// Returns the id of the affected minimap or [`None`] if there is nothing to redo.
+pub fn redo() -> Result<Option<i64>> {
+    Ok(HISTORY
+        .lock()
+        .unwrap()
+        .redo(Edit::apply)?
+        .and_then(|edit| edit.minimap_id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::{Edit, EditHistory};
+    use crate::database::Minimap;
+
+    fn minimap(name: &str) -> Minimap {
+        Minimap {
+            id: Some(1),
+            name: name.to_string(),
+            ..Minimap::default()
+        }
+    }
+
+    #[test]
+    fn undo_redo_applies_inverse_then_original() {
+        let mut history = EditHistory::new(1);
+        let create = Edit::Upsert {
+            before: None,
+            after: minimap("a"),
+        };
+        let rename = Edit::Upsert {
+            before: Some(minimap("a")),
+            after: minimap("b"),
+        };
+        history.record(create);
+        history.record(rename.clone());
+
+        let mut applied = vec![];
+        assert_eq!(
+            history
+                .undo(|edit| {
+                    applied.push(edit.clone());
+                    Ok(())
+                })
+                .unwrap(),
+            Some(rename.inverse())
+        );
+        assert_eq!(history.undo(|_| Ok(())).unwrap(), None);
+        assert_eq!(
+            history
+                .redo(|edit| {
+                    applied.push(edit.clone());
+                    Ok(())
+                })
+                .unwrap(),
+            Some(rename.clone())
+        );
+        assert_eq!(applied, vec![rename.inverse(), rename]);
+    }
+
+    #[test]
+    fn undo_keeps_edit_on_failure_and_record_clears_redo() {
+        let mut history = EditHistory::new(10);
+        history.record(Edit::Delete(minimap("a")));
+
+        assert!(history.undo(|_| Err(anyhow!("failed"))).is_err());
+        assert_eq!(history.undos.len(), 1);
+        assert!(history.undo(|_| Ok(())).unwrap().is_some());
+        assert_eq!(history.redos.len(), 1);
+
+        history.record(Edit::Delete(minimap("b")));
+        assert!(history.redos.is_empty());
+        assert_eq!(
+            history.undo(|_| Ok(())).unwrap(),
+            Some(Edit::Upsert {
+                before: None,
+                after: minimap("b"),
+            })
+        );
+    }
+}
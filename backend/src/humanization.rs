@@ -0,0 +1,144 @@
+use crate::{database::HumanizationProfile, rng::Rng};
+
+/// The tunable parameters of a [`HumanizationProfile`].
+#[derive(Clone, Copy, Debug)]
+struct HumanizationParams {
+    /// Maximum milliseconds added or removed from a key press duration.
+    jitter_ms: f32,
+    /// Probability of a key press being followed by a micro-pause.
+    micro_pause_probability: f64,
+    /// Milliseconds range of a micro-pause.
+    micro_pause_ms: (f32, f32),
+    /// Maximum perpendicular offset of the mouse path relative to the travel distance.
+    mouse_curve_ratio: f32,
+    /// Number of intermediate mouse moves before reaching the destination.
+    mouse_steps: u32,
+}
+
+impl HumanizationParams {
+    fn from_profile(profile: HumanizationProfile) -> Option<Self> {
+        match profile {
+            HumanizationProfile::Off => None,
+            HumanizationProfile::Light => Some(Self {
+                jitter_ms: 10.0,
+                micro_pause_probability: 0.02,
+                micro_pause_ms: (30.0, 90.0),
+                mouse_curve_ratio: 0.1,
+                mouse_steps: 6,
+            }),
+            HumanizationProfile::Heavy => Some(Self {
+                jitter_ms: 25.0,
+                micro_pause_probability: 0.06,
+                micro_pause_ms: (60.0, 200.0),
+                mouse_curve_ratio: 0.25,
+                mouse_steps: 12,
+            }),
+        }
+    }
+}
+
+/// Applies human-like randomness to key press durations and mouse movements.
+#[derive(Debug, Default)]
+pub struct Humanization {
+    params: Option<HumanizationParams>,
+}
+
+impl Humanization {
+    pub fn set_profile(&mut self, profile: HumanizationProfile) {
+        self.params = HumanizationParams::from_profile(profile);
+    }
+
+    /// Jitters the key press duration `ms` and occasionally extends it with a micro-pause.
+    ///
+    /// The returned duration is never less than `min_ms`.
+    pub fn key_press_ms(&self, rng: &Rng, ms: f32, min_ms: f32) -> f32 {
+        let Some(params) = self.params else {
+            return ms;
+        };
+
+        let jitter = rng.random_range(-params.jitter_ms..=params.jitter_ms);
+        let pause = if rng.random_bool(params.micro_pause_probability) {
+            rng.random_range(params.micro_pause_ms.0..=params.micro_pause_ms.1)
+        } else {
+            0.0
+        };
+        (ms + jitter).max(min_ms) + pause
+    }
+
+    /// Generates a curved mouse path from `from` to `to` excluding `from` and including `to`.
+    ///
+    /// The path follows a quadratic Bézier curve with a random control point. If the profile is
+    /// [`HumanizationProfile::Off`], the path only contains `to`.
+    pub fn mouse_path(&self, rng: &Rng, from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+        let Some(params) = self.params else {
+            return vec![to];
+        };
+        let (dx, dy) = ((to.0 - from.0) as f32, (to.1 - from.1) as f32);
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance < 1.0 {
+            return vec![to];
+        }
+
+        let offset_max = distance * params.mouse_curve_ratio;
+        let offset = rng.random_range(-offset_max..=offset_max);
+        let (mid_x, mid_y) = (from.0 as f32 + dx / 2.0, from.1 as f32 + dy / 2.0);
+        // Offsets the control point perpendicular to the travel direction
+        let control = (
+            mid_x - dy / distance * offset,
+            mid_y + dx / distance * offset,
+        );
+
+        let mut path = (1..params.mouse_steps)
+            .map(|step| {
+                let t = step as f32 / params.mouse_steps as f32;
+                let u = 1.0 - t;
+                let x = u * u * from.0 as f32 + 2.0 * u * t * control.0 + t * t * to.0 as f32;
+                let y = u * u * from.1 as f32 + 2.0 * u * t * control.1 + t * t * to.1 as f32;
+                (x.round() as i32, y.round() as i32)
+            })
+            .collect::<Vec<_>>();
+        path.dedup();
+        path.push(to);
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Humanization;
+    use crate::{database::HumanizationProfile, rng::Rng};
+
+    const SEED: [u8; 32] = [
+        64, 241, 206, 219, 49, 21, 218, 145, 254, 152, 68, 176, 242, 238, 152, 14, 176, 241, 153,
+        64, 44, 192, 172, 191, 191, 157, 107, 206, 193, 55, 115, 68,
+    ];
+
+    #[test]
+    fn key_press_ms_and_mouse_path_unchanged_if_off() {
+        let rng = Rng::new(SEED);
+        let mut humanization = Humanization::default();
+        humanization.set_profile(HumanizationProfile::Off);
+
+        assert_eq!(humanization.key_press_ms(&rng, 100.0, 80.0), 100.0);
+        assert_eq!(
+            humanization.mouse_path(&rng, (0, 0), (100, 100)),
+            vec![(100, 100)]
+        );
+    }
+
+    #[test]
+    fn key_press_ms_above_min_and_mouse_path_ends_at_destination() {
+        let rng = Rng::new(SEED);
+        let mut humanization = Humanization::default();
+        humanization.set_profile(HumanizationProfile::Heavy);
+
+        for _ in 0..100 {
+            let ms = humanization.key_press_ms(&rng, 100.0, 80.0);
+            assert!(ms >= 80.0);
+
+            let path = humanization.mouse_path(&rng, (0, 0), (200, 50));
+            assert!(path.len() > 1);
+            assert_eq!(path.last(), Some(&(200, 50)));
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::time::{Duration, Instant};
+
+use crate::database::ItineraryStep;
+
+/// Maximum duration to reach the next step's minimap after leaving.
+const TRAVEL_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ItineraryStage {
+    Farming,
+    Traveling,
+}
+
+/// The result of [`ItineraryRunner::update`] for the caller to act on.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ItineraryUpdate {
+    /// The step at the index has elapsed and its minimap should be left.
+    Leave(usize),
+    /// The next step's minimap was not reached in time.
+    TimedOut,
+}
+
+/// Tracks the current step of an [`Itinerary`](crate::database::Itinerary).
+#[derive(Debug, Default)]
+pub struct ItineraryRunner {
+    /// The current step index, its stage and the [`Instant`] the stage started.
+    current: Option<(usize, ItineraryStage, Instant)>,
+}
+
+impl ItineraryRunner {
+    /// Starts farming the step at `index`.
+    pub fn start(&mut self, index: usize, now: Instant) {
+        self.current = Some((index, ItineraryStage::Farming, now));
+    }
+
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// The index of the step being traveled to.
+    pub fn traveling(&self) -> Option<usize> {
+        match self.current {
+            Some((index, ItineraryStage::Traveling, _)) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Advances the current step of `steps`.
+    ///
+    /// When the current step has elapsed, the runner starts traveling to the next step.
+    pub fn update(&mut self, steps: &[ItineraryStep], now: Instant) -> Option<ItineraryUpdate> {
+        let (index, stage, started) = self.current?;
+        let elapsed = now.saturating_duration_since(started);
+        match stage {
+            ItineraryStage::Farming => {
+                let step = steps.get(index)?;
+                if steps.len() < 2 || elapsed < Duration::from_millis(step.duration_millis) {
+                    return None;
+                }
+                self.current = Some(((index + 1) % steps.len(), ItineraryStage::Traveling, now));
+                Some(ItineraryUpdate::Leave(index))
+            }
+            ItineraryStage::Traveling => {
+                if elapsed < TRAVEL_TIMEOUT {
+                    return None;
+                }
+                self.current = None;
+                Some(ItineraryUpdate::TimedOut)
+            }
+        }
+    }
+
+    /// Starts farming the step being traveled to.
+    pub fn arrive(&mut self, now: Instant) {
+        if let Some(index) = self.traveling() {
+            self.start(index, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{ItineraryRunner, ItineraryUpdate, TRAVEL_TIMEOUT};
+    use crate::database::ItineraryStep;
+
+    fn steps() -> Vec<ItineraryStep> {
+        vec![
+            ItineraryStep {
+                duration_millis: 1000,
+                ..ItineraryStep::default()
+            },
+            ItineraryStep {
+                duration_millis: 2000,
+                ..ItineraryStep::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn update_leaves_elapsed_step_and_wraps_around() {
+        let steps = steps();
+        let now = Instant::now();
+        let mut runner = ItineraryRunner::default();
+        runner.start(1, now);
+
+        assert_eq!(
+            runner.update(&steps, now + Duration::from_millis(1500)),
+            None
+        );
+        assert_eq!(
+            runner.update(&steps, now + Duration::from_millis(2000)),
+            Some(ItineraryUpdate::Leave(1))
+        );
+        assert_eq!(runner.traveling(), Some(0));
+
+        runner.arrive(now);
+        assert_eq!(runner.traveling(), None);
+        assert_eq!(
+            runner.update(&steps, now + Duration::from_millis(1000)),
+            Some(ItineraryUpdate::Leave(0))
+        );
+    }
+
+    #[test]
+    fn update_times_out_traveling() {
+        let steps = steps();
+        let now = Instant::now();
+        let mut runner = ItineraryRunner::default();
+        runner.start(0, now);
+        runner.update(&steps, now + Duration::from_millis(1000));
+
+        assert_eq!(runner.update(&steps, now + Duration::from_secs(60)), None);
+        assert_eq!(
+            runner.update(&steps, now + Duration::from_millis(1000) + TRAVEL_TIMEOUT),
+            Some(ItineraryUpdate::TimedOut)
+        );
+        assert!(!runner.is_running());
+    }
+}
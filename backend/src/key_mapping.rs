@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
+#[cfg(target_os = "macos")]
+use platforms::macos::KeyKind;
+#[cfg(windows)]
+use platforms::windows::KeyKind;
+
+use crate::database::{KeyOverride, KeyboardLayout};
+
+/// Translates keys named by their position on a QWERTY keyboard to the keys at the same position
+/// on the user [`KeyboardLayout`].
+///
+/// Keys are sent by the platform through the user layout so without translating, a key such as
+/// [`KeyKind::A`] is pressed at the `Q` position on AZERTY.
+#[derive(Debug, Default)]
+pub struct KeyMapping {
+    layout: KeyboardLayout,
+    overrides: Vec<KeyOverride>,
+    map: HashMap<KeyKind, KeyKind>,
+}
+
+impl KeyMapping {
+    /// Rebuilds the mapping if `layout` or `overrides` changed.
+    ///
+    /// Each of `overrides` takes precedence over the `layout` translation of the same key.
+    pub fn set(&mut self, layout: KeyboardLayout, overrides: &[KeyOverride]) {
+        if self.layout == layout && self.overrides == overrides {
+            return;
+        }
+
+        self.layout = layout;
+        self.overrides = overrides.to_vec();
+        self.map = layout_pairs(layout)
+            .iter()
+            .copied()
+            .chain(overrides.iter().map(|key| (key.from.into(), key.to.into())))
+            .collect();
+    }
+
+    #[inline]
+    pub fn map(&self, kind: KeyKind) -> KeyKind {
+        self.map.get(&kind).copied().unwrap_or(kind)
+    }
+}
+
+/// The keys at each differing QWERTY position paired with the key at that position on `layout`.
+fn layout_pairs(layout: KeyboardLayout) -> &'static [(KeyKind, KeyKind)] {
+    match layout {
+        KeyboardLayout::Qwerty => &[],
+        KeyboardLayout::Azerty => &[
+            (KeyKind::A, KeyKind::Q),
+            (KeyKind::Q, KeyKind::A),
+            (KeyKind::W, KeyKind::Z),
+            (KeyKind::Z, KeyKind::W),
+            (KeyKind::Semicolon, KeyKind::M),
+            (KeyKind::M, KeyKind::Comma),
+            (KeyKind::Comma, KeyKind::Semicolon),
+        ],
+        KeyboardLayout::Qwertz => &[(KeyKind::Y, KeyKind::Z), (KeyKind::Z, KeyKind::Y)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::KeyBinding;
+
+    #[test]
+    fn map_layout_with_overrides() {
+        let mut mapping = KeyMapping::default();
+        assert_eq!(mapping.map(KeyKind::A), KeyKind::A);
+
+        mapping.set(
+            KeyboardLayout::Azerty,
+            &[KeyOverride {
+                from: KeyBinding::Q,
+                to: KeyBinding::F1,
+            }],
+        );
+        assert_eq!(mapping.map(KeyKind::A), KeyKind::Q);
+        assert_eq!(mapping.map(KeyKind::Q), KeyKind::F1);
+        assert_eq!(mapping.map(KeyKind::M), KeyKind::Comma);
+        assert_eq!(mapping.map(KeyKind::Space), KeyKind::Space);
+
+        mapping.set(KeyboardLayout::Qwertz, &[]);
+        assert_eq!(mapping.map(KeyKind::A), KeyKind::A);
+        assert_eq!(mapping.map(KeyKind::Z), KeyKind::Y);
+    }
+}
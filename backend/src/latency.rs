@@ -0,0 +1,225 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::database::InputMethod;
+
+/// Number of open and close samples measured per test.
+const SAMPLES: usize = 5;
+
+/// Maximum duration to wait for the menu to open or close after sending a key or for a menu
+/// opened beforehand to be closed.
+const STEP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Duration to wait after the menu closed before the next sample.
+const COOLDOWN: Duration = Duration::from_millis(500);
+
+/// The measured delay between sending a key and seeing its effect in the captured frames.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct InputLatency {
+    pub method: InputMethod,
+    pub samples_millis: Vec<u64>,
+    pub average_millis: u64,
+    /// The average number of update ticks until the effect is detected.
+    pub average_ticks: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    Cooldown { until: Instant },
+    Opening { sent: Instant, tick: u64 },
+    Closing { sent: Instant },
+}
+
+#[derive(Debug)]
+struct Running {
+    method: InputMethod,
+    step: Step,
+    /// The measured milliseconds and ticks of each sample.
+    samples: Vec<(u64, u64)>,
+}
+
+/// Measures the input latency by toggling the ESC settings menu.
+///
+/// Each sample sends ESC to open the menu and waits until it is detected then sends ESC again
+/// to close it.
+#[derive(Debug, Default)]
+pub struct LatencyTester {
+    running: Option<Running>,
+    /// The latest result of each tested [`InputMethod`].
+    results: Vec<InputLatency>,
+}
+
+impl LatencyTester {
+    pub fn start(&mut self, method: InputMethod, now: Instant) {
+        self.running = Some(Running {
+            method,
+            step: Step::Cooldown { until: now },
+            samples: Vec::with_capacity(SAMPLES),
+        });
+    }
+
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    #[inline]
+    pub fn results(&self) -> &[InputLatency] {
+        &self.results
+    }
+
+    /// The latest result of `method` if it has been tested.
+    pub fn latency(&self, method: InputMethod) -> Option<&InputLatency> {
+        self.results.iter().find(|result| result.method == method)
+    }
+
+    /// Advances the test with whether the menu is opened in the current frame.
+    ///
+    /// `send_key` is called whenever ESC should be sent. Returns the result once the test
+    /// completes or fails.
+    pub fn update(
+        &mut self,
+        now: Instant,
+        tick: u64,
+        menu_opened: bool,
+        send_key: impl FnOnce(),
+    ) -> Option<Result<InputLatency>> {
+        let running = self.running.as_mut()?;
+        let result = match running.step {
+            Step::Cooldown { until } => {
+                // Waits for any menu opened beforehand to be closed first
+                if now < until {
+                    None
+                } else if !menu_opened {
+                    send_key();
+                    running.step = Step::Opening { sent: now, tick };
+                    None
+                } else if now.duration_since(until) >= STEP_TIMEOUT {
+                    Some(Err(anyhow!("menu opened beforehand did not close")))
+                } else {
+                    None
+                }
+            }
+            Step::Opening {
+                sent,
+                tick: sent_tick,
+            } => {
+                if menu_opened {
+                    let millis = now.duration_since(sent).as_millis() as u64;
+                    running.samples.push((millis, tick - sent_tick));
+                    send_key();
+                    running.step = Step::Closing { sent: now };
+                    None
+                } else if now.duration_since(sent) >= STEP_TIMEOUT {
+                    Some(Err(anyhow!("menu did not open")))
+                } else {
+                    None
+                }
+            }
+            Step::Closing { sent } => {
+                if !menu_opened {
+                    if running.samples.len() >= SAMPLES {
+                        Some(Ok(to_input_latency(running.method, &running.samples)))
+                    } else {
+                        running.step = Step::Cooldown {
+                            until: now + COOLDOWN,
+                        };
+                        None
+                    }
+                } else if now.duration_since(sent) >= STEP_TIMEOUT {
+                    Some(Err(anyhow!("menu did not close")))
+                } else {
+                    None
+                }
+            }
+        };
+        if result.is_some() {
+            self.running = None;
+        }
+        if let Some(Ok(latency)) = &result {
+            self.results
+                .retain(|result| result.method != latency.method);
+            self.results.push(latency.clone());
+        }
+        result
+    }
+}
+
+fn to_input_latency(method: InputMethod, samples: &[(u64, u64)]) -> InputLatency {
+    let count = samples.len().max(1) as u64;
+    InputLatency {
+        method,
+        samples_millis: samples.iter().map(|(millis, _)| *millis).collect(),
+        average_millis: samples.iter().map(|(millis, _)| millis).sum::<u64>() / count,
+        average_ticks: samples.iter().map(|(_, ticks)| ticks).sum::<u64>() / count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_measures_samples() {
+        let mut tester = LatencyTester::default();
+        let mut now = Instant::now();
+        let mut tick = 0;
+        let mut sent = 0;
+        tester.start(InputMethod::Default, now);
+
+        let result = loop {
+            // Opening, detected 3 ticks later then closing, detected 1 tick later
+            assert!(tester.update(now, tick, false, || sent += 1).is_none());
+            now += Duration::from_millis(100);
+            tick += 3;
+            assert!(tester.update(now, tick, true, || sent += 1).is_none());
+            now += Duration::from_millis(30);
+            tick += 1;
+            if let Some(result) = tester.update(now, tick, false, || sent += 1) {
+                break result;
+            }
+            now += COOLDOWN;
+        };
+
+        let latency = result.unwrap();
+        assert_eq!(sent, SAMPLES * 2);
+        assert_eq!(latency.samples_millis, vec![100; SAMPLES]);
+        assert_eq!(latency.average_ticks, 3);
+        assert_eq!(tester.results(), [latency]);
+        assert!(!tester.is_running());
+    }
+
+    #[test]
+    fn update_fails_when_menu_stays_opened_before_start() {
+        let mut tester = LatencyTester::default();
+        let now = Instant::now();
+        tester.start(InputMethod::Default, now);
+
+        assert!(tester.update(now, 0, true, || panic!()).is_none());
+        assert!(
+            tester
+                .update(now + STEP_TIMEOUT, 1, true, || panic!())
+                .unwrap()
+                .is_err()
+        );
+        assert!(!tester.is_running());
+    }
+
+    #[test]
+    fn update_fails_when_menu_not_opened() {
+        let mut tester = LatencyTester::default();
+        let now = Instant::now();
+        tester.start(InputMethod::Default, now);
+
+        assert!(tester.update(now, 0, false, || ()).is_none());
+        assert!(
+            tester
+                .update(now + STEP_TIMEOUT, 1, false, || ())
+                .unwrap()
+                .is_err()
+        );
+        assert!(!tester.is_running());
+    }
+}
@@ -6,7 +6,13 @@
 #![feature(associated_type_defaults)]
 #![feature(assert_matches)]
 
-use std::sync::{LazyLock, Mutex};
+use std::{
+    convert::identity,
+    sync::{LazyLock, Mutex},
+};
+
+use log::warn;
+use serde::Serialize;
 use strum::Display;
 use tokio::{
     sync::{
@@ -17,38 +23,108 @@ use tokio::{
 };
 
 
+mod anti_afk;
 mod array;
+mod avoidance;
+mod breaks;
+#[cfg(any(debug_assertions, feature = "bench"))]
+pub mod bench;
 mod bridge;
 mod buff;
+mod capture_window;
+mod chat;
+mod cipher;
+mod client_restart;
+mod config;
 mod context;
 mod database;
 #[cfg(debug_assertions)]
 mod debug;
 mod detect;
+mod diagnostics;
+mod editor;
+mod error;
+mod events;
+mod following;
+mod frame_diff;
+mod frame_pool;
+mod history;
+mod humanization;
+mod itinerary;
+mod key_mapping;
+mod latency;
+mod login;
+mod logs;
 mod mat;
+mod metrics;
 mod minimap;
 mod network;
+mod overlay;
+mod panic_playbook;
 mod pathing;
 mod player;
+mod plugin;
+mod rate_limiter;
+mod recording;
 mod request_handler;
 mod rng;
 mod rotator;
+mod routine;
 mod rpc;
+mod schedule;
+mod serial;
+mod self_test;
+mod session;
+mod shutdown;
+#[cfg(feature = "simulation")]
+pub mod simulation;
 mod skill;
+mod stats;
+mod suspension;
 mod task;
+mod update;
+mod watchdog;
+mod wizard;
 
 pub use {
-    context::{init, signal_update_loop_shutdown},
+    buff::BuffKind,
+    context::{init, signal_update_loop_shutdown, wait_update_loop_shutdown},
     database::{
-        Action, ActionCondition, ActionConfiguration, ActionConfigurationCondition, ActionKey,
-        ActionKeyDirection, ActionKeyWith, ActionMove, Bound, CaptureMode, Character, Class,
-        EliteBossBehavior, FamiliarRarity, Familiars, InputMethod, KeyBinding,
-        KeyBindingConfiguration, LinkKeyBinding, Minimap, MobbingKey, Notifications, Platform,
-        Position, PotionMode, RotationMode, Settings, SwappableFamiliars,
+        Action, ActionCondition, ActionConfiguration, ActionConfigurationCondition, ActionFailure,
+        ActionGroup, ActionGroupPredicate, ActionKey, ActionKeyDirection, ActionKeyVerify,
+        ActionKeyWith, ActionMouse, ActionMouseKind, ActionMouseRelativeTo, ActionMove, AntiAfk,
+        AutoMobRegion, AutoMobRegionOrder, AutoMobStrategy, AvoidanceZone, Bound, Breaks,
+        BurstWindow, CaptureHdrMode, CaptureMode, CaptureOverride, CaptureWindow, Character,
+        ChatDetection, Class, ClientPoint, ClientRestart, ComboAction, ComboStep, DetectionTuning,
+        EliteBossBehavior, FamiliarLevelPriority, FamiliarRarity, FamiliarRarityPriority,
+        Familiars, FocusTracking, Following, FollowingRole, HumanizationProfile, InputMethod,
+        InventoryFullBehavior, Itinerary, ItineraryExit, ItineraryStep, KeyBinding,
+        KeyBindingConfiguration, KeyOverride, KeyboardLayout, LinkKeyBinding, Loadout,
+        LoginSequence, MesoDetection, Minimap, MobbingKey, MovementOverrides, Notifications,
+        PanicPlaybook, PanicPlaybooks, PanicStep, PanicStepKind, PingPongBand, Platform,
+        PlatformMotion, Position, Potion, PotionMode, PresetSchedule, PresetScheduleEntry, Rope,
+        RotationMode, Routine, RoutineStep, RoutineStepKind, RoutineTrigger, SafeMode, SellItems,
+        Session, Settings, ShutdownRoutine, SwappableFamiliars, VideoFormat, VideoRecording,
+        Watchdog, WindowArrangement,
     },
+    detect::DetectionScores,
+    editor::{
+        PresetIssue, canvas_to_minimap_point, minimap_to_canvas_point, reorder_actions,
+        validate_preset,
+    },
+    error::BackendError,
+    events::{Event, EventRecord},
+    latency::InputLatency,
+    logs::{LogRecord, LogSink},
+    minimap::MinimapConfidence,
     pathing::MAX_PLATFORMS_COUNT,
-    rotator::RotatorMode,
+    player::MAX_POTIONS,
+    rotator::{RotatorMode, UpcomingPriorityAction},
+    self_test::{DetectionModel, ModelDiagnostics},
+    stats::ActionStats,
     strum::{EnumMessage, IntoEnumIterator, ParseError},
+    update::UpdateInfo,
+    wizard::CaptureTestResult,
 };
 
 type RequestItem = (Request, Sender<Response>);
@@ -63,19 +139,19 @@ static REQUESTS: LazyLock<(
 
 macro_rules! expect_unit_variant {
     ($e:expr, $p:path) => {
-        match $e {
+        $e.map(|response| match response {
             $p => (),
             _ => unreachable!(),
-        }
+        })
     };
 }
 
 macro_rules! expect_value_variant {
     ($e:expr, $p:path) => {
-        match $e {
+        $e.map(|response| match response {
             $p(value) => value,
             _ => unreachable!(),
-        }
+        })
     };
 }
 
@@ -86,10 +162,29 @@ enum Request {
     CreateMinimap(String),
     UpdateMinimap(Option<String>, Option<Minimap>),
     UpdateCharacter(Option<Character>),
+    SwitchLoadout(Option<String>),
+    UpdateRoutines,
+    UpdateAvoidanceZones,
     UpdateSettings(Settings),
     RedetectMinimap,
+    DumpDiagnostics,
+    RecordVideo(bool),
+    ResumeLastSession(bool),
+    QueryUpdate,
+    ApplyUpdate,
+    WizardDetectWindows,
+    WizardTestCapture(CaptureMode),
+    WizardQueryCaptureTest,
+    WizardTestKey(KeyBinding),
+    WizardFinish(String),
+    TestInputLatency,
+    QueryInputLatencies,
+    QueryActionStats,
+    QueryDiagnostics,
+    QueryStatus,
     GameStateReceiver,
     KeyReceiver,
+    EventReceiver,
     QueryCaptureHandles,
     SelectCaptureHandle(Option<usize>),
     #[cfg(debug_assertions)]
@@ -102,6 +197,8 @@ enum Request {
     RecordImages(bool),
     #[cfg(debug_assertions)]
     TestSpinRune,
+    #[cfg(debug_assertions)]
+    RunBenchmarks(u32),
 }
 
 /// Represents response to UI [`Request`].
@@ -114,12 +211,31 @@ enum Response {
     CreateMinimap(Option<Minimap>),
     UpdateMinimap,
     UpdateCharacter,
+    SwitchLoadout(Option<Character>),
+    UpdateRoutines,
+    UpdateAvoidanceZones,
     UpdateSettings,
     RedetectMinimap,
+    DumpDiagnostics(String),
+    RecordVideo(Option<String>),
+    ResumeLastSession(bool),
+    QueryUpdate(Option<UpdateInfo>),
+    ApplyUpdate(bool),
+    WizardDetectWindows((Vec<String>, Option<usize>)),
+    WizardTestCapture(bool),
+    WizardQueryCaptureTest(Option<CaptureTestResult>),
+    WizardTestKey(bool),
+    WizardFinish(Option<Character>),
+    TestInputLatency(bool),
+    QueryInputLatencies(Vec<InputLatency>),
+    QueryActionStats(Vec<ActionStats>),
+    QueryDiagnostics(Vec<ModelDiagnostics>),
+    QueryStatus(BotStatus),
     GameStateReceiver(broadcast::Receiver<GameState>),
     KeyReceiver(broadcast::Receiver<KeyBinding>),
+    EventReceiver(broadcast::Receiver<EventRecord>),
     QueryCaptureHandles((Vec<String>, Option<usize>)),
-    SelectCaptureHandle,
+    SelectCaptureHandle(Option<CaptureWindow>),
     #[cfg(debug_assertions)]
     CaptureImage,
     #[cfg(debug_assertions)]
@@ -130,6 +246,8 @@ enum Response {
     RecordImages,
     #[cfg(debug_assertions)]
     TestSpinRune,
+    #[cfg(debug_assertions)]
+    RunBenchmarks(Vec<bench::BenchmarkLatency>),
 }
 
 /// Request handler of incoming requests from UI.
@@ -142,18 +260,55 @@ pub(crate) trait RequestHandler {
 
     fn on_update_character(&mut self, character: Option<Character>);
 
+    fn on_switch_loadout(&mut self, name: Option<String>) -> Option<Character>;
+
+    fn on_update_routines(&mut self);
+
+    fn on_update_avoidance_zones(&mut self);
+
     fn on_update_settings(&mut self, settings: Settings);
 
     fn on_redetect_minimap(&mut self);
 
+    fn on_dump_diagnostics(&self) -> String;
+
+    fn on_record_video(&mut self, start: bool) -> Option<String>;
+
+    fn on_resume_last_session(&mut self, start: bool) -> bool;
+
+    fn on_query_update(&self) -> Option<UpdateInfo>;
+
+    fn on_apply_update(&mut self) -> bool;
+
+    fn on_wizard_detect_windows(&mut self) -> (Vec<String>, Option<usize>);
+
+    fn on_wizard_test_capture(&mut self, mode: CaptureMode) -> bool;
+
+    fn on_wizard_query_capture_test(&self) -> Option<CaptureTestResult>;
+
+    fn on_wizard_test_key(&mut self, key: KeyBinding) -> bool;
+
+    fn on_wizard_finish(&mut self, character_name: String) -> Option<Character>;
+
+    fn on_test_input_latency(&mut self) -> bool;
+
+    fn on_query_input_latencies(&self) -> Vec<InputLatency>;
+
+    fn on_query_action_stats(&self) -> Vec<ActionStats>;
+
+    fn on_query_diagnostics(&self) -> Vec<ModelDiagnostics>;
+
+    fn on_query_status(&self) -> BotStatus;
 
     fn on_game_state_receiver(&self) -> broadcast::Receiver<GameState>;
 
     fn on_key_receiver(&self) -> broadcast::Receiver<KeyBinding>;
 
+    fn on_event_receiver(&self) -> broadcast::Receiver<EventRecord>;
+
     fn on_query_capture_handles(&mut self) -> (Vec<String>, Option<usize>);
 
-    fn on_select_capture_handle(&mut self, index: Option<usize>);
+    fn on_select_capture_handle(&mut self, index: Option<usize>) -> Option<CaptureWindow>;
 
     #[cfg(debug_assertions)]
     fn on_capture_image(&self, is_grayscale: bool);
@@ -169,10 +324,13 @@ pub(crate) trait RequestHandler {
 
     #[cfg(debug_assertions)]
     fn on_test_spin_rune(&self);
+
+    #[cfg(debug_assertions)]
+    fn on_run_benchmarks(&self, iterations: u32) -> Vec<bench::BenchmarkLatency>;
 }
 
 /// The four quads of a bound.
-#[derive(Clone, Copy, Debug, Display)]
+#[derive(Clone, Copy, Debug, Display, Serialize)]
 pub enum BoundQuadrant {
     TopLeft,
     TopRight,
@@ -180,54 +338,165 @@ pub enum BoundQuadrant {
     BottomLeft,
 }
 
-/// A struct for storing game information.
+/// The kind of another player shown in the minimap.
+#[derive(Clone, Copy, Debug, Display, Serialize)]
+pub enum MinimapPlayerKind {
+    Guildie,
+    Stranger,
+    Friend,
+}
+
+/// A familiar slot read from the familiar setup window.
+#[derive(Clone, Copy, Debug, Display, Serialize)]
+pub enum FamiliarSlotKind {
+    Free,
+    Rare,
+    Epic,
+    /// The slot is occupied but the familiar rarity cannot be recognized.
+    Unknown,
+}
+
+/// A downscaled capture of the desktop for picking the [`CaptureMode::BitBltArea`] area.
 #[derive(Clone, Debug)]
+pub struct DesktopPreview {
+    /// The RGBA pixels of the preview.
+    pub frame: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+    /// The number of desktop pixels per preview pixel.
+    pub scale: usize,
+}
+
+impl DesktopPreview {
+    /// Converts `area` in preview coordinates to desktop coordinates.
+    ///
+    /// The returned area is clamped to the desktop.
+    pub fn to_capture_area(&self, area: Bound) -> Bound {
+        let scale = self.scale as i32;
+        let max_width = self.width as i32 * scale;
+        let max_height = self.height as i32 * scale;
+        let x = (area.x * scale).clamp(0, max_width);
+        let y = (area.y * scale).clamp(0, max_height);
+
+        Bound {
+            x,
+            y,
+            width: (area.width * scale).clamp(0, max_width - x),
+            height: (area.height * scale).clamp(0, max_height - y),
+        }
+    }
+}
+
+/// A struct for storing game information.
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct GameState {
     pub position: Option<(i32, i32)>,
     pub health: Option<(u32, u32)>,
+    pub mp: Option<(u32, u32)>,
     pub state: String,
     pub normal_action: Option<String>,
     pub priority_action: Option<String>,
     pub erda_shower_state: String,
     pub destinations: Vec<(i32, i32)>,
+    /// The planned path from the current position through [`Self::destinations`].
+    pub path: Vec<(i32, i32)>,
+    pub rune: Option<(i32, i32)>,
+    /// Whether an elite boss is detected from the boss HP bar.
+    pub has_elite_boss: bool,
+    /// The elite boss position on the minimap while [`Self::has_elite_boss`].
+    pub elite_boss: Option<(i32, i32)>,
+    pub other_players: Vec<(i32, i32, MinimapPlayerKind)>,
     pub halting: bool,
+    #[serde(skip)]
     pub frame: Option<(Vec<u8>, usize, usize)>,
     pub platforms_bound: Option<Bound>,
     pub portals: Vec<Bound>,
     pub auto_mob_quadrant: Option<BoundQuadrant>,
+    pub exp_per_hour: Option<u64>,
+    pub exp_percent_per_hour: Option<f32>,
+    pub mesos_per_hour: Option<u64>,
+    /// The latest match scores to compare against [`DetectionTuning`].
+    pub detection_scores: DetectionScores,
+    /// The ongoing minimap detection confidence or [`None`] while the minimap is being detected.
+    pub minimap_confidence: Option<MinimapConfidence>,
+    /// The names of the next normal actions in rotation order.
+    pub upcoming_normal_actions: Vec<String>,
+    /// The priority actions with the queued ones first.
+    pub upcoming_priority_actions: Vec<UpcomingPriorityAction>,
+    /// The familiar slots read in the last familiars swapping.
+    pub familiar_slots: Vec<FamiliarSlotKind>,
+    /// Whether [`Self::familiar_slots`] matches the familiars swapping rules.
+    ///
+    /// This is [`None`] if familiars swapping has not run yet.
+    pub familiar_slots_matched: Option<bool>,
+    /// Milliseconds until the next [`SafeMode`] break or [`None`] if there is no scheduled break.
+    pub safe_mode_break_in_millis: Option<u64>,
+    /// Remaining milliseconds of the current [`SafeMode`] break.
+    pub safe_mode_breaking_millis: Option<u64>,
 }
 
-pub async fn rotate_actions(halting: bool) {
+/// A brief status of the bot shown outside of the main window (e.g. in the system tray).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BotStatus {
+    pub halting: bool,
+    /// The name of the selected character or [`None`] if there is none.
+    pub character: Option<String>,
+    /// The runtime, map and EXP rate of the current session on separate lines.
+    pub summary: String,
+}
+
+pub async fn rotate_actions(halting: bool) -> Result<(), BackendError> {
     expect_unit_variant!(
         request(Request::RotateActions(halting)).await,
         Response::RotateActions
     )
 }
 
+/// Whether the database is encrypted and needs [`unlock_database`] before it can be used.
+pub async fn is_database_locked() -> bool {
+    spawn_blocking(database::is_locked).await.unwrap_or(false)
+}
+
+/// Unlocks the encrypted database with `passphrase`.
+///
+/// Returns [`BackendError::Database`] if `passphrase` is incorrect.
+pub async fn unlock_database(passphrase: String) -> Result<(), BackendError> {
+    spawn_blocking(move || database::unlock(passphrase))
+        .await
+        .map_err(|_| BackendError::ChannelClosed)?
+        .map_err(BackendError::database)
+}
+
 /// Queries settings from the database.
-pub async fn query_settings() -> Settings {
-    spawn_blocking(database::query_settings).await.unwrap()
+pub async fn query_settings() -> Result<Settings, BackendError> {
+    run_database(database::query_settings).await
+}
+
+/// Captures a downscaled preview of the desktop.
+///
+/// Returns [`BackendError::CaptureUnavailable`] if the desktop cannot be captured.
+pub async fn query_desktop_preview() -> Result<DesktopPreview, BackendError> {
+    spawn_blocking(bridge::capture_desktop_preview)
+        .await
+        .ok()
+        .flatten()
+        .ok_or(BackendError::CaptureUnavailable)
 }
 
 /// Upserts settings to the database.
-pub async fn upsert_settings(mut settings: Settings) -> Settings {
-    spawn_blocking(move || {
-        database::upsert_settings(&mut settings).expect("failed to upsert settings");
-        settings
-    })
-    .await
-    .unwrap()
+pub async fn upsert_settings(mut settings: Settings) -> Result<Settings, BackendError> {
+    run_database(move || database::upsert_settings(&mut settings).map(|_| settings)).await
 }
 
 /// Queries minimaps from the database.
-pub async fn query_minimaps() -> Option<Vec<Minimap>> {
-    spawn_blocking(database::query_minimaps).await.unwrap().ok()
+pub async fn query_minimaps() -> Result<Vec<Minimap>, BackendError> {
+    run_database(database::query_minimaps).await
 }
 
 /// Creates a new minimap from the currently detected minimap.
 ///
 /// This function does not insert the created minimap into the database.
-pub async fn create_minimap(name: String) -> Option<Minimap> {
+pub async fn create_minimap(name: String) -> Result<Option<Minimap>, BackendError> {
     expect_value_variant!(
         request(Request::CreateMinimap(name)).await,
         Response::CreateMinimap
@@ -240,17 +509,15 @@ pub async fn create_minimap(name: String) -> Option<Minimap> {
 /// be updated.
 ///
 /// Returns the updated [`Minimap`].
-pub async fn upsert_minimap(mut minimap: Minimap) -> Minimap {
-    spawn_blocking(move || {
-        database::upsert_minimap(&mut minimap).expect("failed to upsert minimap");
-        minimap
-    })
-    .await
-    .unwrap()
+pub async fn upsert_minimap(mut minimap: Minimap) -> Result<Minimap, BackendError> {
+    run_database(move || history::upsert_minimap(&mut minimap).map(|_| minimap)).await
 }
 
 /// Updates the current minimap used by the main game loop.
-pub async fn update_minimap(preset: Option<String>, minimap: Option<Minimap>) {
+pub async fn update_minimap(
+    preset: Option<String>,
+    minimap: Option<Minimap>,
+) -> Result<(), BackendError> {
     expect_unit_variant!(
         request(Request::UpdateMinimap(preset, minimap)).await,
         Response::UpdateMinimap
@@ -258,21 +525,81 @@ pub async fn update_minimap(preset: Option<String>, minimap: Option<Minimap>) {
 }
 
 
-/// Deletes `minimap` from the database.
-pub async fn delete_minimap(minimap: Minimap) {
-    spawn_blocking(move || {
-        database::delete_minimap(&minimap).expect("failed to delete minimap");
+/// Duplicates `preset` of the minimap with `minimap_id` as `new_name` and saves it to the database.
+///
+/// Returns the updated [`Minimap`] or [`None`] if the minimap or `preset` does not exist or
+/// `new_name` is already used.
+pub async fn duplicate_preset(
+    minimap_id: i64,
+    preset: String,
+    new_name: String,
+) -> Result<Option<Minimap>, BackendError> {
+    run_database(move || {
+        let Some(mut minimap) = database::query_minimaps()?
+            .into_iter()
+            .find(|minimap| minimap.id == Some(minimap_id))
+        else {
+            return Ok(None);
+        };
+        if let Err(err) = editor::duplicate_preset(&mut minimap, &preset, new_name) {
+            warn!("failed to duplicate preset {err}");
+            return Ok(None);
+        }
+        history::upsert_minimap(&mut minimap)?;
+        Ok(Some(minimap))
     })
     .await
-    .unwrap();
+}
+
+/// Copies `preset` of the minimap with `from_minimap_id` to the minimap with `to_minimap_id`
+/// and saves it to the database.
+///
+/// Action positions are scaled to the destination map dimensions. Returns the updated destination
+/// [`Minimap`] or [`None`] if either minimap or `preset` does not exist or the destination already
+/// has a preset with the same name.
+pub async fn copy_preset_to_minimap(
+    from_minimap_id: i64,
+    preset: String,
+    to_minimap_id: i64,
+) -> Result<Option<Minimap>, BackendError> {
+    run_database(move || {
+        let minimaps = database::query_minimaps()?;
+        let find = |id| minimaps.iter().find(|minimap| minimap.id == Some(id));
+        let Some((from, mut to)) = find(from_minimap_id).zip(find(to_minimap_id).cloned()) else {
+            return Ok(None);
+        };
+        if let Err(err) = editor::copy_preset(from, &preset, &mut to) {
+            warn!("failed to copy preset {err}");
+            return Ok(None);
+        }
+        history::upsert_minimap(&mut to)?;
+        Ok(Some(to))
+    })
+    .await
+}
+
+/// Deletes `minimap` from the database.
+pub async fn delete_minimap(minimap: Minimap) -> Result<(), BackendError> {
+    run_database(move || history::delete_minimap(&minimap)).await
+}
+
+/// Undoes the last minimap upsert or deletion.
+///
+/// Returns the id of the affected minimap or [`None`] if there is nothing to undo.
+pub async fn undo() -> Result<Option<i64>, BackendError> {
+    run_database(history::undo).await
+}
+
+/// Redoes the last undone minimap edit.
+///
+/// Returns the id of the affected minimap or [`None`] if there is nothing to redo.
+pub async fn redo() -> Result<Option<i64>, BackendError> {
+    run_database(history::redo).await
 }
 
 /// Queries characters from the database.
-pub async fn query_characters() -> Option<Vec<Character>> {
-    spawn_blocking(database::query_characters)
-        .await
-        .unwrap()
-        .ok()
+pub async fn query_characters() -> Result<Vec<Character>, BackendError> {
+    run_database(database::query_characters).await
 }
 
 /// Upserts character to the database.
@@ -281,73 +608,293 @@ pub async fn query_characters() -> Option<Vec<Character>> {
 /// be updated.
 ///
 /// Returns the updated [`Character`].
-pub async fn upsert_character(mut character: Character) -> Character {
-    spawn_blocking(move || {
-        database::upsert_character(&mut character).expect("failed to upsert character");
-        character
-    })
-    .await
-    .unwrap()
+pub async fn upsert_character(mut character: Character) -> Result<Character, BackendError> {
+    run_database(move || database::upsert_character(&mut character).map(|_| character)).await
 }
 
 /// Updates the current character used by the main game loop.
-pub async fn update_character(character: Option<Character>) {
+pub async fn update_character(character: Option<Character>) -> Result<(), BackendError> {
     expect_unit_variant!(
         request(Request::UpdateCharacter(character)).await,
         Response::UpdateCharacter
     )
 }
 
+/// Switches the current character to the [`Loadout`] named `name` or to the base configuration
+/// if `name` is [`None`].
+///
+/// Returns the updated and persisted [`Character`] or [`None`] if there is no current character
+/// or no loadout is named `name`.
+pub async fn switch_loadout(name: Option<String>) -> Result<Option<Character>, BackendError> {
+    expect_value_variant!(
+        request(Request::SwitchLoadout(name)).await,
+        Response::SwitchLoadout
+    )
+}
+
 /// Deletes `character` from the database.
-pub async fn delete_character(character: Character) {
-    spawn_blocking(move || {
-        database::delete_character(&character).expect("failed to delete character");
-    })
-    .await
-    .unwrap();
+pub async fn delete_character(character: Character) -> Result<(), BackendError> {
+    run_database(move || database::delete_character(&character)).await
+}
+
+/// Queries routines from the database.
+pub async fn query_routines() -> Result<Vec<Routine>, BackendError> {
+    run_database(database::query_routines).await
+}
+
+/// Upserts routine to the database.
+///
+/// If `routine` does not previously exist, a new one will be created and its `id` will
+/// be updated.
+///
+/// Returns the updated [`Routine`].
+pub async fn upsert_routine(mut routine: Routine) -> Result<Routine, BackendError> {
+    run_database(move || database::upsert_routine(&mut routine).map(|_| routine)).await
+}
+
+/// Deletes `routine` from the database.
+pub async fn delete_routine(routine: Routine) -> Result<(), BackendError> {
+    run_database(move || database::delete_routine(&routine)).await
+}
+
+/// Reloads the routines used by the main game loop from the database.
+pub async fn update_routines() -> Result<(), BackendError> {
+    expect_unit_variant!(
+        request(Request::UpdateRoutines).await,
+        Response::UpdateRoutines
+    )
+}
+
+/// Queries the avoidance zones learned on the minimap with `minimap_id`.
+pub async fn query_avoidance_zones(minimap_id: i64) -> Result<Vec<AvoidanceZone>, BackendError> {
+    run_database(move || database::query_avoidance_zones(minimap_id)).await
+}
+
+/// Deletes `zone` from the database.
+pub async fn delete_avoidance_zone(zone: AvoidanceZone) -> Result<(), BackendError> {
+    run_database(move || database::delete_avoidance_zone(zone.id)).await
 }
 
-pub async fn update_settings(settings: Settings) {
+/// Deletes all avoidance zones learned on the minimap with `minimap_id` from the database.
+pub async fn clear_avoidance_zones(minimap_id: i64) -> Result<(), BackendError> {
+    run_database(move || database::delete_avoidance_zones(minimap_id)).await
+}
+
+/// Reloads the avoidance zones used by the main game loop from the database.
+pub async fn update_avoidance_zones() -> Result<(), BackendError> {
+    expect_unit_variant!(
+        request(Request::UpdateAvoidanceZones).await,
+        Response::UpdateAvoidanceZones
+    )
+}
+
+pub async fn update_settings(settings: Settings) -> Result<(), BackendError> {
     expect_unit_variant!(
         request(Request::UpdateSettings(settings)).await,
         Response::UpdateSettings
     )
 }
 
-pub async fn redetect_minimap() {
+pub async fn redetect_minimap() -> Result<(), BackendError> {
     expect_unit_variant!(
         request(Request::RedetectMinimap).await,
         Response::RedetectMinimap
     )
 }
 
-pub async fn game_state_receiver() -> broadcast::Receiver<GameState> {
+/// Dumps the recent game state history to disk for diagnosing the bot behavior.
+///
+/// The history is written in the background. Returns the path of the dumped directory.
+pub async fn dump_diagnostics() -> Result<String, BackendError> {
+    expect_value_variant!(
+        request(Request::DumpDiagnostics).await,
+        Response::DumpDiagnostics
+    )
+}
+
+/// Starts or stops recording the capture stream to a video file.
+///
+/// Returns the path of the video file when recording is started or [`None`] otherwise.
+pub async fn record_video(start: bool) -> Result<Option<String>, BackendError> {
+    expect_value_variant!(
+        request(Request::RecordVideo(start)).await,
+        Response::RecordVideo
+    )
+}
+
+/// Restores the minimap, preset and character of the session before the last crash or restart.
+///
+/// If `start` is true, actions are also started when they were running in that session. Returns
+/// `false` if there is no session to resume.
+pub async fn resume_last_session(start: bool) -> Result<bool, BackendError> {
+    expect_value_variant!(
+        request(Request::ResumeLastSession(start)).await,
+        Response::ResumeLastSession
+    )
+}
+
+/// Queries the newer release downloaded on startup if [`Settings::check_updates`] is enabled.
+pub async fn query_update() -> Result<Option<UpdateInfo>, BackendError> {
+    expect_value_variant!(request(Request::QueryUpdate).await, Response::QueryUpdate)
+}
+
+/// Replaces the current executable with the downloaded release and launches it.
+///
+/// Returns `true` if the update is applied and the current process should exit.
+pub async fn apply_update() -> Result<bool, BackendError> {
+    expect_value_variant!(request(Request::ApplyUpdate).await, Response::ApplyUpdate)
+}
+
+/// Queries the capture handle names for the first-run setup.
+///
+/// Returns the names and the index of the suggested game window if any. The handle is then
+/// selected through [`select_capture_handle`].
+pub async fn wizard_detect_windows() -> Result<(Vec<String>, Option<usize>), BackendError> {
+    expect_value_variant!(
+        request(Request::WizardDetectWindows).await,
+        Response::WizardDetectWindows
+    )
+}
+
+/// Starts testing whether `mode` can capture the selected window and detect the minimap from it.
+///
+/// The result is queried through [`wizard_query_capture_test`] once the test completes. Returns
+/// `false` if a test is already running.
+pub async fn wizard_test_capture(mode: CaptureMode) -> Result<bool, BackendError> {
+    expect_value_variant!(
+        request(Request::WizardTestCapture(mode)).await,
+        Response::WizardTestCapture
+    )
+}
+
+/// Queries the result of the last completed capture test started by [`wizard_test_capture`].
+///
+/// Returns [`None`] if the test is still running or none has been started.
+pub async fn wizard_query_capture_test() -> Result<Option<CaptureTestResult>, BackendError> {
+    expect_value_variant!(
+        request(Request::WizardQueryCaptureTest).await,
+        Response::WizardQueryCaptureTest
+    )
+}
+
+/// Sends `key` with the current input method for the user to confirm the game received it.
+///
+/// Returns `false` if the key cannot be sent.
+pub async fn wizard_test_key(key: KeyBinding) -> Result<bool, BackendError> {
+    expect_value_variant!(
+        request(Request::WizardTestKey(key)).await,
+        Response::WizardTestKey
+    )
+}
+
+/// Creates the initial character named `character_name` and completes the first-run setup.
+///
+/// Returns the created character or [`None`] if it cannot be created. Since
+/// [`Settings::setup_completed`] is updated, settings should be queried again afterward.
+pub async fn wizard_finish(character_name: String) -> Result<Option<Character>, BackendError> {
+    expect_value_variant!(
+        request(Request::WizardFinish(character_name)).await,
+        Response::WizardFinish
+    )
+}
+
+/// Starts measuring the latency of the current input method by toggling the ESC menu.
+///
+/// Once measured, the movement timeout is extended to cover the latency unless it is overridden
+/// by the minimap.
+///
+/// Returns `false` if the bot is running or a test is already running.
+pub async fn test_input_latency() -> Result<bool, BackendError> {
+    expect_value_variant!(
+        request(Request::TestInputLatency).await,
+        Response::TestInputLatency
+    )
+}
+
+/// Queries the latest measured latency of each tested input method.
+pub async fn query_input_latencies() -> Result<Vec<InputLatency>, BackendError> {
+    expect_value_variant!(
+        request(Request::QueryInputLatencies).await,
+        Response::QueryInputLatencies
+    )
+}
+
+/// Queries the execution statistics of the actions in the current preset.
+pub async fn query_action_stats() -> Result<Vec<ActionStats>, BackendError> {
+    expect_value_variant!(
+        request(Request::QueryActionStats).await,
+        Response::QueryActionStats
+    )
+}
+
+/// Queries a brief status of the bot for showing while the main window is closed.
+pub async fn query_status() -> Result<BotStatus, BackendError> {
+    expect_value_variant!(request(Request::QueryStatus).await, Response::QueryStatus)
+}
+
+/// Queries the results of the detection models self-test run on startup.
+///
+/// Returns an empty list if the self-test has not completed yet.
+pub async fn query_diagnostics() -> Result<Vec<ModelDiagnostics>, BackendError> {
+    expect_value_variant!(
+        request(Request::QueryDiagnostics).await,
+        Response::QueryDiagnostics
+    )
+}
+
+pub async fn game_state_receiver() -> Result<broadcast::Receiver<GameState>, BackendError> {
     expect_value_variant!(
         request(Request::GameStateReceiver).await,
         Response::GameStateReceiver
     )
 }
 
-pub async fn key_receiver() -> broadcast::Receiver<KeyBinding> {
+pub async fn key_receiver() -> Result<broadcast::Receiver<KeyBinding>, BackendError> {
     expect_value_variant!(request(Request::KeyReceiver).await, Response::KeyReceiver)
 }
 
-pub async fn query_capture_handles() -> (Vec<String>, Option<usize>) {
+pub async fn event_receiver() -> Result<broadcast::Receiver<EventRecord>, BackendError> {
+    expect_value_variant!(
+        request(Request::EventReceiver).await,
+        Response::EventReceiver
+    )
+}
+
+/// Queries the latest persisted events in chronological order.
+pub async fn query_events(limit: u32) -> Result<Vec<EventRecord>, BackendError> {
+    run_database(move || database::query_events(limit)).await
+}
+
+/// Returns the recently buffered log records and a receiver for newer ones.
+///
+/// Records are only captured if [`LogSink`] is chained into the application logger.
+pub async fn log_receiver() -> (Vec<LogRecord>, broadcast::Receiver<LogRecord>) {
+    spawn_blocking(logs::subscribe).await.unwrap()
+}
+
+pub async fn query_capture_handles() -> Result<(Vec<String>, Option<usize>), BackendError> {
     expect_value_variant!(
         request(Request::QueryCaptureHandles).await,
         Response::QueryCaptureHandles
     )
 }
 
-pub async fn select_capture_handle(index: Option<usize>) {
-    expect_unit_variant!(
+/// Selects the capture handle at `index` from [`query_capture_handles`] or the default handle
+/// if [`None`].
+///
+/// Returns the [`CaptureWindow`] to persist in [`Settings::capture_window`] so that the window is
+/// re-selected on startup and after the game restarts.
+pub async fn select_capture_handle(
+    index: Option<usize>,
+) -> Result<Option<CaptureWindow>, BackendError> {
+    expect_value_variant!(
         request(Request::SelectCaptureHandle(index)).await,
         Response::SelectCaptureHandle
     )
 }
 
 #[cfg(debug_assertions)]
-pub async fn capture_image(is_grayscale: bool) {
+pub async fn capture_image(is_grayscale: bool) -> Result<(), BackendError> {
     expect_unit_variant!(
         request(Request::CaptureImage(is_grayscale)).await,
         Response::CaptureImage
@@ -355,17 +902,17 @@ pub async fn capture_image(is_grayscale: bool) {
 }
 
 #[cfg(debug_assertions)]
-pub async fn infer_rune() {
+pub async fn infer_rune() -> Result<(), BackendError> {
     expect_unit_variant!(request(Request::InferRune).await, Response::InferRune)
 }
 
 #[cfg(debug_assertions)]
-pub async fn infer_minimap() {
+pub async fn infer_minimap() -> Result<(), BackendError> {
     expect_unit_variant!(request(Request::InferMinimap).await, Response::InferMinimap)
 }
 
 #[cfg(debug_assertions)]
-pub async fn record_images(start: bool) {
+pub async fn record_images(start: bool) -> Result<(), BackendError> {
     expect_unit_variant!(
         request(Request::RecordImages(start)).await,
         Response::RecordImages
@@ -373,10 +920,19 @@ pub async fn record_images(start: bool) {
 }
 
 #[cfg(debug_assertions)]
-pub async fn test_spin_rune() {
+pub async fn test_spin_rune() -> Result<(), BackendError> {
     expect_unit_variant!(request(Request::TestSpinRune).await, Response::TestSpinRune)
 }
 
+/// Runs the detection benchmarks on the current frame for `iterations` times per stage.
+#[cfg(debug_assertions)]
+pub async fn run_benchmarks(iterations: u32) -> Result<Vec<bench::BenchmarkLatency>, BackendError> {
+    expect_value_variant!(
+        request(Request::RunBenchmarks(iterations)).await,
+        Response::RunBenchmarks
+    )
+}
+
 pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
     if let Ok((request, sender)) = LazyLock::force(&REQUESTS).1.lock().unwrap().try_recv() {
         let result = match request {
@@ -395,6 +951,17 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 handler.on_update_character(character);
                 Response::UpdateCharacter
             }
+            Request::SwitchLoadout(name) => {
+                Response::SwitchLoadout(handler.on_switch_loadout(name))
+            }
+            Request::UpdateRoutines => {
+                handler.on_update_routines();
+                Response::UpdateRoutines
+            }
+            Request::UpdateAvoidanceZones => {
+                handler.on_update_avoidance_zones();
+                Response::UpdateAvoidanceZones
+            }
             Request::UpdateSettings(settings) => {
                 handler.on_update_settings(settings);
                 Response::UpdateSettings
@@ -403,16 +970,49 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 handler.on_redetect_minimap();
                 Response::RedetectMinimap
             }
+            Request::DumpDiagnostics => Response::DumpDiagnostics(handler.on_dump_diagnostics()),
+            Request::RecordVideo(start) => Response::RecordVideo(handler.on_record_video(start)),
+            Request::ResumeLastSession(start) => {
+                Response::ResumeLastSession(handler.on_resume_last_session(start))
+            }
+            Request::QueryUpdate => Response::QueryUpdate(handler.on_query_update()),
+            Request::ApplyUpdate => Response::ApplyUpdate(handler.on_apply_update()),
+            Request::WizardDetectWindows => {
+                Response::WizardDetectWindows(handler.on_wizard_detect_windows())
+            }
+            Request::WizardTestCapture(mode) => {
+                Response::WizardTestCapture(handler.on_wizard_test_capture(mode))
+            }
+            Request::WizardQueryCaptureTest => {
+                Response::WizardQueryCaptureTest(handler.on_wizard_query_capture_test())
+            }
+            Request::WizardTestKey(key) => Response::WizardTestKey(handler.on_wizard_test_key(key)),
+            Request::WizardFinish(character_name) => {
+                Response::WizardFinish(handler.on_wizard_finish(character_name))
+            }
+            Request::TestInputLatency => {
+                Response::TestInputLatency(handler.on_test_input_latency())
+            }
+            Request::QueryInputLatencies => {
+                Response::QueryInputLatencies(handler.on_query_input_latencies())
+            }
+            Request::QueryActionStats => {
+                Response::QueryActionStats(handler.on_query_action_stats())
+            }
+            Request::QueryDiagnostics => {
+                Response::QueryDiagnostics(handler.on_query_diagnostics())
+            }
+            Request::QueryStatus => Response::QueryStatus(handler.on_query_status()),
             Request::GameStateReceiver => {
                 Response::GameStateReceiver(handler.on_game_state_receiver())
             }
             Request::KeyReceiver => Response::KeyReceiver(handler.on_key_receiver()),
+            Request::EventReceiver => Response::EventReceiver(handler.on_event_receiver()),
             Request::QueryCaptureHandles => {
                 Response::QueryCaptureHandles(handler.on_query_capture_handles())
             }
             Request::SelectCaptureHandle(index) => {
-                handler.on_select_capture_handle(index);
-                Response::SelectCaptureHandle
+                Response::SelectCaptureHandle(handler.on_select_capture_handle(index))
             }
             #[cfg(debug_assertions)]
             Request::CaptureImage(is_grayscale) => {
@@ -439,18 +1039,34 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 handler.on_test_spin_rune();
                 Response::TestSpinRune
             }
+            #[cfg(debug_assertions)]
+            Request::RunBenchmarks(iterations) => {
+                Response::RunBenchmarks(handler.on_run_benchmarks(iterations))
+            }
         };
         let _ = sender.send(result);
     }
 }
 
-async fn request(request: Request) -> Response {
+async fn request(request: Request) -> Result<Response, BackendError> {
     let (tx, rx) = oneshot::channel();
     LazyLock::force(&REQUESTS)
         .0
         .send((request, tx))
         .await
-        .unwrap();
-    rx.await.unwrap()
+        .map_err(|_| BackendError::ChannelClosed)?;
+    rx.await.map_err(|_| BackendError::ChannelClosed)
+}
+
+/// Runs `f` on the database worker and converts its failure to [`BackendError::Database`].
+async fn run_database<T, F>(f: F) -> Result<T, BackendError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> anyhow::Result<T> + Send + 'static,
+{
+    database::run(f)
+        .await
+        .and_then(identity)
+        .map_err(BackendError::database)
 }
 
@@ -0,0 +1,231 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::database::{ClientPoint, KeyBinding, LoginSequence, RoutineStepKind};
+
+/// Milliseconds between each click of the PIC keypad.
+const PIC_DIGIT_DELAY_MILLIS: u64 = 500;
+
+/// A single step of a [`LoginSequence`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LoginStep {
+    Key(KeyBinding),
+    Click(ClientPoint),
+    /// Detects the on-screen keypad for [`LoginRunner::enter_pic`].
+    EnterPic,
+}
+
+/// Runs the steps of a [`LoginSequence`] in order with each step's delay.
+#[derive(Debug, Default)]
+pub struct LoginRunner {
+    running: bool,
+    /// The remaining steps and the milliseconds to wait before running each.
+    steps: VecDeque<(LoginStep, u64)>,
+    /// The [`Instant`] the next step can run.
+    next_step_instant: Option<Instant>,
+    /// Whether to resume rotating actions once completed.
+    resume: bool,
+    login_screen_instant: Option<Instant>,
+}
+
+impl LoginRunner {
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Starts running `login` and resumes rotating actions once completed if `resume`.
+    ///
+    /// Returns `false` if there is already a running login or `login` is disabled.
+    pub fn start(&mut self, login: &LoginSequence, resume: bool, now: Instant) -> bool {
+        if self.running || !login.enabled {
+            return false;
+        }
+
+        self.steps = login
+            .steps
+            .iter()
+            .map(|step| {
+                let login_step = match step.kind {
+                    RoutineStepKind::Key => LoginStep::Key(step.key),
+                    RoutineStepKind::Click => LoginStep::Click(step.point),
+                };
+                (login_step, step.delay_millis)
+            })
+            .collect();
+        if !login.pic.is_empty() {
+            self.steps
+                .push_back((LoginStep::EnterPic, PIC_DIGIT_DELAY_MILLIS));
+        }
+        self.running = true;
+        self.resume = resume;
+        self.login_screen_instant = None;
+        self.schedule_next_step(now);
+        true
+    }
+
+    /// Takes the next step to run if its delay has passed.
+    pub fn next_step(&mut self, now: Instant) -> Option<LoginStep> {
+        if self.next_step_instant.is_none_or(|instant| now < instant) {
+            return None;
+        }
+
+        let (step, _) = self.steps.pop_front()?;
+        self.schedule_next_step(now);
+        Some(step)
+    }
+
+    /// Enters `pic` by clicking the matching digit of the detected `keypad` then pressing enter.
+    ///
+    /// Returns `false` if any digit of `pic` is not on `keypad`.
+    pub fn enter_pic(&mut self, pic: &str, keypad: &[(ClientPoint, u8)], now: Instant) -> bool {
+        let Some(clicks) = pic
+            .chars()
+            .map(|char| {
+                let digit = char.to_digit(10)? as u8;
+                keypad
+                    .iter()
+                    .find(|(_, key)| *key == digit)
+                    .map(|(point, _)| (LoginStep::Click(*point), PIC_DIGIT_DELAY_MILLIS))
+            })
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+
+        self.steps
+            .push_front((LoginStep::Key(KeyBinding::Enter), PIC_DIGIT_DELAY_MILLIS));
+        for click in clicks.into_iter().rev() {
+            self.steps.push_front(click);
+        }
+        self.schedule_next_step(now);
+        true
+    }
+
+    /// Whether there are steps left to run.
+    #[inline]
+    pub fn has_pending_steps(&self) -> bool {
+        self.next_step_instant.is_some()
+    }
+
+    /// Completes the running login and returns whether to resume rotating actions.
+    pub fn complete(&mut self) -> bool {
+        let resume = self.resume;
+        self.cancel();
+        resume
+    }
+
+    /// Cancels the running login without running the remaining steps.
+    pub fn cancel(&mut self) {
+        self.running = false;
+        self.steps.clear();
+        self.next_step_instant = None;
+        self.resume = false;
+    }
+
+    /// Tracks how long the login screen is assumed to be shown.
+    ///
+    /// Returns `true` once `on_login_screen` has been continuously `true` for at least `millis`.
+    pub fn update_login_screen(
+        &mut self,
+        on_login_screen: bool,
+        millis: u64,
+        now: Instant,
+    ) -> bool {
+        if !on_login_screen {
+            self.login_screen_instant = None;
+            return false;
+        }
+
+        let instant = *self.login_screen_instant.get_or_insert(now);
+        now.duration_since(instant) >= Duration::from_millis(millis)
+    }
+
+    fn schedule_next_step(&mut self, now: Instant) {
+        self.next_step_instant = self
+            .steps
+            .front()
+            .map(|(_, delay_millis)| now + Duration::from_millis(*delay_millis));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{LoginRunner, LoginStep, PIC_DIGIT_DELAY_MILLIS};
+    use crate::database::{ClientPoint, KeyBinding, LoginSequence, RoutineStep, RoutineStepKind};
+
+    fn login() -> LoginSequence {
+        LoginSequence {
+            enabled: true,
+            steps: vec![RoutineStep {
+                kind: RoutineStepKind::Click,
+                delay_millis: 1000,
+                point: ClientPoint { x: 10, y: 20 },
+                ..RoutineStep::default()
+            }],
+            pic: "12".to_string(),
+            ..LoginSequence::default()
+        }
+    }
+
+    #[test]
+    fn start_runs_steps_and_enters_pic() {
+        let mut runner = LoginRunner::default();
+        let now = Instant::now();
+        let delay = Duration::from_millis(PIC_DIGIT_DELAY_MILLIS);
+
+        assert!(!runner.start(&LoginSequence::default(), true, now));
+        assert!(runner.start(&login(), true, now));
+        assert!(!runner.start(&login(), true, now));
+        assert_eq!(runner.next_step(now + Duration::from_millis(999)), None);
+        let now = now + Duration::from_millis(1000);
+        assert_eq!(
+            runner.next_step(now),
+            Some(LoginStep::Click(ClientPoint { x: 10, y: 20 }))
+        );
+        let now = now + delay;
+        assert_eq!(runner.next_step(now), Some(LoginStep::EnterPic));
+        assert!(!runner.has_pending_steps());
+
+        let one = ClientPoint { x: 1, y: 1 };
+        let two = ClientPoint { x: 2, y: 2 };
+        assert!(runner.enter_pic("12", &[(two, 2), (one, 1)], now));
+        assert_eq!(runner.next_step(now + delay), Some(LoginStep::Click(one)));
+        assert_eq!(
+            runner.next_step(now + delay * 2),
+            Some(LoginStep::Click(two))
+        );
+        assert_eq!(
+            runner.next_step(now + delay * 3),
+            Some(LoginStep::Key(KeyBinding::Enter))
+        );
+        assert!(!runner.has_pending_steps());
+        assert!(runner.complete());
+        assert!(!runner.is_running());
+    }
+
+    #[test]
+    fn enter_pic_missing_digit() {
+        let mut runner = LoginRunner::default();
+        let now = Instant::now();
+
+        assert!(runner.start(&login(), false, now));
+        assert!(!runner.enter_pic("13", &[(ClientPoint::default(), 1)], now));
+    }
+
+    #[test]
+    fn update_login_screen_after_millis() {
+        let mut runner = LoginRunner::default();
+        let now = Instant::now();
+
+        assert!(!runner.update_login_screen(true, 1000, now));
+        assert!(!runner.update_login_screen(true, 1000, now + Duration::from_millis(999)));
+        assert!(runner.update_login_screen(true, 1000, now + Duration::from_millis(1000)));
+        assert!(!runner.update_login_screen(false, 1000, now + Duration::from_millis(1000)));
+        assert!(!runner.update_login_screen(true, 1000, now + Duration::from_millis(1000)));
+    }
+}
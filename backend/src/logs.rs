@@ -0,0 +1,124 @@
+use std::{
+    collections::VecDeque,
+    sync::{LazyLock, Mutex},
+    time::SystemTime,
+};
+
+use log::{Level, Log, Metadata, Record};
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+/// Maximum number of recent records kept in [`LOG_BUFFER`].
+const LOG_BUFFER_CAPACITY: usize = 1000;
+
+static LOG_BUFFER: LazyLock<Mutex<LogBuffer>> =
+    LazyLock::new(|| Mutex::new(LogBuffer::new(LOG_BUFFER_CAPACITY)));
+
+/// A log record captured by [`LogSink`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogRecord {
+    pub time: SystemTime,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// A [`Log`] that buffers recent records and broadcasts new ones to the UI.
+///
+/// This is meant to be chained into the application logger alongside other outputs.
+#[derive(Debug, Default)]
+pub struct LogSink;
+
+impl Log for LogSink {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let record = LogRecord {
+            time: SystemTime::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+        if let Ok(mut buffer) = LOG_BUFFER.lock() {
+            buffer.push(record);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[derive(Debug)]
+struct LogBuffer {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+    sender: Sender<LogRecord>,
+}
+
+impl LogBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+            sender: broadcast::channel(capacity).0,
+        }
+    }
+
+    fn push(&mut self, record: LogRecord) {
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record.clone());
+        let _ = self.sender.send(record);
+    }
+
+    fn subscribe(&self) -> (Vec<LogRecord>, Receiver<LogRecord>) {
+        (
+            self.records.iter().cloned().collect(),
+            self.sender.subscribe(),
+        )
+    }
+}
+
+/// Returns the currently buffered records and a receiver for records logged afterward.
+pub fn subscribe() -> (Vec<LogRecord>, Receiver<LogRecord>) {
+    LOG_BUFFER.lock().unwrap().subscribe()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use log::Level;
+
+    use super::{LogBuffer, LogRecord};
+
+    fn record(message: &str) -> LogRecord {
+        LogRecord {
+            time: SystemTime::now(),
+            level: Level::Info,
+            target: "test".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn push_drops_oldest_and_broadcasts_after_subscribe() {
+        let mut buffer = LogBuffer::new(2);
+        buffer.push(record("first"));
+        buffer.push(record("second"));
+        let (records, mut receiver) = buffer.subscribe();
+        buffer.push(record("third"));
+
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| record.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["first", "second"]
+        );
+        assert_eq!(buffer.records.front().unwrap().message, "second");
+        assert_eq!(receiver.try_recv().unwrap().message, "third");
+        assert!(receiver.try_recv().is_err());
+    }
+}
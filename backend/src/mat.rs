@@ -6,25 +6,34 @@ use opencv::{
 };
 #[cfg(windows)]
 use platforms::windows::Frame;
+#[cfg(target_os = "linux")]
+use platforms::linux::Frame;
 #[cfg(target_os = "macos")]
 use platforms::macos::Frame;
 
+use crate::frame_pool::PooledBuffer;
+
 // A Mat that owns the external buffer.
 #[derive(Debug)]
 pub struct OwnedMat {
     mat: BoxedRef<'static, Mat>,
     #[allow(unused)]
-    data: Vec<u8>,
+    data: PooledBuffer,
 }
 
 impl OwnedMat {
     pub fn new(frame: Frame) -> Self {
-        let data = frame.data;
+        Self::new_pooled(frame.width, frame.height, PooledBuffer::from(frame.data))
+    }
+
+    /// Creates a Mat of `width` x `height` BGRA pixels in `data` taken from a
+    /// [`crate::frame_pool::FramePool`].
+    pub fn new_pooled(width: i32, height: i32, data: PooledBuffer) -> Self {
         let mat = BoxedRef::from(unsafe {
             Mat::new_nd_with_data_unsafe_def(
-                &[frame.height, frame.width],
+                &[height, width],
                 CV_8UC4,
-                data.as_ptr().cast_mut().cast(),
+                data.as_slice().as_ptr().cast_mut().cast(),
             )
             .unwrap()
         });
@@ -37,7 +46,7 @@ impl From<Mat> for OwnedMat {
     fn from(value: Mat) -> Self {
         Self {
             mat: BoxedRef::from(value),
-            data: vec![],
+            data: PooledBuffer::default(),
         }
     }
 }
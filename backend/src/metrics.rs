@@ -0,0 +1,234 @@
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Write as _},
+    io::{Read, Write},
+    iter,
+    net::{TcpListener, TcpStream},
+    sync::{LazyLock, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use log::{debug, error, info};
+
+/// The window for computing the actions per minute.
+const ACTIONS_WINDOW: Duration = Duration::from_secs(60);
+
+/// The window for computing the capture frames per second.
+const FRAMES_WINDOW: Duration = Duration::from_secs(1);
+
+static METRICS: LazyLock<Mutex<Metrics>> = LazyLock::new(|| Mutex::new(Metrics::default()));
+
+/// A gauge of the last observed duration together with its running sum and count.
+#[derive(Debug, Default)]
+struct DurationMetric {
+    last: Duration,
+    sum: Duration,
+    count: u64,
+}
+
+impl DurationMetric {
+    fn observe(&mut self, duration: Duration) {
+        self.last = duration;
+        self.sum += duration;
+        self.count += 1;
+    }
+
+    fn write(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name}_seconds {help}");
+        let _ = writeln!(out, "# TYPE {name}_seconds gauge");
+        let _ = writeln!(out, "{name}_seconds {}", self.last.as_secs_f64());
+        let _ = writeln!(out, "# HELP {name}_seconds_total Total of {help}");
+        let _ = writeln!(out, "# TYPE {name}_seconds_total counter");
+        let _ = writeln!(out, "{name}_seconds_total {}", self.sum.as_secs_f64());
+        let _ = writeln!(out, "# HELP {name}_count Number of observations of {help}");
+        let _ = writeln!(out, "# TYPE {name}_count counter");
+        let _ = writeln!(out, "{name}_count {}", self.count);
+    }
+}
+
+/// The update loop metrics exported in Prometheus text format.
+#[derive(Debug, Default)]
+struct Metrics {
+    tick: DurationMetric,
+    detection: DurationMetric,
+    actions_total: u64,
+    action_failures_total: u64,
+    frames_total: u64,
+    /// Instants of the actions completed within [`ACTIONS_WINDOW`].
+    action_instants: VecDeque<Instant>,
+    /// Instants of the frames captured within [`FRAMES_WINDOW`].
+    frame_instants: VecDeque<Instant>,
+}
+
+impl Metrics {
+    fn record_tick(&mut self, now: Instant, tick: Duration, detection: Option<Duration>) {
+        self.tick.observe(tick);
+        if let Some(detection) = detection {
+            self.detection.observe(detection);
+            self.frames_total += 1;
+            self.frame_instants.push_back(now);
+        }
+        prune(&mut self.frame_instants, now, FRAMES_WINDOW);
+    }
+
+    fn record_actions_completed(&mut self, now: Instant, count: usize) {
+        self.actions_total += count as u64;
+        self.action_instants.extend(iter::repeat_n(now, count));
+        prune(&mut self.action_instants, now, ACTIONS_WINDOW);
+    }
+
+    fn render(&mut self, now: Instant) -> String {
+        prune(&mut self.action_instants, now, ACTIONS_WINDOW);
+        prune(&mut self.frame_instants, now, FRAMES_WINDOW);
+
+        let mut out = String::new();
+        self.tick.write(
+            &mut out,
+            "komari_tick_duration",
+            "the update loop tick duration",
+        );
+        self.detection.write(
+            &mut out,
+            "komari_detection_duration",
+            "the detection duration of a captured frame",
+        );
+        write_metric(
+            &mut out,
+            "komari_actions_total",
+            "counter",
+            "Number of completed actions",
+            self.actions_total,
+        );
+        write_metric(
+            &mut out,
+            "komari_actions_per_minute",
+            "gauge",
+            "Number of actions completed in the last minute",
+            self.action_instants.len(),
+        );
+        write_metric(
+            &mut out,
+            "komari_action_failures_total",
+            "counter",
+            "Number of failed actions",
+            self.action_failures_total,
+        );
+        write_metric(
+            &mut out,
+            "komari_frames_total",
+            "counter",
+            "Number of captured frames",
+            self.frames_total,
+        );
+        write_metric(
+            &mut out,
+            "komari_capture_fps",
+            "gauge",
+            "Number of frames captured in the last second",
+            self.frame_instants.len(),
+        );
+        out
+    }
+}
+
+/// Records the duration of an update loop tick.
+///
+/// `detection` is the duration of detecting the captured frame or [`None`] if no frame was
+/// captured.
+pub fn record_tick(now: Instant, tick: Duration, detection: Option<Duration>) {
+    METRICS.lock().unwrap().record_tick(now, tick, detection);
+}
+
+pub fn record_actions_completed(now: Instant, count: usize) {
+    if count > 0 {
+        METRICS.lock().unwrap().record_actions_completed(now, count);
+    }
+}
+
+pub fn record_action_failure() {
+    METRICS.lock().unwrap().action_failures_total += 1;
+}
+
+/// Serves the metrics on `address` (e.g. `127.0.0.1:9184`) in a background thread.
+pub fn serve(address: &str) -> Result<()> {
+    let listener = TcpListener::bind(address)?;
+    info!(target: "metrics", "serving metrics on {address}");
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(err) = respond(stream) {
+                        debug!(target: "metrics", "failed to respond {err}");
+                    }
+                }
+                Err(err) => error!(target: "metrics", "failed to accept connection {err}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Responds with the metrics regardless of the request path.
+fn respond(mut stream: TcpStream) -> Result<()> {
+    let mut buf = [0; 1024];
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let _ = stream.read(&mut buf)?;
+
+    let body = METRICS.lock().unwrap().render(Instant::now());
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    Ok(())
+}
+
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: impl Display) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+#[inline]
+fn prune(instants: &mut VecDeque<Instant>, now: Instant, window: Duration) {
+    while instants
+        .front()
+        .is_some_and(|instant| now.duration_since(*instant) > window)
+    {
+        instants.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_windowed_gauges() {
+        let now = Instant::now();
+        let mut metrics = Metrics::default();
+        metrics.record_tick(
+            now,
+            Duration::from_millis(20),
+            Some(Duration::from_millis(10)),
+        );
+        metrics.record_tick(now, Duration::from_millis(30), None);
+        metrics.record_actions_completed(now, 2);
+        metrics.action_failures_total = 1;
+
+        let out = metrics.render(now);
+        assert!(out.contains("komari_tick_duration_seconds 0.03\n"));
+        assert!(out.contains("komari_tick_duration_count 2\n"));
+        assert!(out.contains("komari_detection_duration_seconds 0.01\n"));
+        assert!(out.contains("komari_actions_per_minute 2\n"));
+        assert!(out.contains("komari_action_failures_total 1\n"));
+        assert!(out.contains("komari_capture_fps 1\n"));
+
+        let out = metrics.render(now + Duration::from_secs(61));
+        assert!(out.contains("komari_actions_total 2\n"));
+        assert!(out.contains("komari_actions_per_minute 0\n"));
+        assert!(out.contains("komari_capture_fps 0\n"));
+    }
+}
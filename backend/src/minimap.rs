@@ -6,23 +6,40 @@ use std::{
 
 use anyhow::{Result, anyhow};
 use log::{debug, info};
-use opencv::core::{MatTraitConst, Point, Rect, Vec4b};
+use opencv::{
+    core::{Mat, MatTraitConst, MatTraitConstManual, Point, Rect, Size, Vec4b},
+    imgproc::{COLOR_BGRA2GRAY, INTER_AREA, cvt_color_def, resize},
+};
+use serde::Serialize;
 
 use crate::{
     array::Array,
     context::{Context, Contextual, ControlFlow},
     database::Minimap as MinimapData,
-    detect::{Detector, OtherPlayerKind},
+    detect::{Detector, OtherPlayerKind, SearchRegion},
     network::NotificationKind,
     pathing::{
-        MAX_PLATFORMS_COUNT, Platform, PlatformWithNeighbors, find_neighbors, find_platforms_bound,
+        MAX_PLATFORMS_COUNT, Platform, PlatformWithNeighbors, Rope, connect_ropes, find_neighbors,
+        find_platforms_bound, mark_avoided_platforms, mark_no_go_platforms,
     },
-    player::{DOUBLE_JUMP_THRESHOLD, GRAPPLING_MAX_THRESHOLD, JUMP_THRESHOLD, Player},
+    player::{FLYING_MAX_THRESHOLD, MovementThresholds, Player},
     task::{Task, Update, update_detection_task},
 };
 
-const MINIMAP_BORDER_WHITENESS_THRESHOLD: u8 = 160;
 const MAX_PORTALS_COUNT: usize = 16;
+const MAX_NO_GO_ZONES_COUNT: usize = 16;
+const MAX_OTHER_PLAYERS_COUNT: usize = 16;
+/// The width and height of a minimap fingerprint.
+const FINGERPRINT_SIZE: i32 = 16;
+/// The maximum width or height difference for a stored minimap to match the detected one.
+const MINIMAP_SIZE_TOLERANCE: i32 = 2;
+/// The weight of the latest frame anchor matches in [`MinimapConfidence::score`].
+const CONFIDENCE_SMOOTHING: f32 = 0.02;
+/// The [`MinimapConfidence::score`] below which the minimap is re-detected.
+const MIN_CONFIDENCE_SCORE: f32 = 0.6;
+
+/// The positions of other players of the same kind in the minimap.
+type OtherPlayers = Array<Point, MAX_OTHER_PLAYERS_COUNT>;
 
 /// A wrapper struct for [`Rect`] that implements [`Hash`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -49,6 +66,8 @@ pub struct MinimapState {
     data: Option<MinimapData>,
     /// Task to detect the current minimap bounding box and anchor points.
     minimap_task: Option<Task<Result<(Anchors, Rect)>>>,
+    /// The last detected minimap bounding box to search around on re-detection.
+    minimap_region: SearchRegion,
     /// Task to detect the current minimap's rune.
     rune_task: Option<Task<Result<Point>>>,
     /// Task to detect the current minimap's portals.
@@ -60,16 +79,22 @@ pub struct MinimapState {
     portals_invalidate_map: HashMap<HashedRect, u32>,
     /// Task to detect elite boss.
     has_elite_boss_task: Option<Task<Result<()>>>,
+    /// Task to detect the elite boss position in the minimap.
+    elite_boss_task: Option<Task<Result<Point>>>,
     /// Task to detect guildie player(s) in the minimap.
-    has_guildie_player_task: Option<Task<Result<()>>>,
+    guildie_players_task: Option<Task<Result<OtherPlayers>>>,
     /// Task to detect stranger player(s) in the minimap.
-    has_stranger_player_task: Option<Task<Result<()>>>,
+    stranger_players_task: Option<Task<Result<OtherPlayers>>>,
     /// Task to detect firend player(s) in the minimap.
-    has_friend_player_task: Option<Task<Result<()>>>,
+    friend_players_task: Option<Task<Result<OtherPlayers>>>,
+    /// Task to detect inventory full notice.
+    has_inventory_full_task: Option<Task<Result<()>>>,
     /// Whether to update the [`MinimapIdle::platforms`].
     ///
-    /// This is set to true each time [`Self::data`] is updated.
+    /// This is set to true each time [`Self::data`] or [`Self::avoidance_zones`] is updated.
     update_platforms: bool,
+    /// Positions learned from the player repeatedly getting stuck on [`Self::data`].
+    avoidance_zones: Vec<Point>,
 }
 
 impl MinimapState {
@@ -81,17 +106,23 @@ impl MinimapState {
         self.data = data;
         self.update_platforms = true;
     }
+
+    pub fn set_avoidance_zones(&mut self, zones: Vec<Point>) {
+        self.avoidance_zones = zones;
+        self.update_platforms = true;
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
-#[cfg_attr(test, derive(Default, PartialEq))]
+#[cfg_attr(any(test, feature = "simulation"), derive(Default))]
+#[cfg_attr(test, derive(PartialEq))]
 struct Anchors {
     tl: (Point, Vec4b),
     br: (Point, Vec4b),
 }
 
 #[derive(Clone, Copy, Debug)]
-#[cfg_attr(test, derive(Default))]
+#[cfg_attr(any(test, feature = "simulation"), derive(Default))]
 pub struct Threshold<T> {
     value: Option<T>,
     fail_count: u32,
@@ -108,8 +139,45 @@ impl<T> Threshold<T> {
     }
 }
 
+/// The ongoing confidence of an idle minimap detection.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct MinimapConfidence {
+    /// The smoothed ratio of matching anchors over the recent frames from `0.0` to `1.0`.
+    pub score: f32,
+    /// The largest offset in pixels of the detected bounding box from the previous detection.
+    pub jitter: u32,
+    /// The number of frames since both anchors last matched.
+    pub frames_since_lock: u32,
+}
+
+impl Default for MinimapConfidence {
+    fn default() -> Self {
+        Self {
+            score: 1.0,
+            jitter: 0,
+            frames_since_lock: 0,
+        }
+    }
+}
+
+impl MinimapConfidence {
+    fn updated(self, tl_match: bool, br_match: bool) -> Self {
+        let ratio = (tl_match as u32 + br_match as u32) as f32 / 2.0;
+        let frames_since_lock = if tl_match && br_match {
+            0
+        } else {
+            self.frames_since_lock.saturating_add(1)
+        };
+        Self {
+            score: self.score + (ratio - self.score) * CONFIDENCE_SMOOTHING,
+            frames_since_lock,
+            ..self
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
-#[cfg_attr(test, derive(Default))]
+#[cfg_attr(any(test, feature = "simulation"), derive(Default))]
 pub struct MinimapIdle {
     /// Two anchors top left and bottom right of the minimap.
     ///
@@ -124,6 +192,11 @@ pub struct MinimapIdle {
     ///
     /// It is partially overlapped by other UIs if one of the anchor mismatches.
     pub partially_overlapping: bool,
+    /// The detection confidence decaying while the anchors mismatch.
+    ///
+    /// The minimap is re-detected once it falls below [`MIN_CONFIDENCE_SCORE`] instead of waiting
+    /// for both anchors to mismatch.
+    pub confidence: MinimapConfidence,
     /// The rune position.
     ///
     /// The rune position is in player-relative coordinate, which is bottom-left.
@@ -132,12 +205,22 @@ pub struct MinimapIdle {
     ///
     /// TODO: This does not belong to minimap.
     has_elite_boss: Threshold<()>,
-    /// Whether there is a guildie.
-    has_guildie_player: Threshold<()>,
-    /// Whether there is a stranger.
-    has_stranger_player: Threshold<()>,
-    /// Whether there is a friend.
-    has_friend_player: Threshold<()>,
+    /// The elite boss position while [`Self::has_elite_boss`].
+    ///
+    /// The position is in player-relative coordinate, which is bottom-left.
+    elite_boss: Threshold<Point>,
+    /// The guildie positions.
+    ///
+    /// The positions are in player-relative coordinate, which is bottom-left.
+    guildie_players: Threshold<OtherPlayers>,
+    /// The stranger positions.
+    stranger_players: Threshold<OtherPlayers>,
+    /// The friend positions.
+    friend_players: Threshold<OtherPlayers>,
+    /// Whether the inventory full notice is shown.
+    ///
+    /// TODO: This does not belong to minimap either.
+    has_inventory_full: Threshold<()>,
     /// The portal positions.
     ///
     /// The portals are in player-relative coordinate, which is bottom-left.
@@ -150,9 +233,33 @@ pub struct MinimapIdle {
     ///
     /// The platforms bound is in OpenCV native coordinate, which is top-left.
     pub platforms_bound: Option<Rect>,
+    /// The user provided no-go zones.
+    ///
+    /// The zones are in player-relative coordinate, which is bottom-left.
+    no_go_zones: Array<Rect, MAX_NO_GO_ZONES_COUNT>,
 }
 
 impl MinimapIdle {
+    /// Creates an idle minimap spanning the whole `minimap` size for simulation.
+    #[cfg(feature = "simulation")]
+    pub fn simulated(minimap: &MinimapData) -> Self {
+        let bbox = Rect::new(0, 0, minimap.width, minimap.height);
+        let (platforms, platforms_bound) = platforms_from_data(bbox, minimap, &[]);
+        MinimapIdle {
+            bbox,
+            platforms,
+            platforms_bound,
+            no_go_zones: Array::from_iter(no_go_zones_from_data(bbox, minimap)),
+            ..MinimapIdle::default()
+        }
+    }
+
+    /// Whether `point` in player-relative coordinate is inside any of the no-go zones.
+    #[inline]
+    pub fn is_in_no_go_zone(&self, point: Point) -> bool {
+        self.no_go_zones.iter().any(|zone| zone.contains(point))
+    }
+
     #[inline]
     pub fn rune(&self) -> Option<Point> {
         self.rune.value
@@ -163,6 +270,12 @@ impl MinimapIdle {
         self.rune.value = Some(rune);
     }
 
+    #[cfg(test)]
+    pub fn set_elite_boss(&mut self, elite_boss: Point) {
+        self.has_elite_boss.value = Some(());
+        self.elite_boss.value = Some(elite_boss);
+    }
+
     #[inline]
     pub fn portals(&self) -> Array<Rect, MAX_PORTALS_COUNT> {
         self.portals
@@ -173,11 +286,39 @@ impl MinimapIdle {
         self.has_elite_boss.value.is_some()
     }
 
+    #[inline]
+    pub fn elite_boss(&self) -> Option<Point> {
+        self.elite_boss.value
+    }
+
+    #[inline]
+    pub fn has_inventory_full(&self) -> bool {
+        self.has_inventory_full.value.is_some()
+    }
+
+    /// The other players positions and their kinds.
+    #[inline]
+    pub fn other_players(&self) -> Vec<(OtherPlayerKind, Point)> {
+        [
+            (OtherPlayerKind::Guildie, self.guildie_players.value),
+            (OtherPlayerKind::Stranger, self.stranger_players.value),
+            (OtherPlayerKind::Friend, self.friend_players.value),
+        ]
+        .into_iter()
+        .flat_map(|(kind, players)| {
+            players
+                .into_iter()
+                .flatten()
+                .map(move |point| (kind, point))
+        })
+        .collect()
+    }
+
     #[inline]
     pub fn has_any_other_player(&self) -> bool {
-        self.has_guildie_player.value.is_some()
-            || self.has_stranger_player.value.is_some()
-            || self.has_friend_player.value.is_some()
+        self.guildie_players.value.is_some()
+            || self.stranger_players.value.is_some()
+            || self.friend_players.value.is_some()
     }
 
     #[inline]
@@ -221,46 +362,74 @@ fn update_context(contextual: Minimap, context: &Context, state: &mut MinimapSta
 }
 
 fn update_detecting_context(context: &Context, state: &mut MinimapState) -> Minimap {
-    let Update::Ok((anchors, bbox)) =
-        update_detection_task(context, 2000, &mut state.minimap_task, move |detector| {
-            let bbox = detector.detect_minimap(MINIMAP_BORDER_WHITENESS_THRESHOLD)?;
+    let region = state.minimap_region.get();
+    let border_threshold = context.detection_tuning.minimap_border_threshold;
+    let (anchors, bbox, jitter) =
+        match update_detection_task(context, 2000, &mut state.minimap_task, move |detector| {
+            let bbox = detector.detect_minimap(region, border_threshold)?;
             let size = bbox.width.min(bbox.height) as usize;
-            let tl = anchor_at(detector.mat(), bbox.tl(), size, 1)?;
-            let br = anchor_at(detector.mat(), bbox.br(), size, -1)?;
+            let tl = anchor_at(detector.mat(), bbox.tl(), size, 1, border_threshold)?;
+            let br = anchor_at(detector.mat(), bbox.br(), size, -1, border_threshold)?;
             let anchors = Anchors { tl, br };
             debug!(target: "minimap", "anchor points: {anchors:?}");
             Ok((anchors, bbox))
-        })
-    else {
-        return Minimap::Detecting;
-    };
+        }) {
+            Update::Ok((anchors, bbox)) => {
+                let jitter = state
+                    .minimap_region
+                    .last()
+                    .map(|last| (last.x - bbox.x).abs().max((last.y - bbox.y).abs()) as u32)
+                    .unwrap_or_default();
+                state.minimap_region.update(Some(bbox));
+                (anchors, bbox, jitter)
+            }
+            Update::Err(_) => {
+                state.minimap_region.update(None);
+                return Minimap::Detecting;
+            }
+            Update::Pending => return Minimap::Detecting,
+        };
 
     let (platforms, platforms_bound) = state
         .data
         .as_ref()
-        .map(|data| platforms_from_data(bbox, data))
+        .map(|data| platforms_from_data(bbox, data, &state.avoidance_zones))
+        .unwrap_or_default();
+    let no_go_zones = state
+        .data
+        .as_ref()
+        .map(|data| Array::from_iter(no_go_zones_from_data(bbox, data)))
         .unwrap_or_default();
     state.update_platforms = false;
     state.rune_task = None;
     state.portals_task = None;
     state.portals_invalidate_map.clear();
     state.has_elite_boss_task = None;
-    state.has_guildie_player_task = None;
-    state.has_stranger_player_task = None;
-    state.has_friend_player_task = None;
+    state.elite_boss_task = None;
+    state.guildie_players_task = None;
+    state.stranger_players_task = None;
+    state.friend_players_task = None;
+    state.has_inventory_full_task = None;
 
     Minimap::Idle(MinimapIdle {
         anchors,
         bbox,
         partially_overlapping: false,
+        confidence: MinimapConfidence {
+            jitter,
+            ..MinimapConfidence::default()
+        },
         rune: Threshold::new(3),
         has_elite_boss: Threshold::new(2),
-        has_guildie_player: Threshold::new(2),
-        has_stranger_player: Threshold::new(2),
-        has_friend_player: Threshold::new(2),
+        elite_boss: Threshold::new(3),
+        guildie_players: Threshold::new(2),
+        stranger_players: Threshold::new(2),
+        friend_players: Threshold::new(2),
+        has_inventory_full: Threshold::new(2),
         portals: Array::new(),
         platforms,
         platforms_bound,
+        no_go_zones,
     })
 }
 
@@ -278,12 +447,15 @@ fn update_idle_context(
         bbox,
         rune,
         has_elite_boss,
-        has_guildie_player,
-        has_stranger_player,
-        has_friend_player,
+        elite_boss,
+        guildie_players,
+        stranger_players,
+        friend_players,
+        has_inventory_full,
         portals,
         mut platforms,
         mut platforms_bound,
+        mut no_go_zones,
         ..
     } = idle;
     let tl_pixel = pixel_at(context.detector_unwrap().mat(), anchors.tl.0)?;
@@ -300,62 +472,105 @@ fn update_idle_context(
         return None;
     }
 
+    let confidence = idle.confidence.updated(tl_match, br_match);
+    if confidence.score < MIN_CONFIDENCE_SCORE {
+        debug!(target: "minimap", "confidence decayed: {confidence:?}");
+        return None;
+    }
+
     let partially_overlapping = (tl_match && !br_match) || (!tl_match && br_match);
-    let rune = update_rune_task(context, &mut state.rune_task, bbox, rune);
     let has_elite_boss =
         update_elite_boss_task(context, &mut state.has_elite_boss_task, has_elite_boss);
-    let has_guildie_player = update_other_player_task(
+    let elite_boss = update_elite_boss_position_task(
         context,
-        &mut state.has_guildie_player_task,
+        &mut state.elite_boss_task,
         bbox,
-        has_guildie_player,
-        OtherPlayerKind::Guildie,
+        has_elite_boss.value.is_some(),
+        elite_boss,
     );
-    let has_stranger_player = update_other_player_task(
+    let has_inventory_full = update_inventory_full_task(
         context,
-        &mut state.has_stranger_player_task,
-        bbox,
-        has_stranger_player,
-        OtherPlayerKind::Stranger,
-    );
-    let has_friend_player = update_other_player_task(
-        context,
-        &mut state.has_friend_player_task,
-        bbox,
-        has_friend_player,
-        OtherPlayerKind::Friend,
-    );
-    let portals = update_portals_task(
-        context,
-        &mut state.portals_task,
-        &mut state.portals_invalidate_map,
-        portals,
-        bbox,
+        &mut state.has_inventory_full_task,
+        has_inventory_full,
     );
+    // Detections inside the minimap would have the same results
+    let (rune, guildie_players, stranger_players, friend_players, portals) =
+        if context.minimap_unchanged {
+            (
+                rune,
+                guildie_players,
+                stranger_players,
+                friend_players,
+                portals,
+            )
+        } else {
+            (
+                if context.low_power {
+                    rune
+                } else {
+                    update_rune_task(context, &mut state.rune_task, bbox, rune)
+                },
+                update_other_player_task(
+                    context,
+                    &mut state.guildie_players_task,
+                    bbox,
+                    guildie_players,
+                    OtherPlayerKind::Guildie,
+                ),
+                update_other_player_task(
+                    context,
+                    &mut state.stranger_players_task,
+                    bbox,
+                    stranger_players,
+                    OtherPlayerKind::Stranger,
+                ),
+                update_other_player_task(
+                    context,
+                    &mut state.friend_players_task,
+                    bbox,
+                    friend_players,
+                    OtherPlayerKind::Friend,
+                ),
+                update_portals_task(
+                    context,
+                    &mut state.portals_task,
+                    &mut state.portals_invalidate_map,
+                    portals,
+                    bbox,
+                ),
+            )
+        };
 
     // TODO: any better way to read persistent state in other contextual?
     if state.update_platforms {
         if let Some(data) = state.data() {
-            let (updated_platforms, updated_bound) = platforms_from_data(bbox, data);
+            let (updated_platforms, updated_bound) =
+                platforms_from_data(bbox, data, &state.avoidance_zones);
             platforms = updated_platforms;
-            platforms_bound = updated_bound
+            platforms_bound = updated_bound;
+            no_go_zones = Array::from_iter(no_go_zones_from_data(bbox, data));
         } else {
             platforms = Array::new();
             platforms_bound = None;
+            no_go_zones = Array::new();
         }
         state.update_platforms = false;
     }
 
     Some(Minimap::Idle(MinimapIdle {
         partially_overlapping,
+        confidence,
         rune,
         has_elite_boss,
-        has_guildie_player,
-        has_stranger_player,
-        has_friend_player,
+        elite_boss,
+        guildie_players,
+        stranger_players,
+        friend_players,
+        has_inventory_full,
         portals,
         platforms,
         platforms_bound,
+        no_go_zones,
         ..idle
     }))
 }
@@ -424,19 +639,70 @@ fn update_elite_boss_task(
 }
 
 #[inline]
-fn update_other_player_task(
+fn update_elite_boss_position_task(
+    context: &Context,
+    task: &mut Option<Task<Result<Point>>>,
+    minimap: Rect,
+    has_elite_boss: bool,
+    elite_boss: Threshold<Point>,
+) -> Threshold<Point> {
+    if !has_elite_boss {
+        *task = None;
+        return Threshold::new(elite_boss.max_fail_count);
+    }
+
+    update_threshold_detection(context, 1000, elite_boss, task, move |detector| {
+        detector
+            .detect_minimap_elite_boss(minimap)
+            .map(|bbox| center_of_bbox(bbox, minimap))
+    })
+}
+
+#[inline]
+fn update_inventory_full_task(
     context: &Context,
     task: &mut Option<Task<Result<()>>>,
+    has_inventory_full: Threshold<()>,
+) -> Threshold<()> {
+    let did_have_inventory_full = has_inventory_full.value.is_some();
+    let has_inventory_full =
+        update_threshold_detection(context, 10000, has_inventory_full, task, move |detector| {
+            if detector.detect_inventory_full() {
+                Ok(())
+            } else {
+                Err(anyhow!("inventory is not full"))
+            }
+        });
+
+    if !context.halting && !did_have_inventory_full && has_inventory_full.value.is_some() {
+        info!(target: "minimap", "sending inventory full notification...");
+        let _ = context
+            .notification
+            .schedule_notification(NotificationKind::InventoryFull);
+    }
+    has_inventory_full
+}
+
+#[inline]
+fn update_other_player_task(
+    context: &Context,
+    task: &mut Option<Task<Result<OtherPlayers>>>,
     minimap: Rect,
-    threshold: Threshold<()>,
+    threshold: Threshold<OtherPlayers>,
     kind: OtherPlayerKind,
-) -> Threshold<()> {
+) -> Threshold<OtherPlayers> {
     let has_player = threshold.value.is_some();
     let threshold = update_threshold_detection(context, 3000, threshold, task, move |detector| {
-        if detector.detect_player_kind(minimap, kind) {
-            Ok(())
-        } else {
+        let players = detector
+            .detect_player_kind(minimap, kind)
+            .into_iter()
+            .take(MAX_OTHER_PLAYERS_COUNT)
+            .map(|bbox| center_of_bbox(bbox, minimap))
+            .collect::<OtherPlayers>();
+        if players.is_empty() {
             Err(anyhow!("player not found"))
+        } else {
+            Ok(players)
         }
     });
     if !context.halting && !has_player && threshold.value.is_some() {
@@ -528,22 +794,53 @@ fn merge_portals_and_invalidate_if_needed(
 fn platforms_from_data(
     bbox: Rect,
     minimap: &MinimapData,
+    avoidance_zones: &[Point],
 ) -> (Array<PlatformWithNeighbors, 24>, Option<Rect>) {
-    let platforms = Array::from_iter(find_neighbors(
+    let thresholds = MovementThresholds::from(minimap.movement_overrides);
+    let mut platforms = find_neighbors(
         &minimap
             .platforms
             .iter()
             .copied()
             .map(Platform::from)
             .collect::<Vec<_>>(),
-        DOUBLE_JUMP_THRESHOLD,
-        JUMP_THRESHOLD,
-        GRAPPLING_MAX_THRESHOLD,
-    ));
+        thresholds.double_jump,
+        thresholds.jump,
+        FLYING_MAX_THRESHOLD,
+    );
+    connect_ropes(
+        &mut platforms,
+        &minimap
+            .ropes
+            .iter()
+            .copied()
+            .map(Rope::from)
+            .collect::<Vec<_>>(),
+    );
+    mark_avoided_platforms(&mut platforms, avoidance_zones);
+    mark_no_go_platforms(&mut platforms, &no_go_zones_from_data(bbox, minimap));
+    let platforms = Array::from_iter(platforms);
     let bound = find_platforms_bound(bbox, &platforms);
     (platforms, bound)
 }
 
+/// Converts the no-go zones of `minimap` from top-left to player-relative coordinate.
+fn no_go_zones_from_data(bbox: Rect, minimap: &MinimapData) -> Vec<Rect> {
+    minimap
+        .no_go_zones
+        .iter()
+        .take(MAX_NO_GO_ZONES_COUNT)
+        .map(|zone| {
+            Rect::new(
+                zone.x,
+                bbox.height - (zone.y + zone.height),
+                zone.width,
+                zone.height,
+            )
+        })
+        .collect()
+}
+
 #[inline]
 fn update_threshold_detection<T, F>(
     context: &Context,
@@ -603,16 +900,14 @@ fn anchor_at(
     offset: Point,
     size: usize,
     sign: i32,
+    border_threshold: u8,
 ) -> Result<(Point, Vec4b)> {
     (0..size)
         .find_map(|i| {
             let value = sign * i as i32;
             let diag = offset + Point::new(value, value);
             let pixel = pixel_at(mat, diag)?;
-            if pixel
-                .iter()
-                .all(|v| *v >= MINIMAP_BORDER_WHITENESS_THRESHOLD)
-            {
+            if pixel.iter().all(|v| *v >= border_threshold) {
                 Some((diag, pixel))
             } else {
                 None
@@ -621,6 +916,89 @@ fn anchor_at(
         .ok_or(anyhow!("anchor not found"))
 }
 
+/// Computes a downscaled grayscale fingerprint of the minimap inside `bbox` of `mat`.
+pub fn minimap_fingerprint(mat: &impl MatTraitConst, bbox: Rect) -> Option<Vec<u8>> {
+    let minimap = mat.roi(bbox).ok()?;
+    let mut gray = Mat::default();
+    cvt_color_def(&minimap, &mut gray, COLOR_BGRA2GRAY).ok()?;
+    let mut resized = Mat::default();
+    resize(
+        &gray,
+        &mut resized,
+        Size::new(FINGERPRINT_SIZE, FINGERPRINT_SIZE),
+        0.0,
+        0.0,
+        INTER_AREA,
+    )
+    .ok()?;
+    resized.data_bytes().ok().map(<[u8]>::to_vec)
+}
+
+/// Whether stored `minimap` matches the detected minimap of `size` and `fingerprint`.
+///
+/// The `min_similarity` is the minimum fingerprint similarity from 0 to 1 to match.
+pub fn minimap_matches(
+    minimap: &MinimapData,
+    size: (i32, i32),
+    fingerprint: &[u8],
+    min_similarity: f32,
+) -> bool {
+    minimap_match_score(minimap, size, fingerprint, min_similarity).is_some()
+}
+
+/// Finds the stored minimap in `minimaps` that best matches the detected minimap of `size` and
+/// `fingerprint`.
+///
+/// A stored minimap without a fingerprint is matched by its size only.
+pub fn find_matching_minimap<'a>(
+    minimaps: &'a [MinimapData],
+    size: (i32, i32),
+    fingerprint: &[u8],
+    min_similarity: f32,
+) -> Option<&'a MinimapData> {
+    minimaps
+        .iter()
+        .filter_map(|minimap| {
+            let score = minimap_match_score(minimap, size, fingerprint, min_similarity)?;
+            Some((minimap, score))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(minimap, _)| minimap)
+}
+
+fn minimap_match_score(
+    minimap: &MinimapData,
+    size: (i32, i32),
+    fingerprint: &[u8],
+    min_similarity: f32,
+) -> Option<f32> {
+    if (minimap.width - size.0).abs() > MINIMAP_SIZE_TOLERANCE
+        || (minimap.height - size.1).abs() > MINIMAP_SIZE_TOLERANCE
+    {
+        return None;
+    }
+    if minimap.fingerprint.is_empty() {
+        return Some(min_similarity);
+    }
+
+    let similarity = fingerprint_similarity(&minimap.fingerprint, fingerprint);
+    (similarity >= min_similarity).then_some(similarity)
+}
+
+/// Computes the similarity between two fingerprints from `0.0` to `1.0` if identical.
+fn fingerprint_similarity(a: &[u8], b: &[u8]) -> f32 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+
+    let diff = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| a.abs_diff(*b) as u32)
+        .sum::<u32>();
+    1.0 - diff as f32 / (a.len() as f32 * u8::MAX as f32)
+}
+
 #[cfg(test)]
 mod tests {
     use std::{assert_matches::assert_matches, time::Duration};
@@ -630,7 +1008,7 @@ mod tests {
     use tokio::time;
 
     use super::*;
-    use crate::detect::MockDetector;
+    use crate::{database::DetectionTuning, detect::MockDetector};
 
     fn create_test_mat() -> (Mat, Anchors) {
         let mut mat = Mat::zeros(100, 100, opencv::core::CV_8UC4)
@@ -665,8 +1043,11 @@ mod tests {
             .returning(|| create_mock_detector().0);
         detector
             .expect_detect_minimap()
-            .with(eq(MINIMAP_BORDER_WHITENESS_THRESHOLD))
-            .returning(move |_| Ok(bbox));
+            .with(
+                eq(None),
+                eq(DetectionTuning::default().minimap_border_threshold),
+            )
+            .returning(move |_, _| Ok(bbox));
         detector.expect_mat().return_const(mat.into());
         (detector, bbox, anchors, rune_bbox)
     }
@@ -707,15 +1088,17 @@ mod tests {
                 assert_eq!(idle.rune.value, None);
                 assert!(!idle.has_elite_boss());
                 assert!(!idle.has_any_other_player());
+                assert!(!idle.has_inventory_full());
                 assert!(idle.portals.is_empty());
 
                 assert_eq!(state.data, None);
                 assert_matches!(state.minimap_task, Some(_));
                 assert_matches!(state.rune_task, None);
                 assert_matches!(state.has_elite_boss_task, None);
-                assert_matches!(state.has_guildie_player_task, None);
-                assert_matches!(state.has_stranger_player_task, None);
-                assert_matches!(state.has_friend_player_task, None);
+                assert_matches!(state.guildie_players_task, None);
+                assert_matches!(state.stranger_players_task, None);
+                assert_matches!(state.friend_players_task, None);
+                assert_matches!(state.has_inventory_full_task, None);
                 assert_matches!(state.portals_task, None);
                 assert!(state.portals_invalidate_map.is_empty());
             }
@@ -732,14 +1115,18 @@ mod tests {
             anchors,
             bbox,
             partially_overlapping: false,
+            confidence: MinimapConfidence::default(),
             rune: Threshold::new(3),
             has_elite_boss: Threshold::default(),
-            has_guildie_player: Threshold::default(),
-            has_stranger_player: Threshold::default(),
-            has_friend_player: Threshold::default(),
+            elite_boss: Threshold::default(),
+            guildie_players: Threshold::default(),
+            stranger_players: Threshold::default(),
+            friend_players: Threshold::default(),
+            has_inventory_full: Threshold::default(),
             portals: Array::new(),
             platforms: Array::new(),
             platforms_bound: None,
+            no_go_zones: Array::new(),
         };
 
         let minimap = advance_task(Minimap::Idle(idle), detector, &mut state).await;
@@ -752,6 +1139,39 @@ mod tests {
         }
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn minimap_idle_redetect_on_confidence_decay() {
+        let mut state = MinimapState::default();
+        let (detector, bbox, mut anchors, _) = create_mock_detector();
+        anchors.br.1 = Vec4b::all(0);
+        let context = Context::new(None, Some(detector));
+
+        let mut minimap = Minimap::Idle(MinimapIdle {
+            anchors,
+            bbox,
+            ..MinimapIdle::default()
+        });
+        let mut frames = 0;
+        while let Minimap::Idle(idle) = minimap {
+            assert_eq!(idle.confidence.frames_since_lock, frames);
+            assert!(idle.confidence.score >= MIN_CONFIDENCE_SCORE);
+            minimap = update_context(minimap, &context, &mut state);
+            frames += 1;
+        }
+        // Partially overlapping is tolerated for a while before re-detecting
+        assert!(frames > 10);
+    }
+
+    #[test]
+    fn minimap_confidence_updated() {
+        let confidence = MinimapConfidence::default().updated(true, false);
+        assert!(confidence.score < 1.0);
+        assert_eq!(confidence.frames_since_lock, 1);
+
+        let confidence = confidence.updated(true, true);
+        assert_eq!(confidence.frames_since_lock, 0);
+    }
+
     fn rect(x: i32, y: i32, w: i32, h: i32) -> Rect {
         Rect::new(x, y, w, h)
     }
@@ -909,4 +1329,47 @@ mod tests {
         assert_eq!(threshold.value, None);
         assert_eq!(threshold.fail_count, 0);
     }
+
+    #[test]
+    fn find_matching_minimap_by_size_and_fingerprint() {
+        let minimap = |id, width, fingerprint| MinimapData {
+            id: Some(id),
+            width,
+            height: 50,
+            fingerprint,
+            ..MinimapData::default()
+        };
+        let minimaps = vec![
+            minimap(1, 100, vec![0; 4]),
+            minimap(2, 101, vec![200; 4]),
+            minimap(3, 200, vec![]),
+        ];
+        let similarity = DetectionTuning::default().minimap_similarity;
+
+        assert_eq!(
+            find_matching_minimap(&minimaps, (100, 50), &[190; 4], similarity)
+                .and_then(|data| data.id),
+            Some(2)
+        );
+        assert_eq!(
+            find_matching_minimap(&minimaps, (100, 50), &[100; 4], similarity),
+            None
+        );
+        assert_eq!(
+            find_matching_minimap(&minimaps, (199, 51), &[100; 4], similarity)
+                .and_then(|data| data.id),
+            Some(3)
+        );
+        assert!(!minimap_matches(
+            &minimaps[0],
+            (150, 50),
+            &[0; 4],
+            similarity
+        ));
+        // A lower similarity matches a less similar fingerprint
+        assert_eq!(
+            find_matching_minimap(&minimaps, (100, 50), &[60; 4], 0.7).and_then(|data| data.id),
+            Some(1)
+        );
+    }
 }
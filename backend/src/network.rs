@@ -7,20 +7,33 @@ use std::{
     time::Duration,
 };
 
-use anyhow::{Error, Ok, bail};
+use anyhow::{Error, Ok, anyhow, bail};
+use base64::{Engine, engine::general_purpose::STANDARD};
 use bit_vec::BitVec;
 use log::{debug, error};
 use reqwest::{
-    Client, Url,
+    Client, Proxy, Url,
     multipart::{Form, Part},
 };
 use serde::Serialize;
 use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
     spawn,
     time::{Instant, sleep},
 };
+use tokio_socks::tcp::Socks5Stream;
 
-use crate::Settings;
+use crate::{
+    Settings,
+    config::{ProxyConfig, config},
+};
+
+/// The port used when a SOCKS5 proxy URL has none.
+const DEFAULT_SOCKS5_PORT: u16 = 1080;
+
+/// The maximum length of a HTTP proxy response to a `CONNECT` request.
+const MAX_CONNECT_RESPONSE_LEN: usize = 4096;
 
 static TRUE: bool = true;
 static FALSE: bool = false;
@@ -35,6 +48,15 @@ pub enum NotificationKind {
     PlayerStrangerAppear,
     PlayerFriendAppear,
     PlayerIsDead,
+    InventoryFull,
+    PanicTriggered,
+    ChatKeyword,
+    WatchdogTripped,
+    FocusLost,
+    ActionFailed,
+    BreakStarted,
+    BreakEnded,
+    SessionEnded,
 }
 
 impl From<NotificationKind> for usize {
@@ -73,7 +95,8 @@ struct ScheduledNotification {
 
 #[derive(Debug)]
 pub struct DiscordNotification {
-    client: Client,
+    /// The client or [`None`] if the configured proxy is invalid.
+    client: Option<Client>,
     settings: Rc<RefCell<Settings>>,
     scheduled: Arc<Mutex<Vec<ScheduledNotification>>>,
     /// Storing currently incomplete / pending notifications
@@ -85,7 +108,7 @@ pub struct DiscordNotification {
 impl DiscordNotification {
     pub fn new(settings: Rc<RefCell<Settings>>) -> Self {
         Self {
-            client: Client::new(),
+            client: http_client(),
             settings,
             scheduled: Arc::new(Mutex::new(vec![])),
             pending: Arc::new(Mutex::new(BitVec::from_elem(
@@ -96,6 +119,23 @@ impl DiscordNotification {
     }
 
     pub fn schedule_notification(&self, kind: NotificationKind) -> Result<(), Error> {
+        self.schedule_notification_inner(kind, None)
+    }
+
+    /// Schedules a notification of `kind` with `details` appended to its content.
+    pub fn schedule_notification_with_details(
+        &self,
+        kind: NotificationKind,
+        details: String,
+    ) -> Result<(), Error> {
+        self.schedule_notification_inner(kind, Some(details))
+    }
+
+    fn schedule_notification_inner(
+        &self,
+        kind: NotificationKind,
+        details: Option<String>,
+    ) -> Result<(), Error> {
         let settings = self.settings.borrow();
         let is_enabled = match kind {
             NotificationKind::FailOrMapChange => {
@@ -113,6 +153,19 @@ impl DiscordNotification {
             NotificationKind::PlayerFriendAppear => {
                 settings.notifications.notify_on_player_friend_appear
             }
+            NotificationKind::InventoryFull => settings.notifications.notify_on_inventory_full,
+            // Opted in through the panic playbook notify step
+            NotificationKind::PanicTriggered => true,
+            NotificationKind::ChatKeyword => settings.notifications.notify_on_chat_keyword,
+            NotificationKind::WatchdogTripped => settings.notifications.notify_on_watchdog_trip,
+            NotificationKind::FocusLost => settings.notifications.notify_on_focus_lost,
+            // Opted in through the action failure policy
+            NotificationKind::ActionFailed => true,
+            NotificationKind::BreakStarted | NotificationKind::BreakEnded => {
+                settings.notifications.notify_on_break
+            }
+            // Opted in through the character shutdown routine
+            NotificationKind::SessionEnded => true,
         };
         if !is_enabled {
             bail!("notification not enabled");
@@ -121,6 +174,9 @@ impl DiscordNotification {
             bail!("webhook url not provided");
         }
 
+        let Some(client) = self.client.clone() else {
+            bail!("configured proxy is invalid");
+        };
         let mut pending = self.pending.lock().unwrap();
         if pending[kind] {
             bail!("notification is already sending");
@@ -138,7 +194,7 @@ impl DiscordNotification {
             .not()
             .then_some(format!("<@{}> ", settings.notifications.discord_user_id))
             .unwrap_or_default();
-        let content = match kind {
+        let mut content = match kind {
             NotificationKind::FailOrMapChange => {
                 if self.settings.borrow().stop_on_fail_or_change_map {
                     format!(
@@ -166,19 +222,60 @@ impl DiscordNotification {
             NotificationKind::PlayerFriendAppear => {
                 format!("{user_id}Bot has detected friend player(s)")
             }
+            NotificationKind::InventoryFull => {
+                format!("{user_id}The inventory is full")
+            }
+            NotificationKind::PanicTriggered => {
+                format!("{user_id}Bot has triggered a panic playbook")
+            }
+            NotificationKind::ChatKeyword => {
+                format!("{user_id}Bot has detected a keyword in the chat")
+            }
+            NotificationKind::WatchdogTripped => {
+                format!("{user_id}Bot stopped because the watchdog has tripped")
+            }
+            NotificationKind::FocusLost => {
+                format!("{user_id}The game window has lost focus")
+            }
+            NotificationKind::ActionFailed => {
+                format!("{user_id}Bot has failed to perform an action after retrying")
+            }
+            NotificationKind::BreakStarted => {
+                format!("{user_id}Bot is taking a break")
+            }
+            NotificationKind::BreakEnded => {
+                format!("{user_id}Bot has resumed from a break")
+            }
+            NotificationKind::SessionEnded => {
+                format!("{user_id}Bot has ended the session")
+            }
         };
+        if let Some(details) = details {
+            content.push('\n');
+            content.push_str(&details);
+        }
         let body = DiscordWebhookBody {
             content,
             username: "maple-bot",
             attachments: vec![],
         };
         let frames = match kind {
+            _ if settings.notifications.hide_screenshots => vec![],
             NotificationKind::FailOrMapChange => vec![(None, 2), (None, 4)],
             NotificationKind::EliteBossAppear
             | NotificationKind::PlayerIsDead
             | NotificationKind::PlayerGuildieAppear
             | NotificationKind::PlayerStrangerAppear
             | NotificationKind::PlayerFriendAppear
+            | NotificationKind::InventoryFull
+            | NotificationKind::PanicTriggered
+            | NotificationKind::ChatKeyword
+            | NotificationKind::WatchdogTripped
+            | NotificationKind::FocusLost
+            | NotificationKind::ActionFailed
+            | NotificationKind::BreakStarted
+            | NotificationKind::BreakEnded
+            | NotificationKind::SessionEnded
             | NotificationKind::RuneAppear => vec![(None, 2)],
         };
         let delay = match kind {
@@ -188,6 +285,15 @@ impl DiscordNotification {
             | NotificationKind::PlayerGuildieAppear
             | NotificationKind::PlayerStrangerAppear
             | NotificationKind::PlayerFriendAppear
+            | NotificationKind::InventoryFull
+            | NotificationKind::PanicTriggered
+            | NotificationKind::ChatKeyword
+            | NotificationKind::WatchdogTripped
+            | NotificationKind::FocusLost
+            | NotificationKind::ActionFailed
+            | NotificationKind::BreakStarted
+            | NotificationKind::BreakEnded
+            | NotificationKind::SessionEnded
             | NotificationKind::RuneAppear => 3,
         };
 
@@ -201,7 +307,6 @@ impl DiscordNotification {
         });
         pending.set(kind.into(), true);
 
-        let client = self.client.clone();
         let pending = self.pending.clone();
         let scheduled = self.scheduled.clone();
         spawn(async move {
@@ -267,7 +372,7 @@ async fn post_notification(
         notification.body.attachments.push(Attachment {
             id: i,
             description: format!("Game snapshot #{i}"),
-            filename: format!("image_{i}.png"),
+            filename: format!("image_{i}.jpg"),
         });
     }
 
@@ -284,9 +389,9 @@ async fn post_notification(
         form = form.part(
             format!("files[{i}]"),
             Part::bytes(frame)
-                .mime_str("image/png")
+                .mime_str("image/jpeg")
                 .unwrap()
-                .file_name(format!("image_{i}.png")),
+                .file_name(format!("image_{i}.jpg")),
         );
     }
 
@@ -319,14 +424,113 @@ struct Attachment {
     filename: String,
 }
 
+/// Creates a [`Client`] routed through the configured proxy if any.
+///
+/// Returns [`None`] if the proxy is invalid so that no traffic bypasses it.
+pub fn http_client() -> Option<Client> {
+    let Some(proxy) = config().proxy.as_ref() else {
+        return Some(Client::new());
+    };
+    proxied_client(proxy)
+        .inspect_err(|err| error!(target: "network", "failed to use proxy {err}"))
+        .ok()
+}
+
+/// Opens a TCP connection to `host:port` through the configured proxy if any.
+pub async fn connect_tcp(host: &str, port: u16) -> Result<TcpStream, Error> {
+    let Some(proxy) = config().proxy.as_ref() else {
+        return Ok(TcpStream::connect((host, port)).await?);
+    };
+    let url = proxy_url(proxy)?;
+    let proxy_address = format!(
+        "{}:{}",
+        url.host_str().ok_or(anyhow!("proxy URL has no host"))?,
+        url.port_or_known_default().unwrap_or(DEFAULT_SOCKS5_PORT)
+    );
+
+    if url.scheme() == "http" {
+        let mut stream = TcpStream::connect(proxy_address.as_str()).await?;
+        http_connect(&mut stream, proxy, host, port).await?;
+        return Ok(stream);
+    }
+    let stream = match proxy.credentials() {
+        Some((username, password)) => {
+            Socks5Stream::connect_with_password(
+                proxy_address.as_str(),
+                (host, port),
+                username,
+                password,
+            )
+            .await?
+        }
+        None => Socks5Stream::connect(proxy_address.as_str(), (host, port)).await?,
+    };
+    Ok(stream.into_inner())
+}
+
+fn proxied_client(proxy: &ProxyConfig) -> Result<Client, Error> {
+    let mut reqwest_proxy = Proxy::all(proxy_url(proxy)?)?;
+    if let Some((username, password)) = proxy.credentials() {
+        reqwest_proxy = reqwest_proxy.basic_auth(username, password);
+    }
+    Ok(Client::builder().proxy(reqwest_proxy).build()?)
+}
+
+fn proxy_url(proxy: &ProxyConfig) -> Result<Url, Error> {
+    let url = Url::parse(&proxy.url)?;
+    match url.scheme() {
+        "http" | "socks5" | "socks5h" => Ok(url),
+        scheme => bail!("unsupported proxy scheme {scheme}"),
+    }
+}
+
+/// Tunnels `stream` connected to a HTTP proxy to `host:port` with a `CONNECT` request.
+async fn http_connect(
+    stream: &mut TcpStream,
+    proxy: &ProxyConfig,
+    host: &str,
+    port: u16,
+) -> Result<(), Error> {
+    stream
+        .write_all(connect_request(proxy, host, port).as_bytes())
+        .await?;
+
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_CONNECT_RESPONSE_LEN {
+            bail!("proxy response is too large");
+        }
+        response.push(stream.read_u8().await?);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status = response.split_whitespace().nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        bail!("proxy refused to connect with status {status}");
+    }
+    Ok(())
+}
+
+fn connect_request(proxy: &ProxyConfig, host: &str, port: u16) -> String {
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if let Some((username, password)) = proxy.credentials() {
+        let auth = STANDARD.encode(format!("{username}:{password}"));
+        request.push_str(&format!("Proxy-Authorization: Basic {auth}\r\n"));
+    }
+    request.push_str("\r\n");
+    request
+}
+
 #[cfg(test)]
 mod test {
     use std::{cell::RefCell, rc::Rc, time::Duration};
 
     use tokio::time::{Instant, advance};
 
-    use super::{DiscordNotification, DiscordWebhookBody, NotificationKind, ScheduledNotification};
-    use crate::{Notifications, Settings};
+    use super::{
+        DiscordNotification, DiscordWebhookBody, NotificationKind, ScheduledNotification,
+        connect_request,
+    };
+    use crate::{Notifications, Settings, config::ProxyConfig};
 
     #[tokio::test(start_paused = true)]
     async fn schedule_kind_unique() {
@@ -362,6 +566,25 @@ mod test {
         );
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn schedule_hide_screenshots() {
+        let noti = DiscordNotification::new(Rc::new(RefCell::new(Settings {
+            notifications: Notifications {
+                discord_webhook_url: "https://discord.com/api/webhooks/foo/bar".to_string(),
+                notify_on_player_die: true,
+                hide_screenshots: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        })));
+
+        assert!(
+            noti.schedule_notification(NotificationKind::PlayerIsDead)
+                .is_ok()
+        );
+        assert!(noti.scheduled.lock().unwrap()[0].frames.is_empty());
+    }
+
     #[tokio::test(start_paused = true)]
     async fn schedule_invalid_url() {
         let noti = DiscordNotification::new(Rc::new(RefCell::new(Settings {
@@ -413,4 +636,26 @@ mod test {
         assert!(scheduled.frames[1].0.is_some());
         assert!(scheduled.frames[2].0.is_some());
     }
+
+    #[test]
+    fn connect_request_with_credentials() {
+        let proxy = ProxyConfig {
+            url: "http://127.0.0.1:8080".to_string(),
+            username: Some("user".to_string()),
+            password: Some("pass".to_string()),
+        };
+        assert_eq!(
+            connect_request(&proxy, "localhost", 5001),
+            "CONNECT localhost:5001 HTTP/1.1\r\nHost: localhost:5001\r\nProxy-Authorization: Basic dXNlcjpwYXNz\r\n\r\n"
+        );
+
+        let proxy = ProxyConfig {
+            username: None,
+            ..proxy
+        };
+        assert_eq!(
+            connect_request(&proxy, "localhost", 5001),
+            "CONNECT localhost:5001 HTTP/1.1\r\nHost: localhost:5001\r\n\r\n"
+        );
+    }
 }
@@ -0,0 +1,196 @@
+use std::{
+    fmt::Write as _,
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{LazyLock, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use log::{debug, error, info};
+use serde::Serialize;
+
+/// The browser source page polling `/text` once a second.
+const OVERLAY_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+body {
+    margin: 0;
+    background: transparent;
+    color: white;
+    font: 24px monospace;
+    text-shadow: 2px 2px 2px black;
+}
+</style>
+</head>
+<body>
+<pre id="status"></pre>
+<script>
+async function update() {
+    try {
+        const response = await fetch("/text");
+        document.getElementById("status").textContent = await response.text();
+    } catch (_) {}
+}
+update();
+setInterval(update, 1000);
+</script>
+</body>
+</html>
+"#;
+
+static OVERLAY: LazyLock<Mutex<Overlay>> = LazyLock::new(|| Mutex::new(Overlay::default()));
+
+/// The bot status shown in a streaming overlay.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct OverlayStatus {
+    pub state: String,
+    /// The name of the selected minimap.
+    pub map: Option<String>,
+    pub halting: bool,
+    /// Seconds since rotating actions started or `0` while halting.
+    pub runtime_secs: u64,
+    pub exp_per_hour: Option<u64>,
+    pub exp_percent_per_hour: Option<f32>,
+}
+
+#[derive(Debug, Default)]
+struct Overlay {
+    status: OverlayStatus,
+    /// The [`Instant`] rotating actions started at.
+    running_since: Option<Instant>,
+}
+
+impl Overlay {
+    fn record(&mut self, now: Instant, status: OverlayStatus) {
+        self.running_since = if status.halting {
+            None
+        } else {
+            Some(self.running_since.unwrap_or(now))
+        };
+        self.status = OverlayStatus {
+            runtime_secs: self
+                .running_since
+                .map_or(0, |since| now.duration_since(since).as_secs()),
+            ..status
+        };
+    }
+
+    fn render_text(&self) -> String {
+        let status = &self.status;
+        let runtime = status.runtime_secs;
+        let mut out = String::new();
+        let _ = writeln!(out, "State: {}", status.state);
+        let _ = writeln!(out, "Map: {}", status.map.as_deref().unwrap_or("None"));
+        let _ = writeln!(
+            out,
+            "Runtime: {:02}:{:02}:{:02}",
+            runtime / 3600,
+            runtime / 60 % 60,
+            runtime % 60
+        );
+        match (status.exp_per_hour, status.exp_percent_per_hour) {
+            (Some(exp), Some(percent)) => {
+                let _ = writeln!(out, "EXP / hour: {exp} ({percent:.2}%)");
+            }
+            _ => {
+                let _ = writeln!(out, "EXP / hour: Unknown");
+            }
+        }
+        out
+    }
+}
+
+/// Records the latest `status` to serve.
+///
+/// [`OverlayStatus::runtime_secs`] is computed from when `status` stopped halting.
+pub fn record(now: Instant, status: OverlayStatus) {
+    OVERLAY.lock().unwrap().record(now, status);
+}
+
+/// Serves the overlay on `address` (e.g. `127.0.0.1:9186`) in a background thread.
+///
+/// The status is served as plain text on `/text`, as JSON on `/json` and as a browser source page
+/// for OBS on any other path.
+pub fn serve(address: &str) -> Result<()> {
+    let listener = TcpListener::bind(address)?;
+    info!(target: "overlay", "serving overlay on {address}");
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(err) = respond(stream) {
+                        debug!(target: "overlay", "failed to respond {err}");
+                    }
+                }
+                Err(err) => error!(target: "overlay", "failed to accept connection {err}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream) -> Result<()> {
+    let mut buf = [0; 1024];
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let read = stream.read(&mut buf)?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let (content_type, body) = match path {
+        "/text" => (
+            "text/plain; charset=utf-8",
+            OVERLAY.lock().unwrap().render_text(),
+        ),
+        "/json" => (
+            "application/json",
+            serde_json::to_string(&OVERLAY.lock().unwrap().status)?,
+        ),
+        _ => ("text/html; charset=utf-8", OVERLAY_HTML.to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nCache-Control: no-store\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_runtime_and_render_text() {
+        let now = Instant::now();
+        let mut overlay = Overlay::default();
+        let status = OverlayStatus {
+            state: "Idle".to_string(),
+            map: Some("Arcana".to_string()),
+            halting: false,
+            exp_per_hour: Some(1000),
+            exp_percent_per_hour: Some(1.5),
+            ..OverlayStatus::default()
+        };
+        overlay.record(now, status.clone());
+        overlay.record(now + Duration::from_secs(3725), status.clone());
+        assert_eq!(overlay.status.runtime_secs, 3725);
+        assert_eq!(
+            overlay.render_text(),
+            "State: Idle\nMap: Arcana\nRuntime: 01:02:05\nEXP / hour: 1000 (1.50%)\n"
+        );
+
+        overlay.record(
+            now + Duration::from_secs(3726),
+            OverlayStatus {
+                halting: true,
+                ..status
+            },
+        );
+        assert_eq!(overlay.status.runtime_secs, 0);
+        assert_eq!(overlay.running_since, None);
+    }
+}
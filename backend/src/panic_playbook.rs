@@ -0,0 +1,287 @@
+use std::time::{Duration, Instant};
+
+use strum::Display;
+
+use crate::database::{PanicPlaybook, PanicStep, PanicStepKind};
+
+/// Milliseconds after a playbook completed before any playbook can run again.
+const PANIC_PLAYBOOK_COOLDOWN_MILLIS: u64 = 15000;
+
+/// Milliseconds after the last step before the playbook completes even if the player is still
+/// panicking.
+const PANIC_PLAYBOOK_LAST_STEP_TIMEOUT_MILLIS: u64 = 60000;
+
+/// The event that triggers a [`PanicPlaybook`].
+#[derive(Clone, Copy, PartialEq, Debug, Display)]
+pub enum PanicTrigger {
+    StrangerDetected,
+    GmWhisper,
+    DetectionFailureStreak,
+}
+
+/// Runs the steps of a [`PanicPlaybook`] in order with each step's delay.
+#[derive(Debug, Default)]
+pub struct PanicPlaybookRunner {
+    /// The trigger of the currently running playbook.
+    trigger: Option<PanicTrigger>,
+    steps: Vec<PanicStep>,
+    /// The index of the next step to run.
+    index: usize,
+    /// The [`Instant`] the next step can run.
+    next_step_instant: Option<Instant>,
+    /// The [`Instant`] the last run step ran.
+    last_step_instant: Option<Instant>,
+    /// Whether the bot should resume after the playbook completed.
+    ///
+    /// This is `false` if the bot was not rotating when the playbook started or any of the run
+    /// steps stops the bot or leaves the map.
+    resume: bool,
+    last_completed_instant: Option<Instant>,
+    detection_failure_instant: Option<Instant>,
+}
+
+impl PanicPlaybookRunner {
+    /// The trigger of the currently running playbook.
+    #[inline]
+    pub fn trigger(&self) -> Option<PanicTrigger> {
+        self.trigger
+    }
+
+    /// Starts running `playbook` for `trigger`.
+    ///
+    /// `rotating` is whether the bot was rotating when the playbook started and so should resume
+    /// once it completes.
+    ///
+    /// Returns `false` if there is already a running playbook, `playbook` is disabled or empty,
+    /// or the last playbook has only just completed.
+    pub fn start(
+        &mut self,
+        trigger: PanicTrigger,
+        playbook: &PanicPlaybook,
+        rotating: bool,
+        now: Instant,
+    ) -> bool {
+        if self.trigger.is_some() || !playbook.enabled || playbook.steps.is_empty() {
+            return false;
+        }
+        if self.last_completed_instant.is_some_and(|instant| {
+            now.duration_since(instant) < Duration::from_millis(PANIC_PLAYBOOK_COOLDOWN_MILLIS)
+        }) {
+            return false;
+        }
+
+        self.trigger = Some(trigger);
+        self.steps = playbook.steps.clone();
+        self.index = 0;
+        self.next_step_instant = Some(now + Duration::from_millis(self.steps[0].delay_millis));
+        self.resume = rotating;
+        true
+    }
+
+    /// Takes the next step to run if its delay has passed.
+    pub fn next_step(&mut self, now: Instant) -> Option<PanicStep> {
+        if self.next_step_instant.is_none_or(|instant| now < instant) {
+            return None;
+        }
+
+        let step = self.steps[self.index].clone();
+        self.index += 1;
+        self.next_step_instant = self
+            .steps
+            .get(self.index)
+            .map(|step| now + Duration::from_millis(step.delay_millis));
+        self.last_step_instant = Some(now);
+        if matches!(
+            step.kind,
+            PanicStepKind::StopInput | PanicStepKind::GoToTown | PanicStepKind::ExitToLobby
+        ) {
+            self.resume = false;
+        }
+        Some(step)
+    }
+
+    /// Whether there are steps left to run.
+    #[inline]
+    pub fn has_pending_steps(&self) -> bool {
+        self.next_step_instant.is_some()
+    }
+
+    /// Whether the running playbook has run all of its steps and can complete.
+    ///
+    /// `panicking` is whether the player is still running the panic of the last step. The
+    /// playbook waits for it to finish up to a timeout.
+    pub fn can_complete(&self, panicking: bool, now: Instant) -> bool {
+        if self.trigger.is_none() || self.has_pending_steps() {
+            return false;
+        }
+
+        !panicking
+            || self.last_step_instant.is_none_or(|instant| {
+                now.duration_since(instant)
+                    >= Duration::from_millis(PANIC_PLAYBOOK_LAST_STEP_TIMEOUT_MILLIS)
+            })
+    }
+
+    /// Completes the running playbook and returns whether the bot should resume.
+    pub fn complete(&mut self, now: Instant) -> bool {
+        let resume = self.resume;
+        self.cancel();
+        self.last_completed_instant = Some(now);
+        resume
+    }
+
+    /// Cancels the running playbook without running the remaining steps.
+    pub fn cancel(&mut self) {
+        self.trigger = None;
+        self.steps.clear();
+        self.index = 0;
+        self.next_step_instant = None;
+        self.last_step_instant = None;
+        self.resume = false;
+    }
+
+    /// Tracks consecutive detection failures.
+    ///
+    /// Returns `true` once `failing` has been continuously `true` for at least `millis`.
+    pub fn update_detection_failure_streak(
+        &mut self,
+        failing: bool,
+        millis: u64,
+        now: Instant,
+    ) -> bool {
+        if !failing {
+            self.detection_failure_instant = None;
+            return false;
+        }
+
+        let instant = *self.detection_failure_instant.get_or_insert(now);
+        now.duration_since(instant) >= Duration::from_millis(millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{PanicPlaybookRunner, PanicTrigger};
+    use crate::database::{PanicPlaybook, PanicStep, PanicStepKind};
+
+    fn playbook() -> PanicPlaybook {
+        PanicPlaybook {
+            enabled: true,
+            steps: vec![
+                PanicStep {
+                    kind: PanicStepKind::ChangeChannel,
+                    delay_millis: 0,
+                    ..PanicStep::default()
+                },
+                PanicStep {
+                    kind: PanicStepKind::Notify,
+                    delay_millis: 1000,
+                    ..PanicStep::default()
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn next_step_waits_for_delay() {
+        let mut runner = PanicPlaybookRunner::default();
+        let now = Instant::now();
+
+        assert!(runner.start(PanicTrigger::StrangerDetected, &playbook(), true, now));
+        assert!(!runner.start(PanicTrigger::GmWhisper, &playbook(), true, now));
+        assert_eq!(
+            runner.next_step(now).map(|step| step.kind),
+            Some(PanicStepKind::ChangeChannel)
+        );
+        assert_eq!(runner.next_step(now + Duration::from_millis(500)), None);
+        assert_eq!(
+            runner
+                .next_step(now + Duration::from_millis(1000))
+                .map(|step| step.kind),
+            Some(PanicStepKind::Notify)
+        );
+        assert!(!runner.has_pending_steps());
+        assert!(runner.complete(now));
+        assert_eq!(runner.trigger(), None);
+    }
+
+    #[test]
+    fn can_complete_waits_for_panicking_until_timeout() {
+        let mut runner = PanicPlaybookRunner::default();
+        let now = Instant::now();
+        let playbook = PanicPlaybook {
+            enabled: true,
+            steps: vec![PanicStep {
+                kind: PanicStepKind::GoToTown,
+                ..PanicStep::default()
+            }],
+        };
+
+        assert!(!runner.can_complete(false, now));
+        assert!(runner.start(PanicTrigger::GmWhisper, &playbook, true, now));
+        assert!(!runner.can_complete(false, now));
+        assert!(runner.next_step(now).is_some());
+        assert!(runner.can_complete(false, now));
+        assert!(!runner.can_complete(true, now + Duration::from_secs(59)));
+        assert!(runner.can_complete(true, now + Duration::from_secs(60)));
+        // Going to town leaves the map so rotating cannot resume
+        assert!(!runner.complete(now));
+    }
+
+    #[test]
+    fn complete_does_not_resume_if_not_rotating() {
+        let mut runner = PanicPlaybookRunner::default();
+        let now = Instant::now();
+
+        assert!(runner.start(PanicTrigger::GmWhisper, &playbook(), false, now));
+        assert!(!runner.complete(now));
+    }
+
+    #[test]
+    fn start_ignores_disabled_and_cooldown() {
+        let mut runner = PanicPlaybookRunner::default();
+        let now = Instant::now();
+        let disabled = PanicPlaybook {
+            enabled: false,
+            ..playbook()
+        };
+
+        assert!(!runner.start(PanicTrigger::StrangerDetected, &disabled, true, now));
+
+        runner.start(PanicTrigger::StrangerDetected, &playbook(), true, now);
+        runner.complete(now);
+
+        assert!(!runner.start(PanicTrigger::StrangerDetected, &playbook(), true, now));
+        assert!(runner.start(
+            PanicTrigger::StrangerDetected,
+            &playbook(),
+            true,
+            now + Duration::from_secs(15)
+        ));
+    }
+
+    #[test]
+    fn update_detection_failure_streak_resets_on_success() {
+        let mut runner = PanicPlaybookRunner::default();
+        let now = Instant::now();
+
+        assert!(!runner.update_detection_failure_streak(true, 1000, now));
+        assert!(runner.update_detection_failure_streak(
+            true,
+            1000,
+            now + Duration::from_millis(1000)
+        ));
+        assert!(!runner.update_detection_failure_streak(
+            false,
+            1000,
+            now + Duration::from_millis(1500)
+        ));
+        assert!(!runner.update_detection_failure_streak(
+            true,
+            1000,
+            now + Duration::from_millis(2000)
+        ));
+    }
+}
@@ -10,6 +10,16 @@ use crate::array::Array;
 
 pub const MAX_PLATFORMS_COUNT: usize = 24;
 
+/// The weight score of moving to a platform above when flying.
+const FLYING_WEIGHT_SCORE: u32 = 1;
+
+/// Maximum x and y distance from an avoidance zone for a platform to be avoided.
+pub const AVOIDANCE_ZONE_RADIUS: i32 = 6;
+
+/// Maximum y distance between a platform and either end of a rope for the platform to be
+/// reachable by the rope.
+const ROPE_Y_MARGIN: i32 = 5;
+
 /// The kind of movement the player should perform.
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
@@ -18,18 +28,95 @@ pub enum MovementHint {
     Infer,
     /// Performs a walk and then jump.
     WalkAndJump,
+    /// Climbs a rope or ladder to the next point.
+    Climb,
+    /// Jumps down through the current platform to the next point.
+    JumpDown,
+}
+
+/// The x-wise motion of a moving or conveyor [`Platform`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum PlatformMotion {
+    /// Moves back and forth by `range` x and returns after `period` ticks.
+    Moving { period: u32, range: i32 },
+    /// Carries the player standing on it by `range` x every `period` ticks.
+    Conveyor { period: u32, range: i32 },
+}
+
+impl PlatformMotion {
+    /// The x a player standing on the platform is carried by after `ticks`.
+    ///
+    /// Only [`PlatformMotion::Conveyor`] carries at a constant rate that can be predicted.
+    #[inline]
+    pub fn carried_x(&self, ticks: u32) -> i32 {
+        match self {
+            PlatformMotion::Moving { .. } => 0,
+            PlatformMotion::Conveyor { period, range } => {
+                range * ticks as i32 / (*period).max(1) as i32
+            }
+        }
+    }
 }
 
 /// A platform where player can stand on.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Platform {
+    /// The x range that can be landed on.
+    ///
+    /// For [`PlatformMotion::Moving`], this is the x range covered at every phase of the motion.
     xs: Range<i32>,
     y: i32,
+    motion: Option<PlatformMotion>,
 }
 
 impl Platform {
     pub fn new<R: Into<Range<i32>>>(xs: R, y: i32) -> Self {
-        Self { xs: xs.into(), y }
+        Self {
+            xs: xs.into(),
+            y,
+            motion: None,
+        }
+    }
+
+    pub fn with_motion(self, motion: PlatformMotion) -> Self {
+        Self {
+            motion: Some(motion),
+            ..self
+        }
+    }
+
+    /// The x range covered at any phase of the motion.
+    ///
+    /// This is used for reachability and finding the platform the player is standing on.
+    #[inline]
+    fn swept_xs(&self) -> Range<i32> {
+        match self.motion {
+            Some(PlatformMotion::Moving { range, .. }) if !self.xs.is_empty() => {
+                (self.xs.start - range.abs()..self.xs.end + range.abs()).into()
+            }
+            _ => self.xs,
+        }
+    }
+}
+
+/// A rope or ladder that can be climbed within `ys`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rope {
+    x: i32,
+    ys: Range<i32>,
+}
+
+impl Rope {
+    pub fn new<R: Into<Range<i32>>>(x: i32, ys: R) -> Self {
+        Self { x, ys: ys.into() }
+    }
+
+    /// Whether `platform` can be stepped on from this rope or the other way around.
+    #[inline]
+    fn connects(&self, platform: Platform) -> bool {
+        platform.xs.contains(&self.x)
+            && platform.y >= self.ys.start - ROPE_Y_MARGIN
+            && platform.y < self.ys.end + ROPE_Y_MARGIN
     }
 }
 
@@ -38,6 +125,13 @@ impl Platform {
 pub struct PlatformWithNeighbors {
     inner: Platform,
     neighbors: Array<Platform, MAX_PLATFORMS_COUNT>,
+    /// The neighbor platforms connected by a rope along with the rope x.
+    ropes: Array<(Platform, i32), MAX_PLATFORMS_COUNT>,
+    /// Whether this platform is near an avoidance zone and should only be moved to when it is
+    /// the destination.
+    avoided: bool,
+    /// Whether this platform overlaps a no-go zone and should never be moved to.
+    no_go: bool,
 }
 
 impl PlatformWithNeighbors {
@@ -50,6 +144,24 @@ impl PlatformWithNeighbors {
     pub fn y(&self) -> i32 {
         self.inner.y
     }
+
+    #[inline]
+    pub fn swept_xs(&self) -> Range<i32> {
+        self.inner.swept_xs()
+    }
+
+    #[inline]
+    pub fn motion(&self) -> Option<PlatformMotion> {
+        self.inner.motion
+    }
+
+    #[inline]
+    fn rope_x_to(&self, neighbor: Platform) -> Option<i32> {
+        self.ropes
+            .iter()
+            .find(|(platform, _)| *platform == neighbor)
+            .map(|(_, x)| *x)
+    }
 }
 
 /// The platform being visited during path finding.
@@ -135,33 +247,123 @@ pub fn find_neighbors(
         vec.push(PlatformWithNeighbors {
             inner: current,
             neighbors,
+            ropes: Array::new(),
+            avoided: false,
+            no_go: false,
         });
     }
     vec
 }
 
+/// Connects platforms that are both reachable by any of the `ropes`.
+///
+/// Connected platforms become neighbors of each other even when they are too far apart to
+/// grapple or up jump.
+pub fn connect_ropes(platforms: &mut [PlatformWithNeighbors], ropes: &[Rope]) {
+    let inners = platforms
+        .iter()
+        .map(|platform| platform.inner)
+        .collect::<Vec<_>>();
+    for platform in platforms {
+        for rope in ropes.iter().filter(|rope| rope.connects(platform.inner)) {
+            for neighbor in inners.iter().copied() {
+                if neighbor.y == platform.inner.y
+                    || !rope.connects(neighbor)
+                    || platform.rope_x_to(neighbor).is_some()
+                {
+                    continue;
+                }
+                if !platform.neighbors.iter().any(|other| *other == neighbor) {
+                    platform.neighbors.push(neighbor);
+                }
+                platform.ropes.push((neighbor, rope.x));
+            }
+        }
+    }
+}
+
+/// Marks platforms near any of the `zones` as avoided.
+///
+/// An avoided platform is never used as an intermediate platform by [`find_points_with`].
+pub fn mark_avoided_platforms(platforms: &mut [PlatformWithNeighbors], zones: &[Point]) {
+    for platform in platforms {
+        platform.avoided = zones.iter().any(|zone| {
+            (platform.inner.y - zone.y).abs() <= AVOIDANCE_ZONE_RADIUS
+                && ranges_overlap(
+                    platform.inner.xs,
+                    (zone.x - AVOIDANCE_ZONE_RADIUS..zone.x + AVOIDANCE_ZONE_RADIUS + 1).into(),
+                )
+        });
+    }
+}
+
+/// Marks platforms overlapping any of the no-go `zones` as no-go.
+///
+/// A platform overlaps a zone if the zone is within [`AVOIDANCE_ZONE_RADIUS`] y of the platform
+/// and x of where the platform can be at any phase of its motion. A no-go platform is never used
+/// by [`find_points_with`], even as the destination.
+///
+/// The `zones` are in player coordinate relative to bottom-left.
+pub fn mark_no_go_platforms(platforms: &mut [PlatformWithNeighbors], zones: &[Rect]) {
+    for platform in platforms {
+        let y = platform.inner.y;
+        let ys = Range::from(y - AVOIDANCE_ZONE_RADIUS..y + AVOIDANCE_ZONE_RADIUS + 1);
+        let xs = platform.inner.swept_xs();
+        platform.no_go = zones.iter().any(|zone| {
+            ranges_overlap(ys, (zone.y..zone.y + zone.height).into())
+                && ranges_overlap(xs, (zone.x..zone.x + zone.width).into())
+        });
+    }
+}
+
+/// The movement abilities and preferences used by [`find_points_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct FindPointsArgs {
+    /// Whether to provide movement hints like [`MovementHint::WalkAndJump`].
+    pub enable_hint: bool,
+    pub double_jump_threshold: i32,
+    pub jump_threshold: i32,
+    /// The maximum y distance between two connected platforms to perform a grappling.
+    ///
+    /// This is used as weight score to help prioritize vertical movement over horizontal
+    /// movement.
+    pub vertical_threshold: i32,
+    /// Whether moving to any platform above has the same weight score so that a direct flight is
+    /// preferred over hopping through intermediate platforms.
+    pub flying: bool,
+    /// Whether a platform directly below is moved to with [`MovementHint::JumpDown`].
+    ///
+    /// Otherwise, the path walks off the current platform edge when possible.
+    pub jump_down: bool,
+}
+
 /// Finds a sequence of points representing a path from `from` to `to`, using the given
 /// platform map.
 ///
-/// `vertical_threshold` represents maximum y distance between two connected platforms to perform
-/// a grappling. This is used as weight score to help prioritize vertical movement over
-/// horizontal movement. If `enable_hint` is true, provides movement hints like `WalkAndJump`.
+/// Platforms connected by a rope are always climbed between with [`MovementHint::Climb`].
 pub fn find_points_with(
     platforms: &Array<PlatformWithNeighbors, MAX_PLATFORMS_COUNT>,
     from: Point,
     to: Point,
-    enable_hint: bool,
-    double_jump_threshold: i32,
-    jump_threshold: i32,
-    vertical_threshold: i32,
+    args: FindPointsArgs,
 ) -> Option<Vec<(Point, MovementHint)>> {
+    let FindPointsArgs {
+        jump_threshold,
+        vertical_threshold,
+        flying,
+        ..
+    } = args;
     let platforms = platforms
         .iter()
         .map(|platform| (platform.inner, *platform))
         .collect::<HashMap<_, _>>();
     let from_platform = find_platform(&platforms, from, None)?; // Clamp `from` to nearest platform
     let to_platform = find_platform(&platforms, to, Some(jump_threshold))?;
+    if platforms[&to_platform].no_go {
+        return None;
+    }
     let mut came_from = HashMap::<Platform, Platform>::new();
+    let mut climbed_to = HashMap::<Platform, i32>::new();
     let mut visiting = BinaryHeap::new();
     let mut score = HashMap::<Platform, u32>::new();
 
@@ -177,26 +379,35 @@ pub fn find_points_with(
         if current.platform == to_platform {
             return points_from(
                 &came_from,
-                from,
-                from_platform,
-                to_platform,
-                to,
-                enable_hint,
-                double_jump_threshold,
-                jump_threshold,
+                &climbed_to,
+                (from, from_platform),
+                (to, to_platform),
+                args,
             );
         }
 
-        let neighbors = platforms[&current.platform].neighbors;
-        for neighbor in neighbors {
-            let tentative_score = current_score.saturating_add(weight_score(
-                current.platform,
-                neighbor,
-                vertical_threshold,
-            ));
+        let current_with_neighbors = platforms[&current.platform];
+        for neighbor in current_with_neighbors.neighbors {
+            let neighbor_with_neighbors = platforms[&neighbor];
+            if neighbor_with_neighbors.no_go
+                || (neighbor != to_platform && neighbor_with_neighbors.avoided)
+            {
+                continue;
+            }
+            let rope_x = current_with_neighbors.rope_x_to(neighbor);
+            let weight = if rope_x.is_some() {
+                (current.platform.y - neighbor.y).unsigned_abs()
+            } else {
+                weight_score(current.platform, neighbor, vertical_threshold, flying)
+            };
+            let tentative_score = current_score.saturating_add(weight);
             let neighbor_score = score.get(&neighbor).copied().unwrap_or(u32::MAX);
             if tentative_score < neighbor_score {
                 came_from.insert(neighbor, current.platform);
+                match rope_x {
+                    Some(x) => climbed_to.insert(neighbor, x),
+                    None => climbed_to.remove(&neighbor),
+                };
                 score.insert(neighbor, tentative_score);
                 if !visiting
                     .iter()
@@ -216,17 +427,14 @@ pub fn find_points_with(
 /// Converts a path from the `came_from` graph into a list of `(Point, MovementHint)` pairs
 /// indicating how to move from `from` to `to`.
 ///
-/// Adds offsets to handle jump and landing safety margins.
-#[allow(clippy::too_many_arguments)]
+/// Adds offsets to handle jump and landing safety margins. Platforms in `climbed_to` are moved to
+/// by climbing the rope at the mapped x.
 fn points_from(
     came_from: &HashMap<Platform, Platform>,
-    from: Point,
-    from_platform: Platform,
-    to_platform: Platform,
-    to: Point,
-    enable_hint: bool,
-    double_jump_threshold: i32,
-    jump_threshold: i32,
+    climbed_to: &HashMap<Platform, i32>,
+    (from, from_platform): (Point, Platform),
+    (to, to_platform): (Point, Platform),
+    args: FindPointsArgs,
 ) -> Option<Vec<(Point, MovementHint)>> {
     /// A margin of error to ensure double jump slide on landing does not make the
     /// player drops from platform
@@ -237,6 +445,14 @@ fn points_from(
 
     const WALK_AND_JUMP_THRESHOLD: i32 = 13;
 
+    let FindPointsArgs {
+        enable_hint,
+        double_jump_threshold,
+        jump_threshold,
+        jump_down,
+        ..
+    } = args;
+
     let mut current = to_platform;
     let mut went_to = HashMap::new();
     while came_from.contains_key(&current) {
@@ -255,16 +471,35 @@ fn points_from(
         let start_max = max(next.xs.start, current.xs.start);
         let end_min = min(next.xs.end, current.xs.end);
 
-        // Check if the current platform overlap with the next platform
-        if ranges_overlap(next.xs, current.xs) {
-            if (start_max..end_min).contains(&last_point.x) {
+        if let Some(x) = climbed_to.get(&next).copied() {
+            // Moves to the rope and then climbs to the next platform
+            points.push((Point::new(x, current.y), MovementHint::Climb));
+            points.push((Point::new(x, next.y), MovementHint::Infer));
+        } else if ranges_overlap(next.xs, current.xs) {
+            // The current platform overlap with the next platform
+            if last_point.y > next.y
+                && !jump_down
+                && let Some((edge_x, landing_x)) = walk_off_edge_xs(current, next, last_point.x)
+            {
+                // Walks off the edge to land on the platform below
+                points.push((Point::new(edge_x, current.y), MovementHint::Infer));
+                points.push((Point::new(landing_x, next.y), MovementHint::Infer));
+            } else if (start_max..end_min).contains(&last_point.x) {
                 if last_point.y <= next.y {
                     // Already inside intersection range, add a point to move up.
                     points.push((Point::new(last_point.x, next.y), MovementHint::Infer));
+                } else if jump_down {
+                    // Already inside intersection range, add 2 points to jump down through
+                    points.push((Point::new(last_point.x, current.y), MovementHint::JumpDown));
+                    points.push((Point::new(last_point.x, next.y), MovementHint::Infer));
                 } else {
                     // Moving down is skipped but last_point is updated as if already moved.
                     last_point = Point::new(last_point.x, next.y);
                 }
+            } else if current.y > next.y && jump_down {
+                let x = rand::random_range(start_max..end_min);
+                points.push((Point::new(x, current.y), MovementHint::JumpDown));
+                points.push((Point::new(x, next.y), MovementHint::Infer));
             } else {
                 // Outside intersection range, add 2 points to move inside and then up or down
                 // TODO: Replace rand with Rng
@@ -316,6 +551,32 @@ fn points_from(
     Some(points)
 }
 
+/// Finds the x to walk to on the edge of `current` and the x to land on `next` below after
+/// walking off that edge.
+///
+/// The edge closest to `x` is preferred. Returns [`None`] if `next` does not extend past
+/// either edge of `current`.
+#[inline]
+fn walk_off_edge_xs(current: Platform, next: Platform, x: i32) -> Option<(i32, i32)> {
+    /// Number of x past the edge to land on
+    const LANDING_OFFSET: i32 = 2;
+
+    let right = (next.xs.end > current.xs.end + LANDING_OFFSET)
+        .then_some((current.xs.end - 1, current.xs.end - 1 + LANDING_OFFSET));
+    let left = (next.xs.start < current.xs.start - LANDING_OFFSET)
+        .then_some((current.xs.start, current.xs.start - LANDING_OFFSET));
+    match (left, right) {
+        (Some(left), Some(right)) => {
+            if (x - left.0).abs() <= (x - right.0).abs() {
+                Some(left)
+            } else {
+                Some(right)
+            }
+        }
+        (left, right) => left.or(right),
+    }
+}
+
 /// Finds the closest platform underneath or near a given `point`.
 ///
 /// If `jump_threshold` is provided, it limits how far vertically the point can be from a platform.
@@ -327,7 +588,7 @@ fn find_platform(
 ) -> Option<Platform> {
     platforms
         .keys()
-        .filter(|platform| platform.xs.contains(&point.x))
+        .filter(|platform| platform.swept_xs().contains(&point.x))
         .min_by_key(|platform| (platform.y - point.y).abs())
         .filter(|platform| {
             jump_threshold.is_none() || (platform.y - point.y).abs() < jump_threshold.unwrap()
@@ -336,9 +597,16 @@ fn find_platform(
 }
 
 #[inline]
-fn weight_score(current: Platform, neighbor: Platform, vertical_threshold: i32) -> u32 {
+fn weight_score(
+    current: Platform,
+    neighbor: Platform,
+    vertical_threshold: i32,
+    flying: bool,
+) -> u32 {
     let y_distance = (current.y - neighbor.y).abs();
-    if y_distance < vertical_threshold {
+    if flying && neighbor.y > current.y {
+        FLYING_WEIGHT_SCORE
+    } else if y_distance < vertical_threshold {
         y_distance as u32
     } else {
         u32::MAX
@@ -350,6 +618,8 @@ fn weight_score(current: Platform, neighbor: Platform, vertical_threshold: i32)
 /// One platform is reachable to another platform if:
 /// - The two platforms [`Platform::xs`] overlap and one is above the other or can be grappled to
 /// - The two platforms [`Platform::xs`] do not overlap but can double jump from one to another
+///
+/// A moving platform is reachable from or to where it can be at any phase of its motion.
 #[inline]
 fn platforms_reachable(
     from: Platform,
@@ -359,14 +629,16 @@ fn platforms_reachable(
     grappling_threshold: i32,
 ) -> bool {
     let diff = from.y - to.y;
-    if !ranges_overlap(from.xs, to.xs) {
+    let from_xs = from.swept_xs();
+    let to_xs = to.swept_xs();
+    if !ranges_overlap(from_xs, to_xs) {
         if diff >= 0 || diff.abs() < jump_threshold {
-            return max(from.xs.start, to.xs.start) - min(from.xs.end, to.xs.end)
+            return max(from_xs.start, to_xs.start) - min(from_xs.end, to_xs.end)
                 < double_jump_threshold;
         }
         return false;
     }
-    if from.xs.is_empty() || to.xs.is_empty() {
+    if from_xs.is_empty() || to_xs.is_empty() {
         return false;
     }
     diff >= 0 || diff.abs() < grappling_threshold
@@ -385,16 +657,27 @@ fn ranges_overlap<R: Into<Range<i32>>>(first: R, second: R) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use opencv::core::Point;
+    use opencv::core::{Point, Rect};
 
     use super::{
-        MAX_PLATFORMS_COUNT, MovementHint, Platform, PlatformWithNeighbors, find_neighbors,
+        FindPointsArgs, MAX_PLATFORMS_COUNT, MovementHint, Platform, PlatformMotion,
+        PlatformWithNeighbors, Rope, connect_ropes, find_neighbors, mark_avoided_platforms,
+        mark_no_go_platforms,
     };
     use crate::{
         array::Array,
         pathing::{find_points_with, ranges_overlap},
     };
 
+    const ARGS: FindPointsArgs = FindPointsArgs {
+        enable_hint: true,
+        double_jump_threshold: 25,
+        jump_threshold: 7,
+        vertical_threshold: 41,
+        flying: false,
+        jump_down: true,
+    };
+
     fn make_platforms_with_neighbors(
         platforms: &[Platform],
     ) -> Array<PlatformWithNeighbors, MAX_PLATFORMS_COUNT> {
@@ -428,7 +711,7 @@ mod tests {
         let from = Point::new(10, 50);
         let to = Point::new(20, 60);
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41).unwrap();
+        let points = find_points_with(&platforms, from, to, ARGS).unwrap();
 
         let expected = vec![
             (Point::new(10, 60), MovementHint::Infer),
@@ -449,13 +732,31 @@ mod tests {
         let from = Point::new(25, 50);
         let to = Point::new(65, 55);
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41).unwrap();
+        let points = find_points_with(&platforms, from, to, ARGS).unwrap();
 
         assert_eq!(points.first().unwrap().0.y, 50);
         assert_eq!(points.last().unwrap().0.y, 55);
         assert!(points.len() >= 2);
     }
 
+    #[test]
+    fn find_points_with_moving_platform_swept_range() {
+        let from = Point::new(10, 50);
+        let to = Point::new(65, 50);
+        let mut platforms = [Platform::new(0..20, 50), Platform::new(60..70, 50)];
+        let points = find_points_with(&make_platforms_with_neighbors(&platforms), from, to, ARGS);
+        assert!(points.is_none());
+
+        // Moves as close as 30 to the first platform
+        platforms[1] = platforms[1].with_motion(PlatformMotion::Moving {
+            period: 60,
+            range: 30,
+        });
+        let points =
+            find_points_with(&make_platforms_with_neighbors(&platforms), from, to, ARGS).unwrap();
+        assert_eq!(points.last().unwrap().0, to);
+    }
+
     #[test]
     fn find_points_with_multi_hop_path() {
         let platforms = [
@@ -468,7 +769,7 @@ mod tests {
         let from = Point::new(10, 50);
         let to = Point::new(20, 131);
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41).unwrap();
+        let points = find_points_with(&platforms, from, to, ARGS).unwrap();
 
         // Check that y-values ascend (multi-hop upward movement)
         let ys: Vec<_> = points.iter().map(|(p, _)| p.y).collect();
@@ -492,7 +793,7 @@ mod tests {
         let from = Point::new(25, 50);
         let to = Point::new(125, 55);
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41);
+        let points = find_points_with(&platforms, from, to, ARGS);
         assert!(points.is_none());
     }
 
@@ -507,7 +808,7 @@ mod tests {
         let from = Point::new(45, 50); // Near right edge of first platform
         let to = Point::new(60, 52); // Near left edge of second platform
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41).unwrap();
+        let points = find_points_with(&platforms, from, to, ARGS).unwrap();
 
         let has_walk_and_jump = points
             .iter()
@@ -520,4 +821,145 @@ mod tests {
         assert_eq!(points.first().unwrap().0.y, 50);
         assert_eq!(points.last().unwrap().0.y, 52);
     }
+
+    #[test]
+    fn find_points_with_avoided_platform() {
+        let platforms = [
+            Platform::new(0..50, 50),
+            Platform::new(0..20, 70),  // Shorter route but avoided
+            Platform::new(30..50, 75), // Longer route
+            Platform::new(0..50, 100),
+        ];
+        let mut connected = find_neighbors(&platforms, 25, 7, 41);
+        mark_avoided_platforms(&mut connected, &[Point::new(10, 72)]);
+        let platforms = Array::from_iter(connected);
+
+        let from = Point::new(10, 50);
+        let to = Point::new(20, 100);
+
+        let points = find_points_with(&platforms, from, to, ARGS).unwrap();
+
+        assert!(points.iter().all(|(p, _)| p.y != 70), "{points:?}");
+        assert!(points.iter().any(|(p, _)| p.y == 75), "{points:?}");
+    }
+
+    #[test]
+    fn find_points_with_no_go_platform() {
+        let platforms = [
+            Platform::new(0..50, 50),
+            Platform::new(0..20, 70), // Shorter route but inside a no-go zone
+            Platform::new(30..50, 75), // Longer route
+            Platform::new(0..50, 100),
+        ];
+        let mut connected = find_neighbors(&platforms, 25, 7, 41);
+        mark_avoided_platforms(&mut connected, &[]);
+        mark_no_go_platforms(&mut connected, &[Rect::new(15, 65, 10, 10)]);
+        let platforms = Array::from_iter(connected);
+
+        let from = Point::new(10, 50);
+        let to = Point::new(20, 100);
+
+        let points = find_points_with(&platforms, from, to, ARGS).unwrap();
+
+        assert!(points.iter().all(|(p, _)| p.y != 70), "{points:?}");
+        assert!(points.iter().any(|(p, _)| p.y == 75), "{points:?}");
+    }
+
+    #[test]
+    fn find_points_with_no_go_destination() {
+        let platforms = [Platform::new(0..50, 50), Platform::new(0..50, 70)];
+        let mut connected = find_neighbors(&platforms, 25, 7, 41);
+        // Only covers the player standing on the platform above
+        mark_no_go_platforms(&mut connected, &[Rect::new(20, 72, 10, 10)]);
+        let platforms = Array::from_iter(connected);
+
+        let from = Point::new(10, 50);
+        let to = Point::new(10, 70);
+
+        assert!(find_points_with(&platforms, from, to, ARGS).is_none());
+    }
+
+    #[test]
+    fn find_points_with_rope_climb() {
+        let platforms = [
+            Platform::new(0..50, 50),
+            Platform::new(0..50, 120), // Too high to grapple
+        ];
+        let mut connected = find_neighbors(&platforms, 25, 7, 41);
+        let from = Point::new(10, 50);
+        let to = Point::new(30, 120);
+        assert!(find_points_with(&Array::from_iter(connected.clone()), from, to, ARGS).is_none());
+
+        connect_ropes(&mut connected, &[Rope::new(20, 52..118)]);
+        let platforms = Array::from_iter(connected);
+        let points = find_points_with(&platforms, from, to, ARGS).unwrap();
+
+        let expected = vec![
+            (Point::new(20, 50), MovementHint::Climb),
+            (Point::new(20, 120), MovementHint::Infer),
+            (Point::new(30, 120), MovementHint::Infer),
+        ];
+        assert_eq!(points, expected);
+    }
+
+    #[test]
+    fn find_points_with_jump_down_or_walk_off_edge() {
+        let platforms = [Platform::new(20..40, 50), Platform::new(0..60, 30)];
+        let platforms = Array::from_iter(find_neighbors(&platforms, 25, 7, 41));
+        let from = Point::new(25, 50);
+        let to = Point::new(10, 30);
+
+        let points = find_points_with(&platforms, from, to, ARGS).unwrap();
+        let expected = vec![
+            (Point::new(25, 50), MovementHint::JumpDown),
+            (Point::new(25, 30), MovementHint::Infer),
+            (Point::new(10, 30), MovementHint::Infer),
+        ];
+        assert_eq!(points, expected);
+
+        let points = find_points_with(
+            &platforms,
+            from,
+            to,
+            FindPointsArgs {
+                jump_down: false,
+                ..ARGS
+            },
+        )
+        .unwrap();
+        let expected = vec![
+            (Point::new(20, 50), MovementHint::Infer),
+            (Point::new(18, 30), MovementHint::Infer),
+            (Point::new(10, 30), MovementHint::Infer),
+        ];
+        assert_eq!(points, expected);
+    }
+
+    #[test]
+    fn find_points_with_flying_direct_path() {
+        let platforms = [
+            Platform::new(0..50, 50),
+            Platform::new(0..50, 90),
+            Platform::new(0..50, 130),
+        ];
+        let connected = find_neighbors(&platforms, 25, 7, i32::MAX);
+        let mut platforms = Array::new();
+        for p in connected {
+            platforms.push(p);
+        }
+
+        let from = Point::new(10, 50);
+        let to = Point::new(20, 131);
+
+        let args = FindPointsArgs {
+            vertical_threshold: i32::MAX,
+            flying: true,
+            ..ARGS
+        };
+        let points = find_points_with(&platforms, from, to, args).unwrap();
+
+        // Flies directly without stopping at the middle platform
+        assert!(points.iter().all(|(p, _)| p.y != 90), "{points:?}");
+        assert_eq!(points.last().unwrap().0.y, 130);
+    }
 }
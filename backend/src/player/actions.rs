@@ -1,17 +1,25 @@
 use opencv::core::{Point, Rect};
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 use strum::Display;
 
-use super::{Player, PlayerState, use_key::UseKey};
+use super::{
+    Player, PlayerState,
+    combo::COMBO_MAX_STEPS,
+    sell_items::{SELL_ITEMS_MAX_SLOTS, SELL_ITEMS_MAX_TABS},
+    use_key::UseKey,
+};
 use crate::{
-    Action, ActionKey, ActionKeyDirection, ActionKeyWith, ActionMove, FamiliarRarity, KeyBinding,
-    Position, SwappableFamiliars,
+    Action, ActionKey, ActionKeyDirection, ActionKeyVerify, ActionKeyWith, ActionMouse,
+    ActionMouseKind, ActionMouseRelativeTo, ActionMove, FamiliarLevelPriority, FamiliarRarity,
+    FamiliarRarityPriority, KeyBinding, Position, SwappableFamiliars,
     array::Array,
-    context::{Context, MS_PER_TICK},
-    database::LinkKeyBinding,
+    context::{Context, ms_per_tick},
+    database::{ComboStep, LinkKeyBinding},
     minimap::Minimap,
 };
 
@@ -36,6 +44,7 @@ pub struct PlayerActionKey {
     pub wait_before_use_ticks_random_range: u32,
     pub wait_after_use_ticks: u32,
     pub wait_after_use_ticks_random_range: u32,
+    pub verify: Option<ActionKeyVerify>,
 }
 
 impl From<ActionKey> for PlayerActionKey {
@@ -51,6 +60,7 @@ impl From<ActionKey> for PlayerActionKey {
             wait_before_use_millis_random_range,
             wait_after_use_millis,
             wait_after_use_millis_random_range,
+            verify,
             ..
         }: ActionKey,
     ) -> Self {
@@ -61,12 +71,22 @@ impl From<ActionKey> for PlayerActionKey {
             position,
             direction,
             with,
-            wait_before_use_ticks: (wait_before_use_millis / MS_PER_TICK) as u32,
-            wait_before_use_ticks_random_range: (wait_before_use_millis_random_range / MS_PER_TICK)
-                as u32,
-            wait_after_use_ticks: (wait_after_use_millis / MS_PER_TICK) as u32,
-            wait_after_use_ticks_random_range: (wait_after_use_millis_random_range / MS_PER_TICK)
+            wait_before_use_ticks: (wait_before_use_millis / ms_per_tick()) as u32,
+            wait_before_use_ticks_random_range: (wait_before_use_millis_random_range
+                / ms_per_tick()) as u32,
+            wait_after_use_ticks: (wait_after_use_millis / ms_per_tick()) as u32,
+            wait_after_use_ticks_random_range: (wait_after_use_millis_random_range / ms_per_tick())
                 as u32,
+            verify,
+        }
+    }
+}
+
+impl std::fmt::Display for PlayerActionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{}, {}, {}", self.key, position.x, position.y),
+            None => write!(f, "{}", self.key),
         }
     }
 }
@@ -90,11 +110,56 @@ impl From<ActionMove> for PlayerActionMove {
     ) -> Self {
         Self {
             position,
-            wait_after_move_ticks: (wait_after_move_millis / MS_PER_TICK) as u32,
+            wait_after_move_ticks: (wait_after_move_millis / ms_per_tick()) as u32,
+        }
+    }
+}
+
+impl std::fmt::Display for PlayerActionMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}", self.position.x, self.position.y)
+    }
+}
+
+/// Represents the mouse action.
+///
+/// Converted from [`ActionMouse`] without fields used by [`Rotator`].
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerActionMouse {
+    pub x: i32,
+    pub y: i32,
+    pub action: ActionMouseKind,
+    pub relative_to: ActionMouseRelativeTo,
+    pub wait_after_use_ticks: u32,
+}
+
+impl From<ActionMouse> for PlayerActionMouse {
+    fn from(
+        ActionMouse {
+            x,
+            y,
+            action,
+            relative_to,
+            wait_after_use_millis,
+            ..
+        }: ActionMouse,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            action,
+            relative_to,
+            wait_after_use_ticks: (wait_after_use_millis / ms_per_tick()) as u32,
         }
     }
 }
 
+impl std::fmt::Display for PlayerActionMouse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}, {}", self.action, self.x, self.y)
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(test, derive(Default))]
 pub struct PlayerActionAutoMob {
@@ -157,6 +222,9 @@ impl Default for PingPongDirection {
 pub struct PlayerActionFamiliarsSwapping {
     pub swappable_slots: SwappableFamiliars,
     pub swappable_rarities: Array<FamiliarRarity, 2>,
+    pub swap_when_points_depleted: bool,
+    pub rarity_priority: FamiliarRarityPriority,
+    pub level_priority: FamiliarLevelPriority,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -164,18 +232,41 @@ pub struct PlayerActionPanic {
     pub to: PanicTo,
 }
 
+/// Represents a selling items action.
+///
+/// The player moves to [`Self::npc_position`] then opens the shop and sells items in each of
+/// [`Self::tabs`] by clicking on [`Self::slots`].
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerActionSellItems {
+    pub npc_position: Position,
+    pub tabs: Array<Point, SELL_ITEMS_MAX_TABS>,
+    pub slots: Array<Point, SELL_ITEMS_MAX_SLOTS>,
+}
+
+/// Represents a combo action.
+///
+/// Converted from [`ComboAction`](crate::database::ComboAction) and used by
+/// [`Player::Comboing`].
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerActionCombo {
+    pub steps: Array<ComboStep, COMBO_MAX_STEPS>,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum PanicTo {
     Town,
     Channel,
+    Lobby,
 }
 
 /// Represents an action the [`Rotator`] can use.
 #[derive(Clone, Copy, Debug, Display)]
 pub enum PlayerAction {
     /// Fixed key action provided by the user.
+    #[strum(to_string = "Key({0})")]
     Key(PlayerActionKey),
     /// Fixed move action provided by the user.
+    #[strum(to_string = "Move({0})")]
     Move(PlayerActionMove),
     /// Solve rune action.
     SolveRune,
@@ -188,6 +279,13 @@ pub enum PlayerAction {
     FamiliarsSwapping(PlayerActionFamiliarsSwapping),
     /// Panicking to town or another channel action.
     Panic(PlayerActionPanic),
+    /// Selling items to an NPC shop action.
+    SellItems(PlayerActionSellItems),
+    /// Sequenced key steps action.
+    Combo(PlayerActionCombo),
+    /// Fixed mouse action provided by the user.
+    #[strum(to_string = "Mouse({0})")]
+    Mouse(PlayerActionMouse),
 }
 
 impl From<Action> for PlayerAction {
@@ -195,6 +293,8 @@ impl From<Action> for PlayerAction {
         match action {
             Action::Move(action) => PlayerAction::Move(action.into()),
             Action::Key(action) => PlayerAction::Key(action.into()),
+            Action::Mouse(action) => PlayerAction::Mouse(action.into()),
+            Action::Group(_) => unreachable!("group is resolved by the rotator"),
         }
     }
 }
@@ -313,6 +413,7 @@ pub fn on_action_state_mut(
                 PlayerAction::SolveRune
                 | PlayerAction::PingPong(_)
                 | PlayerAction::Move(_)
+                | PlayerAction::SellItems(_)
                 | PlayerAction::Key(PlayerActionKey {
                     position: Some(Position { .. }),
                     ..
@@ -321,6 +422,8 @@ pub fn on_action_state_mut(
                 }
                 PlayerAction::Panic(_)
                 | PlayerAction::FamiliarsSwapping(_)
+                | PlayerAction::Combo(_)
+                | PlayerAction::Mouse(_)
                 | PlayerAction::AutoMob(_)
                 | PlayerAction::Key(PlayerActionKey { position: None, .. }) => (),
             }
@@ -2,6 +2,8 @@ use std::cmp::Ordering;
 
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
@@ -18,7 +20,6 @@ use crate::{
         Player,
         actions::{on_action_state, on_auto_mob_use_key_action},
         double_jump::DoubleJumping,
-        moving::MOVE_TIMEOUT,
         state::LastMovement,
         timeout::{ChangeAxis, MovingLifecycle, Timeout, next_moving_lifecycle_with_axis},
     },
@@ -80,8 +81,9 @@ pub fn update_adjusting_context(
     let cur_pos = state.last_known_pos.expect("in positional context");
     let (x_distance, x_direction) = moving.x_distance_direction_from(true, cur_pos);
     let is_intermediate = moving.is_destination_intermediate();
+    let move_timeout = state.config.movement.move_timeout;
 
-    match next_moving_lifecycle_with_axis(moving, cur_pos, MOVE_TIMEOUT, ChangeAxis::Both) {
+    match next_moving_lifecycle_with_axis(moving, cur_pos, move_timeout, ChangeAxis::Both) {
         MovingLifecycle::Started(moving) => {
             // Check to perform a fall and returns to walk
             if !is_intermediate
@@ -173,7 +175,7 @@ pub fn update_adjusting_context(
                         );
                     }
 
-                    Player::Adjusting(adjusting.moving(moving.timeout_current(MOVE_TIMEOUT)))
+                    Player::Adjusting(adjusting.moving(moving.timeout_current(move_timeout)))
                 },
             )
         }
@@ -234,8 +236,13 @@ fn on_player_action(
             ..
         })
         | PlayerAction::SolveRune
+        | PlayerAction::SellItems(_)
         | PlayerAction::Move(_) => None,
-        PlayerAction::PingPong(_) | PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => {
+        PlayerAction::PingPong(_)
+        | PlayerAction::Panic(_)
+        | PlayerAction::FamiliarsSwapping(_)
+        | PlayerAction::Combo(_)
+        | PlayerAction::Mouse(_) => {
             unreachable!()
         }
     }
@@ -0,0 +1,118 @@
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
+#[cfg(target_os = "macos")]
+use platforms::macos::KeyKind;
+#[cfg(windows)]
+use platforms::windows::KeyKind;
+
+use super::{
+    Player, PlayerState,
+    moving::Moving,
+    state::LastMovement,
+    timeout::{ChangeAxis, MovingLifecycle, next_moving_lifecycle_with_axis},
+};
+use crate::context::Context;
+
+/// Timeout for climbing as a multiple of the move timeout.
+const TIMEOUT_MULTIPLIER: u32 = 2;
+
+/// Maximum y distance allowed to stop climbing.
+const STOPPING_THRESHOLD: i32 = 1;
+
+/// Updates the [`Player::Climbing`] contextual state.
+///
+/// This state can only be transitioned via [`Player::Moving`] when the player has reached a rope
+/// or ladder x-wise in platforms pathing.
+///
+/// The Up or Down key is held until the player is close enough to the destination y-wise. If the
+/// player does not grab the rope, this state times out and returns to [`Player::Moving`] to
+/// use other vertical movements instead.
+pub fn update_climbing_context(
+    context: &Context,
+    state: &mut PlayerState,
+    moving: Moving,
+) -> Player {
+    let timeout = state.config.movement.move_timeout * TIMEOUT_MULTIPLIER;
+
+    match next_moving_lifecycle_with_axis(
+        moving,
+        state.last_known_pos.expect("in positional context"),
+        timeout,
+        ChangeAxis::Vertical,
+    ) {
+        MovingLifecycle::Started(moving) => {
+            let (_, y_direction) = moving.y_distance_direction_from(true, moving.pos);
+            let key = if y_direction > 0 {
+                KeyKind::Up
+            } else {
+                KeyKind::Down
+            };
+            state.last_movement = Some(LastMovement::Climbing);
+            let _ = context.keys.send_down(key);
+            Player::Climbing(moving)
+        }
+        MovingLifecycle::Ended(moving) => {
+            stop_climbing(context);
+            Player::Moving(moving.dest, moving.exact, moving.intermediates)
+        }
+        MovingLifecycle::Updated(mut moving) => {
+            let (y_distance, _) = moving.y_distance_direction_from(true, moving.pos);
+            if !moving.completed && y_distance <= STOPPING_THRESHOLD {
+                stop_climbing(context);
+                moving = moving.completed(true).timeout_current(timeout);
+            }
+            Player::Climbing(moving)
+        }
+    }
+}
+
+#[inline]
+fn stop_climbing(context: &Context) {
+    let _ = context.keys.send_up(KeyKind::Up);
+    let _ = context.keys.send_up(KeyKind::Down);
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+    use opencv::core::Point;
+
+    use super::*;
+    use crate::bridge::MockKeySender;
+
+    #[test]
+    fn update_climbing_context_started_holds_up() {
+        let mut state = PlayerState::default();
+        state.last_known_pos = Some(Point::new(20, 50));
+        let mut keys = MockKeySender::new();
+        keys.expect_send_down()
+            .once()
+            .with(eq(KeyKind::Up))
+            .returning(|_| Ok(()));
+        let context = Context::new(Some(keys), None);
+        let moving = Moving::new(Point::new(20, 50), Point::new(20, 120), false, None);
+
+        let player = update_climbing_context(&context, &mut state, moving);
+
+        assert!(matches!(player, Player::Climbing(_)));
+        assert_eq!(state.last_movement, Some(LastMovement::Climbing));
+    }
+
+    #[test]
+    fn update_climbing_context_completes_near_destination() {
+        let mut state = PlayerState::default();
+        state.last_known_pos = Some(Point::new(20, 119));
+        let mut keys = MockKeySender::new();
+        keys.expect_send_up().times(2).returning(|_| Ok(()));
+        let context = Context::new(Some(keys), None);
+        let mut moving = Moving::new(Point::new(20, 110), Point::new(20, 120), false, None);
+        moving.timeout.started = true;
+
+        let player = update_climbing_context(&context, &mut state, moving);
+
+        match player {
+            Player::Climbing(moving) => assert!(moving.completed),
+            _ => panic!("Expected Player::Climbing"),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
+#[cfg(target_os = "macos")]
+use platforms::macos::KeyKind;
+#[cfg(windows)]
+use platforms::windows::KeyKind;
+
+use super::{
+    Player, PlayerState,
+    actions::{PlayerActionCombo, on_action},
+};
+use crate::{ActionKeyDirection, array::Array, context::Context, database::ComboStep};
+
+/// Maximum number of steps in a combo.
+pub const COMBO_MAX_STEPS: usize = 16;
+
+/// Struct for storing comboing data.
+#[derive(Debug, Clone, Copy)]
+pub struct Comboing {
+    steps: Array<ComboStep, COMBO_MAX_STEPS>,
+    /// The index of the next step to send.
+    index: usize,
+    /// The remaining ticks before sending the next step.
+    delay_ticks: u32,
+}
+
+impl Comboing {
+    pub fn new(action: PlayerActionCombo) -> Self {
+        let delay_ticks = if action.steps.is_empty() {
+            0
+        } else {
+            action.steps[0].delay_ticks
+        };
+
+        Self {
+            steps: action.steps,
+            index: 0,
+            delay_ticks,
+        }
+    }
+
+    #[inline]
+    fn is_completed(&self) -> bool {
+        self.index >= self.steps.len()
+    }
+}
+
+/// Updates [`Player::Comboing`] contextual state.
+///
+/// Sends at most one step per tick. Each step is sent [`ComboStep::delay_ticks`] after the
+/// previous step and this state cannot be overridden by other actions until all steps are sent.
+pub fn update_comboing_context(
+    context: &Context,
+    state: &mut PlayerState,
+    comboing: Comboing,
+) -> Player {
+    let comboing = update_step(context, state, comboing);
+    let next = if comboing.is_completed() {
+        Player::Idle
+    } else {
+        Player::Comboing(comboing)
+    };
+
+    on_action(
+        state,
+        |_| Some((next, matches!(next, Player::Idle))),
+        || Player::Idle, // Force cancel if it is not initiated from an action
+    )
+}
+
+fn update_step(context: &Context, state: &mut PlayerState, mut comboing: Comboing) -> Comboing {
+    if comboing.is_completed() {
+        return comboing;
+    }
+    if comboing.delay_ticks > 0 {
+        comboing.delay_ticks -= 1;
+        return comboing;
+    }
+
+    let step = comboing.steps[comboing.index];
+    let direction_key = match step.direction {
+        ActionKeyDirection::Any => None,
+        ActionKeyDirection::Left => Some(KeyKind::Left),
+        ActionKeyDirection::Right => Some(KeyKind::Right),
+    };
+    if let Some(key) = direction_key
+        && step.direction != state.last_known_direction
+    {
+        let _ = context.keys.send(key);
+        state.last_known_direction = step.direction;
+    }
+    let _ = context.keys.send(step.key.into());
+
+    comboing.index += 1;
+    if !comboing.is_completed() {
+        // The next update is already one tick later
+        comboing.delay_ticks = comboing.steps[comboing.index].delay_ticks.saturating_sub(1);
+    }
+    comboing
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
+    #[cfg(target_os = "macos")]
+    use platforms::macos::KeyKind;
+    #[cfg(windows)]
+    use platforms::windows::KeyKind;
+
+    use super::{Comboing, update_comboing_context};
+    use crate::{
+        ActionKeyDirection, KeyBinding,
+        bridge::MockKeySender,
+        context::Context,
+        database::ComboStep,
+        player::{Player, PlayerAction, PlayerState, actions::PlayerActionCombo},
+    };
+
+    #[test]
+    fn update_comboing_context_sends_steps_after_delays() {
+        let mut keys = MockKeySender::new();
+        let mut sequence = mockall::Sequence::new();
+        for key in [KeyKind::A, KeyKind::Left, KeyKind::B] {
+            keys.expect_send()
+                .once()
+                .in_sequence(&mut sequence)
+                .withf(move |kind| *kind == key)
+                .returning(|_| Ok(()));
+        }
+        let context = Context::new(Some(keys), None);
+        let mut state = PlayerState::default();
+        let action = PlayerActionCombo {
+            steps: [
+                ComboStep {
+                    key: KeyBinding::A,
+                    direction: ActionKeyDirection::Any,
+                    delay_ticks: 1,
+                },
+                ComboStep {
+                    key: KeyBinding::B,
+                    direction: ActionKeyDirection::Left,
+                    delay_ticks: 2,
+                },
+            ]
+            .into_iter()
+            .collect(),
+        };
+        state.set_priority_action(0, PlayerAction::Combo(action));
+
+        let mut player = Player::Comboing(Comboing::new(action));
+        // Waits 1 tick, sends A then sends B 2 ticks later
+        for _ in 0..3 {
+            player = update_comboing_context(&context, &mut state, comboing(player));
+            assert_matches!(player, Player::Comboing(_));
+        }
+        player = update_comboing_context(&context, &mut state, comboing(player));
+        assert_matches!(player, Player::Idle);
+        assert_eq!(state.last_known_direction, ActionKeyDirection::Left);
+        assert!(!state.has_priority_action());
+    }
+
+    fn comboing(player: Player) -> Comboing {
+        match player {
+            Player::Comboing(comboing) => comboing,
+            _ => unreachable!(),
+        }
+    }
+}
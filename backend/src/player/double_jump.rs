@@ -4,6 +4,8 @@ use log::debug;
 use opencv::core::{Point, Rect};
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
@@ -21,7 +23,6 @@ use crate::{
     ActionKeyDirection, ActionKeyWith,
     context::Context,
     player::{
-        moving::MOVE_TIMEOUT,
         state::LastMovement,
         timeout::{ChangeAxis, Timeout},
     },
@@ -39,16 +40,9 @@ const USE_KEY_X_THRESHOLD: i32 = DOUBLE_JUMP_THRESHOLD;
 /// Minimum y distance from the destination required to transition to [`Player::UseKey`].
 const USE_KEY_Y_THRESHOLD: i32 = 10;
 
-/// Maximum number of ticks before timing out.
-const TIMEOUT: u32 = MOVE_TIMEOUT;
-
-const TIMEOUT_FORCED: u32 = MOVE_TIMEOUT + 3;
-
-/// Number of ticks to wait after a double jump.
-///
-/// A heuristic to mostly avoid mid-air jump keys sending. The current approach of using velocity
-/// does not send much keys after double jumped, but only few are sent mid-air.
-const COOLDOWN_TIMEOUT: u32 = MOVE_TIMEOUT;
+/// Number of ticks added to the move timeout before timing out when
+/// [`DoubleJumping::forced`].
+const TIMEOUT_FORCED_EXTRA: u32 = 3;
 
 /// Minimum x distance from the destination required to transition to [`Player::Grappling`].
 const GRAPPLING_THRESHOLD: i32 = 4;
@@ -95,10 +89,14 @@ impl DoubleJumping {
         DoubleJumping { moving, ..self }
     }
 
+    /// Updates the number of ticks to wait after a double jump.
+    ///
+    /// A heuristic to mostly avoid mid-air jump keys sending. The current approach of using
+    /// velocity does not send much keys after double jumped, but only few are sent mid-air.
     #[inline]
-    fn update_jump_cooldown(&mut self) {
+    fn update_jump_cooldown(&mut self, cooldown_timeout: u32) {
         self.cooldown_timeout =
-            match next_timeout_lifecycle(self.cooldown_timeout, COOLDOWN_TIMEOUT) {
+            match next_timeout_lifecycle(self.cooldown_timeout, cooldown_timeout) {
                 Lifecycle::Started(timeout) => timeout,
                 Lifecycle::Ended => Timeout::default(),
                 Lifecycle::Updated(timeout) => timeout,
@@ -129,10 +127,11 @@ pub fn update_double_jumping_context(
     let moving = double_jumping.moving;
     let ignore_grappling = double_jumping.forced || state.should_disable_grappling();
     let is_intermediate = moving.is_destination_intermediate();
+    let move_timeout = state.config.movement.move_timeout;
     let timeout = if double_jumping.forced {
-        TIMEOUT_FORCED
+        move_timeout + TIMEOUT_FORCED_EXTRA
     } else {
-        TIMEOUT
+        move_timeout
     };
     let axis = if double_jumping.forced {
         // This ensures it won't double jump forever when jumping towards either
@@ -229,7 +228,7 @@ pub fn update_double_jumping_context(
                             .keys
                             .send(state.config.teleport_key.unwrap_or(state.config.jump_key));
                     } else {
-                        double_jumping.update_jump_cooldown();
+                        double_jumping.update_jump_cooldown(move_timeout);
                     }
                 } else {
                     let _ = context.keys.send_up(KeyKind::Right);
@@ -263,7 +262,7 @@ pub fn update_double_jumping_context(
 
                     if moving.completed {
                         Player::DoubleJumping(
-                            double_jumping.moving(moving.timeout_current(TIMEOUT)),
+                            double_jumping.moving(moving.timeout_current(move_timeout)),
                         )
                     } else {
                         Player::DoubleJumping(double_jumping.moving(moving))
@@ -330,8 +329,14 @@ fn on_player_action(
             ..
         })
         | PlayerAction::SolveRune
+        | PlayerAction::SellItems(_)
         | PlayerAction::Move { .. } => None,
-        PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
+        PlayerAction::Panic(_)
+        | PlayerAction::FamiliarsSwapping(_)
+        | PlayerAction::Combo(_)
+        | PlayerAction::Mouse(_) => {
+            unreachable!()
+        }
     }
 }
 
@@ -440,6 +445,8 @@ mod tests {
     use opencv::core::{Point, Rect};
     #[cfg(windows)]
 use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
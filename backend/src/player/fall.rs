@@ -1,6 +1,8 @@
 use opencv::core::Point;
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
@@ -15,8 +17,7 @@ use crate::{
     ActionKeyWith,
     context::Context,
     player::{
-        MOVE_TIMEOUT, PlayerAction, actions::on_auto_mob_use_key_action, state::LastMovement,
-        timeout::ChangeAxis,
+        PlayerAction, actions::on_auto_mob_use_key_action, state::LastMovement, timeout::ChangeAxis,
     },
 };
 
@@ -30,8 +31,8 @@ const FALLING_TO_USE_KEY_THRESHOLD: i32 = 5;
 /// Tick to stop helding down [`KeyKind::Down`] at.
 const STOP_DOWN_KEY_TICK: u32 = 3;
 
-/// Maximum number of ticks before timing out.
-const TIMEOUT: u32 = MOVE_TIMEOUT + 3;
+/// Number of ticks added to the move timeout before timing out.
+const TIMEOUT_EXTRA: u32 = 3;
 
 /// Maximum y distance from the destination allowed to skip normal falling and use teleportation
 /// for mage.
@@ -54,10 +55,11 @@ pub fn update_falling_context(
     anchor: Point,
     timeout_on_complete: bool,
 ) -> Player {
+    let timeout = state.config.movement.move_timeout + TIMEOUT_EXTRA;
     match next_moving_lifecycle_with_axis(
         moving,
         state.last_known_pos.expect("in positional context"),
-        TIMEOUT,
+        timeout,
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
@@ -108,7 +110,7 @@ pub fn update_falling_context(
                     moving = moving.completed(true);
                 }
             } else if timeout_on_complete {
-                moving = moving.timeout_current(TIMEOUT);
+                moving = moving.timeout_current(timeout);
             }
 
             on_action_state(
@@ -169,8 +171,12 @@ fn on_player_action(
         })
         | PlayerAction::PingPong(_)
         | PlayerAction::Move(_)
+        | PlayerAction::SellItems(_)
         | PlayerAction::SolveRune => None,
-        PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => {
+        PlayerAction::Panic(_)
+        | PlayerAction::FamiliarsSwapping(_)
+        | PlayerAction::Combo(_)
+        | PlayerAction::Mouse(_) => {
             unreachable!()
         }
     }
@@ -183,6 +189,8 @@ mod tests {
     use opencv::core::Point;
     #[cfg(windows)]
 use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
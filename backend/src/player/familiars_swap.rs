@@ -1,28 +1,34 @@
-use std::fmt::Display;
+use std::{cmp::Reverse, fmt::Display};
 
-use log::debug;
+use anyhow::Result;
+use log::{debug, info};
 use opencv::core::{Point, Rect};
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
 use super::{
     Player, PlayerState,
-    actions::on_action,
+    actions::{PlayerActionFamiliarsSwapping, on_action},
     timeout::{Lifecycle, Timeout, next_timeout_lifecycle},
 };
 use crate::{
     array::Array,
     bridge::MouseAction,
     context::Context,
-    database::{FamiliarRarity, SwappableFamiliars},
-    detect::{FamiliarLevel, FamiliarRank},
+    database::{FamiliarLevelPriority, FamiliarRarity, FamiliarRarityPriority, SwappableFamiliars},
+    detect::{FamiliarLevel, FamiliarRank, FamiliarSlot},
 };
 
 /// Number of familiar slots available.
 const FAMILIAR_SLOTS: usize = 3;
 
+/// The maximum level a familiar can reach.
+const FAMILIAR_MAX_LEVEL: u32 = 5;
+
 const MAX_RETRY: u32 = 4;
 
 /// Internal state machine representing the current stage of familiar swapping.
@@ -40,6 +46,8 @@ enum SwappingStage {
     FreeSlot(Timeout, usize),
     /// Find swappable familiar cards.
     FindCards,
+    /// Hovering over each familiar card to detect its level for prioritizing.
+    ScanningCards(Timeout, usize),
     /// Swapping a card into an empty slot.
     Swapping(Timeout, usize),
     /// Scrolling the familiar cards list to find more cards.
@@ -49,6 +57,16 @@ enum SwappingStage {
     Completing(Timeout, bool),
 }
 
+/// The familiar setup read before saving in familiars swapping.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FamiliarSetup {
+    pub slots: Vec<FamiliarSlot>,
+    /// Whether every swappable slot is occupied by a familiar of one of the swappable rarities.
+    ///
+    /// Slots with unrecognized ranks are assumed to match.
+    pub matched: bool,
+}
+
 /// Struct for storing familiar swapping data.
 #[derive(Debug, Clone, Copy)]
 pub struct FamiliarsSwapping {
@@ -56,12 +74,21 @@ pub struct FamiliarsSwapping {
     stage: SwappingStage,
     /// Detected familiar slots with free/occupied status.
     slots: Array<(Rect, bool), 3>,
-    /// Detected familiar cards.
-    cards: Array<Rect, 64>,
+    /// Detected familiar cards with their rarity and level.
+    ///
+    /// The level is only detected when [`Self::level_priority`] is
+    /// [`FamiliarLevelPriority::Highest`].
+    cards: Array<(Rect, FamiliarRarity, Option<u32>), 64>,
     /// Indicates which familiar slots are allowed to be swapped.
     swappable_slots: SwappableFamiliars,
     /// Only familiars with these rarities will be considered for swapping.
     swappable_rarities: Array<FamiliarRarity, 2>,
+    /// Whether familiars with depleted points are also swapped out.
+    swap_when_points_depleted: bool,
+    /// The rarity of the cards to swap in first.
+    rarity_priority: FamiliarRarityPriority,
+    /// The level of the cards to swap in first.
+    level_priority: FamiliarLevelPriority,
     /// Mouse rest point for other operations.
     mouse_rest: Point,
 }
@@ -76,6 +103,7 @@ impl Display for FamiliarsSwapping {
                 write!(f, "Freeing Slots")
             }
             SwappingStage::FindCards => write!(f, "Finding Cards"),
+            SwappingStage::ScanningCards(_, _) => write!(f, "Scanning Cards"),
             SwappingStage::Swapping(_, _) => write!(f, "Swapping"),
             SwappingStage::Scrolling(_, _, _) => write!(f, "Scrolling"),
             SwappingStage::Saving(_, _) => write!(f, "Saving"),
@@ -85,16 +113,16 @@ impl Display for FamiliarsSwapping {
 }
 
 impl FamiliarsSwapping {
-    pub fn new(
-        swappable_slots: SwappableFamiliars,
-        swappable_rarities: Array<FamiliarRarity, 2>,
-    ) -> Self {
+    pub fn new(action: PlayerActionFamiliarsSwapping) -> Self {
         Self {
             stage: SwappingStage::OpenMenu(Timeout::default(), 0),
             slots: Array::new(),
             cards: Array::new(),
-            swappable_slots,
-            swappable_rarities,
+            swappable_slots: action.swappable_slots,
+            swappable_rarities: action.swappable_rarities,
+            swap_when_points_depleted: action.swap_when_points_depleted,
+            rarity_priority: action.rarity_priority,
+            level_priority: action.level_priority,
             mouse_rest: Point::new(50, 50),
         }
     }
@@ -126,6 +154,11 @@ impl FamiliarsSwapping {
         self.stage(SwappingStage::FreeSlot(timeout, index))
     }
 
+    #[inline]
+    fn stage_scanning_cards(self, timeout: Timeout, index: usize) -> FamiliarsSwapping {
+        self.stage(SwappingStage::ScanningCards(timeout, index))
+    }
+
     #[inline]
     fn stage_swapping(self, timeout: Timeout, index: usize) -> FamiliarsSwapping {
         self.stage(SwappingStage::Swapping(timeout, index))
@@ -183,6 +216,9 @@ pub fn update_familiars_swapping_context(
                 update_free_slot(context, swapping, timeout, index)
             }
             SwappingStage::FindCards => update_find_cards(context, swapping),
+            SwappingStage::ScanningCards(timeout, index) => {
+                update_scanning_cards(context, swapping, timeout, index)
+            }
             SwappingStage::Swapping(timeout, index) => {
                 update_swapping(context, swapping, timeout, index)
             }
@@ -197,6 +233,10 @@ pub fn update_familiars_swapping_context(
             }
         }
     };
+    // Only entered once from swapping when all slots are occupied
+    if matches!(swapping.stage, SwappingStage::Saving(timeout, 0) if !timeout.started) {
+        state.familiar_setup = Some(detect_setup(context, swapping));
+    }
     let next = if matches!(swapping.stage, SwappingStage::Completing(_, true)) {
         Player::Idle
     } else {
@@ -332,14 +372,7 @@ fn update_free_slots(
         (true, index) if index > 0 => swapping.stage_free_slots(index - 1, false),
         (true, 0) => find_cards_or_complete(context, swapping),
         (false, _) => {
-            let can_free = match swapping.swappable_slots {
-                SwappableFamiliars::All => true,
-                SwappableFamiliars::Last => index == FAMILIAR_SLOTS - 1,
-                SwappableFamiliars::SecondAndLast => {
-                    index == FAMILIAR_SLOTS - 1 || index == FAMILIAR_SLOTS - 2
-                }
-            };
-            if !can_free {
+            if !is_swappable_slot(swapping.swappable_slots, index) {
                 return find_cards_or_complete(context, swapping);
             }
 
@@ -384,15 +417,15 @@ fn update_free_slot(
 
             match timeout.current {
                 FAMILIAR_CHECK_LVL_5_TICK => {
-                    match detector.detect_familiar_hover_level() {
-                        Ok(FamiliarLevel::Level5) => {
+                    match detect_hover_familiar_swappable(context, swapping) {
+                        Ok(true) => {
                             // Double click to free
                             let _ = context.keys.send_mouse(x, y, MouseAction::Click);
                             let _ = context.keys.send_mouse(x, y, MouseAction::Click);
                             // Move mouse to rest position to check if it has been truely freed
                             let _ = context.keys.send_mouse(x, bbox.y - 20, MouseAction::Move);
                         }
-                        Ok(FamiliarLevel::LevelOther) => {
+                        Ok(false) => {
                             return if index > 0 {
                                 // If current slot is already non-level-5, check next slot
                                 swapping.stage_free_slots(index - 1, false)
@@ -444,12 +477,9 @@ fn update_find_cards(context: &Context, mut swapping: FamiliarsSwapping) -> Fami
             return swapping.stage_scrolling(Timeout::default(), None, 0);
         }
         for pair in vec {
-            let rarity = match pair.1 {
-                FamiliarRank::Rare => FamiliarRarity::Rare,
-                FamiliarRank::Epic => FamiliarRarity::Epic,
-            };
+            let rarity = rank_rarity(pair.1);
             if swapping.swappable_rarities.iter().any(|r| *r == rarity) {
-                swapping.cards.push(pair.0);
+                swapping.cards.push((pair.0, rarity, None));
             }
         }
     }
@@ -457,8 +487,75 @@ fn update_find_cards(context: &Context, mut swapping: FamiliarsSwapping) -> Fami
     if swapping.cards.is_empty() {
         // Try scroll even if it is empty
         swapping.stage_scrolling(Timeout::default(), None, 0)
+    } else if matches!(swapping.level_priority, FamiliarLevelPriority::Highest) {
+        swapping.stage_scanning_cards(Timeout::default(), 0)
     } else {
-        swapping.stage_swapping(Timeout::default(), 0)
+        // Cards are already sorted from the lowest level by the level button
+        sort_cards(swapping).stage_swapping(Timeout::default(), 0)
+    }
+}
+
+fn update_scanning_cards(
+    context: &Context,
+    swapping: FamiliarsSwapping,
+    timeout: Timeout,
+    index: usize,
+) -> FamiliarsSwapping {
+    const SCANNING_TIMEOUT: u32 = 5;
+
+    match next_timeout_lifecycle(timeout, SCANNING_TIMEOUT) {
+        Lifecycle::Started(timeout) => {
+            let (x, y) = bbox_click_point(swapping.cards[index].0);
+            let _ = context.keys.send_mouse(x, y, MouseAction::Move);
+            swapping.stage_scanning_cards(timeout, index)
+        }
+        Lifecycle::Ended => {
+            let mut swapping = swapping;
+            swapping.cards[index].2 = context
+                .detector_unwrap()
+                .detect_familiar_hover_stats()
+                .ok()
+                .map(|stats| stats.level);
+
+            if index + 1 < swapping.cards.len() {
+                swapping.stage_scanning_cards(Timeout::default(), index + 1)
+            } else {
+                let rest = swapping.mouse_rest;
+                let _ = context.keys.send_mouse(rest.x, rest.y, MouseAction::Move);
+                sort_cards(swapping).stage_swapping(Timeout::default(), 0)
+            }
+        }
+        Lifecycle::Updated(timeout) => swapping.stage_scanning_cards(timeout, index),
+    }
+}
+
+/// Sorts [`FamiliarsSwapping::cards`] by rarity and then level priority.
+///
+/// Cards with the same priority keep their detected order.
+fn sort_cards(swapping: FamiliarsSwapping) -> FamiliarsSwapping {
+    let mut cards = swapping.cards.into_iter().collect::<Vec<_>>();
+    cards.sort_by_key(|(_, rarity, level)| {
+        let rarity_order = match (swapping.rarity_priority, rarity) {
+            (FamiliarRarityPriority::Any, _)
+            | (FamiliarRarityPriority::Rare, FamiliarRarity::Rare)
+            | (FamiliarRarityPriority::Epic, FamiliarRarity::Epic) => 0,
+            _ => 1,
+        };
+        let level_order = match swapping.level_priority {
+            FamiliarLevelPriority::Lowest => Reverse(0),
+            // Maximum level cards are skipped when swapping so they are placed last
+            FamiliarLevelPriority::Highest => Reverse(
+                level
+                    .filter(|level| *level < FAMILIAR_MAX_LEVEL)
+                    .unwrap_or_default(),
+            ),
+        };
+        (rarity_order, level_order)
+    });
+
+    FamiliarsSwapping {
+        cards: Array::from_iter(cards),
+        ..swapping
     }
 }
 
@@ -473,7 +570,7 @@ fn update_swapping(
 
     match next_timeout_lifecycle(timeout, SWAPPING_TIMEOUT) {
         Lifecycle::Started(timeout) => {
-            let (x, y) = bbox_click_point(swapping.cards[index]);
+            let (x, y) = bbox_click_point(swapping.cards[index].0);
             let _ = context.keys.send_mouse(x, y, MouseAction::Move);
             swapping.stage_swapping(timeout, index)
         }
@@ -504,14 +601,14 @@ fn update_swapping(
             if timeout.current == SWAPPING_DETECT_LEVEL_TICK {
                 let rest = swapping.mouse_rest;
 
-                match context.detector_unwrap().detect_familiar_hover_level() {
-                    Ok(FamiliarLevel::Level5) => {
+                match detect_hover_familiar_swappable(context, swapping) {
+                    Ok(true) => {
                         // Move to rest position and wait for timeout
                         let _ = context.keys.send_mouse(rest.x, rest.y, MouseAction::Move);
                     }
-                    Ok(FamiliarLevel::LevelOther) => {
+                    Ok(false) => {
                         // Double click to select and then move to rest point
-                        let bbox = swapping.cards[index];
+                        let (bbox, _, _) = swapping.cards[index];
                         let (x, y) = bbox_click_point(bbox);
                         let _ = context.keys.send_mouse(x, y, MouseAction::Click);
                         let _ = context.keys.send_mouse(x, y, MouseAction::Click);
@@ -663,6 +760,66 @@ fn update_completing(
     }
 }
 
+/// Reads the current familiar setup and verifies it against the swapping rules.
+fn detect_setup(context: &Context, swapping: FamiliarsSwapping) -> FamiliarSetup {
+    let slots = context.detector_unwrap().detect_familiar_setup();
+    let matched = is_setup_matched(swapping, &slots);
+    if matched {
+        debug!(target: "player", "familiar setup {slots:?} matched");
+    } else {
+        info!(target: "player", "familiar setup {slots:?} does not match swapping rules");
+    }
+    FamiliarSetup { slots, matched }
+}
+
+fn is_setup_matched(swapping: FamiliarsSwapping, slots: &[FamiliarSlot]) -> bool {
+    slots.len() == FAMILIAR_SLOTS
+        && slots.iter().enumerate().all(|(index, slot)| match slot {
+            FamiliarSlot::Free => false,
+            FamiliarSlot::Occupied(Some(rank))
+                if is_swappable_slot(swapping.swappable_slots, index) =>
+            {
+                let rarity = rank_rarity(*rank);
+                swapping.swappable_rarities.iter().any(|r| *r == rarity)
+            }
+            FamiliarSlot::Occupied(_) => true,
+        })
+}
+
+#[inline]
+fn is_swappable_slot(slots: SwappableFamiliars, index: usize) -> bool {
+    match slots {
+        SwappableFamiliars::All => true,
+        SwappableFamiliars::Last => index == FAMILIAR_SLOTS - 1,
+        SwappableFamiliars::SecondAndLast => {
+            index == FAMILIAR_SLOTS - 1 || index == FAMILIAR_SLOTS - 2
+        }
+    }
+}
+
+#[inline]
+fn rank_rarity(rank: FamiliarRank) -> FamiliarRarity {
+    match rank {
+        FamiliarRank::Rare => FamiliarRarity::Rare,
+        FamiliarRank::Epic => FamiliarRarity::Epic,
+    }
+}
+
+/// Detects whether the currently mouse hovering familiar should be swapped out.
+///
+/// This is the case when the familiar is level 5 or its points are depleted if
+/// [`FamiliarsSwapping::swap_when_points_depleted`] is set.
+fn detect_hover_familiar_swappable(context: &Context, swapping: FamiliarsSwapping) -> Result<bool> {
+    let detector = context.detector_unwrap();
+    match detector.detect_familiar_hover_level()? {
+        FamiliarLevel::Level5 => Ok(true),
+        FamiliarLevel::LevelOther => Ok(swapping.swap_when_points_depleted
+            && detector
+                .detect_familiar_hover_stats()
+                .is_ok_and(|stats| stats.points == 0)),
+    }
+}
+
 #[inline]
 fn bbox_click_point(bbox: Rect) -> (i32, i32) {
     let x = bbox.x + bbox.width / 2;
@@ -678,12 +835,26 @@ mod tests {
     use mockall::predicate::{eq, function};
 
     use super::*;
-    use crate::{array::Array, bridge::MockKeySender, detect::MockDetector};
+    use crate::{
+        array::Array,
+        bridge::MockKeySender,
+        detect::{FamiliarStats, MockDetector},
+    };
+
+    fn familiars_swapping(swappable_slots: SwappableFamiliars) -> FamiliarsSwapping {
+        FamiliarsSwapping::new(PlayerActionFamiliarsSwapping {
+            swappable_slots,
+            swappable_rarities: Array::new(),
+            swap_when_points_depleted: false,
+            rarity_priority: FamiliarRarityPriority::Any,
+            level_priority: FamiliarLevelPriority::Lowest,
+        })
+    }
 
     #[test]
     fn update_free_slots_advance_index_if_already_free() {
         let context = Context::new(None, None);
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
         swapping.slots.push((bbox, true)); // Index 1 already free
@@ -711,7 +882,7 @@ mod tests {
             .returning(|_, _, _| Ok(()));
         let context = Context::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
         let bbox = Default::default();
         swapping.slots.push((bbox, true));
 
@@ -723,7 +894,7 @@ mod tests {
     #[test]
     fn update_free_slots_can_free() {
         let context = Context::new(None, None);
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
         // Second slot not free but can free because of SwappableFamiliars::All
@@ -736,7 +907,7 @@ mod tests {
     #[test]
     fn update_free_slots_cannot_free() {
         let context = Context::new(None, None);
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::Last, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::Last);
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
         // Second slot not free but also cannot free because of SwappableFamiliars::Last
@@ -760,7 +931,43 @@ mod tests {
             .returning(|| Ok(FamiliarLevel::Level5));
         let context = Context::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
+        let bbox = Default::default();
+        swapping.slots.push((bbox, false));
+
+        let timeout = Timeout {
+            current: 4, // One tick before detection
+            started: true,
+            ..Default::default()
+        };
+        let result = update_free_slot(&context, swapping, timeout, 0);
+        assert_matches!(result.stage, SwappingStage::FreeSlot(_, 0));
+    }
+
+    #[test]
+    fn update_free_slot_detect_points_depleted_and_click() {
+        let mut keys = MockKeySender::default();
+        keys.expect_send_mouse()
+            .times(3)
+            .returning(|_, _, _| Ok(()));
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_familiar_hover_level()
+            .once()
+            .returning(|| Ok(FamiliarLevel::LevelOther));
+        detector
+            .expect_detect_familiar_hover_stats()
+            .once()
+            .returning(|| {
+                Ok(FamiliarStats {
+                    level: 3,
+                    points: 0,
+                })
+            });
+        let context = Context::new(Some(keys), Some(detector));
+
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
+        swapping.swap_when_points_depleted = true;
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
 
@@ -782,7 +989,7 @@ mod tests {
             .returning(|_| true);
         let context = Context::new(None, Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
         let bbox = Default::default();
         swapping.slots.push((bbox, false));
 
@@ -810,9 +1017,9 @@ mod tests {
             .returning(|| Ok(FamiliarLevel::Level5));
         let context = Context::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
         let bbox = Default::default();
-        swapping.cards.push(bbox);
+        swapping.cards.push((bbox, FamiliarRarity::Rare, None));
 
         let timeout = Timeout {
             current: 4,
@@ -835,9 +1042,9 @@ mod tests {
             .returning(|| Ok(FamiliarLevel::LevelOther));
         let context = Context::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
         let bbox = Default::default();
-        swapping.cards.push(bbox);
+        swapping.cards.push((bbox, FamiliarRarity::Rare, None));
 
         let timeout = Timeout {
             current: 4,
@@ -856,10 +1063,10 @@ mod tests {
             .returning(|_| true);
         let context = Context::new(None, Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
         let bbox = Default::default();
-        swapping.cards.push(bbox);
-        swapping.cards.push(bbox);
+        swapping.cards.push((bbox, FamiliarRarity::Rare, None));
+        swapping.cards.push((bbox, FamiliarRarity::Rare, None));
         for _ in 0..FAMILIAR_SLOTS {
             swapping.slots.push((bbox, true));
         }
@@ -885,9 +1092,9 @@ mod tests {
             .returning(|_| true);
         let context = Context::new(Some(keys), Some(detector));
 
-        let mut swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
         let bbox = Default::default();
-        swapping.cards.push(bbox);
+        swapping.cards.push((bbox, FamiliarRarity::Rare, None));
         for _ in 0..FAMILIAR_SLOTS {
             swapping.slots.push((bbox, true));
         }
@@ -914,7 +1121,7 @@ mod tests {
             .returning(|| Ok(Default::default()));
 
         let context = Context::new(Some(keys), Some(detector));
-        let swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let swapping = familiars_swapping(SwappableFamiliars::All);
 
         let timeout = Timeout::default();
         let result = update_saving(&context, swapping, timeout, 0);
@@ -937,7 +1144,7 @@ mod tests {
             .returning(|| Ok(Default::default()));
 
         let context = Context::new(Some(keys), Some(detector));
-        let swapping = FamiliarsSwapping::new(SwappableFamiliars::All, Array::new());
+        let swapping = familiars_swapping(SwappableFamiliars::All);
 
         let timeout = Timeout {
             current: 14, // PRESS_OK_AT
@@ -958,5 +1165,53 @@ mod tests {
         assert_matches!(result.stage, SwappingStage::Saving(_, 0));
     }
 
+    #[test]
+    fn sort_cards_by_rarity_then_highest_level() {
+        let mut swapping = familiars_swapping(SwappableFamiliars::All);
+        swapping.rarity_priority = FamiliarRarityPriority::Epic;
+        swapping.level_priority = FamiliarLevelPriority::Highest;
+        let bbox = |x| Rect::new(x, 0, 10, 10);
+        swapping
+            .cards
+            .push((bbox(0), FamiliarRarity::Rare, Some(4)));
+        swapping
+            .cards
+            .push((bbox(1), FamiliarRarity::Epic, Some(1)));
+        swapping
+            .cards
+            .push((bbox(2), FamiliarRarity::Epic, Some(5)));
+        swapping
+            .cards
+            .push((bbox(3), FamiliarRarity::Epic, Some(3)));
+
+        let result = sort_cards(swapping);
+        let order = result
+            .cards
+            .iter()
+            .map(|(bbox, _, _)| bbox.x)
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec![3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn is_setup_matched_checks_swappable_slots_rarity() {
+        let mut swapping = familiars_swapping(SwappableFamiliars::Last);
+        swapping.swappable_rarities.push(FamiliarRarity::Epic);
+        let rare = FamiliarSlot::Occupied(Some(FamiliarRank::Rare));
+        let epic = FamiliarSlot::Occupied(Some(FamiliarRank::Epic));
+
+        assert!(is_setup_matched(swapping, &[rare, rare, epic]));
+        assert!(is_setup_matched(
+            swapping,
+            &[rare, rare, FamiliarSlot::Occupied(None)]
+        ));
+        assert!(!is_setup_matched(swapping, &[rare, epic, rare]));
+        assert!(!is_setup_matched(
+            swapping,
+            &[rare, rare, FamiliarSlot::Free]
+        ));
+        assert!(!is_setup_matched(swapping, &[rare, epic]));
+    }
+
     // TODO: more tests
 }
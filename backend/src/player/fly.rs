@@ -0,0 +1,227 @@
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
+#[cfg(target_os = "macos")]
+use platforms::macos::KeyKind;
+#[cfg(windows)]
+use platforms::windows::KeyKind;
+
+use super::{
+    Player, PlayerAction, PlayerState,
+    actions::{on_action_state, on_auto_mob_use_key_action},
+    moving::Moving,
+    state::LastMovement,
+    timeout::{MovingLifecycle, next_moving_lifecycle_with_axis},
+};
+use crate::{context::Context, player::timeout::ChangeAxis};
+
+/// Minimum y distance from the destination required to fly.
+pub const FLYING_THRESHOLD: i32 = 10;
+
+/// Maximum y distance from the destination allowed to fly.
+///
+/// Flying has no upper limit so any platform above can be reached.
+pub const FLYING_MAX_THRESHOLD: i32 = i32::MAX;
+
+/// Timeout for flying as a multiple of the move timeout.
+const TIMEOUT_MULTIPLIER: u32 = 8;
+
+/// Number of ticks added to the move timeout to time out after stopping flying.
+const STOPPING_TIMEOUT_EXTRA: u32 = 3;
+
+/// Maximum y distance allowed to stop flying.
+const STOPPING_THRESHOLD: i32 = 2;
+
+/// Updates the [`Player::Flying`] contextual state.
+///
+/// This state can only be transitioned via [`Player::Moving`] when the player has reached or
+/// close to the destination x-wise.
+///
+/// The flight key is pressed (or held) to take off, then altitude is controlled by holding the
+/// Up or Down key until the player is close enough to the destination y-wise.
+pub fn update_flying_context(context: &Context, state: &mut PlayerState, moving: Moving) -> Player {
+    let key = state
+        .config
+        .flying_key
+        .expect("cannot transition if not set");
+    let move_timeout = state.config.movement.move_timeout;
+    let timeout = move_timeout * TIMEOUT_MULTIPLIER;
+    let hold_key = state.config.hold_flying_key;
+
+    match next_moving_lifecycle_with_axis(
+        moving,
+        state.last_known_pos.expect("in positional context"),
+        timeout,
+        ChangeAxis::Vertical,
+    ) {
+        MovingLifecycle::Started(moving) => {
+            state.last_movement = Some(LastMovement::Flying);
+            if hold_key {
+                let _ = context.keys.send_down(key);
+            } else {
+                let _ = context.keys.send(key);
+            }
+            let _ = context.keys.send_down(KeyKind::Up);
+            Player::Flying(moving)
+        }
+        MovingLifecycle::Ended(moving) => {
+            stop_flying(context, key, hold_key);
+            Player::Moving(moving.dest, moving.exact, moving.intermediates)
+        }
+        MovingLifecycle::Updated(mut moving) => {
+            let cur_pos = moving.pos;
+            let (y_distance, y_direction) = moving.y_distance_direction_from(true, cur_pos);
+
+            if !moving.completed {
+                if y_distance <= stopping_threshold(state.velocity.1) {
+                    stop_flying(context, key, hold_key);
+                    moving = moving.completed(true);
+                } else if y_direction > 0 {
+                    let _ = context.keys.send_up(KeyKind::Down);
+                    let _ = context.keys.send_down(KeyKind::Up);
+                } else {
+                    let _ = context.keys.send_up(KeyKind::Up);
+                    let _ = context.keys.send_down(KeyKind::Down);
+                }
+            } else if moving.timeout.current >= move_timeout + STOPPING_TIMEOUT_EXTRA {
+                moving = moving.timeout_current(timeout);
+            }
+
+            on_action_state(
+                state,
+                |_, action| match action {
+                    PlayerAction::AutoMob(_) => {
+                        if !moving.completed {
+                            return None;
+                        }
+                        if moving.is_destination_intermediate() {
+                            return Some((
+                                Player::Moving(moving.dest, moving.exact, moving.intermediates),
+                                false,
+                            ));
+                        }
+
+                        let (x_distance, _) = moving.x_distance_direction_from(false, cur_pos);
+                        let (y_distance, _) = moving.y_distance_direction_from(false, cur_pos);
+                        on_auto_mob_use_key_action(context, action, cur_pos, x_distance, y_distance)
+                    }
+                    PlayerAction::Key(_)
+                    | PlayerAction::Move(_)
+                    | PlayerAction::PingPong(_)
+                    | PlayerAction::SellItems(_)
+                    | PlayerAction::SolveRune => None,
+                    PlayerAction::Panic(_)
+                    | PlayerAction::FamiliarsSwapping(_)
+                    | PlayerAction::Combo(_)
+                    | PlayerAction::Mouse(_) => unreachable!(),
+                },
+                || Player::Flying(moving),
+            )
+        }
+    }
+}
+
+#[inline]
+fn stop_flying(context: &Context, key: KeyKind, hold_key: bool) {
+    let _ = context.keys.send_up(KeyKind::Up);
+    let _ = context.keys.send_up(KeyKind::Down);
+    if hold_key {
+        let _ = context.keys.send_up(key);
+    }
+}
+
+/// Converts vertical velocity to a stopping threshold.
+#[inline]
+fn stopping_threshold(velocity: f32) -> i32 {
+    (STOPPING_THRESHOLD as f32 + velocity).ceil() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+    use opencv::core::Point;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
+    #[cfg(target_os = "macos")]
+    use platforms::macos::KeyKind;
+    #[cfg(windows)]
+    use platforms::windows::KeyKind;
+
+    use super::*;
+    use crate::bridge::MockKeySender;
+
+    fn mock_state_with_flying(pos: Point) -> PlayerState {
+        let mut state = PlayerState::default();
+        state.last_known_pos = Some(pos);
+        state.config.flying_key = Some(KeyKind::Shift);
+        state
+    }
+
+    #[test]
+    fn update_flying_context_started() {
+        let mut state = mock_state_with_flying(Point::new(100, 100));
+        let moving = Moving::new(Point::new(100, 100), Point::new(100, 200), false, None);
+        let mut keys = MockKeySender::new();
+        keys.expect_send()
+            .once()
+            .with(eq(KeyKind::Shift))
+            .returning(|_| Ok(()));
+        keys.expect_send_down()
+            .once()
+            .with(eq(KeyKind::Up))
+            .returning(|_| Ok(()));
+        let context = Context::new(Some(keys), None);
+
+        let player = update_flying_context(&context, &mut state, moving);
+
+        assert!(matches!(player, Player::Flying(_)));
+        assert_eq!(state.last_movement, Some(LastMovement::Flying));
+    }
+
+    #[test]
+    fn update_flying_context_updated_descends_when_overshot() {
+        let mut keys = MockKeySender::new();
+        keys.expect_send_up()
+            .once()
+            .with(eq(KeyKind::Up))
+            .returning(|_| Ok(()));
+        keys.expect_send_down()
+            .once()
+            .with(eq(KeyKind::Down))
+            .returning(|_| Ok(()));
+        let context = Context::new(Some(keys), None);
+        let mut state = mock_state_with_flying(Point::new(100, 210)); // Overshot
+        let mut moving = Moving::new(Point::new(100, 205), Point::new(100, 200), false, None);
+        moving.timeout.started = true;
+
+        let player = update_flying_context(&context, &mut state, moving);
+
+        match player {
+            Player::Flying(moving) => assert!(!moving.completed),
+            _ => panic!("Expected Player::Flying"),
+        }
+    }
+
+    #[test]
+    fn update_flying_context_updated_completes_near_destination() {
+        let mut keys = MockKeySender::new();
+        keys.expect_send_up()
+            .once()
+            .with(eq(KeyKind::Up))
+            .returning(|_| Ok(()));
+        keys.expect_send_up()
+            .once()
+            .with(eq(KeyKind::Down))
+            .returning(|_| Ok(()));
+        let context = Context::new(Some(keys), None);
+        let mut state = mock_state_with_flying(Point::new(100, 199));
+        let mut moving = Moving::new(Point::new(100, 190), Point::new(100, 200), false, None);
+        moving.timeout.started = true;
+
+        let player = update_flying_context(&context, &mut state, moving);
+
+        match player {
+            Player::Flying(moving) => assert!(moving.completed),
+            _ => panic!("Expected Player::Flying"),
+        }
+    }
+}
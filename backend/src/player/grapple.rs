@@ -5,10 +5,7 @@ use super::{
     state::LastMovement,
     timeout::{MovingLifecycle, next_moving_lifecycle_with_axis},
 };
-use crate::{
-    context::Context,
-    player::{MOVE_TIMEOUT, timeout::ChangeAxis},
-};
+use crate::{context::Context, player::timeout::ChangeAxis};
 
 /// Minimum y distance from the destination required to perform a grappling hook.
 pub const GRAPPLING_THRESHOLD: i32 = 24;
@@ -16,11 +13,11 @@ pub const GRAPPLING_THRESHOLD: i32 = 24;
 /// Maximum y distance from the destination allowed to perform a grappling hook.
 pub const GRAPPLING_MAX_THRESHOLD: i32 = 41;
 
-/// Timeout for grappling.
-const TIMEOUT: u32 = MOVE_TIMEOUT * 8;
+/// Timeout for grappling as a multiple of the move timeout.
+const TIMEOUT_MULTIPLIER: u32 = 8;
 
-/// Timeout after stopping grappling.
-const STOPPING_TIMEOUT: u32 = MOVE_TIMEOUT + 3;
+/// Number of ticks added to the move timeout to time out after stopping grappling.
+const STOPPING_TIMEOUT_EXTRA: u32 = 3;
 
 /// Maximum y distance allowed to stop grappling.
 const STOPPING_THRESHOLD: i32 = 3;
@@ -40,12 +37,14 @@ pub fn update_grappling_context(
         .config
         .grappling_key
         .expect("cannot transition if not set");
+    let move_timeout = state.config.movement.move_timeout;
+    let timeout = move_timeout * TIMEOUT_MULTIPLIER;
     let prev_pos = moving.pos;
 
     match next_moving_lifecycle_with_axis(
         moving,
         state.last_known_pos.expect("in positional context"),
-        TIMEOUT,
+        timeout,
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
@@ -61,17 +60,17 @@ pub fn update_grappling_context(
             let (y_distance, y_direction) = moving.y_distance_direction_from(true, cur_pos);
             let x_changed = prev_pos.x != cur_pos.x;
 
-            if moving.timeout.current >= MOVE_TIMEOUT && x_changed {
+            if moving.timeout.current >= move_timeout && x_changed {
                 // During double jump and grappling failed
-                moving = moving.timeout_current(TIMEOUT).completed(true);
+                moving = moving.timeout_current(timeout).completed(true);
             }
             if !moving.completed {
                 if y_direction <= 0 || y_distance <= stopping_threshold(state.velocity.1) {
                     let _ = context.keys.send(key);
                     moving = moving.completed(true);
                 }
-            } else if moving.timeout.current >= STOPPING_TIMEOUT {
-                moving = moving.timeout_current(TIMEOUT);
+            } else if moving.timeout.current >= move_timeout + STOPPING_TIMEOUT_EXTRA {
+                moving = moving.timeout_current(timeout);
             }
 
             on_action_state(
@@ -113,8 +112,14 @@ pub fn update_grappling_context(
                             None
                         }
                     }
-                    PlayerAction::Key(_) | PlayerAction::Move(_) | PlayerAction::SolveRune => None,
-                    PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
+                    PlayerAction::Key(_)
+                    | PlayerAction::Move(_)
+                    | PlayerAction::SellItems(_)
+                    | PlayerAction::SolveRune => None,
+                    PlayerAction::Panic(_)
+                    | PlayerAction::FamiliarsSwapping(_)
+                    | PlayerAction::Combo(_)
+                    | PlayerAction::Mouse(_) => unreachable!(),
                 },
                 || Player::Grappling(moving),
             )
@@ -134,11 +139,13 @@ mod tests {
     use opencv::core::Point;
     #[cfg(windows)]
 use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
     use super::*;
-    use crate::bridge::MockKeySender;
+    use crate::{bridge::MockKeySender, player::MOVE_TIMEOUT};
 
     const START_POS: Point = Point { x: 100, y: 100 };
     const END_POS: Point = Point { x: 100, y: 200 };
@@ -2,14 +2,19 @@ use log::debug;
 use opencv::core::Point;
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
 use super::{
-    Player, PlayerAction, PlayerActionAutoMob, PlayerActionKey, PlayerActionMove, PlayerState,
+    Player, PlayerAction, PlayerActionAutoMob, PlayerActionKey, PlayerActionMove,
+    PlayerActionSellItems, PlayerState,
     actions::{PlayerActionPingPong, on_action_state_mut, on_ping_pong_double_jump_action},
+    combo::Comboing,
     double_jump::DoubleJumping,
     familiars_swap::FamiliarsSwapping,
+    mouse::UsingMouse,
     moving::{Moving, find_intermediate_points},
     panic::Panicking,
     use_key::UseKey,
@@ -55,7 +60,10 @@ fn on_player_action(
                         point,
                         position.allow_adjusting,
                         state.config.auto_mob_platforms_pathing_up_jump_only,
+                        state.config.flying_key.is_some(),
                         false,
+                        !state.config.disable_jump_down,
+                        state.config.movement,
                     ),
                     _ => unreachable!(),
                 }
@@ -139,7 +147,10 @@ fn on_player_action(
                         rune,
                         true,
                         state.config.rune_platforms_pathing_up_jump_only,
+                        state.config.flying_key.is_some(),
                         true,
+                        !state.config.disable_jump_down,
+                        state.config.movement,
                     );
                     if let Some(mut intermediates) = intermediates {
                         state.last_destinations = Some(
@@ -164,13 +175,26 @@ fn on_player_action(
             context, cur_pos, bound, direction,
         )),
         PlayerAction::FamiliarsSwapping(swapping) => Some((
-            Player::FamiliarsSwapping(FamiliarsSwapping::new(
-                swapping.swappable_slots,
-                swapping.swappable_rarities,
-            )),
+            Player::FamiliarsSwapping(FamiliarsSwapping::new(swapping)),
             false,
         )),
         PlayerAction::Panic(panic) => Some((Player::Panicking(Panicking::new(panic.to)), false)),
+        PlayerAction::Combo(combo) => Some((Player::Comboing(Comboing::new(combo)), false)),
+        PlayerAction::Mouse(mouse) => {
+            Some((Player::UsingMouse(UsingMouse::new(context, mouse)), false))
+        }
+        PlayerAction::SellItems(PlayerActionSellItems { npc_position, .. }) => {
+            let x = get_x_destination(&context.rng, npc_position);
+            debug!(target: "player", "handling move to shop npc: {} {}", x, npc_position.y);
+            Some((
+                Player::Moving(
+                    Point::new(x, npc_position.y),
+                    npc_position.allow_adjusting,
+                    None,
+                ),
+                false,
+            ))
+        }
     }
 }
 
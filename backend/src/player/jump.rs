@@ -1,12 +1,13 @@
 use super::{
     Player, PlayerState,
-    moving::{MOVE_TIMEOUT, Moving},
+    moving::Moving,
     state::LastMovement,
     timeout::{ChangeAxis, MovingLifecycle, next_moving_lifecycle_with_axis},
 };
 use crate::context::Context;
 
-const TIMEOUT: u32 = MOVE_TIMEOUT + 3;
+/// Number of ticks added to the move timeout before timing out.
+const TIMEOUT_EXTRA: u32 = 3;
 
 pub fn update_jumping_context(
     context: &Context,
@@ -16,7 +17,7 @@ pub fn update_jumping_context(
     match next_moving_lifecycle_with_axis(
         moving,
         state.last_known_pos.expect("in positional context"),
-        TIMEOUT,
+        state.config.movement.move_timeout + TIMEOUT_EXTRA,
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
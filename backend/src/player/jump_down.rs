@@ -0,0 +1,119 @@
+use opencv::core::Point;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
+#[cfg(target_os = "macos")]
+use platforms::macos::KeyKind;
+#[cfg(windows)]
+use platforms::windows::KeyKind;
+
+use super::{
+    Player, PlayerState,
+    moving::Moving,
+    state::LastMovement,
+    timeout::{ChangeAxis, MovingLifecycle, next_moving_lifecycle_with_axis},
+};
+use crate::context::Context;
+
+/// Tick to stop helding down [`KeyKind::Down`] at.
+const STOP_DOWN_KEY_TICK: u32 = 3;
+
+/// Number of ticks added to the move timeout before timing out.
+const TIMEOUT_EXTRA: u32 = 3;
+
+/// Updates the [`Player::JumpingDown`] contextual state.
+///
+/// This state can only be transitioned via [`Player::Moving`] when the player has reached a
+/// point above the next platform in platforms pathing. Unlike [`Player::Falling`], it always
+/// jumps down with Down and the jump key and times out as soon as the player is below `anchor`.
+///
+/// Before jumping down, it will wait for the player to become stationary in case the player is
+/// still moving.
+pub fn update_jumping_down_context(
+    context: &Context,
+    state: &mut PlayerState,
+    moving: Moving,
+    anchor: Point,
+) -> Player {
+    let timeout = state.config.movement.move_timeout + TIMEOUT_EXTRA;
+
+    match next_moving_lifecycle_with_axis(
+        moving,
+        state.last_known_pos.expect("in positional context"),
+        timeout,
+        ChangeAxis::Vertical,
+    ) {
+        MovingLifecycle::Started(moving) => {
+            if !state.is_stationary {
+                return Player::JumpingDown {
+                    moving: moving.timeout_started(false),
+                    anchor: moving.pos,
+                };
+            }
+
+            state.last_movement = Some(LastMovement::JumpingDown);
+            let _ = context.keys.send_down(KeyKind::Down);
+            let _ = context.keys.send(state.config.jump_key);
+            Player::JumpingDown { moving, anchor }
+        }
+        MovingLifecycle::Ended(moving) => {
+            let _ = context.keys.send_up(KeyKind::Down);
+            Player::Moving(moving.dest, moving.exact, moving.intermediates)
+        }
+        MovingLifecycle::Updated(mut moving) => {
+            if moving.timeout.total == STOP_DOWN_KEY_TICK {
+                let _ = context.keys.send_up(KeyKind::Down);
+            }
+            if !moving.completed && moving.pos.y < anchor.y {
+                moving = moving.completed(true).timeout_current(timeout);
+            }
+            Player::JumpingDown { moving, anchor }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::bridge::MockKeySender;
+
+    #[test]
+    fn update_jumping_down_context_started_sends_down_and_jump() {
+        let mut state = PlayerState::default();
+        state.is_stationary = true;
+        state.last_known_pos = Some(Point::new(20, 50));
+        let mut keys = MockKeySender::new();
+        keys.expect_send_down()
+            .once()
+            .with(eq(KeyKind::Down))
+            .returning(|_| Ok(()));
+        keys.expect_send()
+            .once()
+            .with(eq(state.config.jump_key))
+            .returning(|_| Ok(()));
+        let context = Context::new(Some(keys), None);
+        let moving = Moving::new(Point::new(20, 50), Point::new(20, 30), false, None);
+
+        let player = update_jumping_down_context(&context, &mut state, moving, moving.pos);
+
+        assert!(matches!(player, Player::JumpingDown { .. }));
+        assert_eq!(state.last_movement, Some(LastMovement::JumpingDown));
+    }
+
+    #[test]
+    fn update_jumping_down_context_completes_below_anchor() {
+        let mut state = PlayerState::default();
+        state.last_known_pos = Some(Point::new(20, 45));
+        let context = Context::new(None, None);
+        let mut moving = Moving::new(Point::new(20, 50), Point::new(20, 30), false, None);
+        moving.timeout.started = true;
+
+        let player = update_jumping_down_context(&context, &mut state, moving, Point::new(20, 50));
+
+        match player {
+            Player::JumpingDown { moving, .. } => assert!(moving.completed),
+            _ => panic!("Expected Player::JumpingDown"),
+        }
+    }
+}
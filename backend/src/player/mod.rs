@@ -1,19 +1,27 @@
 use actions::{on_action, on_action_state_mut};
 use adjust::{Adjusting, update_adjusting_context};
 use cash_shop::{CashShop, update_cash_shop_context};
+use climb::update_climbing_context;
+use combo::{Comboing, update_comboing_context};
 use double_jump::{DoubleJumping, update_double_jumping_context};
 use fall::update_falling_context;
 use familiars_swap::{FamiliarsSwapping, update_familiars_swapping_context};
+use fly::update_flying_context;
 use grapple::update_grappling_context;
 use idle::update_idle_context;
 use jump::update_jumping_context;
+use jump_down::update_jumping_down_context;
+use mouse::{UsingMouse, update_using_mouse_context};
 use moving::{MOVE_TIMEOUT, Moving, MovingIntermediates, update_moving_context};
 use opencv::core::Point;
 use panic::update_panicking_context;
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
+use sell_items::{SellingItems, update_selling_items_context};
 use solve_rune::{SolvingRune, update_solving_rune_context};
 use stall::update_stalling_context;
 use state::LastMovement;
@@ -25,21 +33,27 @@ use use_key::{UseKey, update_use_key_context};
 
 use crate::{
     context::{Context, Contextual, ControlFlow},
-    database::ActionKeyDirection,
+    database::{ActionKeyDirection, MovementOverrides},
     minimap::Minimap,
 };
 
 mod actions;
 mod adjust;
 mod cash_shop;
+mod climb;
+mod combo;
 mod double_jump;
 mod fall;
 mod familiars_swap;
+mod fly;
 mod grapple;
 mod idle;
 mod jump;
+mod jump_down;
+mod mouse;
 mod moving;
 mod panic;
+mod sell_items;
 mod solve_rune;
 mod stall;
 mod state;
@@ -50,15 +64,71 @@ mod use_key;
 
 pub use {
     actions::PanicTo, actions::PingPongDirection, actions::PlayerAction,
-    actions::PlayerActionAutoMob, actions::PlayerActionFamiliarsSwapping, actions::PlayerActionKey,
-    actions::PlayerActionMove, actions::PlayerActionPanic, actions::PlayerActionPingPong,
-    double_jump::DOUBLE_JUMP_THRESHOLD, grapple::GRAPPLING_MAX_THRESHOLD,
-    grapple::GRAPPLING_THRESHOLD, panic::Panicking, state::PlayerState, state::Quadrant,
+    actions::PlayerActionAutoMob, actions::PlayerActionCombo,
+    actions::PlayerActionFamiliarsSwapping, actions::PlayerActionKey, actions::PlayerActionMouse,
+    actions::PlayerActionMove, actions::PlayerActionPanic, actions::PlayerActionPingPong, actions::PlayerActionSellItems,
+    combo::COMBO_MAX_STEPS, double_jump::DOUBLE_JUMP_THRESHOLD, fly::FLYING_MAX_THRESHOLD,
+    grapple::GRAPPLING_MAX_THRESHOLD, grapple::GRAPPLING_THRESHOLD, panic::Panicking,
+    sell_items::SELL_ITEMS_MAX_SLOTS, sell_items::SELL_ITEMS_MAX_TABS, state::MAX_POTIONS,
+    state::PlayerConfiguration, state::PlayerPotion, state::PlayerState, state::Quadrant,
 };
 
 /// Minimum y distance from the destination required to perform a jump.
 pub const JUMP_THRESHOLD: i32 = 7;
 
+/// The number of ticks added to the measured input latency for the move timeout.
+const INPUT_LATENCY_MOVE_TIMEOUT_EXTRA: u32 = 2;
+
+/// The movement thresholds after applying [`MovementOverrides`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MovementThresholds {
+    pub double_jump: i32,
+    pub jump: i32,
+    pub grappling: i32,
+    pub grappling_max: i32,
+    pub move_timeout: u32,
+}
+
+impl Default for MovementThresholds {
+    fn default() -> Self {
+        Self::from(MovementOverrides::default())
+    }
+}
+
+impl MovementThresholds {
+    /// Creates from `overrides` with the built-in move timeout extended to cover the measured
+    /// input `latency_ticks` of the current input method.
+    ///
+    /// An overridden move timeout is used as is.
+    pub fn with_input_latency(overrides: MovementOverrides, latency_ticks: Option<u32>) -> Self {
+        let mut thresholds = Self::from(overrides);
+        if overrides.move_timeout.is_none()
+            && let Some(ticks) = latency_ticks
+        {
+            thresholds.move_timeout = thresholds
+                .move_timeout
+                .max(ticks + INPUT_LATENCY_MOVE_TIMEOUT_EXTRA);
+        }
+        thresholds
+    }
+}
+
+impl From<MovementOverrides> for MovementThresholds {
+    fn from(overrides: MovementOverrides) -> Self {
+        Self {
+            double_jump: overrides
+                .double_jump_threshold
+                .unwrap_or(DOUBLE_JUMP_THRESHOLD),
+            jump: overrides.jump_threshold.unwrap_or(JUMP_THRESHOLD),
+            grappling: overrides.grappling_threshold.unwrap_or(GRAPPLING_THRESHOLD),
+            grappling_max: overrides
+                .grappling_max_threshold
+                .unwrap_or(GRAPPLING_MAX_THRESHOLD),
+            move_timeout: overrides.move_timeout.unwrap_or(MOVE_TIMEOUT).max(1),
+        }
+    }
+}
+
 /// The player contextual states.
 #[derive(Clone, Copy, Debug, Display)]
 #[allow(clippy::large_enum_variant)] // There is only ever a single instance of Player
@@ -79,6 +149,10 @@ pub enum Player {
     DoubleJumping(DoubleJumping),
     /// Performs a grappling action.
     Grappling(Moving),
+    /// Performs a flying action with altitude controlled by Up or Down.
+    Flying(Moving),
+    /// Climbs a rope or ladder with Up or Down.
+    Climbing(Moving),
     /// Performs a normal jump.
     Jumping(Moving),
     /// Performs an up jump action.
@@ -89,6 +163,11 @@ pub enum Player {
         anchor: Point,
         timeout_on_complete: bool,
     },
+    /// Jumps down through the current platform with Down and jump key in platforms pathing.
+    JumpingDown {
+        moving: Moving,
+        anchor: Point,
+    },
     /// Unstucks when inside non-detecting position or because of [`PlayerState::unstuck_counter`].
     Unstucking(Timeout, Option<bool>, bool),
     /// Stalls for time and return to [`Player::Idle`] or [`PlayerState::stalling_timeout_state`].
@@ -100,6 +179,13 @@ pub enum Player {
     #[strum(to_string = "FamiliarsSwapping({0})")]
     FamiliarsSwapping(FamiliarsSwapping),
     Panicking(Panicking),
+    /// Opens the NPC shop and sells items.
+    #[strum(to_string = "SellingItems({0})")]
+    SellingItems(SellingItems),
+    /// Sends the steps of a combo in order.
+    Comboing(Comboing),
+    /// Sends a mouse action at a point.
+    UsingMouse(UsingMouse),
 }
 
 impl Player {
@@ -127,13 +213,16 @@ impl Player {
                 distance >= OVERRIDABLE_DISTANCE
             }
             Player::Grappling(moving)
+            | Player::Flying(moving)
+            | Player::Climbing(moving)
             | Player::Jumping(moving)
             | Player::UpJumping(UpJumping { moving, .. })
             | Player::Falling {
                 moving,
                 anchor: _,
                 timeout_on_complete: _,
-            } => moving.completed,
+            }
+            | Player::JumpingDown { moving, .. } => moving.completed,
             Player::SolvingRune(_)
             | Player::CashShopThenExit(_, _)
             | Player::Unstucking(_, _, _)
@@ -141,9 +230,32 @@ impl Player {
             | Player::UseKey(_)
             | Player::FamiliarsSwapping(_)
             | Player::Panicking(_)
+            | Player::SellingItems(_)
+            | Player::Comboing(_)
+            | Player::UsingMouse(_)
             | Player::Stalling(_, _) => false,
         }
     }
+
+    /// Whether an action can interrupt the current state even when
+    /// [`Self::can_action_override_current_state`] does not allow it.
+    ///
+    /// States that are sending keys or interacting with the game UI are never interrupted.
+    #[inline]
+    pub fn can_action_preempt_current_state(&self) -> bool {
+        !matches!(
+            self,
+            Player::UseKey(_)
+                | Player::Comboing(_)
+                | Player::UsingMouse(_)
+                | Player::Unstucking(_, _, _)
+                | Player::SolvingRune(_)
+                | Player::CashShopThenExit(_, _)
+                | Player::FamiliarsSwapping(_)
+                | Player::Panicking(_)
+                | Player::SellingItems(_)
+        )
+    }
 }
 
 impl Contextual for Player {
@@ -256,19 +368,29 @@ fn update_non_positional_context(
             failed_to_detect_player,
         )),
         Player::Panicking(panicking) => Some(update_panicking_context(context, state, panicking)),
+        Player::SellingItems(selling) => {
+            Some(update_selling_items_context(context, state, selling))
+        }
+        Player::Comboing(comboing) => {
+            (!failed_to_detect_player).then(|| update_comboing_context(context, state, comboing))
+        }
+        Player::UsingMouse(using) => Some(update_using_mouse_context(context, state, using)),
         Player::Detecting
         | Player::Idle
         | Player::Moving(_, _, _)
         | Player::Adjusting(_)
         | Player::DoubleJumping(_)
         | Player::Grappling(_)
+        | Player::Flying(_)
+        | Player::Climbing(_)
         | Player::Jumping(_)
         | Player::UpJumping(_)
         | Player::Falling {
             moving: _,
             anchor: _,
             timeout_on_complete: _,
-        } => None,
+        }
+        | Player::JumpingDown { .. } => None,
     }
 }
 
@@ -290,6 +412,8 @@ fn update_positional_context(
             update_double_jumping_context(context, state, double_jumping)
         }
         Player::Grappling(moving) => update_grappling_context(context, state, moving),
+        Player::Flying(moving) => update_flying_context(context, state, moving),
+        Player::Climbing(moving) => update_climbing_context(context, state, moving),
         Player::UpJumping(moving) => update_up_jumping_context(context, state, moving),
         Player::Jumping(moving) => update_jumping_context(context, state, moving),
         Player::Falling {
@@ -297,12 +421,18 @@ fn update_positional_context(
             anchor,
             timeout_on_complete,
         } => update_falling_context(context, state, moving, anchor, timeout_on_complete),
+        Player::JumpingDown { moving, anchor } => {
+            update_jumping_down_context(context, state, moving, anchor)
+        }
         Player::UseKey(_)
         | Player::Unstucking(_, _, _)
         | Player::Stalling(_, _)
         | Player::SolvingRune(_)
         | Player::FamiliarsSwapping(_)
         | Player::Panicking(_)
+        | Player::SellingItems(_)
+        | Player::Comboing(_)
+        | Player::UsingMouse(_)
         | Player::CashShopThenExit(_, _) => unreachable!(),
     }
 }
@@ -0,0 +1,183 @@
+use log::debug;
+
+use super::{
+    Player, PlayerState,
+    actions::{PlayerActionMouse, on_action},
+};
+use crate::{
+    bridge::MouseAction,
+    context::Context,
+    database::{ActionMouseKind, ActionMouseRelativeTo},
+    minimap::Minimap,
+};
+
+/// Struct for storing mouse using data.
+#[derive(Debug, Clone, Copy)]
+pub struct UsingMouse {
+    /// The client point to send the mouse to or [`None`] if it cannot be resolved.
+    point: Option<(i32, i32)>,
+    action: MouseAction,
+    /// The remaining number of mouse actions to send.
+    remaining_count: u32,
+    /// The remaining ticks to wait after all mouse actions are sent.
+    wait_after_ticks: u32,
+}
+
+impl UsingMouse {
+    /// Creates the mouse using data with the point of `action` resolved to client coordinate.
+    pub fn new(context: &Context, action: PlayerActionMouse) -> Self {
+        let point = match action.relative_to {
+            ActionMouseRelativeTo::Window => Some((action.x, action.y)),
+            ActionMouseRelativeTo::Minimap => match context.minimap {
+                Minimap::Idle(idle) => {
+                    let (offset_x, offset_y) = context.frame_to_client_offset;
+                    Some((
+                        idle.bbox.x + action.x + offset_x,
+                        idle.bbox.y + action.y + offset_y,
+                    ))
+                }
+                Minimap::Detecting => {
+                    debug!(target: "player", "skipping mouse action relative to undetected minimap");
+                    None
+                }
+            },
+        };
+        let (action_kind, count) = match action.action {
+            ActionMouseKind::Click => (MouseAction::Click, 1),
+            ActionMouseKind::DoubleClick => (MouseAction::Click, 2),
+            ActionMouseKind::Scroll => (MouseAction::Scroll, 1),
+        };
+
+        Self {
+            point,
+            action: action_kind,
+            remaining_count: if point.is_some() { count } else { 0 },
+            wait_after_ticks: action.wait_after_use_ticks,
+        }
+    }
+
+    #[inline]
+    fn is_completed(&self) -> bool {
+        self.remaining_count == 0 && self.wait_after_ticks == 0
+    }
+}
+
+/// Updates [`Player::UsingMouse`] contextual state.
+///
+/// Sends at most one mouse action per tick so that the two clicks of a double click are apart
+/// by one tick.
+pub fn update_using_mouse_context(
+    context: &Context,
+    state: &mut PlayerState,
+    mut using: UsingMouse,
+) -> Player {
+    if using.remaining_count > 0 {
+        let (x, y) = using.point.expect("resolved if has remaining count");
+        let _ = context.keys.send_mouse(x, y, using.action);
+        using.remaining_count -= 1;
+    } else if using.wait_after_ticks > 0 {
+        using.wait_after_ticks -= 1;
+    }
+    let next = if using.is_completed() {
+        Player::Idle
+    } else {
+        Player::UsingMouse(using)
+    };
+
+    on_action(
+        state,
+        |_| Some((next, matches!(next, Player::Idle))),
+        || Player::Idle, // Force cancel if it is not initiated from an action
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use opencv::core::Rect;
+
+    use super::{UsingMouse, update_using_mouse_context};
+    use crate::{
+        bridge::{MockKeySender, MouseAction},
+        context::Context,
+        database::{ActionMouseKind, ActionMouseRelativeTo},
+        minimap::{Minimap, MinimapIdle},
+        player::{Player, PlayerAction, PlayerState, actions::PlayerActionMouse},
+    };
+
+    #[test]
+    fn update_using_mouse_context_double_click_then_wait() {
+        let mut keys = MockKeySender::new();
+        keys.expect_send_mouse()
+            .times(2)
+            .withf(|x, y, action| *x == 10 && *y == 20 && matches!(action, MouseAction::Click))
+            .returning(|_, _, _| Ok(()));
+        let context = Context::new(Some(keys), None);
+        let mut state = PlayerState::default();
+        let action = PlayerActionMouse {
+            x: 10,
+            y: 20,
+            action: ActionMouseKind::DoubleClick,
+            relative_to: ActionMouseRelativeTo::Window,
+            wait_after_use_ticks: 1,
+        };
+        state.set_normal_action(0, PlayerAction::Mouse(action));
+
+        let mut player = Player::UsingMouse(UsingMouse::new(&context, action));
+        // Clicks twice then waits 1 tick
+        for _ in 0..2 {
+            player = update_using_mouse_context(&context, &mut state, using_mouse(player));
+            assert_matches!(player, Player::UsingMouse(_));
+        }
+        player = update_using_mouse_context(&context, &mut state, using_mouse(player));
+        assert_matches!(player, Player::Idle);
+        assert!(!state.has_normal_action());
+    }
+
+    #[test]
+    fn using_mouse_new_minimap_relative_to_client() {
+        let mut context = Context::new(None, None);
+        context.minimap = Minimap::Idle(MinimapIdle {
+            bbox: Rect::new(5, 10, 100, 100),
+            ..MinimapIdle::default()
+        });
+        context.frame_to_client_offset = (100, 200);
+        let action = PlayerActionMouse {
+            x: 10,
+            y: 20,
+            action: ActionMouseKind::Click,
+            relative_to: ActionMouseRelativeTo::Minimap,
+            wait_after_use_ticks: 0,
+        };
+
+        let using = UsingMouse::new(&context, action);
+
+        assert_eq!(using.point, Some((115, 230)));
+    }
+
+    #[test]
+    fn update_using_mouse_context_skips_undetected_minimap() {
+        let context = Context::new(None, None);
+        let mut state = PlayerState::default();
+        let action = PlayerActionMouse {
+            x: 10,
+            y: 20,
+            action: ActionMouseKind::Click,
+            relative_to: ActionMouseRelativeTo::Minimap,
+            wait_after_use_ticks: 0,
+        };
+        state.set_normal_action(0, PlayerAction::Mouse(action));
+
+        let player = Player::UsingMouse(UsingMouse::new(&context, action));
+        let player = update_using_mouse_context(&context, &mut state, using_mouse(player));
+        assert_matches!(player, Player::Idle);
+    }
+
+    fn using_mouse(player: Player) -> UsingMouse {
+        match player {
+            Player::UsingMouse(using) => using,
+            _ => unreachable!(),
+        }
+    }
+}
@@ -1,16 +1,16 @@
-use std::ops::Range;
-
 use log::{debug, info};
 use opencv::core::Point;
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
 use super::{
-    GRAPPLING_MAX_THRESHOLD, JUMP_THRESHOLD, Player, PlayerState,
+    MovementThresholds, Player, PlayerState,
     actions::{PlayerAction, PlayerActionKey, PlayerActionMove},
-    double_jump::{DOUBLE_JUMP_THRESHOLD, DoubleJumping},
+    double_jump::DoubleJumping,
     state::LastMovement,
     timeout::Timeout,
     up_jump::UpJumping,
@@ -19,11 +19,15 @@ use crate::{
     ActionKeyDirection, ActionKeyWith, MAX_PLATFORMS_COUNT,
     array::Array,
     context::Context,
-    pathing::{MovementHint, PlatformWithNeighbors, find_points_with},
+    minimap::Minimap,
+    pathing::{
+        FindPointsArgs, MovementHint, PlatformMotion, PlatformWithNeighbors, find_points_with,
+    },
     player::{
         adjust::{ADJUSTING_MEDIUM_THRESHOLD, ADJUSTING_SHORT_THRESHOLD, Adjusting},
-        grapple::GRAPPLING_THRESHOLD,
+        fly::{FLYING_MAX_THRESHOLD, FLYING_THRESHOLD},
         on_action,
+        sell_items::SellingItems,
         solve_rune::SolvingRune,
         use_key::UseKey,
     },
@@ -32,10 +36,15 @@ use crate::{
 /// Maximum amount of ticks a change in x or y direction must be detected.
 pub const MOVE_TIMEOUT: u32 = 5;
 
-/// Jumpable y distances.
-const JUMPABLE_RANGE: Range<i32> = 4..JUMP_THRESHOLD;
+/// Minimum jumpable y distance.
+///
+/// The maximum is [`MovementThresholds::jump`].
+const JUMPABLE_MIN_THRESHOLD: i32 = 4;
 const UP_JUMP_THRESHOLD: i32 = 10;
 
+/// Number of ticks ahead to predict where a conveyor platform carries the player to.
+const CONVEYOR_LOOKAHEAD_TICKS: u32 = 5;
+
 /// Intermediate points to move by.
 ///
 /// The last point is the destination.
@@ -242,10 +251,10 @@ impl Moving {
             matches!(state.last_movement, Some(LastMovement::Falling)) && y_direction >= 0;
         let did_up_jump =
             matches!(state.last_movement, Some(LastMovement::UpJumping)) && y_direction <= 0;
-        let y_within_jump = y_distance < JUMP_THRESHOLD;
+        let y_within_jump = y_distance < state.config.movement.jump;
 
         let can_skip_y = did_fall_down || did_up_jump || y_within_jump;
-        let can_skip_x = x_distance < DOUBLE_JUMP_THRESHOLD;
+        let can_skip_x = x_distance < state.config.movement.double_jump;
 
         can_skip_x && can_skip_y
     }
@@ -258,11 +267,17 @@ impl Moving {
 /// state looping and advancing `intermediates` when the current destination is reached.
 ///
 /// It will first transition to [`Player::DoubleJumping`] and [`Player::Adjusting`] for
-/// matching `x` of `dest`. Then, [`Player::Grappling`], [`Player::UpJumping`], [`Player::Jumping`]
-/// or [`Player::Falling`] for matching `y` of `dest`. (e.g. horizontal then vertical)
+/// matching `x` of `dest`. Then, [`Player::Flying`], [`Player::Grappling`], [`Player::UpJumping`],
+/// [`Player::Jumping`] or [`Player::Falling`] for matching `y` of `dest`. (e.g. horizontal then
+/// vertical)
 ///
 /// In auto mob or intermediate destination, most of the movement thresholds are relaxed for
 /// more fluid movement.
+///
+/// When the player is standing on a moving platform and the platform is carrying the player
+/// toward a destination on another platform, it waits for the platform instead of moving. When
+/// standing on a conveyor platform, the x distance is measured from where the conveyor will
+/// carry the player to.
 pub fn update_moving_context(
     context: &Context,
     state: &mut PlayerState,
@@ -284,9 +299,25 @@ pub fn update_moving_context(
     let is_intermediate = moving.is_destination_intermediate();
     let skip_destination = moving.auto_mob_can_skip_current_destination(state);
 
-    let (x_distance, _) = moving.x_distance_direction_from(true, cur_pos);
+    let standing_motion = standing_platform_motion(context, cur_pos);
+    let x_pos = match standing_motion {
+        Some(motion @ PlatformMotion::Conveyor { .. }) => Point::new(
+            cur_pos.x + motion.carried_x(CONVEYOR_LOOKAHEAD_TICKS),
+            cur_pos.y,
+        ),
+        _ => cur_pos,
+    };
+    let (x_distance, _) = moving.x_distance_direction_from(true, x_pos);
     let (y_distance, y_direction) = moving.y_distance_direction_from(true, cur_pos);
 
+    if matches!(standing_motion, Some(PlatformMotion::Moving { .. }))
+        && dest.y != cur_pos.y
+        && x_distance >= ADJUSTING_SHORT_THRESHOLD
+        && is_carried_toward(state.last_carried_x_delta(), cur_pos.x, dest.x)
+    {
+        return Player::Moving(dest, exact, intermediates);
+    }
+
     let disable_adjusting = state.config.disable_adjusting;
 
     // Check to double jump
@@ -294,7 +325,12 @@ pub fn update_moving_context(
         let require_stationary = state.has_ping_pong_action_only()
             && !matches!(
                 state.last_movement,
-                Some(LastMovement::Grappling | LastMovement::UpJumping)
+                Some(
+                    LastMovement::Grappling
+                        | LastMovement::Flying
+                        | LastMovement::Climbing
+                        | LastMovement::UpJumping
+                )
             );
         return abort_action_on_state_repeat(
             Player::DoubleJumping(DoubleJumping::new(moving, false, require_stationary)),
@@ -315,10 +351,19 @@ pub fn update_moving_context(
         );
     }
 
+    // Check to fly
+    if !skip_destination
+        && y_direction > 0
+        && y_distance >= FLYING_THRESHOLD
+        && !state.should_disable_flying()
+    {
+        return abort_action_on_state_repeat(Player::Flying(moving), context, state);
+    }
+
     // Check to grapple
     if !skip_destination
         && y_direction > 0
-        && y_distance >= GRAPPLING_THRESHOLD
+        && y_distance >= state.config.movement.grappling
         && !state.should_disable_grappling()
     {
         return abort_action_on_state_repeat(Player::Grappling(moving), context, state);
@@ -332,7 +377,7 @@ pub fn update_moving_context(
             && state.config.auto_mob_platforms_pathing
             && state.config.auto_mob_platforms_pathing_up_jump_only
             && intermediates.is_none()
-            && y_distance >= GRAPPLING_THRESHOLD
+            && y_distance >= state.config.movement.grappling
         {
             debug!(target: "player", "auto mob aborted because distance for up jump only is too big");
             state.clear_action_completed();
@@ -347,7 +392,10 @@ pub fn update_moving_context(
     }
 
     // Check to jump
-    if !skip_destination && y_direction > 0 && JUMPABLE_RANGE.contains(&y_distance) {
+    if !skip_destination
+        && y_direction > 0
+        && (JUMPABLE_MIN_THRESHOLD..state.config.movement.jump).contains(&y_distance)
+    {
         return abort_action_on_state_repeat(Player::Jumping(moving), context, state);
     }
 
@@ -392,6 +440,17 @@ pub fn update_moving_context(
             return Player::Stalling(Timeout::default(), 3);
         }
 
+        if matches!(moving.intermediate_hint(), Some(MovementHint::Climb)) {
+            return Player::Climbing(Moving::new(cur_pos, dest, exact, Some(intermediates)));
+        }
+
+        if matches!(moving.intermediate_hint(), Some(MovementHint::JumpDown)) {
+            return Player::JumpingDown {
+                moving: Moving::new(cur_pos, dest, exact, Some(intermediates)),
+                anchor: cur_pos,
+            };
+        }
+
         return Player::Moving(dest, exact, Some(intermediates));
     }
 
@@ -415,12 +474,30 @@ fn abort_action_on_state_repeat(
     if state.track_last_movement_repeated() {
         info!(target: "player", "abort action due to repeated state");
         state.auto_mob_track_ignore_xs(context, true);
-        state.clear_action_completed();
+        state.clear_action_failed();
         return Player::Idle;
     }
     next
 }
 
+/// Finds the motion of the platform the player at `pos` is standing on.
+#[inline]
+fn standing_platform_motion(context: &Context, pos: Point) -> Option<PlatformMotion> {
+    let Minimap::Idle(idle) = context.minimap else {
+        return None;
+    };
+    idle.platforms
+        .iter()
+        .find(|platform| platform.y() == pos.y && platform.swept_xs().contains(&pos.x))
+        .and_then(|platform| platform.motion())
+}
+
+/// Whether the player moving by `x_delta` x-wise is being carried from `x` toward `dest_x`.
+#[inline]
+fn is_carried_toward(x_delta: i32, x: i32, dest_x: i32) -> bool {
+    x_delta != 0 && x_delta.signum() == (dest_x - x).signum()
+}
+
 fn on_player_action(
     last_known_direction: ActionKeyDirection,
     action: PlayerAction,
@@ -464,7 +541,15 @@ fn on_player_action(
         )),
         PlayerAction::SolveRune => Some((Player::SolvingRune(SolvingRune::default()), false)),
         PlayerAction::PingPong(_) => Some((Player::Idle, true)),
-        PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
+        PlayerAction::SellItems(selling) => {
+            Some((Player::SellingItems(SellingItems::new(selling)), false))
+        }
+        PlayerAction::Panic(_)
+        | PlayerAction::FamiliarsSwapping(_)
+        | PlayerAction::Combo(_)
+        | PlayerAction::Mouse(_) => {
+            unreachable!()
+        }
     }
 }
 
@@ -475,28 +560,37 @@ pub fn find_intermediate_points(
     dest: Point,
     exact: bool,
     up_jump_only: bool,
+    flying: bool,
     enable_hint: bool,
+    jump_down: bool,
+    thresholds: MovementThresholds,
 ) -> Option<MovingIntermediates> {
     let vertical_threshold = if up_jump_only {
-        GRAPPLING_THRESHOLD
+        thresholds.grappling
+    } else if flying {
+        FLYING_MAX_THRESHOLD
     } else {
-        GRAPPLING_MAX_THRESHOLD
+        thresholds.grappling_max
     };
-    let vec = find_points_with(
-        platforms,
-        cur_pos,
-        dest,
+    let args = FindPointsArgs {
         enable_hint,
-        DOUBLE_JUMP_THRESHOLD,
-        JUMP_THRESHOLD,
+        double_jump_threshold: thresholds.double_jump,
+        jump_threshold: thresholds.jump,
         vertical_threshold,
-    )?;
+        flying: flying && !up_jump_only,
+        jump_down,
+    };
+    let vec = find_points_with(platforms, cur_pos, dest, args)?;
     let len = vec.len();
-    let array = Array::from_iter(
-        vec.into_iter()
-            .enumerate()
-            .map(|(i, (point, hint))| (point, hint, if i == len - 1 { exact } else { false })),
-    );
+    let array = Array::from_iter(vec.into_iter().enumerate().map(|(i, (point, hint))| {
+        // Climbing requires the player to be right at the rope
+        let exact = if i == len - 1 {
+            exact
+        } else {
+            matches!(hint, MovementHint::Climb)
+        };
+        (point, hint, exact)
+    }));
     Some(MovingIntermediates {
         current: 0,
         inner: array,
@@ -510,7 +604,12 @@ mod tests {
     use opencv::core::Point;
 
     use super::*;
-    use crate::player::Player;
+    use crate::{
+        database::MovementOverrides,
+        minimap::MinimapIdle,
+        pathing::{Platform, find_neighbors},
+        player::{GRAPPLING_THRESHOLD, Player},
+    };
 
     #[test]
     fn update_moving_to_double_jump() {
@@ -536,6 +635,27 @@ mod tests {
         assert_matches!(player, Player::Adjusting(_));
     }
 
+    #[test]
+    fn update_moving_on_conveyor_measures_from_carried_position() {
+        let platform = Platform::new(0..100, 0).with_motion(PlatformMotion::Conveyor {
+            period: CONVEYOR_LOOKAHEAD_TICKS,
+            range: 20,
+        });
+        let mut context = Context::new(None, None);
+        context.minimap = Minimap::Idle(MinimapIdle {
+            platforms: Array::from_iter(find_neighbors(&[platform], 25, 7, 41)),
+            ..MinimapIdle::default()
+        });
+        let mut state = PlayerState::default();
+        state.last_known_pos = Some(Point::new(0, 0));
+
+        // Would be adjusting without the conveyor carrying the player to the destination
+        let dest = Point::new(20, 0);
+        let player = update_moving_context(&context, &mut state, dest, false, None);
+
+        assert_matches!(player, Player::Idle);
+    }
+
     #[test]
     fn update_moving_to_grappling() {
         let context = Context::new(None, None);
@@ -549,6 +669,24 @@ mod tests {
         assert_matches!(player, Player::Grappling(_));
     }
 
+    #[test]
+    fn update_moving_to_upjump_with_grappling_override() {
+        let context = Context::new(None, None);
+        let mut state = PlayerState::default();
+        state.config.grappling_key = Some(KeyKind::default());
+        state.config.movement = MovementOverrides {
+            grappling_threshold: Some(GRAPPLING_THRESHOLD + 20),
+            ..MovementOverrides::default()
+        }
+        .into();
+        state.last_known_pos = Some(Point::new(0, 0));
+
+        let dest = Point::new(0, GRAPPLING_THRESHOLD + 10);
+        let player = update_moving_context(&context, &mut state, dest, true, None);
+
+        assert_matches!(player, Player::UpJumping(_));
+    }
+
     #[test]
     fn update_moving_to_upjump() {
         let context = Context::new(None, None);
@@ -1,5 +1,7 @@
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
@@ -23,6 +25,8 @@ enum PanickingStage {
     ChangingChannel(Timeout, u32),
     /// Going to town.
     GoingToTown(Timeout, u32),
+    /// Exiting to the character selection lobby.
+    ExitingToLobby(Timeout, u32),
     Completing(Timeout, bool),
 }
 
@@ -30,6 +34,8 @@ enum PanickingStage {
 pub struct Panicking {
     stage: PanickingStage,
     pub to: PanicTo,
+    /// Whether this panic continues even if it is not initiated from an action.
+    standalone: bool,
 }
 
 impl Panicking {
@@ -38,8 +44,18 @@ impl Panicking {
             stage: match to {
                 PanicTo::Channel => PanickingStage::ChangingChannel(Timeout::default(), 0),
                 PanicTo::Town => PanickingStage::GoingToTown(Timeout::default(), 0),
+                PanicTo::Lobby => PanickingStage::ExitingToLobby(Timeout::default(), 0),
             },
             to,
+            standalone: false,
+        }
+    }
+
+    /// Creates a panic that runs to completion without being initiated from an action.
+    pub fn new_standalone(to: PanicTo) -> Self {
+        Self {
+            standalone: true,
+            ..Self::new(to)
         }
     }
 
@@ -59,6 +75,14 @@ impl Panicking {
         }
     }
 
+    #[inline]
+    fn stage_exiting_to_lobby(self, timeout: Timeout, retry_count: u32) -> Panicking {
+        Panicking {
+            stage: PanickingStage::ExitingToLobby(timeout, retry_count),
+            ..self
+        }
+    }
+
     #[inline]
     fn stage_completing(self, timeout: Timeout, completed: bool) -> Panicking {
         Panicking {
@@ -89,6 +113,9 @@ pub fn update_panicking_context(
             timeout,
             retry_count,
         ),
+        PanickingStage::ExitingToLobby(timeout, retry_count) => {
+            update_exiting_to_lobby(context, panicking, timeout, retry_count)
+        }
         PanickingStage::Completing(timeout, completed) => {
             update_completing(context, panicking, timeout, completed)
         }
@@ -103,8 +130,8 @@ pub fn update_panicking_context(
         state,
         |_| Some((next, matches!(next, Player::Idle))),
         || {
-            if matches!(panicking.to, PanicTo::Town) {
-                // Allow continuing for town even if the bot has already halted
+            if panicking.standalone || matches!(panicking.to, PanicTo::Town | PanicTo::Lobby) {
+                // Allow continuing for town and lobby even if the bot has already halted
                 next
             } else {
                 // Force cancel if it is not initiated from an action for other panic kind
@@ -218,13 +245,61 @@ fn update_going_to_town(
     }
 }
 
+/// Exits to the lobby through the game menu.
+///
+/// There is no detection of the game menu so this relies on the exit entry being the last entry
+/// of the menu opened by `Esc`.
+fn update_exiting_to_lobby(
+    context: &Context,
+    panicking: Panicking,
+    timeout: Timeout,
+    retry_count: u32,
+) -> Panicking {
+    const PRESS_UP_AT: u32 = 20;
+    const PRESS_ENTER_AT: u32 = 35;
+
+    match next_timeout_lifecycle(timeout, 90) {
+        Lifecycle::Started(timeout) => {
+            let _ = context.keys.send(KeyKind::Esc);
+            panicking.stage_exiting_to_lobby(timeout, retry_count)
+        }
+        Lifecycle::Ended => {
+            let has_confirm_button = context
+                .detector_unwrap()
+                .detect_esc_confirm_button()
+                .is_ok();
+            if has_confirm_button {
+                let _ = context.keys.send(KeyKind::Enter);
+            }
+
+            if !has_confirm_button && retry_count + 1 < MAX_RETRY {
+                panicking.stage_exiting_to_lobby(Timeout::default(), retry_count + 1)
+            } else {
+                panicking.stage_completing(Timeout::default(), true)
+            }
+        }
+        Lifecycle::Updated(timeout) => {
+            match timeout.current {
+                PRESS_UP_AT => {
+                    let _ = context.keys.send(KeyKind::Up);
+                }
+                PRESS_ENTER_AT => {
+                    let _ = context.keys.send(KeyKind::Enter);
+                }
+                _ => (),
+            }
+            panicking.stage_exiting_to_lobby(timeout, retry_count)
+        }
+    }
+}
+
 fn update_completing(
     context: &Context,
     panicking: Panicking,
     timeout: Timeout,
     completed: bool,
 ) -> Panicking {
-    if matches!(panicking.to, PanicTo::Town) {
+    if matches!(panicking.to, PanicTo::Town | PanicTo::Lobby) {
         return panicking.stage_completing(timeout, true);
     }
 
@@ -417,6 +492,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_exiting_to_lobby_started_send_esc() {
+        let mut keys = MockKeySender::default();
+        keys.expect_send()
+            .once()
+            .with(eq(KeyKind::Esc))
+            .returning(|_| Ok(()));
+        let context = Context::new(Some(keys), None);
+        let panicking = Panicking::new(PanicTo::Lobby);
+
+        let result = update_exiting_to_lobby(&context, panicking, Timeout::default(), 0);
+        assert_matches!(result.stage, PanickingStage::ExitingToLobby(_, 0));
+    }
+
     #[test]
     fn update_completing_for_town_immediately_complete() {
         let context = Context::new(None, None);
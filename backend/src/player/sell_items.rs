@@ -0,0 +1,310 @@
+use std::fmt::Display;
+
+use opencv::core::Point;
+#[cfg(windows)]
+use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
+#[cfg(target_os = "macos")]
+use platforms::macos::KeyKind;
+
+use super::{
+    Player, PlayerState,
+    actions::{PlayerActionSellItems, on_action},
+    timeout::{Lifecycle, Timeout, next_timeout_lifecycle},
+};
+use crate::{array::Array, bridge::MouseAction, context::Context};
+
+/// Maximum number of inventory tabs to sell from.
+pub const SELL_ITEMS_MAX_TABS: usize = 5;
+
+/// Maximum number of slots to sell per inventory tab.
+pub const SELL_ITEMS_MAX_SLOTS: usize = 32;
+
+/// Ticks to wait for the shop to open after pressing the interact key.
+const OPENING_SHOP_TIMEOUT: u32 = 30;
+
+/// Ticks to wait after clicking an inventory tab.
+const SELECTING_TAB_TIMEOUT: u32 = 10;
+
+/// Ticks to wait after double clicking a slot.
+const SELLING_TIMEOUT: u32 = 15;
+
+/// The tick in [`SellingItemsStage::Selling`] to confirm the sell quantity popup.
+const SELLING_CONFIRM_AT: u32 = 6;
+
+/// Ticks to wait after closing the shop.
+const CLOSING_SHOP_TIMEOUT: u32 = 15;
+
+/// Internal state machine representing the current stage of selling items.
+#[derive(Debug, Clone, Copy)]
+enum SellingItemsStage {
+    /// Opening the shop by interacting with the NPC.
+    OpeningShop(Timeout),
+    /// Clicking on an inventory tab in the shop.
+    SelectingTab(Timeout, usize),
+    /// Selling the item in a slot of the current tab.
+    Selling(Timeout, usize, usize),
+    /// Closing the shop.
+    ClosingShop(Timeout),
+    Completing,
+}
+
+/// Struct for storing selling items data.
+#[derive(Debug, Clone, Copy)]
+pub struct SellingItems {
+    /// Current stage of the selling items state machine.
+    stage: SellingItemsStage,
+    /// Client coordinates of the inventory tabs to sell from.
+    tabs: Array<Point, SELL_ITEMS_MAX_TABS>,
+    /// Client coordinates of the slots to sell in each tab.
+    slots: Array<Point, SELL_ITEMS_MAX_SLOTS>,
+}
+
+impl Display for SellingItems {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.stage {
+            SellingItemsStage::OpeningShop(_) => write!(f, "Opening Shop"),
+            SellingItemsStage::SelectingTab(_, _) => write!(f, "Selecting Tab"),
+            SellingItemsStage::Selling(_, _, _) => write!(f, "Selling"),
+            SellingItemsStage::ClosingShop(_) => write!(f, "Closing Shop"),
+            SellingItemsStage::Completing => write!(f, "Completing"),
+        }
+    }
+}
+
+impl SellingItems {
+    pub fn new(action: PlayerActionSellItems) -> Self {
+        Self {
+            stage: SellingItemsStage::OpeningShop(Timeout::default()),
+            tabs: action.tabs,
+            slots: action.slots,
+        }
+    }
+
+    #[inline]
+    fn stage(self, stage: SellingItemsStage) -> SellingItems {
+        SellingItems { stage, ..self }
+    }
+
+    #[inline]
+    fn stage_selecting_tab(self, timeout: Timeout, tab_index: usize) -> SellingItems {
+        self.stage(SellingItemsStage::SelectingTab(timeout, tab_index))
+    }
+
+    #[inline]
+    fn stage_selling(self, timeout: Timeout, tab_index: usize, slot_index: usize) -> SellingItems {
+        self.stage(SellingItemsStage::Selling(timeout, tab_index, slot_index))
+    }
+
+    #[inline]
+    fn stage_closing_shop(self, timeout: Timeout) -> SellingItems {
+        self.stage(SellingItemsStage::ClosingShop(timeout))
+    }
+}
+
+/// Updates [`Player::SellingItems`] contextual state.
+///
+/// The player is expected to already be next to the shop NPC. There is no detection of the shop
+/// UI so each stage simply waits for a fixed number of ticks.
+pub fn update_selling_items_context(
+    context: &Context,
+    state: &mut PlayerState,
+    selling: SellingItems,
+) -> Player {
+    let selling = match selling.stage {
+        SellingItemsStage::OpeningShop(timeout) => {
+            update_opening_shop(context, state.config.interact_key, selling, timeout)
+        }
+        SellingItemsStage::SelectingTab(timeout, tab_index) => {
+            update_selecting_tab(context, selling, timeout, tab_index)
+        }
+        SellingItemsStage::Selling(timeout, tab_index, slot_index) => {
+            update_selling(context, selling, timeout, tab_index, slot_index)
+        }
+        SellingItemsStage::ClosingShop(timeout) => update_closing_shop(context, selling, timeout),
+        SellingItemsStage::Completing => selling,
+    };
+    let next = if matches!(selling.stage, SellingItemsStage::Completing) {
+        Player::Idle
+    } else {
+        Player::SellingItems(selling)
+    };
+
+    on_action(
+        state,
+        |_| Some((next, matches!(next, Player::Idle))),
+        || Player::Idle, // Force cancel if it is not initiated from an action
+    )
+}
+
+fn update_opening_shop(
+    context: &Context,
+    key: KeyKind,
+    selling: SellingItems,
+    timeout: Timeout,
+) -> SellingItems {
+    match next_timeout_lifecycle(timeout, OPENING_SHOP_TIMEOUT) {
+        Lifecycle::Started(timeout) => {
+            let _ = context.keys.send(key);
+            selling.stage(SellingItemsStage::OpeningShop(timeout))
+        }
+        Lifecycle::Ended => {
+            if selling.tabs.is_empty() || selling.slots.is_empty() {
+                selling.stage_closing_shop(Timeout::default())
+            } else {
+                selling.stage_selecting_tab(Timeout::default(), 0)
+            }
+        }
+        Lifecycle::Updated(timeout) => selling.stage(SellingItemsStage::OpeningShop(timeout)),
+    }
+}
+
+fn update_selecting_tab(
+    context: &Context,
+    selling: SellingItems,
+    timeout: Timeout,
+    tab_index: usize,
+) -> SellingItems {
+    match next_timeout_lifecycle(timeout, SELECTING_TAB_TIMEOUT) {
+        Lifecycle::Started(timeout) => {
+            let tab = selling.tabs[tab_index];
+            let _ = context.keys.send_mouse(tab.x, tab.y, MouseAction::Click);
+            selling.stage_selecting_tab(timeout, tab_index)
+        }
+        Lifecycle::Ended => selling.stage_selling(Timeout::default(), tab_index, 0),
+        Lifecycle::Updated(timeout) => selling.stage_selecting_tab(timeout, tab_index),
+    }
+}
+
+fn update_selling(
+    context: &Context,
+    selling: SellingItems,
+    timeout: Timeout,
+    tab_index: usize,
+    slot_index: usize,
+) -> SellingItems {
+    match next_timeout_lifecycle(timeout, SELLING_TIMEOUT) {
+        Lifecycle::Started(timeout) => {
+            // Double click to sell
+            let slot = selling.slots[slot_index];
+            let _ = context.keys.send_mouse(slot.x, slot.y, MouseAction::Click);
+            let _ = context.keys.send_mouse(slot.x, slot.y, MouseAction::Click);
+            selling.stage_selling(timeout, tab_index, slot_index)
+        }
+        Lifecycle::Ended => {
+            if slot_index + 1 < selling.slots.len() {
+                selling.stage_selling(Timeout::default(), tab_index, slot_index + 1)
+            } else if tab_index + 1 < selling.tabs.len() {
+                selling.stage_selecting_tab(Timeout::default(), tab_index + 1)
+            } else {
+                selling.stage_closing_shop(Timeout::default())
+            }
+        }
+        Lifecycle::Updated(timeout) => {
+            if timeout.current == SELLING_CONFIRM_AT {
+                // Confirms the quantity or the sell popup if there is one
+                let _ = context.keys.send(KeyKind::Enter);
+            }
+            selling.stage_selling(timeout, tab_index, slot_index)
+        }
+    }
+}
+
+fn update_closing_shop(context: &Context, selling: SellingItems, timeout: Timeout) -> SellingItems {
+    match next_timeout_lifecycle(timeout, CLOSING_SHOP_TIMEOUT) {
+        Lifecycle::Started(timeout) => {
+            let _ = context.keys.send(KeyKind::Esc);
+            selling.stage_closing_shop(timeout)
+        }
+        Lifecycle::Ended => selling.stage(SellingItemsStage::Completing),
+        Lifecycle::Updated(timeout) => selling.stage_closing_shop(timeout),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use anyhow::Ok;
+    use mockall::predicate::{eq, function};
+
+    use super::*;
+    use crate::{Position, bridge::MockKeySender};
+
+    fn selling_items(tabs: usize, slots: usize) -> SellingItems {
+        SellingItems::new(PlayerActionSellItems {
+            npc_position: Position::default(),
+            tabs: Array::from_iter((0..tabs).map(|i| Point::new(i as i32, 0))),
+            slots: Array::from_iter((0..slots).map(|i| Point::new(i as i32, 1))),
+        })
+    }
+
+    #[test]
+    fn update_opening_shop_started_send_interact_key() {
+        let mut keys = MockKeySender::default();
+        keys.expect_send()
+            .once()
+            .with(eq(KeyKind::Y))
+            .returning(|_| Ok(()));
+        let context = Context::new(Some(keys), None);
+
+        let result = update_opening_shop(
+            &context,
+            KeyKind::Y,
+            selling_items(1, 1),
+            Timeout::default(),
+        );
+        assert_matches!(result.stage, SellingItemsStage::OpeningShop(_));
+    }
+
+    #[test]
+    fn update_opening_shop_ended_close_if_no_slots() {
+        let context = Context::new(None, None);
+        let timeout = Timeout {
+            current: OPENING_SHOP_TIMEOUT,
+            started: true,
+            ..Default::default()
+        };
+
+        let result = update_opening_shop(&context, KeyKind::Y, selling_items(1, 0), timeout);
+        assert_matches!(result.stage, SellingItemsStage::ClosingShop(_));
+    }
+
+    #[test]
+    fn update_selling_ended_advance_slot_then_tab_then_close() {
+        let context = Context::new(None, None);
+        let selling = selling_items(2, 2);
+        let timeout = Timeout {
+            current: SELLING_TIMEOUT,
+            started: true,
+            ..Default::default()
+        };
+
+        let result = update_selling(&context, selling, timeout, 0, 0);
+        assert_matches!(result.stage, SellingItemsStage::Selling(_, 0, 1));
+
+        let result = update_selling(&context, selling, timeout, 0, 1);
+        assert_matches!(result.stage, SellingItemsStage::SelectingTab(_, 1));
+
+        let result = update_selling(&context, selling, timeout, 1, 1);
+        assert_matches!(result.stage, SellingItemsStage::ClosingShop(_));
+    }
+
+    #[test]
+    fn update_selling_started_double_click_slot() {
+        let mut keys = MockKeySender::default();
+        keys.expect_send_mouse()
+            .times(2)
+            .with(
+                eq(1),
+                eq(1),
+                function(|action| matches!(action, MouseAction::Click)),
+            )
+            .returning(|_, _, _| Ok(()));
+        let context = Context::new(Some(keys), None);
+
+        let result = update_selling(&context, selling_items(1, 2), Timeout::default(), 0, 1);
+        assert_matches!(result.stage, SellingItemsStage::Selling(_, 0, 1));
+    }
+}
@@ -1,5 +1,8 @@
+use log::debug;
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
@@ -16,6 +19,18 @@ use crate::{
 
 const MAX_RETRY_COUNT: u32 = 3;
 
+/// Maximum number of ticks to solve for the rune arrows.
+const SOLVING_TIMEOUT: u32 = 150;
+
+/// Maximum number of ticks to solve for the rune arrows when there are spinning arrows.
+///
+/// Spinning arrows are tracked over multiple frames until they lag so they need longer.
+const SPIN_SOLVING_TIMEOUT: u32 = 240;
+
+/// Maximum number of times an arrow still highlighted after pressing its key is re-inferred and
+/// pressed again.
+const MAX_VERIFY_RETRY_COUNT: u32 = 2;
+
 /// Representing the current stage of rune solving.
 #[derive(Debug, Default, Clone, Copy)]
 pub enum RuneStage {
@@ -26,8 +41,8 @@ pub enum RuneStage {
     FindRegion(ArrowsCalibrating, Timeout, Option<Timeout>, u32),
     // Solves for the rune arrows that possibly include spinning arrows.
     Solving(ArrowsCalibrating, Timeout),
-    // Presses the keys.
-    PressKeys(Timeout, [KeyKind; 4], usize),
+    // Presses the keys and verifies each arrow is solved before pressing the next one.
+    PressKeys(ArrowsCalibrating, Timeout, [KeyKind; 4], usize, u32),
     // Terminal stage.
     Completed,
 }
@@ -68,12 +83,14 @@ impl SolvingRune {
     #[inline]
     fn stage_press_keys(
         self,
+        calibrating: ArrowsCalibrating,
         timeout: Timeout,
         keys: [KeyKind; 4],
         key_index: usize,
+        retry_count: u32,
     ) -> SolvingRune {
         SolvingRune {
-            stage: RuneStage::PressKeys(timeout, keys, key_index),
+            stage: RuneStage::PressKeys(calibrating, timeout, keys, key_index, retry_count),
         }
     }
 
@@ -121,8 +138,16 @@ pub fn update_solving_rune_context(
         RuneStage::Solving(calibrating, timeout) => {
             update_solving(context, solving_rune, calibrating, timeout)
         }
-        RuneStage::PressKeys(timeout, keys, key_index) => {
-            update_press_keys(context, solving_rune, timeout, keys, key_index)
+        RuneStage::PressKeys(calibrating, timeout, keys, key_index, retry_count) => {
+            update_press_keys(
+                context,
+                solving_rune,
+                calibrating,
+                timeout,
+                keys,
+                key_index,
+                retry_count,
+            )
         }
         RuneStage::Completed => unreachable!(),
     };
@@ -147,6 +172,9 @@ pub fn update_solving_rune_context(
             | PlayerAction::Panic(_)
             | PlayerAction::Key(_)
             | PlayerAction::FamiliarsSwapping(_)
+            | PlayerAction::SellItems(_)
+            | PlayerAction::Combo(_)
+            | PlayerAction::Mouse(_)
             | PlayerAction::Move(_) => {
                 unreachable!()
             }
@@ -219,7 +247,13 @@ fn update_solving(
     calibrating: ArrowsCalibrating,
     timeout: Timeout,
 ) -> SolvingRune {
-    match next_timeout_lifecycle(timeout, 150) {
+    let max_timeout = if calibrating.has_spin_arrows() {
+        SPIN_SOLVING_TIMEOUT
+    } else {
+        SOLVING_TIMEOUT
+    };
+
+    match next_timeout_lifecycle(timeout, max_timeout) {
         Lifecycle::Started(timeout) => solving_rune.stage_solving(calibrating, timeout),
         Lifecycle::Ended => solving_rune.stage_completed(),
         Lifecycle::Updated(timeout) => {
@@ -228,7 +262,7 @@ fn update_solving(
                     solving_rune.stage_solving(calibrating, timeout)
                 }
                 Ok(ArrowsState::Complete(keys)) => {
-                    solving_rune.stage_press_keys(Timeout::default(), keys, 0)
+                    solving_rune.stage_press_keys(calibrating, Timeout::default(), keys, 0, 0)
                 }
                 Err(_) => solving_rune.stage_completed(),
             }
@@ -239,34 +273,93 @@ fn update_solving(
 fn update_press_keys(
     context: &Context,
     solving_rune: SolvingRune,
+    calibrating: ArrowsCalibrating,
     timeout: Timeout,
     keys: [KeyKind; 4],
     key_index: usize,
+    retry_count: u32,
 ) -> SolvingRune {
     const PRESS_KEY_INTERVAL: u32 = 8;
 
     match next_timeout_lifecycle(timeout, PRESS_KEY_INTERVAL) {
         Lifecycle::Started(timeout) => {
             let _ = context.keys.send(keys[key_index]);
-            solving_rune.stage_press_keys(timeout, keys, key_index)
+            solving_rune.stage_press_keys(calibrating, timeout, keys, key_index, retry_count)
         }
         Lifecycle::Ended => {
-            if key_index + 1 < keys.len() {
-                solving_rune.stage_press_keys(Timeout::default(), keys, key_index + 1)
+            if calibrating.is_verifiable() {
+                verify_pressed_key(
+                    context,
+                    solving_rune,
+                    calibrating,
+                    keys,
+                    key_index,
+                    retry_count,
+                )
+            } else if key_index + 1 < keys.len() {
+                solving_rune.stage_press_keys(
+                    calibrating,
+                    Timeout::default(),
+                    keys,
+                    key_index + 1,
+                    0,
+                )
             } else {
                 solving_rune.stage_completed()
             }
         }
-        Lifecycle::Updated(timeout) => solving_rune.stage_press_keys(timeout, keys, key_index),
+        Lifecycle::Updated(timeout) => {
+            solving_rune.stage_press_keys(calibrating, timeout, keys, key_index, retry_count)
+        }
     }
 }
 
+/// Re-detects the remaining arrows after pressing the key at `key_index` to find out how many
+/// arrows are solved.
+///
+/// Only the remaining arrows are re-inferred and pressing continues from the first of them. If
+/// the arrow at `key_index` is still highlighted, its key is pressed again.
+fn verify_pressed_key(
+    context: &Context,
+    solving_rune: SolvingRune,
+    calibrating: ArrowsCalibrating,
+    mut keys: [KeyKind; 4],
+    key_index: usize,
+    retry_count: u32,
+) -> SolvingRune {
+    let Ok(remaining) = context
+        .detector_unwrap()
+        .detect_rune_arrows_remaining(calibrating)
+    else {
+        // The rune region is no longer there
+        return solving_rune.stage_completed();
+    };
+    let solved = keys.len() - remaining.len();
+    if solved == keys.len() {
+        return solving_rune.stage_completed();
+    }
+    if solved < key_index || (solved == key_index && retry_count >= MAX_VERIFY_RETRY_COUNT) {
+        debug!(target: "player", "rune arrow {key_index} failed to solve with {solved} arrow(s) solved");
+        return solving_rune.stage_completed();
+    }
+
+    keys[solved..].copy_from_slice(&remaining);
+    let retry_count = if solved == key_index {
+        debug!(target: "player", "rune arrow {key_index} still highlighted, pressing {:?} again", keys[solved]);
+        retry_count + 1
+    } else {
+        0
+    };
+    solving_rune.stage_press_keys(calibrating, Timeout::default(), keys, solved, retry_count)
+}
+
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
 
     use anyhow::{Ok, anyhow};
     use mockall::predicate::eq;
+    use opencv::core::Rect;
 
     use super::*;
     use crate::{
@@ -467,6 +560,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_solving_spin_arrows_longer_timeout() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_rune_arrows()
+            .return_once(move |calibrating| Ok(ArrowsState::Calibrating(calibrating)));
+        let context = Context::new(None, Some(detector));
+        let mut calibrating = ArrowsCalibrating::default();
+        calibrating.set_spin_arrows();
+        let timeout = Timeout {
+            started: true,
+            current: SOLVING_TIMEOUT,
+            ..Default::default()
+        };
+
+        let result = update_solving(&context, SolvingRune::default(), calibrating, timeout);
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::Solving(_, Timeout { started: true, .. })
+            }
+        );
+
+        let result = update_solving(
+            &context,
+            SolvingRune::default(),
+            ArrowsCalibrating::default(),
+            timeout,
+        );
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::Completed
+            }
+        );
+    }
+
     #[test]
     fn update_solving_to_press_keys_on_complete() {
         let expected_keys = [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F];
@@ -492,12 +622,14 @@ mod tests {
             result,
             SolvingRune {
                 stage: RuneStage::PressKeys(
+                    _,
                     Timeout {
                         started: false,
                         current: 0,
                         ..
                     },
                     [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F],
+                    0,
                     0
                 )
             }
@@ -521,14 +653,17 @@ mod tests {
             update_press_keys(
                 &context,
                 SolvingRune::default(),
+                ArrowsCalibrating::default(),
                 Timeout::default(),
                 expected_keys,
                 key_index,
+                0,
             );
             // Timing out and advance key index or complete
             let end_result = update_press_keys(
                 &context,
                 SolvingRune::default(),
+                ArrowsCalibrating::default(),
                 Timeout {
                     current: 8,
                     started: true,
@@ -536,6 +671,7 @@ mod tests {
                 },
                 expected_keys,
                 key_index,
+                0,
             );
 
             if key_index == expected_keys.len() - 1 {
@@ -547,10 +683,104 @@ mod tests {
                 );
             } else {
                 key_index = match end_result.stage {
-                    RuneStage::PressKeys(_, _, index) => index,
+                    RuneStage::PressKeys(_, _, _, index, _) => index,
                     _ => unreachable!(),
                 }
             }
         }
     }
+
+    #[test]
+    fn update_press_keys_verify_still_highlighted_reinfers_remaining() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_rune_arrows_remaining()
+            .return_once(|_| Ok(vec![KeyKind::Up, KeyKind::Down, KeyKind::Left]));
+        let context = Context::new(None, Some(detector));
+        let mut calibrating = ArrowsCalibrating::default();
+        calibrating.set_rune_region(Rect::new(0, 0, 300, 100));
+
+        let result = update_press_keys(
+            &context,
+            SolvingRune::default(),
+            calibrating,
+            Timeout {
+                current: 8,
+                started: true,
+                ..Default::default()
+            },
+            [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F],
+            1,
+            0,
+        );
+
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::PressKeys(
+                    _,
+                    Timeout { started: false, .. },
+                    [KeyKind::A, KeyKind::Up, KeyKind::Down, KeyKind::Left],
+                    1,
+                    1
+                )
+            }
+        );
+    }
+
+    #[test]
+    fn update_press_keys_verify_completed() {
+        let keys = [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F];
+        let mut calibrating = ArrowsCalibrating::default();
+        calibrating.set_rune_region(Rect::new(0, 0, 300, 100));
+        let end_timeout = Timeout {
+            current: 8,
+            started: true,
+            ..Default::default()
+        };
+
+        // All arrows are solved
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_rune_arrows_remaining()
+            .return_once(|_| Ok(vec![]));
+        let context = Context::new(None, Some(detector));
+        let result = update_press_keys(
+            &context,
+            SolvingRune::default(),
+            calibrating,
+            end_timeout,
+            keys,
+            3,
+            0,
+        );
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::Completed
+            }
+        );
+
+        // Still highlighted after all retries
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_rune_arrows_remaining()
+            .return_once(|_| Ok(vec![KeyKind::F]));
+        let context = Context::new(None, Some(detector));
+        let result = update_press_keys(
+            &context,
+            SolvingRune::default(),
+            calibrating,
+            end_timeout,
+            keys,
+            3,
+            MAX_VERIFY_RETRY_COUNT,
+        );
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::Completed
+            }
+        );
+    }
 }
@@ -45,8 +45,14 @@ pub fn update_stalling_context(
             PlayerAction::PingPong(_) | PlayerAction::Key(_) | PlayerAction::Move(_) => {
                 Some((next, matches!(next, Player::Idle)))
             }
-            PlayerAction::SolveRune => None,
-            PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
+            // These actions can stall while moving (e.g. to the shop NPC) and complete in their
+            // own states instead
+            PlayerAction::SolveRune
+            | PlayerAction::Panic(_)
+            | PlayerAction::FamiliarsSwapping(_)
+            | PlayerAction::SellItems(_)
+            | PlayerAction::Combo(_)
+            | PlayerAction::Mouse(_) => None,
         },
         || next,
     )
@@ -1,17 +1,20 @@
-use std::{collections::HashMap, range::Range};
+use std::{collections::HashMap, range::Range, time::Instant};
 
 use anyhow::Result;
 use log::debug;
 use opencv::core::{Point, Rect};
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
 use super::{
-    DOUBLE_JUMP_THRESHOLD, JUMP_THRESHOLD, MOVE_TIMEOUT, Player, PlayerAction,
+    MOVE_TIMEOUT, MovementThresholds, Player, PlayerAction,
     double_jump::DOUBLE_JUMP_AUTO_MOB_THRESHOLD,
     fall::FALLING_THRESHOLD,
+    familiars_swap::FamiliarSetup,
     timeout::{Lifecycle, Timeout, next_timeout_lifecycle},
 };
 use crate::{
@@ -20,11 +23,14 @@ use crate::{
     bridge::MouseAction,
     buff::{Buff, BuffKind},
     context::Context,
+    detect::{DetectionScores, SearchRegion},
     minimap::Minimap,
-    network::NotificationKind,
     task::{Task, Update, update_detection_task},
 };
 
+/// The maximum number of potions that can be configured.
+pub const MAX_POTIONS: usize = 8;
+
 /// The maximum number of times rune solving can fail before transition to
 /// [`Player::CashShopThenExit`].
 const MAX_RUNE_FAILED_COUNT: u32 = 8;
@@ -74,6 +80,10 @@ const UNSTUCK_GAMBA_MODE_COUNT: u32 = 3;
 /// The number of samples to store for approximating velocity.
 const VELOCITY_SAMPLES: usize = MOVE_TIMEOUT as usize;
 
+/// The number of ticks after the player last moved on its own before its x motion is
+/// attributed to the platform it is standing on.
+const CARRIED_SETTLE_TICKS: u64 = 3;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Quadrant {
     TopLeft,
@@ -99,9 +109,23 @@ pub enum LastMovement {
     Adjusting,
     DoubleJumping,
     Falling,
+    Flying,
+    Climbing,
     Grappling,
     UpJumping,
     Jumping,
+    JumpingDown,
+}
+
+/// A potion used when health or mana is below a percentage.
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerPotion {
+    /// The potion key.
+    pub key: KeyKind,
+    /// Uses this potion when health or mana ratio is at or below this value.
+    pub below_ratio: f32,
+    /// Milliseconds before this potion can be used again.
+    pub cooldown_millis: u64,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -112,6 +136,8 @@ pub struct PlayerConfiguration {
     pub class: Class,
     /// Whether to disable [`Player::Adjusting`].
     pub disable_adjusting: bool,
+    /// Whether to disable [`Player::JumpingDown`] in platforms pathing.
+    pub disable_jump_down: bool,
     /// Enables platform pathing for rune.
     pub rune_platforms_pathing: bool,
     /// Uses only up jump(s) in rune platform pathing.
@@ -136,6 +162,10 @@ pub struct PlayerConfiguration {
     pub jump_key: KeyKind,
     /// The up jump key with [`None`] indicating composite jump (Up arrow + Double Space).
     pub upjump_key: Option<KeyKind>,
+    /// The flight key with [`None`] indicating the player cannot fly.
+    pub flying_key: Option<KeyKind>,
+    /// Whether [`Self::flying_key`] is held down while flying.
+    pub hold_flying_key: bool,
     /// The cash shop key.
     pub cash_shop_key: KeyKind,
     /// The familiar key.
@@ -144,12 +174,17 @@ pub struct PlayerConfiguration {
     pub to_town_key: KeyKind,
     /// The change channel key.
     pub change_channel_key: KeyKind,
-    /// The potion key.
-    pub potion_key: KeyKind,
-    /// Uses potion when health is below a percentage.
-    pub use_potion_below_percent: Option<f32>,
-    /// Milliseconds interval to update current health.
+    /// The health-based potions in order of priority.
+    ///
+    /// When health is below multiple potions threshold, the first one that is not
+    /// in cooldown is used.
+    pub potions: Array<PlayerPotion, MAX_POTIONS>,
+    /// The MP-based potions in order of priority.
+    pub mana_potions: Array<PlayerPotion, MAX_POTIONS>,
+    /// Milliseconds interval to update current health and mana.
     pub update_health_millis: Option<u64>,
+    /// The movement thresholds of the current map.
+    pub movement: MovementThresholds,
 }
 
 /// The player persistent states.
@@ -170,14 +205,31 @@ pub struct PlayerState {
     ///
     /// This action will override the normal action if it is in the middle of executing.
     pub(super) priority_action: Option<PlayerAction>,
+    /// The id, action and whether it is a priority action of the last action that failed to
+    /// reach its position.
+    ///
+    /// Taken by [`Rotator`] to retry or to apply the action failure policy.
+    failed_action: Option<(u32, PlayerAction, bool)>,
     /// The player current health and max health.
     pub health: Option<(u32, u32)>,
     /// The task to update health.
     health_task: Option<Task<Result<(u32, u32)>>>,
     /// The rectangular health bar region.
     health_bar: Option<Rect>,
+    /// The last [`Instant`] each potion in [`PlayerConfiguration::potions`] was used.
+    potions_last_used: [Option<Instant>; MAX_POTIONS],
     /// The task for the health bar.
-    health_bar_task: Option<Task<Result<Rect>>>,
+    health_bar_task: Option<Task<Result<(Result<Rect>, Option<f32>)>>>,
+    /// The last detected health bar region to search around on re-detection.
+    ///
+    /// This is kept across [`Self::reset`].
+    health_bar_region: SearchRegion,
+    /// The player current mana and max mana.
+    pub mana: Option<(u32, u32)>,
+    /// The task to update mana.
+    mana_task: Option<Task<Result<(u32, u32)>>>,
+    /// The last [`Instant`] each potion in [`PlayerConfiguration::mana_potions`] was used.
+    mana_potions_last_used: [Option<Instant>; MAX_POTIONS],
     /// Track if the player moved within a specified ticks to determine if the player is
     /// stationary.
     is_stationary_timeout: Timeout,
@@ -205,6 +257,10 @@ pub struct PlayerState {
     ///
     /// This is true whenever [`Self::use_immediate_control_flow`] is true.
     pub(super) ignore_pos_update: bool,
+    /// Whether [`Self::last_known_pos`] was detected on the last update.
+    ///
+    /// The position is only reused for an unchanged minimap if it was detected.
+    last_known_pos_detected: bool,
     /// Indicates whether to reset the contextual state back to [`Player::Idle`] on next update.
     ///
     /// This is true each time player receives [`PlayerAction`].
@@ -259,10 +315,16 @@ pub struct PlayerState {
     ///
     /// Resets when [`Player::Stalling`] timed out or in [`Player::Idle`].
     pub(super) stalling_timeout_state: Option<Player>,
+    /// The familiar setup read in the last familiars swapping.
+    ///
+    /// This is kept across [`Self::reset`].
+    pub familiar_setup: Option<FamiliarSetup>,
     /// Stores a list of [`(Point, u64)`] pair samples for approximating velocity.
     velocity_samples: Array<(Point, u64), VELOCITY_SAMPLES>,
     /// Approximated player velocity.
     pub(super) velocity: (f32, f32),
+    /// The last tick the player was in a state that moves it on its own (e.g. walking, jumping).
+    self_moving_tick: u64,
 }
 
 impl PlayerState {
@@ -273,6 +335,8 @@ impl PlayerState {
     pub fn reset(&mut self) {
         *self = PlayerState {
             config: self.config,
+            health_bar_region: self.health_bar_region,
+            familiar_setup: self.familiar_setup.take(),
             reset_to_idle_next_update: true,
             ..PlayerState::default()
         };
@@ -409,6 +473,25 @@ impl PlayerState {
         }
     }
 
+    /// Clears either normal or priority action due to failing to reach its position and records
+    /// it as [`Self::failed_action`].
+    #[inline]
+    pub fn clear_action_failed(&mut self) {
+        self.failed_action = if let Some(action) = self.priority_action {
+            Some((self.priority_action_id, action, true))
+        } else {
+            self.normal_action
+                .map(|action| (self.normal_action_id, action, false))
+        };
+        self.clear_action_completed();
+    }
+
+    /// Takes the id, action and whether it is a priority action of the last failed action.
+    #[inline]
+    pub fn take_failed_action(&mut self) -> Option<(u32, PlayerAction, bool)> {
+        self.failed_action.take()
+    }
+
     /// Clears the last movement tracking for either normal or priority action.
     #[inline]
     pub(super) fn clear_last_movement(&mut self) {
@@ -483,9 +566,12 @@ impl PlayerState {
                 }
             }
             LastMovement::Falling
+            | LastMovement::Flying
+            | LastMovement::Climbing
             | LastMovement::Grappling
             | LastMovement::UpJumping
-            | LastMovement::Jumping => {
+            | LastMovement::Jumping
+            | LastMovement::JumpingDown => {
                 if self.has_auto_mob_action_only() {
                     AUTO_MOB_VERTICAL_MOVEMENT_REPEAT_COUNT
                 } else {
@@ -515,7 +601,7 @@ impl PlayerState {
     #[inline]
     pub(super) fn falling_threshold(&self, is_intermediate: bool) -> i32 {
         if self.has_auto_mob_action_only() || is_intermediate {
-            JUMP_THRESHOLD
+            self.config.movement.jump
         } else {
             FALLING_THRESHOLD
         }
@@ -532,9 +618,9 @@ impl PlayerState {
         } else if self.has_ping_pong_action_only() {
             0 // Ping pong double jumps forever
         } else if self.config.teleport_key.is_some() {
-            DOUBLE_JUMP_THRESHOLD / 2 // Half the threshold for mage
+            self.config.movement.double_jump / 2 // Half the threshold for mage
         } else {
-            DOUBLE_JUMP_THRESHOLD
+            self.config.movement.double_jump
         }
     }
 
@@ -550,6 +636,17 @@ impl PlayerState {
                 && self.config.rune_platforms_pathing_up_jump_only)
     }
 
+    #[inline]
+    pub(super) fn should_disable_flying(&self) -> bool {
+        (self.config.flying_key.is_none())
+            || (self.has_auto_mob_action_only()
+                && self.config.auto_mob_platforms_pathing
+                && self.config.auto_mob_platforms_pathing_up_jump_only)
+            || (self.has_rune_action()
+                && self.config.rune_platforms_pathing
+                && self.config.rune_platforms_pathing_up_jump_only)
+    }
+
     #[inline]
     pub fn auto_mob_last_quadrant(&self) -> Option<Quadrant> {
         self.auto_mob_last_quadrant
@@ -569,8 +666,8 @@ impl PlayerState {
         let bound_x_mid = bound.x + bound_width_half;
         let bound_y_mid = bound.y + bound_height_half;
 
-        let (bbox, platforms) = match context.minimap {
-            Minimap::Idle(idle) => (idle.bbox, idle.platforms),
+        let bbox = match context.minimap {
+            Minimap::Idle(idle) => idle.bbox,
             _ => unreachable!(),
         };
 
@@ -608,17 +705,45 @@ impl PlayerState {
         };
 
         self.auto_mob_last_quadrant = Some(next_quadrant);
+        self.auto_mob_point_inside(context, next_quadrant_bound)
+    }
+
+    /// Picks a pathing point in auto mobbing to move to inside `bound` relative to the minimap
+    /// top-left coordinate.
+    ///
+    /// Unlike [`Self::auto_mob_pathing_point`], `bound` is used as is instead of one of its
+    /// quadrants.
+    ///
+    /// The returned [`Point`] is in player coordinate relative to bottom-left.
+    #[inline]
+    pub fn auto_mob_sub_bound_point(&mut self, context: &Context, bound: Rect) -> Point {
+        self.auto_mob_last_quadrant = None;
+        self.auto_mob_point_inside(context, bound)
+    }
+
+    /// Stops restricting auto mobbing positions to the bound of the last pathing point.
+    #[inline]
+    pub fn auto_mob_clear_last_bound(&mut self) {
+        self.auto_mob_last_quadrant_bound = None;
+    }
+
+    fn auto_mob_point_inside(&mut self, context: &Context, bound: Rect) -> Point {
+        let (bbox, platforms) = match context.minimap {
+            Minimap::Idle(idle) => (idle.bbox, idle.platforms),
+            _ => unreachable!(),
+        };
+
         self.auto_mob_last_quadrant_bound = Some(Rect::new(
-            next_quadrant_bound.x,
-            bbox.height - next_quadrant_bound.br().y,
-            next_quadrant_bound.width,
-            next_quadrant_bound.height,
+            bound.x,
+            bbox.height - bound.br().y,
+            bound.width,
+            bound.height,
         ));
 
-        let bound_xs = next_quadrant_bound.x..(next_quadrant_bound.x + next_quadrant_bound.width);
-        let bound_ys = next_quadrant_bound.y..(next_quadrant_bound.y + next_quadrant_bound.height);
+        let bound_xs = bound.x..(bound.x + bound.width);
+        let bound_ys = bound.y..(bound.y + bound.height);
 
-        // Use a random platform inside the next quadrant bound if any
+        // Use a random platform inside the bound if any
         if !platforms.is_empty() {
             let platform = context
                 .rng
@@ -780,6 +905,9 @@ impl PlayerState {
             | PlayerAction::Key(_)
             | PlayerAction::Move(_)
             | PlayerAction::Panic(_)
+            | PlayerAction::SellItems(_)
+            | PlayerAction::Combo(_)
+            | PlayerAction::Mouse(_)
             | PlayerAction::SolveRune => {
                 unreachable!()
             }
@@ -925,19 +1053,38 @@ impl PlayerState {
             Minimap::Detecting => return false,
             Minimap::Idle(idle) => idle.bbox,
         };
-        let Ok(player_bbox) = context.detector_unwrap().detect_player(minimap_bbox) else {
-            return false;
+        let pos = match self.last_known_pos {
+            // The player cannot have moved if the minimap has not changed
+            Some(pos) if context.minimap_unchanged && self.last_known_pos_detected => pos,
+            _ => {
+                let (player_bbox, score) = context
+                    .detector_unwrap()
+                    .detect_player(minimap_bbox, context.detection_tuning.player_score);
+                if score.is_some() {
+                    context.detection_scores.set(DetectionScores {
+                        player: score,
+                        ..context.detection_scores.get()
+                    });
+                }
+                let Ok(player_bbox) = player_bbox else {
+                    self.last_known_pos_detected = false;
+                    return false;
+                };
+                let tl = player_bbox.tl();
+                let br = player_bbox.br();
+                let x = (tl.x + br.x) / 2;
+                // The native coordinate of OpenCV is top-left and this flips to bottom-left for
+                // for better intution to the UI. All player states and actions also operate on
+                // this bottom-left coordinate.
+                //
+                // TODO: Should keep original coordinate? And flips before passing to UI?
+                let y = minimap_bbox.height - br.y;
+                Point::new(x, y)
+            }
         };
-        let tl = player_bbox.tl();
-        let br = player_bbox.br();
-        let x = (tl.x + br.x) / 2;
-        // The native coordinate of OpenCV is top-left and this flips to bottom-left for
-        // for better intution to the UI. All player states and actions also operate on this
-        // bottom-left coordinate.
-        //
-        // TODO: Should keep original coordinate? And flips before passing to UI?
-        let y = minimap_bbox.height - br.y;
-        let pos = Point::new(x, y);
+        if !matches!(context.player, Player::Idle | Player::Moving(_, _, _)) {
+            self.self_moving_tick = context.tick;
+        }
         let last_known_pos = self.last_known_pos.unwrap_or(pos);
         if last_known_pos != pos {
             self.unstuck_count = 0;
@@ -946,18 +1093,39 @@ impl PlayerState {
         }
         self.update_velocity(pos, context.tick);
 
-        let (is_stationary, is_stationary_timeout) =
-            match next_timeout_lifecycle(self.is_stationary_timeout, MOVE_TIMEOUT) {
-                Lifecycle::Started(timeout) => (false, timeout),
-                Lifecycle::Ended => (true, self.is_stationary_timeout),
-                Lifecycle::Updated(timeout) => (false, timeout),
-            };
+        let (is_stationary, is_stationary_timeout) = match next_timeout_lifecycle(
+            self.is_stationary_timeout,
+            self.config.movement.move_timeout,
+        ) {
+            Lifecycle::Started(timeout) => (false, timeout),
+            Lifecycle::Ended => (true, self.is_stationary_timeout),
+            Lifecycle::Updated(timeout) => (false, timeout),
+        };
         self.is_stationary = is_stationary;
         self.is_stationary_timeout = is_stationary_timeout;
         self.last_known_pos = Some(pos);
+        self.last_known_pos_detected = true;
         true
     }
 
+    /// The x the player is carried by between the last two detected positions.
+    ///
+    /// This is zero if the player was moving on its own (e.g. walking) too recently because the
+    /// x motion cannot be attributed to the platform it is standing on.
+    #[inline]
+    pub(super) fn last_carried_x_delta(&self) -> i32 {
+        let samples = self.velocity_samples.as_slice();
+        if samples.len() < 2 {
+            return 0;
+        }
+        let (last, _) = samples[samples.len() - 1].unwrap();
+        let (previous, previous_tick) = samples[samples.len() - 2].unwrap();
+        if previous_tick <= self.self_moving_tick + CARRIED_SETTLE_TICKS {
+            return 0;
+        }
+        last.x - previous.x
+    }
+
     /// Approximates the player velocity.
     #[inline]
     fn update_velocity(&mut self, pos: Point, tick: u64) {
@@ -1027,67 +1195,120 @@ impl PlayerState {
         });
     }
 
-    /// Updates the player current health.
+    /// Updates the player current health and mana.
     ///
     /// The detection first detects the HP bar and caches the result. The HP bar is then used
     /// to crop into the game image and detects the current health bar and max health bar. These
-    /// bars are then cached and used to extract the current health and max health.
+    /// bars are then cached and used to extract the current health and max health. The MP bar
+    /// is located right below the HP bar and detected the same way.
     // TODO: This should be a PlayerAction?
     #[inline]
     fn update_health_state(&mut self, context: &Context) {
         if let Player::SolvingRune(_) = context.player {
             return;
         }
-        if self.config.use_potion_below_percent.is_none() {
+        if self.config.potions.is_empty() && self.config.mana_potions.is_empty() {
             {
                 let this = &mut *self;
                 this.health = None;
                 this.health_task = None;
                 this.health_bar = None;
                 this.health_bar_task = None;
+                this.mana = None;
+                this.mana_task = None;
             };
             return;
         }
 
         let Some(health_bar) = self.health_bar else {
+            let region = self.health_bar_region.get();
+            let score_threshold = context.detection_tuning.health_bar_score;
             let update =
                 update_detection_task(context, 1000, &mut self.health_bar_task, move |detector| {
-                    detector.detect_player_health_bar()
+                    Ok(detector.detect_player_health_bar(region, score_threshold))
+                });
+            let Update::Ok((health_bar, score)) = update else {
+                return;
+            };
+            if score.is_some() {
+                context.detection_scores.set(DetectionScores {
+                    health_bar: score,
+                    ..context.detection_scores.get()
                 });
-            if let Update::Ok(health_bar) = update {
-                self.health_bar = Some(health_bar);
+            }
+            match health_bar {
+                Ok(health_bar) => {
+                    self.health_bar = Some(health_bar);
+                    self.health_bar_region.update(Some(health_bar));
+                }
+                Err(_) => self.health_bar_region.update(None),
             }
             return;
         };
 
-        let Update::Ok(health) = update_detection_task(
-            context,
-            self.config.update_health_millis.unwrap_or(1000),
-            &mut self.health_task,
-            move |detector| {
-                let (current_bar, max_bar) =
-                    detector.detect_player_current_max_health_bars(health_bar)?;
-                let health = detector.detect_player_health(current_bar, max_bar)?;
-                debug!(target: "player", "health updated {health:?}");
-                Ok(health)
-            },
-        ) else {
-            return;
-        };
-
-        let percentage = self.config.use_potion_below_percent.unwrap();
-        let (current, max) = health;
-        let ratio = current as f32 / max as f32;
-
-        self.health = Some(health);
-        if ratio <= percentage {
-            let _ = context.keys.send(self.config.potion_key);
+        let update_millis = self.config.update_health_millis.unwrap_or(1000);
+        let color_lower = context.detection_tuning.health_bar_color_lower;
+        let color_upper = context.detection_tuning.health_bar_color_upper;
+        let last_max_health = self.health.map(|(_, max)| max).unwrap_or(100);
+        if !self.config.potions.is_empty()
+            && let Update::Ok(health) = update_detection_task(
+                context,
+                update_millis,
+                &mut self.health_task,
+                move |detector| {
+                    let health = detector
+                        .detect_player_current_max_health_bars(health_bar)
+                        .and_then(|(current_bar, max_bar)| {
+                            detector.detect_player_health(current_bar, max_bar)
+                        })
+                        .or_else(|_| {
+                            // Falls back to the gauge width when the values cannot be read
+                            let ratio = detector.detect_player_health_ratio(
+                                health_bar,
+                                color_lower,
+                                color_upper,
+                            )?;
+                            let current = (ratio * last_max_health as f32).round() as u32;
+                            anyhow::Ok((current, last_max_health))
+                        })?;
+                    debug!(target: "player", "health updated {health:?}");
+                    Ok(health)
+                },
+            )
+        {
+            self.health = Some(health);
+            use_usable_potion(
+                context,
+                &self.config.potions,
+                &mut self.potions_last_used,
+                health,
+            );
+        }
+        if !self.config.mana_potions.is_empty()
+            && let Update::Ok(mana) = update_detection_task(
+                context,
+                update_millis,
+                &mut self.mana_task,
+                move |detector| {
+                    let (current_bar, max_bar) =
+                        detector.detect_player_current_max_mana_bars(health_bar)?;
+                    let mana = detector.detect_player_mana(current_bar, max_bar)?;
+                    debug!(target: "player", "mana updated {mana:?}");
+                    Ok(mana)
+                },
+            )
+        {
+            self.mana = Some(mana);
+            use_usable_potion(
+                context,
+                &self.config.mana_potions,
+                &mut self.mana_potions_last_used,
+                mana,
+            );
         }
     }
 
     /// Updates whether the player is dead.
-    ///
-    /// Upon being dead, a notification will be scheduled to notify the user.
     #[inline]
     fn update_is_dead_state(&mut self, context: &Context) {
         let Update::Ok(is_dead) =
@@ -1097,11 +1318,6 @@ impl PlayerState {
         else {
             return;
         };
-        if is_dead && !self.is_dead {
-            let _ = context
-                .notification
-                .schedule_notification(NotificationKind::PlayerIsDead);
-        }
         if is_dead {
             let update =
                 update_detection_task(context, 1000, &mut self.is_dead_button_task, |detector| {
@@ -1123,6 +1339,39 @@ impl PlayerState {
     }
 }
 
+/// Sends the key of the first usable potion in `potions` given the `(current, max)` `value`.
+fn use_usable_potion(
+    context: &Context,
+    potions: &Array<PlayerPotion, MAX_POTIONS>,
+    last_used: &mut [Option<Instant>; MAX_POTIONS],
+    (current, max): (u32, u32),
+) {
+    let ratio = current as f32 / max as f32;
+    let now = Instant::now();
+    if let Some(index) = usable_potion_index(potions, last_used, ratio, now) {
+        let _ = context.keys.send(potions[index].key);
+        last_used[index] = Some(now);
+    }
+}
+
+/// Finds the index of the first potion in `potions` that can be used at health or mana `ratio`.
+///
+/// A potion can be used when `ratio` is at or below its threshold and it is not in cooldown.
+#[inline]
+fn usable_potion_index(
+    potions: &Array<PlayerPotion, MAX_POTIONS>,
+    last_used: &[Option<Instant>; MAX_POTIONS],
+    ratio: f32,
+    now: Instant,
+) -> Option<usize> {
+    potions.iter().enumerate().find_map(|(index, potion)| {
+        let in_cooldown = last_used[index].is_some_and(|instant| {
+            now.duration_since(instant).as_millis() < potion.cooldown_millis as u128
+        });
+        (ratio <= potion.below_ratio && !in_cooldown).then_some(index)
+    })
+}
+
 #[inline]
 fn auto_mob_ignore_xs_range_value(x: i32) -> (Range<i32>, u32) {
     let x_start = x - AUTO_MOB_IGNORE_XS_RANGE;
@@ -1133,10 +1382,21 @@ fn auto_mob_ignore_xs_range_value(x: i32) -> (Range<i32>, u32) {
 
 #[cfg(test)]
 mod tests {
-    use std::{assert_matches::assert_matches, collections::HashMap};
+    use std::{
+        assert_matches::assert_matches,
+        collections::HashMap,
+        time::{Duration, Instant},
+    };
 
     use opencv::core::{Point, Rect};
-
+    #[cfg(windows)]
+    use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
+    #[cfg(target_os = "macos")]
+    use platforms::macos::KeyKind;
+
+    use super::{MAX_POTIONS, PlayerPotion, usable_potion_index};
     use crate::{
         Position,
         array::Array,
@@ -1152,6 +1412,19 @@ mod tests {
         64, 44, 192, 172, 191, 191, 157, 107, 206, 193, 55, 115, 68,
     ];
 
+    #[test]
+    fn last_carried_x_delta_ignores_recent_self_moving() {
+        let mut state = PlayerState::default();
+        state.update_velocity(Point::new(0, 0), 10);
+        state.update_velocity(Point::new(2, 0), 11);
+
+        state.self_moving_tick = 9;
+        assert_eq!(state.last_carried_x_delta(), 0);
+
+        state.self_moving_tick = 5;
+        assert_eq!(state.last_carried_x_delta(), 2);
+    }
+
     #[test]
     fn auto_mob_pick_reachable_y_should_ignore_solidified_x_range() {
         let context = Context::new(None, None);
@@ -1341,6 +1614,36 @@ mod tests {
         assert_matches!(state.auto_mob_last_quadrant, Some(Quadrant::TopLeft));
     }
 
+    #[test]
+    fn auto_mob_sub_bound_point_uses_whole_bound() {
+        let mut state = PlayerState {
+            auto_mob_last_quadrant: Some(Quadrant::TopLeft),
+            ..Default::default()
+        };
+        let platforms = vec![Platform::new(60..80, 30)];
+        let mut idle = MinimapIdle::default();
+        idle.platforms = Array::from_iter(find_neighbors(&platforms, 25, 7, 41));
+        idle.bbox = Rect::new(0, 0, 100, 100);
+        let context = Context {
+            minimap: Minimap::Idle(idle),
+            rng: Rng::new(SEED),
+            ..Context::new(None, None)
+        };
+
+        let point = state.auto_mob_sub_bound_point(&context, Rect::new(50, 50, 50, 50));
+
+        assert!(point.x >= 60 && point.x < 80);
+        assert_eq!(point.y, 30);
+        assert_eq!(state.auto_mob_last_quadrant, None);
+        assert_eq!(
+            state.auto_mob_last_quadrant_bound,
+            Some(Rect::new(50, 0, 50, 50))
+        );
+
+        state.auto_mob_clear_last_bound();
+        assert_eq!(state.auto_mob_last_quadrant_bound, None);
+    }
+
     #[test]
     fn auto_mob_pathing_point_fallbacks_to_reachable_y_map() {
         let mut state = PlayerState {
@@ -1367,4 +1670,31 @@ mod tests {
         assert_eq!(point.y, 20); // 100 - 80
         assert_matches!(state.auto_mob_last_quadrant, Some(Quadrant::BottomLeft));
     }
+
+    #[test]
+    fn usable_potion_index_prioritized_and_cooldown() {
+        let mut potions = Array::new();
+        potions.push(PlayerPotion {
+            key: KeyKind::A,
+            below_ratio: 0.2,
+            cooldown_millis: 10000,
+        });
+        potions.push(PlayerPotion {
+            key: KeyKind::B,
+            below_ratio: 0.5,
+            cooldown_millis: 0,
+        });
+        let now = Instant::now();
+        let mut last_used = [None; MAX_POTIONS];
+
+        assert_eq!(usable_potion_index(&potions, &last_used, 0.8, now), None);
+        assert_eq!(usable_potion_index(&potions, &last_used, 0.4, now), Some(1));
+        assert_eq!(usable_potion_index(&potions, &last_used, 0.1, now), Some(0));
+
+        last_used[0] = Some(now - Duration::from_secs(5));
+        assert_eq!(usable_potion_index(&potions, &last_used, 0.1, now), Some(1));
+
+        last_used[0] = Some(now - Duration::from_secs(10));
+        assert_eq!(usable_potion_index(&potions, &last_used, 0.1, now), Some(0));
+    }
 }
@@ -1,6 +1,8 @@
 use opencv::core::Point;
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
@@ -11,7 +13,7 @@ use super::{
 use crate::{
     context::Context,
     minimap::Minimap,
-    player::{MOVE_TIMEOUT, Player},
+    player::Player,
     task::{Update, update_detection_task},
 };
 
@@ -47,7 +49,7 @@ pub fn update_unstucking_context(
         .map(|pos| Point::new(pos.x, idle.bbox.height - pos.y));
     let gamba_mode = gamba_mode || pos.is_none();
 
-    match next_timeout_lifecycle(timeout, MOVE_TIMEOUT) {
+    match next_timeout_lifecycle(timeout, state.config.movement.move_timeout) {
         Lifecycle::Started(timeout) => {
             let has_settings = if !gamba_mode && has_settings.is_none() {
                 match update_detection_task(context, 0, &mut state.unstuck_task, move |detector| {
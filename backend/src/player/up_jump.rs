@@ -1,5 +1,7 @@
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
@@ -15,7 +17,7 @@ use crate::{
     context::Context,
     minimap::Minimap,
     player::{
-        MOVE_TIMEOUT, PlayerAction,
+        PlayerAction,
         actions::{on_action, on_auto_mob_use_key_action},
         state::LastMovement,
         timeout::ChangeAxis,
@@ -24,7 +26,7 @@ use crate::{
 
 const SPAM_DELAY: u32 = 7;
 const SOFT_SPAM_DELAY: u32 = 12;
-const TIMEOUT: u32 = MOVE_TIMEOUT + 3;
+const TIMEOUT_EXTRA: u32 = 3;
 const UP_JUMPED_Y_VELOCITY_THRESHOLD: f32 = 1.3;
 const X_NEAR_STATIONARY_THRESHOLD: f32 = 0.28;
 const TELEPORT_UP_JUMP_THRESHOLD: i32 = 14;
@@ -72,7 +74,7 @@ pub fn update_up_jumping_context(
     match next_moving_lifecycle_with_axis(
         up_jumping.moving,
         state.last_known_pos.expect("in positional context"),
-        TIMEOUT,
+        state.config.movement.move_timeout + TIMEOUT_EXTRA,
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
@@ -203,8 +205,12 @@ pub fn update_up_jumping_context(
                         ..
                     })
                     | PlayerAction::Move(_)
+                    | PlayerAction::SellItems(_)
                     | PlayerAction::SolveRune => None,
-                    PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
+                    PlayerAction::Panic(_)
+                    | PlayerAction::FamiliarsSwapping(_)
+                    | PlayerAction::Combo(_)
+                    | PlayerAction::Mouse(_) => unreachable!(),
                 },
                 || Player::UpJumping(up_jumping.moving(moving)),
             )
@@ -230,6 +236,8 @@ mod tests {
     use opencv::core::Point;
     #[cfg(windows)]
 use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
@@ -1,8 +1,10 @@
 use std::cmp::Ordering;
 
-use opencv::core::Point;
+use opencv::core::{Point, Rect};
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
@@ -15,9 +17,10 @@ use super::{
     timeout::{Lifecycle, next_timeout_lifecycle},
 };
 use crate::{
-    ActionKeyDirection, ActionKeyWith, Class, KeyBinding, LinkKeyBinding, Position,
+    ActionKeyDirection, ActionKeyVerify, ActionKeyWith, Bound, Class, KeyBinding, LinkKeyBinding,
+    Position,
     context::Context,
-    player::{LastMovement, MOVE_TIMEOUT, Moving, Player, on_action_state_mut},
+    player::{LastMovement, Moving, Player, on_action_state_mut},
 };
 
 /// The total number of ticks for changing direction before timing out.
@@ -26,6 +29,10 @@ const CHANGE_DIRECTION_TIMEOUT: u32 = 3;
 /// The tick to which the actual key will be pressed for [`LinkKeyBinding::Along`].
 const LINK_ALONG_PRESS_TICK: u32 = 2;
 
+/// The minimum difference in any color channel mean for [`ActionKeyVerify::region`] to be
+/// considered changed.
+const VERIFY_COLOR_CHANGE_THRESHOLD: f64 = 8.0;
+
 /// The different stages of using key.
 #[derive(Clone, Copy, Debug)]
 pub enum UseKeyStage {
@@ -45,6 +52,9 @@ pub enum UseKeyStage {
     /// for [`UseKey::wait_after_use_ticks`].
     Using(Timeout, bool),
     /// Ensures all [`UseKey::count`] times executed.
+    ///
+    /// Returns to [`UseKeyStage::Precondition`] without counting if [`ActionKeyVerify::region`]
+    /// did not change and there are retries left.
     Postcondition,
 }
 
@@ -58,6 +68,10 @@ pub struct UseKey {
     with: ActionKeyWith,
     wait_before_use_ticks: u32,
     wait_after_use_ticks: u32,
+    verify: Option<ActionKeyVerify>,
+    /// The mean color of [`ActionKeyVerify::region`] before using the key.
+    verify_color: Option<[f64; 3]>,
+    verify_retry_count: u32,
     stage: UseKeyStage,
 }
 
@@ -79,6 +93,7 @@ impl UseKey {
                 wait_before_use_ticks_random_range,
                 wait_after_use_ticks,
                 wait_after_use_ticks_random_range,
+                verify,
                 ..
             }) => {
                 let wait_before =
@@ -95,6 +110,9 @@ impl UseKey {
                     with,
                     wait_before_use_ticks: wait_before,
                     wait_after_use_ticks: wait_after,
+                    verify,
+                    verify_color: None,
+                    verify_retry_count: 0,
                     stage: UseKeyStage::Precondition,
                 }
             }
@@ -120,6 +138,9 @@ impl UseKey {
                     with: mob.with,
                     wait_before_use_ticks: wait_before,
                     wait_after_use_ticks: wait_after,
+                    verify: None,
+                    verify_color: None,
+                    verify_retry_count: 0,
                     stage: UseKeyStage::Precondition,
                 }
             }
@@ -146,12 +167,18 @@ impl UseKey {
                     with: ping_pong.with,
                     wait_before_use_ticks: wait_before,
                     wait_after_use_ticks: wait_after,
+                    verify: None,
+                    verify_color: None,
+                    verify_retry_count: 0,
                     stage: UseKeyStage::Precondition,
                 }
             }
             PlayerAction::FamiliarsSwapping(_)
             | PlayerAction::SolveRune
             | PlayerAction::Panic(_)
+            | PlayerAction::SellItems(_)
+            | PlayerAction::Combo(_)
+            | PlayerAction::Mouse(_)
             | PlayerAction::Move { .. } => {
                 unreachable!()
             }
@@ -169,7 +196,7 @@ impl UseKey {
 pub fn update_use_key_context(
     context: &Context,
     state: &mut PlayerState,
-    use_key: UseKey,
+    mut use_key: UseKey,
 ) -> Player {
     // TODO: Am I cooked?
     let next = match use_key.stage {
@@ -259,6 +286,11 @@ pub fn update_use_key_context(
         UseKeyStage::Using(timeout, completed) => {
             debug_assert!(use_key.link_key.is_some() || !completed);
             debug_assert!(state.stalling_timeout_state.is_none());
+            if !timeout.started && !completed {
+                use_key.verify_color = use_key
+                    .verify
+                    .and_then(|verify| detect_region_mean_color(context, verify.region));
+            }
             match use_key.link_key {
                 Some(LinkKeyBinding::After(_)) => {
                     if !timeout.started {
@@ -319,6 +351,14 @@ pub fn update_use_key_context(
         }
         UseKeyStage::Postcondition => {
             debug_assert!(state.stalling_timeout_state.is_none());
+            if should_retry_use_key(context, use_key) {
+                return Player::UseKey(UseKey {
+                    verify_color: None,
+                    verify_retry_count: use_key.verify_retry_count + 1,
+                    stage: UseKeyStage::Precondition,
+                    ..use_key
+                });
+            }
             if use_key.current_count + 1 < use_key.count {
                 Player::UseKey(UseKey {
                     current_count: use_key.current_count + 1,
@@ -342,7 +382,8 @@ pub fn update_use_key_context(
                 if is_terminal {
                     state.auto_mob_track_ignore_xs(context, false);
                     if state.auto_mob_reachable_y_require_update(y) {
-                        return Some((Player::Stalling(Timeout::default(), MOVE_TIMEOUT), false));
+                        let timeout = state.config.movement.move_timeout;
+                        return Some((Player::Stalling(Timeout::default(), timeout), false));
                     }
                 }
                 Some((next, is_terminal))
@@ -367,7 +408,10 @@ pub fn update_use_key_context(
             PlayerAction::Move(_) => None,
             PlayerAction::FamiliarsSwapping(_)
             | PlayerAction::SolveRune
-            | PlayerAction::Panic(_) => unreachable!(),
+            | PlayerAction::Panic(_)
+            | PlayerAction::SellItems(_)
+            | PlayerAction::Combo(_)
+            | PlayerAction::Mouse(_) => unreachable!(),
         },
         || next,
     )
@@ -394,6 +438,34 @@ fn ensure_use_with(state: &PlayerState, use_key: UseKey) -> bool {
     }
 }
 
+/// Checks whether the key should be re-used because [`ActionKeyVerify::region`] did not change.
+fn should_retry_use_key(context: &Context, use_key: UseKey) -> bool {
+    let Some((verify, before)) = use_key.verify.zip(use_key.verify_color) else {
+        return false;
+    };
+    if use_key.verify_retry_count >= verify.retry_count {
+        return false;
+    }
+    let Some(after) = detect_region_mean_color(context, verify.region) else {
+        return false;
+    };
+
+    before
+        .into_iter()
+        .zip(after)
+        .all(|(before, after)| (before - after).abs() < VERIFY_COLOR_CHANGE_THRESHOLD)
+}
+
+#[inline]
+fn detect_region_mean_color(context: &Context, region: Bound) -> Option<[f64; 3]> {
+    let region = Rect::new(region.x, region.y, region.width, region.height);
+    context
+        .detector
+        .as_ref()?
+        .detect_region_mean_color(region)
+        .ok()
+}
+
 #[inline]
 fn update_link_key(
     context: &Context,
@@ -470,13 +542,16 @@ mod tests {
 
     #[cfg(windows)]
 use platforms::windows::KeyKind;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 
     use crate::{
-        ActionKeyDirection, ActionKeyWith, KeyBinding, LinkKeyBinding,
+        ActionKeyDirection, ActionKeyVerify, ActionKeyWith, Bound, KeyBinding, LinkKeyBinding,
         bridge::MockKeySender,
         context::Context,
+        detect::MockDetector,
         player::{
             Player, PlayerState, Timeout, update_non_positional_context,
             use_key::{UseKey, UseKeyStage, update_use_key_context},
@@ -496,6 +571,9 @@ use platforms::macos::KeyKind;
             with: ActionKeyWith::Stationary,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            verify: None,
+            verify_color: None,
+            verify_retry_count: 0,
             stage: UseKeyStage::Precondition,
         };
 
@@ -542,6 +620,9 @@ use platforms::macos::KeyKind;
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            verify: None,
+            verify_color: None,
+            verify_retry_count: 0,
             stage: UseKeyStage::Precondition,
         };
 
@@ -606,6 +687,9 @@ use platforms::macos::KeyKind;
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            verify: None,
+            verify_color: None,
+            verify_retry_count: 0,
             stage: UseKeyStage::Precondition,
         };
 
@@ -659,6 +743,9 @@ use platforms::macos::KeyKind;
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 10,
             wait_after_use_ticks: 20,
+            verify: None,
+            verify_color: None,
+            verify_retry_count: 0,
             stage: UseKeyStage::Precondition,
         };
 
@@ -719,6 +806,9 @@ use platforms::macos::KeyKind;
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            verify: None,
+            verify_color: None,
+            verify_retry_count: 0,
             stage: UseKeyStage::Using(Timeout::default(), false),
         };
 
@@ -794,4 +884,66 @@ use platforms::macos::KeyKind;
         );
         // test check point by dropping here
     }
+
+    #[test]
+    fn use_key_verify_retry_until_count_exhausted() {
+        let mut keys = MockKeySender::new();
+        keys.expect_send()
+            .withf(|key| matches!(key, KeyKind::A))
+            .times(2)
+            .returning(|_| Ok(()));
+        let mut detector = MockDetector::new();
+        detector
+            .expect_detect_region_mean_color()
+            .returning(|_| Ok([10.0, 10.0, 10.0]));
+        let mut state = PlayerState::default();
+        let context = Context::new(Some(keys), Some(detector));
+        let mut use_key = UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Any,
+            with: ActionKeyWith::Any,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            verify: Some(ActionKeyVerify {
+                region: Bound {
+                    x: 0,
+                    y: 0,
+                    width: 10,
+                    height: 10,
+                },
+                retry_count: 1,
+            }),
+            verify_color: None,
+            verify_retry_count: 0,
+            stage: UseKeyStage::Using(Timeout::default(), false),
+        };
+
+        for retry_count in 0..2 {
+            let Player::UseKey(next) = update_use_key_context(&context, &mut state, use_key) else {
+                panic!("expected use key state");
+            };
+            assert_matches!(next.stage, UseKeyStage::Postcondition);
+            assert_eq!(next.verify_color, Some([10.0, 10.0, 10.0]));
+
+            use_key = next;
+            let player = update_use_key_context(&context, &mut state, use_key);
+            if retry_count == 0 {
+                assert_matches!(
+                    player,
+                    Player::UseKey(UseKey {
+                        verify_retry_count: 1,
+                        stage: UseKeyStage::Precondition,
+                        ..
+                    })
+                );
+                use_key.verify_retry_count = 1;
+                use_key.stage = UseKeyStage::Using(Timeout::default(), false);
+            } else {
+                assert_matches!(player, Player::Idle);
+            }
+        }
+    }
 }
@@ -0,0 +1,373 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use anyhow::{Result, anyhow};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use wasmi::{
+    AsContext, Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, TypedFunc,
+};
+
+use crate::{
+    GameState,
+    database::{Action, KeyBinding},
+    events::Event,
+};
+
+static PLUGINS_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+    env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("plugins")
+});
+
+/// The fuel each plugin call can consume before it is aborted.
+const PLUGIN_CALL_FUEL: u64 = 10_000_000;
+
+/// The maximum bytes of linear memory a plugin can grow to.
+const MAX_PLUGIN_MEMORY: usize = 64 * 1024 * 1024;
+
+/// The maximum length of a message read from a plugin memory.
+const MAX_PLUGIN_MESSAGE_LEN: usize = 64 * 1024;
+
+/// The maximum number of commands a plugin can queue in a single call.
+const MAX_PLUGIN_COMMANDS: usize = 16;
+
+/// A command queued by a plugin through the `komari.push_command` import.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum PluginCommand {
+    /// Starts or stops rotating actions.
+    RotateActions { halting: bool },
+    /// Sends a key once.
+    SendKey { key: KeyBinding },
+}
+
+#[derive(Debug)]
+struct PluginData {
+    commands: Vec<PluginCommand>,
+    limits: StoreLimits,
+}
+
+/// A loaded WASM plugin.
+struct Plugin {
+    name: String,
+    store: Store<PluginData>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    dealloc: TypedFunc<(i32, i32), ()>,
+    on_tick: Option<TypedFunc<(i32, i32), ()>>,
+    on_event: Option<TypedFunc<(i32, i32), ()>>,
+    propose_action: Option<TypedFunc<(i32, i32), i64>>,
+}
+
+impl Plugin {
+    fn new(engine: &Engine, name: String, bytes: &[u8]) -> Result<Self> {
+        let module = Module::new(engine, bytes)?;
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_PLUGIN_MEMORY)
+            .instances(1)
+            .memories(1)
+            .build();
+        let mut store = Store::new(
+            engine,
+            PluginData {
+                commands: vec![],
+                limits,
+            },
+        );
+        store.limiter(|data| &mut data.limits);
+        // The start function is also metered
+        store.set_fuel(PLUGIN_CALL_FUEL)?;
+        let mut linker = Linker::<PluginData>::new(engine);
+        linker.func_wrap("komari", "push_command", push_command)?;
+        let instance = linker.instantiate(&mut store, &module)?.start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| anyhow!("plugin does not export memory"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")?;
+        let dealloc = instance.get_typed_func::<(i32, i32), ()>(&store, "dealloc")?;
+
+        Ok(Self {
+            on_tick: instance.get_typed_func(&store, "on_tick").ok(),
+            on_event: instance.get_typed_func(&store, "on_event").ok(),
+            propose_action: instance.get_typed_func(&store, "propose_action").ok(),
+            name,
+            store,
+            memory,
+            alloc,
+            dealloc,
+        })
+    }
+
+    fn on_tick(&mut self, state: &[u8]) -> Result<Vec<PluginCommand>> {
+        if let Some(on_tick) = self.on_tick {
+            let (ptr, len) = self.write(state)?;
+            on_tick.call(&mut self.store, (ptr, len))?;
+            self.dealloc.call(&mut self.store, (ptr, len))?;
+        }
+        Ok(self.store.data_mut().commands.drain(..).collect())
+    }
+
+    fn on_event(&mut self, event: &[u8]) -> Result<()> {
+        if let Some(on_event) = self.on_event {
+            let (ptr, len) = self.write(event)?;
+            on_event.call(&mut self.store, (ptr, len))?;
+            self.dealloc.call(&mut self.store, (ptr, len))?;
+        }
+        Ok(())
+    }
+
+    fn propose_action(&mut self, state: &[u8]) -> Result<Option<Action>> {
+        let Some(propose_action) = self.propose_action else {
+            return Ok(None);
+        };
+        let (ptr, len) = self.write(state)?;
+        let proposed = propose_action.call(&mut self.store, (ptr, len))?;
+        self.dealloc.call(&mut self.store, (ptr, len))?;
+        let Some((ptr, len)) = unpack(proposed) else {
+            return Ok(None);
+        };
+        let bytes = read(self.memory, &self.store, ptr, len)?;
+        self.dealloc
+            .call(&mut self.store, (ptr as i32, len as i32))?;
+        Ok(Some(serde_json::from_slice(&bytes)?))
+    }
+
+    /// Copies `bytes` into a buffer allocated by the plugin and returns its pointer and length.
+    ///
+    /// The buffer must be freed with [`Self::dealloc`] after the call. This also refuels the plugin
+    /// as it is done before every call.
+    fn write(&mut self, bytes: &[u8]) -> Result<(i32, i32)> {
+        self.store.set_fuel(PLUGIN_CALL_FUEL)?;
+        let len = i32::try_from(bytes.len())?;
+        let ptr = self.alloc.call(&mut self.store, len)?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, bytes)
+            .map_err(|err| anyhow!("failed to write plugin memory {err}"))?;
+        Ok((ptr, len))
+    }
+}
+
+/// Hosts WASM plugins loaded from the `plugins` directory next to the executable.
+///
+/// A plugin is sandboxed to its own memory of at most [`MAX_PLUGIN_MEMORY`] bytes and a limited
+/// fuel per call including its start function. It sees the [`GameState`]
+/// and [`Event`]s as JSON and can only affect the bot through [`PluginCommand`]s and proposed
+/// [`Action`]s. A plugin that fails a call is unloaded.
+///
+/// A plugin must export `memory`, `alloc(len: i32) -> i32` and `dealloc(ptr: i32, len: i32)` for
+/// the host to pass JSON into. The host frees each buffer it passed after the call returns and the
+/// buffer of a proposed action after reading it. It can export any of:
+/// - `on_tick(ptr: i32, len: i32)` called each tick with the [`GameState`]
+/// - `on_event(ptr: i32, len: i32)` called with each emitted [`Event`]
+/// - `propose_action(ptr: i32, len: i32) -> i64` called with the [`GameState`] when the player has
+///   no action and returns the pointer and length of an [`Action`] packed as `ptr << 32 | len` or
+///   `0` for none
+///
+/// A [`PluginCommand`] is queued through the `komari.push_command(ptr: i32, len: i32)` import.
+#[derive(Default)]
+pub struct PluginHost {
+    plugins: Vec<Plugin>,
+    /// The JSON of the [`GameState`] last passed to [`Self::on_tick`].
+    state: Vec<u8>,
+}
+
+impl PluginHost {
+    /// Loads all `.wasm` plugins from the plugins directory.
+    pub fn load(&mut self) {
+        let Ok(entries) = fs::read_dir(PLUGINS_DIR.as_path()) else {
+            return;
+        };
+        let engine = plugin_engine();
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            if path.extension().is_none_or(|extension| extension != "wasm") {
+                continue;
+            }
+            match load_plugin(&engine, &path) {
+                Ok(plugin) => {
+                    info!(target: "plugin", "loaded plugin {}", plugin.name);
+                    self.plugins.push(plugin);
+                }
+                Err(err) => {
+                    error!(target: "plugin", "failed to load plugin {} {err}", path.display())
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Passes `state` to all plugins and returns their queued commands in order.
+    pub fn on_tick(&mut self, state: &GameState) -> Vec<PluginCommand> {
+        self.state.clear();
+        if serde_json::to_writer(&mut self.state, state).is_err() {
+            self.state.clear();
+            return vec![];
+        }
+        let mut commands = vec![];
+        retain(&mut self.plugins, |plugin| {
+            commands.extend(plugin.on_tick(&self.state)?);
+            Ok(())
+        });
+        commands
+    }
+
+    /// Passes `event` to all plugins.
+    pub fn on_event(&mut self, event: &Event) {
+        let Ok(event) = serde_json::to_vec(event) else {
+            return;
+        };
+        retain(&mut self.plugins, |plugin| plugin.on_event(&event));
+    }
+
+    /// Asks the plugins in order for an action to run and returns the first proposed one.
+    ///
+    /// The plugins are passed the [`GameState`] from the last [`Self::on_tick`].
+    pub fn propose_action(&mut self) -> Option<Action> {
+        if self.state.is_empty() {
+            return None;
+        }
+        let mut proposed = None;
+        retain(&mut self.plugins, |plugin| {
+            if proposed.is_none() {
+                proposed = plugin.propose_action(&self.state)?;
+            }
+            Ok(())
+        });
+        proposed
+    }
+}
+
+#[inline]
+fn plugin_engine() -> Engine {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    Engine::new(&config)
+}
+
+/// Calls `f` on each of `plugins` and unloads the ones that failed.
+fn retain(plugins: &mut Vec<Plugin>, mut f: impl FnMut(&mut Plugin) -> Result<()>) {
+    plugins.retain_mut(|plugin| match f(plugin) {
+        Ok(()) => true,
+        Err(err) => {
+            error!(target: "plugin", "unloading plugin {} {err}", plugin.name);
+            false
+        }
+    });
+}
+
+fn load_plugin(engine: &Engine, path: &Path) -> Result<Plugin> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Plugin::new(engine, name, &fs::read(path)?)
+}
+
+fn push_command(mut caller: Caller<'_, PluginData>, ptr: i32, len: i32) {
+    let Some(memory) = caller.get_export("memory").and_then(Extern::into_memory) else {
+        return;
+    };
+    let command = read(memory, &caller, ptr as u32, len as u32)
+        .and_then(|bytes| Ok(serde_json::from_slice::<PluginCommand>(&bytes)?));
+    match command {
+        Ok(_) if caller.data().commands.len() >= MAX_PLUGIN_COMMANDS => {
+            warn!(target: "plugin", "too many queued commands, dropping");
+        }
+        Ok(command) => caller.data_mut().commands.push(command),
+        Err(err) => warn!(target: "plugin", "invalid command {err}"),
+    }
+}
+
+fn read(memory: Memory, store: &impl AsContext, ptr: u32, len: u32) -> Result<Vec<u8>> {
+    let len = len as usize;
+    if len > MAX_PLUGIN_MESSAGE_LEN {
+        return Err(anyhow!("message of {len} bytes is too large"));
+    }
+    let mut bytes = vec![0; len];
+    memory
+        .read(store, ptr as usize, &mut bytes)
+        .map_err(|err| anyhow!("failed to read plugin memory {err}"))?;
+    Ok(bytes)
+}
+
+/// Unpacks the pointer and length of a `ptr << 32 | len` value or [`None`] if it is `0`.
+#[inline]
+fn unpack(packed: i64) -> Option<(u32, u32)> {
+    let packed = packed as u64;
+    (packed != 0).then_some(((packed >> 32) as u32, packed as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The exports required by the host for a test plugin.
+    const REQUIRED_EXPORTS: &str = r#"
+        (memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) i32.const 0)
+        (func (export "dealloc") (param i32 i32))
+    "#;
+
+    fn test_plugin(funcs: &str) -> Result<Plugin> {
+        let wat = format!("(module {REQUIRED_EXPORTS} {funcs})");
+        Plugin::new(&plugin_engine(), "test".to_string(), wat.as_bytes())
+    }
+
+    #[test]
+    fn plugin_new_runs_start_with_fuel() {
+        assert!(test_plugin("(func $start) (start $start)").is_ok());
+        assert!(test_plugin("(func $start (loop (br 0))) (start $start)").is_err());
+    }
+
+    #[test]
+    fn plugin_on_tick_out_of_fuel() {
+        let mut plugin =
+            test_plugin(r#"(func (export "on_tick") (param i32 i32) (loop (br 0)))"#).unwrap();
+
+        assert!(plugin.on_tick(b"{}").is_err());
+    }
+
+    #[test]
+    fn plugin_memory_grow_capped() {
+        let pages = MAX_PLUGIN_MEMORY / 65536 + 1;
+        let mut plugin = test_plugin(&format!(
+            r#"(func (export "on_tick") (param i32 i32)
+                (if (i32.lt_s (memory.grow (i32.const {pages})) (i32.const 0))
+                    (then unreachable)))"#
+        ))
+        .unwrap();
+
+        // Traps on unreachable because growing failed
+        assert!(plugin.on_tick(b"{}").is_err());
+    }
+
+    #[test]
+    fn unpack_pointer_and_length() {
+        assert_eq!(unpack(0), None);
+        assert_eq!(unpack((16 << 32) | 8), Some((16, 8)));
+        assert_eq!(unpack(-1), Some((u32::MAX, u32::MAX)));
+    }
+
+    #[test]
+    fn plugin_command_from_json() {
+        assert_eq!(
+            serde_json::from_str::<PluginCommand>(r#"{"kind":"RotateActions","halting":true}"#)
+                .unwrap(),
+            PluginCommand::RotateActions { halting: true }
+        );
+        assert_eq!(
+            serde_json::from_str::<PluginCommand>(r#"{"kind":"SendKey","key":"A"}"#).unwrap(),
+            PluginCommand::SendKey { key: KeyBinding::A }
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::{database::SafeMode, rng::Rng};
+
+/// The window [`SafeMode::max_keys_per_second`] is counted over.
+const KEYS_WINDOW: Duration = Duration::from_secs(1);
+
+/// The window [`SafeMode::max_inputs_per_minute`] is counted over.
+const INPUTS_WINDOW: Duration = Duration::from_secs(60);
+
+/// The mandatory break state of a [`RateLimiter`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RateLimitBreak {
+    /// The next break starts after the duration.
+    Scheduled(Duration),
+    /// The current break ends after the duration.
+    Breaking(Duration),
+}
+
+/// Enforces the [`SafeMode`] caps on inputs regardless of what is sending them.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    safe_mode: SafeMode,
+    /// The [`Instant`]s of the key presses within [`KEYS_WINDOW`].
+    keys: VecDeque<Instant>,
+    /// The [`Instant`]s of the key presses and mouse clicks within [`INPUTS_WINDOW`].
+    inputs: VecDeque<Instant>,
+    /// The [`Instant`] the next break starts at.
+    break_start: Option<Instant>,
+    /// The [`Instant`] the current break ends at.
+    break_end: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// Sets the caps and restarts the break schedule if `safe_mode` changed.
+    pub fn set(&mut self, safe_mode: &SafeMode) {
+        if self.safe_mode == *safe_mode {
+            return;
+        }
+        self.safe_mode = safe_mode.clone();
+        self.break_start = None;
+        self.break_end = None;
+    }
+
+    /// Starts and ends the mandatory breaks.
+    ///
+    /// Returns `true` if a break has just started.
+    pub fn update(&mut self, rng: &Rng, now: Instant) -> bool {
+        if !self.safe_mode.enabled || self.safe_mode.break_interval_millis == 0 {
+            self.break_start = None;
+            self.break_end = None;
+            return false;
+        }
+
+        if let Some(end) = self.break_end {
+            if now < end {
+                return false;
+            }
+            self.break_end = None;
+            self.break_start = None;
+        }
+        let start = *self.break_start.get_or_insert_with(|| {
+            now + rng.random_jittered_duration(
+                self.safe_mode.break_interval_millis,
+                self.safe_mode.break_interval_jitter_millis,
+            )
+        });
+        if now < start {
+            return false;
+        }
+        self.break_end = Some(
+            now + rng.random_jittered_duration(
+                self.safe_mode.break_duration_millis,
+                self.safe_mode.break_duration_jitter_millis,
+            ),
+        );
+        true
+    }
+
+    /// The current break state or [`None`] if there is no scheduled break.
+    pub fn break_state(&self, now: Instant) -> Option<RateLimitBreak> {
+        if let Some(end) = self.break_end {
+            return Some(RateLimitBreak::Breaking(end.saturating_duration_since(now)));
+        }
+        self.break_start
+            .map(|start| RateLimitBreak::Scheduled(start.saturating_duration_since(now)))
+    }
+
+    /// Records a key press at `now` if it is allowed.
+    #[inline]
+    pub fn try_key(&mut self, now: Instant) -> bool {
+        self.try_acquire(true, now)
+    }
+
+    /// Records a mouse click at `now` if it is allowed.
+    #[inline]
+    pub fn try_mouse(&mut self, now: Instant) -> bool {
+        self.try_acquire(false, now)
+    }
+
+    fn try_acquire(&mut self, is_key: bool, now: Instant) -> bool {
+        if !self.safe_mode.enabled {
+            return true;
+        }
+        if self.break_end.is_some() {
+            return false;
+        }
+
+        prune(&mut self.keys, KEYS_WINDOW, now);
+        prune(&mut self.inputs, INPUTS_WINDOW, now);
+        if (is_key && is_capped(&self.keys, self.safe_mode.max_keys_per_second))
+            || is_capped(&self.inputs, self.safe_mode.max_inputs_per_minute)
+        {
+            return false;
+        }
+        if is_key {
+            self.keys.push_back(now);
+        }
+        self.inputs.push_back(now);
+        true
+    }
+}
+
+#[inline]
+fn prune(instants: &mut VecDeque<Instant>, window: Duration, now: Instant) {
+    while instants
+        .front()
+        .is_some_and(|instant| now.saturating_duration_since(*instant) >= window)
+    {
+        instants.pop_front();
+    }
+}
+
+/// Whether `instants` already reached `cap` with `0` being no cap.
+#[inline]
+fn is_capped(instants: &VecDeque<Instant>, cap: u32) -> bool {
+    cap > 0 && instants.len() >= cap as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+
+    fn rate_limiter(safe_mode: SafeMode) -> RateLimiter {
+        let mut limiter = RateLimiter::default();
+        limiter.set(&SafeMode {
+            enabled: true,
+            ..safe_mode
+        });
+        limiter
+    }
+
+    #[test]
+    fn try_key_capped_per_second() {
+        let mut limiter = rate_limiter(SafeMode {
+            max_keys_per_second: 2,
+            max_inputs_per_minute: 0,
+            ..SafeMode::default()
+        });
+        let now = Instant::now();
+
+        assert!(limiter.try_key(now));
+        assert!(limiter.try_key(now));
+        assert!(!limiter.try_key(now));
+        assert!(limiter.try_mouse(now));
+        assert!(limiter.try_key(now + KEYS_WINDOW));
+    }
+
+    #[test]
+    fn try_mouse_capped_per_minute() {
+        let mut limiter = rate_limiter(SafeMode {
+            max_keys_per_second: 0,
+            max_inputs_per_minute: 2,
+            ..SafeMode::default()
+        });
+        let now = Instant::now();
+
+        assert!(limiter.try_key(now));
+        assert!(limiter.try_mouse(now));
+        assert!(!limiter.try_mouse(now + Duration::from_secs(30)));
+        assert!(limiter.try_mouse(now + INPUTS_WINDOW));
+    }
+
+    #[test]
+    fn update_break_rejects_inputs_until_ended() {
+        let mut limiter = rate_limiter(SafeMode {
+            break_interval_millis: 1000,
+            break_interval_jitter_millis: 0,
+            break_duration_millis: 500,
+            break_duration_jitter_millis: 0,
+            ..SafeMode::default()
+        });
+        let rng = Rng::new(rand::random());
+        let now = Instant::now();
+
+        assert!(!limiter.update(&rng, now));
+        assert_eq!(
+            limiter.break_state(now),
+            Some(RateLimitBreak::Scheduled(Duration::from_millis(1000)))
+        );
+
+        let now = now + Duration::from_millis(1000);
+        assert!(limiter.update(&rng, now));
+        assert!(!limiter.try_key(now));
+        assert_eq!(
+            limiter.break_state(now),
+            Some(RateLimitBreak::Breaking(Duration::from_millis(500)))
+        );
+
+        let now = now + Duration::from_millis(500);
+        assert!(!limiter.update(&rng, now));
+        assert!(limiter.try_key(now));
+        assert_matches!(limiter.break_state(now), Some(RateLimitBreak::Scheduled(_)));
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    sync::LazyLock,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, bail};
+use log::{error, info};
+use opencv::{
+    core::{Mat, MatTraitConst, Point, Rect, Scalar, Size},
+    imgproc::{
+        COLOR_BGRA2BGR, FONT_HERSHEY_SIMPLEX, INTER_AREA, LINE_8, cvt_color_def, put_text_def,
+        rectangle, resize,
+    },
+    videoio::{VideoWriter, VideoWriterTrait, VideoWriterTraitConst},
+};
+
+use crate::database::{VideoFormat, VideoRecording};
+
+/// Number of written frames between each check of the video file size.
+const SIZE_CHECK_INTERVAL: u32 = 30;
+
+/// The half size of the box drawn around the player on the minimap.
+const PLAYER_BOX_HALF_SIZE: i32 = 4;
+
+static RECORDINGS_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+    env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("recordings")
+});
+
+/// The detected states drawn on top of a recorded frame.
+#[derive(Debug, Default)]
+pub struct VideoOverlay {
+    /// The minimap bounding box in frame coordinates.
+    pub minimap: Option<Rect>,
+    /// The player box in frame coordinates.
+    pub player: Option<Rect>,
+    pub state: Option<String>,
+}
+
+struct Recording {
+    path: PathBuf,
+    format: VideoFormat,
+    /// The writer lazily opened on the first frame since the frame size must be known.
+    writer: Option<(VideoWriter, Size)>,
+    last_frame: Option<Instant>,
+    frames: u32,
+}
+
+/// Records the capture stream to a video file.
+#[derive(Default)]
+pub struct VideoRecorder {
+    recording: Option<Recording>,
+}
+
+impl VideoRecorder {
+    /// Starts a new recording and returns the path of the video file.
+    ///
+    /// The current recording, if any, is stopped first.
+    pub fn start(&mut self, format: VideoFormat) -> Result<PathBuf> {
+        self.stop();
+        fs::create_dir_all(RECORDINGS_DIR.as_path())?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let extension = match format {
+            VideoFormat::Mp4 => "mp4",
+            VideoFormat::Avi => "avi",
+        };
+        let path = RECORDINGS_DIR.join(format!("{millis}.{extension}"));
+        self.recording = Some(Recording {
+            path: path.clone(),
+            format,
+            writer: None,
+            last_frame: None,
+            frames: 0,
+        });
+        info!(target: "recording", "started recording to {}", path.display());
+        Ok(path)
+    }
+
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(mut recording) = self.recording.take() {
+            if let Some((writer, _)) = recording.writer.as_mut() {
+                let _ = writer.release();
+            }
+            info!(target: "recording", "stopped recording {} frames", recording.frames);
+        }
+    }
+
+    /// Writes `mat` with `overlay` to the video if enough time has passed since the last frame.
+    ///
+    /// `overlay` is only called when a frame is written. The recording is stopped when writing
+    /// fails or the video file exceeds [`VideoRecording::max_size_mb`].
+    pub fn update(
+        &mut self,
+        now: Instant,
+        mat: &impl MatTraitConst,
+        settings: &VideoRecording,
+        overlay: impl FnOnce() -> VideoOverlay,
+    ) {
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+        if !is_frame_due(recording.last_frame, now, settings.fps) {
+            return;
+        }
+        recording.last_frame = Some(now);
+
+        let result = write_frame(recording, mat, settings.fps, &overlay()).and_then(|_| {
+            recording.frames += 1;
+            if recording.frames % SIZE_CHECK_INTERVAL == 0 {
+                let bytes = fs::metadata(&recording.path)?.len();
+                Ok(exceeds_max_size(bytes, settings.max_size_mb))
+            } else {
+                Ok(false)
+            }
+        });
+        match result {
+            Ok(false) => (),
+            Ok(true) => {
+                info!(target: "recording", "video file reached maximum size");
+                self.stop();
+            }
+            Err(err) => {
+                error!(target: "recording", "failed to write video frame {err}");
+                self.stop();
+            }
+        }
+    }
+}
+
+fn write_frame(
+    recording: &mut Recording,
+    mat: &impl MatTraitConst,
+    fps: u32,
+    overlay: &VideoOverlay,
+) -> Result<()> {
+    let mut frame = Mat::default();
+    cvt_color_def(mat, &mut frame, COLOR_BGRA2BGR)?;
+    draw_overlay(&mut frame, overlay)?;
+
+    if recording.writer.is_none() {
+        let fourcc = match recording.format {
+            VideoFormat::Mp4 => VideoWriter::fourcc('m', 'p', '4', 'v')?,
+            VideoFormat::Avi => VideoWriter::fourcc('M', 'J', 'P', 'G')?,
+        };
+        let size = frame.size()?;
+        let writer = VideoWriter::new(
+            recording.path.to_str().unwrap(),
+            fourcc,
+            fps.max(1) as f64,
+            size,
+            true,
+        )?;
+        if !writer.is_opened()? {
+            bail!("unable to open video writer");
+        }
+        recording.writer = Some((writer, size));
+    }
+
+    let (writer, size) = recording.writer.as_mut().unwrap();
+    // The captured frame can change size (e.g. resized window) but the video cannot
+    if frame.size()? != *size {
+        let mut resized = Mat::default();
+        resize(&frame, &mut resized, *size, 0.0, 0.0, INTER_AREA)?;
+        frame = resized;
+    }
+    writer.write(&frame)?;
+    Ok(())
+}
+
+fn draw_overlay(frame: &mut Mat, overlay: &VideoOverlay) -> Result<()> {
+    if let Some(minimap) = overlay.minimap {
+        rectangle(
+            frame,
+            minimap,
+            Scalar::new(255.0, 0.0, 0.0, 0.0),
+            1,
+            LINE_8,
+            0,
+        )?;
+    }
+    if let Some(player) = overlay.player {
+        rectangle(
+            frame,
+            player,
+            Scalar::new(0.0, 255.0, 0.0, 0.0),
+            1,
+            LINE_8,
+            0,
+        )?;
+    }
+    if let Some(state) = overlay.state.as_deref() {
+        let origin = Point::new(10, frame.rows() - 10);
+        put_text_def(
+            frame,
+            state,
+            origin,
+            FONT_HERSHEY_SIMPLEX,
+            0.6,
+            Scalar::new(0.0, 255.0, 255.0, 0.0),
+        )?;
+    }
+    Ok(())
+}
+
+/// Converts the player `pos` on the minimap `bbox` to the player box in frame coordinates.
+pub fn player_overlay_box(bbox: Rect, pos: Point) -> Rect {
+    let center = Point::new(bbox.x + pos.x, bbox.y + bbox.height - pos.y);
+    Rect::new(
+        center.x - PLAYER_BOX_HALF_SIZE,
+        center.y - PLAYER_BOX_HALF_SIZE,
+        PLAYER_BOX_HALF_SIZE * 2,
+        PLAYER_BOX_HALF_SIZE * 2,
+    )
+}
+
+#[inline]
+fn is_frame_due(last_frame: Option<Instant>, now: Instant, fps: u32) -> bool {
+    let interval = Duration::from_secs(1) / fps.max(1);
+    last_frame.is_none_or(|instant| now.duration_since(instant) >= interval)
+}
+
+#[inline]
+fn exceeds_max_size(bytes: u64, max_size_mb: u32) -> bool {
+    max_size_mb != 0 && bytes >= max_size_mb as u64 * 1024 * 1024
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_frame_due_rate_limits() {
+        let now = Instant::now();
+
+        assert!(is_frame_due(None, now, 10));
+        assert!(!is_frame_due(
+            Some(now),
+            now + Duration::from_millis(50),
+            10
+        ));
+        assert!(is_frame_due(
+            Some(now),
+            now + Duration::from_millis(100),
+            10
+        ));
+        // Zero fps is treated as one frame per second
+        assert!(!is_frame_due(
+            Some(now),
+            now + Duration::from_millis(500),
+            0
+        ));
+    }
+
+    #[test]
+    fn exceeds_max_size_unlimited_when_zero() {
+        assert!(!exceeds_max_size(u64::MAX, 0));
+        assert!(!exceeds_max_size(1024 * 1024 - 1, 1));
+        assert!(exceeds_max_size(1024 * 1024, 1));
+    }
+
+    #[test]
+    fn player_overlay_box_inverts_y() {
+        let bbox = Rect::new(10, 20, 100, 50);
+
+        assert_eq!(
+            player_overlay_box(bbox, Point::new(30, 10)),
+            Rect::new(36, 56, 8, 8)
+        );
+    }
+}
@@ -1,11 +1,17 @@
-use std::sync::LazyLock;
-#[cfg(debug_assertions)]
-use std::time::Instant;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::Path,
+    process::Command,
+    sync::LazyLock,
+    thread,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 
 #[cfg(debug_assertions)]
 use include_dir::{Dir, include_dir};
-use log::debug;
-use opencv::core::{MatTraitConst, MatTraitConstManual, Vec4b};
+use log::{debug, error, info, warn};
+use opencv::core::{MatTraitConst, MatTraitConstManual, Point, Rect, Vec4b};
 #[cfg(debug_assertions)]
 use opencv::{
     core::{Mat, ModifyInplace, Vector},
@@ -13,14 +19,27 @@ use opencv::{
     imgproc::{COLOR_BGR2BGRA, cvt_color_def},
 };
 #[cfg(windows)]
-use platforms::windows::{Handle, KeyInputKind, KeyKind, KeyReceiver, query_capture_handles};
+use platforms::windows::{
+    FocusChange, FocusMonitor, Handle, KeyInputKind, KeyKind, KeyReceiver, ProcessSupervisor,
+    arrange_window, close_window, launch_process, query_capture_handles,
+};
+#[cfg(target_os = "linux")]
+use platforms::linux::{
+    FocusChange, FocusMonitor, Handle, KeyInputKind, KeyKind, KeyReceiver, ProcessSupervisor,
+    arrange_window, close_window, launch_process, query_capture_handles,
+};
 #[cfg(target_os = "macos")]
-use platforms::macos::{Handle, KeyInputKind, KeyKind, KeyReceiver, query_capture_handles};
+use platforms::macos::{
+    FocusChange, FocusMonitor, Handle, KeyInputKind, KeyKind, KeyReceiver, ProcessSupervisor,
+    arrange_window, close_window, launch_process, query_capture_handles,
+};
 #[cfg(debug_assertions)]
 use rand::distr::{Alphanumeric, SampleString};
 use strum::IntoEnumIterator;
 use tokio::sync::broadcast;
 
+#[cfg(debug_assertions)]
+use crate::bench::{BenchmarkFrame, BenchmarkLatency, run_benchmarks};
 #[cfg(debug_assertions)]
 use crate::debug::{
     save_image_for_training, save_image_for_training_to, save_minimap_for_training,
@@ -30,20 +49,59 @@ use crate::detect::{ArrowsCalibrating, ArrowsState, CachedDetector, Detector};
 #[cfg(debug_assertions)]
 use crate::mat::OwnedMat;
 use crate::{
-    Action, ActionCondition, ActionConfigurationCondition, ActionKey, BoundQuadrant, CaptureMode,
-    Character, GameState, KeyBinding, KeyBindingConfiguration, Minimap as MinimapData, PotionMode,
-    RequestHandler, RotationMode, RotatorMode, Settings,
-    bridge::{ImageCapture, ImageCaptureKind, KeySenderMethod},
-    buff::{BuffKind, BuffState},
+    Action, ActionCondition, ActionConfiguration, ActionConfigurationCondition, ActionKey,
+    BotStatus, BoundQuadrant, CaptureMode, CaptureWindow, Character, FamiliarSlotKind, GameState,
+    InventoryFullBehavior, KeyBinding, KeyBindingConfiguration, Minimap as MinimapData,
+    MinimapPlayerKind, PotionMode, RequestHandler, RotationMode, RotatorMode, Settings,
+    anti_afk::{AntiAfkInput, AntiAfkKey, AntiAfkRunner},
+    avoidance::AvoidanceZones,
+    breaks::{BreakHold, BreakScheduler, BreakTransition, is_at_break_position},
+    bridge::{ImageCapture, ImageCaptureKind, KeySenderMethod, KeyTiming, MouseAction},
+    buff::{Buff, BuffKind, BuffState},
+    capture_window::{capture_window_of, find_capture_window},
+    client_restart::{ClientRestartAction, ClientRestarter},
+    config::config,
     context::Context,
-    database::{InputMethod, Platform as PlatformData},
-    minimap::{Minimap, MinimapState},
-    player::{PlayerState, Quadrant},
+    database::{
+        ClientPoint, InputMethod, ItineraryExit, ItineraryStep, PanicStepKind,
+        Platform as PlatformData, RoutineStepKind, Session, query_characters, query_minimaps,
+        upsert_character, upsert_routine_run, upsert_settings,
+    },
+    detect::{FamiliarRank, FamiliarSlot, OtherPlayerKind},
+    diagnostics,
+    events::{Event, EventBus, EventRecord, EventSnapshot},
+    following::{FollowingLink, should_follow},
+    itinerary::{ItineraryRunner, ItineraryUpdate},
+    latency::{InputLatency, LatencyTester},
+    login::{LoginRunner, LoginStep},
+    minimap::{Minimap, MinimapState, find_matching_minimap, minimap_fingerprint, minimap_matches},
+    overlay::{self, OverlayStatus},
+    panic_playbook::{PanicPlaybookRunner, PanicTrigger},
+    player::{
+        MAX_POTIONS, MovementThresholds, PanicTo, Panicking, Player, PlayerPotion, PlayerState,
+        Quadrant,
+    },
+    plugin::{PluginCommand, PluginHost},
     poll_request,
-    rotator::{Rotator, RotatorBuildArgs},
+    rate_limiter::RateLimitBreak,
+    recording::{VideoOverlay, VideoRecorder, player_overlay_box},
+    rotator::{ACTION_ID_PLUGIN, Rotator, RotatorBuildArgs},
+    routine::RoutineRunner,
+    schedule::PresetScheduler,
+    self_test::{self, ModelDiagnostics},
+    session::SessionTracker,
+    shutdown::{ShutdownRunner, ShutdownStep, session_summary},
     skill::SkillKind,
+    stats::{ActionStats, ActionStatsTracker, ExpStats, MesoStats, RuneStats},
+    suspension::{CaptureSuspension, CaptureTransition},
+    update::{UpdateChecker, UpdateInfo},
+    watchdog::WatchdogState,
+    wizard::{CaptureTestResult, CaptureTester, suggested_window},
 };
 
+/// The number of upcoming normal actions included in [`GameState`].
+const UPCOMING_NORMAL_ACTIONS_COUNT: usize = 5;
+
 static GAME_STATE: LazyLock<broadcast::Sender<GameState>> =
     LazyLock::new(|| broadcast::channel(1).0);
 
@@ -54,9 +112,37 @@ pub struct DefaultRequestHandler<'a> {
     pub buffs: &'a mut Vec<(BuffKind, KeyBinding)>,
     pub buff_states: &'a mut Vec<BuffState>,
     pub actions: &'a mut Vec<Action>,
+    /// The edited actions of the current preset waiting for the next rotation cycle to apply.
+    pub pending_actions: &'a mut Option<Vec<Action>>,
     pub rotator: &'a mut Rotator,
     pub player: &'a mut PlayerState,
     pub minimap: &'a mut MinimapState,
+    pub exp_stats: &'a mut ExpStats,
+    pub meso_stats: &'a mut MesoStats,
+    pub rune_stats: &'a mut RuneStats,
+    pub action_stats: &'a mut ActionStatsTracker,
+    pub avoidance_zones: &'a mut AvoidanceZones,
+    pub panic_playbook: &'a mut PanicPlaybookRunner,
+    pub routines: &'a mut RoutineRunner,
+    pub itinerary: &'a mut ItineraryRunner,
+    pub login: &'a mut LoginRunner,
+    pub shutdown: &'a mut ShutdownRunner,
+    pub watchdog: &'a mut WatchdogState,
+    pub suspension: &'a mut CaptureSuspension,
+    pub focus_monitor: &'a mut FocusMonitor,
+    pub process_supervisor: &'a mut ProcessSupervisor,
+    pub client_restarter: &'a mut ClientRestarter,
+    pub events: &'a mut EventBus,
+    pub preset_scheduler: &'a mut PresetScheduler,
+    pub video_recorder: &'a mut VideoRecorder,
+    pub session: &'a mut SessionTracker,
+    pub update_checker: &'a mut UpdateChecker,
+    pub latency_tester: &'a mut LatencyTester,
+    pub capture_tester: &'a mut CaptureTester,
+    pub anti_afk: &'a mut AntiAfkRunner,
+    pub breaks: &'a mut BreakScheduler,
+    pub following: &'a mut FollowingLink,
+    pub plugins: &'a mut PluginHost,
     pub key_sender: &'a broadcast::Sender<KeyBinding>,
     pub key_receiver: &'a mut KeyReceiver,
     pub image_capture: &'a mut ImageCapture,
@@ -73,60 +159,151 @@ impl DefaultRequestHandler<'_> {
         poll_request(self);
 
         if GAME_STATE.is_empty() {
-            // TODO: Separate into variables for better readability
-            let game_state = GameState {
-                position: self.player.last_known_pos.map(|pos| (pos.x, pos.y)),
-                health: self.player.health,
-                state: self.context.player.to_string(),
-                normal_action: self.player.normal_action_name(),
-                priority_action: self.player.priority_action_name(),
-                erda_shower_state: self.context.skills[SkillKind::ErdaShower].to_string(),
-                destinations: self
-                    .player
-                    .last_destinations
-                    .clone()
-                    .map(|points| {
-                        points
-                            .into_iter()
-                            .map(|point| (point.x, point.y))
-                            .collect::<Vec<_>>()
+            let _ = GAME_STATE.send(self.game_state(true));
+        }
+    }
+
+    /// The current [`GameState`] with [`GameState::frame`] extracted only if
+    /// `with_minimap_frame` is true.
+    fn game_state(&self, with_minimap_frame: bool) -> GameState {
+        let destinations = self
+            .player
+            .last_destinations
+            .clone()
+            .map(|points| {
+                points
+                    .into_iter()
+                    .map(|point| (point.x, point.y))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let path = self
+            .player
+            .last_known_pos
+            .filter(|_| !destinations.is_empty())
+            .map(|pos| (pos.x, pos.y))
+            .into_iter()
+            .chain(destinations.iter().copied())
+            .collect::<Vec<_>>();
+        let (rune, has_elite_boss, elite_boss, other_players) =
+            if let Minimap::Idle(idle) = self.context.minimap {
+                let other_players = idle
+                    .other_players()
+                    .into_iter()
+                    .map(|(kind, point)| {
+                        let kind = match kind {
+                            OtherPlayerKind::Guildie => MinimapPlayerKind::Guildie,
+                            OtherPlayerKind::Stranger => MinimapPlayerKind::Stranger,
+                            OtherPlayerKind::Friend => MinimapPlayerKind::Friend,
+                        };
+                        (point.x, point.y, kind)
                     })
-                    .unwrap_or_default(),
-                halting: self.context.halting,
-                frame: self
-                    .context
-                    .detector
-                    .as_ref()
-                    .map(|detector| detector.mat())
-                    .and_then(|mat| extract_minimap(self.context, mat)),
-                platforms_bound: if self
-                    .minimap
-                    .data()
-                    .is_some_and(|data| data.auto_mob_platforms_bound)
-                    && let Minimap::Idle(idle) = self.context.minimap
-                {
-                    idle.platforms_bound.map(|bound| bound.into())
-                } else {
-                    None
+                    .collect::<Vec<_>>();
+                (
+                    idle.rune().map(|rune| (rune.x, rune.y)),
+                    idle.has_elite_boss(),
+                    idle.elite_boss().map(|boss| (boss.x, boss.y)),
+                    other_players,
+                )
+            } else {
+                (None, false, None, vec![])
+            };
+        let safe_mode_break = self.context.keys.rate_limit_break();
+        let (safe_mode_break_in_millis, safe_mode_breaking_millis) = match safe_mode_break {
+            Some(RateLimitBreak::Scheduled(duration)) => (Some(duration.as_millis() as u64), None),
+            Some(RateLimitBreak::Breaking(duration)) => (None, Some(duration.as_millis() as u64)),
+            None => (None, None),
+        };
+        // TODO: Separate into variables for better readability
+        GameState {
+            position: self.player.last_known_pos.map(|pos| (pos.x, pos.y)),
+            health: self.player.health,
+            mp: self.player.mana,
+            state: self.context.player.to_string(),
+            normal_action: self.player.normal_action_name(),
+            priority_action: self.player.priority_action_name(),
+            erda_shower_state: self.context.skills[SkillKind::ErdaShower].to_string(),
+            destinations,
+            path,
+            rune,
+            has_elite_boss,
+            elite_boss,
+            other_players,
+            halting: self.context.halting,
+            frame: self
+                .context
+                .detector
+                .as_ref()
+                .filter(|_| with_minimap_frame)
+                .map(|detector| detector.mat())
+                .and_then(|mat| extract_minimap(self.context, mat)),
+            platforms_bound: if self
+                .minimap
+                .data()
+                .is_some_and(|data| data.auto_mob_platforms_bound)
+                && let Minimap::Idle(idle) = self.context.minimap
+            {
+                idle.platforms_bound.map(|bound| bound.into())
+            } else {
+                None
+            },
+            portals: if let Minimap::Idle(idle) = self.context.minimap {
+                idle.portals()
+                    .into_iter()
+                    .map(|portal| portal.into())
+                    .collect::<Vec<_>>()
+            } else {
+                vec![]
+            },
+            auto_mob_quadrant: self.player.auto_mob_last_quadrant().map(
+                |quadrant| match quadrant {
+                    Quadrant::TopLeft => BoundQuadrant::TopLeft,
+                    Quadrant::TopRight => BoundQuadrant::TopRight,
+                    Quadrant::BottomRight => BoundQuadrant::BottomRight,
+                    Quadrant::BottomLeft => BoundQuadrant::BottomLeft,
                 },
-                portals: if let Minimap::Idle(idle) = self.context.minimap {
-                    idle.portals()
-                        .into_iter()
-                        .map(|portal| portal.into())
+            ),
+            exp_per_hour: self.exp_stats.exp_per_hour(),
+            exp_percent_per_hour: self.exp_stats.exp_percent_per_hour(),
+            mesos_per_hour: self.meso_stats.mesos_per_hour(),
+            detection_scores: self.context.detection_scores.get(),
+            minimap_confidence: if let Minimap::Idle(idle) = self.context.minimap {
+                Some(idle.confidence)
+            } else {
+                None
+            },
+            upcoming_normal_actions: self
+                .rotator
+                .upcoming_normal_actions(UPCOMING_NORMAL_ACTIONS_COUNT),
+            upcoming_priority_actions: self.rotator.upcoming_priority_actions(Instant::now()),
+            familiar_slots: self
+                .player
+                .familiar_setup
+                .as_ref()
+                .map(|setup| {
+                    setup
+                        .slots
+                        .iter()
+                        .map(|slot| match slot {
+                            FamiliarSlot::Free => FamiliarSlotKind::Free,
+                            FamiliarSlot::Occupied(Some(FamiliarRank::Rare)) => {
+                                FamiliarSlotKind::Rare
+                            }
+                            FamiliarSlot::Occupied(Some(FamiliarRank::Epic)) => {
+                                FamiliarSlotKind::Epic
+                            }
+                            FamiliarSlot::Occupied(None) => FamiliarSlotKind::Unknown,
+                        })
                         .collect::<Vec<_>>()
-                } else {
-                    vec![]
-                },
-                auto_mob_quadrant: self.player.auto_mob_last_quadrant().map(|quadrant| {
-                    match quadrant {
-                        Quadrant::TopLeft => BoundQuadrant::TopLeft,
-                        Quadrant::TopRight => BoundQuadrant::TopRight,
-                        Quadrant::BottomRight => BoundQuadrant::BottomRight,
-                        Quadrant::BottomLeft => BoundQuadrant::BottomLeft,
-                    }
-                }),
-            };
-            let _ = GAME_STATE.send(game_state);
+                })
+                .unwrap_or_default(),
+            familiar_slots_matched: self
+                .player
+                .familiar_setup
+                .as_ref()
+                .map(|setup| setup.matched),
+            safe_mode_break_in_millis,
+            safe_mode_breaking_millis,
         }
     }
 
@@ -173,40 +350,66 @@ impl DefaultRequestHandler<'_> {
     }
 
     fn update_rotator_actions(&mut self) {
+        self.build_rotator_actions(false);
+    }
+
+    /// Builds the rotator actions, keeping the priority actions queue and timers if `rebuild`.
+    fn build_rotator_actions(&mut self, rebuild: bool) {
         let mode = self
             .minimap
             .data()
-            .map(|minimap| match minimap.rotation_mode {
-                RotationMode::StartToEnd => RotatorMode::StartToEnd,
-                RotationMode::StartToEndThenReverse => RotatorMode::StartToEndThenReverse,
-                RotationMode::AutoMobbing => RotatorMode::AutoMobbing(
-                    minimap.rotation_mobbing_key,
-                    minimap.rotation_auto_mob_bound,
-                ),
-                RotationMode::PingPong => RotatorMode::PingPong(
-                    minimap.rotation_mobbing_key,
-                    minimap.rotation_ping_pong_bound,
-                ),
-            })
+            .map(config_rotator_mode)
             .unwrap_or_default();
         let reset_on_erda = self
             .minimap
             .data()
             .map(|minimap| minimap.actions_any_reset_on_erda_condition)
             .unwrap_or_default();
+        let pickup_drops = self
+            .minimap
+            .data()
+            .is_some_and(|minimap| minimap.auto_mob_pickup_drops);
+        let minimap_id = self.minimap.data().and_then(|minimap| minimap.id);
         let actions = self
             .character
             .as_ref()
             .map(|character| {
-                config_actions(character)
+                config_actions(character, minimap_id)
                     .into_iter()
                     .chain(self.actions.iter().copied())
                     .collect::<Vec<_>>()
             })
             .unwrap_or_default();
+        let burst_actions = self
+            .character
+            .as_ref()
+            .map(|character| config_burst_actions(character, minimap_id))
+            .unwrap_or_default();
+        let elite_boss_fight_actions = self
+            .minimap
+            .data()
+            .and_then(|minimap| {
+                minimap
+                    .elite_boss_fight_preset
+                    .as_ref()
+                    .and_then(|preset| minimap.actions.get(preset))
+            })
+            .cloned()
+            .unwrap_or_default();
         let args = RotatorBuildArgs {
             mode,
             actions: actions.as_slice(),
+            burst_actions: burst_actions.as_slice(),
+            burst_interval_millis: self
+                .character
+                .as_ref()
+                .map(|character| character.burst_window.interval_millis)
+                .unwrap_or_default(),
+            combos: self
+                .character
+                .as_ref()
+                .map(|character| character.combos.as_slice())
+                .unwrap_or_default(),
             buffs: self.buffs,
             familiar_essence_key: self
                 .character
@@ -216,6 +419,9 @@ impl DefaultRequestHandler<'_> {
             familiar_swappable_slots: self.settings.familiars.swappable_familiars,
             familiar_swappable_rarities: &self.settings.familiars.swappable_rarities,
             familiar_swap_check_millis: self.settings.familiars.swap_check_millis,
+            familiar_swap_when_points_depleted: self.settings.familiars.swap_when_points_depleted,
+            familiar_rarity_priority: self.settings.familiars.rarity_priority,
+            familiar_level_priority: self.settings.familiars.level_priority,
             elite_boss_behavior: self.character.as_ref().and_then(|character| {
                 character
                     .elite_boss_behavior_enabled
@@ -226,21 +432,1401 @@ impl DefaultRequestHandler<'_> {
                 .as_ref()
                 .map(|character| character.elite_boss_behavior_key)
                 .unwrap_or_default(),
-            enable_panic_mode: self.settings.enable_panic_mode,
+            elite_boss_fight_actions: elite_boss_fight_actions.as_slice(),
             enable_rune_solving: self.settings.enable_rune_solving,
             enable_familiars_swapping: self.settings.familiars.enable_familiars_swapping,
             enable_reset_normal_actions_on_erda: reset_on_erda,
+            auto_mob_pickup_key: self.character.as_ref().and_then(|character| {
+                (pickup_drops && character.pickup_key.enabled).then_some(character.pickup_key.key)
+            }),
+            auto_mob_pickup_ignore_millis: self
+                .character
+                .as_ref()
+                .map(|character| character.pickup_ignore_millis)
+                .unwrap_or_default(),
+            auto_mob_sequence: self
+                .minimap
+                .data()
+                .map(|minimap| minimap.rotation_auto_mob_sequence.as_slice())
+                .unwrap_or_default(),
+            auto_mob_regions: self
+                .minimap
+                .data()
+                .map(|minimap| minimap.rotation_auto_mob_regions.as_slice())
+                .unwrap_or_default(),
+            auto_mob_region_order: self
+                .minimap
+                .data()
+                .map(|minimap| minimap.rotation_auto_mob_region_order)
+                .unwrap_or_default(),
+            ping_pong_bands: self
+                .minimap
+                .data()
+                .map(|minimap| minimap.rotation_ping_pong_bands.as_slice())
+                .unwrap_or_default(),
+            sell_items: self
+                .character
+                .as_ref()
+                .map(|character| &character.sell_items)
+                .filter(|sell_items| sell_items.enabled),
+            loadout_id: self
+                .character
+                .as_ref()
+                .and_then(|character| character.active_loadout())
+                .map(|loadout| loadout.id),
+        };
+
+        if rebuild {
+            self.rotator.rebuild_actions(args);
+        } else {
+            self.rotator.build_actions(args);
+        }
+    }
+
+    /// Responds to the inventory becoming full according to the character configuration.
+    pub fn update_inventory_full(&mut self) {
+        let Some(character) = self
+            .character
+            .as_ref()
+            .filter(|character| character.inventory_full_behavior_enabled)
+        else {
+            return;
+        };
+        let behavior = character.inventory_full_behavior;
+        let script = character.inventory_full_script.clone();
+
+        debug!(target: "handler", "inventory full, responding with {behavior}");
+        match behavior {
+            InventoryFullBehavior::Stop => {
+                self.update_context_halting(true, true);
+            }
+            InventoryFullBehavior::GoToTown => {
+                self.update_context_halting(true, false);
+                self.context.player = Player::Panicking(Panicking::new(PanicTo::Town));
+            }
+            InventoryFullBehavior::RunScript => {
+                if let Err(err) = spawn_script(&script) {
+                    error!(target: "handler", "failed to run inventory full script {script}: {err}");
+                }
+            }
+        }
+    }
+
+    /// Starts the panic playbook configured for `trigger` if there is none running.
+    ///
+    /// The bot is halted while the playbook runs so that the rotator does not interrupt the steps.
+    pub fn start_panic_playbook(&mut self, trigger: PanicTrigger) {
+        if self.minimap.data().is_none() || self.character.is_none() {
+            return;
+        }
+        let playbooks = &self.settings.panic_playbooks;
+        let playbook = match trigger {
+            PanicTrigger::StrangerDetected => &playbooks.stranger_detected,
+            PanicTrigger::GmWhisper => &playbooks.gm_whisper,
+            PanicTrigger::DetectionFailureStreak => &playbooks.detection_failure_streak,
+        };
+        let rotating = !self.context.halting;
+        if self
+            .panic_playbook
+            .start(trigger, playbook, rotating, Instant::now())
+        {
+            debug!(target: "handler", "panic playbook started for {trigger}");
+            self.update_context_halting(true, false);
+        }
+    }
+
+    /// Checks for panic triggers and runs the next step of the running panic playbook.
+    ///
+    /// Returns `true` if a notification should be sent.
+    pub fn update_panic_playbook(&mut self) -> bool {
+        let now = Instant::now();
+        if !self.context.halting {
+            let has_stranger = matches!(
+                self.context.minimap,
+                Minimap::Idle(idle) if idle.has_any_other_player()
+            );
+            let detection_failing =
+                self.minimap.data().is_some() && matches!(self.context.player, Player::Detecting);
+            let playbooks = &self.settings.panic_playbooks;
+            let streak_millis = playbooks.detection_failure_streak_millis;
+            if has_stranger {
+                self.start_panic_playbook(PanicTrigger::StrangerDetected);
+            } else if self.panic_playbook.update_detection_failure_streak(
+                detection_failing,
+                streak_millis,
+                now,
+            ) {
+                self.start_panic_playbook(PanicTrigger::DetectionFailureStreak);
+            }
+        }
+        if self.panic_playbook.trigger().is_none() {
+            return false;
+        }
+
+        if let Some(step) = self.panic_playbook.next_step(now) {
+            debug!(target: "handler", "running panic step {}", step.kind);
+            match step.kind {
+                PanicStepKind::StopInput => {
+                    // Resets immediately instead of on the next player update so that a later
+                    // going to town or exiting step is not reset too
+                    self.update_context_halting(true, false);
+                    self.context.player = Player::Idle;
+                }
+                PanicStepKind::GoToTown => {
+                    self.context.player = Player::Panicking(Panicking::new(PanicTo::Town));
+                }
+                PanicStepKind::ChangeChannel => {
+                    self.context.player =
+                        Player::Panicking(Panicking::new_standalone(PanicTo::Channel));
+                }
+                PanicStepKind::ExitToLobby => {
+                    self.context.player = Player::Panicking(Panicking::new(PanicTo::Lobby));
+                }
+                PanicStepKind::RunScript => {
+                    if let Err(err) = spawn_script(&step.script) {
+                        error!(target: "handler", "failed to run panic script {}: {err}", step.script);
+                    }
+                }
+                PanicStepKind::Notify => return true,
+            }
+            return false;
+        }
+
+        // Waits for the last step to finish panicking before resuming
+        let panicking = matches!(self.context.player, Player::Panicking(_));
+        if !self.panic_playbook.can_complete(panicking, now) {
+            return false;
+        }
+        if panicking {
+            warn!(target: "handler", "panic playbook last step timed out");
+            self.context.player = Player::Idle;
+        }
+        debug!(target: "handler", "panic playbook completed");
+        if self.panic_playbook.complete(now) {
+            self.update_context_halting(false, true);
+        }
+        false
+    }
+
+    /// Starts a due routine and runs the next step of the running routine.
+    ///
+    /// The bot is halted while a routine is running and resumed once all of its steps are run.
+    pub fn update_routines(&mut self) {
+        let now = Instant::now();
+        if self.routines.running().is_none() {
+            if self.context.halting
+                || self.minimap.data().is_none()
+                || self.character.is_none()
+                || self.panic_playbook.trigger().is_some()
+                || self.login.is_running()
+            {
+                return;
+            }
+
+            let now_millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64;
+            let Some(id) = self.routines.start_due(now, now_millis) else {
+                return;
+            };
+            info!(target: "handler", "starting routine {id}, halting");
+            upsert_routine_run(id, now_millis);
+            self.update_context_halting(true, true);
+            return;
+        }
+
+        if let Some(step) = self.routines.next_step(now) {
+            debug!(target: "handler", "running routine step {}", step.kind);
+            match step.kind {
+                RoutineStepKind::Key => {
+                    let _ = self.context.keys.send(step.key.into());
+                }
+                RoutineStepKind::Click => {
+                    let _ = self.context.keys.send_mouse(
+                        step.point.x,
+                        step.point.y,
+                        MouseAction::Click,
+                    );
+                }
+            }
+            return;
+        }
+
+        if !self.routines.has_pending_steps() {
+            info!(target: "handler", "routine completed, resuming");
+            self.routines.cancel();
+            self.update_context_halting(false, true);
+        }
+    }
+
+    /// Starts the character [`ShutdownRoutine`] once its stop time is reached and runs its next
+    /// step.
+    ///
+    /// Returns the session summary to notify when the notify step is run.
+    pub fn update_shutdown(&mut self) -> Option<String> {
+        let now = Instant::now();
+        if !self.shutdown.is_running() {
+            let routine = self.character.as_ref()?.shutdown_routine;
+            if !self.context.halting && self.shutdown.is_stop_due(&routine, now) {
+                info!(target: "handler", "session stop time reached, shutting down");
+                self.start_shutdown(now);
+            }
+            return None;
+        }
+
+        // Waits for returning to town or exiting the game to finish
+        if matches!(self.context.player, Player::Panicking(_)) {
+            return None;
+        }
+        let Some(step) = self.shutdown.next_step() else {
+            info!(target: "handler", "shutdown routine completed");
+            self.shutdown.cancel();
+            return None;
+        };
+        debug!(target: "handler", "running shutdown step {step}");
+        match step {
+            ShutdownStep::GoToTown => {
+                self.context.player = Player::Panicking(Panicking::new(PanicTo::Town));
+            }
+            ShutdownStep::Notify => return Some(self.shutdown.summary().to_string()),
+            ShutdownStep::ExitGame => {
+                self.context.player = Player::Panicking(Panicking::new(PanicTo::Lobby));
+            }
+            ShutdownStep::CloseClient => self.close_game_window(),
+        }
+        None
+    }
+
+    /// Requests the game window to close.
+    fn close_game_window(&self) {
+        // The capture area does not track the game window
+        if matches!(self.capture_mode(), CaptureMode::BitBltArea) {
+            warn!(target: "handler", "game window cannot be closed when capturing an area");
+            return;
+        }
+
+        let handle = self.selected_capture_handle.unwrap_or(self.context.handle);
+        if let Err(err) = close_window(handle) {
+            error!(target: "handler", "failed to close game window {err}");
+        }
+    }
+
+    /// Starts the character [`ShutdownRoutine`] and halts.
+    ///
+    /// Returns `false` if the routine is disabled or there is nothing to shut down.
+    fn start_shutdown(&mut self, now: Instant) -> bool {
+        let Some(character) = self.character.as_ref() else {
+            return false;
+        };
+        let routine = character.shutdown_routine;
+        if self.minimap.data().is_none() {
+            return false;
+        }
+        // Captured before halting as halting resets the exp stats
+        let summary = session_summary(
+            self.shutdown.runtime(now),
+            self.minimap.data().map(|minimap| minimap.name.as_str()),
+            self.exp_stats.exp_per_hour(),
+            self.exp_stats.exp_percent_per_hour(),
+        );
+        if !self.shutdown.start(&routine, summary) {
+            return false;
+        }
+
+        self.breaks.reset();
+        self.panic_playbook.cancel();
+        self.routines.cancel();
+        self.itinerary.reset();
+        self.update_context_halting(true, true);
+        true
+    }
+
+    /// Halts the bot and releases all held keys if the watchdog trips.
+    ///
+    /// Returns `true` if a notification should be sent.
+    pub fn update_watchdog(&mut self, has_frame: bool) -> bool {
+        if self.context.halting || self.minimap.data().is_none() || self.character.is_none() {
+            self.watchdog.reset();
+            return false;
+        }
+
+        let detection_failing = matches!(self.context.minimap, Minimap::Detecting)
+            || matches!(self.context.player, Player::Detecting);
+        let Some(trip) = self.watchdog.update(
+            &self.settings.watchdog,
+            has_frame,
+            detection_failing,
+            Instant::now(),
+        ) else {
+            return false;
+        };
+
+        info!(target: "handler", "watchdog tripped due to {trip}, halting");
+        self.watchdog.reset();
+        self.panic_playbook.cancel();
+        self.update_context_halting(true, true);
+        true
+    }
+
+    /// Suspends the bot when the game window can no longer be captured and resumes it once a
+    /// frame is captured again.
+    ///
+    /// While suspended, the capture handle is periodically re-acquired.
+    pub fn update_capture_suspension(&mut self, has_frame: bool) {
+        let transition = self.suspension.update(has_frame, Instant::now());
+        self.context.suspended = self.suspension.is_suspended();
+        match transition {
+            Some(CaptureTransition::Suspended) => {
+                info!(target: "handler", "game window can no longer be captured, suspending");
+                let _ = self.context.keys.release_all();
+                self.events.emit(Event::CaptureSuspended);
+            }
+            Some(CaptureTransition::Retry) => {
+                // The capture area does not depend on the game window
+                if matches!(self.capture_mode(), CaptureMode::BitBltArea) {
+                    return;
+                }
+                // The persisted window is re-opened with a new handle after the game restarts
+                if let Some(handle) = self
+                    .settings
+                    .capture_window
+                    .as_ref()
+                    .and_then(find_capture_window)
+                    && Some(handle) != *self.selected_capture_handle
+                {
+                    info!(target: "handler", "re-selecting persisted capture window");
+                    self.set_capture_handle(Some(handle));
+                    return;
+                }
+                debug!(target: "handler", "re-acquiring capture handle");
+                let handle = self.selected_capture_handle.unwrap_or(self.context.handle);
+                let settings = capture_settings(self.settings, self.minimap.data());
+                self.image_capture
+                    .set_mode(handle, settings.capture_mode, &settings);
+            }
+            Some(CaptureTransition::Resumed) => {
+                info!(target: "handler", "game window captured again, resuming");
+                self.events.emit(Event::CaptureResumed);
+            }
+            None => (),
+        }
+    }
+
+    /// Halts when the game client process exits during a session and relaunches the client, logs
+    /// in and resumes if client restart is enabled.
+    ///
+    /// Logging in runs the login routines when resuming.
+    pub fn update_client_restart(&mut self) {
+        let now = Instant::now();
+        if !self.settings.client_restart.enabled {
+            self.client_restarter.cancel();
+            return;
+        }
+        if !self.client_restarter.should_poll(now) {
+            return;
+        }
+        // The persisted window is re-opened with a new handle after the game restarts
+        if self.client_restarter.is_waiting_window()
+            && let Some(handle) = self
+                .settings
+                .capture_window
+                .as_ref()
+                .and_then(find_capture_window)
+            && Some(handle) != *self.selected_capture_handle
+        {
+            info!(target: "handler", "re-selecting restarted game window");
+            self.set_capture_handle(Some(handle));
+        }
+
+        let change = self.process_supervisor.poll();
+        let Some(action) = self.client_restarter.update(
+            &self.settings.client_restart,
+            change,
+            self.shutdown.is_session_active(),
+            now,
+        ) else {
+            return;
+        };
+        match action {
+            ClientRestartAction::Halt => {
+                info!(target: "handler", "game client has exited, halting");
+                self.breaks.reset();
+                self.panic_playbook.cancel();
+                self.routines.cancel();
+                self.itinerary.reset();
+                self.update_context_halting(true, true);
+            }
+            ClientRestartAction::Launch => {
+                let path = &self.settings.client_restart.path;
+                info!(target: "handler", "relaunching game client {path}");
+                if let Err(err) = launch_process(Path::new(path)) {
+                    error!(target: "handler", "failed to relaunch game client {err}");
+                }
+            }
+            ClientRestartAction::Resume => {
+                if self.login.start(&self.settings.login_sequence, true, now) {
+                    info!(target: "handler", "game client restarted, running login sequence");
+                } else {
+                    info!(target: "handler", "game client restarted, resuming");
+                    self.resume_after_login();
+                }
+            }
+            ClientRestartAction::GiveUp => {
+                warn!(target: "handler", "game window not found after the client exited, halting");
+                self.shutdown.cancel();
+            }
+        }
+    }
+
+    /// Runs the login sequence when the login screen is shown while changing channel and the
+    /// login sequence started after the game client restarted.
+    pub fn update_login(&mut self) {
+        let now = Instant::now();
+        let login = &self.settings.login_sequence;
+        if !self.login.is_running() {
+            // The minimap cannot be detected on the login screen
+            let on_login_screen = matches!(
+                self.context.player,
+                Player::Panicking(Panicking {
+                    to: PanicTo::Channel,
+                    ..
+                })
+            ) && !matches!(self.context.minimap, Minimap::Idle(_));
+            if login.enabled
+                && self.login.update_login_screen(
+                    on_login_screen,
+                    login.login_screen_after_millis,
+                    now,
+                )
+            {
+                info!(target: "handler", "login screen shown while changing channel, logging in");
+                // Stops retrying to change channel while clicking through the login screen
+                self.context.player = Player::Idle;
+                self.login.start(login, false, now);
+            }
+            return;
+        }
+
+        if let Some(step) = self.login.next_step(now) {
+            debug!(target: "handler", "running login step {step:?}");
+            match step {
+                LoginStep::Key(key) => {
+                    let _ = self.context.keys.send(key.into());
+                }
+                LoginStep::Click(point) => {
+                    let _ = self
+                        .context
+                        .keys
+                        .send_mouse(point.x, point.y, MouseAction::Click);
+                }
+                LoginStep::EnterPic => {
+                    let (offset_x, offset_y) = self.frame_to_client_offset();
+                    let mut region = Rect::from(login.pic_keypad);
+                    region.x -= offset_x;
+                    region.y -= offset_y;
+                    let keypad = self
+                        .context
+                        .detector
+                        .as_ref()
+                        .map(|detector| detector.detect_pic_keypad(region))
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(bbox, digit)| {
+                            let x = bbox.x + bbox.width / 2 + offset_x;
+                            let y = bbox.y + bbox.height / 2 + offset_y;
+                            (ClientPoint { x, y }, digit)
+                        })
+                        .collect::<Vec<_>>();
+                    if !self.login.enter_pic(&login.pic, &keypad, now) {
+                        error!(target: "handler", "failed to detect PIC keypad digits, cancelling login");
+                        self.login.cancel();
+                    }
+                }
+            }
+            return;
+        }
+
+        if !self.login.has_pending_steps() {
+            info!(target: "handler", "login sequence completed");
+            if self.login.complete() {
+                self.resume_after_login();
+            }
+        }
+    }
+
+    /// Resumes rotating actions after logging in and allows the login routines to run again.
+    fn resume_after_login(&mut self) {
+        self.routines.reset_login();
+        self.update_context_halting(false, true);
+        if !self.context.halting {
+            self.arrange_game_window();
+        }
+    }
+
+    /// Selects `handle` for capturing and sending keys or the default handle if [`None`].
+    fn set_capture_handle(&mut self, handle: Option<Handle>) {
+        let handle_or_default = handle.unwrap_or(self.context.handle);
+
+        *self.selected_capture_handle = handle;
+        self.focus_monitor.set_handle(handle_or_default);
+        self.process_supervisor.set_handle(handle_or_default);
+        let settings = capture_settings(self.settings, self.minimap.data());
+        self.image_capture
+            .set_mode(handle_or_default, settings.capture_mode, &settings);
+        
+        // For BitBltArea, use Foreground key input kind, otherwise use Fixed
+        let key_input_kind = if matches!(settings.capture_mode, CaptureMode::BitBltArea) {
+            KeyInputKind::Foreground
+        } else {
+            KeyInputKind::Fixed
+        };
+        
+        *self.key_receiver = KeyReceiver::new(handle_or_default, key_input_kind);
+        match self.settings.input_method {
+            InputMethod::Default => {
+                self.context.keys.set_method(KeySenderMethod::Default(
+                    handle_or_default,
+                    key_input_kind,
+                ));
+            }
+            InputMethod::Rpc => {
+                self.context.keys.set_method(KeySenderMethod::Rpc(
+                    handle_or_default,
+                    self.settings.input_method_rpc_server_url.clone(),
+                ));
+            }
+            InputMethod::Serial => {
+                self.context.keys.set_method(KeySenderMethod::Serial(
+                    handle_or_default,
+                    self.settings.input_method_serial_port.clone(),
+                ));
+            }
+        }
+    }
+
+    /// Updates [`Context::frame_to_client_offset`] for mouse inputs sent by the player.
+    #[inline]
+    pub fn update_frame_to_client_offset(&mut self) {
+        self.context.frame_to_client_offset = self.frame_to_client_offset();
+    }
+
+    /// Gates key inputs and refocuses the game window depending on whether it is focused.
+    ///
+    /// Returns `true` if a notification should be sent.
+    pub fn update_focus(&mut self) -> bool {
+        let tracking = &self.settings.focus_tracking;
+        // The capture area does not track the game window
+        if !tracking.enabled || matches!(self.capture_mode(), CaptureMode::BitBltArea) {
+            self.context.keys.set_paused(false);
+            self.update_break_hold(BreakHold::FocusLost, false);
+            return false;
+        }
+
+        let was_halting = self.context.halting;
+        let change = self.focus_monitor.poll();
+        let paused = tracking.pause_keys && !self.focus_monitor.is_focused();
+        self.context.keys.set_paused(paused);
+        // Halts the player and rotator too instead of letting them tick on failed inputs
+        self.update_break_hold(BreakHold::FocusLost, paused);
+        if change != Some(FocusChange::Lost) {
+            return false;
+        }
+
+        info!(target: "handler", "game window lost focus");
+        if paused {
+            let _ = self.context.keys.release_all();
+        }
+        if tracking.refocus
+            && let Err(err) = self.focus_monitor.refocus()
+        {
+            debug!(target: "handler", "failed to refocus game window {err}");
+        }
+        !was_halting
+    }
+
+    /// Switches to the next preset of the current minimap preset schedule if due.
+    ///
+    /// The schedule is paused while halting. Emits [`Event::PresetScheduled`] on switching.
+    pub fn update_preset_schedule(&mut self) {
+        if self.context.halting {
+            self.preset_scheduler.pause();
+            return;
+        }
+        let Some(minimap) = self.minimap.data() else {
+            return;
+        };
+        let Some(preset) = self.preset_scheduler.update(
+            &minimap.preset_schedule,
+            &self.context.rng,
+            Instant::now(),
+        ) else {
+            return;
+        };
+        let Some(actions) = minimap.actions.get(&preset).cloned() else {
+            return;
+        };
+
+        info!(target: "handler", "switching to scheduled preset {preset}");
+        self.events.emit(Event::PresetScheduled { preset });
+        *self.actions = actions;
+        self.player.clear_actions_aborted(true);
+        self.update_rotator_actions();
+    }
+
+    /// Switches to the stored minimap matching the detected minimap when it is detected again
+    /// and no longer matches the current minimap.
+    ///
+    /// Returns `true` if the detected minimap does not match any stored minimap.
+    pub fn update_minimap_switch(&mut self, was_minimap_idle: bool) -> bool {
+        if !self.settings.auto_switch_minimap
+            || self.context.halting
+            || was_minimap_idle
+            || self.itinerary.traveling().is_some()
+        {
+            return false;
+        }
+        let Some(minimap) = self.minimap.data() else {
+            return false;
+        };
+        let Some((size, fingerprint)) = self.detected_minimap_fingerprint() else {
+            return false;
+        };
+        let similarity = self.settings.detection_tuning.minimap_similarity;
+        if minimap_matches(minimap, size, &fingerprint, similarity) {
+            return false;
+        }
+
+        let minimaps = query_minimaps()
+            .inspect_err(|err| error!(target: "handler", "failed to query minimaps {err}"))
+            .unwrap_or_default();
+        let Some(matched) =
+            find_matching_minimap(&minimaps, size, &fingerprint, similarity).cloned()
+        else {
+            info!(target: "handler", "detected minimap does not match any stored minimap");
+            return true;
+        };
+        let preset = self
+            .preset_scheduler
+            .preset()
+            .filter(|preset| matched.actions.contains_key(*preset))
+            .cloned()
+            .or_else(|| matched.actions.keys().next().cloned());
+
+        info!(target: "handler", "switching to matching minimap {}", matched.name);
+        self.switch_minimap(matched, preset);
+        false
+    }
+
+    /// Starts the itinerary from the step of the current minimap and leaves each step's minimap
+    /// once elapsed.
+    ///
+    /// The next step's minimap and preset are used once the detected minimap matches it.
+    pub fn update_itinerary(&mut self, was_minimap_idle: bool) {
+        if !self.settings.itinerary.enabled || self.context.halting {
+            return;
+        }
+
+        let now = Instant::now();
+        if !self.itinerary.is_running() {
+            let Some(id) = self.minimap.data().and_then(|minimap| minimap.id) else {
+                return;
+            };
+            let steps = &self.settings.itinerary.steps;
+            if let Some(index) = steps.iter().position(|step| step.minimap_id == Some(id)) {
+                info!(target: "handler", "starting itinerary from step {index}");
+                self.itinerary.start(index, now);
+            }
+            return;
+        }
+
+        match self.itinerary.update(&self.settings.itinerary.steps, now) {
+            Some(ItineraryUpdate::Leave(index)) => {
+                let step = self.settings.itinerary.steps[index].clone();
+                info!(target: "handler", "leaving itinerary step {index} by {}", step.exit);
+                self.leave_itinerary_step(step);
+                return;
+            }
+            Some(ItineraryUpdate::TimedOut) => {
+                info!(target: "handler", "itinerary failed to reach the next minimap, halting");
+                self.update_context_halting(true, true);
+                return;
+            }
+            None => (),
+        }
+
+        if was_minimap_idle {
+            return;
+        }
+        let Some(step) = self
+            .itinerary
+            .traveling()
+            .and_then(|index| self.settings.itinerary.steps.get(index))
+        else {
+            return;
+        };
+        let Some((size, fingerprint)) = self.detected_minimap_fingerprint() else {
+            return;
+        };
+        let minimap = query_minimaps()
+            .inspect_err(|err| error!(target: "handler", "failed to query minimaps {err}"))
+            .unwrap_or_default()
+            .into_iter()
+            .find(|minimap| minimap.id.is_some() && minimap.id == step.minimap_id);
+        let similarity = self.settings.detection_tuning.minimap_similarity;
+        let Some(minimap) =
+            minimap.filter(|minimap| minimap_matches(minimap, size, &fingerprint, similarity))
+        else {
+            debug!(target: "handler", "detected minimap is not the next itinerary minimap");
+            return;
+        };
+        let preset = step
+            .preset
+            .clone()
+            .filter(|preset| minimap.actions.contains_key(preset))
+            .or_else(|| minimap.actions.keys().next().cloned());
+
+        info!(target: "handler", "arrived at itinerary minimap {}", minimap.name);
+        self.switch_minimap(minimap, preset);
+        self.itinerary.arrive(now);
+    }
+
+    fn leave_itinerary_step(&mut self, step: ItineraryStep) {
+        match step.exit {
+            ItineraryExit::Portal => {
+                *self.actions = vec![Action::Key(ActionKey {
+                    key: KeyBinding::Up,
+                    position: Some(step.portal),
+                    ..ActionKey::default()
+                })];
+                self.player.clear_actions_aborted(true);
+                self.update_rotator_actions();
+            }
+            ItineraryExit::ToTown => {
+                self.context.player = Player::Panicking(Panicking::new(PanicTo::Town));
+            }
+            ItineraryExit::Key => {
+                let _ = self.context.keys.send(step.key.into());
+            }
+        }
+    }
+
+    /// Computes the size and fingerprint of the currently detected minimap.
+    fn detected_minimap_fingerprint(&self) -> Option<((i32, i32), Vec<u8>)> {
+        let Minimap::Idle(idle) = self.context.minimap else {
+            return None;
+        };
+        let fingerprint = minimap_fingerprint(self.context.detector.as_ref()?.mat(), idle.bbox)?;
+        Some(((idle.bbox.width, idle.bbox.height), fingerprint))
+    }
+
+    /// Uses stored `minimap` with `preset` and emits [`Event::MinimapSwitched`].
+    fn switch_minimap(&mut self, minimap: MinimapData, preset: Option<String>) {
+        self.events.emit(Event::MinimapSwitched {
+            id: minimap.id.expect("queried minimap has id"),
+            name: minimap.name.clone(),
+            preset: preset.clone(),
+        });
+        self.on_update_minimap(preset, Some(minimap));
+    }
+
+    fn set_minimap(&mut self, preset: Option<String>, minimap: Option<MinimapData>) {
+        self.set_minimap_data(minimap);
+        self.player.reset();
+        self.preset_scheduler.reset(preset.clone());
+        self.action_stats.reset();
+        *self.pending_actions = None;
+
+        let Some(minimap) = self.minimap.data() else {
+            *self.actions = Vec::new();
+            self.update_rotator_actions();
+            return;
+        };
+
+        self.set_minimap_player_config();
+        *self.actions = preset
+            .and_then(|preset| minimap.actions.get(&preset).cloned())
+            .unwrap_or_default();
+        self.update_rotator_actions();
+    }
+
+    /// Updates the current minimap in place and stages the actions of its current `preset` to
+    /// apply at the next rotation cycle.
+    ///
+    /// Unlike [`Self::set_minimap`], the player is not reset so the current action keeps its
+    /// progress.
+    fn hot_swap_minimap(&mut self, preset: String, minimap: MinimapData) {
+        let actions = minimap.actions.get(&preset).cloned().unwrap_or_default();
+        self.set_minimap_data(Some(minimap));
+        self.set_minimap_player_config();
+        info!(target: "handler", "preset {preset} edited, applying at the next rotation cycle");
+        *self.pending_actions = Some(actions);
+    }
+
+    /// Sets the minimap data and re-applies the capture settings if its override changed them.
+    fn set_minimap_data(&mut self, minimap: Option<MinimapData>) {
+        let previous = capture_settings(self.settings, self.minimap.data()).into_owned();
+        self.minimap.set_data(minimap);
+        if *capture_settings(self.settings, self.minimap.data()) != previous {
+            info!(target: "handler", "capture settings changed by the selected minimap");
+            self.set_capture_handle(*self.selected_capture_handle);
+        }
+    }
+
+    /// The capture mode of the selected minimap override or [`Settings::capture_mode`].
+    fn capture_mode(&self) -> CaptureMode {
+        self.minimap
+            .data()
+            .and_then(|minimap| minimap.capture_override)
+            .map_or(self.settings.capture_mode, |capture| capture.mode)
+    }
+
+    /// The offset to add to a frame coordinate for the client coordinate of mouse inputs.
+    ///
+    /// With [`CaptureMode::BitBltArea`] and [`InputMethod::Default`], frames are captured from an
+    /// area of the screen while mouse inputs are relative to the foreground window client area.
+    fn frame_to_client_offset(&self) -> (i32, i32) {
+        let settings = capture_settings(self.settings, self.minimap.data());
+        if matches!(settings.capture_mode, CaptureMode::BitBltArea)
+            && matches!(settings.input_method, InputMethod::Default)
+        {
+            self.image_capture.foreground_client_offset()
+        } else {
+            (0, 0)
+        }
+    }
+
+    fn set_minimap_player_config(&mut self) {
+        let Some(minimap) = self.minimap.data() else {
+            return;
+        };
+        self.player.config.rune_platforms_pathing = minimap.rune_platforms_pathing;
+        self.player.config.rune_platforms_pathing_up_jump_only =
+            minimap.rune_platforms_pathing_up_jump_only;
+        self.player.config.auto_mob_platforms_pathing = minimap.auto_mob_platforms_pathing;
+        self.player.config.auto_mob_platforms_pathing_up_jump_only =
+            minimap.auto_mob_platforms_pathing_up_jump_only;
+        self.player.config.auto_mob_platforms_bound = minimap.auto_mob_platforms_bound;
+        let latency_ticks = self
+            .latency_tester
+            .latency(self.settings.input_method)
+            .map(|latency| latency.average_ticks as u32);
+        self.player.config.movement =
+            MovementThresholds::with_input_latency(minimap.movement_overrides, latency_ticks);
+    }
+
+    fn set_character(&mut self, character: Option<Character>) {
+        *self.character = character;
+
+        let Some(character) = self.character else {
+            self.context.keys.set_key_timings(HashMap::new());
+            return;
+        };
+        *self.buffs = config_buffs(character);
+        self.context
+            .keys
+            .set_key_timings(config_key_timings(character));
+        self.player.reset();
+        self.player.config.class = character.class;
+        self.player.config.disable_adjusting = character.disable_adjusting;
+        self.player.config.disable_jump_down = character.disable_jump_down;
+        self.player.config.interact_key = character.interact_key.key.into();
+        self.player.config.grappling_key = character.ropelift_key.map(|key| key.key.into());
+        self.player.config.teleport_key = character.teleport_key.map(|key| key.key.into());
+        self.player.config.jump_key = character.jump_key.key.into();
+        self.player.config.upjump_key = character.up_jump_key.map(|key| key.key.into());
+        self.player.config.flying_key = character.flight_key.map(|key| key.key.into());
+        self.player.config.hold_flying_key = character.hold_flight_key;
+        self.player.config.cash_shop_key = character.cash_shop_key.key.into();
+        self.player.config.familiar_key = character.familiar_menu_key.key.into();
+        self.player.config.to_town_key = character.to_town_key.key.into();
+        self.player.config.change_channel_key = character.change_channel_key.key.into();
+        let potions = character
+            .potions
+            .iter()
+            .filter(|potion| potion.enabled)
+            .map(|potion| PlayerPotion {
+                key: potion.key.into(),
+                below_ratio: potion.below_percent / 100.0,
+                cooldown_millis: potion.cooldown_millis,
+            })
+            .chain(config_keyed_potions(character, false))
+            .collect::<Vec<_>>();
+        if potions.len() > MAX_POTIONS {
+            warn!(
+                target: "handler",
+                "only the first {MAX_POTIONS} of {} potions are used",
+                potions.len()
+            );
+        }
+        self.player.config.potions = potions.into_iter().take(MAX_POTIONS).collect();
+        self.player.config.mana_potions = config_keyed_potions(character, true)
+            .take(MAX_POTIONS)
+            .collect();
+        self.player.config.update_health_millis = Some(character.health_update_millis);
+        self.buff_states.iter_mut().for_each(|state| {
+            state.update_enabled_state(character, self.settings);
+        });
+        self.update_rotator_actions();
+    }
+
+    /// Queries the character with `id` linked from the selected minimap.
+    ///
+    /// Returns [`None`] if there is no linked character or it is already the current character.
+    fn linked_character(&self, id: Option<i64>) -> Option<Character> {
+        let current = self.character.as_ref().and_then(|character| character.id);
+        let id = id.filter(|id| current != Some(*id))?;
+        query_characters()
+            .inspect_err(|err| error!(target: "handler", "failed to query characters {err}"))
+            .ok()?
+            .into_iter()
+            .find(|character| character.id == Some(id))
+    }
+
+    /// Queries the minimap with `id` linked from the selected character and resolves `preset`.
+    ///
+    /// Returns [`None`] if the linked minimap is already the current minimap. The first preset is
+    /// used if `preset` does not exist in the linked minimap.
+    fn linked_minimap(
+        &self,
+        id: i64,
+        preset: Option<String>,
+    ) -> Option<(MinimapData, Option<String>)> {
+        if self.minimap.data().and_then(|minimap| minimap.id) == Some(id) {
+            return None;
+        }
+        let minimap = query_minimaps()
+            .inspect_err(|err| error!(target: "handler", "failed to query minimaps {err}"))
+            .ok()?
+            .into_iter()
+            .find(|minimap| minimap.id == Some(id))?;
+        let preset = preset
+            .filter(|preset| minimap.actions.contains_key(preset))
+            .or_else(|| minimap.actions.keys().next().cloned());
+        Some((minimap, preset))
+    }
+
+    /// Emits [`Event`]s from changes in the contextual states since the last call.
+    ///
+    /// Returns the emitted events.
+    pub fn update_events(&mut self) -> Vec<Event> {
+        let snapshot = EventSnapshot {
+            normal_action: self
+                .player
+                .normal_action_id()
+                .zip(self.player.normal_action_name()),
+            priority_action: self
+                .player
+                .priority_action_id()
+                .zip(self.player.priority_action_name()),
+            has_rune_buff: !matches!(self.context.buffs[BuffKind::Rune], Buff::No),
+            is_dead: self.player.is_dead,
+            is_changing_channel: matches!(
+                self.context.player,
+                Player::Panicking(Panicking {
+                    to: PanicTo::Channel,
+                    ..
+                })
+            ),
+            is_minimap_idle: matches!(self.context.minimap, Minimap::Idle(_)),
+        };
+        self.events.update(snapshot)
+    }
+
+    /// Learns rune spawns from the current minimap and `events` and passes the expected spawn
+    /// position to the rotator if [`Settings::enable_rune_spawn_pathing`] is enabled.
+    pub fn update_rune_stats(&mut self, events: &[Event]) {
+        let now = Instant::now();
+        let rune = match self.context.minimap {
+            Minimap::Idle(idle) => idle.rune(),
+            _ => None,
+        };
+        self.rune_stats.update(
+            self.minimap.data().and_then(|minimap| minimap.id),
+            rune,
+            events.contains(&Event::RuneSolved),
+            now,
+        );
+
+        let hint = if self.settings.enable_rune_spawn_pathing && !self.context.halting {
+            self.rune_stats.expected_spawn(now)
+        } else {
+            None
+        };
+        self.rotator.set_rune_spawn_hint(hint);
+    }
+
+    /// Passes the game state and `events` to the loaded plugins and applies their commands.
+    ///
+    /// The plugins are asked for an action to run only while rotating and the player has no
+    /// action from the rotator.
+    pub fn update_plugins(&mut self, events: &[Event]) {
+        if self.plugins.is_empty() {
+            return;
+        }
+
+        for event in events {
+            self.plugins.on_event(event);
+        }
+        let state = self.game_state(true);
+        for command in self.plugins.on_tick(&state) {
+            debug!(target: "handler", "plugin command {command:?}");
+            match command {
+                PluginCommand::RotateActions { halting } => self.on_rotate_actions(halting),
+                PluginCommand::SendKey { key } => {
+                    let _ = self.context.keys.send(key.into());
+                }
+            }
+        }
+
+        if self.context.halting
+            || !matches!(self.context.player, Player::Idle)
+            || self.player.has_normal_action()
+            || self.player.has_priority_action()
+        {
+            return;
+        }
+        match self.plugins.propose_action() {
+            Some(Action::Group(_)) => {
+                warn!(target: "handler", "plugin proposed an action group, ignoring");
+            }
+            Some(action) => {
+                self.player
+                    .set_normal_action(ACTION_ID_PLUGIN, action.into());
+            }
+            None => (),
+        }
+    }
+
+    /// Shares or follows the player position as configured in [`Settings::following`].
+    ///
+    /// As a follower, the leader position is passed to the rotator whenever the player is further
+    /// than [`crate::Following::distance`] away from it.
+    pub fn update_following(&mut self) {
+        let following = &self.settings.following;
+        let position = self.player.last_known_pos;
+        let leader = self.following.update(following, position, Instant::now());
+        let target = leader.filter(|leader| {
+            !self.context.halting
+                && position.is_some_and(|pos| should_follow(pos, *leader, following.distance))
+        });
+        self.rotator.set_follow_target(target);
+    }
+
+    /// Updates the execution statistics of the current preset actions from the emitted `events`.
+    pub fn update_action_stats(&mut self, events: &[Event]) {
+        if events.is_empty() {
+            return;
+        }
+        // Character actions are placed before the preset actions when building the rotator
+        let minimap_id = self.minimap.data().and_then(|minimap| minimap.id);
+        let offset = self
+            .character
+            .as_ref()
+            .map(|character| config_actions(character, minimap_id).len())
+            .unwrap_or_default();
+        let now_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let rotator = &*self.rotator;
+        self.action_stats.update(
+            events,
+            |id| rotator.action_index(id)?.checked_sub(offset),
+            Instant::now(),
+            now_millis,
+        );
+    }
+
+    /// Records the current game state into the diagnostics history.
+    ///
+    /// A downscaled frame is also recorded if [`Settings::record_diagnostics_frames`] is enabled.
+    pub fn update_diagnostics(&self) {
+        diagnostics::record(Instant::now(), |with_frame| {
+            let frame = self
+                .context
+                .detector
+                .as_ref()
+                .filter(|_| with_frame && self.settings.record_diagnostics_frames)
+                .and_then(|detector| diagnostics::downscale_frame(detector.mat()));
+            (self.game_state(false), frame)
+        });
+    }
+
+    /// Records the status served to streaming overlays if an overlay address is configured.
+    pub fn update_overlay(&self) {
+        if config().overlay_address.is_none() {
+            return;
+        }
+        overlay::record(
+            Instant::now(),
+            OverlayStatus {
+                state: self.context.player.to_string(),
+                map: self.minimap.data().map(|minimap| minimap.name.clone()),
+                halting: self.context.halting,
+                runtime_secs: 0,
+                exp_per_hour: self.exp_stats.exp_per_hour(),
+                exp_percent_per_hour: self.exp_stats.exp_percent_per_hour(),
+            },
+        );
+    }
+
+    /// Persists the current minimap, preset, character and halting state if any changed.
+    pub fn update_session(&mut self) {
+        self.session.update(Session {
+            id: None,
+            minimap_id: self.minimap.data().and_then(|minimap| minimap.id),
+            preset: self.preset_scheduler.preset().cloned(),
+            character_id: self.character.as_ref().and_then(|character| character.id),
+            halting: self.context.halting,
+        });
+    }
+
+    /// Advances the first-run setup capture test if one is running.
+    pub fn update_capture_test(&mut self) {
+        self.capture_tester.update(
+            self.context.detector.as_deref(),
+            self.settings.detection_tuning.minimap_border_threshold,
+            Instant::now(),
+        );
+    }
+
+    /// Advances the input latency test if one is running.
+    pub fn update_latency_test(&mut self) {
+        if !self.latency_tester.is_running() {
+            return;
+        }
+        let Some(detector) = self.context.detector.as_ref() else {
+            return;
+        };
+        let menu_opened = detector.detect_esc_settings();
+        let keys = &self.context.keys;
+        match self
+            .latency_tester
+            .update(Instant::now(), self.context.tick, menu_opened, || {
+                let _ = keys.send(KeyKind::Esc);
+            }) {
+            Some(Ok(latency)) => {
+                info!(
+                    target: "handler",
+                    "input latency of {} is {}ms",
+                    latency.method,
+                    latency.average_millis
+                );
+                self.set_minimap_player_config();
+            }
+            Some(Err(err)) => error!(target: "handler", "input latency test failed {err}"),
+            None => (),
+        }
+    }
+
+    /// Applies the actions staged by a preset hot-swap once the rotator reaches the next cycle.
+    pub fn update_preset_hot_swap(&mut self) {
+        if self.pending_actions.is_none()
+            || !(self.context.halting || self.rotator.is_at_cycle_boundary())
+        {
+            return;
+        }
+
+        *self.actions = self.pending_actions.take().expect("has pending actions");
+        self.action_stats.reset();
+        self.build_rotator_actions(true);
+        if let Some(preset) = self.preset_scheduler.preset().cloned() {
+            self.events.emit(Event::PresetHotSwapped { preset });
+        }
+    }
+
+    /// Performs [`Settings::anti_afk`] actions while halting or without any pending action.
+    pub fn update_anti_afk(&mut self) {
+        let active = matches!(self.context.minimap, Minimap::Idle(_))
+            && self.routines.running().is_none()
+            && self.panic_playbook.trigger().is_none()
+            && !self.latency_tester.is_running()
+            && (self.context.halting
+                || (matches!(self.context.player, Player::Idle)
+                    && !self.player.has_normal_action()
+                    && !self.player.has_priority_action()));
+        let Some(input) = self.anti_afk.update(
+            &self.settings.anti_afk,
+            active,
+            self.player.last_known_pos.map(|pos| pos.x),
+            &self.context.rng,
+            Instant::now(),
+        ) else {
+            return;
+        };
+
+        debug!(target: "handler", "anti-afk input {input:?}");
+        let to_key_kind = |key| match key {
+            AntiAfkKey::Left => KeyKind::Left,
+            AntiAfkKey::Right => KeyKind::Right,
+            AntiAfkKey::Menu => self.settings.anti_afk.menu_key.key.into(),
+        };
+        let _ = match input {
+            AntiAfkInput::Down(key) => self.context.keys.send_down(to_key_kind(key)),
+            AntiAfkInput::Up(key) => self.context.keys.send_up(to_key_kind(key)),
+            AntiAfkInput::Send(key) => self.context.keys.send(to_key_kind(key)),
+        };
+    }
+
+    /// Takes [`Settings::breaks`] breaks by walking to the break position, halting and then
+    /// resuming once the break has ended.
+    ///
+    /// Returns the transition if a notification should be sent.
+    pub fn update_breaks(&mut self) -> Option<BreakTransition> {
+        let target = self
+            .minimap
+            .data()
+            .and_then(|minimap| minimap.break_position)
+            .map(|position| Point::new(position.x, position.y));
+        let arrived = target.is_none_or(|target| {
+            self.player
+                .last_known_pos
+                .is_some_and(|pos| is_at_break_position(pos, target))
+        });
+        let transition = self.breaks.update(
+            &self.settings.breaks,
+            self.context.halting,
+            arrived,
+            &self.context.rng,
+            Instant::now(),
+        );
+        self.rotator
+            .set_break_target(target.filter(|_| self.breaks.is_walking()));
+
+        match transition? {
+            BreakTransition::Walk => {
+                info!(target: "handler", "break is due, walking to break position");
+                None
+            }
+            BreakTransition::Pause => {
+                info!(target: "handler", "taking a break, halting");
+                self.update_context_halting(true, true);
+                transition
+            }
+            BreakTransition::Resume => {
+                info!(target: "handler", "break has ended, resuming");
+                self.update_context_halting(false, true);
+                transition
+            }
+        }
+    }
+
+    /// Halts rotating while a safe mode mandatory break is ongoing and resumes once it ends.
+    ///
+    /// Sending keys fails during the break anyway, so this avoids the player and rotator getting
+    /// stuck on failed inputs.
+    pub fn update_safe_mode_break(&mut self) {
+        let breaking = matches!(
+            self.context.keys.rate_limit_break(),
+            Some(RateLimitBreak::Breaking(_))
+        );
+        self.update_break_hold(BreakHold::SafeMode, breaking);
+    }
+
+    fn update_break_hold(&mut self, hold: BreakHold, active: bool) {
+        match self.breaks.update_hold(hold, active, self.context.halting) {
+            Some(BreakTransition::Pause) => {
+                info!(target: "handler", "halting until {hold:?} hold ends");
+                self.update_context_halting(true, true);
+            }
+            Some(BreakTransition::Resume) => {
+                info!(target: "handler", "{hold:?} hold has ended, resuming");
+                self.update_context_halting(false, true);
+            }
+            Some(BreakTransition::Walk) | None => (),
+        }
+    }
+
+    /// Writes the current frame to the video if recording.
+    pub fn update_video_recording(&mut self) {
+        let Some(detector) = self.context.detector.as_ref() else {
+            return;
+        };
+        let settings = &self.settings.video_recording;
+        let bbox = match self.context.minimap {
+            Minimap::Idle(idle) => Some(idle.bbox),
+            Minimap::Detecting => None,
+        };
+        let player = bbox.zip(self.player.last_known_pos);
+        self.video_recorder
+            .update(Instant::now(), detector.mat(), settings, || VideoOverlay {
+                minimap: bbox.filter(|_| settings.overlay_minimap),
+                player: player
+                    .filter(|_| settings.overlay_player)
+                    .map(|(bbox, pos)| player_overlay_box(bbox, pos)),
+                state: settings
+                    .overlay_state
+                    .then(|| self.context.player.to_string()),
+            });
+    }
+
+    /// Learns avoidance zones from the position the player just got stuck at and passes the
+    /// zones of the current minimap to platforms pathing.
+    pub fn update_avoidance_zones(&mut self, was_player_unstucking: bool) {
+        let stuck_pos = if !was_player_unstucking
+            && !self.context.halting
+            && matches!(self.context.player, Player::Unstucking(_, _, _))
+        {
+            self.player.last_known_pos
+        } else {
+            None
         };
+        if self.avoidance_zones.update(
+            self.minimap.data().and_then(|minimap| minimap.id),
+            stuck_pos,
+        ) {
+            self.minimap
+                .set_avoidance_zones(self.avoidance_zones.zones().to_vec());
+        }
+    }
+
+    /// Moves and resizes the game window to [`Settings::window_arrangement`] if enabled.
+    fn arrange_game_window(&self) {
+        let arrangement = &self.settings.window_arrangement;
+        // The capture area does not track the game window
+        if !arrangement.enabled || matches!(self.capture_mode(), CaptureMode::BitBltArea) {
+            return;
+        }
+
+        let handle = self.selected_capture_handle.unwrap_or(self.context.handle);
+        if let Err(err) = arrange_window(
+            handle,
+            arrangement.x,
+            arrangement.y,
+            arrangement.width,
+            arrangement.height,
+        ) {
+            error!(target: "handler", "failed to arrange game window {err}");
+        }
+    }
 
-        self.rotator.build_actions(args);
+    /// Whether the update loop can drop to a low tick rate to save CPU.
+    ///
+    /// This is only while halting with nothing running that still sends inputs or needs every
+    /// frame such as a panic playbook, routine, shutdown routine, login sequence, input latency
+    /// test, anti-afk or video recording.
+    pub fn can_low_power(&self) -> bool {
+        self.context.halting
+            && matches!(self.context.player, Player::Idle)
+            && self.routines.running().is_none()
+            && self.panic_playbook.trigger().is_none()
+            && !self.shutdown.is_running()
+            && !self.login.is_running()
+            && !self.latency_tester.is_running()
+            && !self.settings.anti_afk.enabled
+            && !self.video_recorder.is_recording()
     }
 
     pub fn update_context_halting(&mut self, halting: bool, reset_player_to_idle: bool) {
         if self.minimap.data().is_some() && self.character.is_some() {
             self.context.halting = halting;
             if halting {
+                self.exp_stats.reset();
+                self.meso_stats.reset();
                 self.rotator.reset_queue();
                 self.player.clear_actions_aborted(reset_player_to_idle);
+                let _ = self.context.keys.release_all();
             }
         }
     }
@@ -248,7 +1834,25 @@ impl DefaultRequestHandler<'_> {
 
 impl RequestHandler for DefaultRequestHandler<'_> {
     fn on_rotate_actions(&mut self, halting: bool) {
+        // Stopping again while shutting down skips the remaining steps
+        if halting && !self.context.halting && self.start_shutdown(Instant::now()) {
+            return;
+        }
+        self.shutdown.cancel();
+        self.login.cancel();
+        self.client_restarter.cancel();
+        self.breaks.reset();
+        self.panic_playbook.cancel();
+        self.routines.cancel();
+        self.itinerary.reset();
+        if !halting {
+            self.routines.reset_login();
+        }
         self.update_context_halting(halting, true);
+        if !halting && !self.context.halting {
+            self.shutdown.start_session(Instant::now());
+            self.arrange_game_window();
+        }
     }
 
     fn on_create_minimap(&self, name: String) -> Option<MinimapData> {
@@ -257,6 +1861,12 @@ impl RequestHandler for DefaultRequestHandler<'_> {
                 name,
                 width: idle.bbox.width,
                 height: idle.bbox.height,
+                fingerprint: self
+                    .context
+                    .detector
+                    .as_ref()
+                    .and_then(|detector| minimap_fingerprint(detector.mat(), idle.bbox))
+                    .unwrap_or_default(),
                 ..MinimapData::default()
             })
         } else {
@@ -265,70 +1875,98 @@ impl RequestHandler for DefaultRequestHandler<'_> {
     }
 
     fn on_update_minimap(&mut self, preset: Option<String>, minimap: Option<MinimapData>) {
-        self.minimap.set_data(minimap);
-        self.player.reset();
-
-        let Some(minimap) = self.minimap.data() else {
-            *self.actions = Vec::new();
-            self.update_rotator_actions();
+        let character_id = minimap.as_ref().and_then(|minimap| minimap.character_id);
+        let current = self.minimap.data();
+        let is_editing_current = !self.context.halting
+            && preset.is_some()
+            && preset.as_ref() == self.preset_scheduler.preset()
+            && minimap.as_ref().is_some_and(|minimap| {
+                minimap.id.is_some()
+                    && minimap.id == current.and_then(|current| current.id)
+                    && minimap.character_id == current.and_then(|current| current.character_id)
+            });
+        if is_editing_current {
+            self.hot_swap_minimap(preset.expect("has preset"), minimap.expect("has minimap"));
             return;
-        };
+        }
 
-        self.player.config.rune_platforms_pathing = minimap.rune_platforms_pathing;
-        self.player.config.rune_platforms_pathing_up_jump_only =
-            minimap.rune_platforms_pathing_up_jump_only;
-        self.player.config.auto_mob_platforms_pathing = minimap.auto_mob_platforms_pathing;
-        self.player.config.auto_mob_platforms_pathing_up_jump_only =
-            minimap.auto_mob_platforms_pathing_up_jump_only;
-        self.player.config.auto_mob_platforms_bound = minimap.auto_mob_platforms_bound;
-        *self.actions = preset
-            .and_then(|preset| minimap.actions.get(&preset).cloned())
-            .unwrap_or_default();
-        self.update_rotator_actions();
+        self.set_minimap(preset, minimap);
+        if let Some(character) = self.linked_character(character_id) {
+            self.events.emit(Event::CharacterSwitched {
+                id: character.id.expect("queried character has id"),
+                name: character.name.clone(),
+            });
+            self.set_character(Some(character));
+        }
     }
 
     fn on_update_character(&mut self, character: Option<Character>) {
-        *self.character = character;
-
-        let Some(character) = self.character else {
-            return;
-        };
-        *self.buffs = config_buffs(character);
-        self.player.reset();
-        self.player.config.class = character.class;
-        self.player.config.disable_adjusting = character.disable_adjusting;
-        self.player.config.interact_key = character.interact_key.key.into();
-        self.player.config.grappling_key = character.ropelift_key.map(|key| key.key.into());
-        self.player.config.teleport_key = character.teleport_key.map(|key| key.key.into());
-        self.player.config.jump_key = character.jump_key.key.into();
-        self.player.config.upjump_key = character.up_jump_key.map(|key| key.key.into());
-        self.player.config.cash_shop_key = character.cash_shop_key.key.into();
-        self.player.config.familiar_key = character.familiar_menu_key.key.into();
-        self.player.config.to_town_key = character.to_town_key.key.into();
-        self.player.config.change_channel_key = character.change_channel_key.key.into();
-        self.player.config.potion_key = character.potion_key.key.into();
-        self.player.config.use_potion_below_percent =
-            match (character.potion_key.enabled, character.potion_mode) {
-                (false, _) | (_, PotionMode::EveryMillis(_)) => None,
-                (_, PotionMode::Percentage(percent)) => Some(percent / 100.0),
-            };
-        self.player.config.update_health_millis = Some(character.health_update_millis);
-        self.buff_states.iter_mut().for_each(|state| {
-            state.update_enabled_state(character, self.settings);
+        let link = character.as_ref().and_then(|character| {
+            character
+                .minimap_id
+                .map(|id| (id, character.minimap_preset.clone()))
         });
-        self.update_rotator_actions();
+        self.set_character(character);
+        if let Some((minimap, preset)) =
+            link.and_then(|(id, preset)| self.linked_minimap(id, preset))
+        {
+            self.events.emit(Event::MinimapSwitched {
+                id: minimap.id.expect("queried minimap has id"),
+                name: minimap.name.clone(),
+                preset: preset.clone(),
+            });
+            self.set_minimap(preset, Some(minimap));
+        }
+    }
+
+    fn on_switch_loadout(&mut self, name: Option<String>) -> Option<Character> {
+        let mut character = self.character.clone()?;
+        character.loadout = name;
+        if character.loadout.is_some() && character.active_loadout().is_none() {
+            return None;
+        }
+
+        if let Err(err) = upsert_character(&mut character) {
+            error!(target: "handler", "failed to switch loadout {err}");
+            return None;
+        }
+        self.set_character(Some(character.clone()));
+        Some(character)
+    }
+
+    fn on_update_routines(&mut self) {
+        self.routines.load();
+    }
+
+    fn on_update_avoidance_zones(&mut self) {
+        self.avoidance_zones.reload();
+        self.minimap
+            .set_avoidance_zones(self.avoidance_zones.zones().to_vec());
     }
 
     fn on_update_settings(&mut self, settings: Settings) {
         let mut handle_or_default = self.selected_capture_handle.unwrap_or(self.context.handle);
 
-        if settings.capture_mode != self.settings.capture_mode {
+        let current = capture_settings(self.settings, self.minimap.data());
+        let capture = capture_settings(&settings, self.minimap.data());
+        let capture_area_changed = capture.capture_x != current.capture_x
+            || capture.capture_y != current.capture_y
+            || capture.capture_width != current.capture_width
+            || capture.capture_height != current.capture_height;
+        let capture_hdr_mode_changed = capture.capture_hdr_mode != current.capture_hdr_mode;
+        if capture.capture_mode != current.capture_mode
+            || (capture.capture_mode == CaptureMode::BitBltArea && capture_area_changed)
+            || (capture.capture_mode == CaptureMode::WindowsGraphicsCapture
+                && capture_hdr_mode_changed)
+        {
             self.image_capture
-                .set_mode(handle_or_default, settings.capture_mode, &settings);
+                .set_mode(handle_or_default, capture.capture_mode, &capture);
         }
+        let capture_mode = capture.capture_mode;
 
         if settings.input_method != self.settings.input_method
             || settings.input_method_rpc_server_url != self.settings.input_method_rpc_server_url
+            || settings.input_method_serial_port != self.settings.input_method_serial_port
         {
             if let ImageCaptureKind::BitBltArea(capture) = self.image_capture.kind() {
                 handle_or_default = capture.handle();
@@ -336,7 +1974,7 @@ impl RequestHandler for DefaultRequestHandler<'_> {
             }
             match settings.input_method {
                 InputMethod::Default => {
-                    let kind = if matches!(settings.capture_mode, CaptureMode::BitBltArea) {
+                    let kind = if matches!(capture_mode, CaptureMode::BitBltArea) {
                         KeyInputKind::Foreground
                     } else {
                         KeyInputKind::Fixed
@@ -351,10 +1989,24 @@ impl RequestHandler for DefaultRequestHandler<'_> {
                         settings.input_method_rpc_server_url.clone(),
                     ));
                 }
+                InputMethod::Serial => {
+                    self.context.keys.set_method(KeySenderMethod::Serial(
+                        handle_or_default,
+                        settings.input_method_serial_port.clone(),
+                    ));
+                }
             }
         }
 
+        if settings.itinerary != self.settings.itinerary {
+            self.itinerary.reset();
+        }
+        let input_method_changed = settings.input_method != self.settings.input_method;
+        self.context.detection_tuning = settings.detection_tuning;
         *self.settings = settings;
+        if input_method_changed {
+            self.set_minimap_player_config();
+        }
 
         let Some(character) = self.character else {
             return;
@@ -370,6 +2022,163 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         self.context.minimap = Minimap::Detecting;
     }
 
+    fn on_dump_diagnostics(&self) -> String {
+        diagnostics::dump("request").to_string_lossy().into_owned()
+    }
+
+    fn on_resume_last_session(&mut self, start: bool) -> bool {
+        let Some(session) = self.session.last().cloned() else {
+            return false;
+        };
+        info!(target: "handler", "resuming last session {session:?}");
+
+        let character = session.character_id.and_then(|id| {
+            query_characters()
+                .inspect_err(|err| error!(target: "handler", "failed to query characters {err}"))
+                .ok()?
+                .into_iter()
+                .find(|character| character.id == Some(id))
+        });
+        if let Some(character) = character {
+            self.events.emit(Event::CharacterSwitched {
+                id: character.id.expect("queried character has id"),
+                name: character.name.clone(),
+            });
+            self.set_character(Some(character));
+        }
+
+        let minimap = session.minimap_id.and_then(|id| {
+            query_minimaps()
+                .inspect_err(|err| error!(target: "handler", "failed to query minimaps {err}"))
+                .ok()?
+                .into_iter()
+                .find(|minimap| minimap.id == Some(id))
+        });
+        if let Some(minimap) = minimap {
+            let preset = session
+                .preset
+                .filter(|preset| minimap.actions.contains_key(preset));
+            self.events.emit(Event::MinimapSwitched {
+                id: minimap.id.expect("queried minimap has id"),
+                name: minimap.name.clone(),
+                preset: preset.clone(),
+            });
+            self.set_minimap(preset, Some(minimap));
+        }
+
+        if start && !session.halting {
+            self.on_rotate_actions(false);
+        }
+        true
+    }
+
+    fn on_query_update(&self) -> Option<UpdateInfo> {
+        self.update_checker.available().cloned()
+    }
+
+    fn on_apply_update(&mut self) -> bool {
+        self.update_checker
+            .apply()
+            .inspect_err(|err| error!(target: "handler", "failed to apply update {err}"))
+            .is_ok()
+    }
+
+    fn on_wizard_detect_windows(&mut self) -> (Vec<String>, Option<usize>) {
+        let (names, _) = self.on_query_capture_handles();
+        let suggested = suggested_window(&names);
+        (names, suggested)
+    }
+
+    fn on_wizard_test_capture(&mut self, mode: CaptureMode) -> bool {
+        if self.capture_tester.is_running() {
+            return false;
+        }
+        let capture = (mode != self.capture_mode()).then(|| {
+            let handle = self.selected_capture_handle.unwrap_or(self.context.handle);
+            ImageCapture::new(handle, mode, self.settings)
+        });
+        self.capture_tester.start(mode, capture, Instant::now());
+        true
+    }
+
+    fn on_wizard_query_capture_test(&self) -> Option<CaptureTestResult> {
+        self.capture_tester.result()
+    }
+
+    fn on_wizard_test_key(&mut self, key: KeyBinding) -> bool {
+        self.context
+            .keys
+            .send(key.into())
+            .inspect_err(|err| error!(target: "handler", "failed to send test key {err}"))
+            .is_ok()
+    }
+
+    fn on_wizard_finish(&mut self, character_name: String) -> Option<Character> {
+        let mut character = Character {
+            name: character_name,
+            ..Character::default()
+        };
+        if let Err(err) = upsert_character(&mut character) {
+            error!(target: "handler", "failed to create character {err}");
+            return None;
+        }
+        self.settings.setup_completed = true;
+        if let Err(err) = upsert_settings(self.settings) {
+            error!(target: "handler", "failed to complete setup {err}");
+        }
+        self.set_character(Some(character.clone()));
+        Some(character)
+    }
+
+    fn on_test_input_latency(&mut self) -> bool {
+        if !self.context.halting || self.latency_tester.is_running() {
+            return false;
+        }
+        self.latency_tester
+            .start(self.settings.input_method, Instant::now());
+        true
+    }
+
+    fn on_query_input_latencies(&self) -> Vec<InputLatency> {
+        self.latency_tester.results().to_vec()
+    }
+
+    fn on_query_action_stats(&self) -> Vec<ActionStats> {
+        self.action_stats.stats()
+    }
+
+    fn on_query_diagnostics(&self) -> Vec<ModelDiagnostics> {
+        self_test::results()
+    }
+
+    fn on_query_status(&self) -> BotStatus {
+        BotStatus {
+            halting: self.context.halting,
+            character: self
+                .character
+                .as_ref()
+                .map(|character| character.name.clone()),
+            summary: session_summary(
+                self.shutdown.runtime(Instant::now()),
+                self.minimap.data().map(|minimap| minimap.name.as_str()),
+                self.exp_stats.exp_per_hour(),
+                self.exp_stats.exp_percent_per_hour(),
+            ),
+        }
+    }
+
+    fn on_record_video(&mut self, start: bool) -> Option<String> {
+        if !start {
+            self.video_recorder.stop();
+            return None;
+        }
+        self.video_recorder
+            .start(self.settings.video_recording.format)
+            .inspect_err(|err| error!(target: "handler", "failed to start recording {err}"))
+            .ok()
+            .map(|path| path.to_string_lossy().into_owned())
+    }
+
     #[inline]
     fn on_game_state_receiver(&self) -> broadcast::Receiver<GameState> {
         GAME_STATE.subscribe()
@@ -380,6 +2189,10 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         self.key_sender.subscribe()
     }
 
+    fn on_event_receiver(&self) -> broadcast::Receiver<EventRecord> {
+        self.events.subscribe()
+    }
+
     fn on_query_capture_handles(&mut self) -> (Vec<String>, Option<usize>) {
         *self.capture_handles = query_capture_handles();
 
@@ -401,38 +2214,14 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         (names, selected)
     }
 
-    fn on_select_capture_handle(&mut self, index: Option<usize>) {
-        let handle = index
+    fn on_select_capture_handle(&mut self, index: Option<usize>) -> Option<CaptureWindow> {
+        let (window, handle) = index
             .and_then(|index| self.capture_handles.get(index))
-            .map(|(_, handle)| *handle);
-        let handle_or_default = handle.unwrap_or(self.context.handle);
+            .map(|(title, handle)| (capture_window_of(title, *handle), *handle))
+            .unzip();
 
-        *self.selected_capture_handle = handle;
-        self.image_capture
-            .set_mode(handle_or_default, self.settings.capture_mode, &self.settings);
-        
-        // For BitBltArea, use Foreground key input kind, otherwise use Fixed
-        let key_input_kind = if matches!(self.settings.capture_mode, CaptureMode::BitBltArea) {
-            KeyInputKind::Foreground
-        } else {
-            KeyInputKind::Fixed
-        };
-        
-        *self.key_receiver = KeyReceiver::new(handle_or_default, key_input_kind);
-        match self.settings.input_method {
-            InputMethod::Default => {
-                self.context.keys.set_method(KeySenderMethod::Default(
-                    handle_or_default,
-                    key_input_kind,
-                ));
-            }
-            InputMethod::Rpc => {
-                self.context.keys.set_method(KeySenderMethod::Rpc(
-                    handle_or_default,
-                    self.settings.input_method_rpc_server_url.clone(),
-                ));
-            }
-        }
+        self.set_capture_handle(handle);
+        window
     }
 
     #[cfg(debug_assertions)]
@@ -450,8 +2239,8 @@ impl RequestHandler for DefaultRequestHandler<'_> {
     #[cfg(debug_assertions)]
     fn on_infer_minimap(&self) {
         if let Some(ref detector) = self.context.detector {
-            // FIXME: 160 matches one in minimap.rs
-            if let Ok(rect) = detector.detect_minimap(160) {
+            let border_threshold = self.context.detection_tuning.minimap_border_threshold;
+            if let Ok(rect) = detector.detect_minimap(None, border_threshold) {
                 save_minimap_for_training(detector.mat(), rect);
             }
         }
@@ -506,6 +2295,23 @@ impl RequestHandler for DefaultRequestHandler<'_> {
             }
         }
     }
+
+    #[cfg(debug_assertions)]
+    fn on_run_benchmarks(&self, iterations: u32) -> Vec<BenchmarkLatency> {
+        let Some(mat) = self
+            .context
+            .detector
+            .as_ref()
+            .and_then(|detector| detector.mat().try_clone().ok())
+        else {
+            return vec![];
+        };
+        let latencies = run_benchmarks(&BenchmarkFrame::new(mat), iterations);
+        for latency in &latencies {
+            debug!(target: "test", "benchmark {latency:?}");
+        }
+        latencies
+    }
 }
 
 // TODO: should only handle a single matched key binding
@@ -517,7 +2323,9 @@ fn poll_key(handler: &mut DefaultRequestHandler) {
     debug!(target: "handler", "received key {received_key:?}");
     
     // Handle toggle actions key
-    if let KeyBindingConfiguration { key, enabled: true } = handler.settings.toggle_actions_key
+    if let KeyBindingConfiguration {
+        key, enabled: true, ..
+    } = handler.settings.toggle_actions_key
         && KeyKind::from(key) == received_key
     {
         handler.on_rotate_actions(!handler.context.halting);
@@ -544,8 +2352,46 @@ fn extract_minimap(context: &Context, mat: &impl MatTraitConst) -> Option<(Vec<u
     None
 }
 
+/// Spawns the user `script` and reaps it on a separate thread once it exits.
+fn spawn_script(script: &str) -> std::io::Result<()> {
+    let mut child = Command::new(script).spawn()?;
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// Applies the capture override of the selected `minimap` to `settings` if any.
+fn capture_settings<'a>(
+    settings: &'a Settings,
+    minimap: Option<&MinimapData>,
+) -> Cow<'a, Settings> {
+    match minimap.and_then(|minimap| minimap.capture_override) {
+        Some(capture) => Cow::Owned(capture.apply(settings)),
+        None => Cow::Borrowed(settings),
+    }
+}
+
+pub fn config_rotator_mode(minimap: &MinimapData) -> RotatorMode {
+    match minimap.rotation_mode {
+        RotationMode::StartToEnd => RotatorMode::StartToEnd,
+        RotationMode::StartToEndThenReverse => RotatorMode::StartToEndThenReverse,
+        RotationMode::Shuffled => RotatorMode::Shuffled,
+        RotationMode::AutoMobbing => RotatorMode::AutoMobbing(
+            minimap.rotation_mobbing_key,
+            minimap.rotation_auto_mob_bound,
+            minimap.rotation_auto_mob_strategy,
+        ),
+        RotationMode::PingPong => RotatorMode::PingPong(
+            minimap.rotation_mobbing_key,
+            minimap.rotation_ping_pong_bound,
+        ),
+    }
+}
+
 fn config_buffs(character: &Character) -> Vec<(BuffKind, KeyBinding)> {
     BuffKind::iter()
+        .filter(|kind| !character.is_loadout_buff_disabled(*kind))
         .filter_map(|kind| {
             let enabled_key = match kind {
                 BuffKind::Rune => None, // Internal buff
@@ -607,9 +2453,53 @@ fn config_buffs(character: &Character) -> Vec<(BuffKind, KeyBinding)> {
         .collect()
 }
 
-fn config_actions(character: &Character) -> Vec<Action> {
+/// Converts the enabled potion keys of `character` into percentage-based potions.
+///
+/// Returns MP-based potions if `mana` is true, health-based potions otherwise.
+fn config_keyed_potions(character: &Character, mana: bool) -> impl Iterator<Item = PlayerPotion> {
+    [
+        (character.potion_key, character.potion_mode),
+        (character.mp_potion_key, character.mp_potion_mode),
+    ]
+    .into_iter()
+    .filter(|(key, _)| key.enabled)
+    .filter_map(move |(key, mode)| {
+        let percent = match (mode, mana) {
+            (PotionMode::Percentage(percent), false)
+            | (PotionMode::MpPercentage(percent), true) => percent,
+            _ => return None,
+        };
+        Some(PlayerPotion {
+            key: key.key.into(),
+            below_ratio: percent / 100.0,
+            cooldown_millis: 0,
+        })
+    })
+}
+
+fn config_key_timings(character: &Character) -> HashMap<KeyKind, KeyTiming> {
+    character
+        .key_timings
+        .iter()
+        .filter(|config| config.enabled)
+        .map(|config| {
+            let timing = KeyTiming {
+                press_millis: config.press_millis,
+                repeat_millis: config.repeat_millis,
+            };
+            (config.key.into(), timing)
+        })
+        .collect()
+}
+
+/// Converts the fixed actions of `character` used on the minimap with `minimap_id` into
+/// [`Action`]s.
+fn config_actions(character: &Character, minimap_id: Option<i64>) -> Vec<Action> {
     let mut vec = Vec::new();
-    if let KeyBindingConfiguration { key, enabled: true } = character.feed_pet_key {
+    if let KeyBindingConfiguration {
+        key, enabled: true, ..
+    } = character.feed_pet_key
+    {
         let feed_pet_action = Action::Key(ActionKey {
             key,
             count: 1,
@@ -622,40 +2512,200 @@ fn config_actions(character: &Character) -> Vec<Action> {
             vec.push(feed_pet_action);
         }
     }
-    if let KeyBindingConfiguration { key, enabled: true } = character.potion_key
-        && let PotionMode::EveryMillis(millis) = character.potion_mode
-    {
-        vec.push(Action::Key(ActionKey {
-            key,
-            count: 1,
-            condition: ActionCondition::EveryMillis(millis),
-            wait_before_use_millis: 350,
-            wait_after_use_millis: 350,
-            ..ActionKey::default()
-        }));
+    for (potion_key, potion_mode) in [
+        (character.potion_key, character.potion_mode),
+        (character.mp_potion_key, character.mp_potion_mode),
+    ] {
+        if let KeyBindingConfiguration {
+            key, enabled: true, ..
+        } = potion_key
+            && let PotionMode::EveryMillis(millis) = potion_mode
+        {
+            vec.push(Action::Key(ActionKey {
+                key,
+                count: 1,
+                condition: ActionCondition::EveryMillis(millis),
+                wait_before_use_millis: 350,
+                wait_after_use_millis: 350,
+                ..ActionKey::default()
+            }));
+        }
     }
 
-    let mut i = 0;
-    let config_actions = &character.actions;
-    while i < config_actions.len() {
-        let action = config_actions[i];
-        let enabled = action.enabled;
+    vec.extend(
+        config_action_groups(character, minimap_id)
+            .filter(|group| !character.burst_window.enabled || !group[0].burst)
+            .flatten()
+            .map(|action| Action::from(action.clone())),
+    );
+    vec
+}
 
-        if enabled {
-            vec.push(action.into());
-        }
-        while i + 1 < config_actions.len() {
-            let action = config_actions[i + 1];
-            if !matches!(action.condition, ActionConfigurationCondition::Linked) {
-                break;
-            }
-            if enabled {
-                vec.push(action.into());
-            }
-            i += 1;
+/// Chains the enabled burst [`ActionConfiguration`]s of `character` into a single sequence of
+/// linked actions.
+///
+/// Returns an empty [`Vec`] if the burst window is disabled.
+fn config_burst_actions(character: &Character, minimap_id: Option<i64>) -> Vec<Action> {
+    if !character.burst_window.enabled {
+        return vec![];
+    }
+
+    config_action_groups(character, minimap_id)
+        .filter(|group| group[0].burst)
+        .flatten()
+        .map(|action| {
+            Action::from(ActionConfiguration {
+                condition: ActionConfigurationCondition::Linked,
+                ..action.clone()
+            })
+        })
+        .collect()
+}
+
+/// Splits the active [`ActionConfiguration`]s of `character` into enabled groups of an action
+/// followed by its linked actions.
+///
+/// Groups filtered to minimaps not including the one with `minimap_id` are skipped.
+fn config_action_groups(
+    character: &Character,
+    minimap_id: Option<i64>,
+) -> impl Iterator<Item = &[ActionConfiguration]> {
+    character
+        .active_actions()
+        .chunk_by(|_, next| matches!(next.condition, ActionConfigurationCondition::Linked))
+        .filter(|group| group[0].enabled)
+        .filter(move |group| {
+            group[0].minimap_ids.is_empty()
+                || minimap_id.is_some_and(|id| group[0].minimap_ids.contains(&id))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
+    #[cfg(target_os = "macos")]
+    use platforms::macos::KeyKind;
+    #[cfg(windows)]
+    use platforms::windows::KeyKind;
+
+    use super::{config_action_groups, config_keyed_potions};
+    use crate::{
+        ActionConfiguration, ActionConfigurationCondition, Character, KeyBinding,
+        KeyBindingConfiguration, PotionMode,
+    };
+
+    fn key(key: KeyBinding, enabled: bool) -> KeyBindingConfiguration {
+        KeyBindingConfiguration {
+            key,
+            enabled,
+            ..KeyBindingConfiguration::default()
         }
+    }
 
-        i += 1;
+    fn keyed_potions(character: &Character, mana: bool) -> Vec<(KeyKind, f32)> {
+        config_keyed_potions(character, mana)
+            .map(|potion| (potion.key, potion.below_ratio))
+            .collect()
+    }
+
+    #[test]
+    fn config_keyed_potions_by_mode() {
+        let mut character = Character {
+            potion_key: key(KeyBinding::A, true),
+            potion_mode: PotionMode::Percentage(50.0),
+            mp_potion_key: key(KeyBinding::B, true),
+            mp_potion_mode: PotionMode::MpPercentage(30.0),
+            ..Character::default()
+        };
+        assert_eq!(
+            keyed_potions(&character, false),
+            vec![(KeyKind::from(KeyBinding::A), 0.5)]
+        );
+        assert_eq!(
+            keyed_potions(&character, true),
+            vec![(KeyKind::from(KeyBinding::B), 0.3)]
+        );
+
+        character.potion_mode = PotionMode::MpPercentage(40.0);
+        character.mp_potion_mode = PotionMode::Percentage(60.0);
+        assert_eq!(
+            keyed_potions(&character, false),
+            vec![(KeyKind::from(KeyBinding::B), 0.6)]
+        );
+        assert_eq!(
+            keyed_potions(&character, true),
+            vec![(KeyKind::from(KeyBinding::A), 0.4)]
+        );
+
+        character.potion_mode = PotionMode::Percentage(50.0);
+        assert_eq!(
+            keyed_potions(&character, false),
+            vec![
+                (KeyKind::from(KeyBinding::A), 0.5),
+                (KeyKind::from(KeyBinding::B), 0.6)
+            ]
+        );
+        assert!(keyed_potions(&character, true).is_empty());
+
+        character.potion_mode = PotionMode::EveryMillis(1000);
+        character.mp_potion_mode = PotionMode::EveryMillis(1000);
+        assert!(keyed_potions(&character, false).is_empty());
+        assert!(keyed_potions(&character, true).is_empty());
+    }
+
+    #[test]
+    fn config_keyed_potions_disabled_key() {
+        let character = Character {
+            potion_key: key(KeyBinding::A, false),
+            potion_mode: PotionMode::Percentage(50.0),
+            mp_potion_key: key(KeyBinding::B, false),
+            mp_potion_mode: PotionMode::MpPercentage(30.0),
+            ..Character::default()
+        };
+
+        assert!(keyed_potions(&character, false).is_empty());
+        assert!(keyed_potions(&character, true).is_empty());
+    }
+
+    #[test]
+    fn config_action_groups_minimap_ids() {
+        let action = |key, condition, minimap_ids| ActionConfiguration {
+            key,
+            condition,
+            enabled: true,
+            minimap_ids,
+            ..ActionConfiguration::default()
+        };
+        let every = ActionConfigurationCondition::default();
+        let linked = ActionConfigurationCondition::Linked;
+        let character = Character {
+            actions: vec![
+                action(KeyBinding::A, every, vec![]),
+                action(KeyBinding::B, linked, vec![]),
+                action(KeyBinding::C, every, vec![1]),
+                action(KeyBinding::D, every, vec![2, 3]),
+            ],
+            ..Character::default()
+        };
+        let group_keys = |minimap_id| {
+            config_action_groups(&character, minimap_id)
+                .map(|group| group.iter().map(|action| action.key).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(group_keys(None), vec![vec![KeyBinding::A, KeyBinding::B]]);
+        assert_eq!(
+            group_keys(Some(1)),
+            vec![vec![KeyBinding::A, KeyBinding::B], vec![KeyBinding::C]]
+        );
+        assert_eq!(
+            group_keys(Some(3)),
+            vec![vec![KeyBinding::A, KeyBinding::B], vec![KeyBinding::D]]
+        );
+        assert_eq!(
+            group_keys(Some(4)),
+            vec![vec![KeyBinding::A, KeyBinding::B]]
+        );
     }
-    vec
 }
@@ -1,7 +1,11 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, time::Duration};
 
 use noise::{NoiseFn, Perlin};
-use rand::{Rng as RandRng, SeedableRng, rngs::StdRng, seq::IteratorRandom};
+use rand::{
+    Rng as RandRng, SeedableRng,
+    rngs::StdRng,
+    seq::{IteratorRandom, SliceRandom},
+};
 use rand_distr::{
     Distribution, Normal,
     uniform::{SampleRange, SampleUniform},
@@ -67,6 +71,20 @@ impl Rng {
         iter.choose(&mut self.inner.borrow_mut())
     }
 
+    #[inline]
+    pub fn random_shuffle<T>(&self, slice: &mut [T]) {
+        slice.shuffle(&mut self.inner.borrow_mut());
+    }
+
+    /// Samples a duration of `millis` offset uniformly by up to `jitter_millis` either way.
+    ///
+    /// The jitter is capped to `millis` so that the duration is never negative.
+    pub fn random_jittered_duration(&self, millis: u64, jitter_millis: u64) -> Duration {
+        let jitter = jitter_millis.min(millis) as i64;
+        let millis = millis as i64 + self.random_range(-jitter..=jitter);
+        Duration::from_millis(millis as u64)
+    }
+
     /// Samples a random `(delay, tick count)` pair.
     ///
     /// The delay is sampled from a normal distribution with mean `mean_ms` and
@@ -1,27 +1,36 @@
 use std::{
     assert_matches::debug_assert_matches,
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
+    mem,
     sync::atomic::{AtomicU32, Ordering},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
-use log::debug;
+use log::{debug, warn};
 use opencv::core::{Point, Rect};
 use ordered_hash_map::OrderedHashMap;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    ActionKeyDirection, ActionKeyWith, Bound, FamiliarRarity, KeyBinding, MobbingKey, Position,
-    SwappableFamiliars,
+    ActionKeyDirection, ActionKeyWith, Bound, FamiliarLevelPriority, FamiliarRarity,
+    FamiliarRarityPriority, KeyBinding, MobbingKey, Position, SellItems, SwappableFamiliars,
     array::Array,
     buff::{Buff, BuffKind},
-    context::{Context, MS_PER_TICK},
-    database::{Action, ActionCondition, ActionKey, ActionMove, EliteBossBehavior},
-    minimap::Minimap,
+    context::{Context, ms_per_tick},
+    database::{
+        Action, ActionCondition, ActionFailure, ActionGroupPredicate, ActionKey, ActionMouse,
+        ActionMove, AutoMobRegion, AutoMobRegionOrder, AutoMobStrategy, ComboAction,
+        EliteBossBehavior, PingPongBand,
+    },
+    metrics,
+    minimap::{Minimap, MinimapIdle},
+    network::NotificationKind,
     player::{
-        GRAPPLING_THRESHOLD, PanicTo, PingPongDirection, Player, PlayerAction, PlayerActionAutoMob,
-        PlayerActionFamiliarsSwapping, PlayerActionKey, PlayerActionPanic, PlayerActionPingPong,
-        PlayerState, Quadrant,
+        COMBO_MAX_STEPS, PanicTo, PingPongDirection, Player, PlayerAction, PlayerActionAutoMob,
+        PlayerActionCombo, PlayerActionFamiliarsSwapping, PlayerActionKey, PlayerActionMove,
+        PlayerActionPanic, PlayerActionPingPong, PlayerActionSellItems, PlayerState, Quadrant,
+        SELL_ITEMS_MAX_SLOTS, SELL_ITEMS_MAX_TABS,
     },
     skill::{Skill, SkillKind},
     task::{Task, Update, update_detection_task},
@@ -29,6 +38,49 @@ use crate::{
 
 const COOLDOWN_BETWEEN_QUEUE_MILLIS: u128 = 20_000;
 const AUTO_MOB_SAME_QUAD_THRESHOLD: u32 = 5;
+/// The number of mob detections inside an auto mobbing region before moving to the next region.
+const AUTO_MOB_REGION_SWITCH_THRESHOLD: u32 = 15;
+/// The maximum horizontal distance between two mob positions to be considered the same cluster.
+const AUTO_MOB_CLUSTER_X_THRESHOLD: i32 = 10;
+/// The maximum vertical distance between two mob positions to be considered the same cluster.
+const AUTO_MOB_CLUSTER_Y_THRESHOLD: i32 = 4;
+/// The x range around an attempted drop position to ignore until the ignore timer expires.
+const AUTO_MOB_PICKUP_IGNORE_X_RANGE: i32 = 5;
+/// The y range around an attempted drop position to ignore until the ignore timer expires.
+const AUTO_MOB_PICKUP_IGNORE_Y_RANGE: i32 = 3;
+/// The minimum milliseconds between moving the player to the expected rune spawn position.
+const RUNE_SPAWN_HINT_MOVE_MILLIS: u128 = 20_000;
+/// The x and y range around the expected rune spawn position the player is considered near.
+const RUNE_SPAWN_HINT_RANGE: i32 = 15;
+
+/// The priority of user actions and [`ActionCondition::ErdaShowerOffCooldown`].
+const PRIORITY_DEFAULT: u8 = 0;
+/// The priority of user actions with `queue_to_front`, burst, combos, familiars swapping and
+/// selling items.
+const PRIORITY_QUEUE_TO_FRONT: u8 = 1;
+/// The priority of buffs and familiar essence replenishing.
+const PRIORITY_BUFF: u8 = 2;
+/// The priority of rune solving.
+const PRIORITY_RUNE: u8 = 3;
+/// The priority of elite boss actions.
+const PRIORITY_ELITE_BOSS: u8 = 4;
+
+/// The normal action id of auto mobbing.
+const ACTION_ID_AUTO_MOB: u32 = u32::MAX;
+/// The normal action id of ping pong.
+const ACTION_ID_PING_PONG: u32 = u32::MAX - 1;
+/// The normal action id of picking up a drop while auto mobbing.
+const ACTION_ID_AUTO_MOB_PICKUP: u32 = u32::MAX - 2;
+/// The normal action id of moving to the elite boss.
+const ACTION_ID_ELITE_BOSS_MOVE: u32 = u32::MAX - 3;
+/// The normal action id of moving to the expected rune spawn position.
+const ACTION_ID_RUNE_SPAWN_HINT_MOVE: u32 = u32::MAX - 4;
+/// The normal action id of moving to the followed leader position.
+const ACTION_ID_FOLLOW_MOVE: u32 = u32::MAX - 5;
+/// The normal action id of moving to the break position.
+const ACTION_ID_BREAK_MOVE: u32 = u32::MAX - 6;
+/// The normal action id of an [`Action`] proposed by a plugin.
+pub const ACTION_ID_PLUGIN: u32 = u32::MAX - 7;
 
 /// [`Condition`] evaluation result.
 enum ConditionResult {
@@ -51,6 +103,19 @@ impl std::fmt::Debug for Condition {
     }
 }
 
+/// How a [`PriorityAction`] can preempt the player's current priority action.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Preemption {
+    /// Never preempts and waits for the current priority action to complete.
+    Never,
+    /// Preempts a lower priority action only while the player can be overridden such as when
+    /// moving.
+    Movement,
+    /// Preempts a lower priority action in any state except the ones that are sending keys or
+    /// interacting with the game UI such as [`Player::UseKey`].
+    Anything,
+}
+
 /// A priority action that can override a normal action.
 ///
 /// This includes all non-[`ActionCondition::Any`] actions.
@@ -60,10 +125,10 @@ impl std::fmt::Debug for Condition {
 /// However, it cannot override player states that are considered "terminal". These states
 /// include stalling, using key and forced double jumping. It also cannot override linked action.
 ///
-/// When this type of action has [`Self::queue_to_front`] set, it will be queued to the
-/// front and override other non-[`Self::queue_to_front`] priority action. The overriden
-/// action is simply placed back to the queue in front. It is mostly useful for action such as
-/// `press attack after x seconds even in the middle of moving`.
+/// Queued priority actions are ordered by [`Self::priority`] and then by queue order. When the
+/// player is already executing a priority action, the front action can only replace it if it has
+/// a higher [`Self::priority`] and its [`Self::preemption`] allows it. The replaced action is
+/// placed back to the queue in front of the others with the same priority.
 #[derive(Debug)]
 struct PriorityAction {
     /// The predicate for when this action should be queued.
@@ -72,8 +137,10 @@ struct PriorityAction {
     condition_kind: Option<ActionCondition>,
     /// The inner action.
     inner: RotatorAction,
-    /// Whether to queue this action to the front of [`Rotator::priority_actions_queue`].
-    queue_to_front: bool,
+    /// The priority with higher value rotated first.
+    priority: u8,
+    /// How this action can preempt the player's current priority action.
+    preemption: Preemption,
     /// Whether this action is being ignored.
     ///
     /// While ignored, [`Self::last_queued_time`] will be updated to [`Instant::now`].
@@ -84,16 +151,113 @@ struct PriorityAction {
     last_queued_time: Option<Instant>,
 }
 
+/// The retry and failure policy of an action built from [`Action::Move`] or [`Action::Key`].
+///
+/// A linked action uses the policy of its first action.
+#[derive(Clone, Copy, Debug)]
+struct FailurePolicy {
+    max_retries: u32,
+    retry_delay_millis: u64,
+    on_failure: ActionFailure,
+}
+
+impl FailurePolicy {
+    fn from_action(action: Action) -> Option<Self> {
+        match action {
+            Action::Move(ActionMove {
+                max_retries,
+                retry_delay_millis,
+                on_failure,
+                ..
+            })
+            | Action::Key(ActionKey {
+                max_retries,
+                retry_delay_millis,
+                on_failure,
+                ..
+            }) => Some(Self {
+                max_retries,
+                retry_delay_millis,
+                on_failure,
+            }),
+            Action::Group(_) | Action::Mouse(_) => None,
+        }
+    }
+}
+
+/// An action that failed to reach its position and is being retried.
+#[derive(Debug)]
+struct ActionRetry {
+    id: u32,
+    action: PlayerAction,
+    is_priority: bool,
+    /// The number of retries so far.
+    count: u32,
+    /// The [`Instant`] to retry at or [`None`] if the action has been passed to the player.
+    retry_instant: Option<Instant>,
+}
+
 /// The action that will be passed to the player
 ///
 /// There are [`RotatorAction::Single`] and [`RotatorAction::Linked`] actions.
 /// With [`RotatorAction::Linked`] action is a linked list of actions. [`RotatorAction::Linked`]
 /// action is executed in order, until completion and cannot be replaced by any other
 /// type of actions.
+///
+/// [`RotatorAction::Branch`] is resolved to one of its [`LinkedAction`] when rotated and is
+/// executed the same as [`RotatorAction::Linked`].
 #[derive(Clone, Debug)]
 enum RotatorAction {
     Single(PlayerAction),
     Linked(LinkedAction),
+    Branch(BranchAction),
+}
+
+impl RotatorAction {
+    #[inline]
+    fn is_linked(&self) -> bool {
+        matches!(self, RotatorAction::Linked(_) | RotatorAction::Branch(_))
+    }
+
+    /// The action name for displaying to UI.
+    fn name(&self) -> String {
+        match self {
+            RotatorAction::Single(action) => action.to_string(),
+            RotatorAction::Linked(action) => linked_action_names(action).join(" > "),
+            RotatorAction::Branch(action) => {
+                let names = |action: &Option<LinkedAction>| {
+                    action
+                        .as_ref()
+                        .map(|action| linked_action_names(action).join(" > "))
+                        .unwrap_or_else(|| "Nothing".to_string())
+                };
+                format!(
+                    "If {} then {} else {}",
+                    action.predicate,
+                    names(&action.then),
+                    names(&action.otherwise)
+                )
+            }
+        }
+    }
+}
+
+/// Two linked lists of actions with the predicate to choose between them
+#[derive(Clone, Debug)]
+struct BranchAction {
+    predicate: ActionGroupPredicate,
+    then: Option<LinkedAction>,
+    otherwise: Option<LinkedAction>,
+}
+
+impl BranchAction {
+    fn resolve(self, context: &Context, player: &PlayerState) -> Option<LinkedAction> {
+        if is_branch_predicate_met(context, player, self.predicate) {
+            self.then
+        } else {
+            self.otherwise
+        }
+    }
 }
 
 /// A linked list of actions
@@ -103,13 +267,31 @@ struct LinkedAction {
     next: Option<Box<LinkedAction>>,
 }
 
+/// A priority action and its queuing state for displaying to UI.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct UpcomingPriorityAction {
+    pub name: String,
+    /// The user condition of this action or [`None`] if it is built-in such as buffs.
+    pub condition: Option<ActionCondition>,
+    /// Whether this action is queued and waiting for the player to execute it.
+    pub queued: bool,
+    /// The remaining milliseconds until this action can be queued again.
+    ///
+    /// This is [`None`] if the action is not queued on a timer.
+    pub remaining_millis: Option<u64>,
+}
+
 /// The rotator's rotation mode
 #[derive(Default, Debug)]
 pub enum RotatorMode {
     StartToEnd,
     #[default]
     StartToEndThenReverse,
-    AutoMobbing(MobbingKey, Bound),
+    /// Rotates [`Rotator::normal_actions`] from start to end and shuffles them on each cycle.
+    ///
+    /// Linked actions are kept together because each is a single [`RotatorAction::Linked`].
+    Shuffled,
+    AutoMobbing(MobbingKey, Bound, AutoMobStrategy),
     PingPong(MobbingKey, Bound),
 }
 
@@ -131,30 +313,93 @@ pub struct Rotator {
     /// This limits the number of detections can be done inside the same quad as to help player
     /// advances to the next quad.
     auto_mob_quadrant_consecutive_count: Option<(Quadrant, u32)>,
+    /// The sub-bounds visited in order with [`AutoMobStrategy::Sequence`].
+    auto_mob_sequence: Vec<Bound>,
+    /// The index of the current sub-bound in [`Self::auto_mob_sequence`] and the number of mob
+    /// detections completed inside it.
+    auto_mob_sequence_index: Option<(usize, u32)>,
+    /// The regions used in place of the [`RotatorMode::AutoMobbing`] bound when not empty.
+    auto_mob_regions: Vec<AutoMobRegion>,
+    auto_mob_region_order: AutoMobRegionOrder,
+    /// The index of the current region in [`Self::auto_mob_regions`] and the number of mob
+    /// detections completed inside it.
+    auto_mob_region_index: Option<(usize, u32)>,
+    /// The vertical bands used with [`RotatorMode::PingPong`].
+    ping_pong_bands: Vec<PingPongBand>,
+    /// The key to pick up drops in auto mobbing with [`None`] indicating disabled.
+    auto_mob_pickup_key: Option<KeyBinding>,
+    /// The milliseconds an attempted drop position is ignored.
+    auto_mob_pickup_ignore_millis: u64,
+    /// The [`Task`] for detecting drops when [`Self::auto_mob_pickup_key`] is [`Some`].
+    auto_mob_pickup_task: Option<Task<Result<Vec<Point>>>>,
+    /// Drop positions that have been attempted to pick up and the instant of the attempt.
+    auto_mob_pickup_ignored: Vec<(Point, Instant)>,
+    /// Whether [`EliteBossBehavior::Fight`] is used.
+    elite_boss_fight_enabled: bool,
+    /// The normal actions rotated from start to end in place of [`Self::normal_actions`] while
+    /// fighting an elite boss.
+    elite_boss_fight_actions: Vec<(u32, RotatorAction)>,
+    elite_boss_fight_index: usize,
+    /// Whether an elite boss is currently being fought.
+    elite_boss_fighting: bool,
+    /// Whether the player has been sent to the elite boss position in the current fight.
+    elite_boss_fight_moved: bool,
+    /// The position the next rune is expected to spawn at.
+    rune_spawn_hint: Option<Point>,
+    /// The last time the player was moved to [`Self::rune_spawn_hint`].
+    rune_spawn_hint_last_move: Option<Instant>,
+    /// The leader position to move toward when following another instance.
+    follow_target: Option<Point>,
+    /// The position to move toward before taking a break.
+    break_target: Option<Point>,
     priority_actions: OrderedHashMap<u32, PriorityAction>,
     /// The currently executing [`RotatorAction::Linked`] action
     priority_queuing_linked_action: Option<(u32, Box<LinkedAction>)>,
-    /// A [`VecDeque`] of [`PriorityAction`] ids
+    /// A [`VecDeque`] of [`PriorityAction`] ids ordered by [`PriorityAction::priority`]
     ///
     /// Populates from [`Self::priority_actions`] when its predicate for queuing is true
     priority_actions_queue: VecDeque<u32>,
+    /// The [`FailurePolicy`] of user actions by id.
+    failure_policies: HashMap<u32, FailurePolicy>,
+    /// The index in [`RotatorBuildArgs::actions`] of user actions by id.
+    action_indices: HashMap<u32, usize>,
+    /// The failed action currently being retried.
+    action_retry: Option<ActionRetry>,
 }
 
 #[derive(Debug)]
 pub struct RotatorBuildArgs<'a> {
     pub mode: RotatorMode,
     pub actions: &'a [Action],
+    /// The actions used together in order every `burst_interval_millis`.
+    pub burst_actions: &'a [Action],
+    pub burst_interval_millis: u64,
+    pub combos: &'a [ComboAction],
     pub buffs: &'a [(BuffKind, KeyBinding)],
     pub familiar_essence_key: KeyBinding,
     pub familiar_swappable_slots: SwappableFamiliars,
     pub familiar_swappable_rarities: &'a HashSet<FamiliarRarity>,
     pub familiar_swap_check_millis: u64,
+    pub familiar_swap_when_points_depleted: bool,
+    pub familiar_rarity_priority: FamiliarRarityPriority,
+    pub familiar_level_priority: FamiliarLevelPriority,
     pub elite_boss_behavior: Option<EliteBossBehavior>,
     pub elite_boss_behavior_key: KeyBinding,
-    pub enable_panic_mode: bool,
+    /// The actions used while fighting an elite boss with [`EliteBossBehavior::Fight`].
+    pub elite_boss_fight_actions: &'a [Action],
     pub enable_rune_solving: bool,
     pub enable_familiars_swapping: bool,
     pub enable_reset_normal_actions_on_erda: bool,
+    pub auto_mob_pickup_key: Option<KeyBinding>,
+    pub auto_mob_pickup_ignore_millis: u64,
+    pub auto_mob_sequence: &'a [Bound],
+    pub auto_mob_regions: &'a [AutoMobRegion],
+    pub auto_mob_region_order: AutoMobRegionOrder,
+    pub ping_pong_bands: &'a [PingPongBand],
+    pub sell_items: Option<&'a SellItems>,
+    /// The id of the active character [`Loadout`](crate::database::Loadout) for
+    /// [`ActionCondition::LoadoutActive`].
+    pub loadout_id: Option<u32>,
 }
 
 impl Rotator {
@@ -163,54 +408,118 @@ impl Rotator {
         let RotatorBuildArgs {
             mode,
             actions,
+            burst_actions,
+            burst_interval_millis,
+            combos,
             buffs,
             familiar_essence_key,
             familiar_swappable_slots,
             familiar_swappable_rarities,
             familiar_swap_check_millis,
+            familiar_swap_when_points_depleted,
+            familiar_rarity_priority,
+            familiar_level_priority,
             elite_boss_behavior,
             elite_boss_behavior_key,
-            enable_panic_mode,
+            elite_boss_fight_actions,
             enable_rune_solving,
             enable_familiars_swapping,
             enable_reset_normal_actions_on_erda,
+            auto_mob_pickup_key,
+            auto_mob_pickup_ignore_millis,
+            auto_mob_sequence,
+            auto_mob_regions,
+            auto_mob_region_order,
+            ping_pong_bands,
+            sell_items,
+            loadout_id,
         } = args;
         self.reset_queue();
         self.normal_actions.clear();
         self.normal_rotate_mode = mode;
         self.normal_actions_reset_on_erda = enable_reset_normal_actions_on_erda;
+        self.auto_mob_pickup_key = auto_mob_pickup_key;
+        self.auto_mob_pickup_ignore_millis = auto_mob_pickup_ignore_millis;
+        self.auto_mob_pickup_ignored.clear();
+        self.auto_mob_sequence = auto_mob_sequence.to_vec();
+        self.auto_mob_regions = auto_mob_regions.to_vec();
+        self.auto_mob_region_order = auto_mob_region_order;
+        self.ping_pong_bands = ping_pong_bands.to_vec();
         self.priority_actions.clear();
+        self.failure_policies.clear();
+        self.action_indices.clear();
+        self.elite_boss_fight_enabled = false;
+        self.elite_boss_fight_actions.clear();
 
         let mut i = 0;
         while i < actions.len() {
             let action = actions[i];
             let condition = action.condition();
+            let policy = FailurePolicy::from_action(action);
             let queue_to_front = match action {
-                Action::Move(_) => false,
+                Action::Move(_) | Action::Group(_) | Action::Mouse(_) => false,
                 Action::Key(ActionKey { queue_to_front, .. }) => queue_to_front.unwrap_or_default(),
             };
             let (action, offset) = rotator_action(action, i, actions);
-            debug_assert!(i != 0 || !matches!(condition, ActionCondition::Linked));
+            let index = i;
             // Should not move i below the match because it could cause
             // infinite loop due to auto mobbing ignoring Any condition
             i += offset;
             match condition {
                 ActionCondition::EveryMillis(_) | ActionCondition::ErdaShowerOffCooldown => {
+                    let id = self.id_counter.fetch_add(1, Ordering::Relaxed);
                     self.priority_actions.insert(
-                        self.id_counter.fetch_add(1, Ordering::Relaxed),
-                        priority_action(action, condition, queue_to_front),
+                        id,
+                        priority_action(action, condition, user_priority(queue_to_front)),
                     );
+                    self.failure_policies
+                        .extend(policy.map(|policy| (id, policy)));
+                    self.action_indices.insert(id, index);
                 }
-                ActionCondition::Any => {
-                    if matches!(self.normal_rotate_mode, RotatorMode::AutoMobbing(_, _)) {
+                ActionCondition::LoadoutActive(id) if loadout_id != Some(id) => (),
+                ActionCondition::Any | ActionCondition::LoadoutActive(_) => {
+                    if matches!(self.normal_rotate_mode, RotatorMode::AutoMobbing(_, _, _)) {
                         continue;
                     }
-                    self.normal_actions
-                        .push((self.id_counter.fetch_add(1, Ordering::Relaxed), action))
+                    let id = self.id_counter.fetch_add(1, Ordering::Relaxed);
+                    self.normal_actions.push((id, action));
+                    self.failure_policies
+                        .extend(policy.map(|policy| (id, policy)));
+                    self.action_indices.insert(id, index);
+                }
+                // A linked action not following another action (e.g. a linked group, which
+                // cannot be chained) has nothing to link to
+                ActionCondition::Linked => {
+                    warn!(target: "rotator", "skipping linked action without a preceding action");
                 }
-                ActionCondition::Linked => unreachable!(),
             }
         }
+        if let Some(action) = linked_action(burst_actions) {
+            self.priority_actions.insert(
+                self.id_counter.fetch_add(1, Ordering::Relaxed),
+                priority_action(
+                    RotatorAction::Linked(action),
+                    ActionCondition::EveryMillis(burst_interval_millis),
+                    (PRIORITY_QUEUE_TO_FRONT, Preemption::Movement),
+                ),
+            );
+        }
+        for combo in combos
+            .iter()
+            .filter(|combo| combo.enabled && !combo.steps.is_empty())
+        {
+            let action = PlayerActionCombo {
+                steps: combo.steps.iter().copied().take(COMBO_MAX_STEPS).collect(),
+            };
+            self.priority_actions.insert(
+                self.id_counter.fetch_add(1, Ordering::Relaxed),
+                priority_action(
+                    RotatorAction::Single(PlayerAction::Combo(action)),
+                    ActionCondition::EveryMillis(combo.every_millis),
+                    (PRIORITY_QUEUE_TO_FRONT, Preemption::Movement),
+                ),
+            );
+        }
 
         if buffs
             .iter()
@@ -241,6 +550,24 @@ impl Rotator {
                         elite_boss_use_key_priority_action(elite_boss_behavior_key),
                     );
                 }
+                EliteBossBehavior::Fight => {
+                    self.elite_boss_fight_enabled = true;
+                    let mut i = 0;
+                    while i < elite_boss_fight_actions.len() {
+                        let action = elite_boss_fight_actions[i];
+                        let condition = action.condition();
+                        let policy = FailurePolicy::from_action(action);
+                        let (action, offset) = rotator_action(action, i, elite_boss_fight_actions);
+                        i += offset;
+                        // Only normal actions are used as the fight rotation
+                        if matches!(condition, ActionCondition::Any) {
+                            let id = self.id_counter.fetch_add(1, Ordering::Relaxed);
+                            self.elite_boss_fight_actions.push((id, action));
+                            self.failure_policies
+                                .extend(policy.map(|policy| (id, policy)));
+                        }
+                    }
+                }
             }
         }
         if enable_familiars_swapping {
@@ -253,17 +580,20 @@ impl Rotator {
                             swappable_rarities: Array::from_iter(
                                 familiar_swappable_rarities.clone(),
                             ),
+                            swap_when_points_depleted: familiar_swap_when_points_depleted,
+                            rarity_priority: familiar_rarity_priority,
+                            level_priority: familiar_level_priority,
                         },
                     )),
                     ActionCondition::EveryMillis(familiar_swap_check_millis),
-                    true,
+                    (PRIORITY_QUEUE_TO_FRONT, Preemption::Movement),
                 ),
             );
         }
-        if enable_panic_mode {
+        if let Some(sell_items) = sell_items {
             self.priority_actions.insert(
                 self.id_counter.fetch_add(1, Ordering::Relaxed),
-                panic_priority_action(),
+                sell_items_priority_action(sell_items),
             );
         }
         for (i, key) in buffs.iter().copied() {
@@ -274,13 +604,215 @@ impl Rotator {
         }
     }
 
+    /// Rebuilds the actions from `args` without resetting the priority actions queue.
+    ///
+    /// Priority actions built the same as before keep their id and last queued time so their
+    /// timers carry over. Queued priority actions that are no longer built are dropped.
+    pub fn rebuild_actions(&mut self, args: RotatorBuildArgs<'_>) {
+        let mut previous = mem::take(&mut self.priority_actions)
+            .into_iter()
+            .map(|(id, action)| (priority_action_key(&action), id, action))
+            .collect::<Vec<_>>();
+        let queue = mem::take(&mut self.priority_actions_queue);
+        let queuing_linked_action = self.priority_queuing_linked_action.take();
+        let action_retry = self.action_retry.take();
+
+        self.build_actions(args);
+        for (id, mut action) in mem::take(&mut self.priority_actions) {
+            let key = priority_action_key(&action);
+            let id = match previous.iter().position(|(other, _, _)| *other == key) {
+                Some(index) => {
+                    let (_, previous_id, previous_action) = previous.swap_remove(index);
+                    action.last_queued_time = previous_action.last_queued_time;
+                    action.ignoring = previous_action.ignoring;
+                    if let Some(policy) = self.failure_policies.remove(&id) {
+                        self.failure_policies.insert(previous_id, policy);
+                    }
+                    if let Some(index) = self.action_indices.remove(&id) {
+                        self.action_indices.insert(previous_id, index);
+                    }
+                    previous_id
+                }
+                None => id,
+            };
+            self.priority_actions.insert(id, action);
+        }
+
+        self.priority_actions_queue = queue
+            .into_iter()
+            .filter(|id| self.priority_actions.get(id).is_some())
+            .collect();
+        self.priority_queuing_linked_action =
+            queuing_linked_action.filter(|(id, _)| self.priority_actions.get(id).is_some());
+        self.action_retry = action_retry
+            .filter(|retry| retry.is_priority && self.priority_actions.get(&retry.id).is_some());
+    }
+
     #[inline]
     pub fn reset_queue(&mut self) {
         self.normal_actions_backward = false;
         self.reset_normal_actions_queue();
         self.priority_actions_queue.clear();
         self.priority_queuing_linked_action = None;
+        self.action_retry = None;
         self.auto_mob_quadrant_consecutive_count = None;
+        self.auto_mob_sequence_index = None;
+        self.auto_mob_region_index = None;
+        self.elite_boss_fighting = false;
+        self.elite_boss_fight_index = 0;
+        self.elite_boss_fight_moved = false;
+        self.rune_spawn_hint_last_move = None;
+    }
+
+    /// Whether the normal actions are at the start of a new cycle.
+    ///
+    /// For [`RotatorMode::StartToEndThenReverse`], a cycle is going from start to end and then
+    /// back to start. Modes without normal actions are always at a cycle boundary.
+    pub fn is_at_cycle_boundary(&self) -> bool {
+        if self.normal_queuing_linked_action.is_some() {
+            return false;
+        }
+        match self.normal_rotate_mode {
+            RotatorMode::StartToEnd | RotatorMode::Shuffled => self.normal_index == 0,
+            RotatorMode::StartToEndThenReverse => {
+                self.normal_index == 0
+                    || (self.normal_actions_backward
+                        && self.normal_index + 1 == self.normal_actions.len())
+            }
+            RotatorMode::AutoMobbing(_, _, _) | RotatorMode::PingPong(_, _) => true,
+        }
+    }
+
+    /// The index in [`RotatorBuildArgs::actions`] of the user action with `id`.
+    #[inline]
+    pub fn action_index(&self, id: u32) -> Option<usize> {
+        self.action_indices.get(&id).copied()
+    }
+
+    /// The names of the next `count` normal actions in rotation order.
+    ///
+    /// This is empty for [`RotatorMode::AutoMobbing`] and [`RotatorMode::PingPong`] because their
+    /// actions are only known when rotated. For [`RotatorMode::Shuffled`], only the actions until
+    /// the end of the current cycle are known.
+    pub fn upcoming_normal_actions(&self, count: usize) -> Vec<String> {
+        let linked = self
+            .normal_queuing_linked_action
+            .iter()
+            .flat_map(|(_, action)| linked_action_names(action));
+        let (actions, indices) = if self.elite_boss_fighting {
+            let len = self.elite_boss_fight_actions.len();
+            let indices = (0..len)
+                .map(|i| (self.elite_boss_fight_index + i) % len)
+                .collect::<Vec<_>>();
+            (&self.elite_boss_fight_actions, indices)
+        } else {
+            (&self.normal_actions, self.upcoming_normal_indices(count))
+        };
+
+        linked
+            .chain(indices.into_iter().map(|i| actions[i].1.name()))
+            .take(count)
+            .collect()
+    }
+
+    fn upcoming_normal_indices(&self, count: usize) -> Vec<usize> {
+        let len = self.normal_actions.len();
+        if len == 0 {
+            return vec![];
+        }
+        match self.normal_rotate_mode {
+            RotatorMode::StartToEnd => (0..count.min(len))
+                .map(|i| (self.normal_index + i) % len)
+                .collect(),
+            // The actions are shuffled again when rotating from the first index
+            RotatorMode::Shuffled if self.normal_index == 0 => vec![],
+            RotatorMode::Shuffled => (self.normal_index..len).collect(),
+            RotatorMode::StartToEndThenReverse => {
+                let mut index = self.normal_index;
+                let mut backward = self.normal_actions_backward;
+                (0..count)
+                    .map(|_| {
+                        // Mirrors Self::rotate_start_to_end_then_reverse
+                        if index + 1 == len {
+                            backward = !backward;
+                            index = 0;
+                        }
+                        let i = if backward { len - index - 1 } else { index };
+                        index = (index + 1) % len;
+                        i
+                    })
+                    .collect()
+            }
+            RotatorMode::AutoMobbing(_, _, _) | RotatorMode::PingPong(_, _) => vec![],
+        }
+    }
+
+    /// The priority actions with the queued ones first in queue order.
+    pub fn upcoming_priority_actions(&self, now: Instant) -> Vec<UpcomingPriorityAction> {
+        let not_queued = self
+            .priority_actions
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| !self.priority_actions_queue.contains(id));
+
+        self.priority_actions_queue
+            .iter()
+            .copied()
+            .chain(not_queued)
+            .filter_map(|id| {
+                let action = self.priority_actions.get(&id)?;
+                let interval_millis = match action.condition_kind {
+                    Some(ActionCondition::EveryMillis(millis)) => Some(millis),
+                    Some(ActionCondition::ErdaShowerOffCooldown) => {
+                        Some(COOLDOWN_BETWEEN_QUEUE_MILLIS as u64)
+                    }
+                    Some(
+                        ActionCondition::Any
+                        | ActionCondition::Linked
+                        | ActionCondition::LoadoutActive(_),
+                    )
+                    | None => None,
+                };
+                let remaining_millis = interval_millis.map(|millis| {
+                    let elapsed_millis = action.last_queued_time.map_or(millis, |instant| {
+                        now.saturating_duration_since(instant).as_millis() as u64
+                    });
+                    millis.saturating_sub(elapsed_millis)
+                });
+
+                Some(UpcomingPriorityAction {
+                    name: action.inner.name(),
+                    condition: action.condition_kind,
+                    queued: self.priority_actions_queue.contains(&id),
+                    remaining_millis,
+                })
+            })
+            .collect()
+    }
+
+    /// Sets the position the next rune is expected to spawn at.
+    ///
+    /// While this is [`Some`], the player is periodically moved near the position in between
+    /// normal actions.
+    pub fn set_rune_spawn_hint(&mut self, hint: Option<Point>) {
+        if hint.is_none() {
+            self.rune_spawn_hint_last_move = None;
+        }
+        self.rune_spawn_hint = hint;
+    }
+
+    /// Sets the leader position to move toward when following another instance.
+    ///
+    /// While this is [`Some`], the player is moved to the position in between normal actions.
+    pub fn set_follow_target(&mut self, target: Option<Point>) {
+        self.follow_target = target;
+    }
+
+    /// Sets the position to move toward before taking a break.
+    ///
+    /// While this is [`Some`], the player is moved to the position instead of any normal action.
+    pub fn set_break_target(&mut self, target: Option<Point>) {
+        self.break_target = target;
     }
 
     #[inline]
@@ -294,14 +826,32 @@ impl Rotator {
         if context.halting || matches!(context.player, Player::CashShopThenExit(_, _)) {
             return;
         }
+        let waiting_retry = self.rotate_failed_action(context, player);
         self.rotate_priority_actions(context, player);
         self.rotate_priority_actions_queue(context, player);
-        if !player.has_priority_action() && !player.has_normal_action() {
+        self.update_elite_boss_fighting(context, player);
+        if !waiting_retry && !player.has_priority_action() && !player.has_normal_action() {
+            if self.rotate_break_target(player) {
+                return;
+            }
+            if self.elite_boss_fighting {
+                self.rotate_elite_boss_fight(context, player);
+                return;
+            }
+            if self.rotate_follow_target(player) {
+                return;
+            }
+            if self.rotate_rune_spawn_hint(player) {
+                return;
+            }
             match self.normal_rotate_mode {
-                RotatorMode::StartToEnd => self.rotate_start_to_end(player),
-                RotatorMode::StartToEndThenReverse => self.rotate_start_to_end_then_reverse(player),
-                RotatorMode::AutoMobbing(key, bound) => {
-                    self.rotate_auto_mobbing(context, player, key, bound)
+                RotatorMode::StartToEnd => self.rotate_start_to_end(context, player),
+                RotatorMode::StartToEndThenReverse => {
+                    self.rotate_start_to_end_then_reverse(context, player)
+                }
+                RotatorMode::Shuffled => self.rotate_shuffled(context, player),
+                RotatorMode::AutoMobbing(key, bound, strategy) => {
+                    self.rotate_auto_mobbing(context, player, key, bound, strategy)
                 }
                 RotatorMode::PingPong(key, bound) => {
                     self.rotate_ping_pong(context, player, key, bound)
@@ -310,6 +860,83 @@ impl Rotator {
         }
     }
 
+    /// Retries the action that failed to reach its position or applies its [`ActionFailure`] when
+    /// there is no retry left.
+    ///
+    /// Returns `true` if a normal action is waiting to be retried and the normal rotation should
+    /// not continue.
+    fn rotate_failed_action(&mut self, context: &Context, player: &mut PlayerState) -> bool {
+        if let Some((id, action, is_priority)) = player.take_failed_action() {
+            let count = self
+                .action_retry
+                .take()
+                .filter(|retry| retry.id == id)
+                .map_or(0, |retry| retry.count);
+            match self.failure_policies.get(&id).copied() {
+                Some(policy) if count < policy.max_retries => {
+                    debug!(target: "rotator", "retrying failed action {id} #{}", count + 1);
+                    self.action_retry = Some(ActionRetry {
+                        id,
+                        action,
+                        is_priority,
+                        count: count + 1,
+                        retry_instant: Some(
+                            Instant::now() + Duration::from_millis(policy.retry_delay_millis),
+                        ),
+                    });
+                }
+                Some(policy) => {
+                    debug!(target: "rotator", "action {id} failed with {:?}", policy.on_failure);
+                    metrics::record_action_failure();
+                    match policy.on_failure {
+                        ActionFailure::Skip => (),
+                        ActionFailure::AbortCycle => {
+                            self.normal_actions_backward = false;
+                            self.reset_normal_actions_queue();
+                            if is_priority {
+                                self.priority_queuing_linked_action = None;
+                            }
+                            player.reset_normal_action();
+                        }
+                        ActionFailure::Notify => {
+                            let _ = context
+                                .notification
+                                .schedule_notification(NotificationKind::ActionFailed);
+                        }
+                    }
+                }
+                None => (),
+            }
+        }
+
+        let Some(retry) = self.action_retry.as_mut() else {
+            return false;
+        };
+        let Some(retry_instant) = retry.retry_instant else {
+            // The retried action has completed once the player no longer has it
+            let action_id = if retry.is_priority {
+                player.priority_action_id()
+            } else {
+                player.normal_action_id()
+            };
+            if action_id != Some(retry.id) {
+                self.action_retry = None;
+            }
+            return false;
+        };
+        if Instant::now() < retry_instant || (retry.is_priority && player.has_priority_action()) {
+            return !retry.is_priority;
+        }
+
+        retry.retry_instant = None;
+        if retry.is_priority {
+            player.set_priority_action(retry.id, retry.action);
+        } else {
+            player.set_normal_action(retry.id, retry.action);
+        }
+        false
+    }
+
     /// Rotates the actions inside the [`Self::priority_actions`]
     ///
     /// This function does not pass the action to the player but only pushes the action to
@@ -334,7 +961,7 @@ impl Rotator {
                     && rotator
                         .priority_actions
                         .get(&id)
-                        .is_some_and(|action| matches!(action.inner, RotatorAction::Linked(_)))
+                        .is_some_and(|action| action.inner.is_linked())
             })
         }
 
@@ -386,7 +1013,7 @@ impl Rotator {
                             .any(|action_id| *action_id == id)
                         || has_linked_action
                 }
-                Some(ActionCondition::Any) => unreachable!(),
+                Some(ActionCondition::Any | ActionCondition::LoadoutActive(_)) => unreachable!(),
             };
             if action.ignoring {
                 action.last_queued_time = Some(Instant::now());
@@ -396,11 +1023,6 @@ impl Rotator {
             let result = (action.condition.0)(context, player, action.last_queued_time);
             match result {
                 ConditionResult::Queue => {
-                    if action.queue_to_front {
-                        self.priority_actions_queue.push_front(id);
-                    } else {
-                        self.priority_actions_queue.push_back(id);
-                    }
                     action.last_queued_time = Some(Instant::now());
                     if !did_queue_erda_action {
                         did_queue_erda_action = matches!(
@@ -408,6 +1030,7 @@ impl Rotator {
                             Some(ActionCondition::ErdaShowerOffCooldown)
                         );
                     }
+                    self.queue_priority_action(id, false);
                 }
                 ConditionResult::Skip => (),
                 ConditionResult::Ignore => {
@@ -443,9 +1066,11 @@ impl Rotator {
                 return true;
             }
             player.normal_action_id().is_some_and(|id| {
-                rotator.normal_actions.iter().any(|(action_id, action)| {
-                    *action_id == id && matches!(action, RotatorAction::Linked(_))
-                })
+                rotator
+                    .normal_actions
+                    .iter()
+                    .chain(rotator.elite_boss_fight_actions.iter())
+                    .any(|(action_id, action)| *action_id == id && action.is_linked())
             })
         }
 
@@ -459,22 +1084,25 @@ impl Rotator {
                 rotator
                     .priority_actions
                     .get(&id)
-                    .is_some_and(|action| matches!(action.inner, RotatorAction::Linked(_)))
+                    .is_some_and(|action| action.inner.is_linked())
             })
         }
 
         if self.priority_actions_queue.is_empty() && self.priority_queuing_linked_action.is_none() {
             return;
         }
-        if !context
-            .player
-            .can_action_override_current_state(player.last_known_pos)
-            || has_normal_linked_action_queuing_or_executing(self, player)
+        if has_normal_linked_action_queuing_or_executing(self, player)
             || has_priority_linked_action_executing(self, player)
         {
             return;
         }
-        if self.rotate_queuing_linked_action(player, true) {
+        let can_override = context
+            .player
+            .can_action_override_current_state(player.last_known_pos);
+        if self.priority_queuing_linked_action.is_some() {
+            if can_override {
+                self.rotate_queuing_linked_action(player, true);
+            }
             return;
         }
         let id = *self.priority_actions_queue.front().unwrap();
@@ -482,42 +1110,70 @@ impl Rotator {
             self.priority_actions_queue.pop_front();
             return;
         };
-        let has_queue_to_front = player
-            .priority_action_id()
-            .and_then(|id| {
-                self.priority_actions
-                    .get(&id)
-                    .map(|action| action.queue_to_front)
-            })
-            .unwrap_or_default();
-        if has_queue_to_front {
+        let can_interrupt = match action.preemption {
+            Preemption::Never | Preemption::Movement => can_override,
+            Preemption::Anything => {
+                can_override || context.player.can_action_preempt_current_state()
+            }
+        };
+        if !can_interrupt {
             return;
         }
-        if player.has_priority_action() && !action.queue_to_front {
+        let preempting = player.has_priority_action();
+        if preempting
+            && (action.preemption == Preemption::Never
+                || action.priority <= self.priority_of(player.priority_action_id()))
+        {
             return;
         }
 
         self.priority_actions_queue.pop_front();
-        match action.inner.clone() {
+        let linked = match action.inner.clone() {
             RotatorAction::Single(inner) => {
-                if action.queue_to_front {
-                    if let Some(id) = player.replace_priority_action(id, inner) {
-                        self.priority_actions_queue.push_front(id);
-                    }
-                } else {
-                    player.set_priority_action(id, inner);
+                if let Some(id) = player.replace_priority_action(id, inner) {
+                    self.queue_priority_action(id, true);
                 }
+                return;
             }
-            RotatorAction::Linked(linked) => {
-                if action.queue_to_front
-                    && let Some(id) = player.take_priority_action()
-                {
-                    self.priority_actions_queue.push_front(id);
-                }
-                self.priority_queuing_linked_action = Some((id, Box::new(linked)));
-                self.rotate_queuing_linked_action(player, true);
+            RotatorAction::Linked(linked) => linked,
+            RotatorAction::Branch(branch) => {
+                let Some(linked) = branch.resolve(context, player) else {
+                    return;
+                };
+                linked
             }
+        };
+        if preempting && let Some(id) = player.take_priority_action() {
+            self.queue_priority_action(id, true);
         }
+        self.priority_queuing_linked_action = Some((id, Box::new(linked)));
+        self.rotate_queuing_linked_action(player, true);
+    }
+
+    /// Inserts `id` into [`Self::priority_actions_queue`] after all actions with higher priority.
+    ///
+    /// If `front` is true, `id` is placed before actions with the same priority. Otherwise, it is
+    /// placed after them.
+    fn queue_priority_action(&mut self, id: u32, front: bool) {
+        let priority = self.priority_of(Some(id));
+        let index = self
+            .priority_actions_queue
+            .iter()
+            .position(|queued_id| {
+                let queued_priority = self.priority_of(Some(*queued_id));
+                queued_priority < priority || (front && queued_priority == priority)
+            })
+            .unwrap_or(self.priority_actions_queue.len());
+        self.priority_actions_queue.insert(index, id);
+    }
+
+    /// Retrieves the [`PriorityAction::priority`] of `id` or [`PRIORITY_DEFAULT`] if there is
+    /// none.
+    #[inline]
+    fn priority_of(&self, id: Option<u32>) -> u8 {
+        id.and_then(|id| self.priority_actions.get(&id))
+            .map(|action| action.priority)
+            .unwrap_or(PRIORITY_DEFAULT)
     }
 
     fn rotate_auto_mobbing(
@@ -526,6 +1182,7 @@ impl Rotator {
         player: &mut PlayerState,
         key: MobbingKey,
         bound: Bound,
+        strategy: AutoMobStrategy,
     ) {
         debug_assert!(!player.has_normal_action() && !player.has_priority_action());
         let Minimap::Idle(idle) = context.minimap else {
@@ -534,11 +1191,28 @@ impl Rotator {
         let Some(pos) = player.last_known_pos else {
             return;
         };
-        let bound = if player.config.auto_mob_platforms_bound {
+        let strategy = match strategy {
+            AutoMobStrategy::Sequence if self.auto_mob_sequence.is_empty() => {
+                AutoMobStrategy::RotateQuadrants
+            }
+            strategy => strategy,
+        };
+        if matches!(
+            strategy,
+            AutoMobStrategy::Densest | AutoMobStrategy::Nearest
+        ) {
+            player.auto_mob_clear_last_bound();
+        }
+        let bound = if let Some(region) = self.auto_mob_region_bound(context) {
+            region
+        } else if player.config.auto_mob_platforms_bound {
             idle.platforms_bound.unwrap_or(bound.into())
         } else {
             bound.into()
         };
+        if self.rotate_auto_mob_pickup(context, player, idle, pos, bound) {
+            return;
+        }
 
         let Update::Ok(points) =
             update_detection_task(context, 0, &mut self.auto_mob_task, move |detector| {
@@ -547,57 +1221,51 @@ impl Rotator {
         else {
             return;
         };
+        let grappling_threshold = player.config.movement.grappling;
         // FIXME: Collect to a Vec first because `context.rng` needs to be borrowed again.
         let points = points
             .iter()
             .filter_map(|point| {
                 let y = idle.bbox.height - point.y;
-                let point = if y <= pos.y || (y - pos.y).abs() <= GRAPPLING_THRESHOLD {
+                let point = if y <= pos.y || (y - pos.y).abs() <= grappling_threshold {
                     Some(Point::new(point.x, y))
                 } else {
                     None
                 };
                 debug!(target: "rotator", "auto mob raw position {point:?}");
-                point.and_then(|point| player.auto_mob_pick_reachable_y_position(context, point))
+                point
+                    .and_then(|point| player.auto_mob_pick_reachable_y_position(context, point))
+                    .filter(|point| !idle.is_in_no_go_zone(*point))
             })
             .collect::<Vec<_>>();
-        let mut use_pathing_point = false;
-
-        if let Some(last_quad) = player.auto_mob_last_quadrant()
-            && !points.is_empty()
-        {
-            if self
-                .auto_mob_quadrant_consecutive_count
-                .is_none_or(|(quad, _)| quad != last_quad)
-            {
-                self.auto_mob_quadrant_consecutive_count = Some((last_quad, 0));
+        let region_changed = self.update_auto_mob_region(context, points.is_empty());
+        let point = match strategy {
+            AutoMobStrategy::RotateQuadrants => {
+                self.auto_mob_rotate_quadrants_point(context, player, &points, pos, bound)
             }
-            let (_, count) = self
-                .auto_mob_quadrant_consecutive_count
-                .as_mut()
-                .expect("is some");
-
-            *count += 1;
-            if *count >= AUTO_MOB_SAME_QUAD_THRESHOLD {
-                *count = 0;
-                use_pathing_point = true;
+            AutoMobStrategy::Densest => auto_mob_densest_cluster_point(&points, pos)
+                .unwrap_or_else(|| player.auto_mob_pathing_point(context, bound)),
+            AutoMobStrategy::Nearest => auto_mob_nearest_point(&points, pos)
+                .unwrap_or_else(|| player.auto_mob_pathing_point(context, bound)),
+            AutoMobStrategy::Sequence => {
+                self.auto_mob_sequence_point(context, player, &points, pos)
             }
-        }
-
-        let point = if use_pathing_point {
-            player.auto_mob_pathing_point(context, bound)
-        } else {
-            context
-                .rng
-                .random_choose(points.into_iter())
-                .unwrap_or_else(|| player.auto_mob_pathing_point(context, bound))
         };
-        let wait_before_ticks = (key.wait_before_millis / MS_PER_TICK) as u32;
+        if region_changed {
+            // Positions are no longer restricted to a quadrant of the previous region
+            player.auto_mob_clear_last_bound();
+        }
+        if idle.is_in_no_go_zone(point) {
+            // Picks another point in the next tick
+            debug!(target: "rotator", "auto mob position {point:?} inside no-go zone");
+            return;
+        }
+        let wait_before_ticks = (key.wait_before_millis / ms_per_tick()) as u32;
         let wait_before_ticks_random_range =
-            (key.wait_before_millis_random_range / MS_PER_TICK) as u32;
-        let wait_after_ticks = (key.wait_after_millis / MS_PER_TICK) as u32;
+            (key.wait_before_millis_random_range / ms_per_tick()) as u32;
+        let wait_after_ticks = (key.wait_after_millis / ms_per_tick()) as u32;
         let wait_after_ticks_random_range =
-            (key.wait_after_millis_random_range / MS_PER_TICK) as u32;
+            (key.wait_after_millis_random_range / ms_per_tick()) as u32;
         let position = Position {
             x: point.x,
             x_random_range: 0,
@@ -606,7 +1274,7 @@ impl Rotator {
         };
 
         player.set_normal_action(
-            u32::MAX,
+            ACTION_ID_AUTO_MOB,
             PlayerAction::AutoMob(PlayerActionAutoMob {
                 key: key.key,
                 link_key: key.link_key,
@@ -621,78 +1289,418 @@ impl Rotator {
         );
     }
 
-    fn rotate_ping_pong(
-        &mut self,
-        context: &Context,
-        player: &mut PlayerState,
-        key: MobbingKey,
-        bound: Bound,
-    ) {
-        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
-        let Minimap::Idle(idle) = context.minimap else {
-            return;
-        };
-        let Some(pos) = player.last_known_pos else {
-            return;
+    /// Retrieves the bound of the current auto mobbing region in minimap top-left coordinate.
+    ///
+    /// Returns [`None`] if there is no region.
+    fn auto_mob_region_bound(&mut self, context: &Context) -> Option<Rect> {
+        if self.auto_mob_regions.is_empty() {
+            return None;
+        }
+        let index = match self.auto_mob_region_index {
+            Some((index, _)) => index,
+            None => {
+                let index = self.auto_mob_region_order_next(context, None);
+                self.auto_mob_region_index = Some((index, 0));
+                index
+            }
         };
 
-        let bbox = idle.bbox;
-        let dist_left = pos.x - bbox.x;
-        let dist_right = (bbox.x + bbox.width) - pos.x;
-        let direction = if dist_left > dist_right {
-            PingPongDirection::Left
-        } else {
-            PingPongDirection::Right
+        Some(self.auto_mob_regions[index].bound.into())
+    }
+
+    /// Counts a mob detection inside the current auto mobbing region and moves to the next region
+    /// when there is no mob or after [`AUTO_MOB_REGION_SWITCH_THRESHOLD`] detections.
+    ///
+    /// Returns `true` if the region has changed.
+    fn update_auto_mob_region(&mut self, context: &Context, no_mobs: bool) -> bool {
+        let Some((index, count)) = self.auto_mob_region_index else {
+            return false;
         };
-        let bound = Rect::new(
-            bound.x,
-            bbox.height - (bound.y + bound.height),
-            bound.width,
-            bound.height,
-        );
+        let count = count + 1;
+        if !no_mobs && count < AUTO_MOB_REGION_SWITCH_THRESHOLD {
+            self.auto_mob_region_index = Some((index, count));
+            return false;
+        }
 
-        player.set_normal_action(
-            u32::MAX - 1,
-            PlayerAction::PingPong(PlayerActionPingPong {
-                key: key.key,
-                link_key: key.link_key,
-                count: key.count.max(1),
-                with: key.with,
-                wait_before_ticks: (key.wait_before_millis / MS_PER_TICK) as u32,
-                wait_before_ticks_random_range: (key.wait_before_millis_random_range / MS_PER_TICK)
-                    as u32,
-                wait_after_ticks: (key.wait_after_millis / MS_PER_TICK) as u32,
-                wait_after_ticks_random_range: (key.wait_after_millis_random_range / MS_PER_TICK)
-                    as u32,
-                bound,
-                direction,
-            }),
-        );
+        let next = self.auto_mob_region_order_next(context, Some(index));
+        debug!(target: "rotator", "auto mob moving to region {}", self.auto_mob_regions[next].name);
+        self.auto_mob_region_index = Some((next, 0));
+        next != index
     }
 
-    fn rotate_start_to_end(&mut self, player: &mut PlayerState) {
-        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
-        if self.normal_actions.is_empty() {
-            return;
-        }
-        if self.rotate_queuing_linked_action(player, false) {
-            return;
+    /// Picks the index of the region after `current` in [`Self::auto_mob_regions`].
+    fn auto_mob_region_order_next(&self, context: &Context, current: Option<usize>) -> usize {
+        let len = self.auto_mob_regions.len();
+        let cycle_next = current.map_or(0, |index| (index + 1) % len);
+        let total = self
+            .auto_mob_regions
+            .iter()
+            .map(|region| region.weight)
+            .sum::<u32>();
+        if self.auto_mob_region_order == AutoMobRegionOrder::Cycle || total == 0 {
+            return cycle_next;
         }
-        debug_assert!(self.normal_index < self.normal_actions.len());
-        let (id, action) = self.normal_actions[self.normal_index].clone();
-        self.normal_index = (self.normal_index + 1) % self.normal_actions.len();
-        match action {
-            RotatorAction::Single(action) => {
-                player.set_normal_action(id, action);
+
+        let roll = context.rng.random_range(0..total);
+        let mut cumulative = 0;
+        self.auto_mob_regions
+            .iter()
+            .position(|region| {
+                cumulative += region.weight;
+                roll < cumulative
+            })
+            .unwrap_or(cycle_next)
+    }
+
+    /// Picks the densest mob cluster inside the current quadrant and advances to the next quadrant
+    /// when there is no mob or after [`AUTO_MOB_SAME_QUAD_THRESHOLD`] detections.
+    fn auto_mob_rotate_quadrants_point(
+        &mut self,
+        context: &Context,
+        player: &mut PlayerState,
+        points: &[Point],
+        pos: Point,
+        bound: Rect,
+    ) -> Point {
+        let mut use_pathing_point = false;
+
+        if let Some(last_quad) = player.auto_mob_last_quadrant()
+            && !points.is_empty()
+        {
+            if self
+                .auto_mob_quadrant_consecutive_count
+                .is_none_or(|(quad, _)| quad != last_quad)
+            {
+                self.auto_mob_quadrant_consecutive_count = Some((last_quad, 0));
+            }
+            let (_, count) = self
+                .auto_mob_quadrant_consecutive_count
+                .as_mut()
+                .expect("is some");
+
+            *count += 1;
+            if *count >= AUTO_MOB_SAME_QUAD_THRESHOLD {
+                *count = 0;
+                use_pathing_point = true;
             }
-            RotatorAction::Linked(action) => {
-                self.normal_queuing_linked_action = Some((id, Box::new(action)));
-                self.rotate_queuing_linked_action(player, false);
+        }
+
+        if use_pathing_point {
+            player.auto_mob_pathing_point(context, bound)
+        } else {
+            auto_mob_densest_cluster_point(points, pos)
+                .unwrap_or_else(|| player.auto_mob_pathing_point(context, bound))
+        }
+    }
+
+    /// Picks the densest mob cluster inside the current sub-bound of [`Self::auto_mob_sequence`]
+    /// and advances to the next sub-bound when there is no mob or after
+    /// [`AUTO_MOB_SAME_QUAD_THRESHOLD`] detections.
+    fn auto_mob_sequence_point(
+        &mut self,
+        context: &Context,
+        player: &mut PlayerState,
+        points: &[Point],
+        pos: Point,
+    ) -> Point {
+        debug_assert!(!self.auto_mob_sequence.is_empty());
+        let len = self.auto_mob_sequence.len();
+        let (index, count) = match self.auto_mob_sequence_index {
+            Some((index, count))
+                if !points.is_empty() && count + 1 < AUTO_MOB_SAME_QUAD_THRESHOLD =>
+            {
+                self.auto_mob_sequence_index = Some((index, count + 1));
+                return auto_mob_densest_cluster_point(points, pos).expect("not empty");
             }
+            Some((index, _)) => ((index + 1) % len, 0),
+            None => (0, 0),
+        };
+        debug!(target: "rotator", "auto mob advancing to sub-bound #{index}");
+        self.auto_mob_sequence_index = Some((index, count));
+
+        player.auto_mob_sub_bound_point(context, self.auto_mob_sequence[index].into())
+    }
+
+    /// Interrupts auto mobbing to walk to and pick up the nearest detected drop.
+    ///
+    /// Returns `true` if a pick up action has been set.
+    fn rotate_auto_mob_pickup(
+        &mut self,
+        context: &Context,
+        player: &mut PlayerState,
+        idle: MinimapIdle,
+        pos: Point,
+        bound: Rect,
+    ) -> bool {
+        let Some(key) = self.auto_mob_pickup_key else {
+            return false;
+        };
+        let ignore_millis = self.auto_mob_pickup_ignore_millis as u128;
+        self.auto_mob_pickup_ignored
+            .retain(|(_, instant)| !at_least_millis_passed_since(Some(*instant), ignore_millis));
+
+        let Update::Ok(points) = update_detection_task(
+            context,
+            0,
+            &mut self.auto_mob_pickup_task,
+            move |detector| detector.detect_drops(idle.bbox, bound, pos),
+        ) else {
+            return false;
+        };
+        let Some(point) = points
+            .into_iter()
+            .map(|point| Point::new(point.x, idle.bbox.height - point.y))
+            .filter(|point| !is_auto_mob_pickup_ignored(&self.auto_mob_pickup_ignored, *point))
+            .filter_map(|point| player.auto_mob_pick_reachable_y_position(context, point))
+            .filter(|point| !idle.is_in_no_go_zone(*point))
+            .min_by_key(|point| (point.x - pos.x).abs() + (point.y - pos.y).abs())
+        else {
+            return false;
+        };
+        debug!(target: "rotator", "auto mob picking up drop at {point:?}");
+        self.auto_mob_pickup_ignored.push((point, Instant::now()));
+
+        player.set_normal_action(
+            ACTION_ID_AUTO_MOB_PICKUP,
+            PlayerAction::Key(PlayerActionKey {
+                key,
+                link_key: None,
+                count: 1,
+                position: Some(Position {
+                    x: point.x,
+                    x_random_range: 0,
+                    y: point.y,
+                    allow_adjusting: false,
+                }),
+                direction: ActionKeyDirection::Any,
+                with: ActionKeyWith::Any,
+                wait_before_use_ticks: 0,
+                wait_before_use_ticks_random_range: 0,
+                wait_after_use_ticks: 0,
+                wait_after_use_ticks_random_range: 0,
+                verify: None,
+            }),
+        );
+        true
+    }
+
+    fn rotate_ping_pong(
+        &mut self,
+        context: &Context,
+        player: &mut PlayerState,
+        key: MobbingKey,
+        bound: Bound,
+    ) {
+        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        let Minimap::Idle(idle) = context.minimap else {
+            return;
+        };
+        let Some(pos) = player.last_known_pos else {
+            return;
+        };
+
+        let bbox = idle.bbox;
+        let dist_left = pos.x - bbox.x;
+        let dist_right = (bbox.x + bbox.width) - pos.x;
+        let direction = if dist_left > dist_right {
+            PingPongDirection::Left
+        } else {
+            PingPongDirection::Right
+        };
+        let bound = Rect::new(
+            bound.x,
+            bbox.height - (bound.y + bound.height),
+            bound.width,
+            bound.height,
+        );
+        let bound = ping_pong_band_bound(context, bound, &self.ping_pong_bands);
+
+        player.set_normal_action(
+            ACTION_ID_PING_PONG,
+            PlayerAction::PingPong(PlayerActionPingPong {
+                key: key.key,
+                link_key: key.link_key,
+                count: key.count.max(1),
+                with: key.with,
+                wait_before_ticks: (key.wait_before_millis / ms_per_tick()) as u32,
+                wait_before_ticks_random_range: (key.wait_before_millis_random_range
+                    / ms_per_tick()) as u32,
+                wait_after_ticks: (key.wait_after_millis / ms_per_tick()) as u32,
+                wait_after_ticks_random_range: (key.wait_after_millis_random_range / ms_per_tick())
+                    as u32,
+                bound,
+                direction,
+            }),
+        );
+    }
+
+    /// Starts or stops fighting an elite boss when [`Self::elite_boss_fight_enabled`].
+    ///
+    /// The current normal action is aborted so that the fight or the normal rotation can start
+    /// over.
+    fn update_elite_boss_fighting(&mut self, context: &Context, player: &mut PlayerState) {
+        if !self.elite_boss_fight_enabled {
+            return;
+        }
+        let has_elite_boss =
+            matches!(context.minimap, Minimap::Idle(idle) if idle.has_elite_boss());
+        if has_elite_boss == self.elite_boss_fighting {
+            return;
+        }
+
+        debug!(target: "rotator", "elite boss fighting changed to {has_elite_boss}");
+        self.elite_boss_fighting = has_elite_boss;
+        self.elite_boss_fight_index = 0;
+        self.elite_boss_fight_moved = false;
+        self.reset_normal_actions_queue();
+        player.reset_normal_action();
+    }
+
+    /// Moves the player to [`Self::break_target`] if there is one.
+    ///
+    /// Returns `true` if the move action is set.
+    fn rotate_break_target(&mut self, player: &mut PlayerState) -> bool {
+        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        let Some(point) = self.break_target else {
+            return false;
+        };
+
+        debug!(target: "rotator", "moving to break position {point:?}");
+        self.reset_normal_actions_queue();
+        player.set_normal_action(
+            ACTION_ID_BREAK_MOVE,
+            PlayerAction::Move(PlayerActionMove {
+                position: Position {
+                    x: point.x,
+                    x_random_range: 0,
+                    y: point.y,
+                    allow_adjusting: false,
+                },
+                wait_after_move_ticks: 0,
+            }),
+        );
+        true
+    }
+
+    /// Moves the player to [`Self::follow_target`] if there is one.
+    ///
+    /// Returns `true` if the move action is set.
+    fn rotate_follow_target(&mut self, player: &mut PlayerState) -> bool {
+        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        let Some(point) = self.follow_target else {
+            return false;
+        };
+        if self.normal_queuing_linked_action.is_some() {
+            return false;
+        }
+
+        debug!(target: "rotator", "moving to followed leader position {point:?}");
+        player.set_normal_action(
+            ACTION_ID_FOLLOW_MOVE,
+            PlayerAction::Move(PlayerActionMove {
+                position: Position {
+                    x: point.x,
+                    x_random_range: 0,
+                    y: point.y,
+                    allow_adjusting: false,
+                },
+                wait_after_move_ticks: 0,
+            }),
+        );
+        true
+    }
+
+    /// Moves the player to [`Self::rune_spawn_hint`] if the player is not near it.
+    ///
+    /// Returns `true` if the move action is set.
+    fn rotate_rune_spawn_hint(&mut self, player: &mut PlayerState) -> bool {
+        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        let Some(point) = self.rune_spawn_hint else {
+            return false;
+        };
+        let Some(pos) = player.last_known_pos else {
+            return false;
+        };
+        if self.normal_queuing_linked_action.is_some()
+            || !at_least_millis_passed_since(
+                self.rune_spawn_hint_last_move,
+                RUNE_SPAWN_HINT_MOVE_MILLIS,
+            )
+            || ((pos.x - point.x).abs() <= RUNE_SPAWN_HINT_RANGE
+                && (pos.y - point.y).abs() <= RUNE_SPAWN_HINT_RANGE)
+        {
+            return false;
+        }
+
+        debug!(target: "rotator", "moving to expected rune spawn position {point:?}");
+        self.rune_spawn_hint_last_move = Some(Instant::now());
+        player.set_normal_action(
+            ACTION_ID_RUNE_SPAWN_HINT_MOVE,
+            PlayerAction::Move(PlayerActionMove {
+                position: Position {
+                    x: point.x,
+                    x_random_range: 0,
+                    y: point.y,
+                    allow_adjusting: false,
+                },
+                wait_after_move_ticks: 0,
+            }),
+        );
+        true
+    }
+
+    /// Moves to the elite boss position once and then rotates
+    /// [`Self::elite_boss_fight_actions`] from start to end.
+    fn rotate_elite_boss_fight(&mut self, context: &Context, player: &mut PlayerState) {
+        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        if self.rotate_queuing_linked_action(player, false) {
+            return;
+        }
+        if !self.elite_boss_fight_moved
+            && let Minimap::Idle(idle) = context.minimap
+            && let Some(point) = idle.elite_boss()
+        {
+            self.elite_boss_fight_moved = true;
+            player.set_normal_action(
+                ACTION_ID_ELITE_BOSS_MOVE,
+                PlayerAction::Move(PlayerActionMove {
+                    position: Position {
+                        x: point.x,
+                        x_random_range: 0,
+                        y: point.y,
+                        allow_adjusting: false,
+                    },
+                    wait_after_move_ticks: 0,
+                }),
+            );
+            return;
+        }
+        if self.elite_boss_fight_actions.is_empty() {
+            return;
+        }
+
+        debug_assert!(self.elite_boss_fight_index < self.elite_boss_fight_actions.len());
+        let (id, action) = self.elite_boss_fight_actions[self.elite_boss_fight_index].clone();
+        self.elite_boss_fight_index =
+            (self.elite_boss_fight_index + 1) % self.elite_boss_fight_actions.len();
+        self.set_normal_action(context, player, id, action);
+    }
+
+    fn rotate_start_to_end(&mut self, context: &Context, player: &mut PlayerState) {
+        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        if self.normal_actions.is_empty() {
+            return;
+        }
+        if self.rotate_queuing_linked_action(player, false) {
+            return;
         }
+        debug_assert!(self.normal_index < self.normal_actions.len());
+        let (id, action) = self.normal_actions[self.normal_index].clone();
+        self.normal_index = (self.normal_index + 1) % self.normal_actions.len();
+        self.set_normal_action(context, player, id, action);
     }
 
-    fn rotate_start_to_end_then_reverse(&mut self, player: &mut PlayerState) {
+    fn rotate_start_to_end_then_reverse(&mut self, context: &Context, player: &mut PlayerState) {
         debug_assert!(!player.has_normal_action() && !player.has_priority_action());
         if self.normal_actions.is_empty() {
             return;
@@ -717,15 +1725,49 @@ impl Rotator {
         let (id, action) = self.normal_actions[i].clone();
 
         self.normal_index = (self.normal_index + 1) % len;
-        match action {
+        self.set_normal_action(context, player, id, action);
+    }
+
+    fn rotate_shuffled(&mut self, context: &Context, player: &mut PlayerState) {
+        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        if self.normal_actions.is_empty() {
+            return;
+        }
+        if self.rotate_queuing_linked_action(player, false) {
+            return;
+        }
+        if self.normal_index == 0 {
+            context.rng.random_shuffle(&mut self.normal_actions);
+        }
+        debug_assert!(self.normal_index < self.normal_actions.len());
+        let (id, action) = self.normal_actions[self.normal_index].clone();
+        self.normal_index = (self.normal_index + 1) % self.normal_actions.len();
+        self.set_normal_action(context, player, id, action);
+    }
+
+    #[inline]
+    fn set_normal_action(
+        &mut self,
+        context: &Context,
+        player: &mut PlayerState,
+        id: u32,
+        action: RotatorAction,
+    ) {
+        let linked = match action {
             RotatorAction::Single(action) => {
                 player.set_normal_action(id, action);
+                return;
             }
-            RotatorAction::Linked(action) => {
-                self.normal_queuing_linked_action = Some((id, Box::new(action)));
-                self.rotate_queuing_linked_action(player, false);
+            RotatorAction::Linked(action) => action,
+            RotatorAction::Branch(action) => {
+                let Some(action) = action.resolve(context, player) else {
+                    return;
+                };
+                action
             }
-        }
+        };
+        self.normal_queuing_linked_action = Some((id, Box::new(linked)));
+        self.rotate_queuing_linked_action(player, false);
     }
 
     #[inline]
@@ -753,6 +1795,16 @@ impl Rotator {
     }
 }
 
+fn linked_action_names(action: &LinkedAction) -> Vec<String> {
+    let mut names = vec![action.inner.to_string()];
+    let mut next = action.next.as_deref();
+    while let Some(action) = next {
+        names.push(action.inner.to_string());
+        next = action.next.as_deref();
+    }
+    names
+}
+
 /// Creates a [`RotatorAction`] with `start_action` as the initial action
 ///
 /// If `start_action` is linked, this function returns [`RotatorAction::Linked`] with [`usize`] as
@@ -764,6 +1816,20 @@ fn rotator_action(
     start_index: usize,
     actions: &[Action],
 ) -> (RotatorAction, usize) {
+    if let Action::Group(group) = start_action {
+        let branches = actions[start_index + 1..]
+            .iter()
+            .take_while(|action| matches!(action.condition(), ActionCondition::Linked))
+            .copied()
+            .collect::<Vec<_>>();
+        let (then, otherwise) = branches.split_at((group.then_count as usize).min(branches.len()));
+        let action = BranchAction {
+            predicate: group.predicate,
+            then: linked_action(then),
+            otherwise: linked_action(otherwise),
+        };
+        return (RotatorAction::Branch(action), branches.len() + 1);
+    }
     if start_index == actions.len() - 1 {
         // Last action cannot be a linked action
         return (RotatorAction::Single(start_action.into()), 1);
@@ -777,6 +1843,10 @@ fn rotator_action(
             | Action::Key(ActionKey {
                 condition: ActionCondition::Linked,
                 ..
+            })
+            | Action::Mouse(ActionMouse {
+                condition: ActionCondition::Linked,
+                ..
             }) => (),
             _ => return (RotatorAction::Single(start_action.into()), 1),
         }
@@ -796,6 +1866,10 @@ fn rotator_action(
             | Action::Key(ActionKey {
                 condition: ActionCondition::Linked,
                 ..
+            })
+            | Action::Mouse(ActionMouse {
+                condition: ActionCondition::Linked,
+                ..
             }) => {
                 let action = LinkedAction {
                     inner: (*action).into(),
@@ -811,11 +1885,73 @@ fn rotator_action(
     (RotatorAction::Linked(head), offset)
 }
 
+/// Identifies a [`PriorityAction`] across rebuilds by what it was built from.
+fn priority_action_key(action: &PriorityAction) -> String {
+    format!(
+        "{:?} {:?} {}",
+        action.condition_kind, action.inner, action.priority
+    )
+}
+
+/// Creates a [`LinkedAction`] from `actions` in order.
+///
+/// Nested [`Action::Group`]s are not supported and are skipped.
+fn linked_action(actions: &[Action]) -> Option<LinkedAction> {
+    actions
+        .iter()
+        .rev()
+        .filter(|action| !matches!(action, Action::Group(_)))
+        .fold(None, |next, action| {
+            Some(LinkedAction {
+                inner: (*action).into(),
+                next: next.map(Box::new),
+            })
+        })
+}
+
+/// Checks whether `predicate` of a [`BranchAction`] currently holds.
+fn is_branch_predicate_met(
+    context: &Context,
+    player: &PlayerState,
+    predicate: ActionGroupPredicate,
+) -> bool {
+    match predicate {
+        ActionGroupPredicate::HealthBelowPercent(percent) => {
+            player.health.is_some_and(|(current, max)| {
+                max > 0 && current as u64 * 100 <= max as u64 * percent as u64
+            })
+        }
+        ActionGroupPredicate::BuffPresent(kind) => matches!(context.buffs[kind], Buff::Yes),
+        ActionGroupPredicate::RuneActive => {
+            matches!(context.minimap, Minimap::Idle(idle) if idle.rune().is_some())
+        }
+        ActionGroupPredicate::PlayerInRegion(bound) => {
+            let Minimap::Idle(idle) = context.minimap else {
+                return false;
+            };
+            let Some(pos) = player.last_known_pos else {
+                return false;
+            };
+            Rect::from(bound).contains(Point::new(pos.x, idle.bbox.height - pos.y))
+        }
+    }
+}
+
+/// Maps the user action `queue_to_front` to a priority and a [`Preemption`].
+#[inline]
+fn user_priority(queue_to_front: bool) -> (u8, Preemption) {
+    if queue_to_front {
+        (PRIORITY_QUEUE_TO_FRONT, Preemption::Movement)
+    } else {
+        (PRIORITY_DEFAULT, Preemption::Never)
+    }
+}
+
 #[inline]
 fn priority_action(
     action: RotatorAction,
     condition: ActionCondition,
-    queue_to_front: bool,
+    (priority, preemption): (u8, Preemption),
 ) -> PriorityAction {
     debug_assert_matches!(
         condition,
@@ -831,7 +1967,8 @@ fn priority_action(
             }
         })),
         condition_kind: Some(condition),
-        queue_to_front,
+        priority,
+        preemption,
         ignoring: false,
         last_queued_time: None,
     }
@@ -875,8 +2012,10 @@ fn familiar_essence_replenish_priority_action(key: KeyBinding) -> PriorityAction
             wait_before_use_ticks_random_range: 0,
             wait_after_use_ticks: 0,
             wait_after_use_ticks_random_range: 0,
+            verify: None,
         })),
-        queue_to_front: true,
+        priority: PRIORITY_BUFF,
+        preemption: Preemption::Movement,
         ignoring: false,
         last_queued_time: None,
     }
@@ -910,7 +2049,8 @@ fn solve_rune_priority_action() -> PriorityAction {
         })),
         condition_kind: None,
         inner: RotatorAction::Single(PlayerAction::SolveRune),
-        queue_to_front: true,
+        priority: PRIORITY_RUNE,
+        preemption: Preemption::Movement,
         ignoring: false,
         last_queued_time: None,
     }
@@ -950,36 +2090,66 @@ fn buff_priority_action(buff: BuffKind, key: KeyBinding) -> PriorityAction {
             wait_before_use_ticks_random_range: 0,
             wait_after_use_ticks: 10,
             wait_after_use_ticks_random_range: 0,
+            verify: None,
         })),
-        queue_to_front: true,
+        priority: PRIORITY_BUFF,
+        preemption: Preemption::Movement,
         ignoring: false,
         last_queued_time: None,
     }
 }
 
+/// Creates a [`PlayerAction::SellItems`] priority action to sell items at the shop NPC.
+///
+/// The action queues if:
+/// - The minimap is in the [`Minimap::Idle`] state.
+/// - [`SellItems::every_millis`] has passed since the last queue or the action was built.
+/// - Or the inventory is full and enough time has passed since the last queue attempt.
 #[inline]
-fn panic_priority_action() -> PriorityAction {
+fn sell_items_priority_action(sell_items: &SellItems) -> PriorityAction {
+    let every_millis = sell_items.every_millis as u128;
+    let on_inventory_full = sell_items.on_inventory_full;
+    let action = PlayerActionSellItems {
+        npc_position: sell_items.npc_position,
+        tabs: sell_items
+            .tabs
+            .iter()
+            .take(SELL_ITEMS_MAX_TABS)
+            .map(|point| Point::new(point.x, point.y))
+            .collect(),
+        slots: sell_items
+            .slots
+            .iter()
+            .take(SELL_ITEMS_MAX_SLOTS)
+            .map(|point| Point::new(point.x, point.y))
+            .collect(),
+    };
+
     PriorityAction {
-        condition: Condition(Box::new(|context, _, last_queued_time| {
-            match context.minimap {
-                Minimap::Detecting => ConditionResult::Skip,
-                Minimap::Idle(idle) => {
-                    if !idle.has_any_other_player() || last_queued_time.is_none() {
-                        return ConditionResult::Ignore;
-                    }
-                    if at_least_millis_passed_since(last_queued_time, 15000) {
-                        ConditionResult::Queue
-                    } else {
-                        ConditionResult::Skip
-                    }
-                }
+        condition: Condition(Box::new(move |context, _, last_queued_time| {
+            let Minimap::Idle(idle) = context.minimap else {
+                return ConditionResult::Skip;
+            };
+            // Starts counting from when the action is built instead of selling right away
+            if last_queued_time.is_none() {
+                return ConditionResult::Ignore;
+            }
+            if on_inventory_full
+                && idle.has_inventory_full()
+                && at_least_millis_passed_since(last_queued_time, COOLDOWN_BETWEEN_QUEUE_MILLIS)
+            {
+                return ConditionResult::Queue;
+            }
+            if every_millis > 0 && at_least_millis_passed_since(last_queued_time, every_millis) {
+                ConditionResult::Queue
+            } else {
+                ConditionResult::Skip
             }
         })),
         condition_kind: None,
-        inner: RotatorAction::Single(PlayerAction::Panic(PlayerActionPanic {
-            to: PanicTo::Channel,
-        })),
-        queue_to_front: true,
+        inner: RotatorAction::Single(PlayerAction::SellItems(action)),
+        priority: PRIORITY_QUEUE_TO_FRONT,
+        preemption: Preemption::Movement,
         ignoring: false,
         last_queued_time: None,
     }
@@ -1004,7 +2174,8 @@ fn elite_boss_change_channel_priority_action() -> PriorityAction {
         inner: RotatorAction::Single(PlayerAction::Panic(PlayerActionPanic {
             to: PanicTo::Channel,
         })),
-        queue_to_front: true,
+        priority: PRIORITY_ELITE_BOSS,
+        preemption: Preemption::Anything,
         ignoring: false,
         last_queued_time: None,
     }
@@ -1037,13 +2208,111 @@ fn elite_boss_use_key_priority_action(key: KeyBinding) -> PriorityAction {
             wait_before_use_ticks_random_range: 0,
             wait_after_use_ticks: 10,
             wait_after_use_ticks_random_range: 0,
+            verify: None,
         })),
-        queue_to_front: true,
+        priority: PRIORITY_ELITE_BOSS,
+        preemption: Preemption::Anything,
         ignoring: false,
         last_queued_time: None,
     }
 }
 
+/// Whether `point` is within the range of a previously attempted drop position.
+#[inline]
+fn is_auto_mob_pickup_ignored(ignored: &[(Point, Instant)], point: Point) -> bool {
+    ignored.iter().any(|(ignored, _)| {
+        (ignored.x - point.x).abs() <= AUTO_MOB_PICKUP_IGNORE_X_RANGE
+            && (ignored.y - point.y).abs() <= AUTO_MOB_PICKUP_IGNORE_Y_RANGE
+    })
+}
+
+/// Groups mob `points` into clusters and picks a destination inside the densest cluster.
+///
+/// Points are clustered by chaining neighbors within [`AUTO_MOB_CLUSTER_X_THRESHOLD`] and
+/// [`AUTO_MOB_CLUSTER_Y_THRESHOLD`]. The cluster with the most mobs is preferred and ties are
+/// broken by the distance from `player` to the cluster center. The returned point is the cluster
+/// member closest to the cluster center so that it stays a reachable position.
+fn auto_mob_densest_cluster_point(points: &[Point], player: Point) -> Option<Point> {
+    let mut cluster_ids = vec![usize::MAX; points.len()];
+    let mut clusters = Vec::<Vec<Point>>::new();
+    for i in 0..points.len() {
+        if cluster_ids[i] != usize::MAX {
+            continue;
+        }
+        let id = clusters.len();
+        let mut cluster = vec![];
+        let mut stack = vec![i];
+        cluster_ids[i] = id;
+        while let Some(j) = stack.pop() {
+            let point = points[j];
+            cluster.push(point);
+            for (k, other) in points.iter().enumerate() {
+                if cluster_ids[k] == usize::MAX
+                    && (point.x - other.x).abs() <= AUTO_MOB_CLUSTER_X_THRESHOLD
+                    && (point.y - other.y).abs() <= AUTO_MOB_CLUSTER_Y_THRESHOLD
+                {
+                    cluster_ids[k] = id;
+                    stack.push(k);
+                }
+            }
+        }
+        clusters.push(cluster);
+    }
+
+    clusters
+        .into_iter()
+        .map(|cluster| {
+            let len = cluster.len() as i32;
+            let (sum_x, sum_y) = cluster
+                .iter()
+                .fold((0, 0), |(x, y), point| (x + point.x, y + point.y));
+            let center = Point::new(sum_x / len, sum_y / len);
+            let point = cluster
+                .into_iter()
+                .min_by_key(|point| (point.x - center.x).pow(2) + (point.y - center.y).pow(2))
+                .expect("cluster not empty");
+            let distance = (center.x - player.x).pow(2) + (center.y - player.y).pow(2);
+            (len, distance, point)
+        })
+        .max_by(|(len_a, dist_a, _), (len_b, dist_b, _)| len_a.cmp(len_b).then(dist_b.cmp(dist_a)))
+        .map(|(_, _, point)| point)
+}
+
+/// Narrows the vertical range of ping pong `bound` to one of `bands` picked by its probability.
+///
+/// Both `bound` and `bands` are in player coordinate. Returns `bound` as is when no band is picked
+/// or the picked band does not overlap `bound`.
+fn ping_pong_band_bound(context: &Context, bound: Rect, bands: &[PingPongBand]) -> Rect {
+    if bands.is_empty() {
+        return bound;
+    }
+
+    let roll = context.rng.random_range(0..100u32);
+    let mut cumulative = 0;
+    let Some(band) = bands.iter().find(|band| {
+        cumulative += band.probability;
+        roll < cumulative
+    }) else {
+        return bound;
+    };
+    let y_start = band.y_start.max(bound.y);
+    let y_end = band.y_end.min(bound.y + bound.height);
+    if y_start >= y_end {
+        return bound;
+    }
+    debug!(target: "rotator", "ping pong staying inside band {y_start} - {y_end}");
+
+    Rect::new(bound.x, y_start, bound.width, y_end - y_start)
+}
+
+/// Picks the mob position in `points` nearest to `player`.
+fn auto_mob_nearest_point(points: &[Point], player: Point) -> Option<Point> {
+    points
+        .iter()
+        .copied()
+        .min_by_key(|point| (point.x - player.x).abs() + (point.y - player.y).abs())
+}
+
 #[inline]
 fn at_least_millis_passed_since(last_queued_time: Option<Instant>, millis: u128) -> bool {
     last_queued_time
@@ -1060,7 +2329,9 @@ fn should_queue_fixed_action(
     let millis_should_passed = match condition {
         ActionCondition::EveryMillis(millis) => millis as u128,
         ActionCondition::ErdaShowerOffCooldown => COOLDOWN_BETWEEN_QUEUE_MILLIS,
-        ActionCondition::Linked | ActionCondition::Any => unreachable!(),
+        ActionCondition::Linked | ActionCondition::Any | ActionCondition::LoadoutActive(_) => {
+            unreachable!()
+        }
     };
     if !at_least_millis_passed_since(last_queued_time, millis_should_passed) {
         return false;
@@ -1077,13 +2348,14 @@ fn should_queue_fixed_action(
 mod tests {
     use std::{
         assert_matches::assert_matches,
+        sync::LazyLock,
         time::{Duration, Instant},
     };
 
     use opencv::core::{Point, Vec4b};
 
     use super::*;
-    use crate::{Position, buff::BuffKind, minimap::MinimapIdle, skill::SkillKind};
+    use crate::{ClientPoint, Position, buff::BuffKind, database::ActionGroup, skill::SkillKind};
 
     const NORMAL_ACTION: Action = Action::Move(ActionMove {
         position: Position {
@@ -1094,6 +2366,9 @@ mod tests {
         },
         condition: ActionCondition::Any,
         wait_after_move_millis: 0,
+        max_retries: 0,
+        retry_delay_millis: 0,
+        on_failure: ActionFailure::Skip,
     });
     const PRIORITY_ACTION: Action = Action::Move(ActionMove {
         position: Position {
@@ -1104,6 +2379,9 @@ mod tests {
         },
         condition: ActionCondition::ErdaShowerOffCooldown,
         wait_after_move_millis: 0,
+        max_retries: 0,
+        retry_delay_millis: 0,
+        on_failure: ActionFailure::Skip,
     });
 
     #[test]
@@ -1125,134 +2403,668 @@ mod tests {
         let context = Context::new(None, None);
         let now = Instant::now();
 
-        assert!(should_queue_fixed_action(
-            &context,
-            Some(now - Duration::from_millis(3000)),
-            ActionCondition::EveryMillis(2000)
-        ));
-        assert!(!should_queue_fixed_action(
-            &context,
-            Some(now - Duration::from_millis(1000)),
-            ActionCondition::EveryMillis(2000)
-        ));
+        assert!(should_queue_fixed_action(
+            &context,
+            Some(now - Duration::from_millis(3000)),
+            ActionCondition::EveryMillis(2000)
+        ));
+        assert!(!should_queue_fixed_action(
+            &context,
+            Some(now - Duration::from_millis(1000)),
+            ActionCondition::EveryMillis(2000)
+        ));
+    }
+
+    #[test]
+    fn rotator_should_queue_fixed_action_erda_shower() {
+        let mut context = Context::new(None, None);
+        let now = Instant::now();
+
+        context.skills[SkillKind::ErdaShower] = Skill::Idle(Point::default(), Vec4b::default());
+        assert!(!should_queue_fixed_action(
+            &context,
+            Some(now - Duration::from_millis(COOLDOWN_BETWEEN_QUEUE_MILLIS as u64 - 1000)),
+            ActionCondition::ErdaShowerOffCooldown
+        ));
+        assert!(should_queue_fixed_action(
+            &context,
+            Some(now - Duration::from_millis(COOLDOWN_BETWEEN_QUEUE_MILLIS as u64)),
+            ActionCondition::ErdaShowerOffCooldown
+        ));
+
+        context.skills[SkillKind::ErdaShower] = Skill::Detecting;
+        assert!(!should_queue_fixed_action(
+            &context,
+            Some(now - Duration::from_millis(COOLDOWN_BETWEEN_QUEUE_MILLIS as u64)),
+            ActionCondition::ErdaShowerOffCooldown
+        ));
+    }
+
+    #[test]
+    fn rotator_build_actions() {
+        let mut rotator = Rotator::default();
+        let actions = vec![NORMAL_ACTION, NORMAL_ACTION, PRIORITY_ACTION];
+        let buffs = vec![(BuffKind::Rune, KeyBinding::default()); 4];
+        let args = RotatorBuildArgs {
+            mode: RotatorMode::default(),
+            actions: &actions,
+            burst_actions: &[],
+            burst_interval_millis: 0,
+            combos: &[],
+            buffs: &buffs,
+            familiar_essence_key: KeyBinding::default(),
+            familiar_swappable_slots: SwappableFamiliars::default(),
+            familiar_swappable_rarities: &HashSet::default(),
+            familiar_swap_check_millis: 0,
+            familiar_swap_when_points_depleted: false,
+            familiar_rarity_priority: FamiliarRarityPriority::default(),
+            familiar_level_priority: FamiliarLevelPriority::default(),
+            elite_boss_behavior: Some(EliteBossBehavior::CycleChannel),
+            elite_boss_behavior_key: KeyBinding::default(),
+            elite_boss_fight_actions: &[],
+            enable_rune_solving: true,
+            enable_familiars_swapping: false,
+            enable_reset_normal_actions_on_erda: false,
+            auto_mob_pickup_key: None,
+            auto_mob_pickup_ignore_millis: 0,
+            auto_mob_sequence: &[],
+            auto_mob_regions: &[],
+            auto_mob_region_order: AutoMobRegionOrder::default(),
+            ping_pong_bands: &[],
+            sell_items: None,
+            loadout_id: None,
+        };
+
+        rotator.build_actions(args);
+        assert_eq!(rotator.priority_actions.len(), 7);
+        assert_eq!(rotator.normal_actions.len(), 2);
+    }
+
+    fn build_args(actions: &[Action]) -> RotatorBuildArgs<'_> {
+        static EMPTY_RARITIES: LazyLock<HashSet<FamiliarRarity>> = LazyLock::new(HashSet::new);
+
+        RotatorBuildArgs {
+            mode: RotatorMode::default(),
+            actions,
+            burst_actions: &[],
+            burst_interval_millis: 0,
+            combos: &[],
+            buffs: &[],
+            familiar_essence_key: KeyBinding::default(),
+            familiar_swappable_slots: SwappableFamiliars::default(),
+            familiar_swappable_rarities: &EMPTY_RARITIES,
+            familiar_swap_check_millis: 0,
+            familiar_swap_when_points_depleted: false,
+            familiar_rarity_priority: FamiliarRarityPriority::default(),
+            familiar_level_priority: FamiliarLevelPriority::default(),
+            elite_boss_behavior: None,
+            elite_boss_behavior_key: KeyBinding::default(),
+            elite_boss_fight_actions: &[],
+            enable_rune_solving: false,
+            enable_familiars_swapping: false,
+            enable_reset_normal_actions_on_erda: false,
+            auto_mob_pickup_key: None,
+            auto_mob_pickup_ignore_millis: 0,
+            auto_mob_sequence: &[],
+            auto_mob_regions: &[],
+            auto_mob_region_order: AutoMobRegionOrder::default(),
+            ping_pong_bands: &[],
+            sell_items: None,
+            loadout_id: None,
+        }
+    }
+
+    #[test]
+    fn rotator_build_actions_skips_unlinkable_linked_actions() {
+        let mut rotator = Rotator::default();
+        let linked = Action::Key(ActionKey {
+            condition: ActionCondition::Linked,
+            ..ActionKey::default()
+        });
+        let linked_group = Action::Group(ActionGroup {
+            predicate: ActionGroupPredicate::RuneActive,
+            condition: ActionCondition::Linked,
+            then_count: 1,
+        });
+        let actions = vec![linked, NORMAL_ACTION, linked_group, linked, NORMAL_ACTION];
+
+        rotator.build_actions(build_args(&actions));
+        assert_eq!(rotator.normal_actions.len(), 2);
+        assert_matches!(rotator.normal_actions[1].1, RotatorAction::Single(_));
+    }
+
+    #[test]
+    fn rotator_rebuild_actions_keeps_priority_actions_queue() {
+        let mut rotator = Rotator::default();
+        let other_priority_action = Action::Key(ActionKey {
+            condition: ActionCondition::EveryMillis(5000),
+            ..ActionKey::default()
+        });
+        rotator.build_actions(build_args(&[
+            NORMAL_ACTION,
+            PRIORITY_ACTION,
+            other_priority_action,
+        ]));
+        let (id, _) = rotator.priority_actions.iter().next().unwrap();
+        let id = *id;
+        let now = Instant::now();
+        rotator
+            .priority_actions
+            .get_mut(&id)
+            .unwrap()
+            .last_queued_time = Some(now);
+        rotator
+            .priority_actions_queue
+            .extend(rotator.priority_actions.keys());
+
+        rotator.rebuild_actions(build_args(&[PRIORITY_ACTION, NORMAL_ACTION]));
+        assert_eq!(rotator.normal_actions.len(), 1);
+        assert_eq!(rotator.priority_actions.len(), 1);
+        assert_eq!(
+            rotator.priority_actions.get(&id).unwrap().last_queued_time,
+            Some(now)
+        );
+        assert_eq!(rotator.priority_actions_queue, [id]);
+        assert_eq!(rotator.action_index(id), Some(0));
+    }
+
+    #[test]
+    fn rotator_build_actions_burst_window() {
+        let mut rotator = Rotator::default();
+        let burst_action = Action::Key(ActionKey {
+            condition: ActionCondition::Linked,
+            ..ActionKey::default()
+        });
+        let burst_actions = vec![burst_action; 3];
+        let args = RotatorBuildArgs {
+            mode: RotatorMode::default(),
+            actions: &[NORMAL_ACTION],
+            burst_actions: &burst_actions,
+            burst_interval_millis: 120000,
+            combos: &[],
+            buffs: &[],
+            familiar_essence_key: KeyBinding::default(),
+            familiar_swappable_slots: SwappableFamiliars::default(),
+            familiar_swappable_rarities: &HashSet::default(),
+            familiar_swap_check_millis: 0,
+            familiar_swap_when_points_depleted: false,
+            familiar_rarity_priority: FamiliarRarityPriority::default(),
+            familiar_level_priority: FamiliarLevelPriority::default(),
+            elite_boss_behavior: None,
+            elite_boss_behavior_key: KeyBinding::default(),
+            elite_boss_fight_actions: &[],
+            enable_rune_solving: false,
+            enable_familiars_swapping: false,
+            enable_reset_normal_actions_on_erda: false,
+            auto_mob_pickup_key: None,
+            auto_mob_pickup_ignore_millis: 0,
+            auto_mob_sequence: &[],
+            auto_mob_regions: &[],
+            auto_mob_region_order: AutoMobRegionOrder::default(),
+            ping_pong_bands: &[],
+            sell_items: None,
+            loadout_id: None,
+        };
+
+        rotator.build_actions(args);
+        assert_eq!(rotator.normal_actions.len(), 1);
+        assert_eq!(rotator.priority_actions.len(), 1);
+        // The normal action takes the first id
+        let action = rotator.priority_actions.get(&1).unwrap();
+        assert_eq!(
+            action.condition_kind,
+            Some(ActionCondition::EveryMillis(120000))
+        );
+        assert_matches!(
+            &action.inner,
+            RotatorAction::Linked(LinkedAction { next: Some(next), .. })
+                if next.next.as_ref().is_some_and(|last| last.next.is_none())
+        );
+    }
+
+    #[test]
+    fn rotator_rotate_action_start_to_end_then_reverse() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEndThenReverse;
+        for i in 0..3 {
+            rotator
+                .normal_actions
+                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
+        }
+
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(0));
+        assert!(!rotator.normal_actions_backward);
+        assert_eq!(rotator.normal_index, 1);
+
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(1));
+        assert!(!rotator.normal_actions_backward);
+        assert_eq!(rotator.normal_index, 2);
+
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(2));
+        assert!(rotator.normal_actions_backward);
+        assert_eq!(rotator.normal_index, 1);
+
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(1));
+        assert!(rotator.normal_actions_backward);
+        assert_eq!(rotator.normal_index, 2);
+
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(0));
+        assert!(!rotator.normal_actions_backward);
+        assert_eq!(rotator.normal_index, 1);
+    }
+
+    #[test]
+    fn rotator_is_at_cycle_boundary_start_to_end_then_reverse() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEndThenReverse;
+        for i in 0..3 {
+            rotator
+                .normal_actions
+                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
+        }
+        assert!(rotator.is_at_cycle_boundary());
+
+        let mut boundaries = vec![];
+        for _ in 0..4 {
+            player.clear_actions_aborted(true);
+            rotator.rotate_action(&context, &mut player);
+            boundaries.push(rotator.is_at_cycle_boundary());
+        }
+
+        // Rotates 0, 1, 2, 1 with the cycle ending after 1 as 0 is next
+        assert_eq!(boundaries, vec![false, false, false, true]);
+    }
+
+    #[test]
+    fn rotator_upcoming_normal_actions_start_to_end_then_reverse() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEndThenReverse;
+        for i in 0..3 {
+            let action = PlayerAction::Move(PlayerActionMove {
+                position: Position {
+                    x: i,
+                    ..Position::default()
+                },
+                wait_after_move_ticks: 0,
+            });
+            rotator
+                .normal_actions
+                .push((i as u32, RotatorAction::Single(action)));
+        }
+        let names = |xs: &[i32]| {
+            xs.iter()
+                .map(|x| format!("Move({x}, 0)"))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(rotator.upcoming_normal_actions(5), names(&[0, 1, 2, 1, 0]));
+
+        rotator.rotate_action(&context, &mut player);
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+
+        assert_eq!(rotator.upcoming_normal_actions(3), names(&[1, 0, 1]));
+    }
+
+    #[test]
+    fn rotator_upcoming_priority_actions_queued_first() {
+        let mut rotator = Rotator::default();
+        let now = Instant::now();
+        rotator.priority_actions.insert(
+            0,
+            priority_action(
+                RotatorAction::Single(NORMAL_ACTION.into()),
+                ActionCondition::EveryMillis(10_000),
+                (PRIORITY_DEFAULT, Preemption::Never),
+            ),
+        );
+        rotator
+            .priority_actions
+            .insert(1, solve_rune_priority_action());
+        rotator
+            .priority_actions
+            .get_mut(&0)
+            .unwrap()
+            .last_queued_time = Some(now - Duration::from_millis(4_000));
+        rotator.priority_actions_queue.push_back(1);
+
+        let upcoming = rotator.upcoming_priority_actions(now);
+
+        assert_eq!(upcoming.len(), 2);
+        assert_eq!(upcoming[0].name, "SolveRune");
+        assert!(upcoming[0].queued);
+        assert_eq!(upcoming[0].remaining_millis, None);
+        assert_eq!(
+            upcoming[1].condition,
+            Some(ActionCondition::EveryMillis(10_000))
+        );
+        assert!(!upcoming[1].queued);
+        assert_eq!(upcoming[1].remaining_millis, Some(6_000));
+    }
+
+    #[test]
+    fn rotator_rotate_action_start_to_end() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        for i in 0..2 {
+            rotator
+                .normal_actions
+                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
+        }
+
+        rotator.rotate_action(&context, &mut player);
+        assert!(player.has_normal_action());
+        assert!(!rotator.normal_actions_backward);
+        assert_eq!(rotator.normal_index, 1);
+
+        player.clear_actions_aborted(true);
+
+        rotator.rotate_action(&context, &mut player);
+        assert!(player.has_normal_action());
+        assert!(!rotator.normal_actions_backward);
+        assert_eq!(rotator.normal_index, 0);
+    }
+
+    #[test]
+    fn rotator_failed_action_retries_then_skips() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        for i in 0..2 {
+            rotator
+                .normal_actions
+                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
+        }
+        rotator.failure_policies.insert(
+            0,
+            FailurePolicy {
+                max_retries: 1,
+                retry_delay_millis: 0,
+                on_failure: ActionFailure::Skip,
+            },
+        );
+
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(0));
+
+        // Retries the same action once
+        player.clear_action_failed();
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(0));
+        assert_eq!(rotator.action_retry.as_ref().unwrap().count, 1);
+
+        // Skips to the next action after running out of retries
+        player.clear_action_failed();
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(1));
+        assert!(rotator.action_retry.is_none());
+    }
+
+    #[test]
+    fn rotator_failed_action_abort_cycle() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        for i in 0..3 {
+            rotator
+                .normal_actions
+                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
+        }
+        rotator.failure_policies.insert(
+            1,
+            FailurePolicy {
+                max_retries: 0,
+                retry_delay_millis: 0,
+                on_failure: ActionFailure::AbortCycle,
+            },
+        );
+
+        rotator.rotate_action(&context, &mut player);
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(1));
+
+        // Restarts from the first action
+        player.clear_action_failed();
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(0));
+    }
+
+    #[test]
+    fn rotator_rotate_action_shuffled_each_action_once_per_cycle() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::Shuffled;
+        for i in 0..4 {
+            rotator
+                .normal_actions
+                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
+        }
+
+        let mut ids = vec![];
+        for _ in 0..4 {
+            rotator.rotate_action(&context, &mut player);
+            ids.push(player.normal_action_id().unwrap());
+            player.clear_actions_aborted(true);
+        }
+        ids.sort();
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+        assert_eq!(rotator.normal_index, 0);
     }
 
     #[test]
-    fn rotator_should_queue_fixed_action_erda_shower() {
+    fn rotator_rotate_action_group_branch() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
         let mut context = Context::new(None, None);
-        let now = Instant::now();
+        let linked = |x| {
+            Action::Move(ActionMove {
+                position: Position {
+                    x,
+                    x_random_range: 0,
+                    y: 0,
+                    allow_adjusting: false,
+                },
+                condition: ActionCondition::Linked,
+                wait_after_move_millis: 0,
+                ..ActionMove::default()
+            })
+        };
+        let actions = vec![
+            Action::Group(ActionGroup {
+                predicate: ActionGroupPredicate::RuneActive,
+                condition: ActionCondition::Any,
+                then_count: 1,
+            }),
+            linked(1),
+            linked(2),
+        ];
+        let (action, offset) = rotator_action(actions[0], 0, &actions);
+        assert_eq!(offset, 3);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        rotator.normal_actions.push((0, action));
 
-        context.skills[SkillKind::ErdaShower] = Skill::Idle(Point::default(), Vec4b::default());
-        assert!(!should_queue_fixed_action(
-            &context,
-            Some(now - Duration::from_millis(COOLDOWN_BETWEEN_QUEUE_MILLIS as u64 - 1000)),
-            ActionCondition::ErdaShowerOffCooldown
-        ));
-        assert!(should_queue_fixed_action(
-            &context,
-            Some(now - Duration::from_millis(COOLDOWN_BETWEEN_QUEUE_MILLIS as u64)),
-            ActionCondition::ErdaShowerOffCooldown
-        ));
+        rotator.rotate_action(&context, &mut player);
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::Move(PlayerActionMove {
+                position: Position { x: 2, .. },
+                ..
+            }))
+        );
 
-        context.skills[SkillKind::ErdaShower] = Skill::Detecting;
-        assert!(!should_queue_fixed_action(
-            &context,
-            Some(now - Duration::from_millis(COOLDOWN_BETWEEN_QUEUE_MILLIS as u64)),
-            ActionCondition::ErdaShowerOffCooldown
-        ));
+        let mut minimap = MinimapIdle::default();
+        minimap.set_rune(Point::default());
+        context.minimap = Minimap::Idle(minimap);
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::Move(PlayerActionMove {
+                position: Position { x: 1, .. },
+                ..
+            }))
+        );
     }
 
     #[test]
-    fn rotator_build_actions() {
+    fn rotator_build_actions_loadout_active() {
         let mut rotator = Rotator::default();
-        let actions = vec![NORMAL_ACTION, NORMAL_ACTION, PRIORITY_ACTION];
-        let buffs = vec![(BuffKind::Rune, KeyBinding::default()); 4];
-        let args = RotatorBuildArgs {
-            mode: RotatorMode::default(),
-            actions: &actions,
-            buffs: &buffs,
-            familiar_essence_key: KeyBinding::default(),
-            familiar_swappable_slots: SwappableFamiliars::default(),
-            familiar_swappable_rarities: &HashSet::default(),
-            familiar_swap_check_millis: 0,
-            elite_boss_behavior: Some(EliteBossBehavior::CycleChannel),
-            elite_boss_behavior_key: KeyBinding::default(),
-            enable_panic_mode: true,
-            enable_rune_solving: true,
-            enable_familiars_swapping: false,
-            enable_reset_normal_actions_on_erda: false,
-        };
-
-        rotator.build_actions(args);
-        assert_eq!(rotator.priority_actions.len(), 8);
+        let actions = vec![
+            NORMAL_ACTION,
+            NORMAL_ACTION.with_condition(ActionCondition::LoadoutActive(1)),
+            NORMAL_ACTION.with_condition(ActionCondition::LoadoutActive(2)),
+        ];
+
+        rotator.build_actions(RotatorBuildArgs {
+            loadout_id: Some(1),
+            ..build_args(&actions)
+        });
         assert_eq!(rotator.normal_actions.len(), 2);
+
+        rotator.build_actions(build_args(&actions));
+        assert_eq!(rotator.normal_actions.len(), 1);
     }
 
     #[test]
-    fn rotator_rotate_action_start_to_end_then_reverse() {
+    fn rotator_rotate_action_elite_boss_fight() {
         let mut rotator = Rotator::default();
         let mut player = PlayerState::default();
-        let context = Context::new(None, None);
-        rotator.normal_rotate_mode = RotatorMode::StartToEndThenReverse;
-        for i in 0..3 {
-            rotator
-                .normal_actions
-                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
-        }
+        let mut context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        rotator
+            .normal_actions
+            .push((0, RotatorAction::Single(NORMAL_ACTION.into())));
+        rotator.elite_boss_fight_enabled = true;
+        rotator
+            .elite_boss_fight_actions
+            .push((1, RotatorAction::Single(NORMAL_ACTION.into())));
 
+        let mut minimap = MinimapIdle::default();
+        minimap.set_elite_boss(Point::new(10, 5));
+        context.minimap = Minimap::Idle(minimap);
         rotator.rotate_action(&context, &mut player);
-        assert_eq!(player.normal_action_id(), Some(0));
-        assert!(!rotator.normal_actions_backward);
-        assert_eq!(rotator.normal_index, 1);
+        assert!(rotator.elite_boss_fighting);
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::Move(PlayerActionMove {
+                position: Position { x: 10, y: 5, .. },
+                ..
+            }))
+        );
 
         player.clear_actions_aborted(true);
         rotator.rotate_action(&context, &mut player);
         assert_eq!(player.normal_action_id(), Some(1));
-        assert!(!rotator.normal_actions_backward);
-        assert_eq!(rotator.normal_index, 2);
 
-        player.clear_actions_aborted(true);
+        // Aborts the fight action and resumes normal rotation once the boss is gone
+        context.minimap = Minimap::Idle(MinimapIdle::default());
         rotator.rotate_action(&context, &mut player);
-        assert_eq!(player.normal_action_id(), Some(2));
-        assert!(rotator.normal_actions_backward);
-        assert_eq!(rotator.normal_index, 1);
+        assert!(!rotator.elite_boss_fighting);
+        assert_eq!(player.normal_action_id(), Some(0));
+    }
+
+    #[test]
+    fn rotator_rotate_action_rune_spawn_hint() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        rotator
+            .normal_actions
+            .push((0, RotatorAction::Single(NORMAL_ACTION.into())));
+        rotator.set_rune_spawn_hint(Some(Point::new(100, 20)));
+        player.last_known_pos = Some(Point::new(10, 20));
 
-        player.clear_actions_aborted(true);
         rotator.rotate_action(&context, &mut player);
-        assert_eq!(player.normal_action_id(), Some(1));
-        assert!(rotator.normal_actions_backward);
-        assert_eq!(rotator.normal_index, 2);
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::Move(PlayerActionMove {
+                position: Position { x: 100, y: 20, .. },
+                ..
+            }))
+        );
 
+        // Resumes normal rotation until the move interval has passed
         player.clear_actions_aborted(true);
         rotator.rotate_action(&context, &mut player);
         assert_eq!(player.normal_action_id(), Some(0));
-        assert!(!rotator.normal_actions_backward);
-        assert_eq!(rotator.normal_index, 1);
     }
 
     #[test]
-    fn rotator_rotate_action_start_to_end() {
+    fn rotator_rotate_action_follow_target() {
         let mut rotator = Rotator::default();
         let mut player = PlayerState::default();
         let context = Context::new(None, None);
         rotator.normal_rotate_mode = RotatorMode::StartToEnd;
-        for i in 0..2 {
-            rotator
-                .normal_actions
-                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
-        }
+        rotator
+            .normal_actions
+            .push((0, RotatorAction::Single(NORMAL_ACTION.into())));
+        rotator.set_follow_target(Some(Point::new(50, 30)));
 
         rotator.rotate_action(&context, &mut player);
-        assert!(player.has_normal_action());
-        assert!(!rotator.normal_actions_backward);
-        assert_eq!(rotator.normal_index, 1);
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::Move(PlayerActionMove {
+                position: Position { x: 50, y: 30, .. },
+                ..
+            }))
+        );
 
         player.clear_actions_aborted(true);
+        rotator.set_follow_target(None);
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(0));
+    }
+
+    #[test]
+    fn rotator_rotate_action_break_target() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        rotator
+            .normal_actions
+            .push((0, RotatorAction::Single(NORMAL_ACTION.into())));
+        rotator.set_follow_target(Some(Point::new(50, 30)));
+        rotator.set_break_target(Some(Point::new(10, 20)));
 
         rotator.rotate_action(&context, &mut player);
-        assert!(player.has_normal_action());
-        assert!(!rotator.normal_actions_backward);
-        assert_eq!(rotator.normal_index, 0);
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::Move(PlayerActionMove {
+                position: Position { x: 10, y: 20, .. },
+                ..
+            }))
+        );
+
+        player.clear_actions_aborted(true);
+        rotator.set_follow_target(None);
+        rotator.set_break_target(None);
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(0));
     }
 
     #[test]
@@ -1276,7 +3088,8 @@ mod tests {
                 })),
                 condition_kind: None,
                 inner: RotatorAction::Single(PlayerAction::SolveRune),
-                queue_to_front: true,
+                priority: PRIORITY_QUEUE_TO_FRONT,
+                preemption: Preemption::Movement,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1299,7 +3112,8 @@ mod tests {
                 condition: Condition(Box::new(|_, _, _| ConditionResult::Queue)),
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
-                queue_to_front: false,
+                priority: PRIORITY_DEFAULT,
+                preemption: Preemption::Never,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1310,7 +3124,8 @@ mod tests {
                 condition: Condition(Box::new(|_, _, _| ConditionResult::Queue)),
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
-                queue_to_front: false,
+                priority: PRIORITY_DEFAULT,
+                preemption: Preemption::Never,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1327,7 +3142,8 @@ mod tests {
                 condition: Condition(Box::new(|_, _, _| ConditionResult::Queue)),
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
-                queue_to_front: true,
+                priority: PRIORITY_QUEUE_TO_FRONT,
+                preemption: Preemption::Movement,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1348,7 +3164,8 @@ mod tests {
                 condition: Condition(Box::new(|_, _, _| ConditionResult::Queue)),
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
-                queue_to_front: true,
+                priority: PRIORITY_QUEUE_TO_FRONT,
+                preemption: Preemption::Movement,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1381,7 +3198,8 @@ mod tests {
                         next: None,
                     })),
                 }),
-                queue_to_front: false,
+                priority: PRIORITY_DEFAULT,
+                preemption: Preemption::Never,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1400,7 +3218,8 @@ mod tests {
                 condition: Condition(Box::new(|_, _, _| ConditionResult::Queue)),
                 condition_kind: None,
                 inner: RotatorAction::Single(PlayerAction::SolveRune),
-                queue_to_front: true,
+                priority: PRIORITY_QUEUE_TO_FRONT,
+                preemption: Preemption::Movement,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1421,6 +3240,72 @@ mod tests {
         assert_eq!(player.priority_action_id(), Some(2));
     }
 
+    #[test]
+    fn rotator_priority_action_preemption_anything() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let mut context = Context::new(None, None);
+        // Close to the destination so the state cannot be overridden
+        context.player = Player::Moving(Point::new(10, 0), false, None);
+        player.last_known_pos = Some(Point::new(10, 0));
+        for (id, priority, preemption) in [
+            (1, PRIORITY_DEFAULT, Preemption::Never),
+            (2, PRIORITY_RUNE, Preemption::Movement),
+            (3, PRIORITY_ELITE_BOSS, Preemption::Anything),
+        ] {
+            rotator.priority_actions.insert(
+                id,
+                PriorityAction {
+                    condition: Condition(Box::new(|_, _, _| ConditionResult::Queue)),
+                    condition_kind: None,
+                    inner: RotatorAction::Single(NORMAL_ACTION.into()),
+                    priority,
+                    preemption,
+                    ignoring: false,
+                    last_queued_time: None,
+                },
+            );
+        }
+        player.set_priority_action(1, NORMAL_ACTION.into());
+
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.priority_action_id(), Some(3));
+        assert_eq!(
+            rotator.priority_actions_queue,
+            VecDeque::from_iter([2, 1].into_iter())
+        );
+    }
+
+    #[test]
+    fn rotator_priority_action_preemption_never() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        for (id, priority) in [(1, PRIORITY_DEFAULT), (2, PRIORITY_RUNE)] {
+            rotator.priority_actions.insert(
+                id,
+                PriorityAction {
+                    condition: Condition(Box::new(|_, _, _| ConditionResult::Queue)),
+                    condition_kind: None,
+                    inner: RotatorAction::Single(NORMAL_ACTION.into()),
+                    priority,
+                    preemption: Preemption::Never,
+                    ignoring: false,
+                    last_queued_time: None,
+                },
+            );
+        }
+        player.set_priority_action(1, NORMAL_ACTION.into());
+
+        // Higher priority action still waits for the current action
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.priority_action_id(), Some(1));
+        assert_eq!(
+            rotator.priority_actions_queue,
+            VecDeque::from_iter([2].into_iter())
+        );
+    }
+
     #[test]
     fn rotate_ping_pong_direction() {
         let mut rotator = Rotator::default();
@@ -1467,6 +3352,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn auto_mob_region_cycles_after_no_mobs_or_threshold() {
+        let context = Context::new(None, None);
+        let region = |x| AutoMobRegion {
+            bound: Bound {
+                x,
+                y: 0,
+                width: 10,
+                height: 10,
+            },
+            ..AutoMobRegion::default()
+        };
+        let mut rotator = Rotator {
+            auto_mob_regions: vec![region(0), region(50)],
+            ..Rotator::default()
+        };
+
+        assert_eq!(
+            rotator.auto_mob_region_bound(&context),
+            Some(Rect::new(0, 0, 10, 10))
+        );
+        assert!(!rotator.update_auto_mob_region(&context, false));
+        assert!(rotator.update_auto_mob_region(&context, true));
+        assert_eq!(
+            rotator.auto_mob_region_bound(&context),
+            Some(Rect::new(50, 0, 10, 10))
+        );
+
+        for _ in 0..AUTO_MOB_REGION_SWITCH_THRESHOLD - 1 {
+            assert!(!rotator.update_auto_mob_region(&context, false));
+        }
+        assert!(rotator.update_auto_mob_region(&context, false));
+        assert_eq!(rotator.auto_mob_region_index, Some((0, 0)));
+    }
+
+    #[test]
+    fn auto_mob_region_weighted_picks_by_weight() {
+        let context = Context::new(None, None);
+        let mut rotator = Rotator {
+            auto_mob_regions: vec![
+                AutoMobRegion::default(),
+                AutoMobRegion {
+                    weight: 1,
+                    ..AutoMobRegion::default()
+                },
+            ],
+            auto_mob_region_order: AutoMobRegionOrder::Weighted,
+            ..Rotator::default()
+        };
+
+        for current in [None, Some(0), Some(1)] {
+            assert_eq!(rotator.auto_mob_region_order_next(&context, current), 1);
+        }
+
+        rotator.auto_mob_regions[1].weight = 0;
+        assert_eq!(rotator.auto_mob_region_order_next(&context, Some(0)), 1);
+    }
+
+    #[test]
+    fn ping_pong_band_bound_narrows_to_picked_band() {
+        let context = Context::new(None, None);
+        let bound = Rect::new(20, 10, 80, 60);
+        let band = |y_start, y_end, probability| PingPongBand {
+            y_start,
+            y_end,
+            probability,
+        };
+
+        assert_eq!(ping_pong_band_bound(&context, bound, &[]), bound);
+        assert_eq!(
+            ping_pong_band_bound(&context, bound, &[band(30, 40, 0), band(50, 90, 100)]),
+            Rect::new(20, 50, 80, 20)
+        );
+        assert_eq!(
+            ping_pong_band_bound(&context, bound, &[band(80, 90, 100)]),
+            bound
+        );
+    }
+
     #[test]
     fn rotator_priority_action_is_ignored_when_executing() {
         let mut rotator = Rotator::default();
@@ -1481,7 +3445,8 @@ mod tests {
                 condition: Condition(Box::new(|_, _, _| panic!("should not be called"))),
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
-                queue_to_front: false,
+                priority: PRIORITY_DEFAULT,
+                preemption: Preemption::Never,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1518,7 +3483,8 @@ mod tests {
                     inner: NORMAL_ACTION.into(),
                     next: None,
                 }),
-                queue_to_front: false,
+                priority: PRIORITY_DEFAULT,
+                preemption: Preemption::Never,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1551,7 +3517,8 @@ mod tests {
                 condition: Condition(Box::new(|_, _, _| ConditionResult::Queue)),
                 condition_kind: Some(ActionCondition::ErdaShowerOffCooldown),
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
-                queue_to_front: false,
+                priority: PRIORITY_DEFAULT,
+                preemption: Preemption::Never,
                 ignoring: false,
                 last_queued_time: Some(Instant::now()),
             },
@@ -1563,7 +3530,8 @@ mod tests {
                 condition: Condition(Box::new(|_, _, _| panic!("should not be called"))),
                 condition_kind: Some(ActionCondition::ErdaShowerOffCooldown),
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
-                queue_to_front: false,
+                priority: PRIORITY_DEFAULT,
+                preemption: Preemption::Never,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1581,4 +3549,100 @@ mod tests {
         assert!(second_erda.last_queued_time.is_some());
         assert!(!rotator.priority_actions_queue.contains(&second_erda_id));
     }
+
+    #[test]
+    fn auto_mob_densest_cluster_point_prefers_most_mobs() {
+        let points = [
+            Point::new(10, 10),
+            Point::new(50, 10),
+            Point::new(55, 11),
+            Point::new(60, 10),
+        ];
+
+        assert_eq!(
+            auto_mob_densest_cluster_point(&points, Point::new(10, 10)),
+            Some(Point::new(55, 11))
+        );
+        assert_eq!(
+            auto_mob_densest_cluster_point(&[], Point::new(10, 10)),
+            None
+        );
+    }
+
+    #[test]
+    fn auto_mob_densest_cluster_point_tie_prefers_nearest() {
+        let points = [Point::new(10, 10), Point::new(80, 10)];
+
+        assert_eq!(
+            auto_mob_densest_cluster_point(&points, Point::new(70, 10)),
+            Some(Point::new(80, 10))
+        );
+    }
+
+    #[test]
+    fn auto_mob_nearest_point_ignores_density() {
+        let points = [
+            Point::new(10, 10),
+            Point::new(50, 10),
+            Point::new(55, 11),
+            Point::new(60, 10),
+        ];
+
+        assert_eq!(
+            auto_mob_nearest_point(&points, Point::new(20, 10)),
+            Some(Point::new(10, 10))
+        );
+        assert_eq!(auto_mob_nearest_point(&[], Point::new(20, 10)), None);
+    }
+
+    #[test]
+    fn is_auto_mob_pickup_ignored_within_range() {
+        let ignored = [(Point::new(50, 20), Instant::now())];
+
+        assert!(is_auto_mob_pickup_ignored(&ignored, Point::new(54, 22)));
+        assert!(!is_auto_mob_pickup_ignored(&ignored, Point::new(60, 20)));
+        assert!(!is_auto_mob_pickup_ignored(&ignored, Point::new(50, 25)));
+    }
+
+    #[test]
+    fn sell_items_priority_action_every_millis() {
+        let mut player = PlayerState::default();
+        let mut context = Context::new(None, None);
+        let sell_items = SellItems {
+            enabled: true,
+            every_millis: 1000,
+            tabs: vec![ClientPoint::default(); 10],
+            ..SellItems::default()
+        };
+        let action = sell_items_priority_action(&sell_items);
+        let condition = action.condition.0;
+
+        assert_matches!(
+            action.inner,
+            RotatorAction::Single(PlayerAction::SellItems(PlayerActionSellItems { tabs, .. }))
+                if tabs.len() == SELL_ITEMS_MAX_TABS
+        );
+        assert!(matches!(
+            condition(&context, &mut player, None),
+            ConditionResult::Skip
+        ));
+
+        context.minimap = Minimap::Idle(MinimapIdle::default());
+        assert!(matches!(
+            condition(&context, &mut player, None),
+            ConditionResult::Ignore
+        ));
+        assert!(matches!(
+            condition(&context, &mut player, Some(Instant::now())),
+            ConditionResult::Skip
+        ));
+        assert!(matches!(
+            condition(
+                &context,
+                &mut player,
+                Some(Instant::now() - Duration::from_millis(1000))
+            ),
+            ConditionResult::Queue
+        ));
+    }
 }
@@ -0,0 +1,206 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use log::error;
+
+use crate::database::{Routine, RoutineStep, RoutineTrigger, query_routine_runs, query_routines};
+
+/// Runs the steps of due [`Routine`]s in order with each step's delay.
+#[derive(Debug, Default)]
+pub struct RoutineRunner {
+    routines: Vec<Routine>,
+    /// The Unix timestamp in milliseconds each routine id last ran.
+    last_runs: HashMap<i64, u64>,
+    /// The ids of [`RoutineTrigger::Login`] routines that have run since the last login.
+    logged_in_runs: HashSet<i64>,
+    /// The id of the currently running routine.
+    running: Option<i64>,
+    steps: Vec<RoutineStep>,
+    /// The index of the next step to run.
+    index: usize,
+    /// The [`Instant`] the next step can run.
+    next_step_instant: Option<Instant>,
+}
+
+impl RoutineRunner {
+    /// Loads the routines and their last run timestamps from the database.
+    pub fn load(&mut self) {
+        let routines = query_routines()
+            .inspect_err(|err| error!(target: "routine", "failed to load routines {err}"))
+            .unwrap_or_default();
+        let last_runs = query_routine_runs()
+            .inspect_err(|err| error!(target: "routine", "failed to load routine runs {err}"))
+            .unwrap_or_default();
+        self.set_routines(routines, last_runs);
+    }
+
+    /// Replaces the routines and their last run timestamps.
+    ///
+    /// The running routine is cancelled if it no longer exists.
+    fn set_routines(&mut self, routines: Vec<Routine>, last_runs: HashMap<i64, u64>) {
+        if self
+            .running
+            .is_some_and(|id| !routines.iter().any(|routine| routine.id == Some(id)))
+        {
+            self.cancel();
+        }
+        self.routines = routines;
+        self.last_runs = last_runs;
+    }
+
+    /// Allows [`RoutineTrigger::Login`] routines to run again.
+    pub fn reset_login(&mut self) {
+        self.logged_in_runs.clear();
+    }
+
+    /// The id of the currently running routine.
+    #[inline]
+    pub fn running(&self) -> Option<i64> {
+        self.running
+    }
+
+    /// Starts running the first enabled routine that is due at `now_millis` Unix timestamp.
+    ///
+    /// Returns the id of the started routine.
+    pub fn start_due(&mut self, now: Instant, now_millis: u64) -> Option<i64> {
+        if self.running.is_some() {
+            return None;
+        }
+
+        let routine = self.routines.iter().find(|routine| {
+            let Some(id) = routine.id else {
+                return false;
+            };
+            if !routine.enabled || routine.steps.is_empty() {
+                return false;
+            }
+            match routine.trigger {
+                RoutineTrigger::Login => !self.logged_in_runs.contains(&id),
+                RoutineTrigger::Schedule => self.last_runs.get(&id).is_none_or(|last_run| {
+                    now_millis.saturating_sub(*last_run) >= routine.interval_millis
+                }),
+            }
+        })?;
+        let id = routine.id.expect("has id");
+
+        self.running = Some(id);
+        self.steps = routine.steps.clone();
+        self.index = 0;
+        self.next_step_instant = Some(now + Duration::from_millis(self.steps[0].delay_millis));
+        self.last_runs.insert(id, now_millis);
+        if matches!(routine.trigger, RoutineTrigger::Login) {
+            self.logged_in_runs.insert(id);
+        }
+        Some(id)
+    }
+
+    /// Takes the next step to run if its delay has passed.
+    pub fn next_step(&mut self, now: Instant) -> Option<RoutineStep> {
+        if self.next_step_instant.is_none_or(|instant| now < instant) {
+            return None;
+        }
+
+        let step = self.steps[self.index].clone();
+        self.index += 1;
+        self.next_step_instant = self
+            .steps
+            .get(self.index)
+            .map(|step| now + Duration::from_millis(step.delay_millis));
+        Some(step)
+    }
+
+    /// Whether there are steps left to run.
+    #[inline]
+    pub fn has_pending_steps(&self) -> bool {
+        self.next_step_instant.is_some()
+    }
+
+    /// Cancels the running routine without running the remaining steps.
+    pub fn cancel(&mut self) {
+        self.running = None;
+        self.steps.clear();
+        self.index = 0;
+        self.next_step_instant = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        time::{Duration, Instant},
+    };
+
+    use super::RoutineRunner;
+    use crate::database::{Routine, RoutineStep, RoutineStepKind, RoutineTrigger};
+
+    fn routine(id: i64, trigger: RoutineTrigger) -> Routine {
+        Routine {
+            id: Some(id),
+            enabled: true,
+            trigger,
+            interval_millis: 60_000,
+            steps: vec![
+                RoutineStep::default(),
+                RoutineStep {
+                    kind: RoutineStepKind::Click,
+                    delay_millis: 1000,
+                    ..RoutineStep::default()
+                },
+            ],
+            ..Routine::default()
+        }
+    }
+
+    #[test]
+    fn start_due_login_runs_once_until_reset() {
+        let mut runner = RoutineRunner::default();
+        let now = Instant::now();
+        runner.set_routines(vec![routine(1, RoutineTrigger::Login)], HashMap::new());
+
+        assert_eq!(runner.start_due(now, 0), Some(1));
+        assert_eq!(
+            runner.next_step(now).map(|step| step.kind),
+            Some(RoutineStepKind::Key)
+        );
+        assert_eq!(runner.next_step(now + Duration::from_millis(500)), None);
+        assert_eq!(
+            runner
+                .next_step(now + Duration::from_millis(1000))
+                .map(|step| step.kind),
+            Some(RoutineStepKind::Click)
+        );
+        assert!(!runner.has_pending_steps());
+
+        runner.cancel();
+        assert_eq!(runner.start_due(now, 0), None);
+
+        runner.reset_login();
+        assert_eq!(runner.start_due(now, 0), Some(1));
+    }
+
+    #[test]
+    fn start_due_schedule_waits_for_interval() {
+        let mut runner = RoutineRunner::default();
+        let now = Instant::now();
+        runner.set_routines(
+            vec![
+                Routine {
+                    enabled: false,
+                    ..routine(1, RoutineTrigger::Schedule)
+                },
+                routine(2, RoutineTrigger::Schedule),
+            ],
+            HashMap::from([(2, 10_000)]),
+        );
+
+        assert_eq!(runner.start_due(now, 30_000), None);
+        assert_eq!(runner.start_due(now, 70_000), Some(2));
+
+        runner.cancel();
+        assert_eq!(runner.start_due(now, 100_000), None);
+        assert_eq!(runner.start_due(now, 130_000), Some(2));
+    }
+}
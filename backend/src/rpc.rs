@@ -2,23 +2,46 @@ use std::time::Duration;
 
 use anyhow::{Error, Ok, bail};
 use bit_vec::BitVec;
+use hyper_util::rt::TokioIo;
 use input::key_input_client::KeyInputClient;
 pub use input::{Coordinate, MouseAction};
-use input::{Key, KeyDownRequest, KeyInitRequest, KeyRequest, KeyUpRequest, MouseRequest};
+use input::{
+    HeartbeatRequest, Key, KeyDownRequest, KeyInitRequest, KeyInitResponse, KeyRequest,
+    KeyUpRequest, MouseRequest,
+};
 #[cfg(windows)]
 use platforms::windows::KeyKind;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
 #[cfg(target_os = "macos")]
 use platforms::macos::KeyKind;
 use tokio::runtime::Handle;
 use tokio::task::block_in_place;
 use tokio::time::timeout;
 use tonic::Request;
-use tonic::transport::{Channel, Endpoint};
+use tonic::transport::{Channel, Endpoint, Uri};
+use tower::service_fn;
+
+use crate::{
+    config::config,
+    network::connect_tcp,
+    task::{Task, Update, update_task},
+};
 
 mod input {
     tonic::include_proto!("input");
 }
 
+/// The protocol version of the bot sent on `Init`.
+///
+/// Version 2 adds heartbeats, key-state reconciliation and resuming sessions.
+const PROTOCOL_VERSION: u32 = 2;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The timeout of a heartbeat before reconnecting.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_millis(500);
+
 /// Format user input into a valid gRPC server URL
 /// Handles common input patterns:
 /// - "5001" -> "http://localhost:5001"
@@ -71,12 +94,47 @@ fn format_rpc_url(input: &str) -> Result<String, Error> {
     bail!("Invalid RPC server URL format: '{}'. Expected formats: '5001', 'localhost:5001', or 'http://localhost:5001'", trimmed);
 }
 
+/// Connects to `endpoint` through the configured proxy if any.
+async fn connect_client(
+    endpoint: Endpoint,
+) -> Result<KeyInputClient<Channel>, tonic::transport::Error> {
+    if config().proxy.is_none() {
+        return KeyInputClient::connect(endpoint).await;
+    }
+    let channel = endpoint
+        .connect_with_connector(service_fn(|uri: Uri| async move {
+            let host = uri.host().unwrap_or_default();
+            let port = uri.port_u16().unwrap_or(80);
+            connect_tcp(host, port).await.map(TokioIo::new)
+        }))
+        .await?;
+    std::result::Result::Ok(KeyInputClient::new(channel))
+}
+
 #[derive(Debug)]
 pub struct KeysService {
     client: KeyInputClient<Channel>,
+    endpoint: Endpoint,
     url: String,
     key_down: BitVec, // TODO: is a bit wrong good?
     mouse_coordinate: Coordinate,
+    /// The seed of the last `Init` for initializing again after reconnecting.
+    seed: Vec<u8>,
+    /// The session id assigned by a protocol version 2 server.
+    session_id: String,
+    /// The heartbeat interval of a protocol version 2 server or [`None`] otherwise.
+    heartbeat_interval: Option<Duration>,
+    /// The background heartbeat that also resumes the session on a new connection if it failed.
+    heartbeat_task: Option<Task<Result<Heartbeat>>>,
+}
+
+/// The outcome of a background heartbeat.
+#[derive(Debug)]
+enum Heartbeat {
+    /// The server is alive with the keys it believes are down.
+    Alive(Vec<i32>),
+    /// The heartbeat failed and the session was resumed on the new client.
+    Resumed(KeyInputClient<Channel>, KeyInitResponse),
 }
 
 impl KeysService {
@@ -92,17 +150,22 @@ impl KeysService {
         let endpoint = TryInto::<Endpoint>::try_into(formatted_url.clone())
             .map_err(|e| anyhow::anyhow!("Invalid RPC server URL '{}': {}", formatted_url, e))?;
             
-        let client = block_future(async move {
-            timeout(Duration::from_secs(3), KeyInputClient::connect(endpoint)).await
+        let client = block_future(async {
+            timeout(CONNECT_TIMEOUT, connect_client(endpoint.clone())).await
         }).map_err(|e| anyhow::anyhow!("Failed to connect to RPC server '{}': {}", formatted_url, e))??;
         
         log::info!("Successfully connected to RPC server: {}", formatted_url);
         
         Ok(Self {
             client,
+            endpoint,
             url: formatted_url,
             key_down: BitVec::from_elem(128, false),
             mouse_coordinate: Coordinate::Screen,
+            seed: vec![],
+            session_id: String::new(),
+            heartbeat_interval: None,
+            heartbeat_task: None,
         })
     }
 
@@ -123,16 +186,90 @@ impl KeysService {
         self.key_down.clear();
     }
 
+    /// Initializes or resumes the session with the server.
+    ///
+    /// For a resumed session, the keys the server believes are down are reconciled.
     pub fn init(&mut self, seed: &[u8]) -> Result<(), Error> {
-        let response = block_future(async {
-            self.client
-                .init(KeyInitRequest {
-                    seed: seed.to_vec(),
-                })
-                .await
-        })?
-        .into_inner();
+        self.seed = seed.to_vec();
+        self.heartbeat_task = None;
+        let request = KeyInitRequest {
+            seed: seed.to_vec(),
+            protocol_version: PROTOCOL_VERSION,
+            session_id: self.session_id.clone(),
+        };
+        let response = block_future(async { self.client.init(request).await })?.into_inner();
+        self.on_init(response)
+    }
+
+    fn on_init(&mut self, response: KeyInitResponse) -> Result<(), Error> {
         self.mouse_coordinate = response.mouse_coordinate();
+        if response.protocol_version < PROTOCOL_VERSION || response.heartbeat_interval_ms == 0 {
+            self.session_id.clear();
+            self.heartbeat_interval = None;
+            return Ok(());
+        }
+
+        self.session_id = response.session_id;
+        self.heartbeat_interval =
+            Some(Duration::from_millis(response.heartbeat_interval_ms as u64));
+        self.reconcile(&response.keys_down)
+    }
+
+    /// Polls the background heartbeat and reconciles the keys the server believes are down.
+    ///
+    /// If the heartbeat failed, the background task reconnects and resumes the session. Does
+    /// nothing if the server does not support heartbeats.
+    pub fn heartbeat(&mut self) -> Result<(), Error> {
+        let Some(interval) = self.heartbeat_interval else {
+            return Ok(());
+        };
+
+        let update = update_task(
+            interval.as_millis() as u64,
+            &mut self.heartbeat_task,
+            || {
+                let request = KeyInitRequest {
+                    seed: self.seed.clone(),
+                    protocol_version: PROTOCOL_VERSION,
+                    session_id: self.session_id.clone(),
+                };
+                (self.client.clone(), self.endpoint.clone(), request)
+            },
+            |(client, endpoint, request)| {
+                Handle::current().block_on(heartbeat_or_resume(client, endpoint, request))
+            },
+        );
+        match update {
+            Update::Ok(Heartbeat::Alive(keys_down)) => self.reconcile(&keys_down),
+            Update::Ok(Heartbeat::Resumed(client, response)) => {
+                self.client = client;
+                self.on_init(response)
+            }
+            Update::Err(err) => Err(err),
+            Update::Pending => Ok(()),
+        }
+    }
+
+    /// Releases keys only down on the server and presses again keys only down on the bot.
+    ///
+    /// The bot is the source of truth so keys it still holds are resumed after the server released
+    /// them on missed heartbeats.
+    fn reconcile(&mut self, server_keys_down: &[i32]) -> Result<(), Error> {
+        let (release, press) = key_state_diff(&self.key_down, server_keys_down);
+        for key in release {
+            block_future(async {
+                self.client
+                    .send_up(Request::new(KeyUpRequest { key }))
+                    .await
+            })?;
+        }
+        for key in press {
+            block_future(async {
+                self.client
+                    .send_down(Request::new(KeyDownRequest { key }))
+                    .await
+            })?;
+        }
         Ok(())
     }
 
@@ -217,11 +354,69 @@ impl KeysService {
     }
 }
 
+/// Sends a heartbeat with `client` or resumes the session of `request` on a new connection to
+/// `endpoint` if the heartbeat failed.
+async fn heartbeat_or_resume(
+    mut client: KeyInputClient<Channel>,
+    endpoint: Endpoint,
+    request: KeyInitRequest,
+) -> Result<Heartbeat, Error> {
+    let heartbeat = HeartbeatRequest {
+        session_id: request.session_id.clone(),
+    };
+    if let std::result::Result::Ok(std::result::Result::Ok(response)) =
+        timeout(HEARTBEAT_TIMEOUT, client.heartbeat(heartbeat)).await
+    {
+        return Ok(Heartbeat::Alive(response.into_inner().keys_down));
+    }
+
+    log::warn!(
+        "RPC heartbeat failed, resuming session {} with {}",
+        request.session_id,
+        endpoint.uri()
+    );
+    let mut client = timeout(CONNECT_TIMEOUT, connect_client(endpoint)).await??;
+    let response = client.init(request).await?.into_inner();
+    Ok(Heartbeat::Resumed(client, response))
+}
+
+/// Computes the keys to release and to press so that `server_keys_down` matches `key_down`.
+fn key_state_diff(key_down: &BitVec, server_keys_down: &[i32]) -> (Vec<i32>, Vec<i32>) {
+    let mut server_key_down = BitVec::from_elem(key_down.len(), false);
+    for &key in server_keys_down {
+        if let Some(index) = usize::try_from(key)
+            .ok()
+            .filter(|index| *index < key_down.len())
+        {
+            server_key_down.set(index, true);
+        }
+    }
+
+    let mut release = vec![];
+    let mut press = vec![];
+    for (index, (down, server_down)) in key_down.iter().zip(server_key_down.iter()).enumerate() {
+        match (down, server_down) {
+            (false, true) => release.push(index as i32),
+            (true, false) => press.push(index as i32),
+            _ => (),
+        }
+    }
+    (release, press)
+}
+
 #[inline]
 fn block_future<F: Future>(f: F) -> F::Output {
     block_in_place(|| Handle::current().block_on(f))
 }
 
+/// Retrieves the RPC `Key` code of `key`.
+///
+/// Also used by other input methods so that they share the same key codes.
+#[inline]
+pub fn key_code(key: KeyKind) -> i32 {
+    i32::from(from_key_kind(key))
+}
+
 // TODO: Use gRPC enum instead of platforms
 #[inline]
 fn from_key_kind(key: KeyKind) -> Key {
@@ -301,5 +496,18 @@ fn from_key_kind(key: KeyKind) -> Key {
 
 #[cfg(test)]
 mod test {
-    // TODO HOW TO?
+    use bit_vec::BitVec;
+
+    use super::key_state_diff;
+
+    #[test]
+    fn key_state_diff_release_and_press() {
+        let mut key_down = BitVec::from_elem(128, false);
+        key_down.set(1, true);
+        key_down.set(50, true);
+
+        let (release, press) = key_state_diff(&key_down, &[50, 51, 200, -1]);
+        assert_eq!(release, vec![51]);
+        assert_eq!(press, vec![1]);
+    }
 }
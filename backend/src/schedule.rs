@@ -0,0 +1,191 @@
+use std::time::{Duration, Instant};
+
+use crate::{database::PresetSchedule, rng::Rng};
+
+/// The minimum duration an entry is used for so that a short duration does not switch presets
+/// every tick.
+const MIN_ENTRY_DURATION: Duration = Duration::from_secs(60);
+
+/// Tracks the current entry of a [`PresetSchedule`].
+#[derive(Debug, Default)]
+pub struct PresetScheduler {
+    /// The currently used preset.
+    preset: Option<String>,
+    /// The current entry index and how long it has been used.
+    entry: Option<(usize, Duration)>,
+    /// The [`Instant`] of the last update since the schedule was paused.
+    last_update: Option<Instant>,
+}
+
+impl PresetScheduler {
+    /// The currently used preset.
+    #[inline]
+    pub fn preset(&self) -> Option<&String> {
+        self.preset.as_ref()
+    }
+
+    /// Restarts the schedule with `preset` as the currently used preset.
+    pub fn reset(&mut self, preset: Option<String>) {
+        self.preset = preset;
+        self.entry = None;
+        self.last_update = None;
+    }
+
+    /// Pauses the current entry so that the time until the next update is not counted toward
+    /// its duration.
+    #[inline]
+    pub fn pause(&mut self) {
+        self.last_update = None;
+    }
+
+    /// Advances `schedule` and returns the preset to switch to if the current entry has elapsed.
+    ///
+    /// When restarted, the schedule starts from the entry of the currently used preset if there
+    /// is one or from the first entry otherwise.
+    pub fn update(&mut self, schedule: &PresetSchedule, rng: &Rng, now: Instant) -> Option<String> {
+        let entries = &schedule.entries;
+        if !schedule.enabled || entries.is_empty() {
+            self.entry = None;
+            self.last_update = None;
+            return None;
+        }
+
+        let elapsed = self
+            .last_update
+            .map(|instant| now.duration_since(instant))
+            .unwrap_or_default();
+        self.last_update = Some(now);
+        let index = match self.entry {
+            Some((index, used)) if index < entries.len() => {
+                let used = used + elapsed;
+                let duration =
+                    Duration::from_millis(entries[index].duration_millis).max(MIN_ENTRY_DURATION);
+                if used < duration {
+                    self.entry = Some((index, used));
+                    return None;
+                }
+                next_entry_index(schedule, index, rng)
+            }
+            _ => entries
+                .iter()
+                .position(|entry| self.preset.as_ref() == Some(&entry.preset))
+                .unwrap_or_default(),
+        };
+        self.entry = Some((index, Duration::ZERO));
+
+        let preset = &entries[index].preset;
+        if self.preset.as_ref() == Some(preset) {
+            return None;
+        }
+        self.preset = Some(preset.clone());
+        self.preset.clone()
+    }
+}
+
+fn next_entry_index(schedule: &PresetSchedule, index: usize, rng: &Rng) -> usize {
+    let entries = &schedule.entries;
+    if !schedule.random {
+        return (index + 1) % entries.len();
+    }
+
+    let total = entries.iter().map(|entry| entry.weight as u64).sum::<u64>();
+    if total == 0 {
+        return rng.random_range(0..entries.len());
+    }
+    let mut value = rng.random_range(0..total);
+    for (i, entry) in entries.iter().enumerate() {
+        if value < entry.weight as u64 {
+            return i;
+        }
+        value -= entry.weight as u64;
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{MIN_ENTRY_DURATION, PresetScheduler};
+    use crate::{
+        database::{PresetSchedule, PresetScheduleEntry},
+        rng::Rng,
+    };
+
+    fn schedule(random: bool, weights: [u32; 3], duration_millis: u64) -> PresetSchedule {
+        PresetSchedule {
+            enabled: true,
+            random,
+            entries: ["a", "b", "c"]
+                .into_iter()
+                .zip(weights)
+                .map(|(preset, weight)| PresetScheduleEntry {
+                    preset: preset.to_string(),
+                    duration_millis,
+                    weight,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn update_starts_from_current_preset_and_cycles() {
+        let rng = Rng::new([0; 32]);
+        let schedule = schedule(false, [0; 3], 120_000);
+        let mut scheduler = PresetScheduler::default();
+        let now = Instant::now();
+        scheduler.reset(Some("c".to_string()));
+
+        assert_eq!(scheduler.update(&schedule, &rng, now), None);
+        assert_eq!(
+            scheduler.update(&schedule, &rng, now + Duration::from_secs(60)),
+            None
+        );
+        assert_eq!(
+            scheduler.update(&schedule, &rng, now + Duration::from_secs(120)),
+            Some("a".to_string())
+        );
+    }
+
+    #[test]
+    fn update_excludes_paused_time_and_clamps_duration() {
+        let rng = Rng::new([0; 32]);
+        let schedule = schedule(false, [0; 3], 0);
+        let mut scheduler = PresetScheduler::default();
+        let mut now = Instant::now();
+
+        assert_eq!(
+            scheduler.update(&schedule, &rng, now),
+            Some("a".to_string())
+        );
+        now += Duration::from_secs(1);
+        assert_eq!(scheduler.update(&schedule, &rng, now), None);
+
+        scheduler.pause();
+        now += MIN_ENTRY_DURATION;
+        assert_eq!(scheduler.update(&schedule, &rng, now), None);
+        now += MIN_ENTRY_DURATION - Duration::from_secs(1);
+        assert_eq!(
+            scheduler.update(&schedule, &rng, now),
+            Some("b".to_string())
+        );
+    }
+
+    #[test]
+    fn update_random_skips_zero_weight() {
+        let rng = Rng::new([0; 32]);
+        let schedule = schedule(true, [0, 1, 0], 1000);
+        let mut scheduler = PresetScheduler::default();
+        let mut now = Instant::now();
+
+        assert_eq!(
+            scheduler.update(&schedule, &rng, now),
+            Some("a".to_string())
+        );
+        for _ in 0..10 {
+            now += MIN_ENTRY_DURATION;
+            scheduler.update(&schedule, &rng, now);
+            assert_eq!(scheduler.preset.as_deref(), Some("b"));
+        }
+    }
+}
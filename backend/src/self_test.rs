@@ -0,0 +1,155 @@
+use std::{ops::RangeInclusive, sync::OnceLock, thread, time::Duration};
+
+use anyhow::Result;
+use log::{info, warn};
+use opencv::{
+    core::{CV_8UC4, Mat, Scalar, Vector},
+    imgcodecs::{IMREAD_COLOR, imdecode},
+    imgproc::{COLOR_BGR2BGRA, cvt_color_def},
+};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::detect::{ModelCheck, check_model};
+
+/// The size of the blank sample used for the models without an embedded sample.
+const BLANK_SAMPLE_SIZE: (i32, i32) = (1280, 720);
+
+static RESULTS: OnceLock<Vec<ModelDiagnostics>> = OnceLock::new();
+
+/// A bundled detection model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumIter)]
+pub enum DetectionModel {
+    Minimap,
+    Mob,
+    Rune,
+}
+
+/// The self-test result of a [`DetectionModel`] against its sample image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelDiagnostics {
+    pub model: DetectionModel,
+    pub load_time: Duration,
+    pub inference_time: Duration,
+    /// Whether the number of predictions matches what is expected of the sample image.
+    ///
+    /// This is [`None`] if the model has no embedded sample with an expected detection and is
+    /// only run on a blank frame for measuring its load and inference time.
+    pub correct: Option<bool>,
+    /// The error if the model failed to load or run.
+    pub error: Option<String>,
+}
+
+/// Runs the self-test of all bundled models once on a separate thread.
+///
+/// Loading the models may take a while so this does not block the startup.
+pub fn spawn() {
+    thread::spawn(|| {
+        let results = DetectionModel::iter()
+            .map(|model| {
+                diagnose(
+                    model,
+                    sample(model).and_then(|mat| check_model(model, &mat)),
+                )
+            })
+            .inspect(|result| {
+                if result.correct == Some(false) || result.error.is_some() {
+                    warn!(target: "self_test", "{result:?}");
+                } else {
+                    info!(target: "self_test", "{result:?}");
+                }
+            })
+            .collect();
+        let _ = RESULTS.set(results);
+    });
+}
+
+/// The self-test results or empty if the self-test has not completed.
+pub fn results() -> Vec<ModelDiagnostics> {
+    RESULTS.get().cloned().unwrap_or_default()
+}
+
+/// Loads the sample image of `model`.
+///
+/// Only the rune model has an embedded sample captured from the game. The other models are run
+/// on a blank frame that does not verify their predictions.
+fn sample(model: DetectionModel) -> Result<Mat> {
+    match model {
+        DetectionModel::Minimap | DetectionModel::Mob => Ok(Mat::new_rows_cols_with_default(
+            BLANK_SAMPLE_SIZE.1,
+            BLANK_SAMPLE_SIZE.0,
+            CV_8UC4,
+            Scalar::all(0.0),
+        )?),
+        DetectionModel::Rune => {
+            let bytes = Vector::from_slice(include_bytes!(env!("RUNE_SAMPLE")));
+            let mat = imdecode(&bytes, IMREAD_COLOR)?;
+            let mut bgra = Mat::default();
+            cvt_color_def(&mat, &mut bgra, COLOR_BGR2BGRA)?;
+            Ok(bgra)
+        }
+    }
+}
+
+/// The number of predictions expected from the sample image of `model` or [`None`] if the
+/// sample is not captured from the game.
+fn expected_predictions(model: DetectionModel) -> Option<RangeInclusive<usize>> {
+    match model {
+        DetectionModel::Minimap | DetectionModel::Mob => None,
+        // The sample has two normal and two spinning arrows
+        DetectionModel::Rune => Some(1..=4),
+    }
+}
+
+fn diagnose(model: DetectionModel, check: Result<ModelCheck>) -> ModelDiagnostics {
+    match check {
+        Ok(check) => ModelDiagnostics {
+            model,
+            load_time: check.load_time,
+            inference_time: check.inference_time,
+            correct: expected_predictions(model)
+                .map(|expected| expected.contains(&check.predictions)),
+            error: None,
+        },
+        Err(err) => ModelDiagnostics {
+            model,
+            load_time: Duration::ZERO,
+            inference_time: Duration::ZERO,
+            correct: None,
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn diagnose_predictions_and_error() {
+        let check = |predictions| ModelCheck {
+            load_time: Duration::from_millis(100),
+            inference_time: Duration::from_millis(10),
+            predictions,
+        };
+
+        assert_eq!(
+            diagnose(DetectionModel::Minimap, Ok(check(0))).correct,
+            None
+        );
+        assert_eq!(diagnose(DetectionModel::Mob, Ok(check(1))).correct, None);
+        assert_eq!(
+            diagnose(DetectionModel::Rune, Ok(check(2))).correct,
+            Some(true)
+        );
+        assert_eq!(
+            diagnose(DetectionModel::Rune, Ok(check(0))).correct,
+            Some(false)
+        );
+
+        let diagnostics = diagnose(DetectionModel::Rune, Err(anyhow!("failed to load")));
+        assert_eq!(diagnostics.correct, None);
+        assert_eq!(diagnostics.error.as_deref(), Some("failed to load"));
+    }
+}
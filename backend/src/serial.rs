@@ -0,0 +1,174 @@
+use std::{io::Write, time::Duration};
+
+use anyhow::{Result, bail};
+use bit_vec::BitVec;
+#[cfg(target_os = "linux")]
+use platforms::linux::KeyKind;
+#[cfg(target_os = "macos")]
+use platforms::macos::KeyKind;
+#[cfg(windows)]
+use platforms::windows::KeyKind;
+use serialport::SerialPort;
+
+use crate::rpc;
+
+/// The baud rate of the serial port.
+const BAUD_RATE: u32 = 115200;
+
+/// The first byte of every frame.
+const FRAME_START: u8 = 0xAA;
+
+/// The command byte of a frame.
+///
+/// A frame is `[FRAME_START, command, payload length, payload..., checksum]` where checksum is
+/// the XOR of command, payload length and payload bytes. Keys are encoded using the same codes
+/// as the RPC `Key` enum.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug)]
+enum SerialCommand {
+    /// Payload is `[key]`.
+    KeyDown = 1,
+    /// Payload is `[key]`.
+    KeyUp = 2,
+    /// Payload is `[key, down_ms (u16 LE)]`.
+    Key = 3,
+    /// Payload is `[action, width, height, x, y]` with coordinates as `i16` LE.
+    Mouse = 4,
+}
+
+/// The mouse action of [`SerialCommand::Mouse`].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug)]
+pub enum SerialMouseAction {
+    Move = 0,
+    Click = 1,
+    ScrollDown = 2,
+}
+
+/// Sends key and mouse inputs to an Arduino-style HID device over a serial port.
+#[derive(Debug)]
+pub struct SerialService {
+    port: Box<dyn SerialPort>,
+    path: String,
+    key_down: BitVec,
+}
+
+impl SerialService {
+    pub fn connect(path: impl AsRef<str>) -> Result<Self> {
+        let path = path.as_ref().trim().to_string();
+        if path.is_empty() {
+            bail!("serial port cannot be empty");
+        }
+        let port = serialport::new(&path, BAUD_RATE)
+            .timeout(Duration::from_millis(100))
+            .open()?;
+        log::info!("Successfully opened serial port: {path}");
+
+        Ok(Self {
+            port,
+            path,
+            key_down: BitVec::from_elem(128, false),
+        })
+    }
+
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    /// Releases all keys that were sent down.
+    pub fn reset(&mut self) {
+        for i in 0..self.key_down.len() {
+            if self.key_down.get(i).unwrap() {
+                let _ = self.write_frame(SerialCommand::KeyUp, &[i as u8]);
+            }
+        }
+        self.key_down.clear();
+    }
+
+    pub fn send_mouse(
+        &mut self,
+        width: i32,
+        height: i32,
+        x: i32,
+        y: i32,
+        action: SerialMouseAction,
+    ) -> Result<()> {
+        let mut payload = vec![action as u8];
+        for value in [width, height, x, y] {
+            payload.extend_from_slice(
+                &(value.clamp(i16::MIN as i32, i16::MAX as i32) as i16).to_le_bytes(),
+            );
+        }
+        self.write_frame(SerialCommand::Mouse, &payload)
+    }
+
+    pub fn send(&mut self, key: KeyKind, down_ms: f32) -> Result<()> {
+        let code = key_code(key);
+        let down_ms = (down_ms.max(0.0) as u16).to_le_bytes();
+        self.write_frame(SerialCommand::Key, &[code, down_ms[0], down_ms[1]])?;
+        self.key_down.set(code as usize, false);
+        Ok(())
+    }
+
+    pub fn send_up(&mut self, key: KeyKind) -> Result<()> {
+        let code = key_code(key);
+        if !self.key_down.get(code as usize).unwrap() {
+            bail!("key not sent");
+        }
+        self.write_frame(SerialCommand::KeyUp, &[code])?;
+        self.key_down.set(code as usize, false);
+        Ok(())
+    }
+
+    pub fn send_down(&mut self, key: KeyKind) -> Result<()> {
+        let code = key_code(key);
+        if self.key_down.get(code as usize).unwrap() {
+            bail!("key not sent");
+        }
+        self.write_frame(SerialCommand::KeyDown, &[code])?;
+        self.key_down.set(code as usize, true);
+        Ok(())
+    }
+
+    fn write_frame(&mut self, command: SerialCommand, payload: &[u8]) -> Result<()> {
+        self.port.write_all(&encode_frame(command, payload))?;
+        self.port.flush()?;
+        Ok(())
+    }
+}
+
+#[inline]
+fn key_code(key: KeyKind) -> u8 {
+    rpc::key_code(key) as u8
+}
+
+fn encode_frame(command: SerialCommand, payload: &[u8]) -> Vec<u8> {
+    debug_assert!(payload.len() <= u8::MAX as usize);
+
+    let header = [command as u8, payload.len() as u8];
+    let checksum = header
+        .iter()
+        .chain(payload)
+        .fold(0u8, |checksum, byte| checksum ^ byte);
+    let mut frame = Vec::with_capacity(payload.len() + 4);
+    frame.push(FRAME_START);
+    frame.extend_from_slice(&header);
+    frame.extend_from_slice(payload);
+    frame.push(checksum);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FRAME_START, SerialCommand, encode_frame};
+
+    #[test]
+    fn encode_frame_with_checksum() {
+        let frame = encode_frame(SerialCommand::Key, &[5, 0x64, 0x00]);
+
+        assert_eq!(
+            frame,
+            vec![FRAME_START, 3, 3, 5, 0x64, 0x00, 3 ^ 3 ^ 5 ^ 0x64]
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use log::error;
+
+use crate::database::{Session, query_session, spawn_update_session, upsert_session};
+
+/// Persists the [`Session`] whenever it changes and keeps the session from before startup for
+/// resuming.
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    current: Session,
+    /// The session persisted by the previous run if it has anything to resume.
+    last: Option<Session>,
+}
+
+impl SessionTracker {
+    /// Loads the session persisted by the previous run.
+    ///
+    /// The session is inserted if it does not exist yet so that later updates from the update
+    /// loop do not need to wait for the inserted id.
+    pub fn load() -> Self {
+        let mut session = query_session()
+            .inspect_err(|err| error!(target: "session", "failed to query session {err}"))
+            .unwrap_or_default();
+        if session.id.is_none()
+            && let Err(err) = upsert_session(&mut session)
+        {
+            error!(target: "session", "failed to insert session {err}");
+        }
+        let last = (session.minimap_id.is_some() || session.character_id.is_some())
+            .then(|| session.clone());
+        Self {
+            current: session,
+            last,
+        }
+    }
+
+    #[inline]
+    pub fn last(&self) -> Option<&Session> {
+        self.last.as_ref()
+    }
+
+    /// Replaces the current session with `session` and persists it without waiting if anything
+    /// changed.
+    pub fn update(&mut self, session: Session) {
+        if self.set(session) {
+            spawn_update_session(&self.current);
+        }
+    }
+
+    fn set(&mut self, session: Session) -> bool {
+        let session = Session {
+            id: self.current.id,
+            ..session
+        };
+        if session == self.current {
+            return false;
+        }
+        self.current = session;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_only_when_changed() {
+        let mut tracker = SessionTracker {
+            current: Session {
+                id: Some(1),
+                ..Session::default()
+            },
+            last: None,
+        };
+        let session = Session {
+            minimap_id: Some(2),
+            preset: Some("preset".to_string()),
+            ..Session::default()
+        };
+
+        assert!(tracker.set(session.clone()));
+        assert_eq!(tracker.current.id, Some(1));
+        assert!(!tracker.set(session.clone()));
+        assert!(tracker.set(Session {
+            halting: false,
+            ..session
+        }));
+    }
+}
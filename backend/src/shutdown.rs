@@ -0,0 +1,210 @@
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    time::{Duration, Instant},
+};
+
+use strum::Display;
+
+use crate::database::ShutdownRoutine;
+
+/// A step of a [`ShutdownRoutine`].
+#[derive(Clone, Copy, PartialEq, Debug, Display)]
+pub enum ShutdownStep {
+    GoToTown,
+    Notify,
+    /// Exits to the lobby through the game menu.
+    ExitGame,
+    /// Closes the game client once exited to the lobby.
+    CloseClient,
+}
+
+/// Runs the enabled steps of a [`ShutdownRoutine`] in order.
+#[derive(Debug, Default)]
+pub struct ShutdownRunner {
+    /// The [`Instant`] rotating actions was started by the user.
+    started_instant: Option<Instant>,
+    /// The remaining steps of the running routine or [`None`] if there is none running.
+    steps: Option<VecDeque<ShutdownStep>>,
+    /// The session summary captured when the running routine started.
+    summary: String,
+}
+
+impl ShutdownRunner {
+    /// Marks the start of a session for computing its runtime and scheduled stop.
+    #[inline]
+    pub fn start_session(&mut self, now: Instant) {
+        self.started_instant = Some(now);
+    }
+
+    /// The time since [`Self::start_session`] or zero if there is no session.
+    pub fn runtime(&self, now: Instant) -> Duration {
+        self.started_instant
+            .map(|instant| now.duration_since(instant))
+            .unwrap_or_default()
+    }
+
+    /// Whether there is a session started by the user that has not ended yet.
+    #[inline]
+    pub fn is_session_active(&self) -> bool {
+        self.started_instant.is_some()
+    }
+
+    /// Whether the session has run for [`ShutdownRoutine::stop_after_millis`].
+    pub fn is_stop_due(&self, routine: &ShutdownRoutine, now: Instant) -> bool {
+        routine.enabled
+            && routine.stop_after_millis > 0
+            && !self.is_running()
+            && self.is_session_active()
+            && self.runtime(now) >= Duration::from_millis(routine.stop_after_millis)
+    }
+
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        self.steps.is_some()
+    }
+
+    /// Starts running `routine` and ends the current session.
+    ///
+    /// Returns `false` if there is already a running routine or `routine` is disabled.
+    pub fn start(&mut self, routine: &ShutdownRoutine, summary: String) -> bool {
+        if self.is_running() || !routine.enabled {
+            return false;
+        }
+
+        let steps = [
+            (routine.go_to_town, ShutdownStep::GoToTown),
+            (routine.notify, ShutdownStep::Notify),
+            (routine.exit_game, ShutdownStep::ExitGame),
+            (routine.exit_game, ShutdownStep::CloseClient),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, step)| enabled.then_some(step))
+        .collect();
+        self.started_instant = None;
+        self.steps = Some(steps);
+        self.summary = summary;
+        true
+    }
+
+    /// Takes the next step to run or [`None`] if all steps have been run.
+    #[inline]
+    pub fn next_step(&mut self) -> Option<ShutdownStep> {
+        self.steps.as_mut()?.pop_front()
+    }
+
+    #[inline]
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    /// Cancels the running routine and ends the current session.
+    pub fn cancel(&mut self) {
+        self.started_instant = None;
+        self.steps = None;
+        self.summary.clear();
+    }
+}
+
+/// Formats the summary of a session notified when it ends.
+pub fn session_summary(
+    runtime: Duration,
+    map: Option<&str>,
+    exp_per_hour: Option<u64>,
+    exp_percent_per_hour: Option<f32>,
+) -> String {
+    let runtime = runtime.as_secs();
+    let mut summary = String::new();
+    let _ = writeln!(
+        summary,
+        "Runtime: {:02}:{:02}:{:02}",
+        runtime / 3600,
+        runtime / 60 % 60,
+        runtime % 60
+    );
+    let _ = writeln!(summary, "Map: {}", map.unwrap_or("None"));
+    match (exp_per_hour, exp_percent_per_hour) {
+        (Some(exp), Some(percent)) => {
+            let _ = write!(summary, "EXP / hour: {exp} ({percent:.2}%)");
+        }
+        _ => {
+            let _ = write!(summary, "EXP / hour: Unknown");
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{ShutdownRunner, ShutdownStep, session_summary};
+    use crate::database::ShutdownRoutine;
+
+    #[test]
+    fn start_runs_enabled_steps_in_order() {
+        let mut runner = ShutdownRunner::default();
+        let routine = ShutdownRoutine {
+            enabled: true,
+            exit_game: true,
+            ..ShutdownRoutine::default()
+        };
+
+        assert!(!runner.start(&ShutdownRoutine::default(), String::new()));
+        assert!(runner.start(&routine, "summary".to_string()));
+        assert!(!runner.start(&routine, String::new()));
+        assert_eq!(runner.summary(), "summary");
+        assert_eq!(runner.next_step(), Some(ShutdownStep::GoToTown));
+        assert_eq!(runner.next_step(), Some(ShutdownStep::Notify));
+        assert_eq!(runner.next_step(), Some(ShutdownStep::ExitGame));
+        assert_eq!(runner.next_step(), Some(ShutdownStep::CloseClient));
+        assert_eq!(runner.next_step(), None);
+        assert!(runner.is_running());
+
+        runner.cancel();
+        assert!(!runner.is_running());
+    }
+
+    #[test]
+    fn is_stop_due_after_running_for_stop_after_millis() {
+        let now = Instant::now();
+        let mut runner = ShutdownRunner::default();
+        let routine = ShutdownRoutine {
+            enabled: true,
+            stop_after_millis: 1000,
+            ..ShutdownRoutine::default()
+        };
+
+        assert!(!runner.is_stop_due(&routine, now));
+        runner.start_session(now);
+        assert!(!runner.is_stop_due(&routine, now + Duration::from_millis(999)));
+        assert!(runner.is_stop_due(&routine, now + Duration::from_millis(1000)));
+        assert!(!runner.is_stop_due(
+            &ShutdownRoutine {
+                stop_after_millis: 0,
+                ..routine
+            },
+            now + Duration::from_millis(1000)
+        ));
+
+        assert!(runner.start(&routine, String::new()));
+        assert!(!runner.is_stop_due(&routine, now + Duration::from_millis(1000)));
+    }
+
+    #[test]
+    fn session_summary_format() {
+        assert_eq!(
+            session_summary(
+                Duration::from_secs(3725),
+                Some("Arcana"),
+                Some(1000),
+                Some(1.5)
+            ),
+            "Runtime: 01:02:05\nMap: Arcana\nEXP / hour: 1000 (1.50%)"
+        );
+        assert_eq!(
+            session_summary(Duration::ZERO, None, None, None),
+            "Runtime: 00:00:00\nMap: None\nEXP / hour: Unknown"
+        );
+    }
+}
@@ -0,0 +1,582 @@
+use std::{
+    any::Any,
+    cell::{Cell, Ref, RefCell},
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    sync::Arc,
+};
+
+use anyhow::{Result, anyhow};
+use opencv::core::{Mat, Point, Rect};
+#[cfg(target_os = "linux")]
+use platforms::linux::{Handle, KeyKind};
+#[cfg(target_os = "macos")]
+use platforms::macos::{Handle, KeyKind};
+#[cfg(windows)]
+use platforms::windows::{Handle, KeyKind};
+
+use crate::{
+    Settings,
+    bridge::{KeySender, KeySenderMethod, KeyTiming, MouseAction},
+    buff::{Buff, BuffKind},
+    context::{Context, fold_context},
+    database::{DetectionTuning, Minimap as MinimapData},
+    detect::{
+        ArrowsCalibrating, ArrowsState, DetectionScores, Detector, FamiliarLevel, FamiliarRank,
+        FamiliarSlot, FamiliarStats, OtherPlayerKind,
+    },
+    mat::OwnedMat,
+    minimap::{Minimap, MinimapIdle},
+    network::DiscordNotification,
+    player::{Player, PlayerState},
+    rate_limiter::RateLimitBreak,
+    request_handler::config_rotator_mode,
+    rng::Rng,
+    rotator::{Rotator, RotatorBuildArgs},
+    skill::{Skill, SkillKind},
+};
+pub use crate::{player::PlayerConfiguration, rng::RngSeed};
+
+/// The kind of a recorded key input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimulatedKeyAction {
+    Press,
+    Down,
+    Up,
+}
+
+/// A key input recorded during a [`Simulation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimulatedKey {
+    /// The tick the key was sent.
+    pub tick: u64,
+    pub kind: KeyKind,
+    pub action: SimulatedKeyAction,
+}
+
+/// The key inputs recorded during a [`Simulation`].
+#[derive(Debug, Default)]
+pub struct SimulatedKeys {
+    tick: u64,
+    events: Vec<SimulatedKey>,
+    held: HashSet<KeyKind>,
+}
+
+impl SimulatedKeys {
+    /// All the recorded key inputs in the order they were sent.
+    #[inline]
+    pub fn events(&self) -> &[SimulatedKey] {
+        &self.events
+    }
+
+    /// Whether `kind` is currently held down.
+    #[inline]
+    pub fn is_held(&self, kind: KeyKind) -> bool {
+        self.held.contains(&kind)
+    }
+
+    /// Whether `kind` was pressed during `tick`.
+    pub fn is_pressed_at(&self, kind: KeyKind, tick: u64) -> bool {
+        self.events
+            .iter()
+            .rev()
+            .take_while(|key| key.tick >= tick)
+            .any(|key| {
+                key.tick == tick && key.kind == kind && key.action == SimulatedKeyAction::Press
+            })
+    }
+
+    fn record(&mut self, kind: KeyKind, action: SimulatedKeyAction) {
+        self.events.push(SimulatedKey {
+            tick: self.tick,
+            kind,
+            action,
+        });
+    }
+}
+
+/// A [`KeySender`] that records the keys into [`SimulatedKeys`] instead of sending them.
+#[derive(Debug)]
+struct RecordingKeySender {
+    keys: Rc<RefCell<SimulatedKeys>>,
+}
+
+impl KeySender for RecordingKeySender {
+    fn set_method(&mut self, _method: KeySenderMethod) {}
+
+    fn set_key_timings(&mut self, _timings: HashMap<KeyKind, KeyTiming>) {}
+
+    fn set_paused(&mut self, _paused: bool) {}
+
+    fn send(&self, kind: KeyKind) -> Result<()> {
+        self.keys
+            .borrow_mut()
+            .record(kind, SimulatedKeyAction::Press);
+        Ok(())
+    }
+
+    fn send_mouse(&self, _x: i32, _y: i32, _action: MouseAction) -> Result<()> {
+        Ok(())
+    }
+
+    fn send_up(&self, kind: KeyKind) -> Result<()> {
+        let mut keys = self.keys.borrow_mut();
+        keys.held.remove(&kind);
+        keys.record(kind, SimulatedKeyAction::Up);
+        Ok(())
+    }
+
+    fn send_down(&self, kind: KeyKind) -> Result<()> {
+        let mut keys = self.keys.borrow_mut();
+        keys.held.insert(kind);
+        keys.record(kind, SimulatedKeyAction::Down);
+        Ok(())
+    }
+
+    fn all_keys_cleared(&self) -> bool {
+        self.keys.borrow().held.is_empty()
+    }
+
+    fn release_all(&self) -> Result<()> {
+        let mut keys = self.keys.borrow_mut();
+        let held = keys.held.drain().collect::<Vec<_>>();
+        for kind in held {
+            keys.record(kind, SimulatedKeyAction::Up);
+        }
+        Ok(())
+    }
+
+    fn rate_limit_break(&self) -> Option<RateLimitBreak> {
+        None
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// A [`Detector`] that only detects the scripted player position.
+///
+/// All other detections fail or detect nothing.
+#[derive(Clone, Debug)]
+struct SimulatedDetector {
+    mat: Arc<OwnedMat>,
+    player: Option<Rect>,
+}
+
+impl Detector for SimulatedDetector {
+    fn mat(&self) -> &OwnedMat {
+        &self.mat
+    }
+
+    fn detect_mobs(&self, _minimap: Rect, _bound: Rect, _player: Point) -> Result<Vec<Point>> {
+        Ok(vec![])
+    }
+
+    fn detect_drops(&self, _minimap: Rect, _bound: Rect, _player: Point) -> Result<Vec<Point>> {
+        Ok(vec![])
+    }
+
+    fn detect_esc_settings(&self) -> bool {
+        false
+    }
+
+    fn detect_esc_confirm_button(&self) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_tomb_ok_button(&self) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_elite_boss_bar(&self) -> bool {
+        false
+    }
+
+    fn detect_minimap(&self, _region: Option<Rect>, _border_threshold: u8) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_minimap_portals(&self, _minimap: Rect) -> Vec<Rect> {
+        vec![]
+    }
+
+    fn detect_minimap_rune(&self, _minimap: Rect) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_minimap_elite_boss(&self, _minimap: Rect) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_player(&self, _minimap: Rect, _score_threshold: f32) -> (Result<Rect>, Option<f32>) {
+        (self.player.ok_or(anyhow!("player not scripted")), None)
+    }
+
+    fn detect_player_kind(&self, _minimap: Rect, _kind: OtherPlayerKind) -> Vec<Rect> {
+        vec![]
+    }
+
+    fn detect_player_is_dead(&self) -> bool {
+        false
+    }
+
+    fn detect_player_in_cash_shop(&self) -> bool {
+        false
+    }
+
+    fn detect_player_health_bar(
+        &self,
+        _region: Option<Rect>,
+        _score_threshold: f32,
+    ) -> (Result<Rect>, Option<f32>) {
+        (Err(not_simulated()), None)
+    }
+
+    fn detect_player_current_max_health_bars(&self, _health_bar: Rect) -> Result<(Rect, Rect)> {
+        Err(not_simulated())
+    }
+
+    fn detect_player_health(&self, _current_bar: Rect, _max_bar: Rect) -> Result<(u32, u32)> {
+        Err(not_simulated())
+    }
+
+    fn detect_player_health_ratio(
+        &self,
+        _health_bar: Rect,
+        _color_lower: [u8; 3],
+        _color_upper: [u8; 3],
+    ) -> Result<f32> {
+        Err(not_simulated())
+    }
+
+    fn detect_player_current_max_mana_bars(&self, _health_bar: Rect) -> Result<(Rect, Rect)> {
+        Err(not_simulated())
+    }
+
+    fn detect_player_mana(&self, _current_bar: Rect, _max_bar: Rect) -> Result<(u32, u32)> {
+        Err(not_simulated())
+    }
+
+    fn detect_player_buff(&self, _kind: BuffKind) -> bool {
+        false
+    }
+
+    fn detect_player_exp(&self) -> Result<(u64, f32)> {
+        Err(not_simulated())
+    }
+
+    fn detect_player_mesos(&self, _region: Rect) -> Result<u64> {
+        Err(not_simulated())
+    }
+
+    fn detect_rune_arrows(&self, _calibrating: ArrowsCalibrating) -> Result<ArrowsState> {
+        Err(not_simulated())
+    }
+
+    fn detect_rune_arrows_remaining(
+        &self,
+        _calibrating: ArrowsCalibrating,
+    ) -> Result<Vec<KeyKind>> {
+        Err(not_simulated())
+    }
+
+    fn detect_erda_shower(&self) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_familiar_save_button(&self) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_familiar_setup_button(&self) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_familiar_level_button(&self) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_familiar_slots(&self) -> Vec<(Rect, bool)> {
+        vec![]
+    }
+
+    fn detect_familiar_slot_is_free(&self, _slot: Rect) -> bool {
+        false
+    }
+
+    fn detect_familiar_setup(&self) -> Vec<FamiliarSlot> {
+        vec![]
+    }
+
+    fn detect_familiar_hover_level(&self) -> Result<FamiliarLevel> {
+        Err(not_simulated())
+    }
+
+    fn detect_familiar_hover_stats(&self) -> Result<FamiliarStats> {
+        Err(not_simulated())
+    }
+
+    fn detect_familiar_cards(&self) -> Vec<(Rect, FamiliarRank)> {
+        vec![]
+    }
+
+    fn detect_familiar_scrollbar(&self) -> Result<Rect> {
+        Err(not_simulated())
+    }
+
+    fn detect_familiar_menu_opened(&self) -> bool {
+        false
+    }
+
+    fn detect_familiar_essence_depleted(&self) -> bool {
+        false
+    }
+
+    fn detect_change_channel_menu_opened(&self) -> bool {
+        false
+    }
+
+    fn detect_inventory_full(&self) -> bool {
+        false
+    }
+
+    fn detect_chat_lines(&self, _region: Rect) -> Vec<String> {
+        vec![]
+    }
+
+    fn detect_region_mean_color(&self, _region: Rect) -> Result<[f64; 3]> {
+        Err(not_simulated())
+    }
+
+    fn detect_pic_keypad(&self, _region: Rect) -> Vec<(Rect, u8)> {
+        vec![]
+    }
+}
+
+/// A headless simulation of the player state machine and rotator for a minimap preset.
+///
+/// Instead of capturing frames and sending inputs, the player position on each tick is provided
+/// by a script given the keys recorded so far. The minimap is always idle and all other
+/// detections fail. The random generator is seeded but time-based conditions (e.g.
+/// [`crate::ActionCondition::EveryMillis`]) still use the wall clock.
+pub struct Simulation {
+    context: Context,
+    player: PlayerState,
+    rotator: Rotator,
+    keys: Rc<RefCell<SimulatedKeys>>,
+    mat: Arc<OwnedMat>,
+    /// Provides the player position in player-relative coordinate, which is bottom-left.
+    script: Box<dyn FnMut(u64, &SimulatedKeys) -> Option<Point>>,
+}
+
+impl Simulation {
+    /// Creates a simulation of the actions in `preset` of `minimap`.
+    ///
+    /// `script` is called on each tick with the current tick and the recorded keys to provide
+    /// the player position, with [`None`] meaning the player cannot be detected.
+    pub fn new(
+        minimap: &MinimapData,
+        preset: Option<&str>,
+        seed: RngSeed,
+        script: impl FnMut(u64, &SimulatedKeys) -> Option<Point> + 'static,
+    ) -> Self {
+        let keys = Rc::new(RefCell::new(SimulatedKeys::default()));
+        let settings = Settings::default();
+        let context = Context {
+            handle: Handle::new(""),
+            keys: Box::new(RecordingKeySender { keys: keys.clone() }),
+            rng: Rng::new(seed),
+            notification: DiscordNotification::new(Rc::new(RefCell::new(settings.clone()))),
+            detector: None,
+            minimap: Minimap::Idle(MinimapIdle::simulated(minimap)),
+            player: Player::Idle,
+            skills: [Skill::Detecting; SkillKind::COUNT],
+            buffs: [Buff::No; BuffKind::COUNT],
+            halting: false,
+            minimap_unchanged: false,
+            detection_tuning: DetectionTuning::default(),
+            detection_scores: Cell::new(DetectionScores::default()),
+            suspended: false,
+            low_power: false,
+            frame_to_client_offset: (0, 0),
+            tick: 0,
+        };
+
+        let mut player = PlayerState::default();
+        player.config.rune_platforms_pathing = minimap.rune_platforms_pathing;
+        player.config.rune_platforms_pathing_up_jump_only =
+            minimap.rune_platforms_pathing_up_jump_only;
+        player.config.auto_mob_platforms_pathing = minimap.auto_mob_platforms_pathing;
+        player.config.auto_mob_platforms_pathing_up_jump_only =
+            minimap.auto_mob_platforms_pathing_up_jump_only;
+        player.config.auto_mob_platforms_bound = minimap.auto_mob_platforms_bound;
+
+        let actions = preset
+            .and_then(|preset| minimap.actions.get(preset))
+            .cloned()
+            .unwrap_or_default();
+        let mut rotator = Rotator::default();
+        rotator.build_actions(RotatorBuildArgs {
+            mode: config_rotator_mode(minimap),
+            actions: actions.as_slice(),
+            burst_actions: &[],
+            burst_interval_millis: 0,
+            combos: &[],
+            buffs: &[],
+            familiar_essence_key: Default::default(),
+            familiar_swappable_slots: settings.familiars.swappable_familiars,
+            familiar_swappable_rarities: &settings.familiars.swappable_rarities,
+            familiar_swap_check_millis: settings.familiars.swap_check_millis,
+            familiar_swap_when_points_depleted: settings.familiars.swap_when_points_depleted,
+            familiar_rarity_priority: settings.familiars.rarity_priority,
+            familiar_level_priority: settings.familiars.level_priority,
+            elite_boss_behavior: None,
+            elite_boss_behavior_key: Default::default(),
+            elite_boss_fight_actions: &[],
+            enable_rune_solving: false,
+            enable_familiars_swapping: false,
+            enable_reset_normal_actions_on_erda: minimap.actions_any_reset_on_erda_condition,
+            auto_mob_pickup_key: None,
+            auto_mob_pickup_ignore_millis: 0,
+            auto_mob_sequence: &minimap.rotation_auto_mob_sequence,
+            auto_mob_regions: &minimap.rotation_auto_mob_regions,
+            auto_mob_region_order: minimap.rotation_auto_mob_region_order,
+            ping_pong_bands: &minimap.rotation_ping_pong_bands,
+            sell_items: None,
+            loadout_id: None,
+        });
+
+        Self {
+            context,
+            player,
+            rotator,
+            keys,
+            mat: Arc::new(OwnedMat::from(Mat::default())),
+            script: Box::new(script),
+        }
+    }
+
+    /// The player configuration such as the jump and up jump keys.
+    #[inline]
+    pub fn player_config_mut(&mut self) -> &mut PlayerConfiguration {
+        &mut self.player.config
+    }
+
+    /// Runs the simulation for `ticks` ticks.
+    pub fn run(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            self.step();
+        }
+    }
+
+    /// Runs the simulation for a single tick.
+    pub fn step(&mut self) {
+        self.context.tick += 1;
+
+        let pos = {
+            let mut keys = self.keys.borrow_mut();
+            keys.tick = self.context.tick;
+            (self.script)(self.context.tick, &keys)
+        };
+        let Minimap::Idle(idle) = self.context.minimap else {
+            unreachable!("minimap is always idle");
+        };
+        // Flips back to OpenCV top-left coordinate as if detected from the minimap
+        let player = pos.map(|pos| Rect::new(pos.x, idle.bbox.height - pos.y - 1, 1, 1));
+        self.context.detector = Some(Box::new(SimulatedDetector {
+            mat: self.mat.clone(),
+            player,
+        }));
+
+        self.context.player = fold_context(&self.context, self.context.player, &mut self.player);
+        self.rotator.rotate_action(&self.context, &mut self.player);
+    }
+
+    #[inline]
+    pub fn tick(&self) -> u64 {
+        self.context.tick
+    }
+
+    /// The keys recorded so far.
+    #[inline]
+    pub fn keys(&self) -> Ref<'_, SimulatedKeys> {
+        self.keys.borrow()
+    }
+
+    /// The last detected player position.
+    #[inline]
+    pub fn position(&self) -> Option<Point> {
+        self.player.last_known_pos
+    }
+
+    /// The name of the current player contextual state.
+    #[inline]
+    pub fn player_state(&self) -> String {
+        self.context.player.to_string()
+    }
+}
+
+#[inline]
+fn not_simulated() -> anyhow::Error {
+    anyhow!("not simulated")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use opencv::core::Point;
+    #[cfg(target_os = "linux")]
+    use platforms::linux::KeyKind;
+    #[cfg(target_os = "macos")]
+    use platforms::macos::KeyKind;
+    #[cfg(windows)]
+    use platforms::windows::KeyKind;
+
+    use super::{SimulatedKeyAction, Simulation};
+    use crate::{Action, ActionMove, Position, database::Minimap as MinimapData};
+
+    #[test]
+    fn simulation_moves_player_to_action_position() {
+        let minimap = MinimapData {
+            width: 100,
+            height: 50,
+            actions: HashMap::from([(
+                "preset".to_string(),
+                vec![Action::Move(ActionMove {
+                    position: Position {
+                        x: 20,
+                        x_random_range: 0,
+                        y: 10,
+                        allow_adjusting: false,
+                    },
+                    ..ActionMove::default()
+                })],
+            )]),
+            ..MinimapData::default()
+        };
+        let mut pos = Point::new(10, 10);
+        let mut simulation = Simulation::new(&minimap, Some("preset"), [0; 32], move |_, keys| {
+            if keys.is_held(KeyKind::Right) {
+                pos.x += 1;
+            }
+            if keys.is_held(KeyKind::Left) {
+                pos.x -= 1;
+            }
+            Some(pos)
+        });
+
+        simulation.run(120);
+
+        assert!(
+            simulation.keys().events().iter().any(|key| {
+                key.kind == KeyKind::Right && key.action == SimulatedKeyAction::Down
+            })
+        );
+        let pos = simulation.position().unwrap();
+        // Not exact so it stops within the medium adjusting threshold
+        assert!((pos.x - 20).abs() <= 3);
+        assert_eq!(pos.y, 10);
+    }
+}
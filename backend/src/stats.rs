@@ -0,0 +1,567 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
+
+use anyhow::Result;
+use log::error;
+use opencv::core::{Point, Rect};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::Context,
+    database::{MesoDetection, insert_rune_spawn, query_rune_spawns},
+    events::Event,
+    task::{Task, Update, update_detection_task, update_task},
+};
+
+/// Milliseconds interval between each EXP detection.
+const EXP_UPDATE_MILLIS: u64 = 5000;
+
+/// Milliseconds interval between each meso detection.
+const MESO_UPDATE_MILLIS: u64 = 5000;
+
+/// Samples older than this window are dropped so that the rate reflects recent farming.
+const EXP_SAMPLES_WINDOW_MILLIS: u128 = 600_000;
+
+/// The minimum milliseconds between the first and last samples before estimating a rate.
+const EXP_RATE_MIN_ELAPSED_MILLIS: u128 = 60_000;
+
+const MILLIS_PER_HOUR: f32 = 3_600_000.0;
+
+/// The assumed milliseconds between solving a rune and the next spawn until one is observed.
+const RUNE_SPAWN_DEFAULT_MILLIS: u64 = 900_000;
+
+/// Milliseconds before the expected rune spawn from which a spawn position is expected.
+const RUNE_SPAWN_LEAD_MILLIS: u64 = 60_000;
+
+/// Maximum number of recent spawn intervals averaged for the expected spawn time.
+const RUNE_SPAWN_INTERVALS_COUNT: usize = 5;
+
+/// Maximum number of spawn positions kept per map.
+const RUNE_SPAWNS_COUNT: usize = 100;
+
+/// Spawn positions within this distance on both axes count as the same position.
+const RUNE_SPAWN_PROXIMITY: i32 = 8;
+
+#[derive(Clone, Copy, Debug)]
+struct ExpSample {
+    instant: Instant,
+    exp: u64,
+    percent: f32,
+}
+
+/// Aggregates EXP read from the EXP bar into per-hour rate estimates.
+#[derive(Debug, Default)]
+pub struct ExpStats {
+    task: Option<Task<Result<(u64, f32)>>>,
+    samples: VecDeque<ExpSample>,
+}
+
+impl ExpStats {
+    /// Detects the current EXP and records it as a sample.
+    pub fn update(&mut self, context: &Context) {
+        if let Update::Ok((exp, percent)) =
+            update_detection_task(context, EXP_UPDATE_MILLIS, &mut self.task, |detector| {
+                detector.detect_player_exp()
+            })
+        {
+            self.push_sample(Instant::now(), exp, percent);
+        }
+    }
+
+    /// Clears all samples.
+    ///
+    /// Used when the bot stops so that idle time does not lower the rate.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /// The estimated EXP points gained per hour.
+    pub fn exp_per_hour(&self) -> Option<u64> {
+        let (first, last, hours) = self.first_last_hours()?;
+        Some(((last.exp - first.exp) as f32 / hours) as u64)
+    }
+
+    /// The estimated EXP percentage gained per hour.
+    pub fn exp_percent_per_hour(&self) -> Option<f32> {
+        let (first, last, hours) = self.first_last_hours()?;
+        Some((last.percent - first.percent) / hours)
+    }
+
+    fn push_sample(&mut self, instant: Instant, exp: u64, percent: f32) {
+        // EXP only goes down on level up or death, either way the previous samples
+        // cannot be compared anymore
+        if self
+            .samples
+            .back()
+            .is_some_and(|last| last.exp > exp || last.percent > percent)
+        {
+            self.samples.clear();
+        }
+        self.samples.push_back(ExpSample {
+            instant,
+            exp,
+            percent,
+        });
+        while self.samples.front().is_some_and(|sample| {
+            instant.duration_since(sample.instant).as_millis() > EXP_SAMPLES_WINDOW_MILLIS
+        }) {
+            self.samples.pop_front();
+        }
+    }
+
+    fn first_last_hours(&self) -> Option<(ExpSample, ExpSample, f32)> {
+        let first = *self.samples.front()?;
+        let last = *self.samples.back()?;
+        let elapsed = last.instant.duration_since(first.instant).as_millis();
+        if elapsed < EXP_RATE_MIN_ELAPSED_MILLIS {
+            return None;
+        }
+        Some((first, last, elapsed as f32 / MILLIS_PER_HOUR))
+    }
+}
+
+/// Aggregates the meso count read from [`MesoDetection`] into a per-hour rate estimate.
+///
+/// Shares the sample window of [`ExpStats`].
+#[derive(Debug, Default)]
+pub struct MesoStats {
+    task: Option<Task<Result<u64>>>,
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl MesoStats {
+    /// Detects the current meso count and records it as a sample.
+    pub fn update(&mut self, context: &Context, mesos: &MesoDetection) {
+        if !mesos.enabled {
+            self.samples.clear();
+            return;
+        }
+
+        let region = Rect::new(mesos.x, mesos.y, mesos.width, mesos.height);
+        if let Update::Ok(count) = update_detection_task(
+            context,
+            MESO_UPDATE_MILLIS,
+            &mut self.task,
+            move |detector| detector.detect_player_mesos(region),
+        ) {
+            self.push_sample(Instant::now(), count);
+        }
+    }
+
+    /// Clears all samples.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /// The estimated mesos gained per hour.
+    pub fn mesos_per_hour(&self) -> Option<u64> {
+        let (first_instant, first) = *self.samples.front()?;
+        let (last_instant, last) = *self.samples.back()?;
+        let elapsed = last_instant.duration_since(first_instant).as_millis();
+        if elapsed < EXP_RATE_MIN_ELAPSED_MILLIS {
+            return None;
+        }
+        Some(((last - first) as f32 / (elapsed as f32 / MILLIS_PER_HOUR)) as u64)
+    }
+
+    fn push_sample(&mut self, instant: Instant, count: u64) {
+        // Mesos only go down when spent (e.g. buying potions) so the previous samples cannot be
+        // compared anymore
+        if self.samples.back().is_some_and(|(_, last)| *last > count) {
+            self.samples.clear();
+        }
+        self.samples.push_back((instant, count));
+        while self.samples.front().is_some_and(|(sample_instant, _)| {
+            instant.duration_since(*sample_instant).as_millis() > EXP_SAMPLES_WINDOW_MILLIS
+        }) {
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// Learns per map where and how often runes spawn.
+#[derive(Debug, Default)]
+pub struct RuneStats {
+    minimap_id: Option<i64>,
+    /// Spawn positions observed on the current map from oldest to newest.
+    spawns: VecDeque<Point>,
+    /// Recent milliseconds between solving a rune and the next spawn.
+    intervals: VecDeque<u64>,
+    last_solved: Option<Instant>,
+    /// The rune position in the last update.
+    rune: Option<Point>,
+    /// Whether the next rune appearing is a new spawn to record.
+    awaiting_spawn: bool,
+    /// Whether the spawn positions of the current map have been loaded from the database.
+    loaded: bool,
+    task: Option<Task<Result<Vec<(i32, i32)>>>>,
+}
+
+impl RuneStats {
+    /// Updates with the currently detected `rune` on the map with `minimap_id`.
+    ///
+    /// `solved` is whether a rune has just been solved. A newly spawned rune is persisted so
+    /// that spawn positions are learned across sessions. The spawn positions are loaded from the
+    /// database in the background after the map changes.
+    pub fn update(
+        &mut self,
+        minimap_id: Option<i64>,
+        rune: Option<Point>,
+        solved: bool,
+        now: Instant,
+    ) {
+        if self.minimap_id != minimap_id {
+            self.reset(minimap_id);
+        }
+        if !self.loaded {
+            self.update_load();
+        }
+        if solved {
+            self.last_solved = Some(now);
+            self.awaiting_spawn = true;
+        }
+
+        let appeared = self.rune.is_none() && rune.is_some();
+        self.rune = rune;
+        if appeared
+            && self.awaiting_spawn
+            && let Some(point) = rune
+        {
+            self.push_spawn(point, now);
+            if let Some(id) = self.minimap_id {
+                insert_rune_spawn(id, point.x, point.y);
+            }
+        }
+    }
+
+    /// The most frequent spawn position if the next rune is expected to spawn soon.
+    pub fn expected_spawn(&self, now: Instant) -> Option<Point> {
+        if self.rune.is_some() || !self.awaiting_spawn {
+            return None;
+        }
+        let elapsed = now.duration_since(self.last_solved?).as_millis() as u64;
+        if elapsed + RUNE_SPAWN_LEAD_MILLIS < self.expected_interval_millis() {
+            return None;
+        }
+        self.frequent_spawn()
+    }
+
+    fn reset(&mut self, minimap_id: Option<i64>) {
+        *self = RuneStats {
+            minimap_id,
+            awaiting_spawn: true,
+            loaded: minimap_id.is_none(),
+            ..RuneStats::default()
+        };
+    }
+
+    fn update_load(&mut self) {
+        let Some(id) = self.minimap_id else {
+            return;
+        };
+        let update = update_task(
+            0,
+            &mut self.task,
+            || id,
+            |id| query_rune_spawns(id, RUNE_SPAWNS_COUNT as u32),
+        );
+        let spawns = match update {
+            Update::Ok(spawns) => spawns,
+            Update::Err(err) => {
+                error!(target: "stats", "failed to load rune spawns {err}");
+                Vec::new()
+            }
+            Update::Pending => return,
+        };
+
+        self.loaded = true;
+        self.task = None;
+        // Spawns observed while loading are newer than the loaded ones
+        for (x, y) in spawns {
+            if self.spawns.len() >= RUNE_SPAWNS_COUNT {
+                break;
+            }
+            self.spawns.push_front(Point::new(x, y));
+        }
+    }
+
+    fn push_spawn(&mut self, point: Point, now: Instant) {
+        if let Some(last_solved) = self.last_solved {
+            if self.intervals.len() >= RUNE_SPAWN_INTERVALS_COUNT {
+                self.intervals.pop_front();
+            }
+            self.intervals
+                .push_back(now.duration_since(last_solved).as_millis() as u64);
+        }
+        if self.spawns.len() >= RUNE_SPAWNS_COUNT {
+            self.spawns.pop_front();
+        }
+        self.spawns.push_back(point);
+        self.awaiting_spawn = false;
+    }
+
+    fn expected_interval_millis(&self) -> u64 {
+        if self.intervals.is_empty() {
+            return RUNE_SPAWN_DEFAULT_MILLIS;
+        }
+        self.intervals.iter().sum::<u64>() / self.intervals.len() as u64
+    }
+
+    /// The spawn position with the most other spawn positions nearby.
+    fn frequent_spawn(&self) -> Option<Point> {
+        self.spawns.iter().copied().max_by_key(|point| {
+            self.spawns
+                .iter()
+                .filter(|other| {
+                    (other.x - point.x).abs() <= RUNE_SPAWN_PROXIMITY
+                        && (other.y - point.y).abs() <= RUNE_SPAWN_PROXIMITY
+                })
+                .count()
+        })
+    }
+}
+
+/// The execution statistics of an action in the active preset.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ActionStats {
+    /// The index of the action in the active preset.
+    pub index: usize,
+    /// The number of times the action started.
+    pub count: u32,
+    /// Unix timestamp in milliseconds of the last time the action started.
+    pub last_run_timestamp_millis: u64,
+    /// The average milliseconds from the action starting to completing.
+    ///
+    /// This is [`None`] if the action has not completed yet.
+    pub average_duration_millis: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ActionStatsEntry {
+    stats: ActionStats,
+    completed_count: u32,
+    total_duration_millis: u64,
+}
+
+/// Tracks [`ActionStats`] of the active preset actions from [`Event::ActionStarted`] and
+/// [`Event::ActionCompleted`].
+#[derive(Debug, Default)]
+pub struct ActionStatsTracker {
+    entries: HashMap<usize, ActionStatsEntry>,
+    /// The running action ids with their preset action index and start [`Instant`].
+    running: HashMap<u32, (usize, Instant)>,
+}
+
+impl ActionStatsTracker {
+    /// Updates the statistics from the `events` emitted in this tick.
+    ///
+    /// `index_of` maps a rotator action id to its index in the active preset and returns [`None`]
+    /// for actions not from the preset such as buffs. Each step of a linked action is emitted
+    /// as a completion followed by a start with the same id, which counts as a single run.
+    pub fn update(
+        &mut self,
+        events: &[Event],
+        index_of: impl Fn(u32) -> Option<usize>,
+        now: Instant,
+        timestamp_millis: u64,
+    ) {
+        let mut completed = Vec::new();
+        for event in events {
+            match event {
+                Event::ActionStarted { id, .. } => {
+                    if let Some(i) = completed.iter().position(|completed| completed == id) {
+                        completed.swap_remove(i);
+                        continue;
+                    }
+                    let Some(index) = index_of(*id) else {
+                        continue;
+                    };
+                    let entry = self.entries.entry(index).or_insert(ActionStatsEntry {
+                        stats: ActionStats {
+                            index,
+                            count: 0,
+                            last_run_timestamp_millis: timestamp_millis,
+                            average_duration_millis: None,
+                        },
+                        completed_count: 0,
+                        total_duration_millis: 0,
+                    });
+                    entry.stats.count += 1;
+                    entry.stats.last_run_timestamp_millis = timestamp_millis;
+                    self.running.insert(*id, (index, now));
+                }
+                Event::ActionCompleted { id, .. } => completed.push(*id),
+                _ => (),
+            }
+        }
+
+        for id in completed {
+            let Some((index, started)) = self.running.remove(&id) else {
+                continue;
+            };
+            let Some(entry) = self.entries.get_mut(&index) else {
+                continue;
+            };
+            entry.completed_count += 1;
+            entry.total_duration_millis += now.duration_since(started).as_millis() as u64;
+            entry.stats.average_duration_millis =
+                Some(entry.total_duration_millis / entry.completed_count as u64);
+        }
+    }
+
+    /// Clears all statistics.
+    ///
+    /// Used when the active preset actions change so that indices no longer match.
+    pub fn reset(&mut self) {
+        self.entries.clear();
+        self.running.clear();
+    }
+
+    /// The statistics of all actions that have run ordered by index.
+    pub fn stats(&self) -> Vec<ActionStats> {
+        let mut stats = self
+            .entries
+            .values()
+            .map(|entry| entry.stats)
+            .collect::<Vec<_>>();
+        stats.sort_by_key(|stats| stats.index);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use opencv::core::Point;
+
+    use super::{ActionStats, ActionStatsTracker, ExpStats, MesoStats, RuneStats};
+    use crate::events::Event;
+
+    #[test]
+    fn exp_per_hour_requires_min_elapsed() {
+        let mut stats = ExpStats::default();
+        let instant = Instant::now();
+        stats.push_sample(instant, 1000, 10.0);
+        stats.push_sample(instant + Duration::from_secs(30), 2000, 11.0);
+
+        assert_eq!(stats.exp_per_hour(), None);
+        assert_eq!(stats.exp_percent_per_hour(), None);
+
+        stats.push_sample(instant + Duration::from_secs(450), 7000, 16.0);
+
+        assert_eq!(stats.exp_per_hour(), Some(48000));
+        assert_eq!(stats.exp_percent_per_hour(), Some(48.0));
+    }
+
+    #[test]
+    fn push_sample_resets_on_decrease_and_window() {
+        let mut stats = ExpStats::default();
+        let instant = Instant::now();
+        stats.push_sample(instant, 1000, 90.0);
+        stats.push_sample(instant + Duration::from_secs(60), 100, 1.0);
+
+        assert_eq!(stats.samples.len(), 1);
+
+        stats.push_sample(instant + Duration::from_secs(720), 200, 2.0);
+
+        assert_eq!(stats.samples.len(), 1);
+    }
+
+    #[test]
+    fn mesos_per_hour_resets_on_spending() {
+        let mut stats = MesoStats::default();
+        let instant = Instant::now();
+        stats.push_sample(instant, 1_000_000);
+        stats.push_sample(instant + Duration::from_secs(450), 1_100_000);
+
+        assert_eq!(stats.mesos_per_hour(), Some(800_000));
+
+        stats.push_sample(instant + Duration::from_secs(480), 50_000);
+
+        assert_eq!(stats.mesos_per_hour(), None);
+    }
+
+    #[test]
+    fn rune_expected_spawn_after_average_interval() {
+        let mut stats = RuneStats::default();
+        let instant = Instant::now();
+        stats.update(None, None, true, instant);
+        stats.update(
+            None,
+            Some(Point::new(10, 10)),
+            false,
+            instant + Duration::from_secs(600),
+        );
+
+        assert_eq!(
+            stats.expected_spawn(instant + Duration::from_secs(600)),
+            None
+        );
+
+        let solved = instant + Duration::from_secs(700);
+        stats.update(None, None, true, solved);
+
+        assert_eq!(
+            stats.expected_spawn(solved + Duration::from_secs(500)),
+            None
+        );
+        assert_eq!(
+            stats.expected_spawn(solved + Duration::from_secs(540)),
+            Some(Point::new(10, 10))
+        );
+    }
+
+    #[test]
+    fn action_stats_counts_linked_steps_as_one_run() {
+        let mut tracker = ActionStatsTracker::default();
+        let instant = Instant::now();
+        let started = |id, name: &str| Event::ActionStarted {
+            id,
+            name: name.to_string(),
+        };
+        let completed = |id, name: &str| Event::ActionCompleted {
+            id,
+            name: name.to_string(),
+        };
+        let index_of = |id| (id == 1).then_some(3);
+
+        tracker.update(&[started(1, "Move")], index_of, instant, 1000);
+        tracker.update(
+            &[completed(1, "Move"), started(1, "Key")],
+            index_of,
+            instant + Duration::from_millis(400),
+            1400,
+        );
+        tracker.update(
+            &[completed(1, "Key"), started(2, "Key")],
+            index_of,
+            instant + Duration::from_millis(1000),
+            2000,
+        );
+
+        assert_eq!(
+            tracker.stats(),
+            vec![ActionStats {
+                index: 3,
+                count: 1,
+                last_run_timestamp_millis: 1000,
+                average_duration_millis: Some(1000),
+            }]
+        );
+    }
+
+    #[test]
+    fn rune_frequent_spawn_counts_nearby_positions() {
+        let mut stats = RuneStats::default();
+        let instant = Instant::now();
+        stats.push_spawn(Point::new(50, 50), instant);
+        stats.push_spawn(Point::new(10, 10), instant);
+        stats.push_spawn(Point::new(53, 46), instant);
+
+        assert_eq!(stats.frequent_spawn(), Some(Point::new(53, 46)));
+        assert!(stats.intervals.is_empty());
+    }
+}
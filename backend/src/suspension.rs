@@ -0,0 +1,126 @@
+use std::time::{Duration, Instant};
+
+/// The duration without any captured frame before suspending.
+const SUSPEND_AFTER: Duration = Duration::from_secs(2);
+
+/// The interval between each capture handle re-acquisition while suspended.
+const RETRY_INTERVAL: Duration = Duration::from_secs(3);
+
+/// A transition returned from [`CaptureSuspension::update`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CaptureTransition {
+    /// No frame has been captured for too long and the capture is now suspended.
+    Suspended,
+    /// The capture is still suspended and the capture handle should be re-acquired.
+    Retry,
+    /// A frame is captured again after being suspended.
+    Resumed,
+}
+
+/// Tracks whether the capture is suspended because the game window disappeared.
+#[derive(Debug, Default)]
+pub struct CaptureSuspension {
+    missing_instant: Option<Instant>,
+    retry_instant: Option<Instant>,
+    suspended: bool,
+}
+
+impl CaptureSuspension {
+    /// Updates the suspension state with whether `has_frame` is captured this tick.
+    pub fn update(&mut self, has_frame: bool, now: Instant) -> Option<CaptureTransition> {
+        if has_frame {
+            self.missing_instant = None;
+            self.retry_instant = None;
+            if self.suspended {
+                self.suspended = false;
+                return Some(CaptureTransition::Resumed);
+            }
+            return None;
+        }
+
+        if self.suspended {
+            let retry_instant = self.retry_instant.get_or_insert(now);
+            if now.duration_since(*retry_instant) >= RETRY_INTERVAL {
+                *retry_instant = now;
+                return Some(CaptureTransition::Retry);
+            }
+            return None;
+        }
+
+        let missing_instant = *self.missing_instant.get_or_insert(now);
+        if now.duration_since(missing_instant) >= SUSPEND_AFTER {
+            self.suspended = true;
+            self.retry_instant = Some(now);
+            return Some(CaptureTransition::Suspended);
+        }
+        None
+    }
+
+    #[inline]
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{CaptureSuspension, CaptureTransition, RETRY_INTERVAL, SUSPEND_AFTER};
+
+    #[test]
+    fn update_suspends_after_missing_frames() {
+        let mut suspension = CaptureSuspension::default();
+        let now = Instant::now();
+
+        assert_eq!(suspension.update(false, now), None);
+        assert_eq!(
+            suspension.update(false, now + SUSPEND_AFTER - Duration::from_millis(1)),
+            None
+        );
+        assert!(!suspension.is_suspended());
+        assert_eq!(
+            suspension.update(false, now + SUSPEND_AFTER),
+            Some(CaptureTransition::Suspended)
+        );
+        assert!(suspension.is_suspended());
+    }
+
+    #[test]
+    fn update_frame_resets_missing_duration() {
+        let mut suspension = CaptureSuspension::default();
+        let now = Instant::now();
+
+        assert_eq!(suspension.update(false, now), None);
+        assert_eq!(suspension.update(true, now + SUSPEND_AFTER), None);
+        assert_eq!(suspension.update(false, now + SUSPEND_AFTER), None);
+        assert!(!suspension.is_suspended());
+    }
+
+    #[test]
+    fn update_retries_and_resumes() {
+        let mut suspension = CaptureSuspension::default();
+        let now = Instant::now();
+        suspension.update(false, now);
+        suspension.update(false, now + SUSPEND_AFTER);
+        let suspended = now + SUSPEND_AFTER;
+
+        assert_eq!(
+            suspension.update(false, suspended + RETRY_INTERVAL / 2),
+            None
+        );
+        assert_eq!(
+            suspension.update(false, suspended + RETRY_INTERVAL),
+            Some(CaptureTransition::Retry)
+        );
+        assert_eq!(
+            suspension.update(false, suspended + RETRY_INTERVAL * 2),
+            Some(CaptureTransition::Retry)
+        );
+        assert_eq!(
+            suspension.update(true, suspended + RETRY_INTERVAL * 2),
+            Some(CaptureTransition::Resumed)
+        );
+        assert!(!suspension.is_suspended());
+    }
+}
@@ -0,0 +1,320 @@
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use log::{error, info};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::runtime::Handle;
+
+use crate::task::{Task, Update, update_task};
+
+/// The GitHub API endpoint of the latest release.
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/sasanquaa/komari/releases/latest";
+
+/// The directory next to the executable that the new binary is downloaded to.
+const STAGING_DIR_NAME: &str = "update";
+
+/// The extension of the replaced binary until it is removed on next startup.
+const PREVIOUS_BINARY_EXTENSION: &str = "old";
+
+/// The extension of the release asset containing the SHA-256 checksum of the binary asset.
+const CHECKSUM_EXTENSION: &str = "sha256";
+
+/// The environment variable set on the launched binary to wait for the replaced process to exit.
+const UPDATED_ENV: &str = "KOMARI_UPDATED";
+
+/// A newer release that has been downloaded and is ready to be applied.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct UpdateInfo {
+    pub version: String,
+    /// The release notes in Markdown.
+    pub notes: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Debug)]
+struct StagedUpdate {
+    info: UpdateInfo,
+    path: PathBuf,
+}
+
+/// Checks the latest release once on startup and stages its binary if it is newer.
+#[derive(Debug, Default)]
+pub struct UpdateChecker {
+    task: Option<Task<Result<Option<StagedUpdate>>>>,
+    checked: bool,
+    staged: Option<StagedUpdate>,
+    /// The client or [`None`] if the configured proxy is invalid.
+    client: Option<Client>,
+    /// The launched updated binary.
+    ///
+    /// Kept until this process exits because the binary waits for its stdin to be closed.
+    launched: Option<Child>,
+}
+
+impl UpdateChecker {
+    /// Creates the checker fetching releases with `client`.
+    pub fn new(client: Option<Client>) -> Self {
+        Self {
+            client,
+            ..Self::default()
+        }
+    }
+
+    /// Polls the update check if `enabled` and it has not completed yet.
+    pub fn update(&mut self, enabled: bool) {
+        if !enabled || self.checked {
+            return;
+        }
+        let Some(client) = self.client.as_ref() else {
+            self.checked = true;
+            return;
+        };
+
+        let update = update_task(
+            0,
+            &mut self.task,
+            || client.clone(),
+            |client| Handle::current().block_on(check_and_stage(client)),
+        );
+        match update {
+            Update::Ok(staged) => {
+                self.checked = true;
+                self.staged = staged;
+            }
+            Update::Err(err) => {
+                self.checked = true;
+                error!(target: "update", "failed to check for update {err}");
+            }
+            Update::Pending => (),
+        }
+    }
+
+    /// The staged update if there is one.
+    pub fn available(&self) -> Option<&UpdateInfo> {
+        self.staged.as_ref().map(|staged| &staged.info)
+    }
+
+    /// Replaces the current executable with the staged binary and launches it.
+    ///
+    /// The launched binary waits for the current process to exit before opening the database.
+    /// The caller is expected to exit the current process afterward.
+    pub fn apply(&mut self) -> Result<()> {
+        let staged = self
+            .staged
+            .as_ref()
+            .ok_or_else(|| anyhow!("no update is staged"))?;
+        let exe = env::current_exe()?;
+        let previous = exe.with_extension(PREVIOUS_BINARY_EXTENSION);
+
+        // A running executable cannot be overwritten on Windows but it can be renamed
+        fs::rename(&exe, &previous)?;
+        if let Err(err) = fs::rename(&staged.path, &exe) {
+            let _ = fs::rename(&previous, &exe);
+            return Err(err.into());
+        }
+        let launched = Command::new(&exe)
+            .env(UPDATED_ENV, "1")
+            .stdin(Stdio::piped())
+            .spawn();
+        match launched {
+            Ok(child) => {
+                info!(target: "update", "applied update {}", staged.info.version);
+                self.launched = Some(child);
+                self.staged = None;
+                Ok(())
+            }
+            Err(err) => {
+                let _ = fs::rename(&exe, &staged.path);
+                let _ = fs::rename(&previous, &exe);
+                Err(err.into())
+            }
+        }
+    }
+}
+
+/// Blocks until the process that launched this one exits if this process is an updated binary.
+///
+/// The replaced process holds the stdin pipe of this process until it exits.
+pub fn wait_for_previous_process() {
+    if env::var_os(UPDATED_ENV).is_none() {
+        return;
+    }
+    info!(target: "update", "waiting for the previous process to exit");
+    let _ = io::stdin().read_to_end(&mut Vec::new());
+}
+
+/// Removes the executable replaced by the last applied update.
+pub fn remove_previous_binary() {
+    if let Ok(exe) = env::current_exe() {
+        let _ = fs::remove_file(exe.with_extension(PREVIOUS_BINARY_EXTENSION));
+    }
+}
+
+async fn check_and_stage(client: Client) -> Result<Option<StagedUpdate>> {
+    let text = client
+        .get(LATEST_RELEASE_URL)
+        .header("User-Agent", "komari")
+        .header("Accept", "application/vnd.github+json")
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let release = serde_json::from_str::<Release>(&text)?;
+    if !is_newer_version(&release.tag_name, env!("CARGO_PKG_VERSION")) {
+        info!(target: "update", "already on the latest version");
+        return Ok(None);
+    }
+
+    let exe = env::current_exe()?;
+    let exe_name = exe
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("invalid executable name")?;
+    let asset = select_asset(&release.assets, exe_name)
+        .with_context(|| format!("release {} has no matching binary", release.tag_name))?;
+    let checksum_name = format!("{}.{CHECKSUM_EXTENSION}", asset.name);
+    let checksum = select_asset(&release.assets, &checksum_name)
+        .with_context(|| format!("release {} has no {checksum_name}", release.tag_name))?;
+    let checksum = client
+        .get(&checksum.browser_download_url)
+        .header("User-Agent", "komari")
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let bytes = client
+        .get(&asset.browser_download_url)
+        .header("User-Agent", "komari")
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    verify_checksum(&bytes, &checksum)?;
+
+    let path = staging_dir(&exe)?.join(&asset.name);
+    fs::write(&path, bytes)?;
+    #[cfg(unix)]
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    info!(target: "update", "staged update {} to {}", release.tag_name, path.display());
+    Ok(Some(StagedUpdate {
+        info: UpdateInfo {
+            version: release.tag_name,
+            notes: release.body.unwrap_or_default(),
+        },
+        path,
+    }))
+}
+
+fn staging_dir(exe: &Path) -> Result<PathBuf> {
+    let dir = exe
+        .parent()
+        .context("executable has no parent directory")?
+        .join(STAGING_DIR_NAME);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Selects the asset with `name` (e.g. the executable name).
+fn select_asset<'a>(assets: &'a [ReleaseAsset], name: &str) -> Option<&'a ReleaseAsset> {
+    assets.iter().find(|asset| asset.name == name)
+}
+
+/// Verifies that the SHA-256 of `bytes` matches `checksum`.
+///
+/// `checksum` is in the `sha256sum` output format where the hex digest is optionally followed by
+/// the file name.
+fn verify_checksum(bytes: &[u8], checksum: &str) -> Result<()> {
+    let expected = checksum
+        .split_whitespace()
+        .next()
+        .context("empty checksum")?
+        .to_ascii_lowercase();
+    let actual = format!("{:x}", Sha256::digest(bytes));
+    if actual != expected {
+        bail!("checksum mismatch, expected {expected} but got {actual}");
+    }
+    Ok(())
+}
+
+/// Whether `latest` (e.g. `v0.20.0`) is a newer version than `current`.
+///
+/// Returns `false` if either cannot be parsed.
+fn is_newer_version(latest: &str, current: &str) -> bool {
+    fn parse(version: &str) -> Option<Vec<u32>> {
+        version
+            .trim()
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse::<u32>().ok())
+            .collect()
+    }
+
+    parse(latest)
+        .zip(parse(current))
+        .is_some_and(|(latest, current)| latest > current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_newer_version_compares_parts() {
+        assert!(is_newer_version("v0.20.0", "0.19.0"));
+        assert!(is_newer_version("v0.19.10", "0.19.9"));
+        assert!(is_newer_version("1.0", "0.19.0"));
+        assert!(!is_newer_version("v0.19.0", "0.19.0"));
+        assert!(!is_newer_version("v0.18.2", "0.19.0"));
+        assert!(!is_newer_version("nightly", "0.19.0"));
+    }
+
+    #[test]
+    fn select_asset_prefers_executable_name() {
+        let asset = |name: &str| ReleaseAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.com/{name}"),
+        };
+        let assets = [asset("models.zip"), asset("ui"), asset("komari.exe")];
+
+        assert_eq!(select_asset(&assets, "ui").unwrap().name, "ui");
+        assert!(select_asset(&assets[..1], "ui").is_none());
+        assert!(select_asset(&assets, "komari-ui.exe").is_none());
+    }
+
+    #[test]
+    fn verify_checksum_compares_sha256() {
+        let checksum = "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824  ui";
+
+        assert!(verify_checksum(b"hello", checksum).is_ok());
+        assert!(verify_checksum(b"hello!", checksum).is_err());
+        assert!(verify_checksum(b"hello", "").is_err());
+    }
+}
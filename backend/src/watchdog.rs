@@ -0,0 +1,190 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Ok, Result};
+use log::debug;
+use reqwest::Client;
+use strum::Display;
+use tokio::runtime::Handle;
+
+use crate::{
+    database::Watchdog as WatchdogData,
+    task::{Task, Update, update_task},
+};
+
+/// The reason the [`WatchdogState`] tripped.
+#[derive(Clone, Copy, PartialEq, Debug, Display)]
+pub enum WatchdogTrip {
+    CaptureLost,
+    DetectionFailure,
+    HeartbeatLost,
+}
+
+/// A dead-man switch that trips when the bot can no longer be trusted to keep running.
+#[derive(Debug, Default)]
+pub struct WatchdogState {
+    capture_lost_instant: Option<Instant>,
+    detection_failure_instant: Option<Instant>,
+    heartbeat_task: Option<Task<Result<()>>>,
+    heartbeat_failures: u32,
+    /// The client or [`None`] if the configured proxy is invalid.
+    client: Option<Client>,
+}
+
+impl WatchdogState {
+    /// Creates the watchdog sending heartbeats with `client`.
+    pub fn new(client: Option<Client>) -> Self {
+        Self {
+            client,
+            ..Self::default()
+        }
+    }
+
+    /// Updates the watchdog states and returns the trip reason if any threshold is exceeded.
+    ///
+    /// `has_frame` is whether a frame is captured this tick and `detection_failing` is whether
+    /// the minimap or the player cannot be detected while the bot is running.
+    pub fn update(
+        &mut self,
+        settings: &WatchdogData,
+        has_frame: bool,
+        detection_failing: bool,
+        now: Instant,
+    ) -> Option<WatchdogTrip> {
+        if !settings.enabled {
+            self.reset();
+            return None;
+        }
+
+        if exceeds_millis(
+            &mut self.capture_lost_instant,
+            !has_frame,
+            settings.capture_lost_millis,
+            now,
+        ) {
+            return Some(WatchdogTrip::CaptureLost);
+        }
+        if exceeds_millis(
+            &mut self.detection_failure_instant,
+            detection_failing,
+            settings.detection_failure_millis,
+            now,
+        ) {
+            return Some(WatchdogTrip::DetectionFailure);
+        }
+        if self.update_heartbeat(settings) {
+            return Some(WatchdogTrip::HeartbeatLost);
+        }
+        None
+    }
+
+    /// Resets all the tracked states.
+    ///
+    /// Used after tripping or when the bot starts so that previous failures are not carried over.
+    pub fn reset(&mut self) {
+        self.capture_lost_instant = None;
+        self.detection_failure_instant = None;
+        self.heartbeat_failures = 0;
+    }
+
+    fn update_heartbeat(&mut self, settings: &WatchdogData) -> bool {
+        if settings.heartbeat_url.is_empty() || settings.heartbeat_max_failures == 0 {
+            self.heartbeat_failures = 0;
+            return false;
+        }
+        let Some(client) = self.client.as_ref() else {
+            return false;
+        };
+
+        let update = update_task(
+            settings.heartbeat_interval_millis,
+            &mut self.heartbeat_task,
+            || (client.clone(), settings.heartbeat_url.clone()),
+            |(client, url)| {
+                Handle::current().block_on(async move {
+                    client
+                        .get(url)
+                        .timeout(Duration::from_secs(10))
+                        .send()
+                        .await?
+                        .error_for_status()?;
+                    Ok(())
+                })
+            },
+        );
+        match update {
+            Update::Ok(_) => self.heartbeat_failures = 0,
+            Update::Err(err) => {
+                debug!(target: "watchdog", "heartbeat failed {err}");
+                self.heartbeat_failures += 1;
+            }
+            Update::Pending => (),
+        }
+        self.heartbeat_failures >= settings.heartbeat_max_failures
+    }
+}
+
+/// Tracks how long `failing` has been continuously `true` in `instant`.
+///
+/// Returns `true` once it has been failing for at least `millis` with `0` indicating disabled.
+fn exceeds_millis(instant: &mut Option<Instant>, failing: bool, millis: u64, now: Instant) -> bool {
+    if !failing || millis == 0 {
+        *instant = None;
+        return false;
+    }
+
+    let since = *instant.get_or_insert(now);
+    now.duration_since(since) >= Duration::from_millis(millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{WatchdogState, WatchdogTrip};
+    use crate::database::Watchdog as WatchdogData;
+
+    #[test]
+    fn update_trips_on_capture_lost_then_detection_failure() {
+        let mut watchdog = WatchdogState::default();
+        let settings = WatchdogData {
+            enabled: true,
+            capture_lost_millis: 1000,
+            detection_failure_millis: 2000,
+            ..WatchdogData::default()
+        };
+        let now = Instant::now();
+
+        assert_eq!(watchdog.update(&settings, false, false, now), None);
+        assert_eq!(
+            watchdog.update(&settings, false, false, now + Duration::from_secs(1)),
+            Some(WatchdogTrip::CaptureLost)
+        );
+
+        assert_eq!(watchdog.update(&settings, true, true, now), None);
+        assert_eq!(
+            watchdog.update(&settings, true, true, now + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(
+            watchdog.update(&settings, true, true, now + Duration::from_secs(2)),
+            Some(WatchdogTrip::DetectionFailure)
+        );
+    }
+
+    #[test]
+    fn update_does_nothing_if_disabled() {
+        let mut watchdog = WatchdogState::default();
+        let settings = WatchdogData {
+            enabled: false,
+            capture_lost_millis: 1,
+            ..WatchdogData::default()
+        };
+        let now = Instant::now();
+
+        watchdog.update(&settings, false, false, now);
+        assert_eq!(
+            watchdog.update(&settings, false, false, now + Duration::from_secs(1)),
+            None
+        );
+    }
+}
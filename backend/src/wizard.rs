@@ -0,0 +1,148 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bridge::ImageCapture,
+    database::CaptureMode,
+    detect::{CachedDetector, Detector},
+    mat::OwnedMat,
+};
+
+/// Maximum duration to wait for a frame when testing a capture mode.
+///
+/// Some capture modes only produce frames after a short delay from being created.
+const CAPTURE_TEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The window title used for suggesting the game window.
+const GAME_WINDOW_TITLE: &str = "maplestory";
+
+/// The result of testing a [`CaptureMode`] during the first-run setup.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CaptureTestResult {
+    pub mode: CaptureMode,
+    /// Whether any frame is captured.
+    pub has_frame: bool,
+    /// Whether the minimap is detected in the captured frame.
+    pub has_minimap: bool,
+}
+
+/// Suggests the index of the game window from the capture handle `names`.
+pub fn suggested_window(names: &[String]) -> Option<usize> {
+    names
+        .iter()
+        .position(|name| name.to_lowercase().contains(GAME_WINDOW_TITLE))
+}
+
+#[derive(Debug)]
+struct RunningCaptureTest {
+    mode: CaptureMode,
+    /// The capture created for testing `mode` or [`None`] if `mode` is the current capture mode.
+    capture: Option<ImageCapture>,
+    started: Instant,
+}
+
+/// Tests a [`CaptureMode`] across update loop ticks until a frame is captured.
+#[derive(Debug, Default)]
+pub struct CaptureTester {
+    running: Option<RunningCaptureTest>,
+    /// The result of the last completed test.
+    result: Option<CaptureTestResult>,
+}
+
+impl CaptureTester {
+    /// Starts testing `mode` with `capture` or the current capture if [`None`].
+    pub fn start(&mut self, mode: CaptureMode, capture: Option<ImageCapture>, now: Instant) {
+        self.running = Some(RunningCaptureTest {
+            mode,
+            capture,
+            started: now,
+        });
+        self.result = None;
+    }
+
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    #[inline]
+    pub fn result(&self) -> Option<CaptureTestResult> {
+        self.result
+    }
+
+    /// Grabs a frame and detects the minimap from it if the test is running.
+    ///
+    /// `detector` is the detector of the frame captured by the current capture in this tick.
+    pub fn update(&mut self, detector: Option<&dyn Detector>, border_threshold: u8, now: Instant) {
+        let Some(running) = self.running.as_mut() else {
+            return;
+        };
+        let has_minimap = match running.capture.as_mut() {
+            Some(capture) => capture.grab().map(|frame| {
+                CachedDetector::new(OwnedMat::new(frame))
+                    .detect_minimap(None, border_threshold)
+                    .is_ok()
+            }),
+            None => {
+                detector.map(|detector| detector.detect_minimap(None, border_threshold).is_ok())
+            }
+        };
+        let result = match has_minimap {
+            Some(has_minimap) => CaptureTestResult {
+                mode: running.mode,
+                has_frame: true,
+                has_minimap,
+            },
+            None if now.duration_since(running.started) >= CAPTURE_TEST_TIMEOUT => {
+                CaptureTestResult {
+                    mode: running.mode,
+                    has_frame: false,
+                    has_minimap: false,
+                }
+            }
+            None => return,
+        };
+        self.running = None;
+        self.result = Some(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_tester_times_out_without_frame() {
+        let mut tester = CaptureTester::default();
+        let now = Instant::now();
+        tester.start(CaptureMode::BitBlt, None, now);
+
+        tester.update(None, 0, now);
+        assert!(tester.is_running());
+        assert_eq!(tester.result(), None);
+
+        tester.update(None, 0, now + CAPTURE_TEST_TIMEOUT);
+        assert!(!tester.is_running());
+        assert_eq!(
+            tester.result(),
+            Some(CaptureTestResult {
+                mode: CaptureMode::BitBlt,
+                has_frame: false,
+                has_minimap: false,
+            })
+        );
+    }
+
+    #[test]
+    fn suggested_window_matches_title() {
+        let names = vec![
+            "Discord".to_string(),
+            "MapleStory".to_string(),
+            "maplestory - 2".to_string(),
+        ];
+
+        assert_eq!(suggested_window(&names), Some(1));
+        assert_eq!(suggested_window(&names[..1]), None);
+    }
+}
@@ -0,0 +1,4 @@
+fn main() {
+    tonic_build::compile_protos("../backend/proto/input.proto").unwrap();
+    println!("cargo:rerun-if-changed=../backend/proto/input.proto");
+}
@@ -0,0 +1,492 @@
+//! A reference input server for the `Rpc` input method.
+//!
+//! The server implements the `KeyInput` gRPC service and sends the received keys and mouse
+//! actions to the game window using the same platform APIs as the bot default input method. It
+//! is meant to be run on the PC or VM the game is running on while the bot runs on another one.
+//!
+//! It implements protocol version 2: the bot sends heartbeats and the keys held by a bot that
+//! missed [`MAX_MISSED_HEARTBEATS`] heartbeats are released so that they are not left stuck when
+//! the connection drops. A reconnecting bot resumes its session and reconciles the held keys.
+//!
+//! Usage: `komari-input-server [address]` where `address` is either a port (e.g. `5001`) or
+//! `host:port` (e.g. `0.0.0.0:5001`). Defaults to [`DEFAULT_ADDRESS`].
+
+use std::{
+    collections::BTreeSet,
+    env, mem,
+    net::SocketAddr,
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use input::{
+    Coordinate, HeartbeatRequest, HeartbeatResponse, Key, KeyDownRequest, KeyDownResponse,
+    KeyInitRequest, KeyInitResponse, KeyRequest, KeyResponse, KeyUpRequest, KeyUpResponse,
+    MouseAction as RpcMouseAction, MouseRequest, MouseResponse,
+    key_input_server::{KeyInput, KeyInputServer},
+};
+use log::{info, warn};
+#[cfg(target_os = "linux")]
+use platforms::linux::{Handle, KeyInputKind, KeyKind, Keys, MouseAction};
+#[cfg(target_os = "macos")]
+use platforms::macos::{Handle, KeyInputKind, KeyKind, Keys, MouseAction};
+#[cfg(windows)]
+use platforms::windows::{Handle, KeyInputKind, KeyKind, Keys, MouseAction};
+use tonic::{Request, Response, Status, transport::Server};
+
+mod input {
+    tonic::include_proto!("input");
+}
+
+/// The address the server listens on if none is provided.
+const DEFAULT_ADDRESS: &str = "0.0.0.0:5001";
+
+/// The window class of the game window to send inputs to.
+const GAME_WINDOW_CLASS: &str = "MapleStoryClass";
+
+/// The protocol version of the server.
+const PROTOCOL_VERSION: u32 = 2;
+
+/// The interval the bot is asked to send heartbeats at.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The number of missed heartbeats before the keys held by the bot are released.
+const MAX_MISSED_HEARTBEATS: u32 = 3;
+
+/// An input command forwarded to the input thread.
+#[derive(Debug)]
+enum InputCommand {
+    Send(KeyKind, f32),
+    SendDown(KeyKind),
+    SendUp(KeyKind),
+    SendMouse(i32, i32, MouseAction),
+}
+
+/// The bot session and the keys it holds down.
+#[derive(Debug, Default)]
+struct Session {
+    id: String,
+    keys_down: BTreeSet<Key>,
+    /// The last heartbeat or [`None`] if the bot does not send heartbeats.
+    last_heartbeat: Option<Instant>,
+}
+
+impl Session {
+    /// Whether the bot holds keys but missed too many heartbeats.
+    fn missed_heartbeats(&self, now: Instant) -> bool {
+        !self.keys_down.is_empty()
+            && self.last_heartbeat.is_some_and(|last| {
+                now.duration_since(last) > HEARTBEAT_INTERVAL * MAX_MISSED_HEARTBEATS
+            })
+    }
+
+    fn keys_down(&self) -> Vec<i32> {
+        self.keys_down.iter().copied().map(i32::from).collect()
+    }
+
+    /// Releases all the held keys.
+    fn release_all(&mut self, sender: &Sender<InputCommand>) {
+        for key in mem::take(&mut self.keys_down) {
+            let _ = sender.send(InputCommand::SendUp(to_key_kind(key)));
+        }
+    }
+}
+
+/// The keys pressed by [`InputCommand::Send`] to release once their down duration has passed.
+///
+/// Releases are scheduled instead of slept on so that other inputs are not blocked meanwhile.
+#[derive(Debug, Default)]
+struct ScheduledReleases {
+    releases: Vec<(Instant, KeyKind)>,
+}
+
+impl ScheduledReleases {
+    fn schedule(&mut self, key: KeyKind, at: Instant) {
+        self.cancel(key);
+        self.releases.push((at, key));
+    }
+
+    /// Removes the scheduled release of `key` and returns whether there was one.
+    fn cancel(&mut self, key: KeyKind) -> bool {
+        let len = self.releases.len();
+        self.releases.retain(|(_, scheduled)| *scheduled != key);
+        self.releases.len() != len
+    }
+
+    /// The duration until the next release is due or [`None`] if there is none.
+    fn timeout(&self, now: Instant) -> Option<Duration> {
+        self.releases
+            .iter()
+            .map(|(at, _)| at.saturating_duration_since(now))
+            .min()
+    }
+
+    /// Takes the keys with a release due at `now`.
+    fn take_due(&mut self, now: Instant) -> Vec<KeyKind> {
+        let (due, pending) = mem::take(&mut self.releases)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(at, _)| *at <= now);
+        self.releases = pending;
+        due.into_iter().map(|(_, key)| key).collect()
+    }
+}
+
+/// The `KeyInput` service forwarding requests to the input thread.
+///
+/// [`Keys`] is not thread-safe so all inputs are sent from a single dedicated thread.
+#[derive(Debug)]
+struct KeyInputService {
+    sender: Sender<InputCommand>,
+    session: Arc<Mutex<Session>>,
+}
+
+impl KeyInputService {
+    fn forward(&self, command: InputCommand) -> Result<(), Status> {
+        self.sender
+            .send(command)
+            .map_err(|_| Status::unavailable("input thread has stopped"))
+    }
+}
+
+#[tonic::async_trait]
+impl KeyInput for KeyInputService {
+    async fn init(
+        &self,
+        request: Request<KeyInitRequest>,
+    ) -> Result<Response<KeyInitResponse>, Status> {
+        let request = request.into_inner();
+        let supports_heartbeat = request.protocol_version >= PROTOCOL_VERSION;
+        let mut session = self.session.lock().unwrap();
+        if supports_heartbeat && !request.session_id.is_empty() && request.session_id == session.id
+        {
+            info!(target: "input_server", "bot resumed session {}", session.id);
+        } else {
+            session.release_all(&self.sender);
+            session.id = new_session_id();
+            info!(target: "input_server", "bot connected with session {}", session.id);
+        }
+        session.last_heartbeat = supports_heartbeat.then(Instant::now);
+
+        // The bot-generated delay in `KeyRequest` is used instead of the seed
+        Ok(Response::new(KeyInitResponse {
+            mouse_coordinate: Coordinate::Relative.into(),
+            protocol_version: PROTOCOL_VERSION,
+            session_id: session.id.clone(),
+            heartbeat_interval_ms: HEARTBEAT_INTERVAL.as_millis() as u32,
+            keys_down: session.keys_down(),
+        }))
+    }
+
+    async fn send_mouse(
+        &self,
+        request: Request<MouseRequest>,
+    ) -> Result<Response<MouseResponse>, Status> {
+        let request = request.into_inner();
+        let action = match request.action() {
+            RpcMouseAction::Move => MouseAction::Move,
+            RpcMouseAction::Click => MouseAction::Click,
+            RpcMouseAction::ScrollDown => MouseAction::Scroll,
+        };
+        // Relative coordinates are already in the game window client coordinates as long as the
+        // game runs in the same resolution on both sides
+        self.forward(InputCommand::SendMouse(request.x, request.y, action))?;
+        Ok(Response::new(MouseResponse {}))
+    }
+
+    async fn send(&self, request: Request<KeyRequest>) -> Result<Response<KeyResponse>, Status> {
+        let request = request.into_inner();
+        self.forward(InputCommand::Send(
+            to_key_kind(request.key()),
+            request.down_ms,
+        ))?;
+        self.session
+            .lock()
+            .unwrap()
+            .keys_down
+            .remove(&request.key());
+        Ok(Response::new(KeyResponse {}))
+    }
+
+    async fn send_up(
+        &self,
+        request: Request<KeyUpRequest>,
+    ) -> Result<Response<KeyUpResponse>, Status> {
+        let key = request.into_inner().key();
+        self.forward(InputCommand::SendUp(to_key_kind(key)))?;
+        self.session.lock().unwrap().keys_down.remove(&key);
+        Ok(Response::new(KeyUpResponse {}))
+    }
+
+    async fn send_down(
+        &self,
+        request: Request<KeyDownRequest>,
+    ) -> Result<Response<KeyDownResponse>, Status> {
+        let key = request.into_inner().key();
+        self.forward(InputCommand::SendDown(to_key_kind(key)))?;
+        self.session.lock().unwrap().keys_down.insert(key);
+        Ok(Response::new(KeyDownResponse {}))
+    }
+
+    async fn heartbeat(
+        &self,
+        request: Request<HeartbeatRequest>,
+    ) -> Result<Response<HeartbeatResponse>, Status> {
+        let mut session = self.session.lock().unwrap();
+        if request.into_inner().session_id != session.id {
+            return Err(Status::not_found("unknown session"));
+        }
+        session.last_heartbeat = Some(Instant::now());
+        Ok(Response::new(HeartbeatResponse {
+            keys_down: session.keys_down(),
+        }))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let address = parse_address(env::args().nth(1).as_deref().unwrap_or(DEFAULT_ADDRESS))?;
+    let (sender, receiver) = mpsc::channel();
+    let session = Arc::new(Mutex::new(Session::default()));
+    spawn_input_thread(receiver);
+    spawn_heartbeat_monitor(session.clone(), sender.clone());
+
+    info!(target: "input_server", "listening on {address}");
+    Server::builder()
+        .add_service(KeyInputServer::new(KeyInputService { sender, session }))
+        .serve(address)
+        .await?;
+    Ok(())
+}
+
+/// Releases the keys held by the bot once it misses too many heartbeats.
+fn spawn_heartbeat_monitor(session: Arc<Mutex<Session>>, sender: Sender<InputCommand>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            interval.tick().await;
+            let mut session = session.lock().unwrap();
+            if session.missed_heartbeats(Instant::now()) {
+                warn!(target: "input_server", "bot missed heartbeats, releasing held keys");
+                session.release_all(&sender);
+            }
+        }
+    });
+}
+
+fn spawn_input_thread(receiver: Receiver<InputCommand>) {
+    thread::spawn(move || {
+        let keys = Keys::new(Handle::new(GAME_WINDOW_CLASS), KeyInputKind::Fixed);
+        let mut releases = ScheduledReleases::default();
+        loop {
+            let command = match releases.timeout(Instant::now()) {
+                Some(timeout) => match receiver.recv_timeout(timeout) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                },
+                None => match receiver.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => break,
+                },
+            };
+            for key in releases.take_due(Instant::now()) {
+                if let Err(err) = keys.send_up(key) {
+                    warn!(target: "input_server", "failed to release key {err:?}");
+                }
+            }
+            let Some(command) = command else {
+                continue;
+            };
+
+            let result = match command {
+                InputCommand::Send(key, down_ms) => {
+                    // Releases first so that pressing again is not ignored as a held key
+                    let released = if releases.cancel(key) {
+                        keys.send_up(key)
+                    } else {
+                        Ok(())
+                    };
+                    released.and_then(|_| keys.send_down(key)).map(|_| {
+                        let down = Duration::from_secs_f32(down_ms.max(0.0) / 1000.0);
+                        releases.schedule(key, Instant::now() + down);
+                    })
+                }
+                InputCommand::SendDown(key) => {
+                    releases.cancel(key);
+                    keys.send_down(key)
+                }
+                InputCommand::SendUp(key) => {
+                    releases.cancel(key);
+                    keys.send_up(key)
+                }
+                InputCommand::SendMouse(x, y, action) => keys.send_mouse(x, y, action),
+            };
+            if let Err(err) = result {
+                warn!(target: "input_server", "failed to send input {err:?}");
+            }
+        }
+    });
+}
+
+fn new_session_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("{nanos:x}")
+}
+
+/// Parses `address` in the form of `port` or `host:port`.
+fn parse_address(address: &str) -> Result<SocketAddr> {
+    let address = address.trim();
+    if let Ok(port) = address.parse::<u16>() {
+        return Ok(SocketAddr::from(([0, 0, 0, 0], port)));
+    }
+    address
+        .parse::<SocketAddr>()
+        .with_context(|| format!("invalid address {address}"))
+}
+
+#[inline]
+fn to_key_kind(key: Key) -> KeyKind {
+    match key {
+        Key::A => KeyKind::A,
+        Key::B => KeyKind::B,
+        Key::C => KeyKind::C,
+        Key::D => KeyKind::D,
+        Key::E => KeyKind::E,
+        Key::F => KeyKind::F,
+        Key::G => KeyKind::G,
+        Key::H => KeyKind::H,
+        Key::I => KeyKind::I,
+        Key::J => KeyKind::J,
+        Key::K => KeyKind::K,
+        Key::L => KeyKind::L,
+        Key::M => KeyKind::M,
+        Key::N => KeyKind::N,
+        Key::O => KeyKind::O,
+        Key::P => KeyKind::P,
+        Key::Q => KeyKind::Q,
+        Key::R => KeyKind::R,
+        Key::S => KeyKind::S,
+        Key::T => KeyKind::T,
+        Key::U => KeyKind::U,
+        Key::V => KeyKind::V,
+        Key::W => KeyKind::W,
+        Key::X => KeyKind::X,
+        Key::Y => KeyKind::Y,
+        Key::Z => KeyKind::Z,
+        Key::Zero => KeyKind::Zero,
+        Key::One => KeyKind::One,
+        Key::Two => KeyKind::Two,
+        Key::Three => KeyKind::Three,
+        Key::Four => KeyKind::Four,
+        Key::Five => KeyKind::Five,
+        Key::Six => KeyKind::Six,
+        Key::Seven => KeyKind::Seven,
+        Key::Eight => KeyKind::Eight,
+        Key::Nine => KeyKind::Nine,
+        Key::F1 => KeyKind::F1,
+        Key::F2 => KeyKind::F2,
+        Key::F3 => KeyKind::F3,
+        Key::F4 => KeyKind::F4,
+        Key::F5 => KeyKind::F5,
+        Key::F6 => KeyKind::F6,
+        Key::F7 => KeyKind::F7,
+        Key::F8 => KeyKind::F8,
+        Key::F9 => KeyKind::F9,
+        Key::F10 => KeyKind::F10,
+        Key::F11 => KeyKind::F11,
+        Key::F12 => KeyKind::F12,
+        Key::Up => KeyKind::Up,
+        Key::Down => KeyKind::Down,
+        Key::Left => KeyKind::Left,
+        Key::Right => KeyKind::Right,
+        Key::Home => KeyKind::Home,
+        Key::End => KeyKind::End,
+        Key::PageUp => KeyKind::PageUp,
+        Key::PageDown => KeyKind::PageDown,
+        Key::Insert => KeyKind::Insert,
+        Key::Delete => KeyKind::Delete,
+        Key::Ctrl => KeyKind::Ctrl,
+        Key::Enter => KeyKind::Enter,
+        Key::Space => KeyKind::Space,
+        Key::Tilde => KeyKind::Tilde,
+        Key::Quote => KeyKind::Quote,
+        Key::Semicolon => KeyKind::Semicolon,
+        Key::Comma => KeyKind::Comma,
+        Key::Period => KeyKind::Period,
+        Key::Slash => KeyKind::Slash,
+        Key::Esc => KeyKind::Esc,
+        Key::Shift => KeyKind::Shift,
+        Key::Alt => KeyKind::Alt,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::SocketAddr,
+        time::{Duration, Instant},
+    };
+
+    use super::{HEARTBEAT_INTERVAL, Key, KeyKind, ScheduledReleases, Session, parse_address};
+
+    #[test]
+    fn parse_address_port_or_host_port() {
+        assert_eq!(
+            parse_address("5001").unwrap(),
+            SocketAddr::from(([0, 0, 0, 0], 5001))
+        );
+        assert_eq!(
+            parse_address("127.0.0.1:5002").unwrap(),
+            SocketAddr::from(([127, 0, 0, 1], 5002))
+        );
+        assert!(parse_address("localhost").is_err());
+    }
+
+    #[test]
+    fn session_missed_heartbeats_only_while_holding_keys() {
+        let now = Instant::now();
+        let mut session = Session {
+            last_heartbeat: Some(now),
+            ..Session::default()
+        };
+        let later = now + HEARTBEAT_INTERVAL * 3 + Duration::from_millis(1);
+        assert!(!session.missed_heartbeats(later));
+
+        session.keys_down.insert(Key::Left);
+        assert!(!session.missed_heartbeats(now + HEARTBEAT_INTERVAL));
+        assert!(session.missed_heartbeats(later));
+
+        session.last_heartbeat = None;
+        assert!(!session.missed_heartbeats(later));
+    }
+
+    #[test]
+    fn scheduled_releases_take_due_keys_in_order() {
+        let now = Instant::now();
+        let mut releases = ScheduledReleases::default();
+        assert_eq!(releases.timeout(now), None);
+
+        releases.schedule(KeyKind::A, now + Duration::from_millis(100));
+        releases.schedule(KeyKind::B, now + Duration::from_millis(50));
+        assert_eq!(releases.timeout(now), Some(Duration::from_millis(50)));
+        assert!(releases.take_due(now).is_empty());
+        assert_eq!(
+            releases.take_due(now + Duration::from_millis(50)),
+            vec![KeyKind::B]
+        );
+
+        assert!(releases.cancel(KeyKind::A));
+        assert!(!releases.cancel(KeyKind::A));
+        assert_eq!(releases.timeout(now), None);
+    }
+}
@@ -5,3 +5,6 @@ pub mod windows;
 
 #[cfg(target_os = "macos")]
 pub mod macos;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
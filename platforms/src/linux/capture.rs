@@ -0,0 +1,176 @@
+use std::{mem, ptr, slice};
+
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        shm::{self, ConnectionExt as _},
+        xproto::ImageFormat,
+    },
+};
+
+use super::{
+    Frame, HandleCell,
+    error::Error,
+    handle::{Handle, window_rect, x11},
+};
+
+/// A shared memory segment attached to both this process and the X server.
+#[derive(Debug)]
+struct SharedMemory {
+    id: i32,
+    seg: shm::Seg,
+    buffer: *mut u8,
+    width: i32,
+    height: i32,
+}
+
+impl SharedMemory {
+    fn new(width: i32, height: i32) -> Result<Self, Error> {
+        let x11 = x11()?;
+        let size = (width * height * 4) as usize;
+        let id = unsafe { libc::shmget(libc::IPC_PRIVATE, size, libc::IPC_CREAT | 0o600) };
+        if id < 0 {
+            return Err(Error::x11("shmget failed"));
+        }
+        let buffer = unsafe { libc::shmat(id, ptr::null(), 0) };
+        if buffer as isize == -1 {
+            unsafe { libc::shmctl(id, libc::IPC_RMID, ptr::null_mut()) };
+            return Err(Error::x11("shmat failed"));
+        }
+        let memory = Self {
+            id,
+            seg: x11.conn.generate_id().map_err(Error::x11)?,
+            buffer: buffer.cast(),
+            width,
+            height,
+        };
+        x11.conn
+            .shm_attach(memory.seg, id as u32, false)
+            .map_err(Error::x11)?
+            .check()
+            .map_err(Error::x11)?;
+
+        Ok(memory)
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        if let Ok(x11) = x11() {
+            let _ = x11.conn.shm_detach(self.seg);
+            let _ = x11.conn.flush();
+        }
+        unsafe {
+            libc::shmdt(self.buffer.cast());
+            libc::shmctl(self.id, libc::IPC_RMID, ptr::null_mut());
+        }
+    }
+}
+
+/// An X11 shared memory capture of a window or an area of the root window.
+///
+/// Named after the Windows counterpart so that the backend can use the same capture modes.
+#[derive(Debug)]
+pub struct BitBltCapture {
+    handle: HandleCell,
+    area: Option<(i32, i32, i32, i32)>,
+    memory: Option<SharedMemory>,
+}
+
+impl BitBltCapture {
+    /// Creates a new capture of `handle` window client area.
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle: HandleCell::new(handle),
+            area: None,
+            memory: None,
+        }
+    }
+
+    /// Creates a new capture of the area `(x, y, width, height)` on the root window.
+    ///
+    /// Any window on top of that area is also visible in the captured frame.
+    pub fn new_area(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            handle: HandleCell::new(Handle::new_root()),
+            area: Some((x, y, width, height)),
+            memory: None,
+        }
+    }
+
+    #[inline]
+    pub fn handle(&self) -> Handle {
+        self.handle.handle()
+    }
+
+    /// The root coordinate of the captured area top-left if created with [`Self::new_area`].
+    #[inline]
+    pub fn area_origin(&self) -> Option<(i32, i32)> {
+        self.area.map(|(x, y, _, _)| (x, y))
+    }
+
+    #[inline]
+    pub fn grab(&mut self) -> Result<Frame, Error> {
+        self.grab_into(&mut Vec::new())
+    }
+
+    /// Grabs a frame reusing the allocation of `data` for its pixels.
+    ///
+    /// `data` is taken into the [`Frame`] on success and left as is otherwise.
+    pub fn grab_into(&mut self, data: &mut Vec<u8>) -> Result<Frame, Error> {
+        let x11 = x11()?;
+        let window = self.handle.as_inner().ok_or(Error::WindowNotFound)?;
+        let (x, y, width, height) = match self.area {
+            Some((x, y, width, height)) => (x, y, width, height),
+            None => {
+                let (_, _, width, height) = window_rect(window)?;
+                (0, 0, width, height)
+            }
+        };
+        if width <= 0 || height <= 0 {
+            return Err(Error::InvalidWindowSize);
+        }
+
+        if self.memory.is_none() {
+            self.memory = Some(SharedMemory::new(width, height)?);
+        }
+        let memory = self.memory.as_ref().unwrap();
+        if width != memory.width || height != memory.height {
+            self.memory = None;
+            return Err(Error::InvalidWindowSize);
+        }
+
+        x11.conn
+            .shm_get_image(
+                window,
+                x as i16,
+                y as i16,
+                width as u16,
+                height as u16,
+                !0,
+                ImageFormat::Z_PIXMAP.into(),
+                memory.seg,
+                0,
+            )
+            .map_err(Error::x11)?
+            .reply()
+            .map_err(|_| Error::FrameNotAvailable)?;
+        // SAFETY: The segment was allocated with `width * height * 4` bytes and the X server has
+        // finished writing to it once the reply is received
+        data.clear();
+        data.extend_from_slice(unsafe {
+            slice::from_raw_parts(memory.buffer, (width * height * 4) as usize)
+        });
+
+        Ok(Frame {
+            width,
+            height,
+            data: mem::take(data),
+        })
+    }
+}
+
+/// Captures the whole root window spanning all monitors.
+pub fn capture_desktop() -> Result<Frame, Error> {
+    BitBltCapture::new(Handle::new_root()).grab()
+}
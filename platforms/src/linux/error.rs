@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+#[derive(Error, PartialEq, Clone, Debug)]
+pub enum Error {
+    #[error("the current window size is invalid")]
+    InvalidWindowSize,
+    #[error("key or click was not sent due to the window not focused or other error")]
+    KeyNotSent,
+    #[error("window matching provided class and title cannot be found")]
+    WindowNotFound,
+    #[error("capture frame is not available")]
+    FrameNotAvailable,
+    #[error("key not found")]
+    KeyNotFound,
+    #[error("failed to launch process {0}")]
+    LaunchFailed(String),
+    #[error("X11 error {0}")]
+    X11(String),
+    #[error("uinput error {0}")]
+    Uinput(String),
+}
+
+impl Error {
+    #[inline]
+    pub(crate) fn x11(error: impl ToString) -> Error {
+        Error::X11(error.to_string())
+    }
+}
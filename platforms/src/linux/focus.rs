@@ -0,0 +1,88 @@
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{ClientMessageEvent, ConnectionExt, EventMask},
+};
+
+use super::{
+    Error, Handle, HandleCell,
+    handle::{active_window, x11},
+};
+
+/// The source indication of `_NET_ACTIVE_WINDOW` telling the window manager that the request is
+/// from a pager so that it is not ignored by focus stealing prevention.
+const SOURCE_PAGER: u32 = 2;
+
+/// A change in the focus state of the tracked window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FocusChange {
+    Gained,
+    Lost,
+}
+
+/// Tracks whether the window of [`Handle`] is the focused foreground window.
+#[derive(Debug)]
+pub struct FocusMonitor {
+    handle: HandleCell,
+    focused: bool,
+}
+
+impl FocusMonitor {
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle: HandleCell::new(handle),
+            focused: true,
+        }
+    }
+
+    /// Replaces the tracked window with `handle`.
+    pub fn set_handle(&mut self, handle: Handle) {
+        self.handle = HandleCell::new(handle);
+        self.focused = true;
+    }
+
+    /// Whether the window was focused as of the last [`Self::poll`].
+    #[inline]
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Polls the current focus state and returns the change since the last poll.
+    pub fn poll(&mut self) -> Option<FocusChange> {
+        let focused = self
+            .handle
+            .as_inner()
+            .is_some_and(|window| active_window() == Some(window));
+        let change = match (self.focused, focused) {
+            (true, false) => Some(FocusChange::Lost),
+            (false, true) => Some(FocusChange::Gained),
+            _ => None,
+        };
+        self.focused = focused;
+        change
+    }
+
+    /// Attempts to bring the window back to the foreground.
+    ///
+    /// The request is sent to the window manager through `_NET_ACTIVE_WINDOW` which may still
+    /// decide to ignore it.
+    pub fn refocus(&self) -> Result<(), Error> {
+        let window = self.handle.as_inner().ok_or(Error::WindowNotFound)?;
+        let x11 = x11()?;
+        let event = ClientMessageEvent::new(
+            32,
+            window,
+            x11.atoms._NET_ACTIVE_WINDOW,
+            [SOURCE_PAGER, x11rb::CURRENT_TIME, 0, 0, 0],
+        );
+        x11.conn
+            .send_event(
+                false,
+                x11.root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )
+            .map_err(Error::x11)?;
+        x11.conn.flush().map_err(Error::x11)?;
+        Ok(())
+    }
+}
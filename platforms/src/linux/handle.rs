@@ -0,0 +1,293 @@
+use std::{cell::Cell, env, fs, sync::LazyLock};
+
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{AtomEnum, ConfigureWindowAux, ConnectionExt, Window},
+    rust_connection::RustConnection,
+};
+
+use super::Error;
+
+x11rb::atom_manager! {
+    pub(crate) Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_CLIENT_LIST,
+        _NET_CLOSE_WINDOW,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+        UTF8_STRING,
+    }
+}
+
+/// The shared X11 connection.
+pub(crate) struct X11 {
+    pub conn: RustConnection,
+    pub root: Window,
+    pub root_width: u16,
+    pub root_height: u16,
+    pub atoms: Atoms,
+}
+
+static X11_CONNECTION: LazyLock<Option<X11>> = LazyLock::new(|| {
+    let (conn, screen) = RustConnection::connect(None)
+        .inspect_err(|err| log::error!("failed to connect to X11 display {err}"))
+        .ok()?;
+    let screen = &conn.setup().roots[screen];
+    let (root, root_width, root_height) =
+        (screen.root, screen.width_in_pixels, screen.height_in_pixels);
+    let atoms = Atoms::new(&conn).ok()?.reply().ok()?;
+
+    Some(X11 {
+        conn,
+        root,
+        root_width,
+        root_height,
+        atoms,
+    })
+});
+
+#[inline]
+pub(crate) fn x11() -> Result<&'static X11, Error> {
+    X11_CONNECTION.as_ref().ok_or_else(|| {
+        // Only X11 capture is supported so a Wayland session must provide XWayland
+        let is_wayland_only =
+            env::var_os("DISPLAY").is_none() && env::var_os("WAYLAND_DISPLAY").is_some();
+        let message = if is_wayland_only {
+            "display is not available, XWayland is required on Wayland"
+        } else {
+            "display is not available"
+        };
+        Error::X11(message.to_string())
+    })
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct HandleCell {
+    handle: Handle,
+    inner: Cell<Option<Window>>,
+}
+
+impl HandleCell {
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle,
+            inner: Cell::new(None),
+        }
+    }
+
+    #[inline]
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    #[inline]
+    pub fn as_inner(&self) -> Option<Window> {
+        match self.handle.kind {
+            HandleKind::Fixed(_) | HandleKind::Root => self.handle.query_handle(),
+            HandleKind::Dynamic(class) => {
+                if self.inner.get().is_none() {
+                    self.inner.set(self.handle.query_handle());
+                }
+
+                let window = self.inner.get()?;
+                if is_class_matched(window, class) {
+                    Some(window)
+                } else {
+                    self.inner.set(None);
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum HandleKind {
+    Fixed(Window),
+    Dynamic(&'static str),
+    /// The root window used for capturing an area of the screen.
+    Root,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Handle {
+    kind: HandleKind,
+}
+
+impl Handle {
+    pub fn new(class: &'static str) -> Self {
+        Self {
+            kind: HandleKind::Dynamic(class),
+        }
+    }
+
+    pub(crate) fn new_fixed(window: Window) -> Self {
+        Self {
+            kind: HandleKind::Fixed(window),
+        }
+    }
+
+    pub(crate) fn new_root() -> Self {
+        Self {
+            kind: HandleKind::Root,
+        }
+    }
+
+    pub(crate) fn query_handle(&self) -> Option<Window> {
+        match self.kind {
+            HandleKind::Fixed(window) => Some(window),
+            HandleKind::Dynamic(class) => client_windows()
+                .into_iter()
+                .find(|window| is_class_matched(*window, class)),
+            HandleKind::Root => x11().ok().map(|x11| x11.root),
+        }
+    }
+}
+
+pub fn query_capture_handles() -> Vec<(String, Handle)> {
+    client_windows()
+        .into_iter()
+        .filter_map(|window| {
+            let title = window_title(window)?;
+            (!title.is_empty()).then_some((title, Handle::new_fixed(window)))
+        })
+        .collect()
+}
+
+/// Queries the name of the process owning `handle` from `/proc/<pid>/comm`.
+///
+/// Under Wine, this is the executable file name (e.g. `MapleStory.exe`).
+pub fn query_process_name(handle: Handle) -> Option<String> {
+    let pid = window_pid(handle.query_handle()?)?;
+    let name = fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Moves `handle` window to `(x, y)` on the root window and resizes it to `width` x `height`.
+///
+/// The window manager may adjust the requested geometry (e.g. to account for decorations).
+pub fn arrange_window(
+    handle: Handle,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+) -> Result<(), Error> {
+    let window = handle.query_handle().ok_or(Error::WindowNotFound)?;
+    if width <= 0 || height <= 0 {
+        return Err(Error::InvalidWindowSize);
+    }
+    let x11 = x11()?;
+    let values = ConfigureWindowAux::new()
+        .x(x)
+        .y(y)
+        .width(width as u32)
+        .height(height as u32);
+    x11.conn
+        .configure_window(window, &values)
+        .map_err(Error::x11)?;
+    x11.conn.flush().map_err(Error::x11)?;
+    Ok(())
+}
+
+/// Retrieves the root coordinate and size `(x, y, width, height)` of `window` client area.
+pub(crate) fn window_rect(window: Window) -> Result<(i32, i32, i32, i32), Error> {
+    let x11 = x11()?;
+    let geometry = x11
+        .conn
+        .get_geometry(window)
+        .map_err(Error::x11)?
+        .reply()
+        .map_err(|_| Error::WindowNotFound)?;
+    let translated = x11
+        .conn
+        .translate_coordinates(window, x11.root, 0, 0)
+        .map_err(Error::x11)?
+        .reply()
+        .map_err(|_| Error::WindowNotFound)?;
+
+    Ok((
+        translated.dst_x as i32,
+        translated.dst_y as i32,
+        geometry.width as i32,
+        geometry.height as i32,
+    ))
+}
+
+/// Retrieves the currently focused top-level window.
+pub(crate) fn active_window() -> Option<Window> {
+    let x11 = x11().ok()?;
+    property_u32s(
+        x11.root,
+        x11.atoms._NET_ACTIVE_WINDOW,
+        AtomEnum::WINDOW.into(),
+    )
+    .into_iter()
+    .next()
+    .filter(|window| *window != 0)
+}
+
+/// Retrieves the process id that owns `window`.
+pub(crate) fn window_pid(window: Window) -> Option<u32> {
+    let x11 = x11().ok()?;
+    property_u32s(window, x11.atoms._NET_WM_PID, AtomEnum::CARDINAL.into())
+        .into_iter()
+        .next()
+}
+
+fn client_windows() -> Vec<Window> {
+    let Ok(x11) = x11() else {
+        return vec![];
+    };
+    property_u32s(
+        x11.root,
+        x11.atoms._NET_CLIENT_LIST,
+        AtomEnum::WINDOW.into(),
+    )
+}
+
+fn window_title(window: Window) -> Option<String> {
+    let x11 = x11().ok()?;
+    let title = property_bytes(window, x11.atoms._NET_WM_NAME, x11.atoms.UTF8_STRING)
+        .or_else(|| property_bytes(window, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into()))?;
+    Some(String::from_utf8_lossy(&title).into_owned())
+}
+
+/// Whether `window` `WM_CLASS` matches `class`.
+///
+/// Under Wine, `WM_CLASS` is the executable name (e.g. `maplestory.exe`) instead of the Windows
+/// class name (e.g. `MapleStoryClass`) so the `Class` suffix is ignored and matched loosely.
+fn is_class_matched(window: Window, class: &'static str) -> bool {
+    let Some(window_class) =
+        property_bytes(window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())
+    else {
+        return false;
+    };
+    let window_class = String::from_utf8_lossy(&window_class).to_lowercase();
+    let class = class.trim_end_matches("Class").to_lowercase();
+    !class.is_empty() && window_class.split('\0').any(|name| name.contains(&class))
+}
+
+fn property_u32s(window: Window, property: u32, type_: u32) -> Vec<u32> {
+    let Ok(x11) = x11() else {
+        return vec![];
+    };
+    x11.conn
+        .get_property(false, window, property, type_, 0, u32::MAX)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .and_then(|reply| reply.value32().map(|values| values.collect()))
+        .unwrap_or_default()
+}
+
+fn property_bytes(window: Window, property: u32, type_: u32) -> Option<Vec<u8>> {
+    let x11 = x11().ok()?;
+    let reply = x11
+        .conn
+        .get_property(false, window, property, type_, 0, u32::MAX)
+        .ok()?
+        .reply()
+        .ok()?;
+    (reply.format == 8 && !reply.value.is_empty()).then_some(reply.value)
+}
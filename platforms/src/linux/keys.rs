@@ -0,0 +1,570 @@
+use std::{
+    cell::RefCell,
+    sync::{LazyLock, Mutex},
+    thread,
+    time::Duration,
+};
+
+use bit_vec::BitVec;
+use evdev::{
+    AbsInfo, AbsoluteAxisCode, AttributeSet, EventType, InputEvent, KeyCode, RelativeAxisCode,
+    UinputAbsSetup, uinput::VirtualDevice,
+};
+use tokio::sync::broadcast::{self, Receiver, Sender};
+use x11rb::protocol::xproto::{ConnectionExt, Window};
+
+use super::{
+    HandleCell,
+    error::Error,
+    handle::{Handle, active_window, window_pid, window_rect, x11},
+};
+
+/// The offset between an X11 keycode and an evdev keycode.
+const X11_KEYCODE_OFFSET: u16 = 8;
+
+static KEY_CHANNEL: LazyLock<Sender<KeyKind>> = LazyLock::new(|| broadcast::channel(1).0);
+static DEVICE: LazyLock<Option<Mutex<VirtualDevice>>> = LazyLock::new(|| {
+    create_device()
+        .inspect_err(|err| log::error!("failed to create uinput device {err}"))
+        .ok()
+        .map(Mutex::new)
+});
+/// Keys currently held down by [`DEVICE`] so that [`run_key_loop`] can ignore them.
+static INJECTED_KEYS: LazyLock<Mutex<BitVec>> =
+    LazyLock::new(|| Mutex::new(BitVec::from_elem(256, false)));
+
+/// Polls the X11 keymap and broadcasts keys released by the user.
+///
+/// Unlike Windows, there is no global keyboard hook without root so the keymap is polled instead.
+pub(crate) fn run_key_loop() {
+    let Ok(x11) = x11() else {
+        return;
+    };
+    let mut previous = [0u8; 32];
+
+    loop {
+        thread::sleep(Duration::from_millis(10));
+        let Some(keymap) = x11
+            .conn
+            .query_keymap()
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        else {
+            continue;
+        };
+
+        for keycode in 0..256usize {
+            let was_down = previous[keycode / 8] & (1 << (keycode % 8)) != 0;
+            let is_down = keymap.keys[keycode / 8] & (1 << (keycode % 8)) != 0;
+            if !was_down || is_down || keycode < X11_KEYCODE_OFFSET as usize {
+                continue;
+            }
+
+            let code = keycode as u16 - X11_KEYCODE_OFFSET;
+            let mut injected = INJECTED_KEYS.lock().unwrap();
+            if injected.get(code as usize).unwrap_or_default() {
+                injected.set(code as usize, false);
+                continue;
+            }
+            if let Ok(key) = KeyKind::try_from(KeyCode(code)) {
+                let _ = KEY_CHANNEL.send(key);
+            }
+        }
+        previous = keymap.keys;
+    }
+}
+
+#[derive(Debug)]
+pub struct ConvertedCoordinates {
+    pub width: i32,
+    pub height: i32,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug)]
+pub struct KeyReceiver {
+    handle: HandleCell,
+    key_input_kind: KeyInputKind,
+    rx: Receiver<KeyKind>,
+}
+
+impl KeyReceiver {
+    pub fn new(handle: Handle, key_input_kind: KeyInputKind) -> Self {
+        Self {
+            handle: HandleCell::new(handle),
+            key_input_kind,
+            rx: KEY_CHANNEL.subscribe(),
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<KeyKind> {
+        self.rx
+            .try_recv()
+            .ok()
+            .and_then(|key| self.can_process_key().then_some(key))
+    }
+
+    fn can_process_key(&self) -> bool {
+        if active_window().and_then(window_pid) == Some(std::process::id()) {
+            return true;
+        }
+        self.handle
+            .as_inner()
+            .map(|window| is_foreground(window, self.key_input_kind))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum KeyInputKind {
+    /// Sends input only if [`Keys::handle`] is in the foreground and focused
+    Fixed,
+    /// Sends input only if the foreground window is not [`Keys::handle`] and is focused
+    ///
+    /// X11 does not expose the window stacking order relative to a capture area so unlike
+    /// Windows, the foreground window is not checked to be on top of [`Keys::handle`].
+    Foreground,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keys {
+    handle: HandleCell,
+    key_input_kind: KeyInputKind,
+    key_down: RefCell<BitVec>,
+}
+
+#[derive(Debug)]
+pub enum MouseAction {
+    Move,
+    Click,
+    Scroll,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Default, Hash, Debug)]
+pub enum KeyKind {
+    #[default]
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Zero,
+    One,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    Ctrl,
+    Enter,
+    Space,
+    Tilde,
+    Quote,
+    Semicolon,
+    Comma,
+    Period,
+    Slash,
+    Esc,
+    Shift,
+    Alt,
+}
+
+impl Keys {
+    pub fn new(handle: Handle, kind: KeyInputKind) -> Self {
+        Self {
+            handle: HandleCell::new(handle),
+            key_input_kind: kind,
+            key_down: RefCell::new(BitVec::from_elem(256, false)),
+        }
+    }
+
+    pub fn send(&self, kind: KeyKind) -> Result<(), Error> {
+        self.send_down(kind)?;
+        self.send_up(kind)?;
+        Ok(())
+    }
+
+    pub fn send_mouse(&self, x: i32, y: i32, action: MouseAction) -> Result<(), Error> {
+        let mut window = self.get_handle()?;
+        if !is_foreground(window, self.key_input_kind) {
+            return Err(Error::WindowNotFound);
+        }
+        if matches!(self.key_input_kind, KeyInputKind::Foreground) {
+            window = active_window().ok_or(Error::WindowNotFound)?;
+        }
+
+        let (window_x, window_y, _, _) = window_rect(window)?;
+        let (x, y) = (window_x + x, window_y + y);
+        let events = [
+            InputEvent::new(EventType::ABSOLUTE.0, AbsoluteAxisCode::ABS_X.0, x),
+            InputEvent::new(EventType::ABSOLUTE.0, AbsoluteAxisCode::ABS_Y.0, y),
+        ];
+
+        match action {
+            MouseAction::Move => emit(&events),
+            MouseAction::Click => {
+                emit(&events)?;
+                emit(&[InputEvent::new(EventType::KEY.0, KeyCode::BTN_LEFT.0, 1)])?;
+                thread::sleep(Duration::from_millis(80));
+                emit(&[InputEvent::new(EventType::KEY.0, KeyCode::BTN_LEFT.0, 0)])
+            }
+            MouseAction::Scroll => {
+                emit(&events)?;
+                emit(&[InputEvent::new(
+                    EventType::RELATIVE.0,
+                    RelativeAxisCode::REL_WHEEL.0,
+                    -3,
+                )])
+            }
+        }
+    }
+
+    pub fn send_up(&self, kind: KeyKind) -> Result<(), Error> {
+        self.send_input(kind, false)
+    }
+
+    pub fn send_down(&self, kind: KeyKind) -> Result<(), Error> {
+        self.send_input(kind, true)
+    }
+
+    #[inline]
+    fn send_input(&self, kind: KeyKind, is_down: bool) -> Result<(), Error> {
+        let window = self.get_handle()?;
+        if is_down && !is_foreground(window, self.key_input_kind) {
+            return Err(Error::KeyNotSent);
+        }
+        let code = KeyCode::from(kind).0 as usize;
+        let mut key_down = self.key_down.borrow_mut();
+        let was_key_down = key_down.get(code).unwrap_or_default();
+        match (is_down, was_key_down) {
+            (true, true) | (false, false) => return Err(Error::KeyNotSent),
+            _ => {
+                key_down.set(code, is_down);
+            }
+        }
+        if is_down {
+            INJECTED_KEYS.lock().unwrap().set(code, true);
+        }
+        emit(&[InputEvent::new(
+            EventType::KEY.0,
+            code as u16,
+            is_down as i32,
+        )])
+    }
+
+    #[inline]
+    fn get_handle(&self) -> Result<Window, Error> {
+        self.handle.as_inner().ok_or(Error::WindowNotFound)
+    }
+}
+
+impl TryFrom<KeyCode> for KeyKind {
+    type Error = Error;
+
+    fn try_from(value: KeyCode) -> Result<Self, Error> {
+        Ok(match value {
+            KeyCode::KEY_A => KeyKind::A,
+            KeyCode::KEY_B => KeyKind::B,
+            KeyCode::KEY_C => KeyKind::C,
+            KeyCode::KEY_D => KeyKind::D,
+            KeyCode::KEY_E => KeyKind::E,
+            KeyCode::KEY_F => KeyKind::F,
+            KeyCode::KEY_G => KeyKind::G,
+            KeyCode::KEY_H => KeyKind::H,
+            KeyCode::KEY_I => KeyKind::I,
+            KeyCode::KEY_J => KeyKind::J,
+            KeyCode::KEY_K => KeyKind::K,
+            KeyCode::KEY_L => KeyKind::L,
+            KeyCode::KEY_M => KeyKind::M,
+            KeyCode::KEY_N => KeyKind::N,
+            KeyCode::KEY_O => KeyKind::O,
+            KeyCode::KEY_P => KeyKind::P,
+            KeyCode::KEY_Q => KeyKind::Q,
+            KeyCode::KEY_R => KeyKind::R,
+            KeyCode::KEY_S => KeyKind::S,
+            KeyCode::KEY_T => KeyKind::T,
+            KeyCode::KEY_U => KeyKind::U,
+            KeyCode::KEY_V => KeyKind::V,
+            KeyCode::KEY_W => KeyKind::W,
+            KeyCode::KEY_X => KeyKind::X,
+            KeyCode::KEY_Y => KeyKind::Y,
+            KeyCode::KEY_Z => KeyKind::Z,
+            KeyCode::KEY_0 => KeyKind::Zero,
+            KeyCode::KEY_1 => KeyKind::One,
+            KeyCode::KEY_2 => KeyKind::Two,
+            KeyCode::KEY_3 => KeyKind::Three,
+            KeyCode::KEY_4 => KeyKind::Four,
+            KeyCode::KEY_5 => KeyKind::Five,
+            KeyCode::KEY_6 => KeyKind::Six,
+            KeyCode::KEY_7 => KeyKind::Seven,
+            KeyCode::KEY_8 => KeyKind::Eight,
+            KeyCode::KEY_9 => KeyKind::Nine,
+            KeyCode::KEY_F1 => KeyKind::F1,
+            KeyCode::KEY_F2 => KeyKind::F2,
+            KeyCode::KEY_F3 => KeyKind::F3,
+            KeyCode::KEY_F4 => KeyKind::F4,
+            KeyCode::KEY_F5 => KeyKind::F5,
+            KeyCode::KEY_F6 => KeyKind::F6,
+            KeyCode::KEY_F7 => KeyKind::F7,
+            KeyCode::KEY_F8 => KeyKind::F8,
+            KeyCode::KEY_F9 => KeyKind::F9,
+            KeyCode::KEY_F10 => KeyKind::F10,
+            KeyCode::KEY_F11 => KeyKind::F11,
+            KeyCode::KEY_F12 => KeyKind::F12,
+            KeyCode::KEY_UP => KeyKind::Up,
+            KeyCode::KEY_DOWN => KeyKind::Down,
+            KeyCode::KEY_LEFT => KeyKind::Left,
+            KeyCode::KEY_RIGHT => KeyKind::Right,
+            KeyCode::KEY_HOME => KeyKind::Home,
+            KeyCode::KEY_END => KeyKind::End,
+            KeyCode::KEY_PAGEUP => KeyKind::PageUp,
+            KeyCode::KEY_PAGEDOWN => KeyKind::PageDown,
+            KeyCode::KEY_INSERT => KeyKind::Insert,
+            KeyCode::KEY_DELETE => KeyKind::Delete,
+            KeyCode::KEY_LEFTCTRL => KeyKind::Ctrl,
+            KeyCode::KEY_ENTER => KeyKind::Enter,
+            KeyCode::KEY_SPACE => KeyKind::Space,
+            KeyCode::KEY_GRAVE => KeyKind::Tilde,
+            KeyCode::KEY_APOSTROPHE => KeyKind::Quote,
+            KeyCode::KEY_SEMICOLON => KeyKind::Semicolon,
+            KeyCode::KEY_COMMA => KeyKind::Comma,
+            KeyCode::KEY_DOT => KeyKind::Period,
+            KeyCode::KEY_SLASH => KeyKind::Slash,
+            KeyCode::KEY_ESC => KeyKind::Esc,
+            KeyCode::KEY_LEFTSHIFT => KeyKind::Shift,
+            KeyCode::KEY_LEFTALT => KeyKind::Alt,
+            _ => return Err(Error::KeyNotFound),
+        })
+    }
+}
+
+impl From<KeyKind> for KeyCode {
+    fn from(value: KeyKind) -> Self {
+        match value {
+            KeyKind::A => KeyCode::KEY_A,
+            KeyKind::B => KeyCode::KEY_B,
+            KeyKind::C => KeyCode::KEY_C,
+            KeyKind::D => KeyCode::KEY_D,
+            KeyKind::E => KeyCode::KEY_E,
+            KeyKind::F => KeyCode::KEY_F,
+            KeyKind::G => KeyCode::KEY_G,
+            KeyKind::H => KeyCode::KEY_H,
+            KeyKind::I => KeyCode::KEY_I,
+            KeyKind::J => KeyCode::KEY_J,
+            KeyKind::K => KeyCode::KEY_K,
+            KeyKind::L => KeyCode::KEY_L,
+            KeyKind::M => KeyCode::KEY_M,
+            KeyKind::N => KeyCode::KEY_N,
+            KeyKind::O => KeyCode::KEY_O,
+            KeyKind::P => KeyCode::KEY_P,
+            KeyKind::Q => KeyCode::KEY_Q,
+            KeyKind::R => KeyCode::KEY_R,
+            KeyKind::S => KeyCode::KEY_S,
+            KeyKind::T => KeyCode::KEY_T,
+            KeyKind::U => KeyCode::KEY_U,
+            KeyKind::V => KeyCode::KEY_V,
+            KeyKind::W => KeyCode::KEY_W,
+            KeyKind::X => KeyCode::KEY_X,
+            KeyKind::Y => KeyCode::KEY_Y,
+            KeyKind::Z => KeyCode::KEY_Z,
+            KeyKind::Zero => KeyCode::KEY_0,
+            KeyKind::One => KeyCode::KEY_1,
+            KeyKind::Two => KeyCode::KEY_2,
+            KeyKind::Three => KeyCode::KEY_3,
+            KeyKind::Four => KeyCode::KEY_4,
+            KeyKind::Five => KeyCode::KEY_5,
+            KeyKind::Six => KeyCode::KEY_6,
+            KeyKind::Seven => KeyCode::KEY_7,
+            KeyKind::Eight => KeyCode::KEY_8,
+            KeyKind::Nine => KeyCode::KEY_9,
+            KeyKind::F1 => KeyCode::KEY_F1,
+            KeyKind::F2 => KeyCode::KEY_F2,
+            KeyKind::F3 => KeyCode::KEY_F3,
+            KeyKind::F4 => KeyCode::KEY_F4,
+            KeyKind::F5 => KeyCode::KEY_F5,
+            KeyKind::F6 => KeyCode::KEY_F6,
+            KeyKind::F7 => KeyCode::KEY_F7,
+            KeyKind::F8 => KeyCode::KEY_F8,
+            KeyKind::F9 => KeyCode::KEY_F9,
+            KeyKind::F10 => KeyCode::KEY_F10,
+            KeyKind::F11 => KeyCode::KEY_F11,
+            KeyKind::F12 => KeyCode::KEY_F12,
+            KeyKind::Up => KeyCode::KEY_UP,
+            KeyKind::Down => KeyCode::KEY_DOWN,
+            KeyKind::Left => KeyCode::KEY_LEFT,
+            KeyKind::Right => KeyCode::KEY_RIGHT,
+            KeyKind::Home => KeyCode::KEY_HOME,
+            KeyKind::End => KeyCode::KEY_END,
+            KeyKind::PageUp => KeyCode::KEY_PAGEUP,
+            KeyKind::PageDown => KeyCode::KEY_PAGEDOWN,
+            KeyKind::Insert => KeyCode::KEY_INSERT,
+            KeyKind::Delete => KeyCode::KEY_DELETE,
+            KeyKind::Ctrl => KeyCode::KEY_LEFTCTRL,
+            KeyKind::Enter => KeyCode::KEY_ENTER,
+            KeyKind::Space => KeyCode::KEY_SPACE,
+            KeyKind::Tilde => KeyCode::KEY_GRAVE,
+            KeyKind::Quote => KeyCode::KEY_APOSTROPHE,
+            KeyKind::Semicolon => KeyCode::KEY_SEMICOLON,
+            KeyKind::Comma => KeyCode::KEY_COMMA,
+            KeyKind::Period => KeyCode::KEY_DOT,
+            KeyKind::Slash => KeyCode::KEY_SLASH,
+            KeyKind::Esc => KeyCode::KEY_ESC,
+            KeyKind::Shift => KeyCode::KEY_LEFTSHIFT,
+            KeyKind::Alt => KeyCode::KEY_LEFTALT,
+        }
+    }
+}
+
+pub fn client_to_monitor_or_frame(
+    handle: Handle,
+    x: i32,
+    y: i32,
+    monitor_coordinate: bool,
+) -> Result<ConvertedCoordinates, Error> {
+    let window = handle.query_handle().ok_or(Error::WindowNotFound)?;
+    let (window_x, window_y, width, height) = window_rect(window)?;
+
+    if !monitor_coordinate {
+        return Ok(ConvertedCoordinates {
+            width,
+            height,
+            x,
+            y,
+        });
+    }
+
+    // X11 exposes all monitors as a single root window
+    let x11 = x11()?;
+    Ok(ConvertedCoordinates {
+        width: x11.root_width as i32,
+        height: x11.root_height as i32,
+        x: window_x + x,
+        y: window_y + y,
+    })
+}
+
+/// Retrieves the root coordinate of the active window client area top-left.
+///
+/// This is the origin of the client coordinates used by [`Keys::send_mouse`] with
+/// [`KeyInputKind::Foreground`].
+pub fn foreground_client_origin() -> Result<(i32, i32), Error> {
+    let window = active_window().ok_or(Error::WindowNotFound)?;
+    let (x, y, _, _) = window_rect(window)?;
+    Ok((x, y))
+}
+
+#[inline]
+fn is_foreground(window: Window, kind: KeyInputKind) -> bool {
+    let Some(active) = active_window() else {
+        return false;
+    };
+    match kind {
+        KeyInputKind::Fixed => active == window,
+        KeyInputKind::Foreground => active != window,
+    }
+}
+
+#[inline]
+fn emit(events: &[InputEvent]) -> Result<(), Error> {
+    let device = DEVICE
+        .as_ref()
+        .ok_or(Error::Uinput("device is not available".to_string()))?;
+    device
+        .lock()
+        .unwrap()
+        .emit(events)
+        .map_err(|err| Error::Uinput(err.to_string()))
+}
+
+fn create_device() -> Result<VirtualDevice, Error> {
+    let x11 = x11()?;
+    let mut keys = AttributeSet::<KeyCode>::new();
+    for code in 0..256u16 {
+        if let Ok(kind) = KeyKind::try_from(KeyCode(code)) {
+            keys.insert(KeyCode::from(kind));
+        }
+    }
+    keys.insert(KeyCode::BTN_LEFT);
+    let mut axes = AttributeSet::<RelativeAxisCode>::new();
+    axes.insert(RelativeAxisCode::REL_WHEEL);
+    let abs_x = UinputAbsSetup::new(
+        AbsoluteAxisCode::ABS_X,
+        AbsInfo::new(0, 0, x11.root_width as i32, 0, 0, 0),
+    );
+    let abs_y = UinputAbsSetup::new(
+        AbsoluteAxisCode::ABS_Y,
+        AbsInfo::new(0, 0, x11.root_height as i32, 0, 0, 0),
+    );
+
+    VirtualDevice::builder()
+        .and_then(|builder| builder.name("komari").with_keys(&keys))
+        .and_then(|builder| builder.with_relative_axes(&axes))
+        .and_then(|builder| builder.with_absolute_axis(&abs_x))
+        .and_then(|builder| builder.with_absolute_axis(&abs_y))
+        .and_then(|builder| builder.build())
+        .map_err(|err| Error::Uinput(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use evdev::KeyCode;
+
+    use super::KeyKind;
+
+    #[test]
+    fn key_kind_key_code_round_trip() {
+        for code in 0..256u16 {
+            if let Ok(kind) = KeyKind::try_from(KeyCode(code)) {
+                assert_eq!(KeyCode::from(kind), KeyCode(code));
+            }
+        }
+        assert_eq!(KeyKind::try_from(KeyCode::KEY_A), Ok(KeyKind::A));
+    }
+}
@@ -0,0 +1,37 @@
+//! Linux platform support using X11 for window lookup and capture and uinput for key injection.
+//!
+//! Wayland sessions are supported through XWayland since the game client runs under Wine as an
+//! X11 window. There is no PipeWire capture so a Wayland session without XWayland cannot be
+//! captured.
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+mod capture;
+mod error;
+mod focus;
+mod handle;
+mod keys;
+mod supervisor;
+
+pub use {capture::*, error::*, focus::*, handle::*, keys::*, supervisor::*};
+
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub width: i32,
+    pub height: i32,
+    pub data: Vec<u8>,
+}
+
+pub fn init() {
+    static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+    if INITIALIZED
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::Acquire)
+        .is_ok()
+    {
+        thread::spawn(keys::run_key_loop);
+    }
+}
@@ -0,0 +1,104 @@
+use std::{path::Path, process::Command};
+
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{ClientMessageEvent, ConnectionExt, EventMask},
+};
+
+use super::{
+    Error, Handle, HandleCell,
+    handle::{window_pid, x11},
+};
+
+/// The source indication of `_NET_CLOSE_WINDOW` telling the window manager that the request is
+/// from a pager.
+const SOURCE_PAGER: u32 = 2;
+
+/// A change in the state of the supervised process.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProcessChange {
+    /// The window of [`Handle`] is found and its owning process is now supervised.
+    Started,
+    /// The supervised process has exited.
+    Exited,
+}
+
+/// Tracks the process owning the window of [`Handle`] to detect it exiting.
+///
+/// The process is found through `_NET_WM_PID` which, under Wine, is the Wine process of the game
+/// client.
+#[derive(Debug)]
+pub struct ProcessSupervisor {
+    handle: HandleCell,
+    pid: Option<u32>,
+}
+
+impl ProcessSupervisor {
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle: HandleCell::new(handle),
+            pid: None,
+        }
+    }
+
+    /// Replaces the supervised window with `handle`.
+    ///
+    /// The current process is still supervised until `handle` window is found.
+    pub fn set_handle(&mut self, handle: Handle) {
+        self.handle = HandleCell::new(handle);
+    }
+
+    /// Polls the supervised process and returns the change since the last poll.
+    pub fn poll(&mut self) -> Option<ProcessChange> {
+        let window_pid = self.handle.as_inner().and_then(window_pid);
+        match self.pid {
+            Some(pid) if !Path::new(&format!("/proc/{pid}")).exists() => {
+                self.pid = None;
+                Some(ProcessChange::Exited)
+            }
+            Some(_) => {
+                self.pid = window_pid.or(self.pid);
+                None
+            }
+            None => {
+                self.pid = window_pid;
+                window_pid.map(|_| ProcessChange::Started)
+            }
+        }
+    }
+}
+
+/// Launches the executable at `path` detached from this process.
+///
+/// A Windows executable should be launched through a script wrapping Wine.
+pub fn launch_process(path: &Path) -> Result<(), Error> {
+    Command::new(path)
+        .current_dir(path.parent().unwrap_or(Path::new(".")))
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| Error::LaunchFailed(err.to_string()))
+}
+
+/// Requests the window of `handle` to close as if its close button is clicked.
+///
+/// The request is sent to the window manager through `_NET_CLOSE_WINDOW`.
+pub fn close_window(handle: Handle) -> Result<(), Error> {
+    let window = handle.query_handle().ok_or(Error::WindowNotFound)?;
+    let x11 = x11()?;
+    let event = ClientMessageEvent::new(
+        32,
+        window,
+        x11.atoms._NET_CLOSE_WINDOW,
+        [x11rb::CURRENT_TIME, SOURCE_PAGER, 0, 0, 0],
+    );
+    x11.conn
+        .send_event(
+            false,
+            x11.root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )
+        .map_err(Error::x11)?;
+    x11.conn.flush().map_err(Error::x11)?;
+    Ok(())
+}
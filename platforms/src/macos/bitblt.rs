@@ -21,6 +21,13 @@ impl BitBltCapture {
         self.inner.grab()
     }
 
+    /// Grabs a frame reusing the allocation of `data` for its pixels.
+    ///
+    /// `data` is taken into the [`Frame`] on success and left as is otherwise.
+    pub fn grab_into(&mut self, data: &mut Vec<u8>) -> Result<Frame, Error> {
+        self.inner.grab_into(data)
+    }
+
     pub fn stop_capture(&mut self) {
         self.inner.stop_capture();
     }
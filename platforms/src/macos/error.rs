@@ -13,6 +13,8 @@ pub enum Error {
     FrameNotAvailable,
     #[error("key not found")]
     KeyNotFound,
+    #[error("failed to launch process {0}")]
+    LaunchFailed(String),
     #[error("macOS API error {0}: {1}")]
     MacOS(u32, String),
     #[error("initialization failed")]
@@ -0,0 +1,40 @@
+use super::{Error, Handle};
+
+/// A change in the focus state of the tracked window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FocusChange {
+    Gained,
+    Lost,
+}
+
+/// Tracks whether the window of [`Handle`] is focused.
+///
+/// macOS capture is coordinate-based without a window to track so the window is always
+/// considered focused.
+#[derive(Debug)]
+pub struct FocusMonitor {
+    handle: Handle,
+}
+
+impl FocusMonitor {
+    pub fn new(handle: Handle) -> Self {
+        Self { handle }
+    }
+
+    pub fn set_handle(&mut self, handle: Handle) {
+        self.handle = handle;
+    }
+
+    #[inline]
+    pub fn is_focused(&self) -> bool {
+        true
+    }
+
+    pub fn poll(&mut self) -> Option<FocusChange> {
+        None
+    }
+
+    pub fn refocus(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use std::cell::Cell;
 use screenshots;
 
+use super::Error;
+
 #[derive(Clone, Debug)]
 pub(crate) struct HandleCell {
     handle: Handle,
@@ -150,6 +152,26 @@ pub fn query_capture_handles() -> Vec<(String, Handle)> {
     handles
 }
 
+/// Queries the name of the process owning `handle`.
+///
+/// Not supported because capture is coordinate-based without a window owned by a process.
+pub fn query_process_name(_handle: Handle) -> Option<String> {
+    None
+}
+
+/// Moves and resizes the window of `handle`.
+///
+/// Not supported because capture is coordinate-based without a window to arrange.
+pub fn arrange_window(
+    _handle: Handle,
+    _x: i32,
+    _y: i32,
+    _width: i32,
+    _height: i32,
+) -> Result<(), Error> {
+    Err(Error::WindowNotFound)
+}
+
 /// Find the best display index for given coordinates
 pub fn find_display_for_coordinates(x: i32, y: i32, width: i32, height: i32) -> Option<usize> {
     match screenshots::Screen::all() {
@@ -8,11 +8,13 @@ use std::{
 
 mod bitblt;
 mod error;
+mod focus;
 mod handle;
 mod keys;
 pub mod screenshot;
+mod supervisor;
 
-pub use {bitblt::*, error::*, handle::*, keys::*, screenshot::*};
+pub use {bitblt::*, error::*, focus::*, handle::*, keys::*, screenshot::*, supervisor::*};
 pub use keys::{client_to_monitor_or_frame, KeyInputKind, KeysManager as Keys, KeyReceiver};
 pub use handle::find_display_for_coordinates;
 
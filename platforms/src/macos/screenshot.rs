@@ -1,3 +1,5 @@
+use std::mem;
+
 use screenshots::Screen;
 use super::{Error, Frame, Handle, HandleCell};
 
@@ -150,7 +152,15 @@ impl ScreenshotCapture {
         Ok(())
     }
 
+    #[inline]
     pub fn grab(&mut self) -> Result<Frame, Error> {
+        self.grab_into(&mut Vec::new())
+    }
+
+    /// Grabs a frame reusing the allocation of `data` for its pixels.
+    ///
+    /// `data` is taken into the [`Frame`] on success and left as is otherwise.
+    pub fn grab_into(&mut self, data: &mut Vec<u8>) -> Result<Frame, Error> {
         let screen = self.screen.as_ref().ok_or(Error::WindowNotFound)?;
         
         let image = screen
@@ -159,19 +169,20 @@ impl ScreenshotCapture {
 
         // Convert RGBA to BGRA format to match Windows Frame format
         let buffer = image.as_raw();
-        let mut bgra_data = Vec::with_capacity(buffer.len());
-        
+        data.clear();
+        data.reserve(buffer.len());
+
         for chunk in buffer.chunks_exact(4) {
-            bgra_data.push(chunk[2]); // B
-            bgra_data.push(chunk[1]); // G
-            bgra_data.push(chunk[0]); // R
-            bgra_data.push(chunk[3]); // A
+            data.push(chunk[2]); // B
+            data.push(chunk[1]); // G
+            data.push(chunk[0]); // R
+            data.push(chunk[3]); // A
         }
 
         Ok(Frame {
             width: self.width,
             height: self.height,
-            data: bgra_data,
+            data: mem::take(data),
         })
     }
 
@@ -184,6 +195,19 @@ impl ScreenshotCapture {
     }
 }
 
+/// Captures the primary display desktop.
+pub fn capture_desktop() -> Result<Frame, Error> {
+    let screen = Screen::all()
+        .ok()
+        .and_then(|screens| screens.into_iter().next())
+        .ok_or(Error::WindowNotFound)?;
+    let width = screen.display_info.width as i32;
+    let height = screen.display_info.height as i32;
+    let handle = Handle::new("Desktop").with_coordinates(0, 0, 0, width, height);
+
+    ScreenshotCapture::new(handle)?.grab()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
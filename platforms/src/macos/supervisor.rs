@@ -0,0 +1,45 @@
+use std::{path::Path, process::Command};
+
+use super::{Error, Handle};
+
+/// A change in the state of the supervised process.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProcessChange {
+    Started,
+    Exited,
+}
+
+/// Tracks the process owning the window of [`Handle`] to detect it exiting.
+///
+/// macOS capture is coordinate-based without a window to track so the process is never
+/// detected as exited.
+#[derive(Debug)]
+pub struct ProcessSupervisor;
+
+impl ProcessSupervisor {
+    pub fn new(_handle: Handle) -> Self {
+        Self
+    }
+
+    pub fn set_handle(&mut self, _handle: Handle) {}
+
+    pub fn poll(&mut self) -> Option<ProcessChange> {
+        None
+    }
+}
+
+/// Launches the executable at `path` detached from this process.
+pub fn launch_process(path: &Path) -> Result<(), Error> {
+    Command::new(path)
+        .current_dir(path.parent().unwrap_or(Path::new(".")))
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| Error::LaunchFailed(err.to_string()))
+}
+
+/// Requests the window of `handle` to close.
+///
+/// Not supported because capture is coordinate-based without a window to close.
+pub fn close_window(_handle: Handle) -> Result<(), Error> {
+    Err(Error::WindowNotFound)
+}
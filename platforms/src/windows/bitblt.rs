@@ -19,6 +19,7 @@ use windows::Win32::Graphics::Gdi::{
     SRCCOPY, SelectObject,
 };
 use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
+use windows::Win32::UI::WindowsAndMessaging::GetDesktopWindow;
 use windows::core::Owned;
 use windows::core::PCWSTR;
 
@@ -79,14 +80,30 @@ impl BitBltCapture {
 
     #[inline]
     pub fn grab(&mut self) -> Result<Frame, Error> {
-        self.grab_inner(None)
+        self.grab_inner(None, &mut Vec::new())
     }
 
-    pub(crate) fn grab_inner_offset(&mut self, offset: Option<(i32, i32)>) -> Result<Frame, Error> {
-        self.grab_inner(offset)
+    /// Grabs a frame reusing the allocation of `data` for its pixels.
+    ///
+    /// `data` is taken into the [`Frame`] on success and left as is otherwise.
+    #[inline]
+    pub fn grab_into(&mut self, data: &mut Vec<u8>) -> Result<Frame, Error> {
+        self.grab_inner(None, data)
+    }
+
+    pub(crate) fn grab_inner_offset(
+        &mut self,
+        offset: Option<(i32, i32)>,
+        data: &mut Vec<u8>,
+    ) -> Result<Frame, Error> {
+        self.grab_inner(offset, data)
     }
 
-    fn grab_inner(&mut self, mut offset: Option<(i32, i32)>) -> Result<Frame, Error> {
+    fn grab_inner(
+        &mut self,
+        mut offset: Option<(i32, i32)>,
+        data: &mut Vec<u8>,
+    ) -> Result<Frame, Error> {
         let handle = self.handle.as_inner().ok_or(Error::WindowNotFound)?;
         let rect = get_rect(handle)?;
         let width = rect.right - rect.left;
@@ -135,15 +152,22 @@ impl BitBltCapture {
         }
         // SAFETY: I swear on the love of Axis Order, this call passed the safety vibe check
         let ptr = unsafe { slice::from_raw_parts(bitmap.buffer, bitmap.size) };
-        let data = ptr.to_vec();
+        data.clear();
+        data.extend_from_slice(ptr);
         Ok(Frame {
             width: bitmap.width,
             height: bitmap.height,
-            data,
+            data: mem::take(data),
         })
     }
 }
 
+/// Captures the primary monitor desktop.
+pub fn capture_desktop() -> Result<Frame, Error> {
+    let desktop = unsafe { GetDesktopWindow() };
+    BitBltCapture::new(Handle::new_fixed(desktop), false).grab()
+}
+
 #[inline]
 fn get_rect(handle: HWND) -> Result<RECT, Error> {
     let mut rect = RECT::default();
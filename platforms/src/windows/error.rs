@@ -13,6 +13,8 @@ pub enum Error {
     FrameNotAvailable,
     #[error("key not found")]
     KeyNotFound,
+    #[error("failed to launch process {0}")]
+    LaunchFailed(String),
     #[error("win32 API error {0}: {1}")]
     Win32(u32, String),
 }
@@ -0,0 +1,70 @@
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, IsIconic, SW_RESTORE, SetForegroundWindow, ShowWindow,
+};
+
+use super::{Error, Handle, HandleCell};
+
+/// A change in the focus state of the tracked window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FocusChange {
+    Gained,
+    Lost,
+}
+
+/// Tracks whether the window of [`Handle`] is the focused foreground window.
+#[derive(Debug)]
+pub struct FocusMonitor {
+    handle: HandleCell,
+    focused: bool,
+}
+
+impl FocusMonitor {
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle: HandleCell::new(handle),
+            focused: true,
+        }
+    }
+
+    /// Replaces the tracked window with `handle`.
+    pub fn set_handle(&mut self, handle: Handle) {
+        self.handle = HandleCell::new(handle);
+        self.focused = true;
+    }
+
+    /// Whether the window was focused as of the last [`Self::poll`].
+    #[inline]
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Polls the current focus state and returns the change since the last poll.
+    pub fn poll(&mut self) -> Option<FocusChange> {
+        let focused = self
+            .handle
+            .as_inner()
+            .is_some_and(|handle| unsafe { GetForegroundWindow() } == handle);
+        let change = match (self.focused, focused) {
+            (true, false) => Some(FocusChange::Lost),
+            (false, true) => Some(FocusChange::Gained),
+            _ => None,
+        };
+        self.focused = focused;
+        change
+    }
+
+    /// Attempts to bring the window back to the foreground.
+    ///
+    /// Windows may refuse the request if this process is not allowed to set the foreground
+    /// window (e.g. the user is actively using another application).
+    pub fn refocus(&self) -> Result<(), Error> {
+        let handle = self.handle.as_inner().ok_or(Error::WindowNotFound)?;
+        unsafe {
+            if IsIconic(handle).as_bool() {
+                let _ = ShowWindow(handle, SW_RESTORE);
+            }
+            SetForegroundWindow(handle).ok()?;
+        }
+        Ok(())
+    }
+}
@@ -1,17 +1,25 @@
-use std::{cell::Cell, ffi::OsString, os::windows::ffi::OsStringExt, ptr, str};
+use std::{cell::Cell, ffi::OsString, os::windows::ffi::OsStringExt, path::Path, ptr, str};
 
 use windows::{
     Win32::{
-        Foundation::{HWND, LPARAM},
+        Foundation::{CloseHandle, HWND, LPARAM, RECT},
         Graphics::Dwm::{DWMWA_CLOAKED, DwmGetWindowAttribute},
+        System::Threading::{
+            OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+            QueryFullProcessImageNameW,
+        },
         UI::WindowsAndMessaging::{
-            EnumWindows, GWL_EXSTYLE, GWL_STYLE, GetClassNameW, GetWindowLongPtrW, GetWindowTextW,
-            IsWindowVisible, WS_DISABLED, WS_EX_TOOLWINDOW,
+            AdjustWindowRectEx, EnumWindows, GWL_EXSTYLE, GWL_STYLE, GetClassNameW,
+            GetWindowLongPtrW, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible,
+            SWP_NOACTIVATE, SWP_NOZORDER, SetWindowPos, WINDOW_EX_STYLE, WINDOW_STYLE, WS_DISABLED,
+            WS_EX_TOOLWINDOW,
         },
     },
-    core::BOOL,
+    core::{BOOL, PWSTR},
 };
 
+use super::Error;
+
 #[derive(Clone, Debug)]
 pub(crate) struct HandleCell {
     handle: Handle,
@@ -146,6 +154,74 @@ pub fn query_capture_handles() -> Vec<(String, Handle)> {
     vec
 }
 
+/// Queries the executable file name (e.g. `MapleStory.exe`) of the process owning `handle`.
+pub fn query_process_name(handle: Handle) -> Option<String> {
+    let handle = handle.query_handle()?;
+    let mut process_id = 0u32;
+    unsafe { GetWindowThreadProcessId(handle, Some(&raw mut process_id)) };
+    if process_id == 0 {
+        return None;
+    }
+
+    let process =
+        unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) }.ok()?;
+    let mut buf = [0u16; 1024];
+    let mut count = buf.len() as u32;
+    let result = unsafe {
+        QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buf.as_mut_ptr()),
+            &raw mut count,
+        )
+    };
+    let _ = unsafe { CloseHandle(process) };
+    result.ok()?;
+
+    let path = OsString::from_wide(&buf[..count as usize]);
+    Path::new(&path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+}
+
+/// Moves `handle` window to `(x, y)` on the virtual screen and resizes its client area to
+/// `width` x `height`.
+pub fn arrange_window(
+    handle: Handle,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+) -> Result<(), Error> {
+    let handle = handle.query_handle().ok_or(Error::WindowNotFound)?;
+    if width <= 0 || height <= 0 {
+        return Err(Error::InvalidWindowSize);
+    }
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: width,
+        bottom: height,
+    };
+    unsafe {
+        let style = WINDOW_STYLE(GetWindowLongPtrW(handle, GWL_STYLE) as u32);
+        let ex_style = WINDOW_EX_STYLE(GetWindowLongPtrW(handle, GWL_EXSTYLE) as u32);
+        // Grows the rectangle to include the window borders and title bar
+        AdjustWindowRectEx(&raw mut rect, style, false, ex_style)?;
+        SetWindowPos(
+            handle,
+            None,
+            x,
+            y,
+            rect.right - rect.left,
+            rect.bottom - rect.top,
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        )?;
+    }
+    Ok(())
+}
+
 #[inline]
 fn is_class_matched(handle: HWND, class: &'static str) -> bool {
     let mut buf = [0u16; 256];
@@ -0,0 +1,197 @@
+use std::sync::LazyLock;
+
+use windows::{
+    Win32::{
+        Foundation::HWND,
+        Graphics::{
+            Direct3D11::ID3D11Device,
+            Dxgi::{
+                Common::{
+                    DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020, DXGI_FORMAT,
+                    DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_FORMAT_R10G10B10A2_UNORM,
+                    DXGI_FORMAT_R16G16B16A16_FLOAT,
+                },
+                IDXGIDevice, IDXGIOutput6,
+            },
+            Gdi::{MONITOR_DEFAULTTONEAREST, MonitorFromWindow},
+        },
+    },
+    core::Interface,
+};
+
+/// The number of entries in the linear to sRGB lookup table.
+const SRGB_LUT_SIZE: usize = 4096;
+
+/// The scRGB value SDR white is composed at, 200 nits where scRGB `1.0` is 80 nits.
+///
+/// This is fixed instead of derived from each frame so that the same pixel always maps to the
+/// same value and template thresholds do not drift between frames.
+const SDR_WHITE_LEVEL: f32 = 2.5;
+
+/// The pixel format to request from [`super::WgcCapture`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WgcPixelFormat {
+    /// Requests 16-bit float frames when the window is on an HDR monitor and 8-bit otherwise.
+    #[default]
+    Auto,
+    /// Always requests 8-bit frames and lets Windows convert HDR content.
+    Bgra8,
+    /// Always requests 16-bit float frames and tone-maps them to 8-bit.
+    Rgba16Float,
+}
+
+/// Whether the monitor that `handle` is on has HDR enabled.
+pub(crate) fn is_hdr_monitor(device: &ID3D11Device, handle: HWND) -> bool {
+    let monitor = unsafe { MonitorFromWindow(handle, MONITOR_DEFAULTTONEAREST) };
+    let Ok(adapter) = device
+        .cast::<IDXGIDevice>()
+        .and_then(|device| unsafe { device.GetAdapter() })
+    else {
+        return false;
+    };
+
+    let mut i = 0;
+    while let Ok(output) = unsafe { adapter.EnumOutputs(i) } {
+        i += 1;
+        let Ok(desc) = output
+            .cast::<IDXGIOutput6>()
+            .and_then(|output| unsafe { output.GetDesc1() })
+        else {
+            continue;
+        };
+        if desc.Monitor == monitor {
+            return desc.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020;
+        }
+    }
+    false
+}
+
+/// The number of bytes of a pixel in `format`.
+#[inline]
+pub(crate) fn bytes_per_pixel(format: DXGI_FORMAT) -> usize {
+    if format == DXGI_FORMAT_R16G16B16A16_FLOAT {
+        8
+    } else {
+        4
+    }
+}
+
+/// Converts tightly packed pixels `data` of `format` to 8-bit BGRA.
+///
+/// Returns [`None`] if `format` is not supported.
+pub(crate) fn to_bgra8(data: Vec<u8>, format: DXGI_FORMAT) -> Option<Vec<u8>> {
+    match format {
+        DXGI_FORMAT_B8G8R8A8_UNORM => Some(data),
+        DXGI_FORMAT_R16G16B16A16_FLOAT => Some(rgba16f_to_bgra8(&data)),
+        DXGI_FORMAT_R10G10B10A2_UNORM => Some(rgb10a2_to_bgra8(&data)),
+        _ => None,
+    }
+}
+
+/// Tone-maps linear scRGB half float pixels to sRGB encoded 8-bit BGRA.
+///
+/// Windows composes SDR content at the SDR white level which is usually brighter than scRGB
+/// `1.0`. Dividing by [`SDR_WHITE_LEVEL`] brings the game's white (e.g. the minimap border) back
+/// to `1.0` before encoding and anything brighter is clipped.
+fn rgba16f_to_bgra8(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(8)
+        .flat_map(|pixel| {
+            let [r, g, b, a] =
+                [0, 2, 4, 6].map(|i| f16_to_f32(u16::from_le_bytes([pixel[i], pixel[i + 1]])));
+            [
+                linear_to_srgb(b / SDR_WHITE_LEVEL),
+                linear_to_srgb(g / SDR_WHITE_LEVEL),
+                linear_to_srgb(r / SDR_WHITE_LEVEL),
+                (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+            ]
+        })
+        .collect()
+}
+
+/// Converts 10-bit per channel pixels to 8-bit BGRA by dropping the lowest bits.
+fn rgb10a2_to_bgra8(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4)
+        .flat_map(|pixel| {
+            let value = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+            let r = (value & 0x3ff) >> 2;
+            let g = ((value >> 10) & 0x3ff) >> 2;
+            let b = ((value >> 20) & 0x3ff) >> 2;
+            let a = (value >> 30) * 85;
+            [b as u8, g as u8, r as u8, a as u8]
+        })
+        .collect()
+}
+
+#[inline]
+fn linear_to_srgb(value: f32) -> u8 {
+    static LUT: LazyLock<[u8; SRGB_LUT_SIZE]> = LazyLock::new(|| {
+        let mut lut = [0; SRGB_LUT_SIZE];
+        for (i, entry) in lut.iter_mut().enumerate() {
+            let linear = i as f32 / (SRGB_LUT_SIZE - 1) as f32;
+            let encoded = if linear <= 0.0031308 {
+                linear * 12.92
+            } else {
+                1.055 * linear.powf(1.0 / 2.4) - 0.055
+            };
+            *entry = (encoded * 255.0).round() as u8;
+        }
+        lut
+    });
+
+    // NaN is clamped to 0 by the cast
+    let index = (value.clamp(0.0, 1.0) * (SRGB_LUT_SIZE - 1) as f32).round() as usize;
+    LUT[index]
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits >> 15 == 0 { 1.0 } else { -1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f16_to_f32_values() {
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0xc000), -2.0);
+        assert_eq!(f16_to_f32(0x3800), 0.5);
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+    }
+
+    #[test]
+    fn rgba16f_to_bgra8_uses_sdr_white_level() {
+        // Red at the SDR white level and a gray at scRGB 1.0
+        let half = |value: u16| value.to_le_bytes();
+        let pixel = |r: u16, g: u16, b: u16| [half(r), half(g), half(b), half(0x3c00)].concat();
+        let data = [pixel(0x4100, 0x0000, 0x0000), pixel(0x3c00, 0x3c00, 0x3c00)].concat();
+        let brighter = [pixel(0x4500, 0x0000, 0x0000), pixel(0x3c00, 0x3c00, 0x3c00)].concat();
+
+        let bgra = rgba16f_to_bgra8(&data);
+        assert_eq!(&bgra[..4], &[0, 0, 255, 255]);
+        assert_eq!(bgra[4], bgra[5]);
+        assert_eq!(bgra[5], bgra[6]);
+        assert!(bgra[4] > 128 && bgra[4] < 255);
+        // A brighter pixel elsewhere does not change how the gray is mapped
+        assert_eq!(rgba16f_to_bgra8(&brighter)[4..], bgra[4..]);
+    }
+
+    #[test]
+    fn rgb10a2_to_bgra8_drops_low_bits() {
+        let value = 0x3ffu32 | (0x200 << 10) | (0x3 << 30);
+        assert_eq!(
+            rgb10a2_to_bgra8(&value.to_le_bytes()),
+            vec![0, 128, 255, 255]
+        );
+    }
+}
@@ -520,6 +520,20 @@ pub fn client_to_monitor_or_frame(
     })
 }
 
+/// Retrieves the screen coordinate of the foreground window client area top-left.
+///
+/// This is the origin of the client coordinates used by [`Keys::send_mouse`] with
+/// [`KeyInputKind::Foreground`].
+pub fn foreground_client_origin() -> Result<(i32, i32), Error> {
+    let handle = unsafe { GetForegroundWindow() };
+    if handle.is_invalid() {
+        return Err(Error::WindowNotFound);
+    }
+    let mut point = POINT { x: 0, y: 0 };
+    unsafe { ClientToScreen(handle, &raw mut point).ok()? };
+    Ok((point.x, point.y))
+}
+
 fn client_to_absolute_coordinate_raw(handle: HWND, x: i32, y: i32) -> Result<(i32, i32), Error> {
     let mut point = POINT { x, y };
     unsafe { ClientToScreen(handle, &raw mut point).ok()? };
@@ -12,12 +12,16 @@ use windows::Win32::UI::WindowsAndMessaging::{
 
 mod bitblt;
 mod error;
+mod focus;
 mod handle;
+mod hdr;
 mod keys;
+mod supervisor;
 mod wgc;
 mod window_box;
 
-pub use {bitblt::*, error::*, handle::*, keys::*, wgc::*, window_box::*};
+pub use hdr::WgcPixelFormat;
+pub use {bitblt::*, error::*, focus::*, handle::*, keys::*, supervisor::*, wgc::*, window_box::*};
 
 #[derive(Clone, Debug)]
 pub struct Frame {
@@ -0,0 +1,92 @@
+use std::{path::Path, process::Command};
+
+use windows::Win32::{
+    Foundation::{CloseHandle, LPARAM, STILL_ACTIVE, WPARAM},
+    System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
+    UI::WindowsAndMessaging::{GetWindowThreadProcessId, PostMessageW, WM_CLOSE},
+};
+
+use super::{Error, Handle, HandleCell};
+
+/// A change in the state of the supervised process.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProcessChange {
+    /// The window of [`Handle`] is found and its owning process is now supervised.
+    Started,
+    /// The supervised process has exited.
+    Exited,
+}
+
+/// Tracks the process owning the window of [`Handle`] to detect it exiting.
+#[derive(Debug)]
+pub struct ProcessSupervisor {
+    handle: HandleCell,
+    process_id: Option<u32>,
+}
+
+impl ProcessSupervisor {
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle: HandleCell::new(handle),
+            process_id: None,
+        }
+    }
+
+    /// Replaces the supervised window with `handle`.
+    ///
+    /// The current process is still supervised until `handle` window is found.
+    pub fn set_handle(&mut self, handle: Handle) {
+        self.handle = HandleCell::new(handle);
+    }
+
+    /// Polls the supervised process and returns the change since the last poll.
+    pub fn poll(&mut self) -> Option<ProcessChange> {
+        let window_process_id = self.handle.as_inner().and_then(|handle| {
+            let mut process_id = 0u32;
+            unsafe { GetWindowThreadProcessId(handle, Some(&raw mut process_id)) };
+            (process_id != 0).then_some(process_id)
+        });
+        match self.process_id {
+            Some(process_id) if !is_process_alive(process_id) => {
+                self.process_id = None;
+                Some(ProcessChange::Exited)
+            }
+            Some(_) => {
+                self.process_id = window_process_id.or(self.process_id);
+                None
+            }
+            None => {
+                self.process_id = window_process_id;
+                window_process_id.map(|_| ProcessChange::Started)
+            }
+        }
+    }
+}
+
+/// Launches the executable at `path` detached from this process.
+pub fn launch_process(path: &Path) -> Result<(), Error> {
+    Command::new(path)
+        .current_dir(path.parent().unwrap_or(Path::new(".")))
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| Error::LaunchFailed(err.to_string()))
+}
+
+/// Requests the window of `handle` to close as if its close button is clicked.
+pub fn close_window(handle: Handle) -> Result<(), Error> {
+    let handle = handle.query_handle().ok_or(Error::WindowNotFound)?;
+    unsafe { PostMessageW(Some(handle), WM_CLOSE, WPARAM(0), LPARAM(0))? };
+    Ok(())
+}
+
+fn is_process_alive(process_id: u32) -> bool {
+    let Ok(process) =
+        (unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) })
+    else {
+        return false;
+    };
+    let mut exit_code = 0u32;
+    let result = unsafe { GetExitCodeProcess(process, &raw mut exit_code) };
+    let _ = unsafe { CloseHandle(process) };
+    result.is_ok() && exit_code == STILL_ACTIVE.0 as u32
+}
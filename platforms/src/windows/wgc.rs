@@ -44,7 +44,10 @@ use windows::{
     core::{HSTRING, Interface, RuntimeName},
 };
 
-use super::{Error, Frame, Handle, HandleCell};
+use super::{
+    Error, Frame, Handle, HandleCell, WgcPixelFormat,
+    hdr::{bytes_per_pixel, is_hdr_monitor, to_bgra8},
+};
 
 const MAX_FRAME_FAILURE: u32 = 3;
 
@@ -68,7 +71,7 @@ struct WgcCaptureInner {
 }
 
 impl WgcCaptureInner {
-    fn grab_with_timeout(&mut self) -> Result<Frame, Error> {
+    fn grab_with_timeout(&mut self, data: &mut Vec<u8>) -> Result<Frame, Error> {
         let message = self
             .frame_rx
             .recv_timeout(Duration::from_millis(self.frame_timeout))
@@ -143,10 +146,13 @@ impl WgcCaptureInner {
                 (texture_height * resource.RowPitch) as usize,
             )
         };
-        let vec = if texture_height * 4 != resource.RowPitch {
-            let capacity = (texture_width * texture_height * 4) as usize;
-            let dst_stride = (texture_width * 4) as usize;
-            let mut vec = Vec::<u8>::with_capacity(capacity);
+        let pixel_size = bytes_per_pixel(surface_desc.Format) as u32;
+        data.clear();
+        let vec = if texture_width * pixel_size != resource.RowPitch {
+            let capacity = (texture_width * texture_height * pixel_size) as usize;
+            let dst_stride = (texture_width * pixel_size) as usize;
+            let mut vec = mem::take(data);
+            vec.reserve(capacity);
             let vec_ptr = vec.as_mut_ptr();
             for i in 0..texture_height as usize {
                 let src_offset = resource.RowPitch as usize * i;
@@ -159,13 +165,16 @@ impl WgcCaptureInner {
                     );
                 }
             }
+            unsafe { vec.set_len(capacity) };
             vec
         } else {
-            buffer.to_vec()
+            data.extend_from_slice(buffer);
+            mem::take(data)
         };
         unsafe {
             self.d3d11_context.Unmap(texture, 0);
         };
+        let vec = to_bgra8(vec, surface_desc.Format).ok_or(Error::FrameNotAvailable)?;
 
         if frame_content_size != self.frame_last_content_size {
             self.frame_format = DirectXPixelFormat(surface_desc.Format.0);
@@ -204,11 +213,19 @@ pub struct WgcCapture {
     d3d11_context: ID3D11DeviceContext,
     d3d_device: IDirect3DDevice,
     frame_timeout: u64,
+    pixel_format: WgcPixelFormat,
     inner: Option<WgcCaptureInner>,
 }
 
 impl WgcCapture {
-    pub fn new(handle: Handle, frame_timeout: u64) -> Result<Self, Error> {
+    /// Creates a capture for `handle` requesting frames in `pixel_format`.
+    ///
+    /// Frames that are not 8-bit are tone-mapped to 8-bit BGRA before being returned.
+    pub fn new(
+        handle: Handle,
+        frame_timeout: u64,
+        pixel_format: WgcPixelFormat,
+    ) -> Result<Self, Error> {
         let (d3d11_device, d3d11_context) = create_d3d11_device()?;
         let d3d_device = create_d3d_device(&d3d11_device)?;
         Ok(Self {
@@ -217,11 +234,22 @@ impl WgcCapture {
             d3d11_context,
             d3d_device,
             frame_timeout,
+            pixel_format,
             inner: None,
         })
     }
 
+    #[inline]
     pub fn grab(&mut self) -> Result<Frame, Error> {
+        self.grab_into(&mut Vec::new())
+    }
+
+    /// Grabs a frame reusing the allocation of `data` for its pixels.
+    ///
+    /// `data` is taken into the [`Frame`] once the frame is copied and left as is if no frame is
+    /// available. The allocation is not reused for HDR pixel formats because they are converted to 8-bit
+    /// BGRA.
+    pub fn grab_into(&mut self, data: &mut Vec<u8>) -> Result<Frame, Error> {
         if self.inner.is_none()
             && let Some(handle) = self.handle.as_inner()
         {
@@ -229,7 +257,7 @@ impl WgcCapture {
         }
 
         if let Some(inner) = self.inner.as_mut() {
-            let result = inner.grab_with_timeout();
+            let result = inner.grab_with_timeout(data);
             if let Err(Error::WindowNotFound) = result.as_ref() {
                 self.stop_capture();
             }
@@ -244,7 +272,15 @@ impl WgcCapture {
 
     fn start_capture(&mut self, handle: HWND) -> Result<(), Error> {
         let (tx, rx) = mpsc::channel::<Message>();
-        let frame_format = DirectXPixelFormat::B8G8R8A8UIntNormalized;
+        let frame_format = match self.pixel_format {
+            WgcPixelFormat::Auto if is_hdr_monitor(&self.d3d11_device, handle) => {
+                DirectXPixelFormat::R16G16B16A16Float
+            }
+            WgcPixelFormat::Auto | WgcPixelFormat::Bgra8 => {
+                DirectXPixelFormat::B8G8R8A8UIntNormalized
+            }
+            WgcPixelFormat::Rgba16Float => DirectXPixelFormat::R16G16B16A16Float,
+        };
 
         let item = create_graphics_capture_item(handle)?;
         let item_closed_tx = tx.clone();
@@ -136,12 +136,21 @@ impl WindowBoxCapture {
         self.handle
     }
 
+    #[inline]
     pub fn grab(&mut self) -> Result<Frame, Error> {
-        self.capture.grab_inner_offset(self.position())
+        self.grab_into(&mut Vec::new())
+    }
+
+    /// Grabs a frame reusing the allocation of `data` for its pixels.
+    ///
+    /// `data` is taken into the [`Frame`] on success and left as is otherwise.
+    pub fn grab_into(&mut self, data: &mut Vec<u8>) -> Result<Frame, Error> {
+        self.capture.grab_inner_offset(self.position(), data)
     }
 
+    /// The screen coordinate of the capture area top-left.
     #[inline]
-    fn position(&self) -> Option<(i32, i32)> {
+    pub fn position(&self) -> Option<(i32, i32)> {
         self.position
             .lock()
             .unwrap()
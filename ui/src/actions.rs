@@ -1,26 +1,37 @@
 use std::{
+    collections::HashMap,
     fmt::Display,
     fs::File,
     io::BufReader,
+    iter,
     mem::{discriminant, swap},
     ops::Range,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use backend::{
-    Action, ActionCondition, ActionKey, ActionKeyDirection, ActionKeyWith, ActionMove, Bound,
-    IntoEnumIterator, KeyBinding, LinkKeyBinding, Minimap, MobbingKey, Platform, Position,
-    RotationMode, key_receiver, update_minimap, upsert_minimap,
+    Action, ActionCondition, ActionFailure, ActionGroup, ActionGroupPredicate, ActionKey,
+    ActionKeyDirection, ActionKeyVerify, ActionKeyWith, ActionMouse, ActionMouseKind,
+    ActionMouseRelativeTo, ActionMove, ActionStats, AutoMobRegion, AutoMobRegionOrder,
+    AutoMobStrategy, AvoidanceZone, Bound, BuffKind, CaptureMode, CaptureOverride, Character,
+    IntoEnumIterator, KeyBinding, LinkKeyBinding, Loadout, Minimap, MobbingKey, MovementOverrides,
+    PingPongBand, Platform, PlatformMotion, Position, PresetIssue, PresetScheduleEntry, Rope,
+    RotationMode, clear_avoidance_zones, delete_avoidance_zone, key_receiver, query_action_stats,
+    query_avoidance_zones, query_characters, reorder_actions, update_avoidance_zones,
+    update_minimap, upsert_minimap, validate_preset,
 };
 use dioxus::prelude::*;
 use futures_util::StreamExt;
 use rand::distr::{Alphanumeric, SampleString};
+use tokio::time::sleep;
 
 use crate::{
     AppState,
     button::{Button, ButtonKind},
     icons::{DownArrowIcon, PositionIcon, UpArrowIcon, XIcon},
-    inputs::{Checkbox, KeyBindingInput, MillisInput, NumberInputI32, NumberInputU32},
-    select::{EnumSelect, TextSelect},
+    inputs::{Checkbox, KeyBindingInput, MillisInput, NumberInputI32, NumberInputU32, TextInput},
+    select::{EnumSelect, Select, TextSelect},
+    toast::ToastResult,
 };
 
 const ITEM_TEXT_CLASS: &str =
@@ -40,6 +51,9 @@ enum ActionUpdate {
 enum PopupInputKind {
     Action(ActionInputKind),
     Bound(Bound),
+    SequenceBound(Bound, Option<usize>),
+    PingPongBand(PingPongBand, Option<usize>),
+    AutoMobRegion(Option<usize>),
     Platform(Platform, Option<usize>),
 }
 
@@ -76,6 +90,13 @@ pub fn Actions() -> Element {
             .and_then(|(minimap, preset)| minimap.actions.get(&preset).cloned())
             .unwrap_or_default()
     });
+    // Validates reachability of currently selected `minimap_preset` action positions
+    let minimap_preset_issues = use_memo(move || {
+        minimap()
+            .zip(minimap_preset())
+            .map(|(minimap, preset)| validate_preset(&minimap, &preset))
+            .unwrap_or_default()
+    });
     // Maps currently selected `minimap_preset` to the index in `minimap_presets`
     let minimap_preset_index = use_memo(move || {
         let presets = minimap_presets();
@@ -92,16 +113,22 @@ pub fn Actions() -> Element {
     // TODO: Split into functions
     let coroutine = use_coroutine(move |mut rx: UnboundedReceiver<ActionUpdate>| async move {
         let mut save_minimap = async move |new_minimap: Minimap| {
-            let new_minimap = upsert_minimap(new_minimap).await;
+            let Some(new_minimap) = upsert_minimap(new_minimap).await.ok_or_toast() else {
+                return;
+            };
 
             minimap.set(Some(new_minimap));
-            update_minimap(minimap_preset(), minimap()).await;
+            update_minimap(minimap_preset(), minimap())
+                .await
+                .ok_or_toast();
         };
 
         while let Some(message) = rx.next().await {
             match message {
                 ActionUpdate::Set => {
-                    update_minimap(minimap_preset(), minimap()).await;
+                    update_minimap(minimap_preset(), minimap())
+                        .await
+                        .ok_or_toast();
                 }
                 ActionUpdate::Create(preset) => {
                     let Some(mut current_minimap) = minimap() else {
@@ -148,6 +175,37 @@ pub fn Actions() -> Element {
         }
     });
     let mut popup_input_kind = use_signal(|| None);
+    let mut route_click = use_context::<AppState>().route_click;
+    let route_place_key = use_signal(|| false);
+
+    // Opens a new action popup at the clicked minimap position
+    use_effect(move || {
+        let Some((x, y)) = route_click() else {
+            return;
+        };
+        route_click.set(None);
+        if minimap_preset.peek().is_none() || popup_input_kind.peek().is_some() {
+            return;
+        }
+
+        let position = Position {
+            x,
+            y,
+            ..Position::default()
+        };
+        let action = if *route_place_key.peek() {
+            Action::Key(ActionKey {
+                position: Some(position),
+                ..ActionKey::default()
+            })
+        } else {
+            Action::Move(ActionMove {
+                position,
+                ..ActionMove::default()
+            })
+        };
+        popup_input_kind.set(Some(PopupInputKind::Action(ActionInputKind::Add(action))));
+    });
 
     // Add/edit action callbacks
     let add_action = use_callback(move |(action, condition): (Action, ActionCondition)| {
@@ -192,7 +250,9 @@ pub fn Actions() -> Element {
         let mut minimap = minimap_view();
 
         match minimap.rotation_mode {
-            RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => return,
+            RotationMode::StartToEnd
+            | RotationMode::StartToEndThenReverse
+            | RotationMode::Shuffled => return,
             RotationMode::AutoMobbing => {
                 minimap.rotation_auto_mob_bound = bound;
             }
@@ -203,6 +263,57 @@ pub fn Actions() -> Element {
         coroutine.send(ActionUpdate::UpdateMinimap(minimap));
     });
 
+    // Add, edit auto-mobbing sequence sub-bound callbacks
+    let add_sequence_bound = use_callback(move |bound| {
+        let mut minimap = minimap_view();
+
+        minimap.rotation_auto_mob_sequence.push(bound);
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+    let edit_sequence_bound = use_callback(move |(new_bound, index): (Bound, usize)| {
+        let mut minimap = minimap_view();
+        let Some(bound) = minimap.rotation_auto_mob_sequence.get_mut(index) else {
+            return;
+        };
+
+        *bound = new_bound;
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+
+    // Add, edit auto-mobbing region callbacks
+    let add_auto_mob_region = use_callback(move |region| {
+        let mut minimap = minimap_view();
+
+        minimap.rotation_auto_mob_regions.push(region);
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+    let edit_auto_mob_region = use_callback(move |(new_region, index): (AutoMobRegion, usize)| {
+        let mut minimap = minimap_view();
+        let Some(region) = minimap.rotation_auto_mob_regions.get_mut(index) else {
+            return;
+        };
+
+        *region = new_region;
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+
+    // Add, edit ping pong band callbacks
+    let add_ping_pong_band = use_callback(move |band| {
+        let mut minimap = minimap_view();
+
+        minimap.rotation_ping_pong_bands.push(band);
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+    let edit_ping_pong_band = use_callback(move |(new_band, index): (PingPongBand, usize)| {
+        let mut minimap = minimap_view();
+        let Some(band) = minimap.rotation_ping_pong_bands.get_mut(index) else {
+            return;
+        };
+
+        *band = new_band;
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+
     //Add, edit platform callbacks
     let add_platform = use_callback(move |platform| {
         let mut minimap = minimap_view();
@@ -225,13 +336,33 @@ pub fn Actions() -> Element {
             SectionRotation {
                 popup_input_kind,
                 minimap_view,
+                minimap_presets,
                 disabled: minimap().is_none(),
             }
+            SectionLinkedCharacter { minimap_view, disabled: minimap().is_none() }
             SectionPlatforms {
                 popup_input_kind,
                 minimap_view,
                 disabled: minimap().is_none(),
             }
+            SectionRopes { minimap_view, disabled: minimap().is_none() }
+            SectionNoGoZones { minimap_view, disabled: minimap().is_none() }
+            SectionMovementOverrides { minimap_view, disabled: minimap().is_none() }
+            SectionBreakPosition { minimap_view, disabled: minimap().is_none() }
+            SectionCaptureOverride { minimap_view, disabled: minimap().is_none() }
+            SectionAvoidanceZones { minimap_view, disabled: minimap().is_none() }
+            SectionRouteEditor {
+                route_place_key,
+                disabled: minimap().is_none() || minimap_preset().is_none(),
+            }
+            SectionPresetSchedule {
+                minimap_view,
+                minimap_presets,
+                disabled: minimap().is_none() || minimap_presets().is_empty(),
+            }
+            if !minimap_preset_issues().is_empty() {
+                SectionPresetIssues { minimap_preset_issues }
+            }
             SectionActions {
                 popup_input_kind,
                 minimap_preset_actions,
@@ -269,6 +400,7 @@ pub fn Actions() -> Element {
                 },
                 PopupInputKind::Bound(bound) => rsx! {
                     PopupBoundInput {
+                        title: "Modify mobbing bound",
                         on_cancel: move |_| {
                             popup_input_kind.take();
                         },
@@ -279,6 +411,61 @@ pub fn Actions() -> Element {
                         value: bound,
                     }
                 },
+                PopupInputKind::SequenceBound(bound, index) => rsx! {
+                    PopupBoundInput {
+                        title: "Modify auto-mobbing sub-bound",
+                        on_cancel: move |_| {
+                            popup_input_kind.take();
+                        },
+                        on_value: move |bound| {
+                            popup_input_kind.take();
+                            if let Some(index) = index {
+                                edit_sequence_bound((bound, index));
+                            } else {
+                                add_sequence_bound(bound);
+                            }
+                        },
+                        value: bound,
+                    }
+                },
+                PopupInputKind::AutoMobRegion(index) => rsx! {
+                    PopupAutoMobRegionInput {
+                        index,
+                        on_cancel: move |_| {
+                            popup_input_kind.take();
+                        },
+                        on_value: move |(region, index): (AutoMobRegion, Option<usize>)| {
+                            popup_input_kind.take();
+                            if let Some(index) = index {
+                                edit_auto_mob_region((region, index));
+                            } else {
+                                add_auto_mob_region(region);
+                            }
+                        },
+                        value: index
+                            .and_then(|index| {
+                                minimap_view().rotation_auto_mob_regions.get(index).cloned()
+                            })
+                            .unwrap_or_default(),
+                    }
+                },
+                PopupInputKind::PingPongBand(band, index) => rsx! {
+                    PopupPingPongBandInput {
+                        index,
+                        on_cancel: move |_| {
+                            popup_input_kind.take();
+                        },
+                        on_value: move |(band, index): (PingPongBand, Option<usize>)| {
+                            popup_input_kind.take();
+                            if let Some(index) = index {
+                                edit_ping_pong_band((band, index));
+                            } else {
+                                add_ping_pong_band(band);
+                            }
+                        },
+                        value: band,
+                    }
+                },
                 PopupInputKind::Platform(platform, index) => {
                     rsx! {
                         PopupPlatformInput {
@@ -343,8 +530,126 @@ fn Section(
 fn SectionRotation(
     popup_input_kind: Signal<Option<PopupInputKind>>,
     minimap_view: Memo<Minimap>,
+    minimap_presets: Memo<Vec<String>>,
     disabled: bool,
 ) -> Element {
+    #[component]
+    fn SequenceBoundItem(
+        index: usize,
+        bound: Bound,
+        on_item_click: EventHandler,
+        on_item_delete: EventHandler,
+    ) -> Element {
+        const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+        const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+        rsx! {
+            div { class: "relative group",
+                div {
+                    class: "grid grid-cols-3 h-6 paragraph-xs gap-2 !text-gray-400 group-hover:bg-gray-900",
+                    onclick: move |e| {
+                        e.stop_propagation();
+                        on_item_click(());
+                    },
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", {format!("#{}", index + 1)} }
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                        {format!("X / {} - {}", bound.x, bound.x + bound.width)}
+                    }
+                    div { class: "{ITEM_TEXT_CLASS}",
+                        {format!("Y / {} - {}", bound.y, bound.y + bound.height)}
+                    }
+                }
+                div { class: "absolute invisible group-hover:visible top-0 right-1 flex",
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            on_item_delete(());
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+        }
+    }
+
+    #[component]
+    fn AutoMobRegionItem(
+        region: AutoMobRegion,
+        on_item_click: EventHandler,
+        on_item_delete: EventHandler,
+    ) -> Element {
+        const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+        const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+        let bound = region.bound;
+
+        rsx! {
+            div { class: "relative group",
+                div {
+                    class: "grid grid-cols-4 h-6 paragraph-xs gap-2 !text-gray-400 group-hover:bg-gray-900",
+                    onclick: move |e| {
+                        e.stop_propagation();
+                        on_item_click(());
+                    },
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", {region.name} }
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                        {format!("X / {} - {}", bound.x, bound.x + bound.width)}
+                    }
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                        {format!("Y / {} - {}", bound.y, bound.y + bound.height)}
+                    }
+                    div { class: "{ITEM_TEXT_CLASS}", {format!("Weight / {}", region.weight)} }
+                }
+                div { class: "absolute invisible group-hover:visible top-0 right-1 flex",
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            on_item_delete(());
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+        }
+    }
+
+    #[component]
+    fn PingPongBandItem(
+        band: PingPongBand,
+        on_item_click: EventHandler,
+        on_item_delete: EventHandler,
+    ) -> Element {
+        const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+        const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+        rsx! {
+            div { class: "relative group",
+                div {
+                    class: "grid grid-cols-2 h-6 paragraph-xs gap-2 !text-gray-400 group-hover:bg-gray-900",
+                    onclick: move |e| {
+                        e.stop_propagation();
+                        on_item_click(());
+                    },
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                        {format!("Y / {} - {}", band.y_start, band.y_end)}
+                    }
+                    div { class: "{ITEM_TEXT_CLASS}", {format!("{}%", band.probability)} }
+                }
+                div { class: "absolute invisible group-hover:visible top-0 right-1 flex",
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            on_item_delete(());
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+        }
+    }
+
     let update_mobbing_button_disabled = use_memo(move || {
         !matches!(
             minimap_view().rotation_mode,
@@ -370,7 +675,17 @@ fn SectionRotation(
                     },
                     selected: minimap_view().rotation_mode,
                 }
-                div {}
+                ActionsSelect::<AutoMobStrategy> {
+                    label: "Auto-mobbing strategy",
+                    disabled: disabled || minimap_view().rotation_mode != RotationMode::AutoMobbing,
+                    on_select: move |rotation_auto_mob_strategy| {
+                        save_minimap(Minimap {
+                            rotation_auto_mob_strategy,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    selected: minimap_view().rotation_auto_mob_strategy,
+                }
                 Button {
                     text: "Update mobbing key",
                     kind: ButtonKind::Primary,
@@ -378,7 +693,9 @@ fn SectionRotation(
                     on_click: move |_| {
                         let minimap = minimap_view.peek();
                         let key = match minimap.rotation_mode {
-                            RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => {
+                            RotationMode::StartToEnd
+                            | RotationMode::StartToEndThenReverse
+                            | RotationMode::Shuffled => {
                                 unreachable!()
                             }
                             RotationMode::AutoMobbing | RotationMode::PingPong => {
@@ -396,7 +713,9 @@ fn SectionRotation(
                     on_click: move |_| {
                         let minimap = minimap_view.peek();
                         let bound = match minimap.rotation_mode {
-                            RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => {
+                            RotationMode::StartToEnd
+                            | RotationMode::StartToEndThenReverse
+                            | RotationMode::Shuffled => {
                                 unreachable!()
                             }
                             RotationMode::AutoMobbing => minimap.rotation_auto_mob_bound,
@@ -416,6 +735,146 @@ fn SectionRotation(
                     },
                     value: minimap_view().actions_any_reset_on_erda_condition,
                 }
+                ActionsCheckbox {
+                    label: "Pick up drops while auto-mobbing",
+                    disabled: disabled || minimap_view().rotation_mode != RotationMode::AutoMobbing,
+                    on_value: move |auto_mob_pickup_drops| {
+                        save_minimap(Minimap {
+                            auto_mob_pickup_drops,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().auto_mob_pickup_drops,
+                }
+                Select::<String> {
+                    label: "Elite boss fight preset",
+                    disabled,
+                    options: iter::once("None".to_string()).chain(minimap_presets()).collect::<Vec<_>>(),
+                    on_select: move |(index, preset): (usize, String)| {
+                        save_minimap(Minimap {
+                            elite_boss_fight_preset: (index > 0).then_some(preset),
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    selected: minimap_view()
+                        .elite_boss_fight_preset
+                        .and_then(|preset| {
+                            minimap_presets().iter().position(|option| option == &preset)
+                        })
+                        .map(|index| index + 1)
+                        .unwrap_or_default(),
+                }
+                ActionsSelect::<AutoMobRegionOrder> {
+                    label: "Auto-mobbing region order",
+                    disabled: disabled || minimap_view().rotation_mode != RotationMode::AutoMobbing,
+                    on_select: move |rotation_auto_mob_region_order| {
+                        save_minimap(Minimap {
+                            rotation_auto_mob_region_order,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    selected: minimap_view().rotation_auto_mob_region_order,
+                }
+            }
+            if minimap_view().rotation_mode == RotationMode::AutoMobbing {
+                if !minimap_view().rotation_auto_mob_regions.is_empty() {
+                    div { class: "mt-2" }
+                }
+                for (index , region) in minimap_view()
+                    .rotation_auto_mob_regions
+                    .into_iter()
+                    .enumerate()
+                {
+                    AutoMobRegionItem {
+                        region,
+                        on_item_click: move |_| {
+                            popup_input_kind.set(Some(PopupInputKind::AutoMobRegion(Some(index))));
+                        },
+                        on_item_delete: move |_| {
+                            let mut minimap = minimap_view.peek().clone();
+                            minimap.rotation_auto_mob_regions.remove(index);
+                            save_minimap(minimap);
+                        },
+                    }
+                }
+                Button {
+                    text: "Add auto-mobbing region",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        popup_input_kind.set(Some(PopupInputKind::AutoMobRegion(None)));
+                    },
+                    disabled,
+                    class: "label mt-2",
+                }
+            }
+            if minimap_view().rotation_mode == RotationMode::AutoMobbing
+                && minimap_view().rotation_auto_mob_strategy == AutoMobStrategy::Sequence
+            {
+                if !minimap_view().rotation_auto_mob_sequence.is_empty() {
+                    div { class: "mt-2" }
+                }
+                for (index , bound) in minimap_view()
+                    .rotation_auto_mob_sequence
+                    .into_iter()
+                    .enumerate()
+                {
+                    SequenceBoundItem {
+                        index,
+                        bound,
+                        on_item_click: move |_| {
+                            popup_input_kind
+                                .set(Some(PopupInputKind::SequenceBound(bound, Some(index))));
+                        },
+                        on_item_delete: move |_| {
+                            let mut minimap = minimap_view.peek().clone();
+                            minimap.rotation_auto_mob_sequence.remove(index);
+                            save_minimap(minimap);
+                        },
+                    }
+                }
+                Button {
+                    text: "Add auto-mobbing sub-bound",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        let kind = PopupInputKind::SequenceBound(Bound::default(), None);
+                        popup_input_kind.set(Some(kind));
+                    },
+                    disabled,
+                    class: "label mt-2",
+                }
+            }
+            if minimap_view().rotation_mode == RotationMode::PingPong {
+                if !minimap_view().rotation_ping_pong_bands.is_empty() {
+                    div { class: "mt-2" }
+                }
+                for (index , band) in minimap_view()
+                    .rotation_ping_pong_bands
+                    .into_iter()
+                    .enumerate()
+                {
+                    PingPongBandItem {
+                        band,
+                        on_item_click: move |_| {
+                            popup_input_kind
+                                .set(Some(PopupInputKind::PingPongBand(band, Some(index))));
+                        },
+                        on_item_delete: move |_| {
+                            let mut minimap = minimap_view.peek().clone();
+                            minimap.rotation_ping_pong_bands.remove(index);
+                            save_minimap(minimap);
+                        },
+                    }
+                }
+                Button {
+                    text: "Add ping-pong band",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        let kind = PopupInputKind::PingPongBand(PingPongBand::default(), None);
+                        popup_input_kind.set(Some(kind));
+                    },
+                    disabled,
+                    class: "label mt-2",
+                }
             }
         }
     }
@@ -486,7 +945,9 @@ fn SectionPlatforms(
 
     use_future(move || async move {
         let mut platform = Platform::default();
-        let mut key_receiver = key_receiver().await;
+        let Some(mut key_receiver) = key_receiver().await.ok_or_toast() else {
+            return;
+        };
         loop {
             let Ok(key) = key_receiver.recv().await else {
                 continue;
@@ -606,13 +1067,848 @@ fn SectionPlatforms(
 }
 
 #[component]
-fn SectionLegends() -> Element {
+fn SectionLinkedCharacter(minimap_view: Memo<Minimap>, disabled: bool) -> Element {
+    let characters =
+        use_resource(async || query_characters().await.ok_or_toast().unwrap_or_default());
+    let character_names = use_memo(move || {
+        iter::once("None".to_string())
+            .chain(
+                characters()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|character| character.name),
+            )
+            .collect::<Vec<_>>()
+    });
+    let character_index = use_memo(move || {
+        let character_id = minimap_view().character_id;
+        characters()
+            .unwrap_or_default()
+            .into_iter()
+            .position(|character| character.id.is_some() && character.id == character_id)
+            .map(|index| index + 1)
+            .unwrap_or_default()
+    });
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+
     rsx! {
-        Section { name: "Action Legends", class: "paragraph-xs",
-            p { "⟳ - Repeat" }
-            p { "⏱︎  - Wait" }
-            p { "ㄨ - No position" }
-            p { "⇈ - Queue to front" }
+        Section { name: "Linked character",
+            p { class: "paragraph-xs",
+                "The linked character is loaded automatically when this map is selected."
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                Select::<String> {
+                    label: "Character",
+                    disabled,
+                    options: character_names(),
+                    on_select: move |(index, _): (usize, String)| {
+                        let character_id = index.checked_sub(1).and_then(|index| {
+                            characters
+                                .peek()
+                                .as_ref()
+                                .and_then(|characters| characters.get(index))
+                                .and_then(|character| character.id)
+                        });
+                        let minimap = Minimap {
+                            character_id,
+                            ..minimap_view.peek().clone()
+                        };
+                        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+                    },
+                    selected: character_index(),
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionRopes(minimap_view: Memo<Minimap>, disabled: bool) -> Element {
+    #[component]
+    fn RopeItem(rope: Rope, on_item_delete: EventHandler) -> Element {
+        const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+        const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+        rsx! {
+            div { class: "relative group",
+                div { class: "grid grid-cols-2 h-6 paragraph-xs gap-2 !text-gray-400 group-hover:bg-gray-900",
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", {format!("X / {}", rope.x)} }
+                    div { class: "{ITEM_TEXT_CLASS}",
+                        {format!("Y / {} - {}", rope.y_start, rope.y_end)}
+                    }
+                }
+                div { class: "absolute invisible group-hover:visible top-0 right-1 flex",
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            on_item_delete(());
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+        }
+    }
+
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let position = use_context::<AppState>().position;
+    let mut rope = use_signal(Rope::default);
+
+    let add_rope = use_callback(move |rope| {
+        let mut minimap = minimap_view();
+
+        minimap.ropes.push(rope);
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+    let delete_rope = use_callback(move |index| {
+        let mut minimap = minimap_view();
+
+        minimap.ropes.remove(index);
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+
+    rsx! {
+        Section { name: "Ropes",
+            p { class: "paragraph-xs",
+                "Ropes and ladders are climbed by platforms pathing to reach platforms that cannot be grappled or up jumped to."
+            }
+            div { class: "grid grid-cols-3 gap-3",
+                NumberInputI32 {
+                    label: "X",
+                    disabled,
+                    on_value: move |x| {
+                        rope.write().x = x;
+                    },
+                    value: rope().x,
+                }
+                NumberInputI32 {
+                    label: "Y start",
+                    disabled,
+                    on_value: move |y_start| {
+                        rope.write().y_start = y_start;
+                    },
+                    value: rope().y_start,
+                }
+                NumberInputI32 {
+                    label: "Y end",
+                    disabled,
+                    on_value: move |y_end| {
+                        rope.write().y_end = y_end;
+                    },
+                    value: rope().y_end,
+                }
+                Button {
+                    text: "Set start",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        let (x, y) = *position.peek();
+                        let mut rope = rope.write();
+                        rope.x = x;
+                        rope.y_start = y;
+                    },
+                    disabled,
+                    class: "label",
+                }
+                Button {
+                    text: "Set end",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        rope.write().y_end = position.peek().1;
+                    },
+                    disabled,
+                    class: "label",
+                }
+                Button {
+                    text: "Add rope",
+                    kind: ButtonKind::Primary,
+                    on_click: move |_| {
+                        add_rope(*rope.peek());
+                    },
+                    disabled: disabled || rope().y_start == rope().y_end,
+                    class: "label",
+                }
+            }
+            if !minimap_view().ropes.is_empty() {
+                div { class: "mt-2" }
+            }
+            for (index , rope) in minimap_view().ropes.into_iter().enumerate() {
+                RopeItem {
+                    rope,
+                    on_item_delete: move |_| {
+                        delete_rope(index);
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionNoGoZones(minimap_view: Memo<Minimap>, disabled: bool) -> Element {
+    #[component]
+    fn NoGoZoneItem(zone: Bound, on_item_delete: EventHandler) -> Element {
+        const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+        const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+        rsx! {
+            div { class: "relative group",
+                div { class: "grid grid-cols-2 h-6 paragraph-xs gap-2 !text-gray-400 group-hover:bg-gray-900",
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                        {format!("X / {} - {}", zone.x, zone.x + zone.width)}
+                    }
+                    div { class: "{ITEM_TEXT_CLASS}",
+                        {format!("Y / {} - {}", zone.y, zone.y + zone.height)}
+                    }
+                }
+                div { class: "absolute invisible group-hover:visible top-0 right-1 flex",
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            on_item_delete(());
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+        }
+    }
+
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let position = use_context::<AppState>().position;
+    let mut zone = use_signal(Bound::default);
+
+    let add_zone = use_callback(move |zone| {
+        let mut minimap = minimap_view();
+
+        minimap.no_go_zones.push(zone);
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+    let delete_zone = use_callback(move |index| {
+        let mut minimap = minimap_view();
+
+        minimap.no_go_zones.remove(index);
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+
+    rsx! {
+        Section { name: "No-go zones",
+            p { class: "paragraph-xs",
+                "Rectangles relative to the minimap top-left that platforms pathing and auto-mobbing never move the player through or into."
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                NumberInputI32 {
+                    label: "X",
+                    disabled,
+                    on_value: move |x| {
+                        zone.write().x = x;
+                    },
+                    value: zone().x,
+                }
+                NumberInputI32 {
+                    label: "Y",
+                    disabled,
+                    on_value: move |y| {
+                        zone.write().y = y;
+                    },
+                    value: zone().y,
+                }
+                NumberInputI32 {
+                    label: "Width",
+                    disabled,
+                    on_value: move |width| {
+                        zone.write().width = width;
+                    },
+                    value: zone().width,
+                }
+                NumberInputI32 {
+                    label: "Height",
+                    disabled,
+                    on_value: move |height| {
+                        zone.write().height = height;
+                    },
+                    value: zone().height,
+                }
+                Button {
+                    text: "Set top-left",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        let (x, y) = *position.peek();
+                        let mut zone = zone.write();
+                        zone.x = x;
+                        zone.y = minimap_view.peek().height - y;
+                    },
+                    disabled,
+                    class: "label",
+                }
+                Button {
+                    text: "Set bottom-right",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        let (x, y) = *position.peek();
+                        let mut zone = zone.write();
+                        zone.width = x - zone.x;
+                        zone.height = minimap_view.peek().height - y - zone.y;
+                    },
+                    disabled,
+                    class: "label",
+                }
+                Button {
+                    text: "Add no-go zone",
+                    kind: ButtonKind::Primary,
+                    on_click: move |_| {
+                        add_zone(*zone.peek());
+                    },
+                    disabled: disabled || zone().width <= 0 || zone().height <= 0,
+                    class: "label col-span-2",
+                }
+            }
+            if !minimap_view().no_go_zones.is_empty() {
+                div { class: "mt-2" }
+            }
+            for (index , zone) in minimap_view().no_go_zones.into_iter().enumerate() {
+                NoGoZoneItem {
+                    zone,
+                    on_item_delete: move |_| {
+                        delete_zone(index);
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionMovementOverrides(minimap_view: Memo<Minimap>, disabled: bool) -> Element {
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let overrides = use_memo(move || minimap_view().movement_overrides);
+    let save_overrides = move |movement_overrides: MovementOverrides| {
+        let minimap = Minimap {
+            movement_overrides,
+            ..minimap_view.peek().clone()
+        };
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    };
+
+    rsx! {
+        Section { name: "Movement overrides",
+            p { class: "paragraph-xs",
+                "Overrides the movement thresholds for maps with unusual platform spacing. A value of 0 uses the default."
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                NumberInputI32 {
+                    label: "Double jump threshold",
+                    disabled,
+                    on_value: move |value: i32| {
+                        save_overrides(MovementOverrides {
+                            double_jump_threshold: (value > 0).then_some(value),
+                            ..*overrides.peek()
+                        });
+                    },
+                    value: overrides().double_jump_threshold.unwrap_or_default(),
+                }
+                NumberInputI32 {
+                    label: "Jump threshold",
+                    disabled,
+                    on_value: move |value: i32| {
+                        save_overrides(MovementOverrides {
+                            jump_threshold: (value > 0).then_some(value),
+                            ..*overrides.peek()
+                        });
+                    },
+                    value: overrides().jump_threshold.unwrap_or_default(),
+                }
+                NumberInputI32 {
+                    label: "Grappling threshold",
+                    disabled,
+                    on_value: move |value: i32| {
+                        save_overrides(MovementOverrides {
+                            grappling_threshold: (value > 0).then_some(value),
+                            ..*overrides.peek()
+                        });
+                    },
+                    value: overrides().grappling_threshold.unwrap_or_default(),
+                }
+                NumberInputI32 {
+                    label: "Grappling max threshold",
+                    disabled,
+                    on_value: move |value: i32| {
+                        save_overrides(MovementOverrides {
+                            grappling_max_threshold: (value > 0).then_some(value),
+                            ..*overrides.peek()
+                        });
+                    },
+                    value: overrides().grappling_max_threshold.unwrap_or_default(),
+                }
+                NumberInputU32 {
+                    label: "Move timeout ticks",
+                    minimum_value: 0,
+                    disabled,
+                    on_value: move |value: u32| {
+                        save_overrides(MovementOverrides {
+                            move_timeout: (value > 0).then_some(value),
+                            ..*overrides.peek()
+                        });
+                    },
+                    value: overrides().move_timeout.unwrap_or_default(),
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionBreakPosition(minimap_view: Memo<Minimap>, disabled: bool) -> Element {
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let position = use_context::<AppState>().position;
+    let break_position = use_memo(move || minimap_view().break_position);
+    let save_break_position = move |break_position: Option<Position>| {
+        let minimap = Minimap {
+            break_position,
+            ..minimap_view.peek().clone()
+        };
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    };
+    let value = use_memo(move || break_position().unwrap_or_default());
+
+    rsx! {
+        Section { name: "Break position",
+            p { class: "paragraph-xs",
+                "The position the player walks to before taking a scheduled break."
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                ActionsCheckbox {
+                    label: "Enabled",
+                    disabled,
+                    on_value: move |enabled: bool| {
+                        save_break_position(enabled.then(|| *value.peek()));
+                    },
+                    value: break_position().is_some(),
+                }
+                div {}
+                NumberInputI32 {
+                    label: "X",
+                    disabled: disabled || break_position().is_none(),
+                    on_value: move |x| {
+                        save_break_position(Some(Position { x, ..*value.peek() }));
+                    },
+                    value: value().x,
+                }
+                NumberInputI32 {
+                    label: "Y",
+                    disabled: disabled || break_position().is_none(),
+                    on_value: move |y| {
+                        save_break_position(Some(Position { y, ..*value.peek() }));
+                    },
+                    value: value().y,
+                }
+                Button {
+                    text: "Set to player position",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        let (x, y) = *position.peek();
+                        save_break_position(Some(Position { x, y, ..*value.peek() }));
+                    },
+                    disabled: disabled || break_position().is_none(),
+                    class: "label col-span-2",
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionCaptureOverride(minimap_view: Memo<Minimap>, disabled: bool) -> Element {
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let settings = use_context::<AppState>().settings;
+    let capture_override = use_memo(move || minimap_view().capture_override);
+    let save_capture_override = move |capture_override: Option<CaptureOverride>| {
+        let minimap = Minimap {
+            capture_override,
+            ..minimap_view.peek().clone()
+        };
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    };
+    let value = use_memo(move || capture_override().unwrap_or_default());
+    let area_disabled = use_memo(move || {
+        capture_override().is_none_or(|capture| capture.mode != CaptureMode::BitBltArea)
+    });
+
+    rsx! {
+        Section { name: "Capture override",
+            p { class: "paragraph-xs",
+                "Uses this capture mode and area instead of the ones in settings while this map is selected."
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                ActionsCheckbox {
+                    label: "Enabled",
+                    disabled,
+                    on_value: move |enabled: bool| {
+                        let capture = settings
+                            .peek()
+                            .as_ref()
+                            .map(|settings| CaptureOverride {
+                                mode: settings.capture_mode,
+                                x: settings.capture_x,
+                                y: settings.capture_y,
+                                width: settings.capture_width,
+                                height: settings.capture_height,
+                            })
+                            .unwrap_or_default();
+                        save_capture_override(enabled.then_some(capture));
+                    },
+                    value: capture_override().is_some(),
+                }
+                ActionsSelect::<CaptureMode> {
+                    label: "Mode",
+                    disabled: disabled || capture_override().is_none(),
+                    on_select: move |mode| {
+                        save_capture_override(Some(CaptureOverride { mode, ..*value.peek() }));
+                    },
+                    selected: value().mode,
+                }
+                NumberInputI32 {
+                    label: "X",
+                    disabled: disabled || area_disabled(),
+                    on_value: move |x| {
+                        save_capture_override(Some(CaptureOverride { x, ..*value.peek() }));
+                    },
+                    value: value().x,
+                }
+                NumberInputI32 {
+                    label: "Y",
+                    disabled: disabled || area_disabled(),
+                    on_value: move |y| {
+                        save_capture_override(Some(CaptureOverride { y, ..*value.peek() }));
+                    },
+                    value: value().y,
+                }
+                NumberInputI32 {
+                    label: "Width",
+                    disabled: disabled || area_disabled(),
+                    on_value: move |width| {
+                        save_capture_override(Some(CaptureOverride { width, ..*value.peek() }));
+                    },
+                    value: value().width,
+                }
+                NumberInputI32 {
+                    label: "Height",
+                    disabled: disabled || area_disabled(),
+                    on_value: move |height| {
+                        save_capture_override(Some(CaptureOverride { height, ..*value.peek() }));
+                    },
+                    value: value().height,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionAvoidanceZones(minimap_view: Memo<Minimap>, disabled: bool) -> Element {
+    #[component]
+    fn AvoidanceZoneItem(zone: AvoidanceZone, on_item_delete: EventHandler) -> Element {
+        const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+        const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+        rsx! {
+            div { class: "relative group",
+                div { class: "grid grid-cols-2 h-6 paragraph-xs gap-2 !text-gray-400 group-hover:bg-gray-900",
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", {format!("X / {}", zone.x)} }
+                    div { class: "{ITEM_TEXT_CLASS}", {format!("Y / {}", zone.y)} }
+                }
+                div { class: "absolute invisible group-hover:visible top-0 right-1 flex",
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            on_item_delete(());
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+        }
+    }
+
+    let minimap_id = use_memo(move || minimap_view().id);
+    let mut zones = use_resource(move || async move {
+        match minimap_id() {
+            Some(id) => query_avoidance_zones(id)
+                .await
+                .ok_or_toast()
+                .unwrap_or_default(),
+            None => Vec::new(),
+        }
+    });
+
+    rsx! {
+        Section { name: "Avoidance zones",
+            p { class: "paragraph-xs",
+                "Positions where the player repeatedly got stuck are learned and platforms nearby are routed around by platforms pathing."
+            }
+            for zone in zones().unwrap_or_default() {
+                AvoidanceZoneItem {
+                    zone,
+                    on_item_delete: move |_| async move {
+                        if delete_avoidance_zone(zone).await.ok_or_toast().is_some() {
+                            update_avoidance_zones().await.ok_or_toast();
+                        }
+                        zones.restart();
+                    },
+                }
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                Button {
+                    text: "Refresh",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        zones.restart();
+                    },
+                    disabled,
+                    class: "label",
+                }
+                Button {
+                    text: "Clear all",
+                    kind: ButtonKind::Danger,
+                    on_click: move |_| async move {
+                        let Some(id) = *minimap_id.peek() else {
+                            return;
+                        };
+                        if clear_avoidance_zones(id).await.ok_or_toast().is_some() {
+                            update_avoidance_zones().await.ok_or_toast();
+                        }
+                        zones.restart();
+                    },
+                    disabled: disabled || zones().is_none_or(|zones| zones.is_empty()),
+                    class: "label",
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionRouteEditor(route_place_key: Signal<bool>, disabled: bool) -> Element {
+    let mut route_editing = use_context::<AppState>().route_editing;
+
+    use_effect(use_reactive!(|disabled| {
+        if disabled {
+            route_editing.set(false);
+        }
+    }));
+    use_drop(move || {
+        route_editing.set(false);
+    });
+
+    rsx! {
+        Section { name: "Route editor",
+            div { class: "grid grid-cols-2 gap-3",
+                ActionsCheckbox {
+                    label: "Click minimap to place actions",
+                    disabled,
+                    on_value: move |value| {
+                        route_editing.set(value);
+                    },
+                    value: route_editing(),
+                }
+                ActionsCheckbox {
+                    label: "Place as key action",
+                    disabled,
+                    on_value: move |value| {
+                        route_place_key.set(value);
+                    },
+                    value: route_place_key(),
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionPresetSchedule(
+    minimap_view: Memo<Minimap>,
+    minimap_presets: Memo<Vec<String>>,
+    disabled: bool,
+) -> Element {
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let save_minimap = use_callback(move |new_minimap: Minimap| {
+        coroutine.send(ActionUpdate::UpdateMinimap(new_minimap));
+    });
+    let add_entry = use_callback(move |_| {
+        let mut minimap = minimap_view();
+        let Some(preset) = minimap_presets.peek().first().cloned() else {
+            return;
+        };
+
+        minimap.preset_schedule.entries.push(PresetScheduleEntry {
+            preset,
+            duration_millis: 1_800_000,
+            weight: 1,
+        });
+        save_minimap(minimap);
+    });
+    let edit_entry = use_callback(move |(new_entry, index): (PresetScheduleEntry, usize)| {
+        let mut minimap = minimap_view();
+        let Some(entry) = minimap.preset_schedule.entries.get_mut(index) else {
+            return;
+        };
+
+        *entry = new_entry;
+        save_minimap(minimap);
+    });
+    let delete_entry = use_callback(move |index| {
+        let mut minimap = minimap_view();
+
+        minimap.preset_schedule.entries.remove(index);
+        save_minimap(minimap);
+    });
+
+    rsx! {
+        Section { name: "Preset schedule",
+            div { class: "grid grid-cols-2 gap-3",
+                ActionsCheckbox {
+                    label: "Enabled",
+                    disabled,
+                    on_value: move |enabled| {
+                        let mut minimap = minimap_view.peek().clone();
+                        minimap.preset_schedule.enabled = enabled;
+                        save_minimap(minimap);
+                    },
+                    value: minimap_view().preset_schedule.enabled,
+                }
+                ActionsCheckbox {
+                    label: "Random by weight",
+                    disabled,
+                    on_value: move |random| {
+                        let mut minimap = minimap_view.peek().clone();
+                        minimap.preset_schedule.random = random;
+                        save_minimap(minimap);
+                    },
+                    value: minimap_view().preset_schedule.random,
+                }
+            }
+            for (index , entry) in minimap_view().preset_schedule.entries.into_iter().enumerate() {
+                div { class: "grid grid-cols-[1fr_1fr_1fr_auto] gap-3 items-end",
+                    Select::<String> {
+                        label: "Preset",
+                        disabled,
+                        options: minimap_presets(),
+                        on_select: {
+                            let entry = entry.clone();
+                            move |(_, preset): (usize, String)| {
+                                edit_entry((
+                                    PresetScheduleEntry {
+                                        preset,
+                                        ..entry.clone()
+                                    },
+                                    index,
+                                ));
+                            }
+                        },
+                        selected: minimap_presets()
+                            .iter()
+                            .position(|preset| preset == &entry.preset)
+                            .unwrap_or_default(),
+                    }
+                    ActionsMillisInput {
+                        label: "Duration",
+                        on_value: {
+                            let entry = entry.clone();
+                            move |duration_millis| {
+                                edit_entry((
+                                    PresetScheduleEntry {
+                                        duration_millis,
+                                        ..entry.clone()
+                                    },
+                                    index,
+                                ));
+                            }
+                        },
+                        value: entry.duration_millis,
+                    }
+                    NumberInputU32 {
+                        label: "Weight",
+                        minimum_value: 0,
+                        disabled: disabled || !minimap_view().preset_schedule.random,
+                        on_value: {
+                            let entry = entry.clone();
+                            move |weight| {
+                                edit_entry((
+                                    PresetScheduleEntry {
+                                        weight,
+                                        ..entry.clone()
+                                    },
+                                    index,
+                                ));
+                            }
+                        },
+                        value: entry.weight,
+                    }
+                    div {
+                        class: "w-4 h-6 flex justify-center items-center",
+                        onclick: move |_| {
+                            delete_entry(index);
+                        },
+                        XIcon { class: "w-[11px] h-[11px] fill-current text-red-500" }
+                    }
+                }
+            }
+            Button {
+                text: "Add preset",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    add_entry(());
+                },
+                disabled,
+                class: "label mt-2",
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionPresetIssues(minimap_preset_issues: Memo<Vec<PresetIssue>>) -> Element {
+    let message = |issue| match issue {
+        PresetIssue::NoPlatforms => {
+            "Map has no platforms to validate action positions against".to_string()
+        }
+        PresetIssue::MissingPlatform { index, position } => format!(
+            "Action #{} at ({}, {}) is not on any platform",
+            index + 1,
+            position.x,
+            position.y
+        ),
+        PresetIssue::Unreachable {
+            index,
+            position,
+            unreachable_count,
+        } => format!(
+            "Action #{} at ({}, {}) cannot be reached from {unreachable_count} platform(s)",
+            index + 1,
+            position.x,
+            position.y
+        ),
+    };
+
+    rsx! {
+        Section { name: "Preset warnings", class: "paragraph-xs text-yellow-500",
+            for issue in minimap_preset_issues() {
+                p { {message(issue)} }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionLegends() -> Element {
+    rsx! {
+        Section { name: "Action Legends", class: "paragraph-xs",
+            p { "⟳ - Repeat" }
+            p { "⏱︎  - Wait" }
+            p { "ㄨ - No position" }
+            p { "⇈ - Queue to front" }
             p { "⇆ - Any direction" }
             p { "← - Left direction" }
             p { "→ - Right direction" }
@@ -632,10 +1928,28 @@ fn SectionActions(
     disabled: bool,
 ) -> Element {
     let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let character = use_context::<AppState>().character;
     let mut popup_input = move |action_input_kind| {
         let popup_kind = PopupInputKind::Action(action_input_kind);
         popup_input_kind.set(Some(popup_kind));
     };
+    let mut action_stats = use_signal(Vec::<ActionStats>::new);
+    // New loadout actions default to the first loadout of the current character
+    let loadout_condition = use_memo(move || {
+        let id = character()
+            .and_then(|character| character.loadouts.first().map(|loadout| loadout.id))
+            .unwrap_or_default();
+        ActionCondition::LoadoutActive(id)
+    });
+
+    use_future(move || async move {
+        loop {
+            if let Some(stats) = query_action_stats().await.ok_or_toast() {
+                action_stats.set(stats);
+            }
+            sleep(Duration::from_secs(2)).await;
+        }
+    });
 
     let export_element_id = use_memo(|| Alphanumeric.sample_string(&mut rand::rng(), 8));
     let export = use_callback(move |_| {
@@ -821,6 +2135,15 @@ fn SectionActions(
             coroutine.send(ActionUpdate::Update(actions));
         },
     );
+    let drop_action = use_callback(move |(from, to): (usize, usize)| {
+        let actions = minimap_preset_actions();
+        let Some(order) = drag_order(&actions, from, to) else {
+            return;
+        };
+        if let Some(actions) = reorder_actions(&actions, &order) {
+            coroutine.send(ActionUpdate::Update(actions));
+        }
+    });
 
     rsx! {
         Section { name: "Normal actions",
@@ -828,18 +2151,59 @@ fn SectionActions(
                 on_add_click: move |_| {
                     popup_input(ActionInputKind::Add(Action::Key(ActionKey::default())));
                 },
+                on_add_group_click: move |_| {
+                    popup_input(ActionInputKind::Add(Action::Group(ActionGroup::default())));
+                },
                 on_item_click: move |(action, index)| {
                     popup_input(ActionInputKind::Edit(action, index));
                 },
                 on_item_move: move |(index, condition, up)| {
                     move_action((index, condition, up));
                 },
+                on_item_drop: move |(from, to)| {
+                    drop_action((from, to));
+                },
                 on_item_delete: move |index| {
                     delete_action(index);
                 },
                 condition_filter: ActionCondition::Any,
                 disabled,
                 actions: minimap_preset_actions(),
+                stats: action_stats(),
+            }
+        }
+        Section { name: "Loadout actions",
+            ActionList {
+                on_add_click: move |_| {
+                    let action = Action::Key(ActionKey {
+                        condition: loadout_condition(),
+                        ..ActionKey::default()
+                    });
+                    popup_input(ActionInputKind::Add(action));
+                },
+                on_add_group_click: move |_| {
+                    let action = Action::Group(ActionGroup {
+                        condition: loadout_condition(),
+                        ..ActionGroup::default()
+                    });
+                    popup_input(ActionInputKind::Add(action));
+                },
+                on_item_click: move |(action, index)| {
+                    popup_input(ActionInputKind::Edit(action, index));
+                },
+                on_item_move: move |(index, condition, up)| {
+                    move_action((index, condition, up));
+                },
+                on_item_drop: move |(from, to)| {
+                    drop_action((from, to));
+                },
+                on_item_delete: move |index| {
+                    delete_action(index);
+                },
+                condition_filter: ActionCondition::LoadoutActive(0),
+                disabled,
+                actions: minimap_preset_actions(),
+                stats: action_stats(),
             }
         }
         Section { name: "Erda Shower off cooldown priority actions",
@@ -851,18 +2215,29 @@ fn SectionActions(
                     });
                     popup_input(ActionInputKind::Add(action));
                 },
+                on_add_group_click: move |_| {
+                    let action = Action::Group(ActionGroup {
+                        condition: ActionCondition::ErdaShowerOffCooldown,
+                        ..ActionGroup::default()
+                    });
+                    popup_input(ActionInputKind::Add(action));
+                },
                 on_item_click: move |(action, index)| {
                     popup_input(ActionInputKind::Edit(action, index));
                 },
                 on_item_move: move |(index, condition, up)| {
                     move_action((index, condition, up));
                 },
+                on_item_drop: move |(from, to)| {
+                    drop_action((from, to));
+                },
                 on_item_delete: move |index| {
                     delete_action(index);
                 },
                 condition_filter: ActionCondition::ErdaShowerOffCooldown,
                 disabled,
                 actions: minimap_preset_actions(),
+                stats: action_stats(),
             }
         }
         Section { name: "Every milliseconds priority actions",
@@ -874,18 +2249,29 @@ fn SectionActions(
                     });
                     popup_input(ActionInputKind::Add(action));
                 },
+                on_add_group_click: move |_| {
+                    let action = Action::Group(ActionGroup {
+                        condition: ActionCondition::EveryMillis(0),
+                        ..ActionGroup::default()
+                    });
+                    popup_input(ActionInputKind::Add(action));
+                },
                 on_item_click: move |(action, index)| {
                     popup_input(ActionInputKind::Edit(action, index));
                 },
                 on_item_move: move |(index, condition, up)| {
                     move_action((index, condition, up));
                 },
+                on_item_drop: move |(from, to)| {
+                    drop_action((from, to));
+                },
                 on_item_delete: move |index| {
                     delete_action(index);
                 },
                 condition_filter: ActionCondition::EveryMillis(0),
                 disabled,
                 actions: minimap_preset_actions(),
+                stats: action_stats(),
             }
         }
         Section { name: "Import/export actions",
@@ -902,27 +2288,238 @@ fn SectionActions(
                         },
                     }
                 }
-                div { class: "flex-grow",
-                    input {
-                        id: import_element_id(),
-                        class: "w-0 h-0 invisible",
-                        r#type: "file",
-                        accept: ".json",
-                        name: "Actions JSON",
-                        onchange: move |e| {
-                            if let Some(files) = e.data.files().map(|engine| engine.files()) {
-                                import_actions(files);
-                            }
-                        },
+                div { class: "flex-grow",
+                    input {
+                        id: import_element_id(),
+                        class: "w-0 h-0 invisible",
+                        r#type: "file",
+                        accept: ".json",
+                        name: "Actions JSON",
+                        onchange: move |e| {
+                            if let Some(files) = e.data.files().map(|engine| engine.files()) {
+                                import_actions(files);
+                            }
+                        },
+                    }
+                    Button {
+                        class: "w-full",
+                        text: "Import",
+                        kind: ButtonKind::Primary,
+                        disabled,
+                        on_click: move |_| {
+                            import(());
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PopupPlatformInput(
+    index: Option<usize>,
+    on_cancel: EventHandler,
+    on_value: EventHandler<(Platform, Option<usize>)>,
+    value: Platform,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "absolute invisible group-hover:visible top-5 right-1 w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-3 h-3 text-gray-50 fill-current";
+
+    let position = use_context::<AppState>().position;
+    let mut platform = use_signal(|| value);
+    let section_name = if index.is_some() {
+        "Modify platform"
+    } else {
+        "Add platform"
+    };
+    let button_name = if index.is_some() { "Save" } else { "Add" };
+
+    use_effect(use_reactive!(|value| platform.set(value)));
+
+    rsx! {
+        div { class: "px-16 py-35 w-full h-full absolute inset-0 z-1 bg-gray-950/80 flex",
+            div { class: "bg-gray-900 w-full max-w-104 h-full max-h-50 px-2 m-auto",
+                Section { name: section_name, class: "relative h-full",
+                    div { class: "grid grid-cols-3 gap-3",
+                        div { class: "relative group",
+                            ActionsNumberInputI32 {
+                                label: "X start",
+                                on_value: move |x| {
+                                    platform.write().x_start = x;
+                                },
+                                value: platform().x_start,
+                            }
+                            div {
+                                class: ICON_CONTAINER_CLASS,
+                                onclick: move |_| {
+                                    platform.write().x_start = position.peek().0;
+                                },
+                                PositionIcon { class: ICON_CLASS }
+                            }
+                        }
+                        div { class: "relative group",
+                            ActionsNumberInputI32 {
+                                label: "X end",
+                                on_value: move |x| {
+                                    platform.write().x_end = x;
+                                },
+                                value: platform().x_end,
+                            }
+                            div {
+                                class: ICON_CONTAINER_CLASS,
+                                onclick: move |_| {
+                                    platform.write().x_end = position.peek().0;
+                                },
+                                PositionIcon { class: ICON_CLASS }
+                            }
+                        }
+                        div { class: "relative group",
+                            ActionsNumberInputI32 {
+                                label: "Y",
+                                on_value: move |y| {
+                                    platform.write().y = y;
+                                },
+                                value: platform().y,
+                            }
+                            div {
+                                class: ICON_CONTAINER_CLASS,
+                                onclick: move |_| {
+                                    platform.write().y = position.peek().1;
+                                },
+                                PositionIcon { class: ICON_CLASS }
+                            }
+                        }
+                        ActionsSelect::<PlatformMotion> {
+                            label: "Motion",
+                            disabled: false,
+                            on_select: move |motion| {
+                                platform.write().motion = motion;
+                            },
+                            selected: platform().motion,
+                        }
+                        ActionsMillisInput {
+                            label: "Motion period",
+                            on_value: move |millis| {
+                                platform.write().motion_period_millis = millis;
+                            },
+                            value: platform().motion_period_millis,
+                        }
+                        ActionsNumberInputI32 {
+                            label: "Motion range",
+                            on_value: move |range| {
+                                platform.write().motion_range = range;
+                            },
+                            value: platform().motion_range,
+                        }
+                    }
+                    div { class: "flex w-full gap-3 absolute bottom-2",
+                        Button {
+                            class: "flex-grow border border-gray-600",
+                            text: button_name,
+                            kind: ButtonKind::Primary,
+                            on_click: move |_| {
+                                on_value((*platform.peek(), index));
+                            },
+                        }
+                        Button {
+                            class: "flex-grow border border-gray-600",
+                            text: "Cancel",
+                            kind: ButtonKind::Danger,
+                            on_click: move |_| {
+                                on_cancel(());
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PopupAutoMobRegionInput(
+    index: Option<usize>,
+    on_cancel: EventHandler,
+    on_value: EventHandler<(AutoMobRegion, Option<usize>)>,
+    value: AutoMobRegion,
+) -> Element {
+    let mut region = use_signal(|| value.clone());
+    let section_name = if index.is_some() {
+        "Modify auto-mobbing region"
+    } else {
+        "Add auto-mobbing region"
+    };
+    let button_name = if index.is_some() { "Save" } else { "Add" };
+
+    use_effect(use_reactive!(|value| region.set(value)));
+
+    rsx! {
+        div { class: "px-16 py-30 w-full h-full absolute inset-0 z-1 bg-gray-950/80 flex",
+            div { class: "bg-gray-900 w-full max-w-108 h-full max-h-64 px-2 m-auto",
+                Section { name: section_name, class: "relative h-full",
+                    div { class: "grid grid-cols-2 gap-3",
+                        TextInput {
+                            label: "Name",
+                            on_value: move |name| {
+                                region.write().name = name;
+                            },
+                            value: region().name,
+                        }
+                        NumberInputU32 {
+                            label: "Weight",
+                            minimum_value: 0,
+                            on_value: move |weight| {
+                                region.write().weight = weight;
+                            },
+                            value: region().weight,
+                        }
+                        ActionsNumberInputI32 {
+                            label: "X offset",
+                            on_value: move |x| {
+                                region.write().bound.x = x;
+                            },
+                            value: region().bound.x,
+                        }
+                        ActionsNumberInputI32 {
+                            label: "Y offset",
+                            on_value: move |y| {
+                                region.write().bound.y = y;
+                            },
+                            value: region().bound.y,
+                        }
+                        ActionsNumberInputI32 {
+                            label: "Width",
+                            on_value: move |width| {
+                                region.write().bound.width = width;
+                            },
+                            value: region().bound.width,
+                        }
+                        ActionsNumberInputI32 {
+                            label: "Height",
+                            on_value: move |height| {
+                                region.write().bound.height = height;
+                            },
+                            value: region().bound.height,
+                        }
                     }
-                    Button {
-                        class: "w-full",
-                        text: "Import",
-                        kind: ButtonKind::Primary,
-                        disabled,
-                        on_click: move |_| {
-                            import(());
-                        },
+                    div { class: "flex w-full gap-3 absolute bottom-2",
+                        Button {
+                            class: "flex-grow border border-gray-600",
+                            text: button_name,
+                            kind: ButtonKind::Primary,
+                            on_click: move |_| {
+                                on_value((region.peek().clone(), index));
+                            },
+                        }
+                        Button {
+                            class: "flex-grow border border-gray-600",
+                            text: "Cancel",
+                            kind: ButtonKind::Danger,
+                            on_click: move |_| {
+                                on_cancel(());
+                            },
+                        }
                     }
                 }
             }
@@ -931,25 +2528,25 @@ fn SectionActions(
 }
 
 #[component]
-fn PopupPlatformInput(
+fn PopupPingPongBandInput(
     index: Option<usize>,
     on_cancel: EventHandler,
-    on_value: EventHandler<(Platform, Option<usize>)>,
-    value: Platform,
+    on_value: EventHandler<(PingPongBand, Option<usize>)>,
+    value: PingPongBand,
 ) -> Element {
     const ICON_CONTAINER_CLASS: &str = "absolute invisible group-hover:visible top-5 right-1 w-4 h-6 flex justify-center items-center";
     const ICON_CLASS: &str = "w-3 h-3 text-gray-50 fill-current";
 
     let position = use_context::<AppState>().position;
-    let mut platform = use_signal(|| value);
+    let mut band = use_signal(|| value);
     let section_name = if index.is_some() {
-        "Modify platform"
+        "Modify ping-pong band"
     } else {
-        "Add platform"
+        "Add ping-pong band"
     };
     let button_name = if index.is_some() { "Save" } else { "Add" };
 
-    use_effect(use_reactive!(|value| platform.set(value)));
+    use_effect(use_reactive!(|value| band.set(value)));
 
     rsx! {
         div { class: "px-16 py-42 w-full h-full absolute inset-0 z-1 bg-gray-950/80 flex",
@@ -958,52 +2555,45 @@ fn PopupPlatformInput(
                     div { class: "grid grid-cols-3 gap-3",
                         div { class: "relative group",
                             ActionsNumberInputI32 {
-                                label: "X start",
-                                on_value: move |x| {
-                                    platform.write().x_start = x;
-                                },
-                                value: platform().x_start,
-                            }
-                            div {
-                                class: ICON_CONTAINER_CLASS,
-                                onclick: move |_| {
-                                    platform.write().x_start = position.peek().0;
-                                },
-                                PositionIcon { class: ICON_CLASS }
-                            }
-                        }
-                        div { class: "relative group",
-                            ActionsNumberInputI32 {
-                                label: "X end",
-                                on_value: move |x| {
-                                    platform.write().x_end = x;
+                                label: "Y start",
+                                on_value: move |y| {
+                                    band.write().y_start = y;
                                 },
-                                value: platform().x_end,
+                                value: band().y_start,
                             }
                             div {
                                 class: ICON_CONTAINER_CLASS,
                                 onclick: move |_| {
-                                    platform.write().x_end = position.peek().0;
+                                    band.write().y_start = position.peek().1;
                                 },
                                 PositionIcon { class: ICON_CLASS }
                             }
                         }
                         div { class: "relative group",
                             ActionsNumberInputI32 {
-                                label: "Y",
+                                label: "Y end",
                                 on_value: move |y| {
-                                    platform.write().y = y;
+                                    band.write().y_end = y;
                                 },
-                                value: platform().y,
+                                value: band().y_end,
                             }
                             div {
                                 class: ICON_CONTAINER_CLASS,
                                 onclick: move |_| {
-                                    platform.write().y = position.peek().1;
+                                    band.write().y_end = position.peek().1;
                                 },
                                 PositionIcon { class: ICON_CLASS }
                             }
                         }
+                        NumberInputU32 {
+                            label: "Probability (%)",
+                            minimum_value: 0,
+                            maximum_value: Some(100),
+                            on_value: move |probability| {
+                                band.write().probability = probability;
+                            },
+                            value: band().probability,
+                        }
                     }
                     div { class: "flex w-full gap-3 absolute bottom-2",
                         Button {
@@ -1011,7 +2601,7 @@ fn PopupPlatformInput(
                             text: button_name,
                             kind: ButtonKind::Primary,
                             on_click: move |_| {
-                                on_value((*platform.peek(), index));
+                                on_value((*band.peek(), index));
                             },
                         }
                         Button {
@@ -1031,6 +2621,7 @@ fn PopupPlatformInput(
 
 #[component]
 fn PopupBoundInput(
+    title: String,
     on_cancel: EventHandler,
     on_value: EventHandler<Bound>,
     value: Bound,
@@ -1042,7 +2633,7 @@ fn PopupBoundInput(
     rsx! {
         div { class: "px-16 py-35 w-full h-full absolute inset-0 z-1 bg-gray-950/80 flex",
             div { class: "bg-gray-900 w-full max-w-108 h-full max-h-50 px-2 m-auto",
-                Section { name: "Modify mobbing bound", class: "relative h-full",
+                Section { name: title, class: "relative h-full",
                     div { class: "grid grid-cols-2 gap-3",
                         ActionsNumberInputI32 {
                             label: "X offset",
@@ -1125,7 +2716,8 @@ fn PopupActionInput(
         ActionInputKind::Add(action) => (action, None),
         ActionInputKind::Edit(action, index) => (action, Some(index)),
     };
-    let switchable = !matches!(kind, ActionInputKind::PingPongOrAutoMobbing(_));
+    let switchable = !matches!(kind, ActionInputKind::PingPongOrAutoMobbing(_))
+        && !matches!(action, Action::Group(_));
     let modifying = matches!(
         kind,
         ActionInputKind::Edit(_, _) | ActionInputKind::PingPongOrAutoMobbing(_)
@@ -1135,7 +2727,8 @@ fn PopupActionInput(
         ActionInputKind::Add(_) | ActionInputKind::Edit(_, _) => match action.condition() {
             ActionCondition::EveryMillis(_)
             | ActionCondition::ErdaShowerOffCooldown
-            | ActionCondition::Any => {
+            | ActionCondition::Any
+            | ActionCondition::LoadoutActive(_) => {
                 let actions = actions();
                 let filtered = filter_actions(actions, action.condition());
                 let is_not_empty = !filtered.is_empty();
@@ -1154,6 +2747,7 @@ fn PopupActionInput(
                 backend::ActionCondition::EveryMillis(_) => "every milliseconds",
                 backend::ActionCondition::ErdaShowerOffCooldown => "Erda Shower off cooldown",
                 backend::ActionCondition::Linked => "linked",
+                backend::ActionCondition::LoadoutActive(_) => "loadout",
             };
             if modifying {
                 format!("Modify a {name} action")
@@ -1172,8 +2766,8 @@ fn PopupActionInput(
                 modifying,
                 copyable,
                 can_create_linked_action,
-                can_have_position: switchable,
-                can_have_direction: switchable,
+                can_have_position: !matches!(kind, ActionInputKind::PingPongOrAutoMobbing(_)),
+                can_have_direction: !matches!(kind, ActionInputKind::PingPongOrAutoMobbing(_)),
                 on_copy,
                 on_cancel,
                 on_value: move |(action, condition)| {
@@ -1186,7 +2780,9 @@ fn PopupActionInput(
                         }
                         ActionInputKind::PingPongOrAutoMobbing(_) => {
                             let action = match action {
-                                Action::Move(_) => unreachable!(),
+                                Action::Move(_) | Action::Group(_) | Action::Mouse(_) => {
+                                    unreachable!()
+                                }
                                 Action::Key(action) => action,
                             };
                             let key = MobbingKey {
@@ -1226,12 +2822,10 @@ fn ActionInput(
     value: Action,
 ) -> Element {
     let mut action = use_signal(|| value);
-    let button_text = use_memo(move || {
-        if matches!(action(), Action::Move(_)) {
-            "Switch to key"
-        } else {
-            "Switch to move"
-        }
+    let button_text = use_memo(move || match action() {
+        Action::Move(_) => "Switch to mouse",
+        Action::Mouse(_) => "Switch to key",
+        Action::Key(_) | Action::Group(_) => "Switch to move",
     });
 
     use_effect(use_reactive!(|value| action.set(value)));
@@ -1245,24 +2839,31 @@ fn ActionInput(
                             text: button_text(),
                             kind: ButtonKind::Primary,
                             on_click: move |_| {
-                                if discriminant(&value) != discriminant(&*action.peek()) {
+                                let condition = value.condition();
+                                let next = match *action.peek() {
+                                    Action::Move(_) => {
+                                        Action::Mouse(ActionMouse {
+                                            condition,
+                                            ..ActionMouse::default()
+                                        })
+                                    }
+                                    Action::Mouse(_) => {
+                                        Action::Key(ActionKey {
+                                            condition,
+                                            ..ActionKey::default()
+                                        })
+                                    }
+                                    Action::Key(_) | Action::Group(_) => {
+                                        Action::Move(ActionMove {
+                                            condition,
+                                            ..ActionMove::default()
+                                        })
+                                    }
+                                };
+                                if discriminant(&value) == discriminant(&next) {
                                     action.set(value);
-                                } else if matches!(value, Action::Move(_)) {
-                                    action
-                                        .set(
-                                            Action::Key(ActionKey {
-                                                condition: value.condition(),
-                                                ..ActionKey::default()
-                                            }),
-                                        );
                                 } else {
-                                    action
-                                        .set(
-                                            Action::Move(ActionMove {
-                                                condition: value.condition(),
-                                                ..ActionMove::default()
-                                            }),
-                                        );
+                                    action.set(next);
                                 }
                             },
                             class: "label border-b border-gray-600",
@@ -1302,6 +2903,27 @@ fn ActionInput(
                             value: action,
                         }
                     },
+                    Action::Group(action) => rsx! {
+                        ActionGroupInput {
+                            modifying,
+                            on_cancel,
+                            on_value: move |(action, condition)| {
+                                on_value((Action::Group(action), condition));
+                            },
+                            value: action,
+                        }
+                    },
+                    Action::Mouse(action) => rsx! {
+                        ActionMouseInput {
+                            modifying,
+                            can_create_linked_action,
+                            on_cancel,
+                            on_value: move |(action, condition)| {
+                                on_value((Action::Mouse(action), condition));
+                            },
+                            value: action,
+                        }
+                    },
                 }
             }
         }
@@ -1389,6 +3011,153 @@ fn ActionMoveInput(
                 },
                 value: action().wait_after_move_millis,
             }
+            ActionsNumberInputU32 {
+                label: "Max retries",
+                minimum_value: 0,
+                on_value: move |retries| {
+                    let mut action = action.write();
+                    action.max_retries = retries;
+                },
+                value: action().max_retries,
+            }
+            ActionsMillisInput {
+                label: "Retry delay",
+                on_value: move |millis| {
+                    let mut action = action.write();
+                    action.retry_delay_millis = millis;
+                },
+                value: action().retry_delay_millis,
+            }
+            ActionsSelect::<ActionFailure> {
+                label: "On failure",
+                disabled: false,
+                on_select: move |on_failure| {
+                    let mut action = action.write();
+                    action.on_failure = on_failure;
+                },
+                selected: action().on_failure,
+            }
+            if let ActionCondition::LoadoutActive(id) = action().condition {
+                ActionsLoadoutSelect {
+                    on_value: move |id| {
+                        let mut action = action.write();
+                        action.condition = ActionCondition::LoadoutActive(id);
+                    },
+                    value: id,
+                }
+            }
+            if can_create_linked_action {
+                ActionsCheckbox {
+                    label: "Linked action",
+                    on_value: move |is_linked: bool| {
+                        let mut action = action.write();
+                        action.condition = if is_linked {
+                            ActionCondition::Linked
+                        } else {
+                            value.condition
+                        };
+                    },
+                    value: matches!(action().condition, ActionCondition::Linked),
+                }
+            }
+        }
+        div { class: "flex w-full gap-3 absolute bottom-2",
+            Button {
+                class: "flex-grow border border-gray-600",
+                text: if modifying { "Save" } else { "Add" },
+                kind: ButtonKind::Primary,
+                on_click: move |_| {
+                    on_value((*action.peek(), value.condition));
+                },
+            }
+            Button {
+                class: "flex-grow border border-gray-600",
+                text: "Cancel",
+                kind: ButtonKind::Danger,
+                on_click: move |_| {
+                    on_cancel(());
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn ActionMouseInput(
+    modifying: bool,
+    can_create_linked_action: bool,
+    on_cancel: EventHandler,
+    on_value: EventHandler<(ActionMouse, ActionCondition)>,
+    value: ActionMouse,
+) -> Element {
+    let mut action = use_signal(|| value);
+
+    use_effect(use_reactive!(|value| { action.set(value) }));
+
+    rsx! {
+        div { class: "grid grid-cols-3 gap-3",
+            ActionsSelect::<ActionMouseKind> {
+                label: "Mouse",
+                disabled: false,
+                on_select: move |kind| {
+                    let mut action = action.write();
+                    action.action = kind;
+                },
+                selected: action().action,
+            }
+            ActionsSelect::<ActionMouseRelativeTo> {
+                label: "Relative to",
+                disabled: false,
+                on_select: move |relative_to| {
+                    let mut action = action.write();
+                    action.relative_to = relative_to;
+                },
+                selected: action().relative_to,
+            }
+            div {} // Spacer
+            ActionsNumberInputI32 {
+                label: "X",
+                on_value: move |x| {
+                    let mut action = action.write();
+                    action.x = x;
+                },
+                value: action().x,
+            }
+            ActionsNumberInputI32 {
+                label: "Y",
+                on_value: move |y| {
+                    let mut action = action.write();
+                    action.y = y;
+                },
+                value: action().y,
+            }
+            ActionsMillisInput {
+                label: "Wait after",
+                on_value: move |millis| {
+                    let mut action = action.write();
+                    action.wait_after_use_millis = millis;
+                },
+                value: action().wait_after_use_millis,
+            }
+            if let ActionCondition::EveryMillis(millis) = action().condition {
+                ActionsMillisInput {
+                    label: "Use every",
+                    on_value: move |millis| {
+                        let mut action = action.write();
+                        action.condition = ActionCondition::EveryMillis(millis);
+                    },
+                    value: millis,
+                }
+            }
+            if let ActionCondition::LoadoutActive(id) = action().condition {
+                ActionsLoadoutSelect {
+                    on_value: move |id| {
+                        let mut action = action.write();
+                        action.condition = ActionCondition::LoadoutActive(id);
+                    },
+                    value: id,
+                }
+            }
             if can_create_linked_action {
                 ActionsCheckbox {
                     label: "Linked action",
@@ -1425,6 +3194,145 @@ fn ActionMoveInput(
     }
 }
 
+#[component]
+fn ActionGroupInput(
+    modifying: bool,
+    on_cancel: EventHandler,
+    on_value: EventHandler<(ActionGroup, ActionCondition)>,
+    value: ActionGroup,
+) -> Element {
+    let mut action = use_signal(|| value);
+
+    use_effect(use_reactive!(|value| { action.set(value) }));
+
+    rsx! {
+        div { class: "grid grid-cols-3 gap-3",
+            ActionsSelect::<ActionGroupPredicate> {
+                label: "If",
+                disabled: false,
+                on_select: move |predicate| {
+                    let mut action = action.write();
+                    action.predicate = predicate;
+                },
+                selected: action().predicate,
+            }
+            ActionsNumberInputU32 {
+                label: "Then actions count",
+                on_value: move |count| {
+                    let mut action = action.write();
+                    action.then_count = count;
+                },
+                value: action().then_count,
+            }
+            if let ActionCondition::EveryMillis(millis) = action().condition {
+                ActionsMillisInput {
+                    label: "Use every",
+                    on_value: move |millis| {
+                        let mut action = action.write();
+                        action.condition = ActionCondition::EveryMillis(millis);
+                    },
+                    value: millis,
+                }
+            } else if let ActionCondition::LoadoutActive(id) = action().condition {
+                ActionsLoadoutSelect {
+                    on_value: move |id| {
+                        let mut action = action.write();
+                        action.condition = ActionCondition::LoadoutActive(id);
+                    },
+                    value: id,
+                }
+            } else {
+                div {} // Spacer
+            }
+            match action().predicate {
+                ActionGroupPredicate::HealthBelowPercent(percent) => rsx! {
+                    ActionsNumberInputU32 {
+                        label: "Health percent",
+                        on_value: move |percent: u32| {
+                            let mut action = action.write();
+                            action.predicate =
+                                ActionGroupPredicate::HealthBelowPercent(percent.min(100));
+                        },
+                        value: percent,
+                    }
+                },
+                ActionGroupPredicate::BuffPresent(kind) => rsx! {
+                    ActionsSelect::<BuffKind> {
+                        label: "Buff",
+                        disabled: false,
+                        on_select: move |kind| {
+                            let mut action = action.write();
+                            action.predicate = ActionGroupPredicate::BuffPresent(kind);
+                        },
+                        selected: kind,
+                    }
+                },
+                ActionGroupPredicate::RuneActive => rsx! {},
+                ActionGroupPredicate::PlayerInRegion(bound) => rsx! {
+                    ActionsNumberInputI32 {
+                        label: "Region X",
+                        on_value: move |x| {
+                            let mut action = action.write();
+                            action.predicate =
+                                ActionGroupPredicate::PlayerInRegion(Bound { x, ..bound });
+                        },
+                        value: bound.x,
+                    }
+                    ActionsNumberInputI32 {
+                        label: "Region Y",
+                        on_value: move |y| {
+                            let mut action = action.write();
+                            action.predicate =
+                                ActionGroupPredicate::PlayerInRegion(Bound { y, ..bound });
+                        },
+                        value: bound.y,
+                    }
+                    ActionsNumberInputI32 {
+                        label: "Region width",
+                        on_value: move |width| {
+                            let mut action = action.write();
+                            action.predicate = ActionGroupPredicate::PlayerInRegion(Bound {
+                                width,
+                                ..bound
+                            });
+                        },
+                        value: bound.width,
+                    }
+                    ActionsNumberInputI32 {
+                        label: "Region height",
+                        on_value: move |height| {
+                            let mut action = action.write();
+                            action.predicate = ActionGroupPredicate::PlayerInRegion(Bound {
+                                height,
+                                ..bound
+                            });
+                        },
+                        value: bound.height,
+                    }
+                },
+            }
+        }
+        div { class: "flex w-full gap-3 absolute bottom-2",
+            Button {
+                class: "flex-grow border border-gray-600",
+                text: if modifying { "Save" } else { "Add" },
+                kind: ButtonKind::Primary,
+                on_click: move |_| {
+                    on_value((*action.peek(), value.condition));
+                },
+            }
+            Button {
+                class: "flex-grow border border-gray-600",
+                text: "Cancel",
+                kind: ButtonKind::Danger,
+                on_click: move |_| {
+                    on_cancel(());
+                },
+            }
+        }
+    }
+}
+
 #[component]
 fn ActionKeyInput(
     modifying: bool,
@@ -1637,6 +3545,16 @@ fn ActionKeyInput(
                 }
                 div { class: "col-span-2" }
             }
+            if let ActionCondition::LoadoutActive(id) = action().condition {
+                ActionsLoadoutSelect {
+                    on_value: move |id| {
+                        let mut action = action.write();
+                        action.condition = ActionCondition::LoadoutActive(id);
+                    },
+                    value: id,
+                }
+                div { class: "col-span-2" }
+            }
 
             // Wait before use
             ActionsMillisInput {
@@ -1674,6 +3592,95 @@ fn ActionKeyInput(
                 },
                 value: action().wait_after_use_millis_random_range,
             }
+            div {} // Spacer
+
+            // Retry on failure
+            ActionsNumberInputU32 {
+                label: "Max retries",
+                minimum_value: 0,
+                on_value: move |retries| {
+                    let mut action = action.write();
+                    action.max_retries = retries;
+                },
+                value: action().max_retries,
+            }
+            ActionsMillisInput {
+                label: "Retry delay",
+                on_value: move |millis| {
+                    let mut action = action.write();
+                    action.retry_delay_millis = millis;
+                },
+                value: action().retry_delay_millis,
+            }
+            ActionsSelect::<ActionFailure> {
+                label: "On failure",
+                disabled: false,
+                on_select: move |on_failure| {
+                    let mut action = action.write();
+                    action.on_failure = on_failure;
+                },
+                selected: action().on_failure,
+            }
+
+            // Verify region change
+            ActionsCheckbox {
+                label: "Verify region change",
+                on_value: move |verify: bool| {
+                    let mut action = action.write();
+                    action.verify = verify.then_some(ActionKeyVerify {
+                        retry_count: 1,
+                        ..ActionKeyVerify::default()
+                    });
+                },
+                value: action().verify.is_some(),
+            }
+            ActionsNumberInputU32 {
+                label: "Retry count",
+                disabled: action().verify.is_none(),
+                on_value: move |retry_count| {
+                    let mut action = action.write();
+                    action.verify.as_mut().unwrap().retry_count = retry_count;
+                },
+                value: action().verify.map(|verify| verify.retry_count).unwrap_or_default(),
+            }
+            div {} // Spacer
+            ActionsNumberInputI32 {
+                label: "Region X",
+                disabled: action().verify.is_none(),
+                on_value: move |x| {
+                    let mut action = action.write();
+                    action.verify.as_mut().unwrap().region.x = x;
+                },
+                value: action().verify.map(|verify| verify.region.x).unwrap_or_default(),
+            }
+            ActionsNumberInputI32 {
+                label: "Region Y",
+                disabled: action().verify.is_none(),
+                on_value: move |y| {
+                    let mut action = action.write();
+                    action.verify.as_mut().unwrap().region.y = y;
+                },
+                value: action().verify.map(|verify| verify.region.y).unwrap_or_default(),
+            }
+            div {} // Spacer
+            ActionsNumberInputI32 {
+                label: "Region width",
+                disabled: action().verify.is_none(),
+                on_value: move |width| {
+                    let mut action = action.write();
+                    action.verify.as_mut().unwrap().region.width = width;
+                },
+                value: action().verify.map(|verify| verify.region.width).unwrap_or_default(),
+            }
+            ActionsNumberInputI32 {
+                label: "Region height",
+                disabled: action().verify.is_none(),
+                on_value: move |height| {
+                    let mut action = action.write();
+                    action.verify.as_mut().unwrap().region.height = height;
+                },
+                value: action().verify.map(|verify| verify.region.height).unwrap_or_default(),
+            }
         }
         div { class: "flex w-full gap-3 absolute bottom-0 py-2 bg-gray-900",
             Button {
@@ -1699,12 +3706,15 @@ fn ActionKeyInput(
 #[component]
 fn ActionList(
     on_add_click: EventHandler,
+    on_add_group_click: EventHandler,
     on_item_click: EventHandler<(Action, usize)>,
     on_item_move: EventHandler<(usize, ActionCondition, bool)>,
+    on_item_drop: EventHandler<(usize, usize)>,
     on_item_delete: EventHandler<usize>,
     condition_filter: ActionCondition,
     disabled: bool,
     actions: Vec<Action>,
+    stats: Vec<ActionStats>,
 ) -> Element {
     #[component]
     fn Icons(
@@ -1753,23 +3763,64 @@ fn ActionList(
     }
 
     let filtered = filter_actions(actions, condition_filter);
+    let branches = group_branches(&filtered);
+    let stats = stats
+        .into_iter()
+        .map(|stats| (stats.index, stats))
+        .collect::<HashMap<_, _>>();
+    let mut dragging_index = use_signal(|| None);
 
     rsx! {
         div { class: "flex flex-col",
-            for (action , index) in filtered {
+            for ((action , index) , branch) in filtered.into_iter().zip(branches) {
                 div {
                     class: "relative group",
+                    draggable: !disabled,
+                    ondragstart: move |_| {
+                        dragging_index.set(Some(index));
+                    },
+                    ondragover: move |e| {
+                        e.prevent_default();
+                    },
+                    ondrop: move |e| {
+                        e.prevent_default();
+                        if let Some(from) = dragging_index.take() {
+                            on_item_drop((from, index));
+                        }
+                    },
                     onclick: move |e| {
                         e.stop_propagation();
                         on_item_click((action, index));
                     },
-                    match action {
-                        Action::Move(action) => rsx! {
-                            ActionMoveItem { action }
-                        },
-                        Action::Key(action) => rsx! {
-                            ActionKeyItem { action }
-                        },
+                    div { class: "flex",
+                        if let Some(then) = branch {
+                            div { class: "flex-none w-10 h-6 paragraph-xs !text-gray-500 {ITEM_TEXT_CLASS}",
+                                if then {
+                                    "Then"
+                                } else {
+                                    "Else"
+                                }
+                            }
+                        }
+                        div { class: "flex-grow",
+                            match action {
+                                Action::Move(action) => rsx! {
+                                    ActionMoveItem { action }
+                                },
+                                Action::Key(action) => rsx! {
+                                    ActionKeyItem { action }
+                                },
+                                Action::Group(action) => rsx! {
+                                    ActionGroupItem { action }
+                                },
+                                Action::Mouse(action) => rsx! {
+                                    ActionMouseItem { action }
+                                },
+                            }
+                            if let Some(stats) = stats.get(&index).copied() {
+                                ActionStatsItem { stats }
+                            }
+                        }
                     }
                     Icons {
                         condition_filter,
@@ -1780,21 +3831,58 @@ fn ActionList(
                     }
                 }
             }
-            Button {
-                text: "Add action",
-                kind: ButtonKind::Secondary,
-                on_click: move |_| {
-                    on_add_click(());
-                },
-                disabled,
-                class: "label mt-2",
+            div { class: "grid grid-cols-2 gap-2 mt-2",
+                Button {
+                    text: "Add action",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        on_add_click(());
+                    },
+                    disabled,
+                    class: "label",
+                }
+                Button {
+                    text: "Add group",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        on_add_group_click(());
+                    },
+                    disabled,
+                    class: "label",
+                }
             }
         }
     }
 }
 
+#[component]
+fn ActionStatsItem(stats: ActionStats) -> Element {
+    let ActionStats {
+        count,
+        last_run_timestamp_millis,
+        average_duration_millis,
+        ..
+    } = stats;
+
+    let now_millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let last_run_secs = now_millis.saturating_sub(last_run_timestamp_millis) / 1000;
+    let average_secs = average_duration_millis
+        .map(|millis| format!(" / ⏱︎ {:.2}s", millis as f32 / 1000.0))
+        .unwrap_or_default();
+
+    rsx! {
+        div { class: "h-5 paragraph-xs !text-gray-500 group-hover:bg-gray-900 {ITEM_TEXT_CLASS}",
+            "Ran × {count}{average_secs} / {last_run_secs}s ago"
+        }
+    }
+}
+
 #[component]
 fn ActionMoveItem(action: ActionMove) -> Element {
+    let character = use_context::<AppState>().character;
     let ActionMove {
         position:
             Position {
@@ -1805,6 +3893,7 @@ fn ActionMoveItem(action: ActionMove) -> Element {
             },
         condition,
         wait_after_move_millis,
+        ..
     } = action;
 
     let x_min = (x - x_random_range).max(0);
@@ -1823,18 +3912,89 @@ fn ActionMoveItem(action: ActionMove) -> Element {
         "mt-2"
     };
     let wait_secs = format!("⏱︎ {:.2}s", wait_after_move_millis as f32 / 1000.0);
+    let loadout = loadout_text(character(), condition);
 
     rsx! {
         div { class: "grid grid-cols-[140px_100px_auto] h-6 paragraph-xs !text-gray-400 group-hover:bg-gray-900 {linked_action}",
             div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "{position}" }
             div { class: "{ITEM_TEXT_CLASS}", "{wait_secs}" }
-            div {}
+            div { class: "pl-1 pr-13 {ITEM_TEXT_CLASS}", "{loadout}" }
+        }
+    }
+}
+
+#[component]
+fn ActionMouseItem(action: ActionMouse) -> Element {
+    let character = use_context::<AppState>().character;
+    let ActionMouse {
+        x,
+        y,
+        action,
+        relative_to,
+        condition,
+        wait_after_use_millis,
+    } = action;
+
+    let linked_action = if matches!(condition, ActionCondition::Linked) {
+        ""
+    } else {
+        "mt-2"
+    };
+    let millis = if let ActionCondition::EveryMillis(millis) = condition {
+        format!("⟳ {:.2}s / ", millis as f32 / 1000.0)
+    } else {
+        "".to_string()
+    };
+    let loadout = loadout_text(character(), condition);
+    let wait_secs = format!("⏱︎ {:.2}s", wait_after_use_millis as f32 / 1000.0);
+
+    rsx! {
+        div { class: "grid grid-cols-[140px_100px_auto] h-6 paragraph-xs !text-gray-400 group-hover:bg-gray-900 {linked_action}",
+            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "{action} {x}, {y}" }
+            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "{relative_to}" }
+            div { class: "pl-1 pr-13 {ITEM_TEXT_CLASS}", "{loadout}{millis}{wait_secs}" }
+        }
+    }
+}
+
+#[component]
+fn ActionGroupItem(action: ActionGroup) -> Element {
+    let character = use_context::<AppState>().character;
+    let ActionGroup {
+        predicate,
+        condition,
+        then_count,
+    } = action;
+
+    let predicate = match predicate {
+        ActionGroupPredicate::HealthBelowPercent(percent) => format!("Health ≤ {percent}%"),
+        ActionGroupPredicate::BuffPresent(kind) => format!("Has {kind}"),
+        ActionGroupPredicate::RuneActive => "Rune active".to_string(),
+        ActionGroupPredicate::PlayerInRegion(Bound {
+            x,
+            y,
+            width,
+            height,
+        }) => format!("In {x}, {y}, {width} × {height}"),
+    };
+    let millis = if let ActionCondition::EveryMillis(millis) = condition {
+        format!("⟳ {:.2}s / ", millis as f32 / 1000.0)
+    } else {
+        "".to_string()
+    };
+    let loadout = loadout_text(character(), condition);
+
+    rsx! {
+        div { class: "grid grid-cols-[240px_auto] h-6 paragraph-xs !text-gray-400 group-hover:bg-gray-900 mt-2",
+            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "If {predicate}" }
+            div { class: "pl-1 pr-13 {ITEM_TEXT_CLASS}", "{loadout}{millis}Then × {then_count}" }
         }
     }
 }
 
 #[component]
 fn ActionKeyItem(action: ActionKey) -> Element {
+    let character = use_context::<AppState>().character;
     let ActionKey {
         key,
         link_key,
@@ -1891,6 +4051,7 @@ fn ActionKeyItem(action: ActionKey) -> Element {
     } else {
         "".to_string()
     };
+    let loadout = loadout_text(character(), condition);
     let wait_before_secs = if wait_before_use_millis > 0 {
         Some(format!("⏱︎ {:.2}s", wait_before_use_millis as f32 / 1000.0))
     } else {
@@ -1924,11 +4085,28 @@ fn ActionKeyItem(action: ActionKey) -> Element {
                     ActionKeyDirection::Right => "→",
                 }
             }
-            div { class: "pl-1 pr-13 {ITEM_TEXT_CLASS}", "{millis}{wait_secs}{with}" }
+            div { class: "pl-1 pr-13 {ITEM_TEXT_CLASS}", "{loadout}{millis}{wait_secs}{with}" }
         }
     }
 }
 
+/// The [`Loadout`] name of an action item with [`ActionCondition::LoadoutActive`].
+fn loadout_text(character: Option<Character>, condition: ActionCondition) -> String {
+    let ActionCondition::LoadoutActive(id) = condition else {
+        return "".to_string();
+    };
+    character
+        .and_then(|character| {
+            character
+                .loadouts
+                .into_iter()
+                .find(|loadout| loadout.id == id)
+        })
+        .map_or(format!("Loadout {id} / "), |loadout| {
+            format!("{} / ", loadout.name)
+        })
+}
+
 #[component]
 fn ActionsSelect<T: 'static + Clone + PartialEq + Display + IntoEnumIterator>(
     label: &'static str,
@@ -1946,6 +4124,37 @@ fn ActionsSelect<T: 'static + Clone + PartialEq + Display + IntoEnumIterator>(
     }
 }
 
+/// Selects the [`Loadout`] of the current character by its id.
+#[component]
+fn ActionsLoadoutSelect(on_value: EventHandler<u32>, value: u32) -> Element {
+    let character = use_context::<AppState>().character;
+    let loadouts = use_memo(move || {
+        character()
+            .map(|character| character.loadouts)
+            .unwrap_or_default()
+    });
+    let loadout_names = use_memo(move || {
+        loadouts()
+            .into_iter()
+            .map(|loadout| loadout.name)
+            .collect::<Vec<_>>()
+    });
+
+    rsx! {
+        Select::<String> {
+            label: "Loadout",
+            disabled: false,
+            options: loadout_names(),
+            on_select: move |(index, _): (usize, String)| {
+                if let Some(loadout) = loadouts.peek().get(index) {
+                    on_value(loadout.id);
+                }
+            },
+            selected: loadouts().iter().position(|loadout| loadout.id == value).unwrap_or_default(),
+        }
+    }
+}
+
 #[component]
 fn ActionsNumberInputI32(
     label: &'static str,
@@ -1967,13 +4176,14 @@ fn ActionsNumberInputI32(
 fn ActionsNumberInputU32(
     label: &'static str,
     #[props(default = false)] disabled: bool,
+    #[props(default = 1)] minimum_value: u32,
     on_value: EventHandler<u32>,
     value: u32,
 ) -> Element {
     rsx! {
         NumberInputU32 {
             label,
-            minimum_value: 1,
+            minimum_value,
             disabled,
             on_value,
             value,
@@ -2048,6 +4258,41 @@ fn find_linked_action_range(actions: &[Action], action_index: usize) -> Option<R
     Some(start..end)
 }
 
+/// Computes the action order after dragging the action at `from` onto the action at `to`.
+///
+/// A non-linked action is dragged together with its linked actions and is placed before `to` when
+/// dragging up or after `to` and its linked actions when dragging down.
+fn drag_order(actions: &[Action], from: usize, to: usize) -> Option<Vec<usize>> {
+    let group_end = |index: usize| {
+        if matches!(actions[index].condition(), ActionCondition::Linked) {
+            index + 1
+        } else {
+            find_linked_action_range(actions, index)
+                .map(|range| range.end)
+                .unwrap_or(index + 1)
+        }
+    };
+    if from >= actions.len() || to >= actions.len() {
+        return None;
+    }
+    let range = from..group_end(from);
+    if range.contains(&to) {
+        return None;
+    }
+
+    let mut order = (0..actions.len())
+        .filter(|index| !range.contains(index))
+        .collect::<Vec<_>>();
+    let target = if to < from {
+        order.iter().position(|index| *index == to)?
+    } else {
+        let last = group_end(to) - 1;
+        order.iter().position(|index| *index == last)? + 1
+    };
+    order.splice(target..target, range);
+    Some(order)
+}
+
 /// Finds the last linked action index of the last action matching `condition_filter`.
 fn find_last_linked_action_index(
     actions: &[Action],
@@ -2067,6 +4312,31 @@ fn find_last_linked_action_index(
     Some(last_index)
 }
 
+/// Maps each of the filtered `actions` to whether it is in the then branch of an
+/// [`Action::Group`].
+///
+/// Actions that are not linked to a group are mapped to [`None`].
+fn group_branches(actions: &[(Action, usize)]) -> Vec<Option<bool>> {
+    let mut group = None;
+
+    actions
+        .iter()
+        .map(|(action, _)| {
+            if !matches!(action.condition(), ActionCondition::Linked) {
+                group = match action {
+                    Action::Group(action) => Some((action.then_count, 0)),
+                    Action::Move(_) | Action::Key(_) | Action::Mouse(_) => None,
+                };
+                return None;
+            }
+            group.as_mut().map(|(then_count, count)| {
+                *count += 1;
+                *count <= *then_count
+            })
+        })
+        .collect()
+}
+
 /// Filters `actions` to find action with condition matching `condition_filter` including linked
 /// action(s) of that matching action.
 ///
@@ -1,20 +1,28 @@
-use std::{fmt::Display, fs::File, io::BufReader};
+use std::{fmt::Display, fs::File, io::BufReader, iter};
 
 use backend::{
-    ActionConfiguration, ActionConfigurationCondition, ActionKeyWith, Character, Class,
-    EliteBossBehavior, IntoEnumIterator, KeyBinding, KeyBindingConfiguration, LinkKeyBinding,
-    PotionMode, delete_character, query_characters, update_character, upsert_character,
+    ActionConfiguration, ActionConfigurationCondition, ActionKeyDirection, ActionKeyWith, BuffKind,
+    BurstWindow, Character, Class, ClientPoint, ComboAction, ComboStep, EliteBossBehavior, Event,
+    IntoEnumIterator, InventoryFullBehavior, KeyBinding, KeyBindingConfiguration, LinkKeyBinding,
+    Loadout, MAX_POTIONS, Position, Potion, PotionMode, SellItems, ShutdownRoutine,
+    delete_character, event_receiver, query_characters, query_minimaps, switch_loadout,
+    update_character, upsert_character,
 };
 use dioxus::prelude::*;
 use futures_util::StreamExt;
 use rand::distr::{Alphanumeric, SampleString};
+use tokio::sync::broadcast::error::RecvError;
 
 use crate::{
     AppState,
     button::{Button, ButtonKind},
-    icons::XIcon,
-    inputs::{Checkbox, KeyBindingInput, MillisInput, NumberInputU32, PercentageInput},
-    select::{EnumSelect, TextSelect},
+    icons::{PositionIcon, XIcon},
+    inputs::{
+        Checkbox, KeyBindingInput, MillisInput, NumberInputI32, NumberInputU32, PercentageInput,
+        TextInput,
+    },
+    select::{EnumSelect, Select, TextSelect},
+    toast::ToastResult,
 };
 
 #[derive(Debug)]
@@ -25,7 +33,7 @@ enum CharacterUpdate {
     Delete,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Debug)]
 enum ActionConfigurationInputKind {
     Add(ActionConfiguration),
     Edit(ActionConfiguration, usize),
@@ -34,7 +42,8 @@ enum ActionConfigurationInputKind {
 #[component]
 pub fn Characters() -> Element {
     let mut character = use_context::<AppState>().character;
-    let mut characters = use_resource(async || query_characters().await.unwrap_or_default());
+    let mut characters =
+        use_resource(async || query_characters().await.ok_or_toast().unwrap_or_default());
     // Maps queried `characters` to names
     let character_names = use_memo(move || {
         characters()
@@ -62,15 +71,19 @@ pub fn Characters() -> Element {
     let coroutine = use_coroutine(
         move |mut rx: UnboundedReceiver<CharacterUpdate>| async move {
             let mut save_character = async move |new_character: Character| {
-                character.set(Some(upsert_character(new_character).await));
+                let Some(new_character) = upsert_character(new_character).await.ok_or_toast()
+                else {
+                    return;
+                };
+                character.set(Some(new_character));
                 characters.restart();
-                update_character(character()).await;
+                update_character(character()).await.ok_or_toast();
             };
 
             while let Some(message) = rx.next().await {
                 match message {
                     CharacterUpdate::Set => {
-                        update_character(character()).await;
+                        update_character(character()).await.ok_or_toast();
                     }
                     CharacterUpdate::Update(new_character) => {
                         save_character(new_character).await;
@@ -84,8 +97,8 @@ pub fn Characters() -> Element {
                     }
                     CharacterUpdate::Delete => {
                         if let Some(character) = character.take() {
-                            delete_character(character).await;
-                            update_character(None).await;
+                            delete_character(character).await.ok_or_toast();
+                            update_character(None).await.ok_or_toast();
                             characters.restart();
                         }
                     }
@@ -108,16 +121,49 @@ pub fn Characters() -> Element {
             coroutine.send(CharacterUpdate::Set);
         }
     });
+    // Follows the character switched automatically from the linked minimap
+    use_future(move || async move {
+        let Some(mut receiver) = event_receiver().await.ok_or_toast() else {
+            return;
+        };
+        loop {
+            let record = match receiver.recv().await {
+                Ok(record) => record,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let Event::CharacterSwitched { id, .. } = record.event else {
+                continue;
+            };
+            let switched = query_characters()
+                .await
+                .ok_or_toast()
+                .unwrap_or_default()
+                .into_iter()
+                .find(|character| character.id == Some(id));
+            if let Some(switched) = switched {
+                character.set(Some(switched));
+                characters.restart();
+            }
+        }
+    });
 
     rsx! {
         div { class: "flex flex-col pb-15 h-full overflow-y-auto scrollbar",
             SectionKeyBindings { character_view, save_character }
             SectionBuffs { character_view, save_character }
+            SectionPotions { character_view, save_character }
+            SectionKeyTimings { character_view, save_character }
             SectionFixedActions {
                 action_input_kind,
                 character_view,
                 save_character,
             }
+            SectionCombos { character_view, save_character }
+            SectionLoadouts { character_view, save_character }
+            SectionSellItems { character_view, save_character }
+            SectionLinkedMap { character_view, save_character }
+            SectionShutdownRoutine { character_view, save_character }
             SectionOthers { character_view, save_character }
         }
 
@@ -231,6 +277,18 @@ fn SectionKeyBindings(
                     },
                     value: character_view().up_jump_key,
                 }
+                KeyBindingConfigurationInput {
+                    label: "Flight",
+                    optional: true,
+                    disabled: character_view().id.is_none(),
+                    on_value: move |flight_key| {
+                        save_character(Character {
+                            flight_key,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().flight_key,
+                }
                 KeyBindingConfigurationInput {
                     label: "Interact",
                     disabled: character_view().id.is_none(),
@@ -275,6 +333,17 @@ fn SectionKeyBindings(
                     },
                     value: character_view().change_channel_key,
                 }
+                KeyBindingConfigurationInput {
+                    label: "Pick up",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |key_config: Option<KeyBindingConfiguration>| {
+                        save_character(Character {
+                            pickup_key: key_config.expect("not optional"),
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().pickup_key,
+                }
                 KeyBindingConfigurationInput {
                     label: "Feed pet",
                     disabled: character_view().id.is_none(),
@@ -297,6 +366,17 @@ fn SectionKeyBindings(
                     },
                     value: character_view().potion_key,
                 }
+                KeyBindingConfigurationInput {
+                    label: "MP potion",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |key_config: Option<KeyBindingConfiguration>| {
+                        save_character(Character {
+                            mp_potion_key: key_config.expect("not optional"),
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().mp_potion_key,
+                }
                 div { class: "col-span-full grid-cols-3 grid gap-2 justify-items-stretch",
                     KeyBindingConfigurationInput {
                         label: "Familiar menu",
@@ -504,24 +584,856 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                 Buff {
                     label: "Extreme Green Potion",
                     disabled: character_view().id.is_none(),
-                    on_value: move |extreme_green_potion_key| {
+                    on_value: move |extreme_green_potion_key| {
+                        save_character(Character {
+                            extreme_green_potion_key,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().extreme_green_potion_key,
+                }
+                Buff {
+                    label: "Extreme Gold Potion",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |extreme_gold_potion_key| {
+                        save_character(Character {
+                            extreme_gold_potion_key,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().extreme_gold_potion_key,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionPotions(character_view: Memo<Character>, save_character: Callback<Character>) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+    let save_potion = use_callback(move |(index, potion): (usize, Potion)| {
+        let mut character = character_view.peek().clone();
+        *character.potions.get_mut(index).expect("valid index") = potion;
+        save_character(character);
+    });
+
+    rsx! {
+        Section { name: "Potions",
+            p { class: "paragraph-xs mb-2",
+                "Potions are checked in order and the first one below health threshold and not in cooldown is used."
+            }
+            div { class: "grid grid-cols-[auto_auto_auto_auto_16px] gap-4 items-end",
+                for (index , potion) in character_view().potions.into_iter().enumerate() {
+                    KeyBindingInput {
+                        label: "Key",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |key: Option<KeyBinding>| {
+                            save_potion((
+                                index,
+                                Potion {
+                                    key: key.expect("not optional"),
+                                    ..potion
+                                },
+                            ));
+                        },
+                        value: Some(potion.key),
+                    }
+                    CharactersPercentageInput {
+                        label: "Below health",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |below_percent| {
+                            save_potion((index, Potion { below_percent, ..potion }));
+                        },
+                        value: potion.below_percent,
+                    }
+                    CharactersMillisInput {
+                        label: "Cooldown",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |cooldown_millis| {
+                            save_potion((index, Potion { cooldown_millis, ..potion }));
+                        },
+                        value: potion.cooldown_millis,
+                    }
+                    CharactersCheckbox {
+                        label: "Enabled",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |enabled| {
+                            save_potion((index, Potion { enabled, ..potion }));
+                        },
+                        value: potion.enabled,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            let mut character = character_view.peek().clone();
+                            character.potions.remove(index);
+                            save_character(character);
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Add potion",
+                kind: ButtonKind::Secondary,
+                disabled: character_view().id.is_none()
+                    || character_view().potions.len() >= MAX_POTIONS,
+                on_click: move |_| {
+                    let mut character = character_view.peek().clone();
+                    character.potions.push(Potion::default());
+                    save_character(character);
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionKeyTimings(
+    character_view: Memo<Character>,
+    save_character: Callback<Character>,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+    let save_key_timing = use_callback(move |(index, config): (usize, KeyBindingConfiguration)| {
+        let mut character = character_view.peek().clone();
+        *character.key_timings.get_mut(index).expect("valid index") = config;
+        save_character(character);
+    });
+
+    rsx! {
+        Section { name: "Key timings",
+            p { class: "paragraph-xs mb-2",
+                "Overrides how long a key is held and how often it can be repeated. Set to 0 to use the default."
+            }
+            div { class: "grid grid-cols-[auto_auto_auto_auto_16px] gap-4 items-end",
+                for (index , config) in character_view().key_timings.into_iter().enumerate() {
+                    KeyBindingInput {
+                        label: "Key",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |key: Option<KeyBinding>| {
+                            save_key_timing((
+                                index,
+                                KeyBindingConfiguration {
+                                    key: key.expect("not optional"),
+                                    ..config
+                                },
+                            ));
+                        },
+                        value: Some(config.key),
+                    }
+                    CharactersMillisInput {
+                        label: "Press duration",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |millis| {
+                            let press_millis = (millis > 0).then_some(millis);
+                            save_key_timing((
+                                index,
+                                KeyBindingConfiguration {
+                                    press_millis,
+                                    ..config
+                                },
+                            ));
+                        },
+                        value: config.press_millis.unwrap_or_default(),
+                    }
+                    CharactersMillisInput {
+                        label: "Repeat interval",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |millis| {
+                            let repeat_millis = (millis > 0).then_some(millis);
+                            save_key_timing((
+                                index,
+                                KeyBindingConfiguration {
+                                    repeat_millis,
+                                    ..config
+                                },
+                            ));
+                        },
+                        value: config.repeat_millis.unwrap_or_default(),
+                    }
+                    CharactersCheckbox {
+                        label: "Enabled",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |enabled| {
+                            save_key_timing((index, KeyBindingConfiguration { enabled, ..config }));
+                        },
+                        value: config.enabled,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            let mut character = character_view.peek().clone();
+                            character.key_timings.remove(index);
+                            save_character(character);
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Add key timing",
+                kind: ButtonKind::Secondary,
+                disabled: character_view().id.is_none(),
+                on_click: move |_| {
+                    let mut character = character_view.peek().clone();
+                    character.key_timings.push(KeyBindingConfiguration {
+                        enabled: true,
+                        ..KeyBindingConfiguration::default()
+                    });
+                    save_character(character);
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionFixedActions(
+    action_input_kind: Signal<Option<ActionConfigurationInputKind>>,
+    character_view: Memo<Character>,
+    save_character: Callback<Character>,
+) -> Element {
+    let delete_action = use_callback(move |index| {
+        let mut character = character_view.peek().clone();
+        character.actions.remove(index);
+        save_character(character);
+    });
+    let toggle_action = use_callback(move |(enabled, index): (bool, usize)| {
+        let mut character = character_view.peek().clone();
+        let action = character.actions.get_mut(index).unwrap();
+        action.enabled = enabled;
+        save_character(character);
+    });
+    let burst_window_view = use_memo(move || character_view().burst_window);
+    let save_burst_window = use_callback(move |burst_window: BurstWindow| {
+        save_character(Character {
+            burst_window,
+            ..character_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Fixed actions",
+            div { class: "grid grid-cols-3 gap-4 mb-2",
+                CharactersCheckbox {
+                    label: "Burst window",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |enabled| {
+                        save_burst_window(BurstWindow {
+                            enabled,
+                            ..*burst_window_view.peek()
+                        });
+                    },
+                    value: burst_window_view().enabled,
+                }
+                CharactersMillisInput {
+                    label: "Burst every",
+                    disabled: character_view().id.is_none() || !burst_window_view().enabled,
+                    on_value: move |interval_millis| {
+                        save_burst_window(BurstWindow {
+                            interval_millis,
+                            ..*burst_window_view.peek()
+                        });
+                    },
+                    value: burst_window_view().interval_millis,
+                }
+                div {} // Spacer
+            }
+            ActionConfigurationList {
+                disabled: character_view().id.is_none(),
+                on_add_click: move |_| {
+                    action_input_kind
+                        .set(
+                            Some(ActionConfigurationInputKind::Add(ActionConfiguration::default())),
+                        );
+                },
+                on_item_click: move |(action, index)| {
+                    action_input_kind.set(Some(ActionConfigurationInputKind::Edit(action, index)));
+                },
+                on_item_delete: delete_action,
+                on_item_toggle: toggle_action,
+                actions: character_view().actions,
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionCombos(character_view: Memo<Character>, save_character: Callback<Character>) -> Element {
+    let save_combo = use_callback(move |(index, combo): (usize, ComboAction)| {
+        let mut character = character_view.peek().clone();
+        *character.combos.get_mut(index).expect("valid index") = combo;
+        save_character(character);
+    });
+
+    rsx! {
+        Section { name: "Combos",
+            p { class: "paragraph-xs mb-2",
+                "Sends the steps in order without being interrupted. Each step is sent after its delay in ticks from the previous step."
+            }
+            for (index , combo) in character_view().combos.into_iter().enumerate() {
+                ComboInput {
+                    disabled: character_view().id.is_none(),
+                    on_value: move |combo| {
+                        save_combo((index, combo));
+                    },
+                    on_delete: move |_| {
+                        let mut character = character_view.peek().clone();
+                        character.combos.remove(index);
+                        save_character(character);
+                    },
+                    value: combo,
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Add combo",
+                kind: ButtonKind::Secondary,
+                disabled: character_view().id.is_none(),
+                on_click: move |_| {
+                    let mut character = character_view.peek().clone();
+                    character.combos.push(ComboAction::default());
+                    save_character(character);
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionLoadouts(
+    character_view: Memo<Character>,
+    save_character: Callback<Character>,
+) -> Element {
+    let mut character = use_context::<AppState>().character;
+    let loadout_names = use_memo(move || {
+        iter::once("None".to_string())
+            .chain(
+                character_view()
+                    .loadouts
+                    .into_iter()
+                    .map(|loadout| loadout.name),
+            )
+            .collect::<Vec<_>>()
+    });
+    let loadout_index = use_memo(move || {
+        let character = character_view();
+        character
+            .loadout
+            .and_then(|name| {
+                character
+                    .loadouts
+                    .iter()
+                    .position(|loadout| loadout.name == name)
+            })
+            .map(|index| index + 1)
+            .unwrap_or_default()
+    });
+    let save_loadout = use_callback(move |(index, loadout): (usize, Loadout)| {
+        let mut character = character_view.peek().clone();
+        let previous = character.loadouts.get_mut(index).expect("valid index");
+        if character.loadout.as_ref() == Some(&previous.name) {
+            character.loadout = Some(loadout.name.clone());
+        }
+        *previous = loadout;
+        save_character(character);
+    });
+
+    rsx! {
+        Section { name: "Loadouts",
+            p { class: "paragraph-xs mb-2",
+                "While active, a loadout uses its copy of the fixed actions instead and does not use the disabled buffs. Loadout actions of the map are only used while their loadout is active."
+            }
+            Select::<String> {
+                label: "Active loadout",
+                div_class: "mb-3",
+                disabled: character_view().id.is_none(),
+                options: loadout_names(),
+                on_select: move |(index, name): (usize, String)| {
+                    spawn(async move {
+                        let name = (index > 0).then_some(name);
+                        if let Some(Some(switched)) = switch_loadout(name).await.ok_or_toast() {
+                            character.set(Some(switched));
+                        }
+                    });
+                },
+                selected: loadout_index(),
+            }
+            for (index , loadout) in character_view().loadouts.into_iter().enumerate() {
+                LoadoutInput {
+                    disabled: character_view().id.is_none(),
+                    on_value: move |loadout| {
+                        save_loadout((index, loadout));
+                    },
+                    on_copy_actions: move |_| {
+                        let mut character = character_view.peek().clone();
+                        let actions = character.actions.clone();
+                        character.loadouts.get_mut(index).expect("valid index").actions = actions;
+                        save_character(character);
+                    },
+                    on_delete: move |_| {
+                        let mut character = character_view.peek().clone();
+                        let loadout = character.loadouts.remove(index);
+                        if character.loadout.as_ref() == Some(&loadout.name) {
+                            character.loadout = None;
+                        }
+                        save_character(character);
+                    },
+                    value: loadout,
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Add loadout",
+                kind: ButtonKind::Secondary,
+                disabled: character_view().id.is_none(),
+                on_click: move |_| {
+                    let mut character = character_view.peek().clone();
+                    let id = character
+                        .loadouts
+                        .iter()
+                        .map(|loadout| loadout.id + 1)
+                        .max()
+                        .unwrap_or_default();
+                    character.loadouts.push(Loadout {
+                        id,
+                        name: format!("Loadout {id}"),
+                        ..Loadout::default()
+                    });
+                    save_character(character);
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn LoadoutInput(
+    disabled: bool,
+    on_value: EventHandler<Loadout>,
+    on_copy_actions: EventHandler,
+    on_delete: EventHandler,
+    value: Loadout,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+    let mut loadout = use_signal(|| value.clone());
+
+    use_effect(use_reactive!(|value| loadout.set(value)));
+
+    rsx! {
+        div { class: "flex flex-col pb-3 mb-3 border-b border-gray-800",
+            div { class: "grid grid-cols-[auto_auto_16px] gap-4 items-end",
+                TextInput {
+                    label: "Name",
+                    disabled,
+                    on_value: move |name| {
+                        on_value(Loadout {
+                            name,
+                            ..loadout.peek().clone()
+                        });
+                    },
+                    value: loadout().name,
+                }
+                Button {
+                    text: format!("Copy fixed actions ({})", loadout().actions.len()),
+                    kind: ButtonKind::Secondary,
+                    disabled,
+                    on_click: move |_| {
+                        on_copy_actions(());
+                    },
+                }
+                div {
+                    class: ICON_CONTAINER_CLASS,
+                    onclick: move |_| {
+                        on_delete(());
+                    },
+                    XIcon { class: "{ICON_CLASS} text-red-500" }
+                }
+            }
+            div { class: "grid grid-cols-2 xl:grid-cols-4 gap-2 mt-2",
+                for kind in BuffKind::iter().filter(|kind| !matches!(kind, BuffKind::Rune)) {
+                    Checkbox {
+                        label: format!("Disable {kind}"),
+                        input_class: "w-6",
+                        disabled,
+                        on_value: move |is_disabled: bool| {
+                            let mut loadout = loadout.peek().clone();
+                            loadout.disabled_buffs.retain(|buff| *buff != kind);
+                            if is_disabled {
+                                loadout.disabled_buffs.push(kind);
+                            }
+                            on_value(loadout);
+                        },
+                        value: loadout().disabled_buffs.contains(&kind),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ComboInput(
+    disabled: bool,
+    on_value: EventHandler<ComboAction>,
+    on_delete: EventHandler,
+    value: ComboAction,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+    let mut combo = use_signal(|| value.clone());
+    let save_step = use_callback(move |(index, step): (usize, ComboStep)| {
+        let mut combo = combo.peek().clone();
+        *combo.steps.get_mut(index).expect("valid index") = step;
+        on_value(combo);
+    });
+
+    use_effect(use_reactive!(|value| combo.set(value)));
+
+    rsx! {
+        div { class: "flex flex-col pb-3 mb-3 border-b border-gray-800",
+            div { class: "grid grid-cols-[auto_auto_auto_16px] gap-4 items-end",
+                TextInput {
+                    label: "Name",
+                    disabled,
+                    on_value: move |name| {
+                        on_value(ComboAction {
+                            name,
+                            ..combo.peek().clone()
+                        });
+                    },
+                    value: combo().name,
+                }
+                CharactersMillisInput {
+                    label: "Use every",
+                    disabled,
+                    on_value: move |every_millis| {
+                        on_value(ComboAction {
+                            every_millis,
+                            ..combo.peek().clone()
+                        });
+                    },
+                    value: combo().every_millis,
+                }
+                CharactersCheckbox {
+                    label: "Enabled",
+                    disabled,
+                    on_value: move |enabled| {
+                        on_value(ComboAction {
+                            enabled,
+                            ..combo.peek().clone()
+                        });
+                    },
+                    value: combo().enabled,
+                }
+                div {
+                    class: ICON_CONTAINER_CLASS,
+                    onclick: move |_| {
+                        on_delete(());
+                    },
+                    XIcon { class: "{ICON_CLASS} text-red-500" }
+                }
+                for (index , step) in combo().steps.into_iter().enumerate() {
+                    KeyBindingInput {
+                        label: "Key",
+                        disabled,
+                        on_value: move |key: Option<KeyBinding>| {
+                            save_step((
+                                index,
+                                ComboStep {
+                                    key: key.expect("not optional"),
+                                    ..step
+                                },
+                            ));
+                        },
+                        value: Some(step.key),
+                    }
+                    CharactersSelect::<ActionKeyDirection> {
+                        label: "Direction",
+                        disabled,
+                        on_select: move |direction| {
+                            save_step((index, ComboStep { direction, ..step }));
+                        },
+                        selected: step.direction,
+                    }
+                    NumberInputU32 {
+                        label: "Delay ticks",
+                        disabled,
+                        minimum_value: 0,
+                        on_value: move |delay_ticks| {
+                            save_step((index, ComboStep { delay_ticks, ..step }));
+                        },
+                        value: step.delay_ticks,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            let mut combo = combo.peek().clone();
+                            combo.steps.remove(index);
+                            on_value(combo);
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Add step",
+                kind: ButtonKind::Secondary,
+                disabled,
+                on_click: move |_| {
+                    let mut combo = combo.peek().clone();
+                    combo.steps.push(ComboStep::default());
+                    on_value(combo);
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionSellItems(
+    character_view: Memo<Character>,
+    save_character: Callback<Character>,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "absolute invisible group-hover:visible top-5 right-1 w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-3 h-3 text-gray-50 fill-current";
+
+    let position = use_context::<AppState>().position;
+    let sell_items_view = use_memo(move || character_view().sell_items);
+    let save_sell_items = use_callback(move |sell_items: SellItems| {
+        save_character(Character {
+            sell_items,
+            ..character_view.peek().clone()
+        });
+    });
+    let save_npc_position = use_callback(move |npc_position: Position| {
+        save_sell_items(SellItems {
+            npc_position,
+            ..sell_items_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Sell items",
+            div { class: "grid grid-cols-3 gap-4",
+                CharactersCheckbox {
+                    label: "Enabled",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |enabled| {
+                        save_sell_items(SellItems {
+                            enabled,
+                            ..sell_items_view.peek().clone()
+                        });
+                    },
+                    value: sell_items_view().enabled,
+                }
+                CharactersCheckbox {
+                    label: "Sell when inventory full",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |on_inventory_full| {
+                        save_sell_items(SellItems {
+                            on_inventory_full,
+                            ..sell_items_view.peek().clone()
+                        });
+                    },
+                    value: sell_items_view().on_inventory_full,
+                }
+                CharactersMillisInput {
+                    label: "Sell every",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |every_millis| {
+                        save_sell_items(SellItems {
+                            every_millis,
+                            ..sell_items_view.peek().clone()
+                        });
+                    },
+                    value: sell_items_view().every_millis,
+                }
+                div { class: "relative group",
+                    NumberInputI32 {
+                        label: "NPC X",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |x| {
+                            save_npc_position(Position {
+                                x,
+                                ..sell_items_view.peek().npc_position
+                            });
+                        },
+                        value: sell_items_view().npc_position.x,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            save_npc_position(Position {
+                                x: position.peek().0,
+                                ..sell_items_view.peek().npc_position
+                            });
+                        },
+                        PositionIcon { class: ICON_CLASS }
+                    }
+                }
+                div { class: "relative group",
+                    NumberInputI32 {
+                        label: "NPC Y",
+                        disabled: character_view().id.is_none(),
+                        on_value: move |y| {
+                            save_npc_position(Position {
+                                y,
+                                ..sell_items_view.peek().npc_position
+                            });
+                        },
+                        value: sell_items_view().npc_position.y,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            save_npc_position(Position {
+                                y: position.peek().1,
+                                ..sell_items_view.peek().npc_position
+                            });
+                        },
+                        PositionIcon { class: ICON_CLASS }
+                    }
+                }
+                CharactersCheckbox {
+                    label: "Adjust",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |allow_adjusting| {
+                        save_npc_position(Position {
+                            allow_adjusting,
+                            ..sell_items_view.peek().npc_position
+                        });
+                    },
+                    value: sell_items_view().npc_position.allow_adjusting,
+                }
+                CharactersPointsInput {
+                    label: "Inventory tabs (x,y; x,y; ...)",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |tabs| {
+                        save_sell_items(SellItems {
+                            tabs,
+                            ..sell_items_view.peek().clone()
+                        });
+                    },
+                    value: sell_items_view().tabs,
+                }
+                CharactersPointsInput {
+                    label: "Slots (x,y; x,y; ...)",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |slots| {
+                        save_sell_items(SellItems {
+                            slots,
+                            ..sell_items_view.peek().clone()
+                        });
+                    },
+                    value: sell_items_view().slots,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionLinkedMap(
+    character_view: Memo<Character>,
+    save_character: Callback<Character>,
+) -> Element {
+    let minimaps = use_resource(async || query_minimaps().await.ok_or_toast().unwrap_or_default());
+    let minimap = use_memo(move || {
+        let minimap_id = character_view().minimap_id;
+        minimaps()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|minimap| minimap.id.is_some() && minimap.id == minimap_id)
+    });
+    let minimap_names = use_memo(move || {
+        iter::once("None".to_string())
+            .chain(
+                minimaps()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|minimap| minimap.name),
+            )
+            .collect::<Vec<_>>()
+    });
+    let minimap_index = use_memo(move || {
+        minimap()
+            .and_then(|minimap| {
+                minimaps()
+                    .unwrap_or_default()
+                    .iter()
+                    .position(|data| data.id == minimap.id)
+            })
+            .map(|index| index + 1)
+            .unwrap_or_default()
+    });
+    let presets = use_memo(move || {
+        let mut presets = minimap()
+            .map(|minimap| minimap.actions.into_keys().collect::<Vec<_>>())
+            .unwrap_or_default();
+        presets.sort();
+        presets
+    });
+    let preset_names = use_memo(move || {
+        iter::once("Default".to_string())
+            .chain(presets())
+            .collect::<Vec<_>>()
+    });
+    let preset_index = use_memo(move || {
+        character_view()
+            .minimap_preset
+            .and_then(|preset| presets().iter().position(|name| *name == preset))
+            .map(|index| index + 1)
+            .unwrap_or_default()
+    });
+
+    rsx! {
+        Section { name: "Linked map",
+            p { class: "paragraph-xs",
+                "The linked map and preset are loaded automatically when this character is selected."
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                Select::<String> {
+                    label: "Map",
+                    disabled: character_view().id.is_none(),
+                    options: minimap_names(),
+                    on_select: move |(index, _): (usize, String)| {
+                        let minimap_id = index.checked_sub(1).and_then(|index| {
+                            minimaps
+                                .peek()
+                                .as_ref()
+                                .and_then(|minimaps| minimaps.get(index))
+                                .and_then(|minimap| minimap.id)
+                        });
                         save_character(Character {
-                            extreme_green_potion_key,
+                            minimap_id,
+                            minimap_preset: None,
                             ..character_view.peek().clone()
                         });
                     },
-                    value: character_view().extreme_green_potion_key,
+                    selected: minimap_index(),
                 }
-                Buff {
-                    label: "Extreme Gold Potion",
-                    disabled: character_view().id.is_none(),
-                    on_value: move |extreme_gold_potion_key| {
+                Select::<String> {
+                    label: "Preset",
+                    disabled: character_view().id.is_none() || minimap().is_none(),
+                    options: preset_names(),
+                    on_select: move |(index, preset): (usize, String)| {
                         save_character(Character {
-                            extreme_gold_potion_key,
+                            minimap_preset: (index > 0).then_some(preset),
                             ..character_view.peek().clone()
                         });
                     },
-                    value: character_view().extreme_gold_potion_key,
+                    selected: preset_index(),
                 }
             }
         }
@@ -529,39 +1441,81 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
 }
 
 #[component]
-fn SectionFixedActions(
-    action_input_kind: Signal<Option<ActionConfigurationInputKind>>,
+fn SectionShutdownRoutine(
     character_view: Memo<Character>,
     save_character: Callback<Character>,
 ) -> Element {
-    let delete_action = use_callback(move |index| {
-        let mut character = character_view.peek().clone();
-        character.actions.remove(index);
-        save_character(character);
-    });
-    let toggle_action = use_callback(move |(enabled, index): (bool, usize)| {
-        let mut character = character_view.peek().clone();
-        let action = character.actions.get_mut(index).unwrap();
-        action.enabled = enabled;
-        save_character(character);
+    let routine_view = use_memo(move || character_view().shutdown_routine);
+    let save_routine = use_callback(move |shutdown_routine: ShutdownRoutine| {
+        save_character(Character {
+            shutdown_routine,
+            ..character_view.peek().clone()
+        });
     });
+    let disabled = use_memo(move || character_view().id.is_none() || !routine_view().enabled);
 
     rsx! {
-        Section { name: "Fixed actions",
-            ActionConfigurationList {
-                disabled: character_view().id.is_none(),
-                on_add_click: move |_| {
-                    action_input_kind
-                        .set(
-                            Some(ActionConfigurationInputKind::Add(ActionConfiguration::default())),
-                        );
-                },
-                on_item_click: move |(action, index)| {
-                    action_input_kind.set(Some(ActionConfigurationInputKind::Edit(action, index)));
-                },
-                on_item_delete: delete_action,
-                on_item_toggle: toggle_action,
-                actions: character_view().actions,
+        Section { name: "Shutdown routine",
+            p { class: "paragraph-xs",
+                "Runs when stopping or after running for the set duration. Stopping again while it is running halts immediately."
+            }
+            div { class: "grid grid-cols-3 gap-3",
+                CharactersCheckbox {
+                    label: "Enabled",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |enabled| {
+                        save_routine(ShutdownRoutine {
+                            enabled,
+                            ..*routine_view.peek()
+                        });
+                    },
+                    value: routine_view().enabled,
+                }
+                CharactersMillisInput {
+                    label: "Stop after (0 to disable)",
+                    disabled: disabled(),
+                    on_value: move |stop_after_millis| {
+                        save_routine(ShutdownRoutine {
+                            stop_after_millis,
+                            ..*routine_view.peek()
+                        });
+                    },
+                    value: routine_view().stop_after_millis,
+                }
+                div {} // Spacer
+                CharactersCheckbox {
+                    label: "Go to town",
+                    disabled: disabled(),
+                    on_value: move |go_to_town| {
+                        save_routine(ShutdownRoutine {
+                            go_to_town,
+                            ..*routine_view.peek()
+                        });
+                    },
+                    value: routine_view().go_to_town,
+                }
+                CharactersCheckbox {
+                    label: "Notify summary",
+                    disabled: disabled(),
+                    on_value: move |notify| {
+                        save_routine(ShutdownRoutine {
+                            notify,
+                            ..*routine_view.peek()
+                        });
+                    },
+                    value: routine_view().notify,
+                }
+                CharactersCheckbox {
+                    label: "Exit game",
+                    disabled: disabled(),
+                    on_value: move |exit_game| {
+                        save_routine(ShutdownRoutine {
+                            exit_game,
+                            ..*routine_view.peek()
+                        });
+                    },
+                    value: routine_view().exit_game,
+                }
             }
         }
     }
@@ -660,6 +1614,33 @@ fn SectionOthers(character_view: Memo<Character>, save_character: Callback<Chara
                     },
                     value: character_view().feed_pet_key.enabled,
                 }
+                div {}
+                CharactersMillisInput {
+                    label: "Ignore picked up drop for",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |pickup_ignore_millis| {
+                        save_character(Character {
+                            pickup_ignore_millis,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().pickup_ignore_millis,
+                }
+                CharactersCheckbox {
+                    label: "Pick up drops",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |enabled| {
+                        let character = character_view.peek().clone();
+                        save_character(Character {
+                            pickup_key: KeyBindingConfiguration {
+                                enabled,
+                                ..character.pickup_key
+                            },
+                            ..character
+                        });
+                    },
+                    value: character_view().pickup_key.enabled,
+                }
                 CharactersSelect::<PotionMode> {
                     label: "Potion mode",
                     disabled: character_view().id.is_none(),
@@ -711,6 +1692,32 @@ fn SectionOthers(character_view: Memo<Character>, save_character: Callback<Chara
                             }
                         }
                     },
+                    PotionMode::MpPercentage(percent) => rsx! {
+                        div { class: "grid grid-cols-2 gap-2",
+                            CharactersPercentageInput {
+                                label: "Use below MP",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |percent| {
+                                    save_character(Character {
+                                        potion_mode: PotionMode::MpPercentage(percent),
+                                        ..character_view.peek().clone()
+                                    });
+                                },
+                                value: percent,
+                            }
+                            CharactersMillisInput {
+                                label: "MP update every",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |millis| {
+                                    save_character(Character {
+                                        health_update_millis: millis,
+                                        ..character_view.peek().clone()
+                                    });
+                                },
+                                value: character_view().health_update_millis,
+                            }
+                        }
+                    },
                 }
                 CharactersCheckbox {
                     label: "Use potion",
@@ -727,6 +1734,99 @@ fn SectionOthers(character_view: Memo<Character>, save_character: Callback<Chara
                     },
                     value: character_view().potion_key.enabled,
                 }
+                CharactersSelect::<PotionMode> {
+                    label: "MP potion mode",
+                    disabled: character_view().id.is_none(),
+                    on_select: move |mp_potion_mode| {
+                        save_character(Character {
+                            mp_potion_mode,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    selected: character_view().mp_potion_mode,
+                }
+                match character_view().mp_potion_mode {
+                    PotionMode::EveryMillis(millis) => rsx! {
+                        CharactersMillisInput {
+                            label: "Use every",
+                            disabled: character_view().id.is_none(),
+                            on_value: move |millis| {
+                                save_character(Character {
+                                    mp_potion_mode: PotionMode::EveryMillis(millis),
+                                    ..character_view.peek().clone()
+                                });
+                            },
+                            value: millis,
+                        }
+                    },
+                    PotionMode::Percentage(percent) => rsx! {
+                        div { class: "grid grid-cols-2 gap-2",
+                            CharactersPercentageInput {
+                                label: "Use below health",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |percent| {
+                                    save_character(Character {
+                                        mp_potion_mode: PotionMode::Percentage(percent),
+                                        ..character_view.peek().clone()
+                                    });
+                                },
+                                value: percent,
+                            }
+                            CharactersMillisInput {
+                                label: "Health update every",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |millis| {
+                                    save_character(Character {
+                                        health_update_millis: millis,
+                                        ..character_view.peek().clone()
+                                    });
+                                },
+                                value: character_view().health_update_millis,
+                            }
+                        }
+                    },
+                    PotionMode::MpPercentage(percent) => rsx! {
+                        div { class: "grid grid-cols-2 gap-2",
+                            CharactersPercentageInput {
+                                label: "Use below MP",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |percent| {
+                                    save_character(Character {
+                                        mp_potion_mode: PotionMode::MpPercentage(percent),
+                                        ..character_view.peek().clone()
+                                    });
+                                },
+                                value: percent,
+                            }
+                            CharactersMillisInput {
+                                label: "MP update every",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |millis| {
+                                    save_character(Character {
+                                        health_update_millis: millis,
+                                        ..character_view.peek().clone()
+                                    });
+                                },
+                                value: character_view().health_update_millis,
+                            }
+                        }
+                    },
+                }
+                CharactersCheckbox {
+                    label: "Use MP potion",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |enabled| {
+                        let character = character_view.peek().clone();
+                        save_character(Character {
+                            mp_potion_key: KeyBindingConfiguration {
+                                enabled,
+                                ..character.mp_potion_key
+                            },
+                            ..character
+                        });
+                    },
+                    value: character_view().mp_potion_key.enabled,
+                }
                 CharactersSelect::<Class> {
                     label: "Link key timing class",
                     disabled: character_view().id.is_none(),
@@ -738,7 +1838,18 @@ fn SectionOthers(character_view: Memo<Character>, save_character: Callback<Chara
                     },
                     selected: character_view().class,
                 }
-                div {}
+                CharactersCheckbox {
+                    label: "Hold flight key",
+                    disabled: character_view().id.is_none()
+                        || character_view().flight_key.is_none(),
+                    on_value: move |hold_flight_key| {
+                        save_character(Character {
+                            hold_flight_key,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().hold_flight_key,
+                }
                 CharactersCheckbox {
                     label: "Disable walking",
                     disabled: character_view().id.is_none(),
@@ -750,6 +1861,19 @@ fn SectionOthers(character_view: Memo<Character>, save_character: Callback<Chara
                     },
                     value: character_view().disable_adjusting,
                 }
+                CharactersCheckbox {
+                    label: "Disable jumping down",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |disable_jump_down| {
+                        save_character(Character {
+                            disable_jump_down,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().disable_jump_down,
+                }
+                div {}
+                div {}
                 CharactersSelect::<EliteBossBehavior> {
                     label: "Elite boss spawns behavior",
                     disabled: character_view().id.is_none(),
@@ -783,6 +1907,41 @@ fn SectionOthers(character_view: Memo<Character>, save_character: Callback<Chara
                     },
                     value: character_view().elite_boss_behavior_enabled,
                 }
+                CharactersSelect::<InventoryFullBehavior> {
+                    label: "Inventory full behavior",
+                    disabled: character_view().id.is_none(),
+                    on_select: move |inventory_full_behavior| {
+                        save_character(Character {
+                            inventory_full_behavior,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    selected: character_view().inventory_full_behavior,
+                }
+                TextInput {
+                    label: "Script path",
+                    disabled: character_view().id.is_none()
+                        || character_view().inventory_full_behavior
+                            != InventoryFullBehavior::RunScript,
+                    on_value: move |inventory_full_script| {
+                        save_character(Character {
+                            inventory_full_script,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().inventory_full_script,
+                }
+                CharactersCheckbox {
+                    label: "Enabled",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |inventory_full_behavior_enabled| {
+                        save_character(Character {
+                            inventory_full_behavior_enabled,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().inventory_full_behavior_enabled,
+                }
                 div { class: "flex gap-2 col-span-3",
                     div { class: "flex-grow",
                         a {
@@ -929,6 +2088,62 @@ fn CharactersMillisInput(
     }
 }
 
+/// Inputs a list of [`ClientPoint`] in the form of `x,y; x,y`.
+///
+/// The points are only saved when all of them can be parsed.
+#[component]
+fn CharactersPointsInput(
+    label: &'static str,
+    #[props(default = false)] disabled: bool,
+    on_value: EventHandler<Vec<ClientPoint>>,
+    value: Vec<ClientPoint>,
+) -> Element {
+    fn parse_points(text: &str) -> Option<Vec<ClientPoint>> {
+        text.split(';')
+            .map(str::trim)
+            .filter(|point| !point.is_empty())
+            .map(|point| {
+                let (x, y) = point.split_once(',')?;
+                Some(ClientPoint {
+                    x: x.trim().parse().ok()?,
+                    y: y.trim().parse().ok()?,
+                })
+            })
+            .collect()
+    }
+
+    let mut text = use_signal(String::default);
+
+    // Only overwrites when the text is not already representing the points so that partial
+    // input such as trailing `;` is kept while typing
+    use_effect(use_reactive!(|value| {
+        if parse_points(&text.peek()).as_ref() != Some(&value) {
+            text.set(
+                value
+                    .iter()
+                    .map(|point| format!("{},{}", point.x, point.y))
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            );
+        }
+    }));
+
+    rsx! {
+        TextInput {
+            label,
+            div_class: "col-span-3",
+            disabled,
+            on_value: move |new_text: String| {
+                if let Some(points) = parse_points(&new_text) {
+                    on_value(points);
+                }
+                text.set(new_text);
+            },
+            value: text(),
+        }
+    }
+}
+
 #[component]
 fn CharactersNumberU32Input(
     label: &'static str,
@@ -956,11 +2171,11 @@ fn PopupActionConfigurationInput(
     on_value: EventHandler<ActionConfiguration>,
     kind: ActionConfigurationInputKind,
 ) -> Element {
+    let modifying = matches!(kind, ActionConfigurationInputKind::Edit(_, _));
     let (action, index) = match kind {
         ActionConfigurationInputKind::Add(action) => (action, None),
         ActionConfigurationInputKind::Edit(action, index) => (action, Some(index)),
     };
-    let modifying = matches!(kind, ActionConfigurationInputKind::Edit(_, _));
     let can_create_linked_action = match action.condition {
         ActionConfigurationCondition::EveryMillis(_) => !is_actions_empty && index != Some(0),
         ActionConfigurationCondition::Linked => false,
@@ -1001,11 +2216,12 @@ fn ActionConfigurationInput(
     on_value: EventHandler<ActionConfiguration>,
     value: ActionConfiguration,
 ) -> Element {
-    let mut action = use_signal(|| value);
+    let mut action = use_signal(|| value.clone());
     let millis = use_memo(move || match action().condition {
         ActionConfigurationCondition::EveryMillis(millis) => Some(millis),
         ActionConfigurationCondition::Linked => None,
     });
+    let minimaps = use_resource(async || query_minimaps().await.ok_or_toast().unwrap_or_default());
 
     use_effect(use_reactive!(|value| { action.set(value) }));
 
@@ -1098,7 +2314,15 @@ fn ActionConfigurationInput(
                 },
                 value: millis().unwrap_or_default(),
             }
-            div {} // Spacer
+            CharactersCheckbox {
+                label: "Use in burst window",
+                disabled: millis().is_none(),
+                on_value: move |burst| {
+                    let mut action = action.write();
+                    action.burst = burst;
+                },
+                value: action().burst,
+            }
 
             // Wait before use
             CharactersMillisInput {
@@ -1136,6 +2360,29 @@ fn ActionConfigurationInput(
                 },
                 value: action().wait_after_millis_random_range,
             }
+            div {} // Spacer
+
+            // Map filter, used on every map if none is checked
+            p { class: "col-span-3 paragraph-xs", "Only on maps" }
+            for (id , name) in minimaps()
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|minimap| Some((minimap.id?, minimap.name)))
+            {
+                Checkbox {
+                    label: name,
+                    input_class: "w-6",
+                    disabled: millis().is_none(),
+                    on_value: move |checked| {
+                        let mut action = action.write();
+                        action.minimap_ids.retain(|minimap_id| *minimap_id != id);
+                        if checked {
+                            action.minimap_ids.push(id);
+                        }
+                    },
+                    value: action().minimap_ids.contains(&id),
+                }
+            }
         }
         div { class: "flex w-full gap-3 absolute bottom-0 py-2 bg-gray-900",
             Button {
@@ -1143,7 +2390,7 @@ fn ActionConfigurationInput(
                 text: if modifying { "Save" } else { "Add" },
                 kind: ButtonKind::Primary,
                 on_click: move |_| {
-                    on_value(*action.peek());
+                    on_value(action.peek().clone());
                 },
             }
             Button {
@@ -1197,11 +2444,14 @@ fn ActionConfigurationList(
                 div { class: "flex items-end",
                     div {
                         class: "relative group flex-grow",
-                        onclick: move |e| {
-                            e.stop_propagation();
-                            on_item_click((action, index));
+                        onclick: {
+                            let action = action.clone();
+                            move |e: Event<MouseData>| {
+                                e.stop_propagation();
+                                on_item_click((action.clone(), index));
+                            }
                         },
-                        ActionConfigurationItem { action }
+                        ActionConfigurationItem { action: action.clone() }
                         Icons {
                             condition: action.condition,
                             on_item_delete: move |_| {
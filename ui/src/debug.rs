@@ -1,11 +1,18 @@
-use backend::{capture_image, infer_minimap, infer_rune, record_images, test_spin_rune};
+use backend::{
+    bench::BenchmarkLatency, capture_image, infer_minimap, infer_rune, record_images,
+    run_benchmarks, test_spin_rune,
+};
 use dioxus::prelude::*;
 
-use crate::button::{Button, ButtonKind};
+use crate::{
+    button::{Button, ButtonKind},
+    toast::ToastResult,
+};
 
 #[component]
 pub fn Debug() -> Element {
     let mut is_recording = use_signal(|| false);
+    let mut benchmarks = use_signal(Vec::<BenchmarkLatency>::new);
 
     rsx! {
         div { class: "flex flex-col h-full overflow-y-auto scrollbar pr-4 pb-3",
@@ -14,35 +21,35 @@ pub fn Debug() -> Element {
                     text: "Capture color image",
                     kind: ButtonKind::Secondary,
                     on_click: move |_| async {
-                        capture_image(false).await;
+                        capture_image(false).await.ok_or_toast();
                     },
                 }
                 Button {
                     text: "Capture grayscale image",
                     kind: ButtonKind::Secondary,
                     on_click: move |_| async {
-                        capture_image(true).await;
+                        capture_image(true).await.ok_or_toast();
                     },
                 }
                 Button {
                     text: "Infer rune",
                     kind: ButtonKind::Secondary,
                     on_click: move |_| async {
-                        infer_rune().await;
+                        infer_rune().await.ok_or_toast();
                     },
                 }
                 Button {
                     text: "Infer minimap",
                     kind: ButtonKind::Secondary,
                     on_click: move |_| async {
-                        infer_minimap().await;
+                        infer_minimap().await.ok_or_toast();
                     },
                 }
                 Button {
                     text: "Spin rune sandbox test",
                     kind: ButtonKind::Secondary,
                     on_click: move |_| async {
-                        test_spin_rune().await;
+                        test_spin_rune().await.ok_or_toast();
                     },
                 }
                 Button {
@@ -51,9 +58,32 @@ pub fn Debug() -> Element {
                     on_click: move |_| async move {
                         let recording = *is_recording.peek();
                         is_recording.toggle();
-                        record_images(!recording).await;
+                        record_images(!recording).await.ok_or_toast();
                     },
                 }
+                Button {
+                    text: "Run benchmarks",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        if let Some(latencies) = run_benchmarks(100).await.ok_or_toast() {
+                            benchmarks.set(latencies);
+                        }
+                    },
+                }
+            }
+            for latency in benchmarks() {
+                p { class: "paragraph-xs mt-2",
+                    {
+                        format!(
+                            "{}: mean {:?}, min {:?}, max {:?}{}",
+                            latency.stage,
+                            latency.mean,
+                            latency.min,
+                            latency.max,
+                            if latency.failed { " (failed)" } else { "" },
+                        )
+                    }
+                }
             }
         }
     }
@@ -59,6 +59,39 @@ pub fn TextInput(
     }
 }
 
+#[component]
+pub fn PasswordInput(
+    GenericInputProps {
+        label,
+        label_class,
+        div_class,
+        input_class,
+        disabled,
+        on_value,
+        value,
+    }: GenericInputProps<String>,
+) -> Element {
+    rsx! {
+        LabeledInput {
+            label,
+            label_class: "{INPUT_LABEL_CLASS} {label_class}",
+            div_class: "{INPUT_DIV_CLASS} {div_class}",
+            disabled,
+            div { class: "{INPUT_CLASS} {input_class}",
+                input {
+                    class: "outline-none disabled:cursor-not-allowed w-full h-full",
+                    disabled,
+                    r#type: "password",
+                    oninput: move |e| {
+                        on_value(e.parsed::<String>().unwrap());
+                    },
+                    value,
+                }
+            }
+        }
+    }
+}
+
 #[component]
 pub fn Checkbox(
     GenericInputProps {
@@ -0,0 +1,177 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use backend::{Event, EventRecord, LogRecord, event_receiver, log_receiver, query_events};
+use dioxus::prelude::*;
+use log::Level;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    inputs::{Checkbox, TextInput},
+    select::Select,
+    toast::ToastResult,
+};
+
+/// Maximum number of records kept for display.
+const MAX_RECORDS: usize = 1000;
+
+/// Maximum number of persisted events loaded when opening the tab.
+const MAX_EVENTS: u32 = 200;
+
+#[component]
+pub fn Logs() -> Element {
+    let mut records = use_signal(VecDeque::<LogRecord>::new);
+    let mut level = use_signal(|| Level::Info);
+    let mut search = use_signal(String::new);
+    let mut events = use_signal(VecDeque::<EventRecord>::new);
+    let mut events_only = use_signal(|| false);
+    let levels = use_memo(|| Level::iter().collect::<Vec<_>>());
+    let filtered = use_memo(move || {
+        let level = level();
+        let search = search().to_lowercase();
+        records()
+            .iter()
+            .rev()
+            .filter(|record| record.level <= level)
+            .filter(|record| {
+                search.is_empty()
+                    || record.message.to_lowercase().contains(&search)
+                    || record.target.to_lowercase().contains(&search)
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    });
+
+    use_future(move || async move {
+        let (initial, mut receiver) = log_receiver().await;
+        records.set(initial.into_iter().collect());
+        loop {
+            let record = match receiver.recv().await {
+                Ok(record) => record,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let mut records = records.write();
+            if records.len() >= MAX_RECORDS {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+    });
+    use_future(move || async move {
+        let Some(mut receiver) = event_receiver().await.ok_or_toast() else {
+            return;
+        };
+        events.set(
+            query_events(MAX_EVENTS)
+                .await
+                .ok_or_toast()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+        );
+        loop {
+            let record = match receiver.recv().await {
+                Ok(record) => record,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let mut events = events.write();
+            if events.len() >= MAX_EVENTS as usize {
+                events.pop_front();
+            }
+            events.push_back(record);
+        }
+    });
+
+    rsx! {
+        div { class: "flex flex-col h-full gap-3 pr-4 pb-3",
+            div { class: "grid grid-cols-3 gap-3",
+                Select {
+                    label: "Level",
+                    options: levels(),
+                    on_select: move |(_, selected): (usize, Level)| {
+                        level.set(selected);
+                    },
+                    selected: levels()
+                        .iter()
+                        .position(|option| *option == level())
+                        .unwrap_or_default(),
+                }
+                TextInput {
+                    label: "Search",
+                    on_value: move |value| {
+                        search.set(value);
+                    },
+                    value: search(),
+                }
+                Checkbox {
+                    label: "Events only",
+                    on_value: move |value| {
+                        events_only.set(value);
+                    },
+                    value: events_only(),
+                }
+            }
+            div { class: "flex flex-col flex-grow overflow-y-auto scrollbar font-mono paragraph-xs",
+                if events_only() {
+                    for record in events().iter().rev().cloned() {
+                        EventLine { record }
+                    }
+                } else {
+                    for record in filtered() {
+                        LogLine { record }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn LogLine(record: LogRecord) -> Element {
+    let level_class = match record.level {
+        Level::Error => "text-red-500",
+        Level::Warn => "text-yellow-500",
+        Level::Info => "text-gray-50",
+        Level::Debug | Level::Trace => "text-gray-400",
+    };
+    let time = humantime::format_rfc3339_seconds(record.time).to_string();
+
+    rsx! {
+        p { class: "whitespace-pre-wrap break-all {level_class}",
+            "[{time} {record.level} {record.target}] {record.message}"
+        }
+    }
+}
+
+#[component]
+fn EventLine(record: EventRecord) -> Element {
+    let time = humantime::format_rfc3339_seconds(
+        UNIX_EPOCH + Duration::from_millis(record.timestamp_millis),
+    )
+    .to_string();
+    let event = match record.event {
+        Event::ActionStarted { id, name } => format!("Action #{id} {name} started"),
+        Event::ActionCompleted { id, name } => format!("Action #{id} {name} completed"),
+        Event::RuneSolved => "Rune solved".to_string(),
+        Event::Death => "Player died".to_string(),
+        Event::ChannelChanged => "Channel changed".to_string(),
+        Event::DetectionLost => "Minimap detection lost".to_string(),
+        Event::MinimapSwitched { name, preset, .. } => match preset {
+            Some(preset) => format!("Switched to map {name} with preset {preset}"),
+            None => format!("Switched to map {name}"),
+        },
+        Event::PresetHotSwapped { preset } => format!("Applied edited preset {preset}"),
+        Event::PresetScheduled { preset } => format!("Switched to scheduled preset {preset}"),
+        Event::CharacterSwitched { name, .. } => format!("Switched to character {name}"),
+        Event::CaptureSuspended => "Game window lost, suspended".to_string(),
+        Event::CaptureResumed => "Game window captured again, resumed".to_string(),
+    };
+
+    rsx! {
+        p { class: "whitespace-pre-wrap break-all text-gray-50", "[{time}] {event}" }
+    }
+}
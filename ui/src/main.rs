@@ -2,10 +2,11 @@
 #![feature(variant_count)]
 #![feature(map_try_insert)]
 
-use std::{env::current_exe, io::stdout, string::ToString, sync::LazyLock};
+use std::{env::current_exe, io::stdout, string::ToString, sync::LazyLock, time::Duration};
 
 use actions::Actions;
 use backend::{Character, Minimap as MinimapData, Settings as SettingsData};
+use button::{Button, ButtonKind};
 use characters::Characters;
 #[cfg(debug_assertions)]
 use debug::Debug;
@@ -20,10 +21,15 @@ use dioxus::{
 #[cfg(windows)]
 use dioxus::desktop::tao::platform::windows::WindowBuilderExtWindows;
 use fern::Dispatch;
-use log::LevelFilter;
+use inputs::PasswordInput;
+use log::{LevelFilter, Log};
+use logs::Logs;
 use minimap::Minimap;
 use rand::distr::{Alphanumeric, SampleString};
+use routines::Routines;
 use settings::Settings;
+use toast::Toasts;
+use tray::Tray;
 
 mod actions;
 mod button;
@@ -32,15 +38,21 @@ mod characters;
 mod debug;
 mod icons;
 mod inputs;
+mod logs;
 mod minimap;
+mod routines;
 mod select;
 mod settings;
+mod toast;
+mod tray;
 
 const TAILWIND_CSS: Asset = asset!("public/tailwind.css");
 const AUTO_NUMERIC_JS: Asset = asset!("assets/autoNumeric.min.js");
 const TAB_ACTIONS: &str = "Actions";
 const TAB_CHARACTERS: &str = "Characters";
+const TAB_ROUTINES: &str = "Routines";
 const TAB_SETTINGS: &str = "Settings";
+const TAB_LOGS: &str = "Logs";
 #[cfg(debug_assertions)]
 const TAB_DEBUG: &str = "Debug";
 
@@ -48,7 +60,9 @@ static TABS: LazyLock<Vec<String>> = LazyLock::new(|| {
     vec![
         TAB_ACTIONS.to_string(),
         TAB_CHARACTERS.to_string(),
+        TAB_ROUTINES.to_string(),
         TAB_SETTINGS.to_string(),
+        TAB_LOGS.to_string(),
         #[cfg(debug_assertions)]
         TAB_DEBUG.to_string(),
     ]
@@ -60,7 +74,7 @@ fn main() {
     } else {
         LevelFilter::Info
     };
-    Dispatch::new()
+    let output = Dispatch::new()
         .format(|out, message, record| {
             out.finish(format_args!(
                 "[{} {} {}] {}",
@@ -70,11 +84,15 @@ fn main() {
                 message
             ))
         })
-        .level(level)
         .chain(stdout())
-        .chain(fern::log_file(current_exe().unwrap().parent().unwrap().join("log.txt")).unwrap())
+        .chain(fern::log_file(current_exe().unwrap().parent().unwrap().join("log.txt")).unwrap());
+    // The level is applied through the global maximum so that `komari.toml` can override it
+    Dispatch::new()
+        .chain(output)
+        .chain(Box::new(backend::LogSink) as Box<dyn Log>)
         .apply()
         .unwrap();
+    log::set_max_level(level);
     log_panics::init();
 
     backend::init();
@@ -83,6 +101,9 @@ fn main() {
     ctrlc::set_handler(move || {
         log::info!("Received shutdown signal, signaling update loop to exit");
         backend::signal_update_loop_shutdown();
+        if !backend::wait_update_loop_shutdown(Duration::from_secs(1)) {
+            log::warn!("Update loop did not exit in time, held keys may not be released");
+        }
         std::process::exit(0);
     }).expect("Error setting Ctrl-C handler");
     
@@ -108,12 +129,17 @@ pub struct AppState {
     character: Signal<Option<Character>>,
     settings: Signal<Option<SettingsData>>,
     position: Signal<(i32, i32)>,
+    /// Whether clicking the minimap places a new action.
+    route_editing: Signal<bool>,
+    /// The last clicked minimap position while [`Self::route_editing`].
+    route_click: Signal<Option<(i32, i32)>>,
 }
 
 #[component]
 fn App() -> Element {
     let mut selected_tab = use_signal(|| TAB_CHARACTERS.to_string());
     let mut script_loaded = use_signal(|| false);
+    let mut database_locked = use_signal(|| None);
 
     use_context_provider(|| AppState {
         minimap: Signal::new(None),
@@ -121,6 +147,8 @@ fn App() -> Element {
         character: Signal::new(None),
         settings: Signal::new(None),
         position: Signal::new((0, 0)),
+        route_editing: Signal::new(false),
+        route_click: Signal::new(None),
     });
 
     // Thanks dioxus
@@ -139,11 +167,21 @@ fn App() -> Element {
         eval.recv::<bool>().await.unwrap();
         script_loaded.set(true);
     });
+    use_future(move || async move {
+        database_locked.set(Some(backend::is_database_locked().await));
+    });
 
     rsx! {
         document::Link { rel: "stylesheet", href: TAILWIND_CSS }
         document::Script { src: AUTO_NUMERIC_JS }
-        if script_loaded() {
+        if script_loaded() && database_locked() == Some(true) {
+            UnlockDatabase {
+                on_unlocked: move |_| {
+                    database_locked.set(Some(false));
+                },
+            }
+        }
+        if script_loaded() && database_locked() == Some(false) {
             div { class: "flex min-w-3xl lg:min-w-5xl min-h-120 h-full",
                 Minimap {}
                 div { class: "flex-grow flex flex-col lg:flex-row z-1",
@@ -162,9 +200,15 @@ fn App() -> Element {
                             TAB_CHARACTERS => rsx! {
                                 Characters {}
                             },
+                            TAB_ROUTINES => rsx! {
+                                Routines {}
+                            },
                             TAB_SETTINGS => rsx! {
                                 Settings {}
                             },
+                            TAB_LOGS => rsx! {
+                                Logs {}
+                            },
                             #[cfg(debug_assertions)]
                             TAB_DEBUG => rsx! {
                                 Debug {}
@@ -174,6 +218,45 @@ fn App() -> Element {
                     }
                 }
             }
+            Toasts {}
+            Tray {}
+        }
+    }
+}
+
+#[component]
+fn UnlockDatabase(on_unlocked: EventHandler) -> Element {
+    let mut passphrase = use_signal(String::default);
+    let mut error = use_signal(|| None);
+    let mut unlocking = use_signal(|| false);
+
+    rsx! {
+        div { class: "flex flex-col w-80 m-auto pt-20 gap-3",
+            p { class: "title", "Database is encrypted" }
+            PasswordInput {
+                label: "Passphrase",
+                disabled: unlocking(),
+                on_value: move |value| {
+                    passphrase.set(value);
+                },
+                value: passphrase(),
+            }
+            if let Some(error) = error() {
+                p { class: "paragraph-xs text-red-500", "{error}" }
+            }
+            Button {
+                text: "Unlock",
+                kind: ButtonKind::Primary,
+                disabled: unlocking(),
+                on_click: move |_| async move {
+                    unlocking.set(true);
+                    match backend::unlock_database(passphrase.peek().clone()).await {
+                        Ok(()) => on_unlocked(()),
+                        Err(err) => error.set(Some(err.to_string())),
+                    }
+                    unlocking.set(false);
+                },
+            }
         }
     }
 }
@@ -1,20 +1,23 @@
 use std::{fs::File, io::BufReader, ops::Deref, time::Duration};
 
 use backend::{
-    Action, ActionKey, ActionMove, Minimap as MinimapData, Position, RotationMode, create_minimap,
-    delete_minimap, game_state_receiver, query_minimaps, redetect_minimap, rotate_actions,
+    Action, ActionKey, ActionMove, DetectionScores, Event, FamiliarSlotKind,
+    Minimap as MinimapData, MinimapConfidence, Position, RotationMode, UpcomingPriorityAction,
+    canvas_to_minimap_point, create_minimap, delete_minimap, event_receiver, game_state_receiver,
+    query_minimaps, redetect_minimap, redo, resume_last_session, rotate_actions, undo,
     update_minimap, upsert_minimap,
 };
 use dioxus::{document::EvalError, prelude::*};
 use futures_util::StreamExt;
 use rand::distr::{Alphanumeric, SampleString};
 use serde::Serialize;
-use tokio::time::sleep;
+use tokio::{sync::broadcast::error::RecvError, time::sleep};
 
 use crate::{
     AppState,
     button::{Button, ButtonKind},
     select::TextSelect,
+    toast::ToastResult,
 };
 
 const BACKGROUND: Asset = asset!(
@@ -27,7 +30,20 @@ const MINIMAP_JS: &str = r#"
     const canvasCtx = canvas.getContext("2d");
 
     while (true) {
-        const [buffer, width, height, destinations, bound, quadrant, portals] = await dioxus.recv();
+        const [
+            buffer,
+            width,
+            height,
+            destinations,
+            bound,
+            quadrant,
+            portals,
+            path,
+            rune,
+            hasEliteBoss,
+            eliteBoss,
+            otherPlayers,
+        ] = await dioxus.recv();
         const data = new ImageData(new Uint8ClampedArray(buffer), width, height);
         const bitmap = await createImageBitmap(data);
 
@@ -37,25 +53,73 @@ const MINIMAP_JS: &str = r#"
 
         const destinationSize = 4;
         const destinationSizeHalf = destinationSize / 2;
-        let prevX = 0;
-        let prevY = 0;
         for (let i = 0; i < destinations.length; i++) {
             let [x, y] = destinations[i];
             x = (x / width) * canvas.width;
             y = ((height - y) / height) * canvas.height;
 
-            canvasCtx.fillRect(x, y, destinationSize, destinationSize);
+            canvasCtx.fillRect(x - destinationSizeHalf, y - destinationSizeHalf, destinationSize, destinationSize);
+        }
 
-            if (i > 0) {
-                canvasCtx.beginPath();
-                canvasCtx.setLineDash([8]);
-                canvasCtx.moveTo(prevX + destinationSizeHalf, prevY + destinationSizeHalf);
-                canvasCtx.lineTo(x + destinationSizeHalf, y + destinationSizeHalf);
-                canvasCtx.stroke();
+        canvasCtx.beginPath();
+        canvasCtx.setLineDash([8]);
+        for (let i = 0; i < path.length; i++) {
+            let [x, y] = path[i];
+            x = (x / width) * canvas.width;
+            y = ((height - y) / height) * canvas.height;
+
+            if (i == 0) {
+                canvasCtx.moveTo(x, y);
+            } else {
+                canvasCtx.lineTo(x, y);
+            }
+        }
+        canvasCtx.stroke();
+
+        if (rune !== null) {
+            const [x, y] = rune;
+            canvasCtx.setLineDash([]);
+            canvasCtx.strokeStyle = "rgb(255, 200, 60)";
+            canvasCtx.beginPath();
+            canvasCtx.arc((x / width) * canvas.width, ((height - y) / height) * canvas.height, 5, 0, 2 * Math.PI);
+            canvasCtx.stroke();
+        }
+
+        const playerSize = 4;
+        for (let i = 0; i < otherPlayers.length; i++) {
+            const [x, y, kind] = otherPlayers[i];
+            switch (kind) {
+                case "Guildie":
+                    canvasCtx.fillStyle = "rgb(190, 120, 255)";
+                    break;
+                case "Friend":
+                    canvasCtx.fillStyle = "rgb(90, 170, 255)";
+                    break;
+                default:
+                    canvasCtx.fillStyle = "rgb(254, 71, 57)";
+                    break;
             }
+            canvasCtx.fillRect(
+                (x / width) * canvas.width - playerSize / 2,
+                ((height - y) / height) * canvas.height - playerSize / 2,
+                playerSize,
+                playerSize,
+            );
+        }
 
-            prevX = x;
-            prevY = y;
+        if (hasEliteBoss) {
+            canvasCtx.fillStyle = "rgb(254, 71, 57)";
+            canvasCtx.font = "10px sans-serif";
+            canvasCtx.fillText("Elite boss", 6, 12);
+        }
+
+        if (eliteBoss !== null) {
+            const [x, y] = eliteBoss;
+            canvasCtx.setLineDash([]);
+            canvasCtx.strokeStyle = "rgb(254, 71, 57)";
+            canvasCtx.beginPath();
+            canvasCtx.arc((x / width) * canvas.width, ((height - y) / height) * canvas.height, 6, 0, 2 * Math.PI);
+            canvasCtx.stroke();
         }
 
         canvasCtx.setLineDash([8]);
@@ -139,10 +203,19 @@ const MINIMAP_JS: &str = r#"
         canvasCtx.stroke();
     }
 "#;
+const ROUTE_EDITOR_JS: &str = r#"
+    const canvas = document.getElementById("canvas-minimap-actions");
+    canvas.addEventListener("click", async (e) => {
+        await dioxus.send([e.offsetX, e.offsetY, canvas.clientWidth, canvas.clientHeight]);
+    });
+    while (true) {
+        await new Promise((resolve) => setTimeout(resolve, 1000));
+    }
+"#;
 const MINIMAP_ACTIONS_JS: &str = r#"
     const canvas = document.getElementById("canvas-minimap-actions");
     const canvasCtx = canvas.getContext("2d");
-    const [width, height, actions, boundAndType, platforms] = await dioxus.recv();
+    const [width, height, actions, boundAndType, platforms, ropes, noGoZones] = await dioxus.recv();
     canvasCtx.clearRect(0, 0, canvas.width, canvas.height);
     const anyActions = actions.filter((action) => action.condition === "Any");
     const erdaActions = actions.filter((action) => action.condition === "ErdaShowerOffCooldown");
@@ -150,6 +223,15 @@ const MINIMAP_ACTIONS_JS: &str = r#"
 
     drawBound(canvasCtx, boundAndType);
 
+    canvasCtx.fillStyle = "rgba(254, 71, 57, 0.25)";
+    for (const zone of noGoZones) {
+        const x = (zone.x / width) * canvas.width;
+        const y = (zone.y / height) * canvas.height;
+        const w = (zone.width / width) * canvas.width;
+        const h = (zone.height / height) * canvas.height;
+        canvasCtx.fillRect(x, y, w, h);
+    }
+
     canvasCtx.setLineDash([]);
     canvasCtx.strokeStyle = "rgb(255, 160, 37)";
     for (const platform of platforms) {
@@ -161,6 +243,15 @@ const MINIMAP_ACTIONS_JS: &str = r#"
         canvasCtx.lineTo(xEnd, y);
         canvasCtx.stroke();
     }
+    for (const rope of ropes) {
+        const x = (rope.x / width) * canvas.width;
+        const yStart = ((height - rope.y_start) / height) * canvas.height;
+        const yEnd = ((height - rope.y_end) / height) * canvas.height;
+        canvasCtx.beginPath();
+        canvasCtx.moveTo(x, yStart);
+        canvasCtx.lineTo(x, yEnd);
+        canvasCtx.stroke();
+    }
 
     canvasCtx.setLineDash([8]);
     canvasCtx.fillStyle = "rgb(255, 153, 128)";
@@ -282,12 +373,24 @@ struct ActionView {
 struct MinimapState {
     position: Option<(i32, i32)>,
     health: Option<(u32, u32)>,
+    mp: Option<(u32, u32)>,
     state: String,
     normal_action: Option<String>,
     priority_action: Option<String>,
     erda_shower_state: String,
     halting: bool,
     detected_size: Option<(usize, usize)>,
+    exp_per_hour: Option<u64>,
+    exp_percent_per_hour: Option<f32>,
+    mesos_per_hour: Option<u64>,
+    detection_scores: DetectionScores,
+    minimap_confidence: Option<MinimapConfidence>,
+    upcoming_normal_actions: Vec<String>,
+    upcoming_priority_actions: Vec<UpcomingPriorityAction>,
+    familiar_slots: Vec<FamiliarSlotKind>,
+    familiar_slots_matched: Option<bool>,
+    safe_mode_break_in_millis: Option<u64>,
+    safe_mode_breaking_millis: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -296,6 +399,8 @@ enum MinimapUpdate {
     Create(String),
     Import(MinimapData),
     Delete,
+    Undo,
+    Redo,
 }
 
 #[component]
@@ -303,7 +408,8 @@ pub fn Minimap() -> Element {
     let mut minimap = use_context::<AppState>().minimap;
     let mut minimap_preset = use_context::<AppState>().minimap_preset;
     let position = use_context::<AppState>().position;
-    let mut minimaps = use_resource(async || query_minimaps().await.unwrap_or_default());
+    let mut minimaps =
+        use_resource(async || query_minimaps().await.ok_or_toast().unwrap_or_default());
     // Maps queried `minimaps` to names
     let minimap_names = use_memo(move || {
         minimaps()
@@ -330,31 +436,72 @@ pub fn Minimap() -> Element {
         while let Some(message) = rx.next().await {
             match message {
                 MinimapUpdate::Set => {
-                    update_minimap(minimap_preset(), minimap()).await;
+                    update_minimap(minimap_preset(), minimap())
+                        .await
+                        .ok_or_toast();
                 }
                 MinimapUpdate::Create(name) => {
-                    let Some(new_minimap) = create_minimap(name).await else {
+                    let Some(new_minimap) = create_minimap(name).await.ok_or_toast().flatten()
+                    else {
+                        continue;
+                    };
+                    let Some(new_minimap) = upsert_minimap(new_minimap).await.ok_or_toast() else {
                         continue;
                     };
-                    let new_minimap = upsert_minimap(new_minimap).await;
 
                     minimap.set(Some(new_minimap));
                     minimap_preset.set(None);
                     minimaps.restart();
-                    update_minimap(None, minimap()).await;
+                    update_minimap(None, minimap()).await.ok_or_toast();
                 }
                 MinimapUpdate::Import(minimap) => {
-                    upsert_minimap(minimap).await;
+                    upsert_minimap(minimap).await.ok_or_toast();
                     minimaps.restart();
                 }
                 MinimapUpdate::Delete => {
                     if let Some(minimap) = minimap.take() {
                         minimap_preset.set(None);
-                        delete_minimap(minimap).await;
-                        update_minimap(None, None).await;
+                        delete_minimap(minimap).await.ok_or_toast();
+                        update_minimap(None, None).await.ok_or_toast();
                         minimaps.restart();
                     }
                 }
+                MinimapUpdate::Undo | MinimapUpdate::Redo => {
+                    let id = if matches!(message, MinimapUpdate::Undo) {
+                        undo().await
+                    } else {
+                        redo().await
+                    };
+                    let Some(id) = id.ok_or_toast().flatten() else {
+                        continue;
+                    };
+                    let edited = query_minimaps()
+                        .await
+                        .ok_or_toast()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .find(|minimap| minimap.id == Some(id));
+
+                    match edited {
+                        Some(edited) => {
+                            let preset = minimap_preset()
+                                .filter(|preset| edited.actions.contains_key(preset))
+                                .or_else(|| edited.actions.keys().next().cloned());
+                            minimap_preset.set(preset);
+                            minimap.set(Some(edited));
+                        }
+                        None => {
+                            if minimap().is_some_and(|minimap| minimap.id == Some(id)) {
+                                minimap.set(None);
+                                minimap_preset.set(None);
+                            }
+                        }
+                    }
+                    update_minimap(minimap_preset(), minimap())
+                        .await
+                        .ok_or_toast();
+                    minimaps.restart();
+                }
             }
         }
     });
@@ -379,6 +526,38 @@ pub fn Minimap() -> Element {
             coroutine.send(MinimapUpdate::Set);
         }
     });
+    // Follows the minimap switched automatically on map change and the scheduled preset
+    use_future(move || async move {
+        let Some(mut receiver) = event_receiver().await.ok_or_toast() else {
+            return;
+        };
+        loop {
+            let record = match receiver.recv().await {
+                Ok(record) => record,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let (id, preset) = match record.event {
+                Event::MinimapSwitched { id, preset, .. } => (id, preset),
+                Event::PresetScheduled { preset } => {
+                    minimap_preset.set(Some(preset));
+                    continue;
+                }
+                _ => continue,
+            };
+            let switched = query_minimaps()
+                .await
+                .ok_or_toast()
+                .unwrap_or_default()
+                .into_iter()
+                .find(|minimap| minimap.id == Some(id));
+            if let Some(switched) = switched {
+                minimap_preset.set(preset);
+                minimap.set(Some(switched));
+                minimaps.restart();
+            }
+        }
+    });
     // External modification checking
     use_effect(move || {
         if let Some((current_minimaps, current_minimap)) = minimaps().zip(minimap()) {
@@ -409,6 +588,7 @@ pub fn Minimap() -> Element {
             Info { state, minimap }
             div { class: "flex-grow flex items-end px-2",
                 div { class: "flex flex-col items-end w-full",
+                    History {}
                     ImportExport { minimap }
                     div { class: "h-10 w-full flex items-center",
                         TextSelect {
@@ -450,13 +630,17 @@ fn Canvas(
     minimap_preset: ReadOnlySignal<Option<String>>,
     position: Signal<(i32, i32)>,
 ) -> Element {
+    let route_editing = use_context::<AppState>().route_editing;
+    let mut route_click = use_context::<AppState>().route_click;
     let mut platforms_bound = use_signal(|| None);
     let rotation_bound_and_type = use_memo(move || {
         let platforms_bound = platforms_bound();
         let minimap = minimap()?;
 
         match minimap.rotation_mode {
-            RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => None,
+            RotationMode::StartToEnd
+            | RotationMode::StartToEndThenReverse
+            | RotationMode::Shuffled => None,
             RotationMode::AutoMobbing => Some((
                 platforms_bound.unwrap_or(minimap.rotation_auto_mob_bound),
                 "AutoMobbing",
@@ -502,13 +686,17 @@ fn Canvas(
                 actions,
                 bound_and_type,
                 minimap.platforms,
+                minimap.ropes,
+                minimap.no_go_zones,
             ));
         });
     });
     // Draw minimap and update game state
     use_future(move || async move {
         let mut canvas = document::eval(MINIMAP_JS);
-        let mut receiver = game_state_receiver().await;
+        let Some(mut receiver) = game_state_receiver().await.ok_or_toast() else {
+            return;
+        };
         loop {
             let Ok(current_state) = receiver.recv().await else {
                 continue;
@@ -520,15 +708,36 @@ fn Canvas(
                 .map(|quadrant| quadrant.to_string());
             let frame = current_state.frame;
             let portals = current_state.portals;
+            let path = current_state.path;
+            let rune = current_state.rune;
+            let has_elite_boss = current_state.has_elite_boss;
+            let elite_boss = current_state.elite_boss;
+            let other_players = current_state
+                .other_players
+                .into_iter()
+                .map(|(x, y, kind)| (x, y, kind.to_string()))
+                .collect::<Vec<_>>();
             let current_state = MinimapState {
                 position: current_state.position,
                 health: current_state.health,
+                mp: current_state.mp,
                 state: current_state.state,
                 normal_action: current_state.normal_action,
                 priority_action: current_state.priority_action,
                 erda_shower_state: current_state.erda_shower_state,
                 halting: current_state.halting,
                 detected_size: frame.as_ref().map(|(_, width, height)| (*width, *height)),
+                exp_per_hour: current_state.exp_per_hour,
+                exp_percent_per_hour: current_state.exp_percent_per_hour,
+                mesos_per_hour: current_state.mesos_per_hour,
+                detection_scores: current_state.detection_scores,
+                minimap_confidence: current_state.minimap_confidence,
+                upcoming_normal_actions: current_state.upcoming_normal_actions,
+                upcoming_priority_actions: current_state.upcoming_priority_actions,
+                familiar_slots: current_state.familiar_slots,
+                familiar_slots_matched: current_state.familiar_slots_matched,
+                safe_mode_break_in_millis: current_state.safe_mode_break_in_millis,
+                safe_mode_breaking_millis: current_state.safe_mode_breaking_millis,
             };
 
             if *platforms_bound.peek() != bound {
@@ -547,9 +756,20 @@ fn Canvas(
             let Some((frame, width, height)) = frame else {
                 continue;
             };
-            let Err(error) =
-                canvas.send((frame, width, height, destinations, bound, quadrant, portals))
-            else {
+            let Err(error) = canvas.send((
+                frame,
+                width,
+                height,
+                destinations,
+                bound,
+                quadrant,
+                portals,
+                path,
+                rune,
+                has_elite_boss,
+                elite_boss,
+                other_players,
+            )) else {
                 continue;
             };
             if matches!(error, EvalError::Finished) {
@@ -559,6 +779,32 @@ fn Canvas(
         }
     });
 
+    // Translate minimap clicks to minimap coordinates for placing actions
+    use_future(move || async move {
+        let mut canvas = document::eval(ROUTE_EDITOR_JS);
+        loop {
+            let Ok((x, y, width, height)) = canvas.recv::<(f32, f32, f32, f32)>().await else {
+                canvas = document::eval(ROUTE_EDITOR_JS);
+                sleep(Duration::from_millis(1000)).await;
+                continue;
+            };
+            if !*route_editing.peek() {
+                continue;
+            }
+            let Some(minimap) = &*minimap.peek() else {
+                continue;
+            };
+            let point =
+                canvas_to_minimap_point((x, y), (width, height), (minimap.width, minimap.height));
+            route_click.set(Some(point));
+        }
+    });
+    let editing_class = if route_editing() {
+        "cursor-crosshair"
+    } else {
+        ""
+    };
+
     rsx! {
         div { class: "relative h-31 xl:h-38 rounded-2xl bg-gray-900",
             canvas {
@@ -566,7 +812,7 @@ fn Canvas(
                 id: "canvas-minimap",
             }
             canvas {
-                class: "absolute inset-0 rounded-2xl w-full h-full",
+                class: "absolute inset-0 rounded-2xl w-full h-full {editing_class}",
                 id: "canvas-minimap-actions",
             }
         }
@@ -582,24 +828,51 @@ fn Info(
     struct GameStateInfo {
         position: String,
         health: String,
+        mp: String,
         state: String,
         normal_action: String,
         priority_action: String,
+        next_normal_actions: String,
+        next_priority_action: String,
         erda_shower_state: String,
         detected_minimap_size: String,
         selected_minimap_size: String,
+        exp_per_hour: String,
+        mesos_per_hour: String,
+        familiars: String,
+        safe_mode_break: String,
+        player_score: String,
+        health_bar_score: String,
+        minimap_confidence: String,
     }
 
+    let settings = use_context::<AppState>().settings;
+    let tuning = use_memo(move || {
+        settings()
+            .map(|settings| settings.detection_tuning)
+            .unwrap_or_default()
+    });
+
     let info = use_memo(move || {
         let mut info = GameStateInfo {
             position: "Unknown".to_string(),
             health: "Unknown".to_string(),
+            mp: "Unknown".to_string(),
             state: "Unknown".to_string(),
             normal_action: "Unknown".to_string(),
             priority_action: "Unknown".to_string(),
+            next_normal_actions: "Unknown".to_string(),
+            next_priority_action: "Unknown".to_string(),
             erda_shower_state: "Unknown".to_string(),
             detected_minimap_size: "Unknown".to_string(),
             selected_minimap_size: "Unknown".to_string(),
+            exp_per_hour: "Unknown".to_string(),
+            mesos_per_hour: "Unknown".to_string(),
+            familiars: "Unknown".to_string(),
+            safe_mode_break: "None".to_string(),
+            player_score: "Unknown".to_string(),
+            health_bar_score: "Unknown".to_string(),
+            minimap_confidence: "Unknown".to_string(),
         };
 
         if let Some(minimap) = minimap() {
@@ -615,15 +888,71 @@ fn Info(
             if let Some((current, max)) = state.health {
                 info.health = format!("{current} / {max}");
             }
+            if let Some((current, max)) = state.mp {
+                info.mp = format!("{current} / {max}");
+            }
             if let Some(action) = state.normal_action {
                 info.normal_action = action;
             }
             if let Some(action) = state.priority_action {
                 info.priority_action = action;
             }
+            if !state.upcoming_normal_actions.is_empty() {
+                info.next_normal_actions = state.upcoming_normal_actions.join(", ");
+            }
+            let queued = state
+                .upcoming_priority_actions
+                .iter()
+                .filter(|action| action.queued)
+                .map(|action| action.name.clone())
+                .collect::<Vec<_>>();
+            let soonest = state
+                .upcoming_priority_actions
+                .iter()
+                .filter_map(|action| action.remaining_millis.map(|millis| (action, millis)))
+                .min_by_key(|(_, millis)| *millis);
+            if !queued.is_empty() {
+                info.next_priority_action = queued.join(", ");
+            } else if let Some((action, millis)) = soonest {
+                info.next_priority_action = format!("{} in {}s", action.name, millis / 1000);
+            }
             if let Some((width, height)) = state.detected_size {
                 info.detected_minimap_size = format!("{width}px x {height}px")
             }
+            if let (Some(exp), Some(percent)) = (state.exp_per_hour, state.exp_percent_per_hour) {
+                info.exp_per_hour = format!("{exp} ({percent:.2}%)");
+            }
+            if let Some(mesos) = state.mesos_per_hour {
+                info.mesos_per_hour = mesos.to_string();
+            }
+            if let Some(matched) = state.familiar_slots_matched {
+                let slots = state
+                    .familiar_slots
+                    .iter()
+                    .map(|slot| slot.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let status = if matched { "matched" } else { "mismatched" };
+                info.familiars = format!("{slots} ({status})");
+            }
+            if let Some(millis) = state.safe_mode_breaking_millis {
+                info.safe_mode_break = format!("Ends in {}s", millis / 1000);
+            } else if let Some(millis) = state.safe_mode_break_in_millis {
+                info.safe_mode_break = format!("In {}s", millis / 1000);
+            }
+            let tuning = tuning();
+            if let Some(score) = state.detection_scores.player {
+                info.player_score = format!("{score:.2} / {:.2}", tuning.player_score);
+            }
+            if let Some(score) = state.detection_scores.health_bar {
+                info.health_bar_score = format!("{score:.2} / {:.2}", tuning.health_bar_score);
+            }
+            if let Some(confidence) = state.minimap_confidence {
+                info.minimap_confidence = format!(
+                    "{:.2} ({}px, {} frames)",
+                    confidence.score, confidence.jitter, confidence.frames_since_lock
+                );
+            }
         }
 
         info
@@ -634,11 +963,23 @@ fn Info(
             InfoItem { name: "State", value: info().state }
             InfoItem { name: "Position", value: info().position }
             InfoItem { name: "Health", value: info().health }
+            InfoItem { name: "MP", value: info().mp }
             InfoItem { name: "Priority action", value: info().priority_action }
             InfoItem { name: "Normal action", value: info().normal_action }
+            InfoItem { name: "Next priority", value: info().next_priority_action }
+            InfoItem { name: "Next normal", value: info().next_normal_actions }
             InfoItem { name: "Erda Shower", value: info().erda_shower_state }
             InfoItem { name: "Detected size", value: info().detected_minimap_size }
             InfoItem { name: "Selected size", value: info().selected_minimap_size }
+            InfoItem { name: "EXP / hour", value: info().exp_per_hour }
+            InfoItem { name: "Mesos / hour", value: info().mesos_per_hour }
+            InfoItem { name: "Familiars", value: info().familiars }
+            InfoItem { name: "Safe mode break", value: info().safe_mode_break }
+            if tuning().show_scores {
+                InfoItem { name: "Player score", value: info().player_score }
+                InfoItem { name: "Health bar score", value: info().health_bar_score }
+                InfoItem { name: "Minimap confidence", value: info().minimap_confidence }
+            }
         }
     }
 }
@@ -667,7 +1008,7 @@ fn Buttons(
                 kind: ButtonKind::Primary,
                 disabled: minimap().is_none() || character().is_none(),
                 on_click: move || async move {
-                    rotate_actions(!*halting.peek()).await;
+                    rotate_actions(!*halting.peek()).await.ok_or_toast();
                 },
             }
             Button {
@@ -675,7 +1016,42 @@ fn Buttons(
                 text: "Re-detect",
                 kind: ButtonKind::Primary,
                 on_click: move |_| async move {
-                    redetect_minimap().await;
+                    redetect_minimap().await.ok_or_toast();
+                },
+            }
+            Button {
+                class: "w-20",
+                text: "Resume",
+                kind: ButtonKind::Secondary,
+                disabled: !halting(),
+                on_click: move |_| async move {
+                    resume_last_session(true).await.ok_or_toast();
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn History() -> Element {
+    let coroutine = use_coroutine_handle::<MinimapUpdate>();
+
+    rsx! {
+        div { class: "flex gap-3 mb-3",
+            Button {
+                class: "w-20",
+                text: "Undo",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    coroutine.send(MinimapUpdate::Undo);
+                },
+            }
+            Button {
+                class: "w-20",
+                text: "Redo",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    coroutine.send(MinimapUpdate::Redo);
                 },
             }
         }
@@ -0,0 +1,290 @@
+use backend::{
+    ClientPoint, KeyBinding, Routine, RoutineStep, RoutineStepKind, RoutineTrigger, delete_routine,
+    query_routines, update_routines, upsert_routine,
+};
+use dioxus::prelude::*;
+use futures_util::StreamExt;
+
+use crate::{
+    button::{Button, ButtonKind},
+    icons::XIcon,
+    inputs::{Checkbox, KeyBindingInput, MillisInput, NumberInputI32},
+    select::{EnumSelect, TextSelect},
+    toast::ToastResult,
+};
+
+#[derive(Debug)]
+enum RoutineUpdate {
+    Update(Routine),
+    Create(String),
+    Delete,
+}
+
+#[component]
+pub fn Routines() -> Element {
+    let mut routine = use_signal(|| None::<Routine>);
+    let mut routines =
+        use_resource(async || query_routines().await.ok_or_toast().unwrap_or_default());
+    // Maps queried `routines` to names
+    let routine_names = use_memo(move || {
+        routines()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|routine| routine.name)
+            .collect()
+    });
+    // Maps currently selected `routine` to the index in `routines`
+    let routine_index = use_memo(move || {
+        routines().zip(routine()).and_then(|(routines, routine)| {
+            routines
+                .into_iter()
+                .enumerate()
+                .find(|(_, data)| routine.id == data.id)
+                .map(|(i, _)| i)
+        })
+    });
+    // Default routine if `routine` is `None`
+    let routine_view = use_memo(move || routine().unwrap_or_default());
+
+    // Handles async operations for routine-related
+    let coroutine = use_coroutine(move |mut rx: UnboundedReceiver<RoutineUpdate>| async move {
+        let mut save_routine = async move |new_routine: Routine| {
+            let Some(new_routine) = upsert_routine(new_routine).await.ok_or_toast() else {
+                return;
+            };
+            routine.set(Some(new_routine));
+            routines.restart();
+            update_routines().await.ok_or_toast();
+        };
+
+        while let Some(message) = rx.next().await {
+            match message {
+                RoutineUpdate::Update(new_routine) => {
+                    save_routine(new_routine).await;
+                }
+                RoutineUpdate::Create(name) => {
+                    save_routine(Routine {
+                        name,
+                        ..Routine::default()
+                    })
+                    .await;
+                }
+                RoutineUpdate::Delete => {
+                    if let Some(routine) = routine.take() {
+                        delete_routine(routine).await.ok_or_toast();
+                        routines.restart();
+                        update_routines().await.ok_or_toast();
+                    }
+                }
+            }
+        }
+    });
+    let save_routine = use_callback(move |new_routine: Routine| {
+        coroutine.send(RoutineUpdate::Update(new_routine));
+    });
+
+    // Selects a routine if there is not one
+    use_effect(move || {
+        if let Some(routines) = routines()
+            && !routines.is_empty()
+            && routine.peek().is_none()
+        {
+            routine.set(routines.into_iter().next());
+        }
+    });
+
+    rsx! {
+        div { class: "flex flex-col pb-15 h-full overflow-y-auto scrollbar",
+            SectionRoutine { routine_view, save_routine }
+            SectionSteps { routine_view, save_routine }
+        }
+
+        div { class: "flex items-center w-full h-10 bg-gray-950 absolute bottom-0 pr-2",
+            TextSelect {
+                class: "flex-grow",
+                options: routine_names(),
+                disabled: false,
+                placeholder: "Create a routine...",
+                on_create: move |name| {
+                    coroutine.send(RoutineUpdate::Create(name));
+                },
+                on_delete: move |_| {
+                    coroutine.send(RoutineUpdate::Delete);
+                },
+                on_select: move |(index, _)| {
+                    let selected = routines.peek().as_ref().unwrap().get(index).cloned().unwrap();
+                    routine.set(Some(selected));
+                },
+                selected: routine_index(),
+            }
+        }
+    }
+}
+
+#[component]
+fn Section(name: &'static str, children: Element) -> Element {
+    rsx! {
+        div { class: "flex flex-col pr-4 pb-3",
+            div { class: "flex items-center title-xs h-10", {name} }
+            {children}
+        }
+    }
+}
+
+#[component]
+fn SectionRoutine(routine_view: Memo<Routine>, save_routine: Callback<Routine>) -> Element {
+    rsx! {
+        Section { name: "Routine",
+            p { class: "paragraph-xs mb-2",
+                "Actions are halted while a routine is running. Login routines run once each time actions start."
+            }
+            div { class: "grid grid-cols-3 gap-4",
+                Checkbox {
+                    label: "Enabled",
+                    disabled: routine_view().id.is_none(),
+                    on_value: move |enabled| {
+                        save_routine(Routine {
+                            enabled,
+                            ..routine_view.peek().clone()
+                        });
+                    },
+                    value: routine_view().enabled,
+                }
+                EnumSelect::<RoutineTrigger> {
+                    label: "Trigger",
+                    disabled: routine_view().id.is_none(),
+                    on_select: move |trigger| {
+                        save_routine(Routine {
+                            trigger,
+                            ..routine_view.peek().clone()
+                        });
+                    },
+                    selected: routine_view().trigger,
+                }
+                MillisInput {
+                    label: "Interval",
+                    disabled: routine_view().id.is_none()
+                        || routine_view().trigger != RoutineTrigger::Schedule,
+                    on_value: move |interval_millis| {
+                        save_routine(Routine {
+                            interval_millis,
+                            ..routine_view.peek().clone()
+                        });
+                    },
+                    value: routine_view().interval_millis,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionSteps(routine_view: Memo<Routine>, save_routine: Callback<Routine>) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+    let save_step = use_callback(move |(index, step): (usize, RoutineStep)| {
+        let mut routine = routine_view.peek().clone();
+        *routine.steps.get_mut(index).expect("valid index") = step;
+        save_routine(routine);
+    });
+
+    rsx! {
+        Section { name: "Steps",
+            div { class: "grid grid-cols-[auto_auto_auto_auto_auto_16px] gap-4 items-end",
+                for (index , step) in routine_view().steps.into_iter().enumerate() {
+                    EnumSelect::<RoutineStepKind> {
+                        label: "Step",
+                        on_select: move |kind| {
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    kind,
+                                    ..routine_view.peek().steps[index].clone()
+                                },
+                            ));
+                        },
+                        selected: step.kind,
+                    }
+                    MillisInput {
+                        label: "Delay",
+                        on_value: move |delay_millis| {
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    delay_millis,
+                                    ..routine_view.peek().steps[index].clone()
+                                },
+                            ));
+                        },
+                        value: step.delay_millis,
+                    }
+                    KeyBindingInput {
+                        label: "Key",
+                        disabled: step.kind != RoutineStepKind::Key,
+                        on_value: move |key: Option<KeyBinding>| {
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    key: key.expect("not optional"),
+                                    ..routine_view.peek().steps[index].clone()
+                                },
+                            ));
+                        },
+                        value: Some(step.key),
+                    }
+                    NumberInputI32 {
+                        label: "Click X",
+                        disabled: step.kind != RoutineStepKind::Click,
+                        on_value: move |x| {
+                            let step = routine_view.peek().steps[index].clone();
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    point: ClientPoint { x, ..step.point },
+                                    ..step
+                                },
+                            ));
+                        },
+                        value: step.point.x,
+                    }
+                    NumberInputI32 {
+                        label: "Click Y",
+                        disabled: step.kind != RoutineStepKind::Click,
+                        on_value: move |y| {
+                            let step = routine_view.peek().steps[index].clone();
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    point: ClientPoint { y, ..step.point },
+                                    ..step
+                                },
+                            ));
+                        },
+                        value: step.point.y,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            let mut routine = routine_view.peek().clone();
+                            routine.steps.remove(index);
+                            save_routine(routine);
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Add step",
+                kind: ButtonKind::Secondary,
+                disabled: routine_view().id.is_none(),
+                on_click: move |_| {
+                    let mut routine = routine_view.peek().clone();
+                    routine.steps.push(RoutineStep::default());
+                    save_routine(routine);
+                },
+            }
+        }
+    }
+}
@@ -1,21 +1,49 @@
-use std::{fmt::Display, fs::File, io::BufReader};
+use std::{fmt::Display, fs::File, io::BufReader, iter, time::Duration};
 
 use backend::{
-    CaptureMode, FamiliarRarity, Familiars, InputMethod, IntoEnumIterator, KeyBinding,
-    KeyBindingConfiguration, Notifications, Settings as SettingsData, SwappableFamiliars,
-    query_capture_handles, query_settings, select_capture_handle, update_settings, upsert_settings,
+    AntiAfk, Bound, Breaks, CaptureHdrMode, CaptureMode, CaptureWindow, ChatDetection, ClientPoint,
+    ClientRestart, DesktopPreview, DetectionTuning, FamiliarLevelPriority, FamiliarRarity,
+    FamiliarRarityPriority, Familiars, FocusTracking, Following, FollowingRole,
+    HumanizationProfile, InputMethod, IntoEnumIterator, Itinerary, ItineraryExit, ItineraryStep,
+    KeyBinding, KeyBindingConfiguration, KeyOverride, KeyboardLayout, LoginSequence, MesoDetection,
+    Minimap as MinimapData, Notifications, PanicPlaybook, PanicPlaybooks, PanicStep, PanicStepKind,
+    Position, RoutineStep, RoutineStepKind, SafeMode, Settings as SettingsData, SwappableFamiliars,
+    UpdateInfo, VideoFormat, VideoRecording, Watchdog, WindowArrangement, apply_update,
+    dump_diagnostics, query_capture_handles, query_desktop_preview, query_diagnostics,
+    query_input_latencies, query_minimaps, query_settings, query_update, record_video,
+    select_capture_handle, signal_update_loop_shutdown, test_input_latency, update_settings,
+    upsert_settings, wait_update_loop_shutdown,
 };
-use dioxus::prelude::*;
+use dioxus::{document::EvalError, prelude::*};
 use futures_util::StreamExt;
 use rand::distr::{Alphanumeric, SampleString};
+use tokio::time::sleep;
 
 use crate::{
     AppState,
     button::{Button, ButtonKind},
-    inputs::{Checkbox, KeyBindingInput, MillisInput, TextInput},
+    icons::XIcon,
+    inputs::{
+        Checkbox, KeyBindingInput, MillisInput, NumberInputI32, NumberInputU32, PercentageInput,
+        TextInput,
+    },
     select::{EnumSelect, Select},
+    toast::ToastResult,
 };
 
+const DESKTOP_PREVIEW_JS: &str = r#"
+    const canvas = document.getElementById("canvas-desktop-preview");
+    const canvasCtx = canvas.getContext("2d");
+
+    while (true) {
+        const [buffer, width, height] = await dioxus.recv();
+        const data = new ImageData(new Uint8ClampedArray(buffer), width, height);
+        canvas.width = width;
+        canvas.height = height;
+        canvasCtx.putImageData(data, 0, 0);
+    }
+"#;
+
 #[derive(Debug)]
 enum SettingsUpdate {
     Set,
@@ -33,11 +61,19 @@ pub fn Settings() -> Element {
             while let Some(message) = rx.next().await {
                 match message {
                     SettingsUpdate::Set => {
-                        update_settings(settings().expect("has value")).await;
+                        update_settings(settings().expect("has value"))
+                            .await
+                            .ok_or_toast();
                     }
                     SettingsUpdate::Update(new_settings) => {
-                        settings.set(Some(upsert_settings(new_settings).await));
-                        update_settings(settings().expect("has value")).await;
+                        let Some(new_settings) = upsert_settings(new_settings).await.ok_or_toast()
+                        else {
+                            continue;
+                        };
+                        settings.set(Some(new_settings));
+                        update_settings(settings().expect("has value"))
+                            .await
+                            .ok_or_toast();
                     }
                 }
             }
@@ -48,19 +84,37 @@ pub fn Settings() -> Element {
     });
 
     use_future(move || async move {
-        if settings.peek().is_none() {
-            settings.set(Some(query_settings().await));
+        if settings.peek().is_none()
+            && let Some(queried) = query_settings().await.ok_or_toast()
+        {
+            settings.set(Some(queried));
             coroutine.send(SettingsUpdate::Set);
         }
     });
 
     rsx! {
         div { class: "flex flex-col h-full overflow-y-auto scrollbar",
+            SectionUpdate {}
             SectionCapture { settings_view, save_settings }
             SectionInput { settings_view, save_settings }
             SectionFamiliars { settings_view, save_settings }
             SectionNotifications { settings_view, save_settings }
             SectionHotkeys { settings_view, save_settings }
+            SectionChat { settings_view, save_settings }
+            SectionMesos { settings_view, save_settings }
+            SectionPanicPlaybooks { settings_view, save_settings }
+            SectionWatchdog { settings_view, save_settings }
+            SectionClientRestart { settings_view, save_settings }
+            SectionLoginSequence { settings_view, save_settings }
+            SectionFocusTracking { settings_view, save_settings }
+            SectionAntiAfk { settings_view, save_settings }
+            SectionBreaks { settings_view, save_settings }
+            SectionSafeMode { settings_view, save_settings }
+            SectionFollowing { settings_view, save_settings }
+            SectionWindowArrangement { settings_view, save_settings }
+            SectionDetectionTuning { settings_view, save_settings }
+            SectionItinerary { settings_view, save_settings }
+            SectionVideoRecording { settings_view, save_settings }
             SectionOthers { settings_view, save_settings }
         }
     }
@@ -76,14 +130,55 @@ fn Section(name: &'static str, children: Element) -> Element {
     }
 }
 
+#[component]
+fn SectionUpdate() -> Element {
+    let mut update = use_signal(|| None::<UpdateInfo>);
+
+    // The update is checked in the background on startup so it is polled until available
+    use_future(move || async move {
+        loop {
+            if let Some(info) = query_update().await.ok_or_toast().flatten() {
+                update.set(Some(info));
+                break;
+            }
+            sleep(Duration::from_secs(10)).await;
+        }
+    });
+
+    rsx! {
+        if let Some(info) = update() {
+            Section { name: "Update available",
+                p { class: "paragraph-xs mb-2", "Version {info.version} has been downloaded." }
+                p { class: "paragraph-xs whitespace-pre-wrap mb-2", "{info.notes}" }
+                Button {
+                    class: "w-full",
+                    text: "Update and restart",
+                    kind: ButtonKind::Primary,
+                    on_click: move |_| async move {
+                        if apply_update().await.ok_or_toast().unwrap_or_default() {
+                            signal_update_loop_shutdown();
+                            wait_update_loop_shutdown(Duration::from_secs(1));
+                            std::process::exit(0);
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
 #[component]
 fn SectionCapture(
     settings_view: Memo<SettingsData>,
     save_settings: EventHandler<SettingsData>,
 ) -> Element {
     let mut selected_handle_index = use_signal(|| None);
+    let mut picking_area = use_signal(|| false);
     let mut handle_names = use_resource(move || async move {
-        let (names, selected) = query_capture_handles().await;
+        let (names, selected) = query_capture_handles()
+            .await
+            .ok_or_toast()
+            .unwrap_or_default();
         selected_handle_index.set(selected);
         names
     });
@@ -101,12 +196,14 @@ fn SectionCapture(
                     label: "Handle",
                     options: handle_names_with_default(),
                     on_select: move |(index, _)| async move {
-                        if index == 0 {
-                            selected_handle_index.set(None);
-                            select_capture_handle(None).await;
-                        } else {
-                            selected_handle_index.set(Some(index - 1));
-                            select_capture_handle(Some(index - 1)).await;
+                        let index = (index != 0).then(|| index - 1);
+                        selected_handle_index.set(index);
+                        let capture_window = select_capture_handle(index).await.ok_or_toast();
+                        if let Some(capture_window) = capture_window {
+                            save_settings(SettingsData {
+                                capture_window,
+                                ..settings_view.peek().clone()
+                            });
                         }
                     },
                     selected: selected_handle_index().map(|index| index + 1).unwrap_or_default(),
@@ -121,6 +218,55 @@ fn SectionCapture(
                     },
                     selected: settings_view().capture_mode,
                 }
+                if settings_view().capture_mode == CaptureMode::WindowsGraphicsCapture {
+                    SettingsEnumSelect::<CaptureHdrMode> {
+                        label: "HDR tone-mapping",
+                        on_select: move |capture_hdr_mode| {
+                            save_settings(SettingsData {
+                                capture_hdr_mode,
+                                ..settings_view.peek().clone()
+                            });
+                        },
+                        selected: settings_view().capture_hdr_mode,
+                    }
+                }
+            }
+            if let Some(capture_window) = settings_view().capture_window {
+                div { class: "grid grid-cols-2 gap-3 mt-2",
+                    SettingsTextInput {
+                        text_label: "Re-select window with title containing",
+                        button_label: "Update",
+                        on_value: move |title: String| {
+                            save_settings(SettingsData {
+                                capture_window: settings_view
+                                    .peek()
+                                    .capture_window
+                                    .clone()
+                                    .map(|window| CaptureWindow { title, ..window }),
+                                ..settings_view.peek().clone()
+                            });
+                        },
+                        value: capture_window.title,
+                    }
+                    SettingsTextInput {
+                        text_label: "And process name (empty for any)",
+                        button_label: "Update",
+                        on_value: move |process_name: String| {
+                            save_settings(SettingsData {
+                                capture_window: settings_view
+                                    .peek()
+                                    .capture_window
+                                    .clone()
+                                    .map(|window| CaptureWindow {
+                                        process_name,
+                                        ..window
+                                    }),
+                                ..settings_view.peek().clone()
+                            });
+                        },
+                        value: capture_window.process_name,
+                    }
+                }
             }
             // Show coordinate inputs when BitBltArea mode is selected
             if settings_view().capture_mode == CaptureMode::BitBltArea {
@@ -151,6 +297,54 @@ fn SectionCapture(
                         },
                         value: settings_view().capture_y.to_string(),
                     }
+                    SettingsTextInput {
+                        text_label: "Capture width",
+                        button_label: "Update",
+                        on_value: move |capture_width: String| {
+                            if let Ok(width) = capture_width.parse::<i32>() {
+                                save_settings(SettingsData {
+                                    capture_width: width,
+                                    ..settings_view.peek().clone()
+                                });
+                            }
+                        },
+                        value: settings_view().capture_width.to_string(),
+                    }
+                    SettingsTextInput {
+                        text_label: "Capture height",
+                        button_label: "Update",
+                        on_value: move |capture_height: String| {
+                            if let Ok(height) = capture_height.parse::<i32>() {
+                                save_settings(SettingsData {
+                                    capture_height: height,
+                                    ..settings_view.peek().clone()
+                                });
+                            }
+                        },
+                        value: settings_view().capture_height.to_string(),
+                    }
+                }
+                Button {
+                    text: if picking_area() { "Cancel picking" } else { "Pick area from desktop" },
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        picking_area.toggle();
+                    },
+                    class: "mt-2",
+                }
+                if picking_area() {
+                    CaptureAreaPicker {
+                        on_area: move |area: Bound| {
+                            picking_area.set(false);
+                            save_settings(SettingsData {
+                                capture_x: area.x,
+                                capture_y: area.y,
+                                capture_width: area.width,
+                                capture_height: area.height,
+                                ..settings_view.peek().clone()
+                            });
+                        },
+                    }
                 }
             }
             Button {
@@ -165,11 +359,99 @@ fn SectionCapture(
     }
 }
 
+/// Shows a live desktop preview for dragging the capture area.
+#[component]
+fn CaptureAreaPicker(on_area: EventHandler<Bound>) -> Element {
+    let mut preview = use_signal(|| None::<DesktopPreview>);
+    let mut drag_start = use_signal(|| None::<(i32, i32)>);
+    let mut area = use_signal(|| None::<Bound>);
+
+    use_future(move || async move {
+        let mut canvas = document::eval(DESKTOP_PREVIEW_JS);
+        loop {
+            // Not shown as toast since this is polled
+            if let Ok(desktop) = query_desktop_preview().await {
+                let result = canvas.send((&desktop.frame, desktop.width, desktop.height));
+                if let Err(EvalError::Finished) = result {
+                    canvas = document::eval(DESKTOP_PREVIEW_JS);
+                }
+                preview.set(Some(desktop));
+            }
+            sleep(Duration::from_millis(500)).await;
+        }
+    });
+
+    rsx! {
+        div { class: "flex flex-col gap-2 mt-2",
+            p { class: "paragraph-xs",
+                "Drag on the preview to select the area containing the game."
+            }
+            div { class: "relative self-start overflow-auto max-w-full",
+                canvas {
+                    id: "canvas-desktop-preview",
+                    class: "block cursor-crosshair",
+                    onmousedown: move |e| {
+                        let point = e.element_coordinates();
+                        drag_start.set(Some((point.x as i32, point.y as i32)));
+                        area.set(None);
+                    },
+                    onmousemove: move |e| {
+                        if let Some(start) = drag_start() {
+                            let point = e.element_coordinates();
+                            area.set(Some(to_area(start, (point.x as i32, point.y as i32))));
+                        }
+                    },
+                    onmouseup: move |_| {
+                        drag_start.set(None);
+                    },
+                }
+                if let Some(area) = area() {
+                    div {
+                        class: "absolute border-2 border-blue-400 pointer-events-none",
+                        style: "left: {area.x}px; top: {area.y}px; width: {area.width}px; height: {area.height}px;",
+                    }
+                }
+            }
+            Button {
+                text: "Use selected area",
+                kind: ButtonKind::Primary,
+                disabled: preview().is_none()
+                    || area().is_none_or(|area| area.width == 0 || area.height == 0),
+                on_click: move |_| {
+                    if let Some(preview) = preview.peek().as_ref()
+                        && let Some(area) = area()
+                    {
+                        on_area(preview.to_capture_area(area));
+                    }
+                },
+                class: "self-start",
+            }
+        }
+    }
+}
+
+/// Converts the dragged `start` and `end` points to an area regardless of drag direction.
+fn to_area(start: (i32, i32), end: (i32, i32)) -> Bound {
+    Bound {
+        x: start.0.min(end.0),
+        y: start.1.min(end.1),
+        width: (start.0 - end.0).abs(),
+        height: (start.1 - end.1).abs(),
+    }
+}
+
 #[component]
 fn SectionInput(
     settings_view: Memo<SettingsData>,
     save_settings: EventHandler<SettingsData>,
 ) -> Element {
+    let mut latencies = use_resource(move || async move {
+        query_input_latencies()
+            .await
+            .ok_or_toast()
+            .unwrap_or_default()
+    });
+
     rsx! {
         Section { name: "Input",
             div { class: "grid grid-cols-3 gap-3",
@@ -194,6 +476,131 @@ fn SectionInput(
                     },
                     value: settings_view().input_method_rpc_server_url,
                 }
+                SettingsTextInput {
+                    text_label: "Serial port",
+                    button_label: "Update",
+                    on_value: move |input_method_serial_port| {
+                        save_settings(SettingsData {
+                            input_method_serial_port,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().input_method_serial_port,
+                }
+                SettingsEnumSelect::<HumanizationProfile> {
+                    label: "Humanization",
+                    on_select: move |humanization| async move {
+                        save_settings(SettingsData {
+                            humanization,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().humanization,
+                }
+                SettingsEnumSelect::<KeyboardLayout> {
+                    label: "Keyboard layout",
+                    on_select: move |keyboard_layout| async move {
+                        save_settings(SettingsData {
+                            keyboard_layout,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().keyboard_layout,
+                }
+            }
+            div { class: "flex flex-col gap-3 mt-2",
+                for (index , key_override) in settings_view()
+                    .key_overrides
+                    .into_iter()
+                    .enumerate()
+                {
+                    KeyOverrideInput {
+                        on_value: move |key_override| {
+                            let mut settings = settings_view.peek().clone();
+                            *settings.key_overrides.get_mut(index).expect("valid index") = key_override;
+                            save_settings(settings);
+                        },
+                        on_delete: move |_| {
+                            let mut settings = settings_view.peek().clone();
+                            settings.key_overrides.remove(index);
+                            save_settings(settings);
+                        },
+                        value: key_override,
+                    }
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Add key override",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    let mut settings = settings_view.peek().clone();
+                    settings.key_overrides.push(KeyOverride {
+                        from: KeyBinding::default(),
+                        to: KeyBinding::default(),
+                    });
+                    save_settings(settings);
+                },
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Test input latency",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| async move {
+                    if !test_input_latency().await.ok_or_toast().unwrap_or_default() {
+                        return;
+                    }
+                    // The test takes a few seconds to toggle the ESC menu for each sample
+                    sleep(Duration::from_secs(10)).await;
+                    latencies.restart();
+                },
+            }
+            for latency in latencies().unwrap_or_default() {
+                p { class: "paragraph-xs mt-2",
+                    "{latency.method}: {latency.average_millis}ms ({latency.average_ticks} ticks) from {latency.samples_millis:?}"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn KeyOverrideInput(
+    on_value: EventHandler<KeyOverride>,
+    on_delete: EventHandler,
+    value: KeyOverride,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+    rsx! {
+        div { class: "grid grid-cols-[auto_auto_16px] gap-3 items-end",
+            KeyBindingInput {
+                label: "Key",
+                on_value: move |key: Option<KeyBinding>| {
+                    on_value(KeyOverride {
+                        from: key.expect("not optional"),
+                        ..value
+                    });
+                },
+                value: Some(value.from),
+            }
+            KeyBindingInput {
+                label: "Sent as",
+                on_value: move |key: Option<KeyBinding>| {
+                    on_value(KeyOverride {
+                        to: key.expect("not optional"),
+                        ..value
+                    });
+                },
+                value: Some(value.to),
+            }
+            div {
+                class: ICON_CONTAINER_CLASS,
+                onclick: move |_| {
+                    on_delete(());
+                },
+                XIcon { class: "{ICON_CLASS} text-red-500" }
             }
         }
     }
@@ -291,6 +698,48 @@ fn SectionFamiliars(
                     },
                     value: familiars_view().swappable_rarities.contains(&FamiliarRarity::Epic),
                 }
+                SettingsEnumSelect::<FamiliarRarityPriority> {
+                    label: "Rarity priority",
+                    disabled: !familiars_view().enable_familiars_swapping,
+                    on_select: move |rarity_priority| async move {
+                        save_settings(SettingsData {
+                            familiars: Familiars {
+                                rarity_priority,
+                                ..familiars_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: familiars_view().rarity_priority,
+                }
+                SettingsEnumSelect::<FamiliarLevelPriority> {
+                    label: "Level priority",
+                    disabled: !familiars_view().enable_familiars_swapping,
+                    on_select: move |level_priority| async move {
+                        save_settings(SettingsData {
+                            familiars: Familiars {
+                                level_priority,
+                                ..familiars_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: familiars_view().level_priority,
+                }
+                SettingsCheckbox {
+                    label: "Swap when points depleted",
+                    disabled: !familiars_view().enable_familiars_swapping,
+                    on_value: move |swap_when_points_depleted| {
+                        save_settings(SettingsData {
+                            familiars: Familiars {
+                                swap_when_points_depleted,
+                                ..familiars_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: familiars_view().swap_when_points_depleted,
+                }
             }
         }
     }
@@ -414,6 +863,71 @@ fn SectionNotifications(
                     },
                     value: notifications_view().notify_on_player_friend_appear,
                 }
+                SettingsCheckbox {
+                    label: "Inventory full",
+                    on_value: move |notify_on_inventory_full| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_inventory_full,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_inventory_full,
+                }
+                SettingsCheckbox {
+                    label: "Chat keyword detected",
+                    on_value: move |notify_on_chat_keyword| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_chat_keyword,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_chat_keyword,
+                }
+                SettingsCheckbox {
+                    label: "Watchdog tripped",
+                    on_value: move |notify_on_watchdog_trip| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_watchdog_trip,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_watchdog_trip,
+                }
+                SettingsCheckbox {
+                    label: "Game window loses focus",
+                    on_value: move |notify_on_focus_lost| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_focus_lost,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_focus_lost,
+                }
+                SettingsCheckbox {
+                    label: "Break starts or ends",
+                    on_value: move |notify_on_break| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_break,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_break,
+                }
                 SettingsCheckbox {
                     label: "Detection fails or map changes",
                     on_value: move |notify_on_fail_or_change_map| {
@@ -428,6 +942,37 @@ fn SectionNotifications(
                     value: notifications_view().notify_on_fail_or_change_map,
                 }
             }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                SettingsCheckbox {
+                    label: "Hide screenshots",
+                    on_value: move |hide_screenshots| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                hide_screenshots,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().hide_screenshots,
+                }
+                NumberInputU32 {
+                    label: "Screenshot JPEG quality",
+                    disabled: notifications_view().hide_screenshots,
+                    minimum_value: 1,
+                    maximum_value: Some(100),
+                    on_value: move |screenshot_jpeg_quality| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                screenshot_jpeg_quality,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().screenshot_jpeg_quality,
+                }
+            }
         }
     }
 }
@@ -519,36 +1064,1641 @@ fn SectionHotkeys(
 }
 
 #[component]
-fn SectionOthers(
+fn SectionChat(
     settings_view: Memo<SettingsData>,
     save_settings: EventHandler<SettingsData>,
 ) -> Element {
-    let export_element_id = use_memo(|| Alphanumeric.sample_string(&mut rand::rng(), 8));
-    let export = use_callback(move |_| {
-        let js = format!(
-            r#"
-            const element = document.getElementById("{}");
-            if (element === null) {{
-                return;
-            }}
-            const json = await dioxus.recv();
-
-            element.setAttribute("href", "data:application/json;charset=utf-8," + encodeURIComponent(json));
-            element.setAttribute("download", "settings.json");
-            element.click();
-            "#,
-            export_element_id(),
-        );
-        let eval = document::eval(js.as_str());
-        let Ok(json) = serde_json::to_string_pretty(&*settings_view.peek()) else {
-            return;
-        };
-        let _ = eval.send(json);
+    let chat_view = use_memo(move || settings_view().chat);
+    let save_chat = use_callback(move |chat: ChatDetection| {
+        save_settings(SettingsData {
+            chat,
+            ..settings_view.peek().clone()
+        });
     });
 
-    let import_element_id = use_memo(|| Alphanumeric.sample_string(&mut rand::rng(), 8));
-    let import = use_callback(move |_| {
-        let js = format!(
+    rsx! {
+        Section { name: "Chat",
+            div { class: "grid grid-cols-2 gap-3",
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enabled| {
+                        save_chat(ChatDetection {
+                            enabled,
+                            ..chat_view.peek().clone()
+                        });
+                    },
+                    value: chat_view().enabled,
+                }
+                SettingsCheckbox {
+                    label: "Run GM-like whisper panic playbook",
+                    disabled: !chat_view().enabled,
+                    on_value: move |trigger_panic_playbook| {
+                        save_chat(ChatDetection {
+                            trigger_panic_playbook,
+                            ..chat_view.peek().clone()
+                        });
+                    },
+                    value: chat_view().trigger_panic_playbook,
+                }
+                NumberInputI32 {
+                    label: "Chat box X",
+                    disabled: !chat_view().enabled,
+                    on_value: move |x| {
+                        save_chat(ChatDetection {
+                            x,
+                            ..chat_view.peek().clone()
+                        });
+                    },
+                    value: chat_view().x,
+                }
+                NumberInputI32 {
+                    label: "Chat box Y",
+                    disabled: !chat_view().enabled,
+                    on_value: move |y| {
+                        save_chat(ChatDetection {
+                            y,
+                            ..chat_view.peek().clone()
+                        });
+                    },
+                    value: chat_view().y,
+                }
+                NumberInputI32 {
+                    label: "Chat box width",
+                    disabled: !chat_view().enabled,
+                    on_value: move |width| {
+                        save_chat(ChatDetection {
+                            width,
+                            ..chat_view.peek().clone()
+                        });
+                    },
+                    value: chat_view().width,
+                }
+                NumberInputI32 {
+                    label: "Chat box height",
+                    disabled: !chat_view().enabled,
+                    on_value: move |height| {
+                        save_chat(ChatDetection {
+                            height,
+                            ..chat_view.peek().clone()
+                        });
+                    },
+                    value: chat_view().height,
+                }
+                SettingsTextInput {
+                    text_label: "Keywords (comma separated)",
+                    button_label: "Update",
+                    on_value: move |keywords: String| {
+                        save_chat(ChatDetection {
+                            keywords: keywords
+                                .split(',')
+                                .map(|keyword| keyword.trim().to_string())
+                                .filter(|keyword| !keyword.is_empty())
+                                .collect(),
+                            ..chat_view.peek().clone()
+                        });
+                    },
+                    value: chat_view().keywords.join(", "),
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionMesos(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let mesos_view = use_memo(move || settings_view().mesos);
+    let save_mesos = use_callback(move |mesos: MesoDetection| {
+        save_settings(SettingsData {
+            mesos,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Mesos",
+            p { class: "paragraph-xs mb-2",
+                "Reads the meso count inside the region (e.g. of the inventory window kept open) to estimate mesos per hour."
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enabled| {
+                        save_mesos(MesoDetection {
+                            enabled,
+                            ..*mesos_view.peek()
+                        });
+                    },
+                    value: mesos_view().enabled,
+                }
+                div {}
+                NumberInputI32 {
+                    label: "Meso count X",
+                    disabled: !mesos_view().enabled,
+                    on_value: move |x| {
+                        save_mesos(MesoDetection {
+                            x,
+                            ..*mesos_view.peek()
+                        });
+                    },
+                    value: mesos_view().x,
+                }
+                NumberInputI32 {
+                    label: "Meso count Y",
+                    disabled: !mesos_view().enabled,
+                    on_value: move |y| {
+                        save_mesos(MesoDetection {
+                            y,
+                            ..*mesos_view.peek()
+                        });
+                    },
+                    value: mesos_view().y,
+                }
+                NumberInputI32 {
+                    label: "Meso count width",
+                    disabled: !mesos_view().enabled,
+                    on_value: move |width| {
+                        save_mesos(MesoDetection {
+                            width,
+                            ..*mesos_view.peek()
+                        });
+                    },
+                    value: mesos_view().width,
+                }
+                NumberInputI32 {
+                    label: "Meso count height",
+                    disabled: !mesos_view().enabled,
+                    on_value: move |height| {
+                        save_mesos(MesoDetection {
+                            height,
+                            ..*mesos_view.peek()
+                        });
+                    },
+                    value: mesos_view().height,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionPanicPlaybooks(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let playbooks_view = use_memo(move || settings_view().panic_playbooks);
+    let save_playbooks = use_callback(move |panic_playbooks: PanicPlaybooks| {
+        save_settings(SettingsData {
+            panic_playbooks,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Panic playbooks",
+            p { class: "paragraph-xs mb-2",
+                "Steps are run in order after their delay. The bot resumes afterward unless a step stops it."
+            }
+            PanicPlaybookInput {
+                label: "Stranger detected",
+                on_value: move |stranger_detected| {
+                    save_playbooks(PanicPlaybooks {
+                        stranger_detected,
+                        ..playbooks_view.peek().clone()
+                    });
+                },
+                value: playbooks_view().stranger_detected,
+            }
+            PanicPlaybookInput {
+                label: "GM-like whisper",
+                on_value: move |gm_whisper| {
+                    save_playbooks(PanicPlaybooks {
+                        gm_whisper,
+                        ..playbooks_view.peek().clone()
+                    });
+                },
+                value: playbooks_view().gm_whisper,
+            }
+            PanicPlaybookInput {
+                label: "Detection failure streak",
+                on_value: move |detection_failure_streak| {
+                    save_playbooks(PanicPlaybooks {
+                        detection_failure_streak,
+                        ..playbooks_view.peek().clone()
+                    });
+                },
+                value: playbooks_view().detection_failure_streak,
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                MillisInput {
+                    label: "Detection failure streak for",
+                    on_value: move |detection_failure_streak_millis| {
+                        save_playbooks(PanicPlaybooks {
+                            detection_failure_streak_millis,
+                            ..playbooks_view.peek().clone()
+                        });
+                    },
+                    value: playbooks_view().detection_failure_streak_millis,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PanicPlaybookInput(
+    label: &'static str,
+    on_value: EventHandler<PanicPlaybook>,
+    value: PanicPlaybook,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+    let mut playbook = use_signal(PanicPlaybook::default);
+    let save_step = use_callback(move |(index, step): (usize, PanicStep)| {
+        let mut playbook = playbook.peek().clone();
+        *playbook.steps.get_mut(index).expect("valid index") = step;
+        on_value(playbook);
+    });
+
+    use_effect(use_reactive!(|value| playbook.set(value)));
+
+    rsx! {
+        div { class: "flex flex-col gap-2 mb-3",
+            SettingsCheckbox {
+                label,
+                on_value: move |enabled| {
+                    on_value(PanicPlaybook {
+                        enabled,
+                        ..playbook.peek().clone()
+                    });
+                },
+                value: playbook().enabled,
+            }
+            div { class: "grid grid-cols-[auto_auto_auto_16px] gap-3 items-end",
+                for (index , step) in playbook().steps.into_iter().enumerate() {
+                    SettingsEnumSelect::<PanicStepKind> {
+                        label: "Step",
+                        on_select: move |kind| {
+                            save_step((
+                                index,
+                                PanicStep {
+                                    kind,
+                                    ..playbook.peek().steps[index].clone()
+                                },
+                            ));
+                        },
+                        selected: step.kind,
+                    }
+                    MillisInput {
+                        label: "Delay",
+                        on_value: move |delay_millis| {
+                            save_step((
+                                index,
+                                PanicStep {
+                                    delay_millis,
+                                    ..playbook.peek().steps[index].clone()
+                                },
+                            ));
+                        },
+                        value: step.delay_millis,
+                    }
+                    TextInput {
+                        label: "Script path",
+                        disabled: step.kind != PanicStepKind::RunScript,
+                        on_value: move |script| {
+                            save_step((
+                                index,
+                                PanicStep {
+                                    script,
+                                    ..playbook.peek().steps[index].clone()
+                                },
+                            ));
+                        },
+                        value: step.script,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            let mut playbook = playbook.peek().clone();
+                            playbook.steps.remove(index);
+                            on_value(playbook);
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+            Button {
+                class: "w-full",
+                text: "Add step",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    let mut playbook = playbook.peek().clone();
+                    playbook.steps.push(PanicStep::default());
+                    on_value(playbook);
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionWatchdog(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let watchdog_view = use_memo(move || settings_view().watchdog);
+    let save_watchdog = use_callback(move |watchdog: Watchdog| {
+        save_settings(SettingsData {
+            watchdog,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Watchdog",
+            p { class: "paragraph-xs mb-2",
+                "Stops the bot and releases all keys when any of the thresholds is exceeded. Zero disables the threshold."
+            }
+            SettingsCheckbox {
+                label: "Enabled",
+                on_value: move |enabled| {
+                    save_watchdog(Watchdog {
+                        enabled,
+                        ..watchdog_view.peek().clone()
+                    });
+                },
+                value: watchdog_view().enabled,
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                MillisInput {
+                    label: "Capture lost for",
+                    disabled: !watchdog_view().enabled,
+                    on_value: move |capture_lost_millis| {
+                        save_watchdog(Watchdog {
+                            capture_lost_millis,
+                            ..watchdog_view.peek().clone()
+                        });
+                    },
+                    value: watchdog_view().capture_lost_millis,
+                }
+                MillisInput {
+                    label: "Detection fails for",
+                    disabled: !watchdog_view().enabled,
+                    on_value: move |detection_failure_millis| {
+                        save_watchdog(Watchdog {
+                            detection_failure_millis,
+                            ..watchdog_view.peek().clone()
+                        });
+                    },
+                    value: watchdog_view().detection_failure_millis,
+                }
+                MillisInput {
+                    label: "Heartbeat every",
+                    disabled: !watchdog_view().enabled,
+                    on_value: move |heartbeat_interval_millis| {
+                        save_watchdog(Watchdog {
+                            heartbeat_interval_millis,
+                            ..watchdog_view.peek().clone()
+                        });
+                    },
+                    value: watchdog_view().heartbeat_interval_millis,
+                }
+                NumberInputU32 {
+                    label: "Heartbeat max failures",
+                    disabled: !watchdog_view().enabled,
+                    on_value: move |heartbeat_max_failures| {
+                        save_watchdog(Watchdog {
+                            heartbeat_max_failures,
+                            ..watchdog_view.peek().clone()
+                        });
+                    },
+                    value: watchdog_view().heartbeat_max_failures,
+                }
+                SettingsTextInput {
+                    text_label: "Heartbeat URL",
+                    button_label: "Update",
+                    on_value: move |heartbeat_url| {
+                        save_watchdog(Watchdog {
+                            heartbeat_url,
+                            ..watchdog_view.peek().clone()
+                        });
+                    },
+                    value: watchdog_view().heartbeat_url,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionClientRestart(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let restart_view = use_memo(move || settings_view().client_restart);
+    let save_restart = use_callback(move |client_restart: ClientRestart| {
+        save_settings(SettingsData {
+            client_restart,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Client restart",
+            p { class: "paragraph-xs mb-2",
+                "Stops the bot when the game client exits while running. The client is relaunched from the path if provided, then the bot waits for the game window, runs the login routines and resumes."
+            }
+            SettingsCheckbox {
+                label: "Enabled",
+                on_value: move |enabled| {
+                    save_restart(ClientRestart {
+                        enabled,
+                        ..restart_view.peek().clone()
+                    });
+                },
+                value: restart_view().enabled,
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                MillisInput {
+                    label: "Wait for window",
+                    disabled: !restart_view().enabled,
+                    on_value: move |window_timeout_millis| {
+                        save_restart(ClientRestart {
+                            window_timeout_millis,
+                            ..restart_view.peek().clone()
+                        });
+                    },
+                    value: restart_view().window_timeout_millis,
+                }
+                MillisInput {
+                    label: "Log in after",
+                    disabled: !restart_view().enabled,
+                    on_value: move |login_delay_millis| {
+                        save_restart(ClientRestart {
+                            login_delay_millis,
+                            ..restart_view.peek().clone()
+                        });
+                    },
+                    value: restart_view().login_delay_millis,
+                }
+                SettingsTextInput {
+                    text_label: "Client path",
+                    button_label: "Update",
+                    on_value: move |path| {
+                        save_restart(ClientRestart {
+                            path,
+                            ..restart_view.peek().clone()
+                        });
+                    },
+                    value: restart_view().path,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionLoginSequence(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+    let login_view = use_memo(move || settings_view().login_sequence);
+    let save_login = use_callback(move |login_sequence: LoginSequence| {
+        save_settings(SettingsData {
+            login_sequence,
+            ..settings_view.peek().clone()
+        });
+    });
+    let save_step = use_callback(move |(index, step): (usize, RoutineStep)| {
+        let mut login = login_view.peek().clone();
+        *login.steps.get_mut(index).expect("valid index") = step;
+        save_login(login);
+    });
+    let save_keypad = use_callback(move |pic_keypad: Bound| {
+        save_login(LoginSequence {
+            pic_keypad,
+            ..login_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Login sequence",
+            p { class: "paragraph-xs mb-2",
+                "Logs in after the game client restarts or when the login screen is shown while changing channel. Steps are run in order after their delay, then the PIC is entered by clicking the detected keypad digits. The PIC is stored in the database, which can be encrypted with the encrypt_database configuration."
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enabled| {
+                        save_login(LoginSequence {
+                            enabled,
+                            ..login_view.peek().clone()
+                        });
+                    },
+                    value: login_view().enabled,
+                }
+                MillisInput {
+                    label: "Login screen after",
+                    disabled: !login_view().enabled,
+                    on_value: move |login_screen_after_millis| {
+                        save_login(LoginSequence {
+                            login_screen_after_millis,
+                            ..login_view.peek().clone()
+                        });
+                    },
+                    value: login_view().login_screen_after_millis,
+                }
+                SettingsTextInput {
+                    text_label: "PIC (empty for none)",
+                    button_label: "Update",
+                    on_value: move |pic: String| {
+                        save_login(LoginSequence {
+                            pic: pic.chars().filter(char::is_ascii_digit).collect(),
+                            ..login_view.peek().clone()
+                        });
+                    },
+                    value: login_view().pic,
+                }
+                div {}
+                NumberInputI32 {
+                    label: "PIC keypad X",
+                    disabled: !login_view().enabled,
+                    on_value: move |x| {
+                        save_keypad(Bound {
+                            x,
+                            ..login_view.peek().pic_keypad
+                        });
+                    },
+                    value: login_view().pic_keypad.x,
+                }
+                NumberInputI32 {
+                    label: "PIC keypad Y",
+                    disabled: !login_view().enabled,
+                    on_value: move |y| {
+                        save_keypad(Bound {
+                            y,
+                            ..login_view.peek().pic_keypad
+                        });
+                    },
+                    value: login_view().pic_keypad.y,
+                }
+                NumberInputI32 {
+                    label: "PIC keypad width",
+                    disabled: !login_view().enabled,
+                    on_value: move |width| {
+                        save_keypad(Bound {
+                            width,
+                            ..login_view.peek().pic_keypad
+                        });
+                    },
+                    value: login_view().pic_keypad.width,
+                }
+                NumberInputI32 {
+                    label: "PIC keypad height",
+                    disabled: !login_view().enabled,
+                    on_value: move |height| {
+                        save_keypad(Bound {
+                            height,
+                            ..login_view.peek().pic_keypad
+                        });
+                    },
+                    value: login_view().pic_keypad.height,
+                }
+            }
+            div { class: "grid grid-cols-[auto_auto_auto_auto_auto_16px] gap-3 items-end mt-2",
+                for (index , step) in login_view().steps.into_iter().enumerate() {
+                    SettingsEnumSelect::<RoutineStepKind> {
+                        label: "Step",
+                        on_select: move |kind| {
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    kind,
+                                    ..login_view.peek().steps[index].clone()
+                                },
+                            ));
+                        },
+                        selected: step.kind,
+                    }
+                    MillisInput {
+                        label: "Delay",
+                        on_value: move |delay_millis| {
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    delay_millis,
+                                    ..login_view.peek().steps[index].clone()
+                                },
+                            ));
+                        },
+                        value: step.delay_millis,
+                    }
+                    KeyBindingInput {
+                        label: "Key",
+                        disabled: step.kind != RoutineStepKind::Key,
+                        on_value: move |key: Option<KeyBinding>| {
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    key: key.expect("not optional"),
+                                    ..login_view.peek().steps[index].clone()
+                                },
+                            ));
+                        },
+                        value: Some(step.key),
+                    }
+                    NumberInputI32 {
+                        label: "Click X",
+                        disabled: step.kind != RoutineStepKind::Click,
+                        on_value: move |x| {
+                            let step = login_view.peek().steps[index].clone();
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    point: ClientPoint { x, ..step.point },
+                                    ..step
+                                },
+                            ));
+                        },
+                        value: step.point.x,
+                    }
+                    NumberInputI32 {
+                        label: "Click Y",
+                        disabled: step.kind != RoutineStepKind::Click,
+                        on_value: move |y| {
+                            let step = login_view.peek().steps[index].clone();
+                            save_step((
+                                index,
+                                RoutineStep {
+                                    point: ClientPoint { y, ..step.point },
+                                    ..step
+                                },
+                            ));
+                        },
+                        value: step.point.y,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            let mut login = login_view.peek().clone();
+                            login.steps.remove(index);
+                            save_login(login);
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Add step",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    let mut login = login_view.peek().clone();
+                    login.steps.push(RoutineStep::default());
+                    save_login(login);
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionFocusTracking(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let focus_tracking_view = use_memo(move || settings_view().focus_tracking);
+    let save_focus_tracking = use_callback(move |focus_tracking: FocusTracking| {
+        save_settings(SettingsData {
+            focus_tracking,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Focus tracking",
+            p { class: "paragraph-xs mb-2",
+                "Prevents keys from leaking into other applications when the game window loses focus. Does not apply to BitBltArea capture mode."
+            }
+            div { class: "grid grid-cols-3 gap-3",
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enabled| {
+                        save_focus_tracking(FocusTracking {
+                            enabled,
+                            ..focus_tracking_view.peek().clone()
+                        });
+                    },
+                    value: focus_tracking_view().enabled,
+                }
+                SettingsCheckbox {
+                    label: "Pause keys",
+                    disabled: !focus_tracking_view().enabled,
+                    on_value: move |pause_keys| {
+                        save_focus_tracking(FocusTracking {
+                            pause_keys,
+                            ..focus_tracking_view.peek().clone()
+                        });
+                    },
+                    value: focus_tracking_view().pause_keys,
+                }
+                SettingsCheckbox {
+                    label: "Attempt to refocus",
+                    disabled: !focus_tracking_view().enabled,
+                    on_value: move |refocus| {
+                        save_focus_tracking(FocusTracking {
+                            refocus,
+                            ..focus_tracking_view.peek().clone()
+                        });
+                    },
+                    value: focus_tracking_view().refocus,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionAntiAfk(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let anti_afk_view = use_memo(move || settings_view().anti_afk);
+    let save_anti_afk = use_callback(move |anti_afk: AntiAfk| {
+        save_settings(SettingsData {
+            anti_afk,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Anti-afk",
+            p { class: "paragraph-xs mb-2",
+                "Performs a random small action on an interval while halting or without any pending action."
+            }
+            SettingsCheckbox {
+                label: "Enabled",
+                on_value: move |enabled| {
+                    save_anti_afk(AntiAfk {
+                        enabled,
+                        ..anti_afk_view.peek().clone()
+                    });
+                },
+                value: anti_afk_view().enabled,
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                MillisInput {
+                    label: "Interval",
+                    disabled: !anti_afk_view().enabled,
+                    on_value: move |interval_millis| {
+                        save_anti_afk(AntiAfk {
+                            interval_millis,
+                            ..anti_afk_view.peek().clone()
+                        });
+                    },
+                    value: anti_afk_view().interval_millis,
+                }
+                MillisInput {
+                    label: "Interval jitter",
+                    disabled: !anti_afk_view().enabled,
+                    on_value: move |interval_jitter_millis| {
+                        save_anti_afk(AntiAfk {
+                            interval_jitter_millis,
+                            ..anti_afk_view.peek().clone()
+                        });
+                    },
+                    value: anti_afk_view().interval_jitter_millis,
+                }
+                SettingsCheckbox {
+                    label: "Walk",
+                    disabled: !anti_afk_view().enabled,
+                    on_value: move |walk| {
+                        save_anti_afk(AntiAfk {
+                            walk,
+                            ..anti_afk_view.peek().clone()
+                        });
+                    },
+                    value: anti_afk_view().walk,
+                }
+                NumberInputU32 {
+                    label: "Walk bound",
+                    disabled: !anti_afk_view().enabled || !anti_afk_view().walk,
+                    minimum_value: 1,
+                    on_value: move |walk_bound| {
+                        save_anti_afk(AntiAfk {
+                            walk_bound,
+                            ..anti_afk_view.peek().clone()
+                        });
+                    },
+                    value: anti_afk_view().walk_bound,
+                }
+                SettingsCheckbox {
+                    label: "Flip facing",
+                    disabled: !anti_afk_view().enabled,
+                    on_value: move |flip_facing| {
+                        save_anti_afk(AntiAfk {
+                            flip_facing,
+                            ..anti_afk_view.peek().clone()
+                        });
+                    },
+                    value: anti_afk_view().flip_facing,
+                }
+                div { class: "flex gap-2",
+                    KeyBindingInput {
+                        label: "Open and close menu",
+                        div_class: "flex-grow",
+                        disabled: !anti_afk_view().enabled,
+                        on_value: move |key: Option<KeyBinding>| {
+                            let anti_afk = anti_afk_view.peek().clone();
+                            save_anti_afk(AntiAfk {
+                                menu_key: KeyBindingConfiguration {
+                                    key: key.expect("not optional"),
+                                    ..anti_afk.menu_key
+                                },
+                                ..anti_afk
+                            });
+                        },
+                        value: Some(anti_afk_view().menu_key.key),
+                    }
+                    SettingsCheckbox {
+                        label: "Enabled",
+                        disabled: !anti_afk_view().enabled,
+                        on_value: move |enabled| {
+                            let anti_afk = anti_afk_view.peek().clone();
+                            save_anti_afk(AntiAfk {
+                                menu_key: KeyBindingConfiguration {
+                                    enabled,
+                                    ..anti_afk.menu_key
+                                },
+                                ..anti_afk
+                            });
+                        },
+                        value: anti_afk_view().menu_key.enabled,
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionBreaks(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let breaks_view = use_memo(move || settings_view().breaks);
+    let save_breaks = use_callback(move |breaks: Breaks| {
+        save_settings(SettingsData {
+            breaks,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Breaks",
+            p { class: "paragraph-xs mb-2",
+                "Pauses rotating for a random duration at random intervals and then resumes. The player walks to the break position of the current map first if there is one."
+            }
+            SettingsCheckbox {
+                label: "Enabled",
+                on_value: move |enabled| {
+                    save_breaks(Breaks {
+                        enabled,
+                        ..breaks_view.peek().clone()
+                    });
+                },
+                value: breaks_view().enabled,
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                MillisInput {
+                    label: "Interval",
+                    disabled: !breaks_view().enabled,
+                    on_value: move |interval_millis| {
+                        save_breaks(Breaks {
+                            interval_millis,
+                            ..breaks_view.peek().clone()
+                        });
+                    },
+                    value: breaks_view().interval_millis,
+                }
+                MillisInput {
+                    label: "Interval jitter",
+                    disabled: !breaks_view().enabled,
+                    on_value: move |interval_jitter_millis| {
+                        save_breaks(Breaks {
+                            interval_jitter_millis,
+                            ..breaks_view.peek().clone()
+                        });
+                    },
+                    value: breaks_view().interval_jitter_millis,
+                }
+                MillisInput {
+                    label: "Duration",
+                    disabled: !breaks_view().enabled,
+                    on_value: move |duration_millis| {
+                        save_breaks(Breaks {
+                            duration_millis,
+                            ..breaks_view.peek().clone()
+                        });
+                    },
+                    value: breaks_view().duration_millis,
+                }
+                MillisInput {
+                    label: "Duration jitter",
+                    disabled: !breaks_view().enabled,
+                    on_value: move |duration_jitter_millis| {
+                        save_breaks(Breaks {
+                            duration_jitter_millis,
+                            ..breaks_view.peek().clone()
+                        });
+                    },
+                    value: breaks_view().duration_jitter_millis,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionSafeMode(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let safe_mode_view = use_memo(move || settings_view().safe_mode);
+    let save_safe_mode = use_callback(move |safe_mode: SafeMode| {
+        save_settings(SettingsData {
+            safe_mode,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Safe mode",
+            p { class: "paragraph-xs mb-2",
+                "Caps the inputs sent regardless of the rotation and stops sending any input during mandatory breaks. A cap of 0 means no cap and an interval of 0 means no breaks."
+            }
+            SettingsCheckbox {
+                label: "Enabled",
+                on_value: move |enabled| {
+                    save_safe_mode(SafeMode {
+                        enabled,
+                        ..safe_mode_view.peek().clone()
+                    });
+                },
+                value: safe_mode_view().enabled,
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                NumberInputU32 {
+                    label: "Max keys per second",
+                    disabled: !safe_mode_view().enabled,
+                    on_value: move |max_keys_per_second| {
+                        save_safe_mode(SafeMode {
+                            max_keys_per_second,
+                            ..safe_mode_view.peek().clone()
+                        });
+                    },
+                    value: safe_mode_view().max_keys_per_second,
+                }
+                NumberInputU32 {
+                    label: "Max inputs per minute",
+                    disabled: !safe_mode_view().enabled,
+                    on_value: move |max_inputs_per_minute| {
+                        save_safe_mode(SafeMode {
+                            max_inputs_per_minute,
+                            ..safe_mode_view.peek().clone()
+                        });
+                    },
+                    value: safe_mode_view().max_inputs_per_minute,
+                }
+                MillisInput {
+                    label: "Break interval",
+                    disabled: !safe_mode_view().enabled,
+                    on_value: move |break_interval_millis| {
+                        save_safe_mode(SafeMode {
+                            break_interval_millis,
+                            ..safe_mode_view.peek().clone()
+                        });
+                    },
+                    value: safe_mode_view().break_interval_millis,
+                }
+                MillisInput {
+                    label: "Break interval jitter",
+                    disabled: !safe_mode_view().enabled,
+                    on_value: move |break_interval_jitter_millis| {
+                        save_safe_mode(SafeMode {
+                            break_interval_jitter_millis,
+                            ..safe_mode_view.peek().clone()
+                        });
+                    },
+                    value: safe_mode_view().break_interval_jitter_millis,
+                }
+                MillisInput {
+                    label: "Break duration",
+                    disabled: !safe_mode_view().enabled,
+                    on_value: move |break_duration_millis| {
+                        save_safe_mode(SafeMode {
+                            break_duration_millis,
+                            ..safe_mode_view.peek().clone()
+                        });
+                    },
+                    value: safe_mode_view().break_duration_millis,
+                }
+                MillisInput {
+                    label: "Break duration jitter",
+                    disabled: !safe_mode_view().enabled,
+                    on_value: move |break_duration_jitter_millis| {
+                        save_safe_mode(SafeMode {
+                            break_duration_jitter_millis,
+                            ..safe_mode_view.peek().clone()
+                        });
+                    },
+                    value: safe_mode_view().break_duration_jitter_millis,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionFollowing(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let following_view = use_memo(move || settings_view().following);
+    let save_following = use_callback(move |following: Following| {
+        save_settings(SettingsData {
+            following,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Following",
+            p { class: "paragraph-xs mb-2",
+                "Shares the player position as the leader or moves toward the leader position as the follower. Both characters must be on the same map."
+            }
+            div { class: "grid grid-cols-3 gap-3",
+                SettingsEnumSelect::<FollowingRole> {
+                    label: "Role",
+                    on_select: move |role| {
+                        save_following(Following {
+                            role,
+                            ..following_view.peek().clone()
+                        });
+                    },
+                    selected: following_view().role,
+                }
+                SettingsTextInput {
+                    text_label: "Leader address",
+                    button_label: "Update",
+                    on_value: move |address| {
+                        save_following(Following {
+                            address,
+                            ..following_view.peek().clone()
+                        });
+                    },
+                    value: following_view().address,
+                }
+                NumberInputU32 {
+                    label: "Follow distance",
+                    disabled: following_view().role != FollowingRole::Follower,
+                    minimum_value: 1,
+                    on_value: move |distance| {
+                        save_following(Following {
+                            distance,
+                            ..following_view.peek().clone()
+                        });
+                    },
+                    value: following_view().distance,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionVideoRecording(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let recording_view = use_memo(move || settings_view().video_recording);
+    let save_recording = use_callback(move |video_recording: VideoRecording| {
+        save_settings(SettingsData {
+            video_recording,
+            ..settings_view.peek().clone()
+        });
+    });
+    let mut recording_path = use_signal(|| None::<String>);
+
+    rsx! {
+        Section { name: "Video recording",
+            p { class: "paragraph-xs mb-2",
+                "Records the captured frames to a video file for reviewing the bot behavior. Recording stops when the file reaches the maximum size with zero indicating unlimited."
+            }
+            div { class: "grid grid-cols-3 gap-3",
+                SettingsEnumSelect::<VideoFormat> {
+                    label: "Format",
+                    disabled: recording_path().is_some(),
+                    on_select: move |format| {
+                        save_recording(VideoRecording {
+                            format,
+                            ..recording_view.peek().clone()
+                        });
+                    },
+                    selected: recording_view().format,
+                }
+                NumberInputU32 {
+                    label: "Frames per second",
+                    on_value: move |fps| {
+                        save_recording(VideoRecording {
+                            fps,
+                            ..recording_view.peek().clone()
+                        });
+                    },
+                    value: recording_view().fps,
+                }
+                NumberInputU32 {
+                    label: "Maximum size (MB)",
+                    on_value: move |max_size_mb| {
+                        save_recording(VideoRecording {
+                            max_size_mb,
+                            ..recording_view.peek().clone()
+                        });
+                    },
+                    value: recording_view().max_size_mb,
+                }
+                SettingsCheckbox {
+                    label: "Draw player",
+                    on_value: move |overlay_player| {
+                        save_recording(VideoRecording {
+                            overlay_player,
+                            ..recording_view.peek().clone()
+                        });
+                    },
+                    value: recording_view().overlay_player,
+                }
+                SettingsCheckbox {
+                    label: "Draw minimap",
+                    on_value: move |overlay_minimap| {
+                        save_recording(VideoRecording {
+                            overlay_minimap,
+                            ..recording_view.peek().clone()
+                        });
+                    },
+                    value: recording_view().overlay_minimap,
+                }
+                SettingsCheckbox {
+                    label: "Draw state",
+                    on_value: move |overlay_state| {
+                        save_recording(VideoRecording {
+                            overlay_state,
+                            ..recording_view.peek().clone()
+                        });
+                    },
+                    value: recording_view().overlay_state,
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: if recording_path().is_some() { "Stop recording" } else { "Start recording" },
+                kind: ButtonKind::Secondary,
+                on_click: move |_| async move {
+                    let start = recording_path.peek().is_none();
+                    let path = record_video(start).await.ok_or_toast().flatten();
+                    recording_path.set(path.filter(|_| start));
+                },
+            }
+            if let Some(path) = recording_path() {
+                p { class: "paragraph-xs mt-2", "Recording to {path}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionWindowArrangement(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let arrangement_view = use_memo(move || settings_view().window_arrangement);
+    let save_arrangement = use_callback(move |window_arrangement: WindowArrangement| {
+        save_settings(SettingsData {
+            window_arrangement,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Window arrangement",
+            p { class: "paragraph-xs mb-2",
+                "Moves and resizes the game window when actions start so detection sees the expected layout. Does not apply to BitBltArea capture mode."
+            }
+            SettingsCheckbox {
+                label: "Enabled",
+                on_value: move |enabled| {
+                    save_arrangement(WindowArrangement {
+                        enabled,
+                        ..arrangement_view.peek().clone()
+                    });
+                },
+                value: arrangement_view().enabled,
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                NumberInputI32 {
+                    label: "X",
+                    disabled: !arrangement_view().enabled,
+                    on_value: move |x| {
+                        save_arrangement(WindowArrangement {
+                            x,
+                            ..arrangement_view.peek().clone()
+                        });
+                    },
+                    value: arrangement_view().x,
+                }
+                NumberInputI32 {
+                    label: "Y",
+                    disabled: !arrangement_view().enabled,
+                    on_value: move |y| {
+                        save_arrangement(WindowArrangement {
+                            y,
+                            ..arrangement_view.peek().clone()
+                        });
+                    },
+                    value: arrangement_view().y,
+                }
+                NumberInputI32 {
+                    label: "Width",
+                    disabled: !arrangement_view().enabled,
+                    on_value: move |width| {
+                        save_arrangement(WindowArrangement {
+                            width,
+                            ..arrangement_view.peek().clone()
+                        });
+                    },
+                    value: arrangement_view().width,
+                }
+                NumberInputI32 {
+                    label: "Height",
+                    disabled: !arrangement_view().enabled,
+                    on_value: move |height| {
+                        save_arrangement(WindowArrangement {
+                            height,
+                            ..arrangement_view.peek().clone()
+                        });
+                    },
+                    value: arrangement_view().height,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionDetectionTuning(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let tuning_view = use_memo(move || settings_view().detection_tuning);
+    let save_tuning = use_callback(move |detection_tuning: DetectionTuning| {
+        save_settings(SettingsData {
+            detection_tuning,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    rsx! {
+        Section { name: "Detection tuning",
+            p { class: "paragraph-xs mb-2",
+                "Adjusts detection thresholds for unusual resolutions. Lower scores detect more leniently but may cause false detections."
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                NumberInputU32 {
+                    label: "Minimap border whiteness",
+                    minimum_value: 0,
+                    maximum_value: Some(255),
+                    on_value: move |threshold: u32| {
+                        save_tuning(DetectionTuning {
+                            minimap_border_threshold: threshold.min(255) as u8,
+                            ..*tuning_view.peek()
+                        });
+                    },
+                    value: tuning_view().minimap_border_threshold as u32,
+                }
+                PercentageInput {
+                    label: "Minimap similarity",
+                    on_value: move |percent: f32| {
+                        save_tuning(DetectionTuning {
+                            minimap_similarity: percent / 100.0,
+                            ..*tuning_view.peek()
+                        });
+                    },
+                    value: tuning_view().minimap_similarity * 100.0,
+                }
+                PercentageInput {
+                    label: "Player match score",
+                    on_value: move |percent: f32| {
+                        save_tuning(DetectionTuning {
+                            player_score: percent / 100.0,
+                            ..*tuning_view.peek()
+                        });
+                    },
+                    value: tuning_view().player_score * 100.0,
+                }
+                PercentageInput {
+                    label: "Health bar match score",
+                    on_value: move |percent: f32| {
+                        save_tuning(DetectionTuning {
+                            health_bar_score: percent / 100.0,
+                            ..*tuning_view.peek()
+                        });
+                    },
+                    value: tuning_view().health_bar_score * 100.0,
+                }
+                for (index , channel) in ["hue", "saturation", "value"].into_iter().enumerate() {
+                    NumberInputU32 {
+                        label: format!("Health bar min {channel}"),
+                        minimum_value: 0,
+                        maximum_value: Some(255),
+                        on_value: move |value: u32| {
+                            let mut tuning = *tuning_view.peek();
+                            tuning.health_bar_color_lower[index] = value.min(255) as u8;
+                            save_tuning(tuning);
+                        },
+                        value: tuning_view().health_bar_color_lower[index] as u32,
+                    }
+                    NumberInputU32 {
+                        label: format!("Health bar max {channel}"),
+                        minimum_value: 0,
+                        maximum_value: Some(255),
+                        on_value: move |value: u32| {
+                            let mut tuning = *tuning_view.peek();
+                            tuning.health_bar_color_upper[index] = value.min(255) as u8;
+                            save_tuning(tuning);
+                        },
+                        value: tuning_view().health_bar_color_upper[index] as u32,
+                    }
+                }
+                SettingsCheckbox {
+                    label: "Show live scores",
+                    on_value: move |show_scores| {
+                        save_tuning(DetectionTuning {
+                            show_scores,
+                            ..*tuning_view.peek()
+                        });
+                    },
+                    value: tuning_view().show_scores,
+                }
+                Button {
+                    text: "Reset to defaults",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        save_tuning(DetectionTuning {
+                            show_scores: tuning_view.peek().show_scores,
+                            ..DetectionTuning::default()
+                        });
+                    },
+                    class: "col-span-2",
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionItinerary(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let minimaps = use_resource(async || query_minimaps().await.ok_or_toast().unwrap_or_default());
+    let itinerary_view = use_memo(move || settings_view().itinerary);
+    let save_itinerary = use_callback(move |itinerary: Itinerary| {
+        save_settings(SettingsData {
+            itinerary,
+            ..settings_view.peek().clone()
+        });
+    });
+    let save_step = use_callback(move |(index, step): (usize, ItineraryStep)| {
+        let mut itinerary = itinerary_view.peek().clone();
+        *itinerary.steps.get_mut(index).expect("valid index") = step;
+        save_itinerary(itinerary);
+    });
+
+    rsx! {
+        Section { name: "Itinerary",
+            p { class: "paragraph-xs mb-2",
+                "Farms each map with its preset for the duration then leaves by the exit. The next map is verified by minimap matching before farming resumes. The itinerary starts from the step of the currently selected map."
+            }
+            SettingsCheckbox {
+                label: "Enabled",
+                on_value: move |enabled| {
+                    save_itinerary(Itinerary {
+                        enabled,
+                        ..itinerary_view.peek().clone()
+                    });
+                },
+                value: itinerary_view().enabled,
+            }
+            div { class: "flex flex-col gap-3 mt-2",
+                for (index , step) in itinerary_view().steps.into_iter().enumerate() {
+                    ItineraryStepInput {
+                        minimaps: minimaps().unwrap_or_default(),
+                        on_value: move |step| {
+                            save_step((index, step));
+                        },
+                        on_delete: move |_| {
+                            let mut itinerary = itinerary_view.peek().clone();
+                            itinerary.steps.remove(index);
+                            save_itinerary(itinerary);
+                        },
+                        value: step,
+                    }
+                }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Add step",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    let mut itinerary = itinerary_view.peek().clone();
+                    itinerary.steps.push(ItineraryStep::default());
+                    save_itinerary(itinerary);
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn ItineraryStepInput(
+    minimaps: Vec<MinimapData>,
+    on_value: EventHandler<ItineraryStep>,
+    on_delete: EventHandler,
+    value: ItineraryStep,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+    let mut step = use_signal(ItineraryStep::default);
+    let minimap = minimaps
+        .iter()
+        .find(|minimap| minimap.id.is_some() && minimap.id == step().minimap_id);
+    let minimap_names = iter::once("None".to_string())
+        .chain(minimaps.iter().map(|minimap| minimap.name.clone()))
+        .collect::<Vec<_>>();
+    let minimap_index = minimap
+        .and_then(|minimap| minimaps.iter().position(|data| data.id == minimap.id))
+        .map(|index| index + 1)
+        .unwrap_or_default();
+    let mut presets = minimap
+        .map(|minimap| minimap.actions.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    presets.sort();
+    let preset_index = step()
+        .preset
+        .and_then(|preset| presets.iter().position(|name| *name == preset))
+        .map(|index| index + 1)
+        .unwrap_or_default();
+    let preset_names = iter::once("Default".to_string())
+        .chain(presets)
+        .collect::<Vec<_>>();
+    let minimap_ids = minimaps
+        .iter()
+        .map(|minimap| minimap.id)
+        .collect::<Vec<_>>();
+
+    use_effect(use_reactive!(|value| step.set(value)));
+
+    rsx! {
+        div { class: "grid grid-cols-[auto_auto_auto_auto_16px] gap-3 items-end",
+            Select {
+                label: "Map",
+                options: minimap_names,
+                on_select: move |(index, _): (usize, String)| {
+                    let minimap_id = index
+                        .checked_sub(1)
+                        .and_then(|index| minimap_ids.get(index).copied().flatten());
+                    on_value(ItineraryStep {
+                        minimap_id,
+                        preset: None,
+                        ..step.peek().clone()
+                    });
+                },
+                selected: minimap_index,
+            }
+            Select {
+                label: "Preset",
+                options: preset_names,
+                on_select: move |(index, preset): (usize, String)| {
+                    on_value(ItineraryStep {
+                        preset: (index > 0).then_some(preset),
+                        ..step.peek().clone()
+                    });
+                },
+                selected: preset_index,
+            }
+            MillisInput {
+                label: "Duration",
+                on_value: move |duration_millis| {
+                    on_value(ItineraryStep {
+                        duration_millis,
+                        ..step.peek().clone()
+                    });
+                },
+                value: step().duration_millis,
+            }
+            SettingsEnumSelect::<ItineraryExit> {
+                label: "Exit",
+                on_select: move |exit| {
+                    on_value(ItineraryStep {
+                        exit,
+                        ..step.peek().clone()
+                    });
+                },
+                selected: step().exit,
+            }
+            div {
+                class: ICON_CONTAINER_CLASS,
+                onclick: move |_| {
+                    on_delete(());
+                },
+                XIcon { class: "{ICON_CLASS} text-red-500" }
+            }
+            NumberInputI32 {
+                label: "Portal X",
+                disabled: step().exit != ItineraryExit::Portal,
+                on_value: move |x| {
+                    let step = step.peek().clone();
+                    on_value(ItineraryStep {
+                        portal: Position { x, ..step.portal },
+                        ..step
+                    });
+                },
+                value: step().portal.x,
+            }
+            NumberInputI32 {
+                label: "Portal Y",
+                disabled: step().exit != ItineraryExit::Portal,
+                on_value: move |y| {
+                    let step = step.peek().clone();
+                    on_value(ItineraryStep {
+                        portal: Position { y, ..step.portal },
+                        ..step
+                    });
+                },
+                value: step().portal.y,
+            }
+            KeyBindingInput {
+                label: "Key",
+                disabled: step().exit != ItineraryExit::Key,
+                on_value: move |key: Option<KeyBinding>| {
+                    on_value(ItineraryStep {
+                        key: key.expect("not optional"),
+                        ..step.peek().clone()
+                    });
+                },
+                value: Some(step().key),
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionOthers(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let export_element_id = use_memo(|| Alphanumeric.sample_string(&mut rand::rng(), 8));
+    let export = use_callback(move |_| {
+        let js = format!(
+            r#"
+            const element = document.getElementById("{}");
+            if (element === null) {{
+                return;
+            }}
+            const json = await dioxus.recv();
+
+            element.setAttribute("href", "data:application/json;charset=utf-8," + encodeURIComponent(json));
+            element.setAttribute("download", "settings.json");
+            element.click();
+            "#,
+            export_element_id(),
+        );
+        let eval = document::eval(js.as_str());
+        let Ok(json) = serde_json::to_string_pretty(&*settings_view.peek()) else {
+            return;
+        };
+        let _ = eval.send(json);
+    });
+
+    let import_element_id = use_memo(|| Alphanumeric.sample_string(&mut rand::rng(), 8));
+    let import = use_callback(move |_| {
+        let js = format!(
             r#"
             const element = document.getElementById("{}");
             if (element === null) {{
@@ -574,6 +2724,10 @@ fn SectionOthers(
         settings.id = Some(id);
         save_settings(settings);
     });
+    let mut diagnostics_dir = use_signal(|| None::<String>);
+    let mut model_diagnostics = use_resource(move || async move {
+        query_diagnostics().await.ok_or_toast().unwrap_or_default()
+    });
 
     rsx! {
         Section { name: "Others",
@@ -588,7 +2742,16 @@ fn SectionOthers(
                     },
                     value: settings_view().enable_rune_solving,
                 }
-                div {}
+                SettingsCheckbox {
+                    label: "Path to expected rune spawns",
+                    on_value: move |enable_rune_spawn_pathing| {
+                        save_settings(SettingsData {
+                            enable_rune_spawn_pathing,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_rune_spawn_pathing,
+                }
                 SettingsCheckbox {
                     label: "Stop actions on fail or map changed",
                     on_value: move |stop_on_fail_or_change_map| {
@@ -600,14 +2763,52 @@ fn SectionOthers(
                     value: settings_view().stop_on_fail_or_change_map,
                 }
                 SettingsCheckbox {
-                    label: "Enable panic mode",
-                    on_value: move |enable_panic_mode| {
+                    label: "Switch to matching map on map changed",
+                    on_value: move |auto_switch_minimap| {
+                        save_settings(SettingsData {
+                            auto_switch_minimap,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().auto_switch_minimap,
+                }
+                SettingsCheckbox {
+                    label: "Check for updates on startup",
+                    on_value: move |check_updates| {
+                        save_settings(SettingsData {
+                            check_updates,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().check_updates,
+                }
+                SettingsCheckbox {
+                    label: "Close to system tray",
+                    on_value: move |close_to_tray| {
+                        save_settings(SettingsData {
+                            close_to_tray,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().close_to_tray,
+                }
+                SettingsCheckbox {
+                    label: "Record frames for diagnostics",
+                    on_value: move |record_diagnostics_frames| {
                         save_settings(SettingsData {
-                            enable_panic_mode,
+                            record_diagnostics_frames,
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: settings_view().enable_panic_mode,
+                    value: settings_view().record_diagnostics_frames,
+                }
+                Button {
+                    class: "w-full",
+                    text: "Dump diagnostics",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        diagnostics_dir.set(dump_diagnostics().await.ok_or_toast());
+                    },
                 }
                 div {
                     a { id: export_element_id(), class: "w-0 h-0 invisible" }
@@ -647,6 +2848,38 @@ fn SectionOthers(
                     }
                 }
             }
+            if let Some(dir) = diagnostics_dir() {
+                p { class: "paragraph-xs mt-2", "Diagnostics dumped to {dir}" }
+            }
+            Button {
+                class: "w-full mt-2",
+                text: "Query models self-test",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    model_diagnostics.restart();
+                },
+            }
+            for diagnostics in model_diagnostics().unwrap_or_default() {
+                p { class: "paragraph-xs mt-2",
+                    if let Some(error) = diagnostics.error {
+                        "{diagnostics.model}: failed with {error}"
+                    } else {
+                        {
+                            format!(
+                                "{}: loaded in {:?}, inferred in {:?}, {}",
+                                diagnostics.model,
+                                diagnostics.load_time,
+                                diagnostics.inference_time,
+                                match diagnostics.correct {
+                                    Some(true) => "correct",
+                                    Some(false) => "incorrect",
+                                    None => "no sample to verify",
+                                },
+                            )
+                        }
+                    }
+                }
+            }
         }
     }
 }
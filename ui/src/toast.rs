@@ -0,0 +1,59 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use backend::BackendError;
+use dioxus::prelude::*;
+use tokio::time::sleep;
+
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+const MAX_TOASTS: usize = 3;
+
+static TOASTS: GlobalSignal<Vec<(u64, String)>> = Signal::global(Vec::new);
+static NEXT_TOAST_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Shows `message` as a toast for a few seconds.
+pub fn toast(message: String) {
+    let id = NEXT_TOAST_ID.fetch_add(1, Ordering::Relaxed);
+    let mut toasts = TOASTS.write();
+    if toasts.len() >= MAX_TOASTS {
+        toasts.remove(0);
+    }
+    toasts.push((id, message));
+
+    spawn_forever(async move {
+        sleep(TOAST_DURATION).await;
+        TOASTS.write().retain(|(toast_id, _)| *toast_id != id);
+    });
+}
+
+/// Converts a [`BackendError`] result to [`Option`] and shows the error as a toast.
+pub trait ToastResult<T> {
+    fn ok_or_toast(self) -> Option<T>;
+}
+
+impl<T> ToastResult<T> for Result<T, BackendError> {
+    fn ok_or_toast(self) -> Option<T> {
+        self.inspect_err(|err| {
+            log::error!("{err}");
+            toast(err.to_string());
+        })
+        .ok()
+    }
+}
+
+#[component]
+pub fn Toasts() -> Element {
+    rsx! {
+        div { class: "fixed bottom-12 right-2 flex flex-col gap-2 z-10",
+            for (id , message) in TOASTS() {
+                div {
+                    key: "{id}",
+                    class: "px-2 py-1 bg-gray-900 border border-red-500 paragraph-xs text-red-400",
+                    {message}
+                }
+            }
+        }
+    }
+}
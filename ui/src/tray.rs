@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use backend::{
+    BackendError, BotStatus, query_settings, query_status, rotate_actions,
+    signal_update_loop_shutdown, wait_update_loop_shutdown,
+};
+use dioxus::{
+    desktop::{
+        WindowCloseBehaviour,
+        trayicon::{
+            Icon, TrayIcon, TrayIconBuilder,
+            menu::{Menu, MenuItem, PredefinedMenuItem},
+        },
+        use_tray_menu_event_handler, window,
+    },
+    prelude::*,
+};
+use notify_rust::Notification;
+use tokio::{task::spawn_blocking, time::sleep};
+
+use crate::{AppState, toast::ToastResult};
+
+const ICON_RGBA: &[u8] = include_bytes!("../assets/tray_icon.rgba");
+const ICON_SIZE: u32 = 32;
+const STATUS_INTERVAL: Duration = Duration::from_secs(2);
+const MENU_SHOW: &str = "show";
+const MENU_START: &str = "start";
+const MENU_STOP: &str = "stop";
+const MENU_QUIT: &str = "quit";
+
+/// The system tray icon and its menu items kept alive while shown.
+#[derive(Clone)]
+struct TrayMenu {
+    icon: TrayIcon,
+    start: MenuItem,
+    stop: MenuItem,
+}
+
+impl TrayMenu {
+    fn new() -> Option<Self> {
+        let show = MenuItem::with_id(MENU_SHOW, "Show window", true, None);
+        let start = MenuItem::with_id(MENU_START, "Start", true, None);
+        let stop = MenuItem::with_id(MENU_STOP, "Stop", false, None);
+        let quit = MenuItem::with_id(MENU_QUIT, "Quit", true, None);
+        let menu = Menu::with_items(&[
+            &show,
+            &PredefinedMenuItem::separator(),
+            &start,
+            &stop,
+            &PredefinedMenuItem::separator(),
+            &quit,
+        ])
+        .inspect_err(|err| log::error!("failed to create tray menu {err}"))
+        .ok()?;
+        let icon = Icon::from_rgba(ICON_RGBA.to_vec(), ICON_SIZE, ICON_SIZE)
+            .inspect_err(|err| log::error!("failed to create tray icon {err}"))
+            .ok()?;
+        let icon = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_icon(icon)
+            .with_tooltip("Stopped")
+            .build()
+            .inspect_err(|err| log::error!("failed to create tray icon {err}"))
+            .ok()?;
+
+        Some(Self { icon, start, stop })
+    }
+
+    fn update(&self, status: &BotStatus) {
+        let state = if status.halting { "Stopped" } else { "Running" };
+        let tooltip = match status.character.as_ref() {
+            Some(character) => format!("{state} - {character}\n{}", status.summary),
+            None => format!("{state}\n{}", status.summary),
+        };
+        let _ = self.icon.set_tooltip(Some(tooltip));
+        self.start.set_enabled(status.halting);
+        self.stop.set_enabled(!status.halting);
+    }
+}
+
+/// Shows the system tray icon for controlling the bot while the main window is closed.
+///
+/// The icon is only shown if [`backend::Settings::close_to_tray`] is enabled.
+#[component]
+pub fn Tray() -> Element {
+    let settings = use_context::<AppState>().settings;
+    let mut queried_close_to_tray = use_signal(|| false);
+    let close_to_tray = use_memo(move || {
+        settings()
+            .map(|settings| settings.close_to_tray)
+            .unwrap_or(queried_close_to_tray())
+    });
+    let mut tray = use_signal(|| None::<TrayMenu>);
+
+    use_tray_menu_event_handler(move |event| match event.id.as_ref() {
+        MENU_SHOW => {
+            let window = window();
+            window.set_visible(true);
+            window.set_focus();
+        }
+        MENU_START => {
+            spawn(rotate_from_tray(false, tray));
+        }
+        MENU_STOP => {
+            spawn(rotate_from_tray(true, tray));
+        }
+        MENU_QUIT => {
+            signal_update_loop_shutdown();
+            spawn(async move {
+                let _ = spawn_blocking(|| wait_update_loop_shutdown(Duration::from_secs(1))).await;
+                std::process::exit(0);
+            });
+        }
+        _ => (),
+    });
+
+    // Settings are otherwise only queried once the settings tab is opened
+    use_future(move || async move {
+        if settings.peek().is_none()
+            && let Some(settings) = query_settings().await.ok_or_toast()
+        {
+            queried_close_to_tray.set(settings.close_to_tray);
+        }
+    });
+
+    use_effect(move || {
+        let close_to_tray = close_to_tray();
+        window().set_close_behavior(if close_to_tray {
+            WindowCloseBehaviour::LastWindowHides
+        } else {
+            WindowCloseBehaviour::LastWindowExitsApp
+        });
+        if !close_to_tray {
+            tray.set(None);
+            window().set_visible(true);
+        } else if tray.peek().is_none() {
+            tray.set(TrayMenu::new());
+        }
+    });
+
+    use_future(move || async move {
+        loop {
+            if tray.peek().is_some()
+                && let Ok(status) = query_status().await
+                && let Some(tray) = tray.peek().as_ref()
+            {
+                tray.update(&status);
+            }
+            sleep(STATUS_INTERVAL).await;
+        }
+    });
+
+    rsx! {}
+}
+
+/// Starts or stops the bot from the tray menu and shows a notification if it cannot be started.
+///
+/// A native notification is used instead of a toast because the main window may be hidden.
+async fn rotate_from_tray(halting: bool, tray: Signal<Option<TrayMenu>>) {
+    if let Err(err) = rotate_actions(halting).await {
+        notify_error(err);
+        return;
+    }
+    let status = match query_status().await {
+        Ok(status) => status,
+        Err(err) => {
+            notify_error(err);
+            return;
+        }
+    };
+    if !halting && status.halting {
+        notify("Select a map and a character before starting");
+    }
+    if let Some(tray) = tray.peek().as_ref() {
+        tray.update(&status);
+    }
+}
+
+fn notify_error(err: BackendError) {
+    log::error!("{err}");
+    notify(&err.to_string());
+}
+
+fn notify(message: &str) {
+    if let Err(err) = Notification::new().summary("Komari").body(message).show() {
+        log::error!("failed to show notification {err}");
+    }
+}